@@ -80,6 +80,10 @@ pub enum Commands {
     Install {
         /// Package specifications (name, name>=version, or ./file.sp)
         packages: Vec<String>,
+
+        /// Keep downloads and staging of a failed install for debugging
+        #[arg(long)]
+        keep_failed: bool,
     },
 
     /// Update packages to newer compatible versions
@@ -87,6 +91,10 @@ pub enum Commands {
     Update {
         /// Specific packages to update (empty = all packages)
         packages: Vec<String>,
+
+        /// Keep downloads and staging of a failed install for debugging
+        #[arg(long)]
+        keep_failed: bool,
     },
 
     /// Upgrade packages to latest versions (ignore upper bounds)
@@ -94,6 +102,10 @@ pub enum Commands {
     Upgrade {
         /// Specific packages to upgrade (empty = all packages)
         packages: Vec<String>,
+
+        /// Keep downloads and staging of a failed install for debugging
+        #[arg(long)]
+        keep_failed: bool,
     },
 
     /// Uninstall packages
@@ -186,7 +198,11 @@ pub enum Commands {
     },
 
     /// Clean up orphaned packages and old states
-    Cleanup,
+    Cleanup {
+        /// Also pack the small objects of each stored package to save inodes
+        #[clap(long)]
+        compact: bool,
+    },
 
     /// Rollback to previous state
     Rollback {
@@ -271,7 +287,7 @@ pub enum Commands {
         #[arg(long, default_value = "standard")]
         level: String,
 
-        /// Verification scope (live, store, all)
+        /// Verification scope (live, changed, store, all)
         #[arg(long, default_value = "live")]
         scope: String,
 
@@ -279,6 +295,11 @@ pub enum Commands {
         /// (one-off maintenance; does not change persistent config)
         #[arg(long)]
         sync_refcounts: bool,
+
+        /// Exit with an error if any discrepancy is at or above this severity
+        /// (low, medium, high, critical), even when only reporting
+        #[arg(long, value_name = "SEVERITY")]
+        fail_on: Option<String>,
     },
 
     /// Manage repositories
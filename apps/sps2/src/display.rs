@@ -86,6 +86,13 @@ impl OutputRenderer {
             }
         }
 
+        if !result.skipped_unchanged.is_empty() {
+            println!(
+                "Skipped {} unchanged packages since the last verified state.",
+                result.skipped_unchanged.len()
+            );
+        }
+
         Ok(())
     }
 
@@ -605,6 +605,24 @@ impl EventHandler {
                                 );
                             }
                         }
+                        BuildDiagnostic::BuildSystemDetected {
+                            system,
+                            markers,
+                            skipped,
+                            ..
+                        } => {
+                            let mut message =
+                                format!("Detected {system} build ({})", markers.join(", "));
+                            for runner_up in &skipped {
+                                message.push_str(&format!(
+                                    "; skipped {} ({}): {}",
+                                    runner_up.name,
+                                    runner_up.markers.join(", "),
+                                    runner_up.reason
+                                ));
+                            }
+                            self.show_operation(&meta, message, "build", EventSeverity::Info);
+                        }
                     },
                 }
             }
@@ -912,6 +930,14 @@ impl EventHandler {
                         };
                         self.show_operation(&meta, text, &operation, severity);
                     }
+                    GeneralEvent::OperationCancelled { operation } => {
+                        self.show_operation(
+                            &meta,
+                            format!("{operation} cancelled"),
+                            &operation,
+                            EventSeverity::Warning,
+                        );
+                    }
                     GeneralEvent::Warning { message, .. } => {
                         self.show_meta_message(&meta, message, EventSeverity::Warning);
                     }
@@ -1517,6 +1543,7 @@ impl EventHandler {
                 AppEvent::Uninstall(_) => "Uninstall",
                 AppEvent::Update(_) => "Update",
                 AppEvent::Platform(_) => "Platform",
+                AppEvent::Resource(_) => "Resource",
             };
             self.show_meta_message(
                 meta,
@@ -214,6 +214,27 @@ pub fn log_event_with_tracing(message: &EventMessage) {
                             "Build cache pruned"
                         );
                     }
+                    BuildDiagnostic::BuildSystemDetected {
+                        source_dir,
+                        system,
+                        markers,
+                        skipped,
+                    } => {
+                        let skipped: Vec<String> = skipped
+                            .iter()
+                            .map(|s| format!("{} ({})", s.name, s.reason))
+                            .collect();
+                        info!(
+                            source = meta.source.as_str(),
+                            event_id = %meta.event_id,
+                            correlation = ?meta.correlation_id,
+                            source_dir = %source_dir.display(),
+                            system = %system,
+                            markers = ?markers,
+                            skipped = ?skipped,
+                            "Build system detected"
+                        );
+                    }
                 },
             }
         }
@@ -972,6 +993,15 @@ pub fn log_event_with_tracing(message: &EventMessage) {
                         );
                     }
                 }
+                GeneralEvent::OperationCancelled { operation } => {
+                    warn!(
+                        source = meta.source.as_str(),
+                        event_id = %meta.event_id,
+                        correlation = ?meta.correlation_id,
+                        operation = %operation,
+                        "Operation cancelled"
+                    );
+                }
                 GeneralEvent::Warning { message, context } => {
                     warn!(
                         source = meta.source.as_str(),
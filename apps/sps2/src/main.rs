@@ -16,7 +16,7 @@ use crate::error::CliError;
 use crate::events::EventHandler;
 use crate::setup::SystemSetup;
 use clap::Parser;
-use sps2_config::{fixed_paths, Config};
+use sps2_config::{fixed_paths, Config, FailureThreshold};
 use sps2_events::{EventReceiver, EventSender};
 use sps2_ops::{OperationResult, OpsContextBuilder};
 use sps2_state::StateManager;
@@ -59,6 +59,13 @@ async fn run(cli: Cli) -> Result<(), CliError> {
 
     // 3. Apply CLI flags (highest precedence)
     apply_cli_config(&mut config, &cli.global, &cli.command)?;
+    let verify_fail_on = matches!(
+        cli.command,
+        Commands::Verify {
+            fail_on: Some(_),
+            ..
+        }
+    );
 
     // Initialize system setup
     let mut setup = SystemSetup::new(config.clone());
@@ -112,6 +119,20 @@ async fn run(cli: Cli) -> Result<(), CliError> {
     // Render final result
     renderer.render_result(&result)?;
 
+    // Verify --fail-on turns qualifying discrepancies into a failing exit status
+    if let OperationResult::VerificationResult(verification) = &result {
+        let threshold = config.verification.failure_threshold.severity();
+        if verify_fail_on && verification.has_failures_at_or_above(threshold) {
+            return Err(CliError::Ops(
+                sps2_errors::OpsError::VerificationFailed {
+                    discrepancies: verification.count_at_or_above(threshold),
+                    state_id: verification.state_id.to_string(),
+                }
+                .into(),
+            ));
+        }
+    }
+
     // Show PATH reminder if this was an install operation and PATH not set
     if matches!(result, OperationResult::InstallReport(_)) {
         show_path_reminder_if_needed();
@@ -210,8 +231,12 @@ async fn execute_command(
             Ok(OperationResult::SearchResults(results))
         }
 
-        Commands::Cleanup => {
-            let result = sps2_ops::cleanup(&ctx).await?;
+        Commands::Cleanup { compact } => {
+            let mut result = sps2_ops::cleanup(&ctx).await?;
+            if compact {
+                let compacted = sps2_ops::compact_store(&ctx).await?;
+                result = format!("{result}\n{compacted}");
+            }
             // Also update the GC timestamp through SystemSetup (best effort)
             if let Err(e) = crate::setup::SystemSetup::update_gc_timestamp_static().await {
                 tracing::warn!("Failed to update GC timestamp: {}", e);
@@ -235,17 +260,17 @@ async fn execute_command(
         }
 
         // Large operations (delegate to specialized crates)
-        Commands::Install { packages } => {
+        Commands::Install { packages, .. } => {
             let report = sps2_ops::install_with_verification(&ctx, &packages).await?;
             Ok(OperationResult::InstallReport(report))
         }
 
-        Commands::Update { packages } => {
+        Commands::Update { packages, .. } => {
             let report = sps2_ops::update(&ctx, &packages).await?;
             Ok(OperationResult::InstallReport(report))
         }
 
-        Commands::Upgrade { packages } => {
+        Commands::Upgrade { packages, .. } => {
             let report = sps2_ops::upgrade_with_verification(&ctx, &packages).await?;
             Ok(OperationResult::InstallReport(report))
         }
@@ -527,13 +552,45 @@ fn apply_cli_config(
         } => {
             config.builder.build.build_jobs = *job_count;
         }
-        cli::Commands::Verify { sync_refcounts, .. } => {
+        cli::Commands::Install {
+            keep_failed: true, ..
+        }
+        | cli::Commands::Update {
+            keep_failed: true, ..
+        }
+        | cli::Commands::Upgrade {
+            keep_failed: true, ..
+        } => {
+            config.general.keep_on_failure = true;
+        }
+        cli::Commands::Verify {
+            sync_refcounts,
+            fail_on,
+            ..
+        } => {
             if *sync_refcounts {
                 // Ensure top-level guard config exists, then enable one-off refcount sync
                 let mut guard_cfg = config.guard.clone().unwrap_or_default();
                 guard_cfg.store_verification.sync_refcounts = true;
                 config.guard = Some(guard_cfg);
             }
+            if let Some(severity) = fail_on {
+                let threshold = match severity.as_str() {
+                    "critical" => FailureThreshold::Critical,
+                    "high" => FailureThreshold::High,
+                    "medium" => FailureThreshold::Medium,
+                    "low" => FailureThreshold::Low,
+                    s => {
+                        return Err(CliError::InvalidArguments(format!(
+                            "Invalid severity '{s}': must be one of: low, medium, high, critical"
+                        )))
+                    }
+                };
+                config.verification.failure_threshold = threshold;
+                if let Some(guard_cfg) = config.guard.as_mut() {
+                    guard_cfg.failure_threshold = threshold;
+                }
+            }
         }
         _ => {}
     }
@@ -5,7 +5,7 @@ use sps2_builder::Builder;
 use sps2_config::{fixed_paths, Config};
 use sps2_index::IndexManager;
 use sps2_net::NetClient;
-use sps2_resolver::Resolver;
+use sps2_resolver::{ConflictOptions, Resolver};
 use sps2_state::StateManager;
 use sps2_store::PackageStore;
 use std::path::{Path, PathBuf};
@@ -240,6 +240,18 @@ impl SystemSetup {
             retry_count: self.config.network.retries,
             retry_delay: std::time::Duration::from_secs(self.config.network.retry_delay),
             user_agent: format!("sps2/{}", env!("CARGO_PKG_VERSION")),
+            request_observer: self
+                .config
+                .network
+                .log_requests
+                .then(|| sps2_net::RequestObserver::new(log_request)),
+            credentials: self
+                .config
+                .network
+                .credentials
+                .iter()
+                .map(net_credential)
+                .collect::<Result<_, _>>()?,
         };
 
         let net = sps2_net::NetClient::new(net_config)
@@ -253,7 +265,9 @@ impl SystemSetup {
     async fn init_resolver(&mut self) -> Result<(), CliError> {
         debug!("Initializing resolver");
         let index = self.index.as_ref().unwrap().clone();
-        let resolver = Resolver::new(index);
+        let conflict_options = ConflictOptions::default()
+            .with_replaced_overlaps_as_warnings(self.config.general.replaced_overlaps_as_warnings);
+        let resolver = Resolver::new(index).with_conflict_options(conflict_options);
 
         self.resolver = Some(resolver);
         Ok(())
@@ -451,3 +465,39 @@ impl SystemSetup {
         Ok(())
     }
 }
+
+/// Log a single HTTP request attempt when request logging is enabled
+fn log_request(record: &sps2_net::RequestRecord) {
+    debug!(
+        method = %record.method,
+        url = %record.url,
+        status = ?record.status,
+        bytes = ?record.bytes,
+        duration_ms = u64::try_from(record.duration.as_millis()).unwrap_or(u64::MAX),
+        attempt = record.attempt,
+        error = ?record.error,
+        "http request"
+    );
+}
+
+/// Convert a configured credential into the scope and value the client uses
+fn net_credential(
+    credential: &sps2_config::NetworkCredential,
+) -> Result<(sps2_net::CredentialScope, sps2_net::HostCredential), CliError> {
+    let scope = sps2_net::CredentialScope::from_url(&credential.url)
+        .map_err(|e| CliError::Setup(format!("Invalid credential URL: {e}")))?;
+    let value = match (&credential.token, &credential.username) {
+        (Some(token), None) => sps2_net::HostCredential::Bearer(token.clone()),
+        (None, Some(username)) => sps2_net::HostCredential::Basic {
+            username: username.clone(),
+            password: credential.password.clone(),
+        },
+        _ => {
+            return Err(CliError::Setup(format!(
+                "Credential for {} needs exactly one of `token` or `username`",
+                credential.url
+            )))
+        }
+    };
+    Ok((scope, value))
+}
@@ -2,17 +2,66 @@
 //! Used by both scanners and patchers to ensure consistent detection
 
 use object::FileKind;
+use std::io::Read;
 use std::path::Path;
 
+/// Mach-O and universal binary magic numbers, in both byte orders
+const MACHO_MAGICS: [[u8; 4]; 6] = [
+    [0xfe, 0xed, 0xfa, 0xce], // MH_MAGIC
+    [0xce, 0xfa, 0xed, 0xfe], // MH_CIGAM
+    [0xfe, 0xed, 0xfa, 0xcf], // MH_MAGIC_64
+    [0xcf, 0xfa, 0xed, 0xfe], // MH_CIGAM_64
+    [0xca, 0xfe, 0xba, 0xbe], // FAT_MAGIC
+    [0xbe, 0xba, 0xfe, 0xca], // FAT_CIGAM
+];
+
+/// Check whether a file starts with a Mach-O magic number
+///
+/// Only the first four bytes are read, so this is cheap enough to run on
+/// every file in the staging directory before anything shells out.
+#[must_use]
+pub fn has_macho_magic(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok()
+        && MACHO_MAGICS.contains(&magic)
+}
+
 /// Check if a file is a Mach-O binary by parsing its header
 ///
 /// Uses the exact same logic as the `MachO` scanner. Returns true if the file
 /// can be parsed as a valid Mach-O binary.
 #[must_use]
 pub fn is_macho_file(path: &Path) -> bool {
+    if !has_macho_magic(path) {
+        return false;
+    }
     if let Ok(data) = std::fs::read(path) {
         FileKind::parse(&*data).is_ok()
     } else {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_macho_magic() {
+        let temp = tempfile::tempdir().unwrap();
+        let cases: [(&str, &[u8], bool); 4] = [
+            ("thin64", &[0xcf, 0xfa, 0xed, 0xfe, 0x0c, 0x00], true),
+            ("universal", &[0xca, 0xfe, 0xba, 0xbe, 0x00, 0x00], true),
+            ("elf", b"\x7fELF\x02\x01", false),
+            ("short", &[0xcf, 0xfa], false),
+        ];
+        for (name, bytes, expected) in cases {
+            let path = temp.path().join(name);
+            std::fs::write(&path, bytes).unwrap();
+            assert_eq!(has_macho_magic(&path), expected, "{name}");
+        }
+        assert!(!has_macho_magic(&temp.path().join("missing")));
+    }
+}
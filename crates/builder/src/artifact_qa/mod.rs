@@ -38,9 +38,11 @@ pub enum PatcherAction {
     PkgConfigPatcher(patchers::pkgconfig::PkgConfigPatcher),
     BinaryStringPatcher(patchers::binary_string::BinaryStringPatcher),
     LaFileCleaner(patchers::la_cleaner::LaFileCleaner),
+    LaFilePatcher(patchers::la_patcher::LaFilePatcher),
     ObjectFileCleaner(patchers::object_cleaner::ObjectFileCleaner),
     PythonBytecodeCleanupPatcher(patchers::python_bytecode_cleanup::PythonBytecodeCleanupPatcher),
     PythonIsolationPatcher(patchers::python_isolation::PythonIsolationPatcher),
+    ShebangPatcher(patchers::shebang::ShebangPatcher),
     CodeSigner(patchers::codesigner::CodeSigner),
 }
 
@@ -85,6 +87,7 @@ impl PatcherAction {
             Self::PkgConfigPatcher(_) => patchers::pkgconfig::PkgConfigPatcher::NAME,
             Self::BinaryStringPatcher(_) => patchers::binary_string::BinaryStringPatcher::NAME,
             Self::LaFileCleaner(_) => patchers::la_cleaner::LaFileCleaner::NAME,
+            Self::LaFilePatcher(_) => patchers::la_patcher::LaFilePatcher::NAME,
             Self::ObjectFileCleaner(_) => patchers::object_cleaner::ObjectFileCleaner::NAME,
             Self::PythonBytecodeCleanupPatcher(_) => {
                 patchers::python_bytecode_cleanup::PythonBytecodeCleanupPatcher::NAME
@@ -92,6 +95,7 @@ impl PatcherAction {
             Self::PythonIsolationPatcher(_) => {
                 patchers::python_isolation::PythonIsolationPatcher::NAME
             }
+            Self::ShebangPatcher(_) => patchers::shebang::ShebangPatcher::NAME,
             Self::CodeSigner(_) => patchers::codesigner::CodeSigner::NAME,
         }
     }
@@ -109,7 +113,7 @@ impl PatcherAction {
             Self::PlaceholderPatcher(_) => {
                 patchers::placeholder::PlaceholderPatcher::run(ctx, env, findings).await
             }
-            Self::RPathPatcher(_) => patchers::rpath::RPathPatcher::run(ctx, env, findings).await,
+            Self::RPathPatcher(patcher) => patcher.patch_staging(ctx, env, findings).await,
             Self::HeaderPatcher(_) => {
                 patchers::headers::HeaderPatcher::run(ctx, env, findings).await
             }
@@ -122,6 +126,9 @@ impl PatcherAction {
             Self::LaFileCleaner(_) => {
                 patchers::la_cleaner::LaFileCleaner::run(ctx, env, findings).await
             }
+            Self::LaFilePatcher(_) => {
+                patchers::la_patcher::LaFilePatcher::run(ctx, env, findings).await
+            }
             Self::ObjectFileCleaner(_) => {
                 patchers::object_cleaner::ObjectFileCleaner::run(ctx, env, findings).await
             }
@@ -134,6 +141,9 @@ impl PatcherAction {
             Self::PythonIsolationPatcher(_) => {
                 patchers::python_isolation::PythonIsolationPatcher::run(ctx, env, findings).await
             }
+            Self::ShebangPatcher(_) => {
+                patchers::shebang::ShebangPatcher::run(ctx, env, findings).await
+            }
             Self::CodeSigner(_) => patchers::codesigner::CodeSigner::run(ctx, env, findings).await,
         }
     }
@@ -142,7 +152,7 @@ impl PatcherAction {
 /// Replace the former `run_quality_checks()`
 ///
 /// * V1 – pre‑validation
-/// * P – patch tree in‑place
+/// * P – patch tree in‑place, with the profile's patchers adjusted by `patchers`
 /// * V2 – must be clean, else the build fails
 ///
 /// # Errors
@@ -162,6 +172,8 @@ pub async fn run_quality_pipeline(
     ctx: &BuildContext,
     env: &BuildEnvironment,
     qa_override: Option<sps2_types::QaPipelineOverride>,
+    patchers: &sps2_types::PatcherSelection,
+    rpath_style: sps2_types::RpathStyle,
 ) -> Result<(), Error> {
     let pipeline_start = Instant::now();
     let mut stats = QaStats::default();
@@ -212,16 +224,14 @@ pub async fn run_quality_pipeline(
     let validator_findings = pre.take_findings();
 
     // ----------------    PHASE 2  -----------------
-    if let Err(err) = run_patchers(
-        ctx,
-        env,
-        validator_findings,
-        router::get_patchers_for_profile(profile),
-        &target,
-        &mut stats,
-    )
-    .await
-    {
+    let profile_patchers = router::get_patchers_for_profile(profile);
+    let patched = match router::select_patchers(profile_patchers, patchers, rpath_style) {
+        Ok(patchers) => {
+            run_patchers(ctx, env, validator_findings, patchers, &target, &mut stats).await
+        }
+        Err(err) => Err(err),
+    };
+    if let Err(err) = patched {
         emit_pipeline_failed(ctx, &target, &err);
         return Err(err);
     }
@@ -19,14 +19,8 @@ impl crate::artifact_qa::traits::Action for HeaderPatcher {
         let build_src = format!("{build_prefix}/src");
         let build_base = "/opt/pm/build";
 
-        // Create regex for all build paths
-        let re = Regex::new(&format!(
-            r#"#\s*include\s*"({}|{}|{})[^"]+""#,
-            regex::escape(&build_src),
-            regex::escape(&build_prefix),
-            regex::escape(build_base)
-        ))
-        .unwrap();
+        // Longest prefix first so `<prefix>/src` is stripped as a whole
+        let re = include_regex(&[&build_src, &build_prefix, build_base]);
 
         let mut changed = Vec::new();
         for dir in ["include", "Headers"] {
@@ -38,19 +32,7 @@ impl crate::artifact_qa::traits::Action for HeaderPatcher {
                 let p = entry.into_path();
                 if p.is_file() {
                     if let Ok(src) = std::fs::read_to_string(&p) {
-                        if re.is_match(&src) {
-                            let repl = re.replace_all(&src, |caps: &regex::Captures| {
-                                // naive: just strip the prefix and keep quotes
-                                let full = &caps.get(0).unwrap().as_str()[0..];
-                                let inner = full.trim_start_matches("#include ").trim();
-                                let stripped = inner
-                                    .trim_matches('"')
-                                    .trim_start_matches(&build_src)
-                                    .trim_start_matches(&build_prefix)
-                                    .trim_start_matches(build_base)
-                                    .trim_start_matches('/');
-                                format!("#include \"{stripped}\"")
-                            });
+                        if let Some(repl) = rewrite_includes(&src, &re) {
                             std::fs::write(&p, repl.as_bytes())?;
                             changed.push(p);
                         }
@@ -65,3 +47,165 @@ impl crate::artifact_qa::traits::Action for HeaderPatcher {
     }
 }
 impl Patcher for HeaderPatcher {}
+
+/// Regex matching an `#include` directive, in quote or angle-bracket form,
+/// whose path starts with one of `prefixes`
+fn include_regex(prefixes: &[&str]) -> Regex {
+    let alternatives: Vec<String> = prefixes.iter().map(|p| regex::escape(p)).collect();
+    Regex::new(&format!(
+        r#"^(?P<indent>\s*)#\s*include\s*(?:"(?:{0})/*(?P<quoted>[^"]+)"|<(?:{0})/*(?P<angled>[^>]+)>)"#,
+        alternatives.join("|")
+    ))
+    .unwrap()
+}
+
+/// Rewrite matching include directives in `src` to their relative form,
+/// returning `None` when nothing changed
+///
+/// Lines continued with a trailing backslash are joined before matching, so
+/// a directive split across lines is rewritten onto one line. Directives
+/// inside block comments are left alone, and a `#include` that does not
+/// start its logical line (in a `//` comment or a string literal) never
+/// matches.
+fn rewrite_includes(src: &str, re: &Regex) -> Option<String> {
+    let mut out = String::with_capacity(src.len());
+    let mut in_block_comment = false;
+    let mut changed = false;
+
+    let mut lines = src.split_inclusive('\n');
+    while let Some(first) = lines.next() {
+        // Gather the physical lines of one logical line
+        let mut physical = first.to_string();
+        while physical.trim_end_matches(['\n', '\r']).ends_with('\\') {
+            let Some(next) = lines.next() else {
+                break;
+            };
+            physical.push_str(next);
+        }
+
+        let rewritten = if in_block_comment {
+            None
+        } else {
+            let logical = join_continuations(&physical);
+            re.captures(&logical).map(|caps| {
+                let indent = &caps["indent"];
+                let directive = if let Some(path) = caps.name("quoted") {
+                    format!("{indent}#include \"{}\"", path.as_str())
+                } else {
+                    format!("{indent}#include <{}>", &caps["angled"])
+                };
+                let rest = &logical[caps.get(0).map_or(0, |m| m.end())..];
+                let newline = if physical.ends_with('\n') { "\n" } else { "" };
+                format!(
+                    "{directive}{}{newline}",
+                    rest.trim_end_matches(['\n', '\r'])
+                )
+            })
+        };
+
+        in_block_comment = ends_in_block_comment(&physical, in_block_comment);
+        if let Some(line) = rewritten {
+            out.push_str(&line);
+            changed = true;
+        } else {
+            out.push_str(&physical);
+        }
+    }
+
+    changed.then_some(out)
+}
+
+/// Join backslash-continued lines into one
+fn join_continuations(physical: &str) -> String {
+    physical
+        .split_inclusive('\n')
+        .map(|line| {
+            let trimmed = line.trim_end_matches(['\n', '\r']);
+            trimmed.strip_suffix('\\').unwrap_or(line)
+        })
+        .collect()
+}
+
+/// Whether a block comment is still open at the end of `line`
+///
+/// String and character literals are skipped so a `/*` inside them does
+/// not count, and a `//` comment ends the scan.
+fn ends_in_block_comment(line: &str, mut in_block_comment: bool) -> bool {
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_block_comment {
+            if c == '*' && chars.peek() == Some(&'/') {
+                chars.next();
+                in_block_comment = false;
+            }
+            continue;
+        }
+        match c {
+            '/' if chars.peek() == Some(&'/') => break,
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                in_block_comment = true;
+            }
+            '"' | '\'' => {
+                while let Some(inner) = chars.next() {
+                    if inner == '\\' {
+                        chars.next();
+                    } else if inner == c {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    in_block_comment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREFIX: &str = "/opt/pm/build/zlib/1.3.1";
+
+    fn rewrite(src: &str) -> Option<String> {
+        let build_src = format!("{PREFIX}/src");
+        rewrite_includes(src, &include_regex(&[&build_src, PREFIX, "/opt/pm/build"]))
+    }
+
+    #[test]
+    fn rewrites_quote_and_angle_includes() {
+        let src = format!(
+            "#include \"{PREFIX}/src/zconf.h\"\n\
+             #  include <{PREFIX}/include/zutil.h> // internal\n\
+             #include <stdio.h>\n"
+        );
+        assert_eq!(
+            rewrite(&src).unwrap(),
+            "#include \"zconf.h\"\n\
+             #include <include/zutil.h> // internal\n\
+             #include <stdio.h>\n"
+        );
+    }
+
+    #[test]
+    fn joins_continued_directives() {
+        let src = format!("#include \\\n  <{PREFIX}/include/zlib.h>\nint x;\n");
+        assert_eq!(
+            rewrite(&src).unwrap(),
+            "#include <include/zlib.h>\nint x;\n"
+        );
+    }
+
+    #[test]
+    fn leaves_comments_and_strings_alone() {
+        let src = format!(
+            "// #include \"{PREFIX}/src/zconf.h\"\n\
+             /* disabled:\n\
+             #include <{PREFIX}/include/old.h>\n\
+             */\n\
+             static const char *p = \"\\\n\
+             #include <{PREFIX}/include/zlib.h>\";\n"
+        );
+        assert_eq!(rewrite(&src), None);
+    }
+}
@@ -0,0 +1,146 @@
+//! Rewrites build-prefix paths inside libtool archive (.la) files.
+//!
+//! Unlike [`super::LaFileCleaner`], which deletes `.la` files, this keeps
+//! them for consumers that still link through libtool. It is not part of a
+//! default pipeline; select it as `la-files` instead of `la-cleaner`.
+
+use crate::artifact_qa::{reports::Report, traits::Patcher};
+use crate::{BuildContext, BuildEnvironment};
+use sps2_errors::Error;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub struct LaFilePatcher;
+
+impl crate::artifact_qa::traits::Action for LaFilePatcher {
+    const NAME: &'static str = "Libtool archive patcher";
+
+    async fn run(
+        _ctx: &BuildContext,
+        env: &BuildEnvironment,
+        _findings: Option<&crate::artifact_qa::diagnostics::DiagnosticCollector>,
+    ) -> Result<Report, Error> {
+        let staging_dir = env.staging_dir();
+        let la_files = find_la_files(staging_dir);
+
+        // Where each archive ends up once installed, by file name
+        let installed: HashMap<String, String> = la_files
+            .iter()
+            .filter_map(|path| {
+                let name = path.file_name()?.to_str()?.to_string();
+                let rel = path.strip_prefix(staging_dir).ok()?;
+                Some((name, format!("/{}", rel.display())))
+            })
+            .collect();
+
+        let build_prefix = env.build_prefix().to_string_lossy().into_owned();
+        let staging = staging_dir.to_string_lossy().into_owned();
+        let live_prefix = env.get_live_prefix();
+
+        let mut changed = Vec::new();
+        for path in la_files {
+            let Ok(src) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let patched = rewrite_la_paths(&src, &build_prefix, &staging, live_prefix, &installed);
+            if patched != src {
+                std::fs::write(&path, patched.as_bytes())?;
+                changed.push(path);
+            }
+        }
+
+        Ok(Report {
+            changed_files: changed,
+            ..Default::default()
+        })
+    }
+}
+
+impl Patcher for LaFilePatcher {}
+
+/// All `.la` files under `root`
+fn find_la_files(root: &Path) -> Vec<PathBuf> {
+    ignore::WalkBuilder::new(root)
+        .hidden(false)
+        .parents(false)
+        .build()
+        .flatten()
+        .map(ignore::DirEntry::into_path)
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "la"))
+        .collect()
+}
+
+/// Rewrite build-prefix paths in the contents of a `.la` file
+///
+/// References into the staging directory lose the staging prefix, and a
+/// reference to another archive in the build tree points at that archive's
+/// installed location when it is part of the package. Any other occurrence
+/// of the build prefix becomes the live prefix.
+fn rewrite_la_paths(
+    src: &str,
+    build_prefix: &str,
+    staging_dir: &str,
+    live_prefix: &str,
+    installed: &HashMap<String, String>,
+) -> String {
+    src.split('\n')
+        .map(|line| {
+            let line = line.replace(staging_dir, "");
+            let line = line
+                .split(' ')
+                .map(|word| {
+                    let path = word.trim_matches('\'');
+                    let name = path.rsplit('/').next().unwrap_or(path);
+                    match installed.get(name) {
+                        Some(target)
+                            if path.starts_with(build_prefix)
+                                && Path::new(path).extension().is_some_and(|ext| ext == "la") =>
+                        {
+                            word.replace(path, target)
+                        }
+                        _ => word.to_string(),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            line.replace(build_prefix, live_prefix)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_prefixes_and_archive_references() {
+        let build_prefix = "/opt/pm/build/foo/1.0";
+        let staging = "/opt/pm/build/foo/1.0/stage";
+        let installed = HashMap::from([(
+            "libbar.la".to_string(),
+            "/opt/pm/live/lib/libbar.la".to_string(),
+        )]);
+
+        let src = "\
+# libfoo.la - a libtool library file
+dependency_libs=' -L/opt/pm/build/foo/1.0/deps/lib /opt/pm/build/foo/1.0/src/bar/libbar.la /opt/pm/build/foo/1.0/stage/opt/pm/live/lib/libbaz.la -lz'
+libdir='/opt/pm/build/foo/1.0/stage/opt/pm/live/lib'
+";
+        let patched = rewrite_la_paths(src, build_prefix, staging, "/opt/pm/live", &installed);
+        assert_eq!(
+            patched,
+            "\
+# libfoo.la - a libtool library file
+dependency_libs=' -L/opt/pm/live/deps/lib /opt/pm/live/lib/libbar.la /opt/pm/live/lib/libbaz.la -lz'
+libdir='/opt/pm/live/lib'
+"
+        );
+
+        let clean = "libdir='/opt/pm/live/lib'\n";
+        assert_eq!(
+            rewrite_la_paths(clean, build_prefix, staging, "/opt/pm/live", &installed),
+            clean
+        );
+    }
+}
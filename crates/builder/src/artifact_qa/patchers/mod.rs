@@ -4,6 +4,7 @@ pub mod binary_string;
 pub mod codesigner;
 pub mod headers;
 pub mod la_cleaner;
+pub mod la_patcher;
 pub mod object_cleaner;
 pub mod permissions;
 pub mod pkgconfig;
@@ -11,6 +12,7 @@ pub mod placeholder;
 pub mod python_bytecode_cleanup;
 pub mod python_isolation;
 pub mod rpath;
+pub mod shebang;
 
 // Re-export the concrete types so callers can use
 // `patchers::PlaceholderPatcher`, etc.
@@ -18,6 +20,7 @@ pub use binary_string::BinaryStringPatcher;
 pub use codesigner::CodeSigner;
 pub use headers::HeaderPatcher;
 pub use la_cleaner::LaFileCleaner;
+pub use la_patcher::LaFilePatcher;
 pub use object_cleaner::ObjectFileCleaner;
 pub use permissions::PermissionsFixer;
 pub use pkgconfig::PkgConfigPatcher;
@@ -25,3 +28,4 @@ pub use placeholder::PlaceholderPatcher;
 pub use python_bytecode_cleanup::PythonBytecodeCleanupPatcher;
 pub use python_isolation::PythonIsolationPatcher;
 pub use rpath::RPathPatcher;
+pub use shebang::ShebangPatcher;
@@ -28,6 +28,12 @@ impl RPathPatcher {
         }
     }
 
+    /// Style install names and RPATHs are fixed to
+    #[must_use]
+    pub fn style(&self) -> RpathStyle {
+        self.style
+    }
+
     /// Create a platform context for this patcher
     #[must_use]
     pub fn create_platform_context(&self, event_sender: Option<EventSender>) -> PlatformContext {
@@ -48,24 +54,12 @@ impl RPathPatcher {
 
     /// Check if a file should be processed by `RPathPatcher`
     ///
-    /// This includes dylibs, shared objects, and Mach-O executables. Returns true
-    /// if the file needs RPATH or install name processing.
+    /// This includes dylibs, shared objects, and Mach-O executables. Only the
+    /// magic number is read, so files that are not Mach-O (scripts, linker
+    /// scripts named `.so`, data) are skipped before any tool is run on them.
     #[must_use]
     pub fn should_process_file(path: &Path) -> bool {
-        if !path.is_file() {
-            return false;
-        }
-
-        // Check if it's a dylib (including versioned ones)
-        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-            if name.contains(".dylib") || name.contains(".so") {
-                return true;
-            }
-        }
-
-        // Use the shared MachO detection logic as a fallback
-        // This will catch any Mach-O files we might have missed with filename patterns
-        macho_utils::is_macho_file(path)
+        path.is_file() && macho_utils::has_macho_magic(path)
     }
 
     /// Get the install name of a Mach-O file using platform abstraction
@@ -155,7 +149,7 @@ impl RPathPatcher {
             .filter_map(Result::ok)
         {
             let path = entry.into_path();
-            if !path.is_file() || !macho_utils::is_macho_file(&path) {
+            if !Self::should_process_file(&path) {
                 continue;
             }
 
@@ -230,6 +224,9 @@ impl RPathPatcher {
     }
 
     /// Process a single file for RPATH and install name fixes
+    ///
+    /// The first flag reports whether any `LC_RPATH` or `LC_LOAD_DYLIB`
+    /// entry was changed, the second whether the install name was fixed.
     pub async fn process_file(
         &self,
         ctx: &PlatformContext,
@@ -271,14 +268,34 @@ impl RPathPatcher {
         }
 
         // Fix RPATHs
+        // Bad entries are replaced by the live lib dir rather than just dropped.
         // Only add RPATH for Modern style (for Absolute style, we convert @rpath to absolute paths)
-        if need_good && self.style == RpathStyle::Modern && !has_good_rpath {
+        if (need_good || !bad_rpaths.is_empty())
+            && self.style == RpathStyle::Modern
+            && !has_good_rpath
+        {
             let _ = self.platform.binary().add_rpath(ctx, path, lib_path).await;
         }
         for bad in &bad_rpaths {
             let _ = self.platform.binary().delete_rpath(ctx, path, bad).await;
         }
 
+        // Point dependencies on libraries in the build prefix at the live prefix
+        let mut deps_rewritten = false;
+        for dep in &deps {
+            if let Some(new_dep) = rewritten_dependency(dep, build_paths, lib_path) {
+                if let Ok(()) = self
+                    .platform
+                    .binary()
+                    .change_dependency(ctx, path, dep, &new_dep)
+                    .await
+                {
+                    deps_rewritten = true;
+                }
+            }
+        }
+        let load_commands_changed = need_good || deps_rewritten || !bad_rpaths.is_empty();
+
         // Check and fix install names for dylibs
         if Self::is_dylib(path) {
             if let Some(install_name) = self.get_install_name(ctx, path).await {
@@ -292,12 +309,7 @@ impl RPathPatcher {
                         Ok(false) => {} // Should not happen with current implementation
                         Err(msg) => {
                             // Store error for reporting later
-                            return (
-                                need_good || !bad_rpaths.is_empty(),
-                                false,
-                                bad_rpaths,
-                                Some(msg),
-                            );
+                            return (load_commands_changed, false, bad_rpaths, Some(msg));
                         }
                     }
                 }
@@ -316,7 +328,7 @@ impl RPathPatcher {
                 Err(msg) => {
                     // Store error for reporting later
                     return (
-                        need_good || !bad_rpaths.is_empty(),
+                        load_commands_changed,
                         install_name_was_fixed,
                         bad_rpaths,
                         Some(msg),
@@ -326,7 +338,7 @@ impl RPathPatcher {
         }
 
         (
-            need_good || !bad_rpaths.is_empty(),
+            load_commands_changed,
             install_name_was_fixed,
             bad_rpaths,
             None,
@@ -394,6 +406,18 @@ impl RPathPatcher {
     }
 }
 
+/// Live location of a dependency that points into one of `build_paths`
+///
+/// The library keeps its file name and moves to `lib_path`; `@rpath/`,
+/// `@loader_path/` and system dependencies are left alone.
+fn rewritten_dependency(dep: &str, build_paths: &[String], lib_path: &str) -> Option<String> {
+    if dep.starts_with('@') || !build_paths.iter().any(|bp| dep.starts_with(bp.as_str())) {
+        return None;
+    }
+    let file_name = Path::new(dep).file_name()?.to_str()?;
+    Some(format!("{lib_path}/{file_name}"))
+}
+
 impl RPathPatcher {
     /// Process all files that need rpath patching
     async fn process_files(
@@ -449,18 +473,16 @@ impl RPathPatcher {
     }
 }
 
-impl crate::artifact_qa::traits::Action for RPathPatcher {
-    const NAME: &'static str = "install_name_tool patcher";
-
-    async fn run(
+impl RPathPatcher {
+    /// Patch the staging directory with this patcher's rpath style
+    pub(crate) async fn patch_staging(
+        &self,
         ctx: &BuildContext,
         env: &BuildEnvironment,
         findings: Option<&crate::artifact_qa::diagnostics::DiagnosticCollector>,
     ) -> Result<Report, Error> {
-        let patcher = Self::new(RpathStyle::Modern);
-
         // Create platform context from build context
-        let platform_ctx = patcher.platform.create_context(ctx.event_sender.clone());
+        let platform_ctx = self.platform.create_context(ctx.event_sender.clone());
 
         let mut files_to_process = HashSet::new();
 
@@ -494,14 +516,13 @@ impl crate::artifact_qa::traits::Action for RPathPatcher {
         ];
 
         // Process all files
-        let (mut changed, rpath_fixes, install_name_fixes, headerpad_errors, mut warnings) =
-            patcher
-                .process_files(files, lib_path, &build_paths, &platform_ctx, ctx)
-                .await;
+        let (mut changed, rpath_fixes, install_name_fixes, headerpad_errors, mut warnings) = self
+            .process_files(files, lib_path, &build_paths, &platform_ctx, ctx)
+            .await;
 
         // Handle headerpad errors
         let (headerpad_fixed, headerpad_warnings) = Self::handle_headerpad_errors(
-            &patcher,
+            self,
             &platform_ctx,
             &headerpad_errors,
             lib_path,
@@ -541,4 +562,61 @@ impl crate::artifact_qa::traits::Action for RPathPatcher {
         })
     }
 }
+
+impl crate::artifact_qa::traits::Action for RPathPatcher {
+    const NAME: &'static str = "install_name_tool patcher";
+
+    async fn run(
+        ctx: &BuildContext,
+        env: &BuildEnvironment,
+        findings: Option<&crate::artifact_qa::diagnostics::DiagnosticCollector>,
+    ) -> Result<Report, Error> {
+        Self::new(RpathStyle::Modern)
+            .patch_staging(ctx, env, findings)
+            .await
+    }
+}
 impl Patcher for RPathPatcher {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_build_prefix_dependencies() {
+        let build_paths = vec![
+            "/opt/pm/build".to_string(),
+            "/private/tmp/sps2/zlib-1.3.1".to_string(),
+        ];
+        let lib_path = "/opt/pm/live/lib";
+
+        assert_eq!(
+            rewritten_dependency(
+                "/opt/pm/build/zlib/1.3.1/stage/opt/pm/live/lib/libz.1.dylib",
+                &build_paths,
+                lib_path
+            )
+            .as_deref(),
+            Some("/opt/pm/live/lib/libz.1.dylib")
+        );
+        assert_eq!(
+            rewritten_dependency(
+                "/private/tmp/sps2/zlib-1.3.1/src/libz.dylib",
+                &build_paths,
+                lib_path
+            )
+            .as_deref(),
+            Some("/opt/pm/live/lib/libz.dylib")
+        );
+        for untouched in [
+            "@rpath/libz.1.dylib",
+            "/usr/lib/libSystem.B.dylib",
+            "/opt/pm/live/lib/libz.1.dylib",
+        ] {
+            assert_eq!(
+                rewritten_dependency(untouched, &build_paths, lib_path),
+                None
+            );
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Rewrites script shebangs that point at interpreters in the build prefix.
+//!
+//! Python, Node and Perl builds install scripts whose `#!` line names the
+//! interpreter they were built with, which no longer exists once the
+//! package is installed.
+
+use crate::artifact_qa::{reports::Report, traits::Patcher};
+use crate::{BuildContext, BuildEnvironment};
+use sps2_errors::Error;
+use std::io::Read;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+pub struct ShebangPatcher;
+
+impl crate::artifact_qa::traits::Action for ShebangPatcher {
+    const NAME: &'static str = "Shebang rewriter";
+
+    async fn run(
+        _ctx: &BuildContext,
+        env: &BuildEnvironment,
+        _findings: Option<&crate::artifact_qa::diagnostics::DiagnosticCollector>,
+    ) -> Result<Report, Error> {
+        let build_prefix = env.build_prefix().to_string_lossy().into_owned();
+        let staging = env.staging_dir().to_string_lossy().into_owned();
+
+        let mut changed = Vec::new();
+        let mut warnings = Vec::new();
+        for entry in ignore::WalkBuilder::new(env.staging_dir())
+            .hidden(false)
+            .parents(false)
+            .build()
+            .flatten()
+        {
+            let path = entry.into_path();
+            if !is_executable_script(&path) {
+                continue;
+            }
+            let Ok(src) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let (first_line, rest) = src.split_at(src.find('\n').unwrap_or(src.len()));
+            let Some(shebang) = rewrite_shebang(first_line, &build_prefix, &staging) else {
+                continue;
+            };
+
+            std::fs::write(&path, format!("{shebang}{rest}"))?;
+            warnings.push(format!(
+                "{}: rewrote shebang to {shebang}",
+                path.strip_prefix(env.staging_dir())
+                    .unwrap_or(&path)
+                    .display()
+            ));
+            changed.push(path);
+        }
+
+        Ok(Report {
+            changed_files: changed,
+            warnings,
+            ..Default::default()
+        })
+    }
+}
+
+impl Patcher for ShebangPatcher {}
+
+/// Whether `path` is an executable file starting with `#!`
+///
+/// Only the first two bytes are read, so binaries are skipped cheaply.
+fn is_executable_script(path: &Path) -> bool {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return false;
+    };
+    if !metadata.is_file() || metadata.permissions().mode() & 0o111 == 0 {
+        return false;
+    }
+    let mut magic = [0u8; 2];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok()
+        && magic == *b"#!"
+}
+
+/// Rewrite a shebang line that refers to the build prefix, returning `None`
+/// when it does not
+///
+/// Paths inside the staging directory are where the interpreter will be
+/// installed, so they just lose the staging prefix. Any other interpreter
+/// in the build prefix is looked up on `PATH` via `/usr/bin/env` instead.
+fn rewrite_shebang(line: &str, build_prefix: &str, staging_dir: &str) -> Option<String> {
+    let command = line.strip_prefix("#!")?.trim_end_matches('\r');
+    if !command.contains(build_prefix) {
+        return None;
+    }
+
+    let mut words = Vec::new();
+    for (index, word) in command.split_whitespace().enumerate() {
+        if let Some(live) = word.strip_prefix(staging_dir) {
+            words.push(live.to_string());
+        } else if word.starts_with(build_prefix) {
+            let name = word.rsplit('/').next().unwrap_or(word);
+            if index == 0 {
+                words.push("/usr/bin/env".to_string());
+            }
+            words.push(name.to_string());
+        } else {
+            words.push(word.to_string());
+        }
+    }
+    Some(format!("#!{}", words.join(" ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREFIX: &str = "/opt/pm/build/black/24.1.0";
+    const STAGING: &str = "/opt/pm/build/black/24.1.0/stage";
+
+    #[test]
+    fn rewrites_build_prefix_interpreters() {
+        let cases = [
+            (
+                format!("#!{STAGING}/opt/pm/live/bin/python3.12"),
+                "#!/opt/pm/live/bin/python3.12",
+            ),
+            (
+                format!("#!{PREFIX}/venv/bin/python3 -E"),
+                "#!/usr/bin/env python3 -E",
+            ),
+            (
+                format!("#!/usr/bin/env {PREFIX}/deps/bin/node\r"),
+                "#!/usr/bin/env node",
+            ),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(
+                rewrite_shebang(&line, PREFIX, STAGING).as_deref(),
+                Some(expected)
+            );
+        }
+
+        assert_eq!(
+            rewrite_shebang("#!/opt/pm/live/bin/perl -w", PREFIX, STAGING),
+            None
+        );
+        assert_eq!(
+            rewrite_shebang(&format!("# {PREFIX}"), PREFIX, STAGING),
+            None
+        );
+    }
+
+    #[test]
+    fn only_executable_scripts_are_considered() {
+        let temp = tempfile::tempdir().unwrap();
+        let write = |name: &str, contents: &[u8], mode: u32| {
+            let path = temp.path().join(name);
+            std::fs::write(&path, contents).unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+            path
+        };
+
+        assert!(is_executable_script(&write("tool", b"#!/bin/sh\n", 0o755)));
+        assert!(!is_executable_script(&write("data", b"#!/bin/sh\n", 0o644)));
+        assert!(!is_executable_script(&write(
+            "binary",
+            b"\xcf\xfa\xed\xfe",
+            0o755
+        )));
+    }
+}
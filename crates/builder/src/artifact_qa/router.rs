@@ -7,16 +7,19 @@
 use super::{PatcherAction, ValidatorAction};
 use crate::artifact_qa::patchers::{
     binary_string::BinaryStringPatcher, codesigner::CodeSigner, headers::HeaderPatcher,
-    la_cleaner::LaFileCleaner, object_cleaner::ObjectFileCleaner, pkgconfig::PkgConfigPatcher,
-    placeholder::PlaceholderPatcher, python_bytecode_cleanup::PythonBytecodeCleanupPatcher,
-    python_isolation::PythonIsolationPatcher, rpath::RPathPatcher,
+    la_cleaner::LaFileCleaner, la_patcher::LaFilePatcher, object_cleaner::ObjectFileCleaner,
+    pkgconfig::PkgConfigPatcher, placeholder::PlaceholderPatcher,
+    python_bytecode_cleanup::PythonBytecodeCleanupPatcher,
+    python_isolation::PythonIsolationPatcher, rpath::RPathPatcher, shebang::ShebangPatcher,
 };
 use crate::artifact_qa::scanners::{
     archive::ArchiveScanner, hardcoded::HardcodedScanner, macho::MachOScanner,
     staging::StagingScanner,
 };
-use sps2_types::{BuildSystemProfile, RpathStyle};
-use std::collections::HashSet;
+use crate::artifact_qa::traits::Action;
+use sps2_errors::{BuildError, Error};
+use sps2_types::{BuildSystemProfile, PatcherSelection, RpathStyle};
+use std::collections::{HashMap, HashSet};
 use std::hash::BuildHasher;
 
 /// Determine the build system profile with optional manual override
@@ -128,6 +131,7 @@ pub fn get_patchers_for_profile(profile: BuildSystemProfile) -> Vec<PatcherActio
                 PatcherAction::RPathPatcher(RPathPatcher::new(RpathStyle::Modern)),
                 PatcherAction::HeaderPatcher(HeaderPatcher),
                 PatcherAction::PkgConfigPatcher(PkgConfigPatcher),
+                PatcherAction::ShebangPatcher(ShebangPatcher),
                 PatcherAction::LaFileCleaner(LaFileCleaner),
                 PatcherAction::ObjectFileCleaner(ObjectFileCleaner),
                 // CodeSigner MUST run last
@@ -157,6 +161,7 @@ pub fn get_patchers_for_profile(profile: BuildSystemProfile) -> Vec<PatcherActio
                 // PermissionsFixer removed - only runs when explicitly called
                 PatcherAction::HeaderPatcher(HeaderPatcher),
                 PatcherAction::PkgConfigPatcher(PkgConfigPatcher),
+                PatcherAction::ShebangPatcher(ShebangPatcher),
                 // Clean up Python bytecode before creating wrapper scripts
                 PatcherAction::PythonBytecodeCleanupPatcher(PythonBytecodeCleanupPatcher),
                 PatcherAction::PythonIsolationPatcher(PythonIsolationPatcher),
@@ -166,6 +171,174 @@ pub fn get_patchers_for_profile(profile: BuildSystemProfile) -> Vec<PatcherActio
     }
 }
 
+/// Creates a fresh instance of one patcher for an rpath style
+type PatcherFactory = fn(RpathStyle) -> PatcherAction;
+
+/// A patcher that can be selected by configuration
+struct PatcherEntry {
+    /// Short id used in configuration files
+    id: &'static str,
+    factory: PatcherFactory,
+}
+
+/// Patchers that can be selected by name, keyed by [`Action::NAME`]
+///
+/// Each entry also has a short id (`headers`, `pkgconfig`, ...) for use in
+/// configuration. The permissions fixer is not listed: it only runs when a
+/// recipe asks for `fix_permissions`.
+pub struct PatcherRegistry {
+    entries: HashMap<&'static str, PatcherEntry>,
+    /// Style the rpath patcher is created with
+    rpath_style: RpathStyle,
+}
+
+impl PatcherRegistry {
+    /// Registry of every pipeline patcher
+    #[must_use]
+    pub fn new() -> Self {
+        let mut registry = Self {
+            entries: HashMap::new(),
+            rpath_style: RpathStyle::Modern,
+        };
+        registry.register::<PlaceholderPatcher>("placeholder", |_| {
+            PatcherAction::PlaceholderPatcher(PlaceholderPatcher)
+        });
+        registry.register::<BinaryStringPatcher>("binary-string", |_| {
+            PatcherAction::BinaryStringPatcher(BinaryStringPatcher)
+        });
+        registry.register::<RPathPatcher>("rpath", |style| {
+            PatcherAction::RPathPatcher(RPathPatcher::new(style))
+        });
+        registry
+            .register::<HeaderPatcher>("headers", |_| PatcherAction::HeaderPatcher(HeaderPatcher));
+        registry.register::<PkgConfigPatcher>("pkgconfig", |_| {
+            PatcherAction::PkgConfigPatcher(PkgConfigPatcher)
+        });
+        registry.register::<LaFileCleaner>("la-cleaner", |_| {
+            PatcherAction::LaFileCleaner(LaFileCleaner)
+        });
+        registry
+            .register::<LaFilePatcher>("la-files", |_| PatcherAction::LaFilePatcher(LaFilePatcher));
+        registry.register::<ShebangPatcher>("shebang", |_| {
+            PatcherAction::ShebangPatcher(ShebangPatcher)
+        });
+        registry.register::<ObjectFileCleaner>("object-cleaner", |_| {
+            PatcherAction::ObjectFileCleaner(ObjectFileCleaner)
+        });
+        registry.register::<PythonBytecodeCleanupPatcher>("python-bytecode-cleanup", |_| {
+            PatcherAction::PythonBytecodeCleanupPatcher(PythonBytecodeCleanupPatcher)
+        });
+        registry.register::<PythonIsolationPatcher>("python-isolation", |_| {
+            PatcherAction::PythonIsolationPatcher(PythonIsolationPatcher)
+        });
+        registry
+            .register::<CodeSigner>("codesign", |_| PatcherAction::CodeSigner(CodeSigner::new()));
+        registry
+    }
+
+    /// Create the rpath patcher with `style` instead of the modern style
+    #[must_use]
+    pub fn with_rpath_style(mut self, style: RpathStyle) -> Self {
+        self.rpath_style = style;
+        self
+    }
+
+    fn register<A: Action>(&mut self, id: &'static str, factory: PatcherFactory) {
+        self.entries.insert(A::NAME, PatcherEntry { id, factory });
+    }
+
+    /// Canonical [`Action::NAME`] for a short id or full name (case-insensitive)
+    #[must_use]
+    pub fn resolve(&self, name: &str) -> Option<&'static str> {
+        self.entries
+            .iter()
+            .find(|(key, entry)| {
+                key.eq_ignore_ascii_case(name) || entry.id.eq_ignore_ascii_case(name)
+            })
+            .map(|(key, _)| *key)
+    }
+
+    /// Create the patcher called `name`
+    #[must_use]
+    pub fn create(&self, name: &str) -> Option<PatcherAction> {
+        let key = self.resolve(name)?;
+        self.entries
+            .get(key)
+            .map(|entry| (entry.factory)(self.rpath_style))
+    }
+
+    fn resolve_or_err(&self, name: &str) -> Result<&'static str, Error> {
+        self.resolve(name).ok_or_else(|| {
+            let mut known: Vec<&str> = self.entries.values().map(|entry| entry.id).collect();
+            known.sort_unstable();
+            BuildError::RecipeError {
+                message: format!(
+                    "unknown patcher '{name}' (expected one of: {})",
+                    known.join(", ")
+                ),
+            }
+            .into()
+        })
+    }
+}
+
+impl Default for PatcherRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Apply a patcher selection to a profile's patchers
+///
+/// A non-empty `order` replaces the profile's list, creating the rpath
+/// patcher with `rpath_style`; disabled patchers are then dropped. The code
+/// signer always runs last, since patching a binary after signing it would
+/// invalidate the signature, and is added to an `order` that leaves it out:
+/// unsigned binaries do not run on arm64 macOS. Only `disable` turns it off.
+///
+/// # Errors
+///
+/// Returns an error if the selection names an unknown patcher.
+pub fn select_patchers(
+    profile_patchers: Vec<PatcherAction>,
+    selection: &PatcherSelection,
+    rpath_style: RpathStyle,
+) -> Result<Vec<PatcherAction>, Error> {
+    let registry = PatcherRegistry::new().with_rpath_style(rpath_style);
+    let disabled = selection
+        .disable
+        .iter()
+        .map(|name| registry.resolve_or_err(name))
+        .collect::<Result<HashSet<_>, _>>()?;
+
+    let mut patchers = if selection.order.is_empty() {
+        profile_patchers
+    } else {
+        let mut seen = HashSet::new();
+        let mut ordered = Vec::new();
+        for name in &selection.order {
+            let key = registry.resolve_or_err(name)?;
+            if seen.insert(key) {
+                ordered.extend(registry.create(key));
+            }
+        }
+        if !seen.contains(CodeSigner::NAME) {
+            ordered.extend(registry.create(CodeSigner::NAME));
+        }
+        ordered
+    };
+
+    patchers.retain(|patcher| !disabled.contains(patcher.name()));
+    if let Some(index) = patchers
+        .iter()
+        .position(|patcher| patcher.name() == CodeSigner::NAME)
+    {
+        let signer = patchers.remove(index);
+        patchers.push(signer);
+    }
+    Ok(patchers)
+}
+
 /// Get a descriptive name for the pipeline
 ///
 /// Returns a human-readable name for the validation pipeline.
@@ -178,3 +351,104 @@ pub fn get_pipeline_name(profile: BuildSystemProfile) -> &'static str {
         BuildSystemProfile::ScriptLight => "Light script validation pipeline",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(patchers: &[PatcherAction]) -> Vec<&'static str> {
+        patchers.iter().map(PatcherAction::name).collect()
+    }
+
+    fn selection(order: &[&str], disable: &[&str]) -> PatcherSelection {
+        PatcherSelection {
+            order: order.iter().map(ToString::to_string).collect(),
+            disable: disable.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn disabled_patchers_are_skipped() {
+        let profile = get_patchers_for_profile(BuildSystemProfile::NativeFull);
+        let selected = select_patchers(
+            profile,
+            &selection(&[], &["headers", "Libtool archive cleaner"]),
+            RpathStyle::Modern,
+        )
+        .unwrap();
+        let selected = names(&selected);
+
+        assert!(!selected.contains(&HeaderPatcher::NAME));
+        assert!(!selected.contains(&LaFileCleaner::NAME));
+        assert!(selected.contains(&PkgConfigPatcher::NAME));
+        assert_eq!(selected.last(), Some(&CodeSigner::NAME));
+    }
+
+    #[test]
+    fn configured_order_is_honored_with_signer_last() {
+        let profile = get_patchers_for_profile(BuildSystemProfile::RustMinimal);
+        let selected = select_patchers(
+            profile,
+            &selection(&["codesign", "pkgconfig", "HEADERS", "pkgconfig"], &[]),
+            RpathStyle::Modern,
+        )
+        .unwrap();
+
+        assert_eq!(
+            names(&selected),
+            [
+                PkgConfigPatcher::NAME,
+                HeaderPatcher::NAME,
+                CodeSigner::NAME
+            ]
+        );
+    }
+
+    #[test]
+    fn configured_order_keeps_signing_and_rpath_style() {
+        let profile = get_patchers_for_profile(BuildSystemProfile::NativeFull);
+        let selected =
+            select_patchers(profile, &selection(&["rpath"], &[]), RpathStyle::Absolute).unwrap();
+
+        assert_eq!(names(&selected), [RPathPatcher::NAME, CodeSigner::NAME]);
+        let PatcherAction::RPathPatcher(rpath) = &selected[0] else {
+            panic!("expected the rpath patcher first");
+        };
+        assert_eq!(rpath.style(), RpathStyle::Absolute);
+
+        // Signing can still be turned off explicitly
+        let profile = get_patchers_for_profile(BuildSystemProfile::NativeFull);
+        let selected = select_patchers(
+            profile,
+            &selection(&["rpath"], &["codesign"]),
+            RpathStyle::Modern,
+        )
+        .unwrap();
+        assert_eq!(names(&selected), [RPathPatcher::NAME]);
+    }
+
+    #[test]
+    fn unknown_patchers_are_rejected() {
+        let profile = get_patchers_for_profile(BuildSystemProfile::NativeFull);
+        let Err(err) = select_patchers(
+            profile,
+            &selection(&[], &["strip-debug"]),
+            RpathStyle::Modern,
+        ) else {
+            panic!("unknown patcher was accepted");
+        };
+        assert!(err.to_string().contains("unknown patcher 'strip-debug'"));
+    }
+
+    #[test]
+    fn recipe_selection_layers_over_global() {
+        let global = selection(&["rpath", "headers"], &["la-cleaner"]);
+        let recipe = selection(&[], &["headers"]);
+        let merged = global.merged_with(&recipe);
+        assert_eq!(merged.order, ["rpath", "headers"]);
+        assert_eq!(merged.disable, ["la-cleaner", "headers"]);
+
+        let merged = global.merged_with(&selection(&["pkgconfig"], &[]));
+        assert_eq!(merged.order, ["pkgconfig"]);
+    }
+}
@@ -1,5 +1,6 @@
 //! Build plan representation for staged execution
 
+use crate::build_systems::MesonWrapMode;
 use crate::environment::IsolationLevel;
 use crate::recipe::model::YamlRecipe;
 use crate::stages::{BuildCommand, PostStep, SourceStep};
@@ -38,6 +39,12 @@ pub struct BuildPlan {
     /// QA pipeline override
     pub qa_pipeline: sps2_types::QaPipelineOverride,
 
+    /// QA patcher order and opt-outs requested by the recipe
+    pub patchers: sps2_types::PatcherSelection,
+
+    /// Rpath style for QA patchers selected by name
+    pub rpath_style: RpathStyle,
+
     /// Whether to automatically install after build
     pub auto_install: bool,
 }
@@ -54,6 +61,12 @@ pub struct EnvironmentConfig {
     /// Whether to allow network access
     pub network: bool,
 
+    /// Whether build-system test phases run
+    pub run_tests: bool,
+
+    /// Meson wrap mode pinned by the recipe
+    pub meson_wrap_mode: Option<MesonWrapMode>,
+
     /// Environment variables to set
     pub variables: HashMap<String, String>,
 }
@@ -74,6 +87,8 @@ impl BuildPlan {
             isolation: recipe.environment.isolation,
             defaults: recipe.environment.defaults,
             network: recipe.environment.network,
+            run_tests: recipe.environment.run_tests,
+            meson_wrap_mode: recipe.environment.meson_wrap_mode,
             variables: recipe.environment.variables.clone(),
         };
 
@@ -86,6 +101,10 @@ impl BuildPlan {
             license: Some(recipe.metadata.license.clone()),
             runtime_deps: recipe.metadata.dependencies.runtime.clone(),
             build_deps: recipe.metadata.dependencies.build.clone(),
+            weak_deps: recipe.metadata.dependencies.weak.clone(),
+            conflicts: recipe.metadata.dependencies.conflicts.clone(),
+            replaces: recipe.metadata.dependencies.replaces.clone(),
+            runtime_env: recipe.metadata.runtime_env.clone(),
         };
 
         // Extract steps by stage
@@ -98,6 +117,8 @@ impl BuildPlan {
             build_steps: stage_steps.build,
             post_steps: stage_steps.post,
             qa_pipeline: recipe.post.qa_pipeline,
+            patchers: recipe.post.patchers.clone(),
+            rpath_style: recipe.post.patch_rpaths.style(),
             auto_install: recipe.install.auto,
         })
     }
@@ -168,6 +189,12 @@ impl BuildPlan {
                 source_steps.push(SourceStep::Git {
                     url: git.url.clone(),
                     ref_: git.git_ref.clone(),
+                    // Shallow by default; an explicit 0 asks for full history
+                    depth: match git.depth {
+                        None => Some(1),
+                        Some(0) => None,
+                        depth => depth,
+                    },
                 });
             }
             SourceMethod::Fetch { fetch } => {
@@ -179,6 +206,8 @@ impl BuildPlan {
                                 url: fetch.url.clone(),
                                 blake3: blake3.clone(),
                                 extract_to,
+                                strip_components: fetch.strip_components,
+                                extract: fetch.extract,
                             });
                         }
                         ChecksumAlgorithm::Sha256 { sha256 } => {
@@ -186,6 +215,8 @@ impl BuildPlan {
                                 url: fetch.url.clone(),
                                 sha256: sha256.clone(),
                                 extract_to,
+                                strip_components: fetch.strip_components,
+                                extract: fetch.extract,
                             });
                         }
                         ChecksumAlgorithm::Md5 { md5 } => {
@@ -193,6 +224,8 @@ impl BuildPlan {
                                 url: fetch.url.clone(),
                                 md5: md5.clone(),
                                 extract_to,
+                                strip_components: fetch.strip_components,
+                                extract: fetch.extract,
                             });
                         }
                     },
@@ -200,6 +233,8 @@ impl BuildPlan {
                         source_steps.push(SourceStep::Fetch {
                             url: fetch.url.clone(),
                             extract_to,
+                            strip_components: fetch.strip_components,
+                            extract: fetch.extract,
                         });
                     }
                 }
@@ -1,6 +1,6 @@
 //! GNU Autotools build system implementation
 
-use super::{BuildSystem, BuildSystemConfig, BuildSystemContext, TestResults};
+use super::{existing_markers, BuildSystem, BuildSystemConfig, BuildSystemContext, TestResults};
 use async_trait::async_trait;
 use sps2_errors::{BuildError, Error};
 use std::collections::HashMap;
@@ -37,6 +37,24 @@ impl AutotoolsBuildSystem {
         }
     }
 
+    /// Find the first of `make check` and `make test` the Makefile defines
+    async fn find_test_target(
+        ctx: &BuildSystemContext,
+        env: &HashMap<String, String>,
+    ) -> Result<Option<&'static str>, Error> {
+        for target in ["check", "test"] {
+            // A dry run fails with "No rule to make target" for unknown targets
+            let result = ctx
+                .env
+                .execute_command_with_env("make", &["-n", target], Some(&ctx.build_dir), env, true)
+                .await?;
+            if result.success || !result.stderr.contains("No rule to make target") {
+                return Ok(Some(target));
+            }
+        }
+        Ok(None)
+    }
+
     /// Check if autoreconf is needed
     async fn needs_autoreconf(&self, source_dir: &Path) -> Result<bool, Error> {
         // If configure exists and is newer than configure.ac, no need for autoreconf
@@ -99,7 +117,7 @@ impl AutotoolsBuildSystem {
         let mut args = vec![];
 
         // Add prefix - use LIVE_PREFIX for runtime installation location
-        if !user_args.iter().any(|arg| arg.starts_with("--prefix=")) {
+        if !has_prefix_arg(user_args) {
             args.push(format!("--prefix={}", ctx.env.get_live_prefix()));
         }
 
@@ -142,6 +160,13 @@ impl AutotoolsBuildSystem {
     }
 }
 
+/// Whether configure arguments already choose an install prefix, as
+/// `--prefix=DIR` or `--prefix DIR`
+pub(crate) fn has_prefix_arg(args: &[String]) -> bool {
+    args.iter()
+        .any(|arg| arg == "--prefix" || arg.starts_with("--prefix="))
+}
+
 impl Default for AutotoolsBuildSystem {
     fn default() -> Self {
         Self::new()
@@ -151,22 +176,15 @@ impl Default for AutotoolsBuildSystem {
 #[async_trait]
 impl BuildSystem for AutotoolsBuildSystem {
     async fn detect(&self, source_dir: &Path) -> Result<bool, Error> {
-        // Check for configure script
-        if source_dir.join("configure").exists() {
-            return Ok(true);
-        }
-
-        // Check for configure.ac or configure.in (needs autoreconf)
-        if source_dir.join("configure.ac").exists() || source_dir.join("configure.in").exists() {
-            return Ok(true);
-        }
-
-        // Check for Makefile.am (automake project)
-        if source_dir.join("Makefile.am").exists() {
-            return Ok(true);
-        }
+        Ok(!self.detection_markers(source_dir).await?.is_empty())
+    }
 
-        Ok(false)
+    async fn detection_markers(&self, source_dir: &Path) -> Result<Vec<String>, Error> {
+        // A configure script, its sources (need autoreconf), or an automake project
+        Ok(existing_markers(
+            source_dir,
+            &["configure", "configure.ac", "configure.in", "Makefile.am"],
+        ))
     }
 
     fn get_config_options(&self) -> BuildSystemConfig {
@@ -254,8 +272,9 @@ impl BuildSystem for AutotoolsBuildSystem {
         let mut make_args = vec![];
 
         // Add parallel jobs
-        if ctx.jobs > 1 {
-            make_args.push(format!("-j{}", ctx.jobs));
+        let jobs = ctx.jobs_for(self.name());
+        if jobs > 1 {
+            make_args.push(format!("-j{jobs}"));
         }
 
         // Add user arguments
@@ -286,38 +305,26 @@ impl BuildSystem for AutotoolsBuildSystem {
         // Run make check or make test
         let start = std::time::Instant::now();
 
-        // Try "make check" first (allow failure to parse)
         let mut merged_env = ctx.get_all_env_vars();
         merged_env.extend(self.get_env_vars(ctx));
+
+        // A project without a test target has nothing to run
+        let Some(target) = Self::find_test_target(ctx, &merged_env).await? else {
+            return Ok(TestResults::not_run());
+        };
+
         let result = ctx
             .env
-            .execute_command_with_env("make", &["check"], Some(&ctx.build_dir), &merged_env, true)
+            .execute_command_with_env("make", &[target], Some(&ctx.build_dir), &merged_env, true)
             .await?;
 
-        let success = if result.success {
-            true
-        } else {
-            // Fallback to "make test"
-            let test_result = ctx
-                .env
-                .execute_command_with_env(
-                    "make",
-                    &["test"],
-                    Some(&ctx.build_dir),
-                    &merged_env,
-                    true,
-                )
-                .await?;
-            test_result.success
-        };
-
         let duration = start.elapsed().as_secs_f64();
 
         // Parse test results from output
         // This is a simple implementation; real implementation would parse test suite output
         let output = format!("{}\n{}", result.stdout, result.stderr);
-        let (total, passed, failed, skipped) = if success {
-            // If make check succeeded, assume all tests passed
+        let (total, passed, failed, skipped) = if result.success {
+            // If the target succeeded, assume all tests passed
             // Real implementation would parse TESTS output
             (1, 1, 0, 0)
         } else {
@@ -332,6 +339,8 @@ impl BuildSystem for AutotoolsBuildSystem {
             duration,
             output,
             failures: vec![],
+            timings: vec![],
+            executed: true,
         })
     }
 
@@ -403,3 +412,54 @@ impl BuildSystem for AutotoolsBuildSystem {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::demo_environment;
+    use tempfile::TempDir;
+
+    #[test]
+    fn user_prefix_is_respected() {
+        let temp = TempDir::new().unwrap();
+        let env = demo_environment(temp.path());
+        let ctx = BuildSystemContext::new(env, temp.path().to_path_buf());
+
+        let prefixes = |args: &[String]| {
+            AutotoolsBuildSystem::get_configure_args(&ctx, args)
+                .into_iter()
+                .filter(|arg| arg.starts_with("--prefix"))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            prefixes(&[]),
+            [format!("--prefix={}", ctx.env.get_live_prefix())]
+        );
+        assert_eq!(
+            prefixes(&["--prefix=/usr/local".to_string()]),
+            ["--prefix=/usr/local"]
+        );
+        assert_eq!(
+            prefixes(&["--prefix".to_string(), "/usr/local".to_string()]),
+            ["--prefix"]
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_test_target_is_skipped() {
+        let temp = TempDir::new().unwrap();
+        let env = demo_environment(temp.path());
+        let ctx = BuildSystemContext::new(env, temp.path().to_path_buf()).with_run_tests(true);
+        let system = AutotoolsBuildSystem::new();
+
+        std::fs::write(temp.path().join("Makefile"), "all:\n").unwrap();
+        let results = system.run_tests(&ctx).await.unwrap();
+        assert!(!results.executed);
+
+        std::fs::write(temp.path().join("Makefile"), "all:\ntest:\n\tfalse\n").unwrap();
+        let results = system.run_tests(&ctx).await.unwrap();
+        assert!(results.executed);
+        assert!(!results.all_passed());
+    }
+}
@@ -1,6 +1,8 @@
 //! Cargo (Rust) build system implementation
 
-use super::{BuildSystem, BuildSystemConfig, BuildSystemContext, TestFailure, TestResults};
+use super::{
+    existing_markers, BuildSystem, BuildSystemConfig, BuildSystemContext, TestFailure, TestResults,
+};
 use async_trait::async_trait;
 use sps2_errors::{BuildError, Error};
 use std::collections::HashMap;
@@ -67,16 +69,21 @@ impl CargoBuildSystem {
     fn get_build_args(&self, ctx: &BuildSystemContext, user_args: &[String]) -> Vec<String> {
         let mut args = vec!["build".to_string()];
 
-        // Add default arguments
+        // Add default arguments; cargo rejects --release alongside --profile
+        let profile_selected = Self::selected_profile(user_args).is_some();
         for default_arg in &self.config.default_build_args {
-            if !user_args.contains(default_arg) && !user_args.contains(&"--debug".to_string()) {
+            if !user_args.contains(default_arg)
+                && !user_args.contains(&"--debug".to_string())
+                && !profile_selected
+            {
                 args.push(default_arg.clone());
             }
         }
 
         // Add parallel jobs
-        if ctx.jobs > 1 && !user_args.iter().any(|arg| arg.starts_with("-j")) {
-            args.push(format!("-j{}", ctx.jobs));
+        let jobs = ctx.jobs_for(self.name());
+        if jobs > 1 && !user_args.iter().any(|arg| arg.starts_with("-j")) {
+            args.push(format!("-j{jobs}"));
         }
 
         // Add offline mode if network is disabled
@@ -104,6 +111,35 @@ impl CargoBuildSystem {
         args
     }
 
+    /// Profile chosen with `--profile` in the arguments, if any
+    fn selected_profile(args: &[String]) -> Option<&str> {
+        args.iter().enumerate().find_map(|(i, arg)| {
+            arg.strip_prefix("--profile=").or_else(|| {
+                (arg == "--profile")
+                    .then(|| args.get(i + 1).map(String::as_str))
+                    .flatten()
+            })
+        })
+    }
+
+    /// Profile the build was made with, recorded in the context by `build`
+    fn build_profile(ctx: &BuildSystemContext) -> String {
+        ctx.extra_env
+            .read()
+            .ok()
+            .and_then(|extra_env| extra_env.get("PROFILE").cloned())
+            .unwrap_or_else(|| "release".to_string())
+    }
+
+    /// Directory under `target/` that holds artifacts built with `profile`
+    fn profile_dir(profile: &str) -> &str {
+        match profile {
+            "dev" | "test" => "debug",
+            "bench" => "release",
+            other => other,
+        }
+    }
+
     /// Extract feature flags from arguments
     fn extract_features(args: &[String]) -> Vec<String> {
         args.iter()
@@ -121,7 +157,7 @@ impl CargoBuildSystem {
 
         // Determine target directory
         let target_base = ctx.source_dir.join("target");
-        let target_dir = target_base.join("release");
+        let target_dir = target_base.join(Self::profile_dir(&Self::build_profile(ctx)));
 
         // Read Cargo.toml to find binary targets
         let cargo_toml = ctx.source_dir.join("Cargo.toml");
@@ -228,7 +264,11 @@ impl Default for CargoBuildSystem {
 #[async_trait]
 impl BuildSystem for CargoBuildSystem {
     async fn detect(&self, source_dir: &Path) -> Result<bool, Error> {
-        Ok(source_dir.join("Cargo.toml").exists())
+        Ok(!self.detection_markers(source_dir).await?.is_empty())
+    }
+
+    async fn detection_markers(&self, source_dir: &Path) -> Result<Vec<String>, Error> {
+        Ok(existing_markers(source_dir, &["Cargo.toml"]))
     }
 
     fn get_config_options(&self) -> BuildSystemConfig {
@@ -270,6 +310,12 @@ impl BuildSystem for CargoBuildSystem {
     }
 
     async fn build(&self, ctx: &BuildSystemContext, args: &[String]) -> Result<(), Error> {
+        // Remember a custom profile so tests and install use the same one
+        if let Some(profile) = Self::selected_profile(args) {
+            if let Ok(mut extra_env) = ctx.extra_env.write() {
+                extra_env.insert("PROFILE".to_string(), profile.to_string());
+            }
+        }
         let build_args = self.get_build_args(ctx, args);
         let arg_refs: Vec<&str> = build_args.iter().map(String::as_str).collect();
 
@@ -303,11 +349,16 @@ impl BuildSystem for CargoBuildSystem {
 
         let mut test_args = vec!["test"];
 
-        // Add release flag if we built in release mode
-        if let Ok(extra_env) = ctx.extra_env.read() {
-            if extra_env.get("PROFILE").map(String::as_str) == Some("release") {
-                test_args.push("--release");
-            }
+        // Test with the profile we built with
+        let profile = ctx
+            .extra_env
+            .read()
+            .ok()
+            .and_then(|extra_env| extra_env.get("PROFILE").cloned());
+        match profile.as_deref() {
+            Some("release") => test_args.push("--release"),
+            Some(profile) => test_args.extend(["--profile", profile]),
+            None => {}
         }
 
         // Add offline mode if needed
@@ -317,8 +368,9 @@ impl BuildSystem for CargoBuildSystem {
 
         // Add parallel jobs
         let jobs_str;
-        if ctx.jobs > 1 {
-            jobs_str = ctx.jobs.to_string();
+        let jobs = ctx.jobs_for(self.name());
+        if jobs > 1 {
+            jobs_str = jobs.to_string();
             test_args.push("--");
             test_args.push("--test-threads");
             test_args.push(&jobs_str);
@@ -352,6 +404,8 @@ impl BuildSystem for CargoBuildSystem {
             duration,
             output,
             failures,
+            timings: vec![],
+            executed: true,
         })
     }
 
@@ -464,7 +518,7 @@ impl BuildSystem for CargoBuildSystem {
         vars.insert("CARGO_TERM_COLOR".to_string(), "always".to_string());
 
         // Set profile
-        vars.insert("PROFILE".to_string(), "release".to_string());
+        vars.insert("PROFILE".to_string(), Self::build_profile(ctx));
 
         // Compiler cache support
         if let Some(cache_config) = &ctx.cache_config {
@@ -526,3 +580,53 @@ fn parse_cargo_test_summary(line: &str) -> Option<(usize, usize, usize)> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::demo_environment;
+    use tempfile::TempDir;
+
+    fn context(temp: &TempDir) -> BuildSystemContext {
+        let env = demo_environment(temp.path());
+        let mut ctx = BuildSystemContext::new(env, temp.path().to_path_buf());
+        ctx.jobs = 8;
+        ctx
+    }
+
+    #[test]
+    fn build_jobs_respect_cargo_override() {
+        let temp = TempDir::new().unwrap();
+        let cargo = CargoBuildSystem::new();
+
+        let args = cargo.get_build_args(&context(&temp), &[]);
+        assert!(args.contains(&"-j8".to_string()));
+
+        let ctx = context(&temp)
+            .with_jobs_for("cmake", 2)
+            .with_jobs_for("cargo", 16);
+        let args = cargo.get_build_args(&ctx, &[]);
+        assert!(args.contains(&"-j16".to_string()));
+        assert!(!args.iter().any(|arg| arg == "-j8" || arg == "-j2"));
+    }
+
+    #[test]
+    fn custom_profile_replaces_release() {
+        let temp = TempDir::new().unwrap();
+        let cargo = CargoBuildSystem::new();
+
+        let args = cargo.get_build_args(&context(&temp), &[]);
+        assert!(args.contains(&"--release".to_string()));
+
+        let user_args = ["--profile".to_string(), "dist".to_string()];
+        let args = cargo.get_build_args(&context(&temp), &user_args);
+        assert!(!args.contains(&"--release".to_string()));
+        assert_eq!(CargoBuildSystem::selected_profile(&user_args), Some("dist"));
+        assert_eq!(
+            CargoBuildSystem::selected_profile(&["--profile=dev".to_string()]),
+            Some("dev")
+        );
+        assert_eq!(CargoBuildSystem::profile_dir("dev"), "debug");
+        assert_eq!(CargoBuildSystem::profile_dir("dist"), "dist");
+    }
+}
@@ -1,10 +1,13 @@
 //! `CMake` build system implementation
 
-use super::{BuildSystem, BuildSystemConfig, BuildSystemContext, TestFailure, TestResults};
+use super::{
+    existing_markers, BuildSystem, BuildSystemConfig, BuildSystemContext, TestFailure, TestResults,
+    TestTiming,
+};
 use async_trait::async_trait;
 use sps2_errors::{BuildError, Error};
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use tokio::fs;
 
 /// `CMake` build system
@@ -91,17 +94,41 @@ impl CMakeBuildSystem {
     }
 
     /// Get `CMake` configuration arguments
-    fn get_cmake_args(&self, ctx: &BuildSystemContext, user_args: &[String]) -> Vec<String> {
+    ///
+    /// With a preset, the preset replaces the source directory argument and
+    /// defaults are only added for cache variables the preset leaves unset.
+    fn get_cmake_args(
+        &self,
+        ctx: &BuildSystemContext,
+        user_args: &[String],
+        preset: Option<&CMakePreset>,
+    ) -> Vec<String> {
         let mut args = vec![];
-
-        // Always specify source directory
-        args.push(ctx.source_dir.display().to_string());
+        let user_args = strip_preset_args(user_args);
+        let defined = |var: &str| {
+            user_args.iter().any(|arg| {
+                arg.strip_prefix("-D")
+                    .and_then(|arg| arg.strip_prefix(var))
+                    .is_some_and(|rest| rest.starts_with('=') || rest.starts_with(':'))
+            }) || preset.is_some_and(|preset| preset.defines(var))
+        };
+
+        if let Some(preset) = preset {
+            // The preset is read from the source directory; keep the binary
+            // directory where the later phases expect it
+            args.extend([
+                "--preset".to_string(),
+                preset.name.clone(),
+                "-B".to_string(),
+                ctx.build_dir.display().to_string(),
+            ]);
+        } else {
+            // Always specify source directory
+            args.push(ctx.source_dir.display().to_string());
+        }
 
         // Add install prefix - use LIVE_PREFIX for runtime installation location
-        if !user_args
-            .iter()
-            .any(|arg| arg.starts_with("-DCMAKE_INSTALL_PREFIX="))
-        {
+        if !defined("CMAKE_INSTALL_PREFIX") {
             args.push(format!(
                 "-DCMAKE_INSTALL_PREFIX={}",
                 ctx.env.get_live_prefix()
@@ -110,17 +137,18 @@ impl CMakeBuildSystem {
 
         // Add default arguments
         for default_arg in &self.config.default_configure_args {
-            if !user_args
-                .iter()
-                .any(|arg| arg.starts_with(default_arg.split('=').next().unwrap_or("")))
-            {
+            let var = default_arg
+                .strip_prefix("-D")
+                .and_then(|arg| arg.split('=').next())
+                .unwrap_or("");
+            if !defined(var) {
                 args.push(default_arg.clone());
             }
         }
 
         // Set RPATH for macOS to ensure binaries can find their libraries
         if cfg!(target_os = "macos") {
-            Self::add_macos_rpath_args(&mut args, ctx, user_args);
+            Self::add_macos_rpath_args(&mut args, ctx, &user_args);
         }
 
         // macOS ARM only - no cross-compilation support
@@ -143,20 +171,182 @@ impl CMakeBuildSystem {
         }
 
         // Add find_package hints
-        if !user_args
-            .iter()
-            .any(|arg| arg.starts_with("-DCMAKE_FIND_PACKAGE_PREFER_CONFIG="))
-        {
+        if !defined("CMAKE_FIND_PACKAGE_PREFER_CONFIG") {
             args.push("-DCMAKE_FIND_PACKAGE_PREFER_CONFIG=ON".to_string());
         }
 
         // Add user arguments
-        args.extend(user_args.iter().cloned());
+        args.extend(user_args);
 
         args
     }
 }
 
+/// Preset file names, in the order `CMake` reads them
+const PRESET_FILES: [&str; 2] = ["CMakePresets.json", "CMakeUserPresets.json"];
+
+/// Limit on `inherits` chains, guarding against cycles
+const MAX_PRESET_DEPTH: usize = 16;
+
+/// Whether `source_dir` defines `CMake` presets
+fn has_presets(source_dir: &Path) -> bool {
+    PRESET_FILES
+        .iter()
+        .any(|file| source_dir.join(file).is_file())
+}
+
+/// Preset named by `--preset <name>` or `--preset=<name>` in `args`
+fn selected_preset(args: &[String]) -> Option<&str> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        arg.strip_prefix("--preset=").or_else(|| {
+            (arg == "--preset")
+                .then(|| args.get(i + 1).map(String::as_str))
+                .flatten()
+        })
+    })
+}
+
+/// `args` without the preset selection
+fn strip_preset_args(args: &[String]) -> Vec<String> {
+    let mut stripped = Vec::with_capacity(args.len());
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--preset" {
+            iter.next();
+        } else if !arg.starts_with("--preset=") {
+            stripped.push(arg.clone());
+        }
+    }
+    stripped
+}
+
+/// A configure preset resolved from the project's preset files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CMakePreset {
+    pub(crate) name: String,
+    /// Binary directory the preset builds in, if it sets one
+    pub(crate) binary_dir: Option<PathBuf>,
+    /// Cache variables set by the preset or the presets it inherits
+    cache_variables: HashSet<String>,
+    /// Whether a build preset of the same name exists
+    has_build_preset: bool,
+}
+
+impl CMakePreset {
+    /// Resolve configure preset `name` from the preset files in `source_dir`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no preset file exists, a file is not valid JSON,
+    /// or the preset is not defined.
+    pub(crate) async fn load(source_dir: &Path, name: &str) -> Result<Self, Error> {
+        if !has_presets(source_dir) {
+            return Err(BuildError::ConfigureFailed {
+                message: format!(
+                    "cmake preset '{name}' requested but {} has no CMakePresets.json",
+                    source_dir.display()
+                ),
+            }
+            .into());
+        }
+
+        let mut configure = HashMap::new();
+        let mut build = HashSet::new();
+        for file in PRESET_FILES {
+            let path = source_dir.join(file);
+            if !path.is_file() {
+                continue;
+            }
+            let content = fs::read_to_string(&path).await?;
+            let presets: serde_json::Value =
+                serde_json::from_str(&content).map_err(|e| BuildError::ConfigureFailed {
+                    message: format!("invalid {file}: {e}"),
+                })?;
+            for preset in presets["configurePresets"].as_array().into_iter().flatten() {
+                if let Some(preset_name) = preset["name"].as_str() {
+                    configure.insert(preset_name.to_string(), preset.clone());
+                }
+            }
+            for preset in presets["buildPresets"].as_array().into_iter().flatten() {
+                if let Some(preset_name) = preset["name"].as_str() {
+                    build.insert(preset_name.to_string());
+                }
+            }
+        }
+        let mut resolved = Self {
+            name: name.to_string(),
+            binary_dir: None,
+            cache_variables: HashSet::new(),
+            has_build_preset: build.contains(name),
+        };
+        let mut raw_binary_dir = None;
+        resolved.collect(&configure, name, 0, &mut raw_binary_dir)?;
+        resolved.binary_dir = raw_binary_dir.map(|dir| expand_binary_dir(&dir, source_dir, name));
+        Ok(resolved)
+    }
+
+    /// Merge `name` and its ancestors; settings closer to the requested
+    /// preset win
+    fn collect(
+        &mut self,
+        presets: &HashMap<String, serde_json::Value>,
+        name: &str,
+        depth: usize,
+        binary_dir: &mut Option<String>,
+    ) -> Result<(), Error> {
+        let preset = presets
+            .get(name)
+            .filter(|_| depth < MAX_PRESET_DEPTH)
+            .ok_or_else(|| BuildError::ConfigureFailed {
+                message: format!("cmake configure preset '{name}' is not defined"),
+            })?;
+
+        if binary_dir.is_none() {
+            *binary_dir = preset["binaryDir"].as_str().map(String::from);
+        }
+        if let Some(vars) = preset["cacheVariables"].as_object() {
+            self.cache_variables.extend(vars.keys().cloned());
+        }
+
+        let parents: Vec<&str> = match &preset["inherits"] {
+            serde_json::Value::String(parent) => vec![parent.as_str()],
+            serde_json::Value::Array(parents) => parents
+                .iter()
+                .filter_map(serde_json::Value::as_str)
+                .collect(),
+            _ => vec![],
+        };
+        for parent in parents {
+            self.collect(presets, parent, depth + 1, binary_dir)?;
+        }
+        Ok(())
+    }
+
+    /// Whether the preset sets cache variable `var`
+    fn defines(&self, var: &str) -> bool {
+        self.cache_variables.contains(var)
+    }
+}
+
+/// Expand the macros `CMake` allows in `binaryDir` and anchor relative paths
+/// at the source directory
+fn expand_binary_dir(dir: &str, source_dir: &Path, preset_name: &str) -> PathBuf {
+    let source_name = source_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let source_parent = source_dir
+        .parent()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_default();
+    let expanded = dir
+        .replace("${sourceDir}", &source_dir.display().to_string())
+        .replace("${sourceParentDir}", &source_parent)
+        .replace("${sourceDirName}", &source_name)
+        .replace("${presetName}", preset_name);
+    source_dir.join(expanded)
+}
+
 impl Default for CMakeBuildSystem {
     fn default() -> Self {
         Self::new()
@@ -166,7 +356,11 @@ impl Default for CMakeBuildSystem {
 #[async_trait]
 impl BuildSystem for CMakeBuildSystem {
     async fn detect(&self, source_dir: &Path) -> Result<bool, Error> {
-        Ok(source_dir.join("CMakeLists.txt").exists())
+        Ok(!self.detection_markers(source_dir).await?.is_empty())
+    }
+
+    async fn detection_markers(&self, source_dir: &Path) -> Result<Vec<String>, Error> {
+        Ok(existing_markers(source_dir, &["CMakeLists.txt"]))
     }
 
     fn get_config_options(&self) -> BuildSystemConfig {
@@ -179,9 +373,18 @@ impl BuildSystem for CMakeBuildSystem {
             fs::create_dir_all(&ctx.build_dir).await?;
         }
 
-        // Build CMake command
-        let cmake_args = self.get_cmake_args(ctx, args);
+        // Build CMake command; presets are read from the source directory
+        let preset = match selected_preset(args) {
+            Some(name) => Some(CMakePreset::load(&ctx.source_dir, name).await?),
+            None => None,
+        };
+        let cmake_args = self.get_cmake_args(ctx, args, preset.as_ref());
         let arg_refs: Vec<&str> = cmake_args.iter().map(String::as_str).collect();
+        let working_dir = if preset.is_some() {
+            &ctx.source_dir
+        } else {
+            &ctx.build_dir
+        };
 
         // Prepare environment overlay
         let mut merged_env = ctx.get_all_env_vars();
@@ -190,7 +393,7 @@ impl BuildSystem for CMakeBuildSystem {
         // Run cmake
         let result = ctx
             .env
-            .execute_command_with_env("cmake", &arg_refs, Some(&ctx.build_dir), &merged_env, false)
+            .execute_command_with_env("cmake", &arg_refs, Some(working_dir), &merged_env, false)
             .await?;
 
         if !result.success {
@@ -204,12 +407,25 @@ impl BuildSystem for CMakeBuildSystem {
     }
 
     async fn build(&self, ctx: &BuildSystemContext, args: &[String]) -> Result<(), Error> {
-        let mut cmake_args = vec!["--build", "."];
+        // Use the matching build preset when the project defines one
+        let preset = match selected_preset(args) {
+            Some(name) => Some(CMakePreset::load(&ctx.source_dir, name).await?),
+            None => None,
+        };
+        let (mut cmake_args, working_dir) = match &preset {
+            Some(preset) if preset.has_build_preset => (
+                vec!["--build", "--preset", preset.name.as_str()],
+                &ctx.source_dir,
+            ),
+            _ => (vec!["--build", "."], &ctx.build_dir),
+        };
+        let args = strip_preset_args(args);
 
         // Add parallel jobs
         let jobs_str;
-        if ctx.jobs > 1 {
-            jobs_str = ctx.jobs.to_string();
+        let jobs = ctx.jobs_for(self.name());
+        if jobs > 1 {
+            jobs_str = jobs.to_string();
             cmake_args.push("--parallel");
             cmake_args.push(&jobs_str);
         }
@@ -225,13 +441,7 @@ impl BuildSystem for CMakeBuildSystem {
         merged_env.extend(self.get_env_vars(ctx));
         let result = ctx
             .env
-            .execute_command_with_env(
-                "cmake",
-                &cmake_args,
-                Some(&ctx.build_dir),
-                &merged_env,
-                false,
-            )
+            .execute_command_with_env("cmake", &cmake_args, Some(working_dir), &merged_env, false)
             .await?;
 
         if !result.success {
@@ -245,32 +455,55 @@ impl BuildSystem for CMakeBuildSystem {
     }
 
     async fn test(&self, ctx: &BuildSystemContext) -> Result<TestResults, Error> {
-        let start = std::time::Instant::now();
-
-        // Run ctest allowing failure to parse output
         let mut merged_env = ctx.get_all_env_vars();
         merged_env.extend(self.get_env_vars(ctx));
-        let result = ctx
+
+        // Ask ctest for JUnit results when it supports them (CTest 3.21+);
+        // older versions get the summary line scraped instead
+        let version = ctx
             .env
-            .execute_command_with_env(
-                "ctest",
-                &["--output-on-failure", "--parallel", &ctx.jobs.to_string()],
-                Some(&ctx.build_dir),
-                &merged_env,
-                true,
-            )
+            .execute_command_with_env("ctest", &["--version"], None, &merged_env, true)
             .await?;
+        let junit_path = ctx.build_dir.join(CTEST_JUNIT_FILE);
+        let _ = fs::remove_file(&junit_path).await;
+        let junit_arg = junit_path.display().to_string();
+
+        let jobs = ctx.jobs_for(self.name()).to_string();
+        let mut args = vec!["--output-on-failure", "--parallel", &jobs];
+        if ctest_supports_junit(&version.stdout) {
+            args.extend(["--output-junit", &junit_arg]);
+        }
 
+        // Run ctest allowing failure to parse output
+        let start = std::time::Instant::now();
+        let result = ctx
+            .env
+            .execute_command_with_env("ctest", &args, Some(&ctx.build_dir), &merged_env, true)
+            .await?;
         let duration = start.elapsed().as_secs_f64();
         let output = format!("{}\n{}", result.stdout, result.stderr);
 
+        let junit = match fs::read_to_string(&junit_path).await {
+            Ok(xml) => parse_ctest_junit(&xml),
+            Err(_) => None,
+        };
+        if let Some(mut results) = junit {
+            results.duration = duration;
+            results.output = output;
+            return Ok(results);
+        }
+
         // Parse CTest output
         let mut total = 0;
         let mut passed = 0;
         let mut failed = 0;
         let mut failures = vec![];
+        let mut timings = vec![];
 
         for line in output.lines() {
+            if let Some(timing) = parse_ctest_timing(line) {
+                timings.push(timing);
+            }
             // Look for test summary line: "X% tests passed, Y tests failed out of Z"
             if line.contains("% tests passed") {
                 if let Some(summary) = parse_ctest_summary(line) {
@@ -305,6 +538,8 @@ impl BuildSystem for CMakeBuildSystem {
             duration,
             output,
             failures,
+            timings,
+            executed: true,
         })
     }
 
@@ -416,6 +651,105 @@ impl BuildSystem for CMakeBuildSystem {
     }
 }
 
+/// `JUnit` report written by ctest into the build directory
+const CTEST_JUNIT_FILE: &str = "ctest-results.xml";
+
+/// Parse the `JUnit` report written by `ctest --output-junit`
+///
+/// Returns `None` if the report is not valid XML or has no test suite, so
+/// the caller can fall back to the text summary. Output and overall
+/// duration are left for the caller to fill in.
+fn parse_ctest_junit(xml: &str) -> Option<TestResults> {
+    let doc = roxmltree::Document::parse(xml).ok()?;
+    let suite = doc
+        .descendants()
+        .find(|node| node.has_tag_name("testsuite"))?;
+
+    let mut results = TestResults {
+        total: 0,
+        passed: 0,
+        failed: 0,
+        skipped: 0,
+        duration: 0.0,
+        output: String::new(),
+        failures: vec![],
+        timings: vec![],
+        executed: true,
+    };
+
+    for case in suite
+        .children()
+        .filter(|node| node.has_tag_name("testcase"))
+    {
+        let name = case.attribute("name").unwrap_or("unknown").to_string();
+        let time = case.attribute("time").and_then(|t| t.parse::<f64>().ok());
+        let child = |tag: &str| case.children().find(|node| node.has_tag_name(tag));
+        results.total += 1;
+        if let Some(duration) = time {
+            results.timings.push(TestTiming {
+                name: name.clone(),
+                duration,
+            });
+        }
+
+        if child("skipped").is_some() {
+            results.skipped += 1;
+        } else if let Some(failure) = child("failure").or_else(|| child("error")) {
+            results.failed += 1;
+            let reason = failure.attribute("message").unwrap_or("Failed");
+            let message = match time {
+                Some(time) => format!("{reason} after {time:.2}s"),
+                None => reason.to_string(),
+            };
+            let details = child("system-out")
+                .and_then(|node| node.text())
+                .map(str::trim)
+                .filter(|text| !text.is_empty())
+                .map(String::from);
+            results.failures.push(TestFailure {
+                name,
+                message,
+                details,
+            });
+        } else {
+            results.passed += 1;
+        }
+    }
+
+    Some(results)
+}
+
+/// Whether `ctest --version` output reports `CTest` 3.21 or newer, the
+/// first release with `--output-junit`
+fn ctest_supports_junit(version_output: &str) -> bool {
+    let Some(version) = version_output
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().last())
+    else {
+        return false;
+    };
+    let mut parts = version.split('.').map(|part| part.parse::<u32>().ok());
+    match (parts.next().flatten(), parts.next().flatten()) {
+        (Some(major), Some(minor)) => (major, minor) >= (3, 21),
+        _ => false,
+    }
+}
+
+/// Parse a per-test result line such as
+/// `1/4 Test #1: unit parser ......   Passed    0.25 sec`
+fn parse_ctest_timing(line: &str) -> Option<TestTiming> {
+    let rest = line.split_once("Test #")?.1;
+    let (_, rest) = rest.split_once(": ")?;
+    let (name, rest) = rest.split_once(" .")?;
+    let seconds = rest.trim_end().strip_suffix("sec")?;
+    let duration = seconds.split_whitespace().last()?.parse().ok()?;
+    Some(TestTiming {
+        name: name.trim().to_string(),
+        duration,
+    })
+}
+
 /// Parse `CTest` summary line
 fn parse_ctest_summary(line: &str) -> Option<(usize, usize, usize)> {
     // Parse "X% tests passed, Y tests failed out of Z"
@@ -438,3 +772,144 @@ fn parse_ctest_summary(line: &str) -> Option<(usize, usize, usize)> {
 
     Some((total, passed, failed))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::demo_environment;
+    use tempfile::TempDir;
+
+    const PRESETS: &str = r#"{
+  "version": 3,
+  "configurePresets": [
+    {
+      "name": "base",
+      "hidden": true,
+      "binaryDir": "${sourceDir}/out/${presetName}",
+      "cacheVariables": { "CMAKE_INSTALL_PREFIX": "/usr/local" }
+    },
+    {
+      "name": "release",
+      "inherits": "base",
+      "cacheVariables": { "CMAKE_BUILD_TYPE": "RelWithDebInfo" }
+    }
+  ],
+  "buildPresets": [{ "name": "release", "configurePreset": "release" }]
+}"#;
+
+    #[tokio::test]
+    async fn preset_inherits_binary_dir_and_cache_variables() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("CMakePresets.json"), PRESETS).unwrap();
+
+        let preset = CMakePreset::load(temp.path(), "release").await.unwrap();
+        assert_eq!(
+            preset.binary_dir,
+            Some(temp.path().join("out").join("release"))
+        );
+        assert!(preset.defines("CMAKE_BUILD_TYPE"));
+        assert!(preset.defines("CMAKE_INSTALL_PREFIX"));
+        assert!(!preset.defines("CMAKE_FIND_PACKAGE_PREFER_CONFIG"));
+        assert!(preset.has_build_preset);
+
+        assert!(CMakePreset::load(temp.path(), "missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn preset_suppresses_defaults_it_defines() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("CMakePresets.json"), PRESETS).unwrap();
+        let env = demo_environment(temp.path());
+        let ctx = BuildSystemContext::new(env, temp.path().to_path_buf());
+        let cmake = CMakeBuildSystem::new();
+
+        let preset = CMakePreset::load(temp.path(), "release").await.unwrap();
+        let args = cmake.get_cmake_args(&ctx, &[], Some(&preset));
+        assert_eq!(args[..2], ["--preset", "release"]);
+        assert!(
+            !args.iter().any(|arg| arg.starts_with("-DCMAKE_BUILD_TYPE=")
+                || arg.starts_with("-DCMAKE_INSTALL_PREFIX="))
+        );
+        assert!(args.contains(&"-DCMAKE_FIND_PACKAGE_PREFER_CONFIG=ON".to_string()));
+
+        let args = cmake.get_cmake_args(&ctx, &[], None);
+        assert!(args.contains(&"-DCMAKE_BUILD_TYPE=Release".to_string()));
+        assert!(args
+            .iter()
+            .any(|arg| arg.starts_with("-DCMAKE_INSTALL_PREFIX=")));
+    }
+
+    #[test]
+    fn junit_report_counts_each_test() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuite name="Linux" tests="4" failures="1" disabled="1" skipped="1" time="1.5">
+  <testcase name="unit parser" classname="unit parser" time="0.25" status="run">
+    <system-out>ok</system-out>
+  </testcase>
+  <testcase name="round trip with spaces" classname="round trip" time="1.20" status="fail">
+    <failure message="Failed"/>
+    <system-out>expected 3, got 4</system-out>
+  </testcase>
+  <testcase name="slow" classname="slow" time="0" status="disabled">
+    <skipped message="Disabled"/>
+  </testcase>
+  <testcase name="smoke" classname="smoke" time="0.05" status="run"/>
+</testsuite>"#;
+
+        let results = parse_ctest_junit(xml).unwrap();
+        assert_eq!(
+            (
+                results.total,
+                results.passed,
+                results.failed,
+                results.skipped
+            ),
+            (4, 2, 1, 1)
+        );
+        assert_eq!(results.failures.len(), 1);
+        let failure = &results.failures[0];
+        assert_eq!(failure.name, "round trip with spaces");
+        assert_eq!(failure.message, "Failed after 1.20s");
+        assert_eq!(failure.details.as_deref(), Some("expected 3, got 4"));
+        assert_eq!(results.timings.len(), 4);
+        assert_eq!(results.timings[0].name, "unit parser");
+        assert!((results.timings[0].duration - 0.25).abs() < f64::EPSILON);
+
+        assert!(parse_ctest_junit("not xml").is_none());
+    }
+
+    #[test]
+    fn text_output_reports_durations() {
+        let timing =
+            parse_ctest_timing("2/4 Test #2: round trip with spaces ......***Failed    1.20 sec")
+                .unwrap();
+        assert_eq!(timing.name, "round trip with spaces");
+        assert!((timing.duration - 1.2).abs() < f64::EPSILON);
+        assert!(parse_ctest_timing("50% tests passed, 2 tests failed out of 4").is_none());
+
+        assert!(ctest_supports_junit(
+            "ctest version 3.28.3\n\nCMake suite maintained"
+        ));
+        assert!(!ctest_supports_junit("ctest version 3.20.5\n"));
+        assert!(!ctest_supports_junit(""));
+    }
+
+    #[tokio::test]
+    async fn missing_presets_file_is_an_error() {
+        let temp = TempDir::new().unwrap();
+        assert!(CMakePreset::load(temp.path(), "release").await.is_err());
+    }
+
+    #[test]
+    fn preset_selection_is_split_from_user_args() {
+        let args = [
+            "--preset".to_string(),
+            "release".to_string(),
+            "-DFOO=ON".to_string(),
+        ];
+        assert_eq!(selected_preset(&args), Some("release"));
+        assert_eq!(strip_preset_args(&args), ["-DFOO=ON"]);
+        assert_eq!(selected_preset(&["--preset=dev".to_string()]), Some("dev"));
+        assert_eq!(selected_preset(&args[2..]), None);
+    }
+}
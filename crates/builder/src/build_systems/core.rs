@@ -1,5 +1,6 @@
 //! Core types and utilities for build systems
 
+use super::MesonWrapMode;
 use crate::BuildEnvironment;
 use sps2_errors::Error;
 use std::collections::HashMap;
@@ -18,12 +19,18 @@ pub struct BuildSystemContext {
     pub prefix: PathBuf,
     /// Number of parallel jobs
     pub jobs: usize,
+    /// Parallel job counts for specific build systems, keyed by build system name
+    pub jobs_overrides: HashMap<String, usize>,
     /// Additional environment variables
     pub extra_env: Arc<RwLock<HashMap<String, String>>>,
     /// Whether network access is allowed
     pub network_allowed: bool,
     /// Cache configuration
     pub cache_config: Option<CacheConfig>,
+    /// Whether the test phase runs
+    pub run_tests: bool,
+    /// Meson wrap mode chosen by the recipe; overrides the network default
+    pub wrap_mode: Option<MesonWrapMode>,
 }
 
 impl BuildSystemContext {
@@ -38,6 +45,9 @@ impl BuildSystemContext {
             .get("JOBS")
             .and_then(|j| j.parse().ok())
             .unwrap_or(1);
+        let run_tests = env.context.run_tests;
+        let jobs_overrides = env.context.jobs_overrides.clone();
+        let wrap_mode = env.context.meson_wrap_mode;
 
         Self {
             env,
@@ -45,9 +55,12 @@ impl BuildSystemContext {
             source_dir,
             prefix,
             jobs,
+            jobs_overrides,
             extra_env: Arc::new(RwLock::new(HashMap::new())),
             network_allowed: false,
             cache_config: None,
+            run_tests,
+            wrap_mode,
         }
     }
 
@@ -79,6 +92,40 @@ impl BuildSystemContext {
         self
     }
 
+    /// Enable or disable the test phase
+    #[must_use]
+    pub fn with_run_tests(mut self, run_tests: bool) -> Self {
+        self.run_tests = run_tests;
+        self
+    }
+
+    /// Set the Meson wrap mode
+    #[must_use]
+    pub fn with_wrap_mode(mut self, wrap_mode: MesonWrapMode) -> Self {
+        self.wrap_mode = Some(wrap_mode);
+        self
+    }
+
+    /// Override the parallel job count for one build system
+    #[must_use]
+    pub fn with_jobs_for(mut self, system: impl Into<String>, jobs: usize) -> Self {
+        self.jobs_overrides.insert(system.into(), jobs);
+        self
+    }
+
+    /// Parallel job count for the named build system
+    ///
+    /// Falls back to `jobs` when the system has no override (or an override
+    /// of zero).
+    #[must_use]
+    pub fn jobs_for(&self, system: &str) -> usize {
+        self.jobs_overrides
+            .get(system)
+            .copied()
+            .filter(|&jobs| jobs > 0)
+            .unwrap_or(self.jobs)
+    }
+
     /// Get all environment variables for the build
     ///
     /// Returns a combined map of base environment variables and any extra variables added.
@@ -116,9 +163,12 @@ impl Clone for BuildSystemContext {
             build_dir: self.build_dir.clone(),
             prefix: self.prefix.clone(),
             jobs: self.jobs,
+            jobs_overrides: self.jobs_overrides.clone(),
             extra_env: Arc::clone(&self.extra_env),
             network_allowed: self.network_allowed,
             cache_config: self.cache_config.clone(),
+            run_tests: self.run_tests,
+            wrap_mode: self.wrap_mode,
         }
     }
 }
@@ -131,9 +181,12 @@ impl std::fmt::Debug for BuildSystemContext {
             .field("build_dir", &self.build_dir)
             .field("prefix", &self.prefix)
             .field("jobs", &self.jobs)
+            .field("jobs_overrides", &self.jobs_overrides)
             .field("extra_env", &self.extra_env)
             .field("network_allowed", &self.network_allowed)
             .field("cache_config", &self.cache_config)
+            .field("run_tests", &self.run_tests)
+            .field("wrap_mode", &self.wrap_mode)
             .finish()
     }
 }
@@ -174,9 +227,29 @@ pub struct TestResults {
     pub output: String,
     /// Test failures with details
     pub failures: Vec<TestFailure>,
+    /// Per-test durations, when the test runner reports them
+    pub timings: Vec<TestTiming>,
+    /// Whether the test suite ran; `false` when the test phase was skipped
+    pub executed: bool,
 }
 
 impl TestResults {
+    /// Results for a test phase that was skipped
+    #[must_use]
+    pub fn not_run() -> Self {
+        Self {
+            total: 0,
+            passed: 0,
+            failed: 0,
+            skipped: 0,
+            duration: 0.0,
+            output: String::new(),
+            failures: vec![],
+            timings: vec![],
+            executed: false,
+        }
+    }
+
     /// Check if all tests passed
     #[must_use]
     pub fn all_passed(&self) -> bool {
@@ -209,6 +282,15 @@ pub struct TestFailure {
     pub details: Option<String>,
 }
 
+/// Duration of a single test
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestTiming {
+    /// Test name
+    pub name: String,
+    /// Test duration in seconds
+    pub duration: f64,
+}
+
 /// Cache configuration for builds
 #[derive(Clone, Debug)]
 pub struct CacheConfig {
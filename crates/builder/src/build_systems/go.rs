@@ -1,6 +1,8 @@
 //! Go build system implementation
 
-use super::{BuildSystem, BuildSystemConfig, BuildSystemContext, TestFailure, TestResults};
+use super::{
+    existing_markers, BuildSystem, BuildSystemConfig, BuildSystemContext, TestFailure, TestResults,
+};
 use async_trait::async_trait;
 use sps2_errors::{BuildError, Error};
 use sps2_events::{AppEvent, EventEmitter, GeneralEvent};
@@ -93,8 +95,9 @@ impl GoBuildSystem {
             }
 
             // Add parallel compilation
-            if ctx.jobs > 1 && !user_args.iter().any(|arg| arg.starts_with("-p=")) {
-                args.push(format!("-p={}", ctx.jobs));
+            let jobs = ctx.jobs_for("go");
+            if jobs > 1 && !user_args.iter().any(|arg| arg.starts_with("-p=")) {
+                args.push(format!("-p={jobs}"));
             }
         }
 
@@ -120,12 +123,14 @@ impl GoBuildSystem {
         // Add build target (current directory by default) only for build command
         if is_build_command
             && !user_args.iter().any(|arg| {
-                std::path::Path::new(arg)
-                    .extension()
-                    .is_some_and(|ext| ext.eq_ignore_ascii_case("go"))
-                    || arg.contains('/')
-                    || arg == "."
-                    || arg == "./..."
+                // Flags such as -ldflags=-X example.com/pkg.Version=1 are not targets
+                !arg.starts_with('-')
+                    && (std::path::Path::new(arg)
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("go"))
+                        || arg.contains('/')
+                        || arg == "."
+                        || arg == "./...")
             })
         {
             args.push(".".to_string()); // Build current package
@@ -193,22 +198,27 @@ impl Default for GoBuildSystem {
 #[async_trait]
 impl BuildSystem for GoBuildSystem {
     async fn detect(&self, source_dir: &Path) -> Result<bool, Error> {
+        Ok(!self.detection_markers(source_dir).await?.is_empty())
+    }
+
+    async fn detection_markers(&self, source_dir: &Path) -> Result<Vec<String>, Error> {
         // Check for go.mod (modern Go modules)
-        if source_dir.join("go.mod").exists() {
-            return Ok(true);
+        let markers = existing_markers(source_dir, &["go.mod"]);
+        if !markers.is_empty() {
+            return Ok(markers);
         }
 
-        // Check for any .go files
+        // Otherwise any .go files
+        let mut go_files = Vec::new();
         let mut entries = fs::read_dir(source_dir).await?;
-
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
             if path.extension().and_then(|s| s.to_str()) == Some("go") {
-                return Ok(true);
+                go_files.push(entry.file_name().to_string_lossy().into_owned());
             }
         }
-
-        Ok(false)
+        go_files.sort();
+        Ok(go_files)
     }
 
     fn get_config_options(&self) -> BuildSystemConfig {
@@ -301,8 +311,9 @@ impl BuildSystem for GoBuildSystem {
 
         // Add parallel test execution
         let jobs_str;
-        if ctx.jobs > 1 {
-            jobs_str = ctx.jobs.to_string();
+        let jobs = ctx.jobs_for(self.name());
+        if jobs > 1 {
+            jobs_str = jobs.to_string();
             test_args.push("-parallel");
             test_args.push(&jobs_str);
         }
@@ -332,6 +343,8 @@ impl BuildSystem for GoBuildSystem {
             duration,
             output,
             failures,
+            timings: vec![],
+            executed: true,
         })
     }
 
@@ -416,3 +429,46 @@ impl BuildSystem for GoBuildSystem {
         "go"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::demo_environment;
+    use tempfile::TempDir;
+
+    fn context(temp: &TempDir) -> BuildSystemContext {
+        let env = demo_environment(temp.path());
+        let mut ctx = BuildSystemContext::new(env, temp.path().to_path_buf());
+        ctx.jobs = 8;
+        ctx
+    }
+
+    #[test]
+    fn build_parallelism_respects_go_override() {
+        let temp = TempDir::new().unwrap();
+
+        let args = GoBuildSystem::get_build_args(&context(&temp), &[]);
+        assert!(args.contains(&"-p=8".to_string()));
+
+        let ctx = context(&temp)
+            .with_jobs_for("go", 12)
+            .with_jobs_for("cmake", 2);
+        let args = GoBuildSystem::get_build_args(&ctx, &[]);
+        assert!(args.contains(&"-p=12".to_string()));
+
+        // Overrides for other systems leave go on the global count
+        let ctx = context(&temp).with_jobs_for("cmake", 2);
+        let args = GoBuildSystem::get_build_args(&ctx, &[]);
+        assert!(args.contains(&"-p=8".to_string()));
+    }
+
+    #[test]
+    fn ldflags_with_import_paths_keep_the_default_target() {
+        let temp = TempDir::new().unwrap();
+        let user_args = ["-ldflags=-X example.com/tool/version.Version=1.0".to_string()];
+
+        let args = GoBuildSystem::get_build_args(&context(&temp), &user_args);
+        assert_eq!(args.last().map(String::as_str), Some("."));
+        assert!(!args.contains(&"-ldflags=-s -w".to_string()));
+    }
+}
@@ -1,10 +1,75 @@
 //! Meson build system implementation
 
-use super::{BuildSystem, BuildSystemConfig, BuildSystemContext, TestFailure, TestResults};
+use super::{
+    existing_markers, BuildSystem, BuildSystemConfig, BuildSystemContext, TestFailure, TestResults,
+};
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use sps2_errors::{BuildError, Error};
 use std::collections::HashMap;
+use std::fmt;
 use std::path::Path;
+use std::str::FromStr;
+
+/// How Meson resolves subprojects through wrap files (`--wrap-mode`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase", try_from = "String")]
+pub enum MesonWrapMode {
+    /// Use wraps, but never download anything
+    NoDownload,
+    /// Only use system dependencies; wraps are not used as fallbacks
+    NoFallback,
+    /// Always build the subproject instead of using system dependencies
+    ForceFallback,
+    /// Do not promote nested subprojects to the top level
+    NoPromote,
+}
+
+impl MesonWrapMode {
+    /// The value passed to `--wrap-mode`
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NoDownload => "nodownload",
+            Self::NoFallback => "nofallback",
+            Self::ForceFallback => "forcefallback",
+            Self::NoPromote => "nopromote",
+        }
+    }
+}
+
+impl fmt::Display for MesonWrapMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<String> for MesonWrapMode {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl FromStr for MesonWrapMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nodownload" => Ok(Self::NoDownload),
+            "nofallback" => Ok(Self::NoFallback),
+            "forcefallback" => Ok(Self::ForceFallback),
+            "nopromote" => Ok(Self::NoPromote),
+            other => Err(BuildError::ConfigureFailed {
+                message: format!(
+                    "invalid meson wrap mode '{other}' (expected nodownload, nofallback, forcefallback or nopromote)"
+                ),
+            }
+            .into()),
+        }
+    }
+}
 
 /// Meson build system
 pub struct MesonBuildSystem {
@@ -42,8 +107,58 @@ impl MesonBuildSystem {
         !ctx.network_allowed
     }
 
+    /// Resolve the wrap mode for `meson setup`
+    ///
+    /// The context's explicit mode wins over a `--wrap-mode` in the user
+    /// arguments, which wins over `nodownload` when the network is off.
+    fn wrap_mode(
+        ctx: &BuildSystemContext,
+        user_args: &[String],
+    ) -> Result<Option<MesonWrapMode>, Error> {
+        if let Some(mode) = ctx.wrap_mode {
+            return Ok(Some(mode));
+        }
+
+        let mut user_mode = None;
+        let mut args = user_args.iter();
+        while let Some(arg) = args.next() {
+            if let Some(value) = arg.strip_prefix("--wrap-mode=") {
+                user_mode = Some(value.parse()?);
+            } else if arg == "--wrap-mode" {
+                if let Some(value) = args.next() {
+                    user_mode = Some(value.parse()?);
+                }
+            }
+        }
+        if user_mode.is_some() {
+            return Ok(user_mode);
+        }
+
+        Ok(Self::should_disable_wrap(ctx).then_some(MesonWrapMode::NoDownload))
+    }
+
+    /// User arguments without any `--wrap-mode` flags
+    fn strip_wrap_mode_args(user_args: &[String]) -> Vec<String> {
+        let mut stripped = Vec::with_capacity(user_args.len());
+        let mut args = user_args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--wrap-mode" {
+                args.next();
+            } else if !arg.starts_with("--wrap-mode=") {
+                stripped.push(arg.clone());
+            }
+        }
+        stripped
+    }
+
     /// Get Meson setup arguments
-    fn get_setup_args(&self, ctx: &BuildSystemContext, user_args: &[String]) -> Vec<String> {
+    fn get_setup_args(
+        &self,
+        ctx: &BuildSystemContext,
+        user_args: &[String],
+    ) -> Result<Vec<String>, Error> {
+        let wrap_mode = Self::wrap_mode(ctx, user_args)?;
+        let user_args = Self::strip_wrap_mode_args(user_args);
         let mut args = vec!["setup".to_string()];
 
         // Build directory
@@ -69,11 +184,9 @@ impl MesonBuildSystem {
             }
         }
 
-        // Handle wrap mode
-        if Self::should_disable_wrap(ctx)
-            && !user_args.iter().any(|arg| arg.starts_with("--wrap-mode="))
-        {
-            args.push("--wrap-mode=nodownload".to_string());
+        // Handle wrap mode; exactly one flag is passed
+        if let Some(mode) = wrap_mode {
+            args.push(format!("--wrap-mode={mode}"));
         }
 
         // macOS ARM only - no cross-compilation support
@@ -84,9 +197,9 @@ impl MesonBuildSystem {
         }
 
         // Add user arguments
-        args.extend(user_args.iter().cloned());
+        args.extend(user_args);
 
-        args
+        Ok(args)
     }
 
     /// Parse Meson test output
@@ -129,7 +242,11 @@ impl Default for MesonBuildSystem {
 #[async_trait]
 impl BuildSystem for MesonBuildSystem {
     async fn detect(&self, source_dir: &Path) -> Result<bool, Error> {
-        Ok(source_dir.join("meson.build").exists())
+        Ok(!self.detection_markers(source_dir).await?.is_empty())
+    }
+
+    async fn detection_markers(&self, source_dir: &Path) -> Result<Vec<String>, Error> {
+        Ok(existing_markers(source_dir, &["meson.build"]))
     }
 
     fn get_config_options(&self) -> BuildSystemConfig {
@@ -138,7 +255,7 @@ impl BuildSystem for MesonBuildSystem {
 
     async fn configure(&self, ctx: &BuildSystemContext, args: &[String]) -> Result<(), Error> {
         // Get setup arguments
-        let setup_args = self.get_setup_args(ctx, args);
+        let setup_args = self.get_setup_args(ctx, args)?;
         let arg_refs: Vec<&str> = setup_args.iter().map(String::as_str).collect();
 
         // Merge environment
@@ -172,8 +289,9 @@ impl BuildSystem for MesonBuildSystem {
 
         // Add parallel jobs
         let jobs_str;
-        if ctx.jobs > 1 {
-            jobs_str = ctx.jobs.to_string();
+        let jobs = ctx.jobs_for(self.name());
+        if jobs > 1 {
+            jobs_str = jobs.to_string();
             compile_args.push("-j");
             compile_args.push(&jobs_str);
         }
@@ -216,7 +334,7 @@ impl BuildSystem for MesonBuildSystem {
 
         // Run meson test (allow failure to parse)
         let build_dir_str = ctx.build_dir.display().to_string();
-        let jobs_str = ctx.jobs.to_string();
+        let jobs_str = ctx.jobs_for(self.name()).to_string();
         let mut merged_env = ctx.get_all_env_vars();
         merged_env.extend(self.get_env_vars(ctx));
         let result = ctx
@@ -252,6 +370,8 @@ impl BuildSystem for MesonBuildSystem {
             duration,
             output,
             failures,
+            timings: vec![],
+            executed: true,
         })
     }
 
@@ -332,3 +452,60 @@ fn parse_meson_test_line(line: &str) -> Option<(usize, &str, &str)> {
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::demo_environment;
+    use tempfile::TempDir;
+
+    fn context(temp: &TempDir) -> BuildSystemContext {
+        let env = demo_environment(temp.path());
+        BuildSystemContext::new(env, temp.path().to_path_buf())
+    }
+
+    fn wrap_flags(args: &[String]) -> Vec<&str> {
+        args.iter()
+            .map(String::as_str)
+            .filter(|arg| arg.starts_with("--wrap-mode"))
+            .collect()
+    }
+
+    #[test]
+    fn explicit_wrap_mode_overrides_network_default() {
+        let temp = TempDir::new().unwrap();
+        let meson = MesonBuildSystem::new();
+
+        let offline = context(&temp);
+        let args = meson.get_setup_args(&offline, &[]).unwrap();
+        assert_eq!(wrap_flags(&args), ["--wrap-mode=nodownload"]);
+
+        let online = context(&temp).with_network_allowed(true);
+        let args = meson.get_setup_args(&online, &[]).unwrap();
+        assert!(wrap_flags(&args).is_empty());
+
+        let pinned = context(&temp)
+            .with_network_allowed(true)
+            .with_wrap_mode(MesonWrapMode::NoFallback);
+        let user_args = vec!["--wrap-mode".to_string(), "forcefallback".to_string()];
+        let args = meson.get_setup_args(&pinned, &user_args).unwrap();
+        assert_eq!(wrap_flags(&args), ["--wrap-mode=nofallback"]);
+        assert!(!args.contains(&"forcefallback".to_string()));
+    }
+
+    #[test]
+    fn user_wrap_mode_is_validated() {
+        let temp = TempDir::new().unwrap();
+        let meson = MesonBuildSystem::new();
+        let ctx = context(&temp);
+
+        let args = meson
+            .get_setup_args(&ctx, &["--wrap-mode=nopromote".to_string()])
+            .unwrap();
+        assert_eq!(wrap_flags(&args), ["--wrap-mode=nopromote"]);
+
+        assert!(meson
+            .get_setup_args(&ctx, &["--wrap-mode=sometimes".to_string()])
+            .is_err());
+    }
+}
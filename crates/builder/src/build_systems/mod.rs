@@ -1,11 +1,13 @@
 //! Build system abstraction and implementations
 //!
 //! This module provides a trait-based abstraction for different build systems
-//! (autotools, cmake, meson, cargo, etc.) with automatic detection and
+//! (autotools, cmake, meson, ninja, cargo, etc.) with automatic detection and
 //! sophisticated configuration handling.
 
 use async_trait::async_trait;
 use sps2_errors::Error;
+use sps2_events::events::SkippedBuildSystem;
+use sps2_events::{AppEvent, BuildDiagnostic, BuildEvent, EventEmitter, EventSender};
 use std::collections::HashMap;
 use std::path::Path;
 
@@ -15,15 +17,19 @@ mod cmake;
 mod core;
 mod go;
 mod meson;
+mod ninja;
 mod nodejs;
 mod python;
 
+pub(crate) use autotools::has_prefix_arg;
 pub use autotools::AutotoolsBuildSystem;
 pub use cargo::CargoBuildSystem;
 pub use cmake::CMakeBuildSystem;
-pub use core::{BuildSystemConfig, BuildSystemContext, TestFailure, TestResults};
+pub(crate) use cmake::CMakePreset;
+pub use core::{BuildSystemConfig, BuildSystemContext, TestFailure, TestResults, TestTiming};
 pub use go::GoBuildSystem;
-pub use meson::MesonBuildSystem;
+pub use meson::{MesonBuildSystem, MesonWrapMode};
+pub use ninja::NinjaBuildSystem;
 pub use nodejs::NodeJsBuildSystem;
 pub use python::PythonBuildSystem;
 
@@ -33,6 +39,13 @@ pub trait BuildSystem: Send + Sync {
     /// Detect if this build system applies to the source directory
     async fn detect(&self, source_dir: &Path) -> Result<bool, Error>;
 
+    /// Files in the source directory that make [`detect`](Self::detect) match
+    ///
+    /// Reported when explaining auto-detection; the default names none.
+    async fn detection_markers(&self, _source_dir: &Path) -> Result<Vec<String>, Error> {
+        Ok(Vec::new())
+    }
+
     /// Get configuration options specific to this build system
     fn get_config_options(&self) -> BuildSystemConfig;
 
@@ -45,6 +58,19 @@ pub trait BuildSystem: Send + Sync {
     /// Test phase
     async fn test(&self, ctx: &BuildSystemContext) -> Result<TestResults, Error>;
 
+    /// Run the test phase unless the context disables it
+    ///
+    /// Callers should use this rather than [`test`](Self::test) so the
+    /// `run_tests` setting is honored by every build system. A skipped phase
+    /// returns [`TestResults::not_run`].
+    async fn run_tests(&self, ctx: &BuildSystemContext) -> Result<TestResults, Error> {
+        if ctx.run_tests {
+            self.test(ctx).await
+        } else {
+            Ok(TestResults::not_run())
+        }
+    }
+
     /// Install phase
     async fn install(&self, ctx: &BuildSystemContext) -> Result<(), Error>;
 
@@ -65,26 +91,133 @@ pub trait BuildSystem: Send + Sync {
     }
 }
 
+/// Names of the build systems registered by [`BuildSystemRegistry::new`],
+/// in detection priority order
+pub const BUILTIN_BUILD_SYSTEMS: [&str; 8] = [
+    "autotools",
+    "cmake",
+    "meson",
+    "cargo",
+    "go",
+    "python",
+    "nodejs",
+    "ninja",
+];
+
+/// Construct a fresh instance of a built-in build system
+fn builtin_build_system(name: &str) -> Option<Box<dyn BuildSystem>> {
+    let system: Box<dyn BuildSystem> = match name {
+        "autotools" => Box::new(AutotoolsBuildSystem::new()),
+        "cmake" => Box::new(CMakeBuildSystem::new()),
+        "meson" => Box::new(MesonBuildSystem::new()),
+        "cargo" => Box::new(CargoBuildSystem::new()),
+        "go" => Box::new(GoBuildSystem::new()),
+        "python" => Box::new(PythonBuildSystem::new()),
+        "nodejs" => Box::new(NodeJsBuildSystem::new()),
+        "ninja" => Box::new(NinjaBuildSystem::new()),
+        _ => return None,
+    };
+    Some(system)
+}
+
+/// Names of the files in `candidates` that exist in `source_dir`
+fn existing_markers(source_dir: &Path, candidates: &[&str]) -> Vec<String> {
+    candidates
+        .iter()
+        .filter(|name| source_dir.join(name).exists())
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Gap between the priorities assigned by [`BuildSystemRegistry::register`]
+const PRIORITY_STEP: u32 = 100;
+
+struct RegisteredBuildSystem {
+    name: String,
+    priority: u32,
+    system: Box<dyn BuildSystem>,
+}
+
 /// Registry of available build systems
+///
+/// Entries are keyed by name and kept in priority order (lower first);
+/// detection tries them in that order. The built-in systems are registered
+/// at construction and embedders can add their own with
+/// [`register`](Self::register).
 pub struct BuildSystemRegistry {
-    systems: Vec<Box<dyn BuildSystem>>,
+    systems: Vec<RegisteredBuildSystem>,
 }
 
 impl BuildSystemRegistry {
     /// Create a new registry with all supported build systems
     #[must_use]
     pub fn new() -> Self {
+        let mut registry = Self::empty();
+        for name in BUILTIN_BUILD_SYSTEMS {
+            if let Some(system) = builtin_build_system(name) {
+                registry.register(name, system);
+            }
+        }
+        registry
+    }
+
+    /// Create a registry with no build systems
+    #[must_use]
+    pub fn empty() -> Self {
         Self {
-            systems: vec![
-                Box::new(AutotoolsBuildSystem::new()),
-                Box::new(CMakeBuildSystem::new()),
-                Box::new(MesonBuildSystem::new()),
-                Box::new(CargoBuildSystem::new()),
-                Box::new(GoBuildSystem::new()),
-                Box::new(PythonBuildSystem::new()),
-                Box::new(NodeJsBuildSystem::new()),
-            ],
+            systems: Vec::new(),
+        }
+    }
+
+    /// Register a build system after all existing entries
+    ///
+    /// Replaces (and keeps the priority of) an existing entry with the same
+    /// name.
+    pub fn register(&mut self, name: impl Into<String>, system: Box<dyn BuildSystem>) {
+        let name = name.into();
+        if let Some(entry) = self.entry_mut(&name) {
+            entry.system = system;
+            return;
         }
+        let priority = self
+            .systems
+            .last()
+            .map_or(PRIORITY_STEP, |s| s.priority.saturating_add(PRIORITY_STEP));
+        self.systems.push(RegisteredBuildSystem {
+            name,
+            priority,
+            system,
+        });
+    }
+
+    /// Register a build system at an explicit priority (lower is tried first)
+    ///
+    /// The built-in systems use priorities 100, 200, ... in the order of
+    /// [`BUILTIN_BUILD_SYSTEMS`]. An existing entry with the same name is
+    /// replaced.
+    pub fn register_with_priority(
+        &mut self,
+        name: impl Into<String>,
+        priority: u32,
+        system: Box<dyn BuildSystem>,
+    ) {
+        let name = name.into();
+        self.systems
+            .retain(|entry| !entry.name.eq_ignore_ascii_case(&name));
+        let index = self.systems.partition_point(|s| s.priority <= priority);
+        self.systems.insert(
+            index,
+            RegisteredBuildSystem {
+                name,
+                priority,
+                system,
+            },
+        );
+    }
+
+    /// Names of the registered build systems in priority order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.systems.iter().map(|entry| entry.name.as_str())
     }
 
     /// Detect which build system to use for a source directory
@@ -93,24 +226,107 @@ impl BuildSystemRegistry {
     ///
     /// Returns an error if detection fails or no suitable build system is found
     pub async fn detect(&self, source_dir: &Path) -> Result<&dyn BuildSystem, Error> {
-        for system in &self.systems {
-            if system.detect(source_dir).await? {
-                return Ok(system.as_ref());
+        Ok(self.detect_with_rationale(source_dir).await?.system)
+    }
+
+    /// Every registered build system that matches `source_dir`, by name
+    ///
+    /// Matches are in priority order, so the first one is the system
+    /// [`detect`](Self::detect) would pick.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a build system's detection fails
+    pub async fn detect_all(
+        &self,
+        source_dir: &Path,
+    ) -> Result<Vec<(&str, &dyn BuildSystem)>, Error> {
+        let mut matches = Vec::new();
+        for entry in &self.systems {
+            if entry.system.detect(source_dir).await? {
+                matches.push((entry.name.as_str(), entry.system.as_ref()));
+            }
+        }
+        Ok(matches)
+    }
+
+    /// Detect the build system and explain the choice
+    ///
+    /// Every registered system is checked; the first match in priority order
+    /// wins and the other matches are reported as skipped runner-ups.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if detection fails or no suitable build system is found
+    pub async fn detect_with_rationale(
+        &self,
+        source_dir: &Path,
+    ) -> Result<BuildSystemDetection<'_>, Error> {
+        let mut matches = Vec::new();
+        for entry in &self.systems {
+            if entry.system.detect(source_dir).await? {
+                let markers = entry.system.detection_markers(source_dir).await?;
+                matches.push((entry, markers));
             }
         }
 
-        Err(sps2_errors::BuildError::NoBuildSystemDetected {
-            path: source_dir.display().to_string(),
+        let mut matches = matches.into_iter();
+        let Some((chosen, markers)) = matches.next() else {
+            return Err(sps2_errors::BuildError::NoBuildSystemDetected {
+                path: source_dir.display().to_string(),
+            }
+            .into());
+        };
+        let skipped = matches
+            .map(|(entry, markers)| SkippedBuildSystem {
+                name: entry.name.clone(),
+                markers,
+                reason: format!("lower priority than {}", chosen.name),
+            })
+            .collect();
+
+        Ok(BuildSystemDetection {
+            system: chosen.system.as_ref(),
+            name: chosen.name.clone(),
+            markers,
+            skipped,
+        })
+    }
+
+    /// Detect the build system, reporting the decision as an event
+    ///
+    /// Emits [`BuildDiagnostic::BuildSystemDetected`] naming the chosen
+    /// system, the files that triggered it and any runner-ups that also
+    /// matched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if detection fails or no suitable build system is found
+    pub async fn detect_with_events(
+        &self,
+        source_dir: &Path,
+        event_sender: Option<&EventSender>,
+    ) -> Result<BuildSystemDetection<'_>, Error> {
+        let detection = self.detect_with_rationale(source_dir).await?;
+        if let Some(sender) = event_sender {
+            sender.emit(detection.to_event(source_dir));
         }
-        .into())
+        Ok(detection)
     }
 
     /// Get a specific build system by name
+    #[must_use]
     pub fn get(&self, name: &str) -> Option<&dyn BuildSystem> {
         self.systems
             .iter()
-            .find(|s| s.name().eq_ignore_ascii_case(name))
-            .map(std::convert::AsRef::as_ref)
+            .find(|entry| entry.name.eq_ignore_ascii_case(name))
+            .map(|entry| entry.system.as_ref())
+    }
+
+    fn entry_mut(&mut self, name: &str) -> Option<&mut RegisteredBuildSystem> {
+        self.systems
+            .iter_mut()
+            .find(|entry| entry.name.eq_ignore_ascii_case(name))
     }
 }
 
@@ -120,24 +336,295 @@ impl Default for BuildSystemRegistry {
     }
 }
 
-/// Automatically detect and return the appropriate build system
+impl std::fmt::Debug for BuildSystemRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+/// Outcome of build system auto-detection
+pub struct BuildSystemDetection<'a> {
+    /// The chosen build system
+    pub system: &'a dyn BuildSystem,
+    /// Registry name of the chosen build system
+    pub name: String,
+    /// Files that made the chosen system match
+    pub markers: Vec<String>,
+    /// Other systems that matched but lost on priority
+    pub skipped: Vec<SkippedBuildSystem>,
+}
+
+impl BuildSystemDetection<'_> {
+    /// Event describing this decision for `source_dir`
+    #[must_use]
+    pub fn to_event(&self, source_dir: &Path) -> AppEvent {
+        AppEvent::Build(BuildEvent::Diagnostic(
+            BuildDiagnostic::BuildSystemDetected {
+                source_dir: source_dir.to_path_buf(),
+                system: self.name.clone(),
+                markers: self.markers.clone(),
+                skipped: self.skipped.clone(),
+            },
+        ))
+    }
+}
+
+impl std::fmt::Debug for BuildSystemDetection<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BuildSystemDetection")
+            .field("name", &self.name)
+            .field("markers", &self.markers)
+            .field("skipped", &self.skipped)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Automatically detect and return the appropriate built-in build system
 ///
 /// # Errors
 ///
 /// Returns an error if no suitable build system can be detected
 pub async fn detect_build_system(source_dir: &Path) -> Result<Box<dyn BuildSystem>, Error> {
+    detect_build_system_with_events(source_dir, None).await
+}
+
+/// Detect the built-in build system, reporting the decision as an event
+///
+/// Emits [`BuildDiagnostic::BuildSystemDetected`] naming the chosen system,
+/// the files that triggered it and any runner-ups that also matched.
+///
+/// # Errors
+///
+/// Returns an error if no suitable build system can be detected
+pub async fn detect_build_system_with_events(
+    source_dir: &Path,
+    event_sender: Option<&EventSender>,
+) -> Result<Box<dyn BuildSystem>, Error> {
     let registry = BuildSystemRegistry::new();
-    let system = registry.detect(source_dir).await?;
-
-    // Return a boxed clone of the detected system
-    match system.name() {
-        "autotools" => Ok(Box::new(AutotoolsBuildSystem::new())),
-        "cmake" => Ok(Box::new(CMakeBuildSystem::new())),
-        "meson" => Ok(Box::new(MesonBuildSystem::new())),
-        "cargo" => Ok(Box::new(CargoBuildSystem::new())),
-        "go" => Ok(Box::new(GoBuildSystem::new())),
-        "python" => Ok(Box::new(PythonBuildSystem::new())),
-        "nodejs" => Ok(Box::new(NodeJsBuildSystem::new())),
-        _ => unreachable!("Unknown build system"),
+    let system = registry
+        .detect_with_events(source_dir, event_sender)
+        .await?
+        .system;
+
+    // Return a fresh instance of the detected system
+    builtin_build_system(system.name()).ok_or_else(|| {
+        sps2_errors::BuildError::UnknownBuildSystem {
+            name: system.name().to_string(),
+        }
+        .into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::demo_context;
+    use tempfile::TempDir;
+
+    /// Build system that claims any directory containing `fake.build`
+    struct FakeBuildSystem;
+
+    #[async_trait]
+    impl BuildSystem for FakeBuildSystem {
+        async fn detect(&self, source_dir: &Path) -> Result<bool, Error> {
+            Ok(source_dir.join("fake.build").exists())
+        }
+
+        fn get_config_options(&self) -> BuildSystemConfig {
+            BuildSystemConfig::default()
+        }
+
+        async fn configure(
+            &self,
+            _ctx: &BuildSystemContext,
+            _args: &[String],
+        ) -> Result<(), Error> {
+            Ok(())
+        }
+
+        async fn build(&self, _ctx: &BuildSystemContext, _args: &[String]) -> Result<(), Error> {
+            Ok(())
+        }
+
+        async fn test(&self, _ctx: &BuildSystemContext) -> Result<TestResults, Error> {
+            Ok(TestResults {
+                total: 0,
+                passed: 0,
+                failed: 0,
+                skipped: 0,
+                duration: 0.0,
+                output: String::new(),
+                failures: vec![],
+                timings: vec![],
+                executed: true,
+            })
+        }
+
+        async fn install(&self, _ctx: &BuildSystemContext) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn get_env_vars(&self, _ctx: &BuildSystemContext) -> HashMap<String, String> {
+            HashMap::new()
+        }
+
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+    }
+
+    #[tokio::test]
+    async fn registered_build_system_is_detected() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(source.path().join("fake.build"), "").unwrap();
+
+        let mut registry = BuildSystemRegistry::new();
+        assert!(registry.detect(source.path()).await.is_err());
+
+        registry.register("fake", Box::new(FakeBuildSystem));
+        assert_eq!(registry.names().last(), Some("fake"));
+        assert_eq!(registry.detect(source.path()).await.unwrap().name(), "fake");
+        assert_eq!(registry.get("FAKE").unwrap().name(), "fake");
+    }
+
+    #[tokio::test]
+    async fn detection_follows_priority_order() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(source.path().join("fake.build"), "").unwrap();
+        std::fs::write(source.path().join("CMakeLists.txt"), "").unwrap();
+
+        let mut registry = BuildSystemRegistry::new();
+        registry.register("fake", Box::new(FakeBuildSystem));
+        assert_eq!(
+            registry.detect(source.path()).await.unwrap().name(),
+            "cmake"
+        );
+
+        registry.register_with_priority("fake", 0, Box::new(FakeBuildSystem));
+        assert_eq!(registry.names().next(), Some("fake"));
+        assert_eq!(registry.names().count(), BUILTIN_BUILD_SYSTEMS.len() + 1);
+        assert_eq!(registry.detect(source.path()).await.unwrap().name(), "fake");
+
+        let matches = registry.detect_all(source.path()).await.unwrap();
+        let names: Vec<&str> = matches.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["fake", "cmake"]);
+    }
+
+    #[tokio::test]
+    async fn shipped_build_ninja_is_detected_after_generators() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(source.path().join("build.ninja"), "").unwrap();
+
+        let registry = BuildSystemRegistry::new();
+        assert_eq!(
+            registry.detect(source.path()).await.unwrap().name(),
+            "ninja"
+        );
+
+        // A generator project wins over a build.ninja left in its tree
+        std::fs::write(source.path().join("meson.build"), "").unwrap();
+        let detection = registry.detect_with_rationale(source.path()).await.unwrap();
+        assert_eq!(detection.name, "meson");
+        assert_eq!(detection.skipped[0].name, "ninja");
+    }
+
+    fn system_context(context: crate::BuildContext, temp: &TempDir) -> BuildSystemContext {
+        let env = crate::BuildEnvironment::new(context, temp.path()).unwrap();
+        BuildSystemContext::new(env, temp.path().to_path_buf())
+    }
+
+    #[tokio::test]
+    async fn test_phase_runs_by_default() {
+        let temp = TempDir::new().unwrap();
+        let ctx = system_context(demo_context(temp.path()), &temp);
+        assert!(ctx.run_tests);
+
+        let results = FakeBuildSystem.run_tests(&ctx).await.unwrap();
+        assert!(results.executed);
+    }
+
+    #[tokio::test]
+    async fn disabled_test_phase_is_reported_as_not_run() {
+        let temp = TempDir::new().unwrap();
+        let ctx = system_context(demo_context(temp.path()).with_run_tests(false), &temp);
+        assert!(!ctx.run_tests, "context should inherit the build setting");
+
+        let results = FakeBuildSystem.run_tests(&ctx).await.unwrap();
+        assert!(!results.executed);
+        assert_eq!(results.total, 0);
+
+        // The per-system context can also re-enable it explicitly
+        let results = FakeBuildSystem
+            .run_tests(&ctx.with_run_tests(true))
+            .await
+            .unwrap();
+        assert!(results.executed);
+    }
+
+    #[test]
+    fn job_overrides_apply_per_build_system() {
+        let temp = TempDir::new().unwrap();
+        let context = demo_context(temp.path())
+            .with_jobs_for("cmake", 2)
+            .with_jobs_for("go", 0);
+        let env = crate::BuildEnvironment::new(context, temp.path()).unwrap();
+        let mut ctx = BuildSystemContext::new(env, temp.path().to_path_buf());
+        ctx.jobs = 8;
+
+        assert_eq!(ctx.jobs_for("cmake"), 2, "inherited from the build context");
+        assert_eq!(ctx.jobs_for("cargo"), 8);
+        assert_eq!(ctx.jobs_for("go"), 8, "zero falls back to the global count");
+
+        let ctx = ctx.with_jobs_for("meson", 3);
+        assert_eq!(ctx.jobs_for("meson"), 3);
+        assert_eq!(ctx.clone().jobs_for("cmake"), 2);
+    }
+
+    #[tokio::test]
+    async fn detection_event_explains_choice_and_runner_ups() {
+        let source = TempDir::new().unwrap();
+        for file in [
+            "CMakeLists.txt",
+            "meson.build",
+            "configure.ac",
+            "Makefile.am",
+        ] {
+            std::fs::write(source.path().join(file), "").unwrap();
+        }
+
+        let (tx, mut rx) = sps2_events::channel();
+        let system = detect_build_system_with_events(source.path(), Some(&tx))
+            .await
+            .unwrap();
+        assert_eq!(system.name(), "autotools");
+
+        let message = rx.try_recv().expect("detection event");
+        let AppEvent::Build(BuildEvent::Diagnostic(BuildDiagnostic::BuildSystemDetected {
+            source_dir,
+            system,
+            markers,
+            skipped,
+        })) = message.event
+        else {
+            panic!("unexpected event: {:?}", message.event);
+        };
+        assert_eq!(source_dir, source.path());
+        assert_eq!(system, "autotools");
+        assert_eq!(markers, ["configure.ac", "Makefile.am"]);
+        assert_eq!(
+            skipped,
+            [
+                SkippedBuildSystem {
+                    name: "cmake".to_string(),
+                    markers: vec!["CMakeLists.txt".to_string()],
+                    reason: "lower priority than autotools".to_string(),
+                },
+                SkippedBuildSystem {
+                    name: "meson".to_string(),
+                    markers: vec!["meson.build".to_string()],
+                    reason: "lower priority than autotools".to_string(),
+                },
+            ]
+        );
     }
 }
@@ -0,0 +1,308 @@
+//! Ninja build system implementation
+//!
+//! For projects that ship a ready-made `build.ninja` rather than a
+//! generator input; projects using `CMake` or Meson are handled by those
+//! systems, which drive Ninja themselves.
+
+use super::{
+    existing_markers, BuildSystem, BuildSystemConfig, BuildSystemContext, TestFailure, TestResults,
+};
+use async_trait::async_trait;
+use sps2_errors::{BuildError, Error};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Ninja build system
+pub struct NinjaBuildSystem {
+    config: BuildSystemConfig,
+}
+
+impl NinjaBuildSystem {
+    /// Create a new Ninja build system instance
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            config: BuildSystemConfig {
+                supports_out_of_source: false,
+                supports_parallel_builds: true,
+                supports_incremental_builds: true,
+                default_configure_args: vec![],
+                default_build_args: vec![],
+                env_prefix: None,
+                watch_patterns: vec!["build.ninja".to_string(), "*.ninja".to_string()],
+            },
+        }
+    }
+
+    /// Run `ninja` with `args` in the build directory
+    async fn run(
+        &self,
+        ctx: &BuildSystemContext,
+        args: &[&str],
+        extra_env: Option<(&str, String)>,
+        allow_failure: bool,
+    ) -> Result<crate::BuildCommandResult, Error> {
+        let build_dir = ctx.build_dir.display().to_string();
+        let mut ninja_args = vec!["-C", build_dir.as_str()];
+        ninja_args.extend_from_slice(args);
+
+        let mut merged_env = ctx.get_all_env_vars();
+        if let Some((key, value)) = extra_env {
+            merged_env.insert(key.to_string(), value);
+        }
+        merged_env.extend(self.get_env_vars(ctx));
+
+        ctx.env
+            .execute_command_with_env(
+                "ninja",
+                &ninja_args,
+                Some(&ctx.source_dir),
+                &merged_env,
+                allow_failure,
+            )
+            .await
+    }
+
+    /// Parse the summary printed by the `test` target
+    ///
+    /// Understands the summaries of `CTest` and `meson test`, the usual
+    /// runners behind a generated `test` target. Returns `None` when no
+    /// summary is found.
+    fn parse_test_output(output: &str) -> Option<(usize, usize, usize, Vec<TestFailure>)> {
+        parse_ctest_summary(output).or_else(|| parse_meson_summary(output))
+    }
+}
+
+impl Default for NinjaBuildSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl BuildSystem for NinjaBuildSystem {
+    async fn detect(&self, source_dir: &Path) -> Result<bool, Error> {
+        Ok(!self.detection_markers(source_dir).await?.is_empty())
+    }
+
+    async fn detection_markers(&self, source_dir: &Path) -> Result<Vec<String>, Error> {
+        Ok(existing_markers(source_dir, &["build.ninja"]))
+    }
+
+    fn get_config_options(&self) -> BuildSystemConfig {
+        self.config.clone()
+    }
+
+    async fn configure(&self, _ctx: &BuildSystemContext, _args: &[String]) -> Result<(), Error> {
+        // build.ninja is used as shipped; there is nothing to configure
+        Ok(())
+    }
+
+    async fn build(&self, ctx: &BuildSystemContext, args: &[String]) -> Result<(), Error> {
+        let jobs = format!("-j{}", ctx.jobs_for(self.name()).max(1));
+        let mut build_args = vec![jobs.as_str()];
+        build_args.extend(args.iter().map(String::as_str));
+
+        let result = self.run(ctx, &build_args, None, false).await?;
+        if !result.success {
+            return Err(BuildError::CompilationFailed {
+                message: format!("ninja failed: {}", result.stderr),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    async fn test(&self, ctx: &BuildSystemContext) -> Result<TestResults, Error> {
+        let start = std::time::Instant::now();
+
+        // Run ninja test (allow failure to parse)
+        let result = self.run(ctx, &["test"], None, true).await?;
+        let duration = start.elapsed().as_secs_f64();
+        let output = format!("{}\n{}", result.stdout, result.stderr);
+
+        if !result.success && output.contains("unknown target 'test'") {
+            return Ok(TestResults::not_run());
+        }
+
+        // Without a recognizable summary the exit status is all there is
+        let (total, passed, failed, failures) =
+            Self::parse_test_output(&output).unwrap_or_else(|| {
+                if result.success {
+                    (1, 1, 0, vec![])
+                } else {
+                    let failure = TestFailure {
+                        name: "test".to_string(),
+                        message: "ninja test failed".to_string(),
+                        details: None,
+                    };
+                    (1, 0, 1, vec![failure])
+                }
+            });
+        let skipped = total.saturating_sub(passed + failed);
+
+        Ok(TestResults {
+            total,
+            passed,
+            failed,
+            skipped,
+            duration,
+            output,
+            failures,
+            timings: vec![],
+            executed: true,
+        })
+    }
+
+    async fn install(&self, ctx: &BuildSystemContext) -> Result<(), Error> {
+        // Run ninja install with DESTDIR in env
+        let destdir = ctx.env.staging_dir().display().to_string();
+        let result = self
+            .run(ctx, &["install"], Some(("DESTDIR", destdir)), false)
+            .await?;
+
+        if !result.success {
+            return Err(BuildError::InstallFailed {
+                message: format!("ninja install failed: {}", result.stderr),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    fn get_env_vars(&self, _ctx: &BuildSystemContext) -> HashMap<String, String> {
+        HashMap::new()
+    }
+
+    fn name(&self) -> &'static str {
+        "ninja"
+    }
+}
+
+/// Parse `CTest`'s summary
+///
+/// Format: "80% tests passed, 1 tests failed out of 5", followed by the
+/// failed tests as "  3 - name (Failed)".
+fn parse_ctest_summary(output: &str) -> Option<(usize, usize, usize, Vec<TestFailure>)> {
+    let (failed, total) = output.lines().find_map(|line| {
+        let (_, rest) = line.split_once("tests passed, ")?;
+        let (failed, rest) = rest.split_once(" tests failed out of ")?;
+        Some((
+            failed.trim().parse::<usize>().ok()?,
+            rest.trim().parse::<usize>().ok()?,
+        ))
+    })?;
+
+    let failures = output
+        .lines()
+        .skip_while(|line| !line.contains("The following tests FAILED:"))
+        .skip(1)
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(" - ")?;
+            let (name, status) = rest.rsplit_once(" (")?;
+            Some(TestFailure {
+                name: name.trim().to_string(),
+                message: format!(
+                    "Test {} failed with status: {}",
+                    name.trim(),
+                    status.trim_end_matches(')')
+                ),
+                details: None,
+            })
+        })
+        .collect();
+
+    Some((total, total.saturating_sub(failed), failed, failures))
+}
+
+/// Parse the summary of `meson test`
+///
+/// Format: one "Ok:", "Fail:", "Skipped:" ... line each, with a count.
+fn parse_meson_summary(output: &str) -> Option<(usize, usize, usize, Vec<TestFailure>)> {
+    let mut counts = HashMap::new();
+    for line in output.lines() {
+        let Some((label, count)) = line.split_once(':') else {
+            continue;
+        };
+        if let Ok(count) = count.trim().parse::<usize>() {
+            counts.insert(label.trim(), count);
+        }
+    }
+    let count = |label| counts.get(label).copied().unwrap_or(0);
+
+    if !counts.contains_key("Ok") || !counts.contains_key("Fail") {
+        return None;
+    }
+    let passed = count("Ok") + count("Expected Fail");
+    let failed = count("Fail") + count("Unexpected Pass") + count("Timeout");
+    let total = passed + failed + count("Skipped");
+
+    Some((total, passed, failed, vec![]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn detects_shipped_build_ninja() {
+        let source = TempDir::new().unwrap();
+        let ninja = NinjaBuildSystem::new();
+        assert!(!ninja.detect(source.path()).await.unwrap());
+
+        std::fs::write(source.path().join("build.ninja"), "").unwrap();
+        assert!(ninja.detect(source.path()).await.unwrap());
+        assert_eq!(
+            ninja.detection_markers(source.path()).await.unwrap(),
+            ["build.ninja"]
+        );
+    }
+
+    #[test]
+    fn parses_ctest_summary() {
+        let output = "\
+1/3 Test #1: parse ..........   Passed    0.01 sec
+2/3 Test #2: render .........***Failed    0.02 sec
+3/3 Test #3: io .............   Passed    0.01 sec
+
+67% tests passed, 1 tests failed out of 3
+
+The following tests FAILED:
+\t  2 - render (Failed)
+FAILED: CMakeFiles/test.util
+";
+        let (total, passed, failed, failures) =
+            NinjaBuildSystem::parse_test_output(output).unwrap();
+        assert_eq!((total, passed, failed), (3, 2, 1));
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].name, "render");
+        assert_eq!(
+            failures[0].message,
+            "Test render failed with status: Failed"
+        );
+    }
+
+    #[test]
+    fn parses_meson_summary() {
+        let output = "\
+1/4 basic        OK              0.01s
+2/4 slow         TIMEOUT        30.00s
+3/4 optional     SKIP            0.00s
+4/4 io           OK              0.02s
+
+Ok:                 2
+Expected Fail:      0
+Fail:               0
+Unexpected Pass:    0
+Skipped:            1
+Timeout:            1
+";
+        let (total, passed, failed, _) = NinjaBuildSystem::parse_test_output(output).unwrap();
+        assert_eq!((total, passed, failed), (4, 2, 1));
+
+        assert!(NinjaBuildSystem::parse_test_output("[1/1] Running tests\n").is_none());
+    }
+}
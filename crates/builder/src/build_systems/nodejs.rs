@@ -1,8 +1,11 @@
 //! Node.js build system implementation
 
-use super::{BuildSystem, BuildSystemConfig, BuildSystemContext, TestFailure, TestResults};
+use super::{
+    existing_markers, BuildSystem, BuildSystemConfig, BuildSystemContext, TestFailure, TestResults,
+};
 use async_trait::async_trait;
 use sps2_errors::{BuildError, Error};
+use sps2_events::{AppEvent, EventEmitter, GeneralEvent};
 use std::collections::HashMap;
 use std::path::Path;
 use tokio::fs;
@@ -39,16 +42,28 @@ impl NodeJsBuildSystem {
     }
 
     /// Detect which package manager to use
-    async fn detect_package_manager(&self, source_dir: &Path) -> Result<PackageManager, Error> {
-        // Check for lock files first (most reliable)
-        if source_dir.join("pnpm-lock.yaml").exists() {
-            return Ok(PackageManager::Pnpm);
-        }
-        if source_dir.join("yarn.lock").exists() {
-            return Ok(PackageManager::Yarn);
-        }
-        if source_dir.join("package-lock.json").exists() {
-            return Ok(PackageManager::Npm);
+    ///
+    /// A lockfile decides, since it pins the dependency tree; when several
+    /// are present the first in [`LOCKFILES`] order (pnpm, yarn, npm) wins
+    /// and a warning names the others. Without a lockfile the
+    /// `packageManager` field of package.json is consulted, then npm is used.
+    async fn detect_package_manager(
+        &self,
+        ctx: &BuildSystemContext,
+    ) -> Result<PackageManager, Error> {
+        let source_dir = &ctx.source_dir;
+        let lockfiles = present_lockfiles(source_dir);
+        if let Some((chosen_file, pm)) = lockfiles.first() {
+            if lockfiles.len() > 1 {
+                let ignored: Vec<&str> = lockfiles[1..].iter().map(|(file, _)| *file).collect();
+                ctx.env
+                    .emit(AppEvent::General(GeneralEvent::warning(format!(
+                        "Multiple lockfiles found; using {} from {chosen_file} and ignoring {}",
+                        pm.command(),
+                        ignored.join(", ")
+                    ))));
+            }
+            return Ok(pm.clone());
         }
 
         // Check for packageManager field in package.json
@@ -128,7 +143,10 @@ impl NodeJsBuildSystem {
                 if offline {
                     args.push("--offline".to_string());
                 }
-                args.push("--frozen-lockfile".to_string());
+                // Fail rather than update the lockfile, like npm ci
+                if has_lock_file {
+                    args.push("--frozen-lockfile".to_string());
+                }
                 args.push("--non-interactive".to_string());
                 args
             }
@@ -137,7 +155,9 @@ impl NodeJsBuildSystem {
                 if offline {
                     args.push("--offline".to_string());
                 }
-                args.push("--frozen-lockfile".to_string());
+                if has_lock_file {
+                    args.push("--frozen-lockfile".to_string());
+                }
                 args
             }
         }
@@ -361,13 +381,38 @@ enum PackageManager {
 }
 
 impl PackageManager {
-    fn command(&self) -> &str {
+    fn command(&self) -> &'static str {
         match self {
             Self::Npm => "npm",
             Self::Yarn => "yarn",
             Self::Pnpm => "pnpm",
         }
     }
+
+    fn lockfile(&self) -> &'static str {
+        match self {
+            Self::Npm => "package-lock.json",
+            Self::Yarn => "yarn.lock",
+            Self::Pnpm => "pnpm-lock.yaml",
+        }
+    }
+}
+
+/// Package managers in the order their lockfiles take precedence
+const LOCKFILES: [PackageManager; 3] = [
+    PackageManager::Pnpm,
+    PackageManager::Yarn,
+    PackageManager::Npm,
+];
+
+/// Lockfiles present in `source_dir` with their package managers, in
+/// precedence order
+fn present_lockfiles(source_dir: &Path) -> Vec<(&'static str, PackageManager)> {
+    LOCKFILES
+        .iter()
+        .filter(|pm| source_dir.join(pm.lockfile()).exists())
+        .map(|pm| (pm.lockfile(), pm.clone()))
+        .collect()
 }
 
 impl Default for NodeJsBuildSystem {
@@ -379,7 +424,11 @@ impl Default for NodeJsBuildSystem {
 #[async_trait]
 impl BuildSystem for NodeJsBuildSystem {
     async fn detect(&self, source_dir: &Path) -> Result<bool, Error> {
-        Ok(source_dir.join("package.json").exists())
+        Ok(!self.detection_markers(source_dir).await?.is_empty())
+    }
+
+    async fn detection_markers(&self, source_dir: &Path) -> Result<Vec<String>, Error> {
+        Ok(existing_markers(source_dir, &["package.json"]))
     }
 
     fn get_config_options(&self) -> BuildSystemConfig {
@@ -388,7 +437,7 @@ impl BuildSystem for NodeJsBuildSystem {
 
     async fn configure(&self, ctx: &BuildSystemContext, _args: &[String]) -> Result<(), Error> {
         // Detect package manager
-        let pm = self.detect_package_manager(&ctx.source_dir).await?;
+        let pm = self.detect_package_manager(ctx).await?;
 
         // Verify package manager is available
         // Verify package manager is available with merged env
@@ -440,11 +489,7 @@ impl BuildSystem for NodeJsBuildSystem {
         };
 
         // Check if lock file exists
-        let has_lock_file = match &pm {
-            PackageManager::Npm => ctx.source_dir.join("package-lock.json").exists(),
-            PackageManager::Yarn => ctx.source_dir.join("yarn.lock").exists(),
-            PackageManager::Pnpm => ctx.source_dir.join("pnpm-lock.yaml").exists(),
-        };
+        let has_lock_file = ctx.source_dir.join(pm.lockfile()).exists();
 
         // Check if package.json has any dependencies
         let package_json = ctx.source_dir.join("package.json");
@@ -553,6 +598,8 @@ impl BuildSystem for NodeJsBuildSystem {
                 duration: 0.0,
                 output: "No test script defined in package.json".to_string(),
                 failures: vec![],
+                timings: vec![],
+                executed: true,
             });
         }
 
@@ -585,6 +632,8 @@ impl BuildSystem for NodeJsBuildSystem {
             duration,
             output,
             failures,
+            timings: vec![],
+            executed: true,
         })
     }
 
@@ -663,3 +712,34 @@ async fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), Error> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn lockfiles_are_ranked_pnpm_yarn_npm() {
+        let temp = TempDir::new().unwrap();
+        assert!(present_lockfiles(temp.path()).is_empty());
+
+        std::fs::write(temp.path().join("package-lock.json"), "{}").unwrap();
+        std::fs::write(temp.path().join("yarn.lock"), "").unwrap();
+        let found: Vec<&str> = present_lockfiles(temp.path())
+            .iter()
+            .map(|(_, pm)| pm.command())
+            .collect();
+        assert_eq!(found, ["yarn", "npm"]);
+    }
+
+    #[test]
+    fn install_is_frozen_only_with_a_lockfile() {
+        let frozen = NodeJsBuildSystem::get_install_command(&PackageManager::Pnpm, false, true);
+        assert!(frozen.contains(&"--frozen-lockfile".to_string()));
+        let fresh = NodeJsBuildSystem::get_install_command(&PackageManager::Pnpm, false, false);
+        assert!(!fresh.contains(&"--frozen-lockfile".to_string()));
+
+        let npm = NodeJsBuildSystem::get_install_command(&PackageManager::Npm, false, true);
+        assert_eq!(npm[0], "ci");
+    }
+}
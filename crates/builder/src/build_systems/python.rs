@@ -1,6 +1,8 @@
 //! Python build system implementation with PEP 517/518 support
 
-use super::{BuildSystem, BuildSystemConfig, BuildSystemContext, TestFailure, TestResults};
+use super::{
+    existing_markers, BuildSystem, BuildSystemConfig, BuildSystemContext, TestFailure, TestResults,
+};
 use async_trait::async_trait;
 use sps2_errors::{BuildError, Error};
 use std::collections::HashMap;
@@ -43,6 +45,11 @@ impl PythonBuildSystem {
             // Read pyproject.toml to detect build backend
             let content = fs::read_to_string(&pyproject_path).await?;
 
+            // Projects managed by poetry or PDM are built with their own tool
+            if let Some(backend) = detect_project_manager(source_dir, &content) {
+                return Ok(backend);
+            }
+
             // Simple parsing - in production would use toml crate
             if content.contains("[build-system]") {
                 if content.contains("setuptools") {
@@ -76,11 +83,20 @@ impl PythonBuildSystem {
 
     /// Check if uv is available
     async fn check_uv_available(&self, ctx: &BuildSystemContext) -> Result<bool, Error> {
+        self.check_tool_available(ctx, "uv").await
+    }
+
+    /// Check if a tool answers `--version` in the build environment
+    async fn check_tool_available(
+        &self,
+        ctx: &BuildSystemContext,
+        program: &str,
+    ) -> Result<bool, Error> {
         let mut env = ctx.get_all_env_vars();
         env.extend(self.get_env_vars(ctx));
         let result = ctx
             .env
-            .execute_command_with_env("uv", &["--version"], None, &env, true)
+            .execute_command_with_env(program, &["--version"], None, &env, true)
             .await;
         Ok(result.map(|r| r.success).unwrap_or(false))
     }
@@ -204,6 +220,43 @@ impl PythonBuildSystem {
         self.find_wheel_in_dir(&wheel_dir).await
     }
 
+    /// Build wheel with the project's own manager (`poetry build` or
+    /// `pdm build`)
+    async fn build_wheel_with_manager(
+        &self,
+        ctx: &BuildSystemContext,
+        backend: BuildBackend,
+    ) -> Result<PathBuf, Error> {
+        let wheel_dir = ctx.build_dir.join("dist");
+        fs::create_dir_all(&wheel_dir).await?;
+        let wheel_dir_str = wheel_dir.display().to_string();
+
+        let (program, args) = match backend {
+            BuildBackend::Poetry => (
+                "poetry",
+                vec!["build", "--format", "wheel", "--output", &wheel_dir_str],
+            ),
+            BuildBackend::Pdm => ("pdm", vec!["build", "--no-sdist", "--dest", &wheel_dir_str]),
+            _ => return self.build_wheel_pep517(ctx).await,
+        };
+
+        let mut env = ctx.get_all_env_vars();
+        env.extend(self.get_env_vars(ctx));
+        let result = ctx
+            .env
+            .execute_command_with_env(program, &args, Some(&ctx.source_dir), &env, false)
+            .await?;
+
+        if !result.success {
+            return Err(BuildError::CompilationFailed {
+                message: format!("Failed to build wheel with {program}: {}", result.stderr),
+            }
+            .into());
+        }
+
+        self.find_wheel_in_dir(&wheel_dir).await
+    }
+
     /// Build wheel using PEP 517
     async fn build_wheel_pep517(&self, ctx: &BuildSystemContext) -> Result<PathBuf, Error> {
         let wheel_dir = ctx.build_dir.join("dist");
@@ -346,7 +399,7 @@ impl PythonBuildSystem {
 }
 
 /// Python build backend types
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum BuildBackend {
     SetupPy,    // Legacy setup.py
     Setuptools, // Modern setuptools with pyproject.toml
@@ -358,6 +411,67 @@ enum BuildBackend {
     Pep517,     // Generic PEP 517 backend
 }
 
+impl BuildBackend {
+    const ALL: [Self; 8] = [
+        Self::SetupPy,
+        Self::Setuptools,
+        Self::Poetry,
+        Self::Flit,
+        Self::Hatch,
+        Self::Pdm,
+        Self::Maturin,
+        Self::Pep517,
+    ];
+
+    /// Name stored in the build metadata between phases
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::SetupPy => "setup.py",
+            Self::Setuptools => "setuptools",
+            Self::Poetry => "poetry",
+            Self::Flit => "flit",
+            Self::Hatch => "hatch",
+            Self::Pdm => "pdm",
+            Self::Maturin => "maturin",
+            Self::Pep517 => "pep517",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|backend| backend.as_str() == name)
+    }
+
+    /// Project manager that builds the wheel itself, if any
+    fn manager(self) -> Option<&'static str> {
+        match self {
+            Self::Poetry => Some("poetry"),
+            Self::Pdm => Some("pdm"),
+            _ => None,
+        }
+    }
+}
+
+/// Detect a poetry or PDM managed project from its lockfile or the
+/// `[tool.poetry]`/`[tool.pdm]` tables in pyproject.toml
+fn detect_project_manager(source_dir: &Path, pyproject: &str) -> Option<BuildBackend> {
+    let has_table = |table: &str| {
+        pyproject
+            .lines()
+            .map(str::trim)
+            .any(|line| line == format!("[{table}]") || line.starts_with(&format!("[{table}.")))
+    };
+
+    if source_dir.join("poetry.lock").exists() || has_table("tool.poetry") {
+        Some(BuildBackend::Poetry)
+    } else if source_dir.join("pdm.lock").exists() || has_table("tool.pdm") {
+        Some(BuildBackend::Pdm)
+    } else {
+        None
+    }
+}
+
 impl PythonBuildSystem {
     /// Generate lockfile with graceful fallback from uv to pip-compile
     async fn generate_lockfile_with_fallback(
@@ -623,22 +737,15 @@ impl Default for PythonBuildSystem {
 #[async_trait]
 impl BuildSystem for PythonBuildSystem {
     async fn detect(&self, source_dir: &Path) -> Result<bool, Error> {
-        // Check for pyproject.toml (PEP 517/518)
-        if source_dir.join("pyproject.toml").exists() {
-            return Ok(true);
-        }
-
-        // Check for setup.py (legacy)
-        if source_dir.join("setup.py").exists() {
-            return Ok(true);
-        }
-
-        // Check for setup.cfg
-        if source_dir.join("setup.cfg").exists() {
-            return Ok(true);
-        }
+        Ok(!self.detection_markers(source_dir).await?.is_empty())
+    }
 
-        Ok(false)
+    async fn detection_markers(&self, source_dir: &Path) -> Result<Vec<String>, Error> {
+        // PEP 517/518 projects, legacy setup.py, or setup.cfg
+        Ok(existing_markers(
+            source_dir,
+            &["pyproject.toml", "setup.py", "setup.cfg"],
+        ))
     }
 
     fn get_config_options(&self) -> BuildSystemConfig {
@@ -675,7 +782,10 @@ impl BuildSystem for PythonBuildSystem {
 
         // Store detected backend and venv path in environment for later use
         if let Ok(mut extra_env) = ctx.extra_env.write() {
-            extra_env.insert("PYTHON_BUILD_BACKEND".to_string(), format!("{backend:?}"));
+            extra_env.insert(
+                "PYTHON_BUILD_BACKEND".to_string(),
+                backend.as_str().to_string(),
+            );
             extra_env.insert(
                 "PYTHON_VENV_PATH".to_string(),
                 venv_path.display().to_string(),
@@ -686,8 +796,25 @@ impl BuildSystem for PythonBuildSystem {
     }
 
     async fn build(&self, ctx: &BuildSystemContext, _args: &[String]) -> Result<(), Error> {
-        // Try uv first if available, with graceful fallback to PEP 517
-        let wheel_path = if self.check_uv_available(ctx).await? {
+        let backend = ctx.extra_env.read().ok().and_then(|extra_env| {
+            extra_env
+                .get("PYTHON_BUILD_BACKEND")
+                .and_then(|name| BuildBackend::from_name(name))
+        });
+
+        // Poetry and PDM projects are built by their own tool when it is
+        // installed; everything else tries uv first, with graceful fallback
+        // to PEP 517
+        let manager_backend = match backend {
+            Some(backend) => match backend.manager() {
+                Some(program) if self.check_tool_available(ctx, program).await? => Some(backend),
+                _ => None,
+            },
+            None => None,
+        };
+        let wheel_path = if let Some(backend) = manager_backend {
+            self.build_wheel_with_manager(ctx, backend).await?
+        } else if self.check_uv_available(ctx).await? {
             match self.build_wheel_uv(ctx).await {
                 Ok(path) => path,
                 Err(_) => {
@@ -749,9 +876,10 @@ impl BuildSystem for PythonBuildSystem {
             let mut args = vec!["-v".to_string(), "--tb=short".to_string()];
 
             // Add parallel execution if supported
-            if ctx.jobs > 1 {
+            let jobs = ctx.jobs_for(self.name());
+            if jobs > 1 {
                 args.push("-n".to_string());
-                jobs_str = ctx.jobs.to_string();
+                jobs_str = jobs.to_string();
                 args.push(jobs_str.clone());
             }
             test_args = args;
@@ -813,6 +941,8 @@ impl BuildSystem for PythonBuildSystem {
             duration,
             output,
             failures,
+            timings: vec![],
+            executed: true,
         })
     }
 
@@ -993,3 +1123,45 @@ impl PythonBuildSystem {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn detects_poetry_and_pdm_projects() {
+        let temp = TempDir::new().unwrap();
+        let poetry = "[tool.poetry]\nname = \"demo\"\n";
+        assert!(matches!(
+            detect_project_manager(temp.path(), poetry),
+            Some(BuildBackend::Poetry)
+        ));
+        let pdm = "[project]\nname = \"demo\"\n\n[tool.pdm.build]\nincludes = []\n";
+        assert!(matches!(
+            detect_project_manager(temp.path(), pdm),
+            Some(BuildBackend::Pdm)
+        ));
+
+        // A plain PEP 517 project is left to the generic path
+        let setuptools = "[build-system]\nrequires = [\"setuptools\"]\n";
+        assert!(detect_project_manager(temp.path(), setuptools).is_none());
+
+        std::fs::write(temp.path().join("poetry.lock"), "").unwrap();
+        assert!(matches!(
+            detect_project_manager(temp.path(), setuptools),
+            Some(BuildBackend::Poetry)
+        ));
+    }
+
+    #[test]
+    fn backend_names_round_trip() {
+        for backend in BuildBackend::ALL {
+            assert_eq!(BuildBackend::from_name(backend.as_str()), Some(backend));
+        }
+        assert_eq!(BuildBackend::from_name("Poetry"), None);
+        assert_eq!(BuildBackend::Poetry.manager(), Some("poetry"));
+        assert_eq!(BuildBackend::Pdm.manager(), Some("pdm"));
+        assert_eq!(BuildBackend::Hatch.manager(), None);
+    }
+}
@@ -5,6 +5,7 @@
 //! configuration system in `sps2_config`. All configuration types are now
 //! centralized in the config crate.
 
+use crate::build_systems::BuildSystemRegistry;
 use sps2_config::builder::{
     BuildSettings, BuilderConfig, CacheSettings, CompressionSettings, EnvironmentSettings,
     PackagingSettings, PerformanceSettings, SbomSettings, SecuritySettings, ShellExpansionPolicy,
@@ -25,6 +26,8 @@ pub struct BuildConfig {
     pub resources: Arc<ResourceManager>,
     /// sps2 system configuration (for command validation)
     pub sps2_config: Option<sps2_config::Config>,
+    /// Build systems available to recipes
+    pub build_systems: Arc<BuildSystemRegistry>,
 }
 
 impl BuildConfig {
@@ -35,6 +38,7 @@ impl BuildConfig {
             config,
             resources: Arc::new(ResourceManager::default()),
             sps2_config: None,
+            build_systems: Arc::new(BuildSystemRegistry::new()),
         }
     }
 
@@ -45,6 +49,7 @@ impl BuildConfig {
             config,
             resources,
             sps2_config: None,
+            build_systems: Arc::new(BuildSystemRegistry::new()),
         }
     }
 
@@ -55,6 +60,13 @@ impl BuildConfig {
         self
     }
 
+    /// Use a custom build-system registry
+    #[must_use]
+    pub fn with_build_systems(mut self, build_systems: BuildSystemRegistry) -> Self {
+        self.build_systems = Arc::new(build_systems);
+        self
+    }
+
     /// Get build settings
     #[must_use]
     pub fn build_settings(&self) -> &BuildSettings {
@@ -143,12 +155,30 @@ impl BuildConfig {
         }
     }
 
+    /// Get parallel job overrides keyed by build system name
+    #[must_use]
+    pub fn system_jobs(&self) -> &std::collections::HashMap<String, usize> {
+        &self.config.build.system_jobs
+    }
+
+    /// Get the post-validation patcher selection
+    #[must_use]
+    pub fn patchers(&self) -> &sps2_types::PatcherSelection {
+        &self.config.build.patchers
+    }
+
     /// Get build root directory
     #[must_use]
     pub fn build_root(&self) -> &std::path::Path {
         &self.config.build.build_root
     }
 
+    /// Get the root staging directories are created under, if configured
+    #[must_use]
+    pub fn staging_root(&self) -> Option<&std::path::Path> {
+        self.config.build.staging_root.as_deref()
+    }
+
     /// Check if strict validation is enabled
     #[must_use]
     pub fn is_strict_validation(&self) -> bool {
@@ -234,6 +264,13 @@ impl BuildConfig {
         self
     }
 
+    /// Set parallel build jobs for one build system
+    #[must_use]
+    pub fn with_jobs_for(mut self, system: impl Into<String>, jobs: usize) -> Self {
+        self.config.build.system_jobs.insert(system.into(), jobs);
+        self
+    }
+
     /// Set compression configuration
     #[must_use]
     pub fn with_compression_config(mut self, compression_config: CompressionSettings) -> Self {
@@ -287,6 +324,13 @@ impl BuildConfig {
         self
     }
 
+    /// Set staging root directory
+    #[must_use]
+    pub fn with_staging_root(mut self, path: std::path::PathBuf) -> Self {
+        self.config.build.staging_root = Some(path);
+        self
+    }
+
     /// Set isolation level (deprecated - isolation comes from recipe)
     #[must_use]
     pub fn with_isolation_level(self, _level: &str) -> Self {
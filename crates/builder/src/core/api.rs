@@ -1,21 +1,179 @@
 //! Builder API for Starlark recipes
 
+use crate::build_systems::{has_prefix_arg, BuildSystem, BuildSystemRegistry, TestResults};
 use crate::environment::IsolationLevel;
 use crate::{BuildCommandResult, BuildEnvironment};
 use md5::{Digest, Md5};
 use sha2::{Digest as Sha2Digest, Sha256};
-use sps2_errors::{BuildError, Error};
+use sps2_errors::{BuildError, Error, NetworkError, UserFacingError};
 use sps2_hash::Hash;
-use sps2_net::{NetClient, NetConfig};
+use sps2_net::{partial_path, NetClient, NetConfig};
 use sps2_platform::{PlatformContext, PlatformManager};
 use sps2_types::RpathStyle;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
 
-use sps2_events::{AppEvent, EventEmitter, GeneralEvent};
+use sps2_events::{AppEvent, EventEmitter, EventSender, GeneralEvent};
 use sps2_resources::ResourceManager;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Download attempts made by [`BuilderApi::fetch`] before giving up
+const FETCH_ATTEMPTS: u32 = 3;
+
+/// Delay before the first fetch retry, doubled for each one after it
+const FETCH_RETRY_BASE: Duration = Duration::from_millis(500);
+
+/// Build metadata key naming the `CMake` preset the build was configured with
+const CMAKE_PRESET_KEY: &str = "CMAKE_PRESET";
+
+/// Options for [`BuilderApi::apply_patch_with_options`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatchOptions {
+    /// Leading path components stripped from file names in the patch (`-p`)
+    pub strip: u8,
+    /// Context lines that may be ignored when placing a hunk (`--fuzz`);
+    /// `None` keeps patch's default
+    pub fuzz: Option<u8>,
+    /// Only check that the patch applies, without changing any files
+    pub dry_run: bool,
+}
+
+impl Default for PatchOptions {
+    fn default() -> Self {
+        Self {
+            strip: 1,
+            fuzz: None,
+            dry_run: false,
+        }
+    }
+}
+
+impl PatchOptions {
+    /// Arguments for `patch` applying `patch_path` with these options
+    fn patch_args(self, patch_path: &Path) -> Vec<String> {
+        let mut args = vec![format!("-p{}", self.strip)];
+        if let Some(fuzz) = self.fuzz {
+            args.push(format!("--fuzz={fuzz}"));
+        }
+        if self.dry_run {
+            args.push("--dry-run".to_string());
+        }
+        args.push("-i".to_string());
+        args.push(patch_path.display().to_string());
+        args
+    }
+}
+
+/// Options for [`BuilderApi::autotools_with_options`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AutotoolsOptions {
+    /// Configure and build in a `build/` directory instead of the source
+    /// tree, for projects that refuse in-source builds
+    pub out_of_source: bool,
+}
+
+/// Feature and profile selection for [`BuilderApi::cargo_with_options`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CargoOptions {
+    /// Features to enable (`--features`)
+    pub features: Vec<String>,
+    /// Build without the package's default features (`--no-default-features`)
+    pub no_default_features: bool,
+    /// Enable every feature (`--all-features`); excludes `features`
+    pub all_features: bool,
+    /// Cargo profile to build with (`--profile`); `None` builds in release
+    pub profile: Option<String>,
+}
+
+impl CargoOptions {
+    /// Cargo arguments selecting these features and profile
+    fn cargo_args(&self) -> Result<Vec<String>, Error> {
+        if self.all_features && !self.features.is_empty() {
+            return Err(BuildError::RecipeError {
+                message: "cargo options cannot combine all_features with an explicit feature list"
+                    .to_string(),
+            }
+            .into());
+        }
+
+        let mut args = Vec::new();
+        if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.join(","));
+        }
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+        if self.all_features {
+            args.push("--all-features".to_string());
+        }
+        if let Some(profile) = &self.profile {
+            args.push("--profile".to_string());
+            args.push(profile.clone());
+        }
+        Ok(args)
+    }
+}
+
+/// Build tags and linker flags for [`BuilderApi::go_with_options`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoOptions {
+    /// Build tags (`-tags tag1,tag2`)
+    pub tags: Vec<String>,
+    /// Linker flags, joined with spaces into a single `-ldflags` argument;
+    /// `-s -w` is used when empty
+    pub ldflags: Vec<String>,
+    /// Strip file system paths from the binary (`-trimpath`) so builds are
+    /// reproducible across build directories
+    pub trimpath: bool,
+}
+
+impl Default for GoOptions {
+    fn default() -> Self {
+        Self {
+            tags: Vec::new(),
+            ldflags: Vec::new(),
+            trimpath: true,
+        }
+    }
+}
+
+impl GoOptions {
+    /// Go subcommands that accept build flags
+    const BUILD_COMMANDS: [&'static str; 3] = ["build", "install", "test"];
+
+    /// `args` with these options inserted after the go subcommand, if any
+    ///
+    /// Other subcommands such as `go mod` reject build flags, so their
+    /// arguments are returned unchanged.
+    fn apply_to(&self, args: &[String]) -> Vec<String> {
+        let command = args.first().filter(|arg| !arg.starts_with('-'));
+        if command.is_some_and(|command| !Self::BUILD_COMMANDS.contains(&command.as_str())) {
+            return args.to_vec();
+        }
+
+        let mut flags = Vec::new();
+        if !self.tags.is_empty() {
+            flags.push("-tags".to_string());
+            flags.push(self.tags.join(","));
+        }
+        if !self.ldflags.is_empty() {
+            flags.push(format!("-ldflags={}", self.ldflags.join(" ")));
+        }
+        if self.trimpath {
+            flags.push("-trimpath".to_string());
+        }
+
+        let command_len = usize::from(command.is_some());
+        let mut combined = args[..command_len].to_vec();
+        combined.extend(flags);
+        combined.extend_from_slice(&args[command_len..]);
+        combined
+    }
+}
 
 /// Builder API exposed to Starlark recipes
 #[derive(Clone)]
@@ -40,6 +198,18 @@ pub struct BuilderApi {
     explicit_isolation_level: Option<IsolationLevel>,
     /// Resource manager
     resources: Arc<ResourceManager>,
+    /// Build systems available to the build methods
+    build_systems: Arc<BuildSystemRegistry>,
+    /// Event sender for progress and retry reporting
+    event_sender: Option<EventSender>,
+    /// Delay before the first fetch retry, doubled for each one after it
+    fetch_retry_base: Duration,
+}
+
+impl EventEmitter for BuilderApi {
+    fn event_sender(&self) -> Option<&EventSender> {
+        self.event_sender.as_ref()
+    }
 }
 
 impl BuilderApi {
@@ -65,9 +235,57 @@ impl BuilderApi {
             build_metadata: HashMap::new(),
             explicit_isolation_level: None,
             resources,
+            build_systems: Arc::new(BuildSystemRegistry::new()),
+            event_sender: None,
+            fetch_retry_base: FETCH_RETRY_BASE,
         })
     }
 
+    /// Report events such as fetch retries through `event_sender`
+    pub fn set_event_sender(&mut self, event_sender: Option<EventSender>) {
+        self.event_sender = event_sender;
+    }
+
+    /// Fetch sources through `net_client` instead of a default client
+    pub fn set_net_client(&mut self, net_client: NetClient) {
+        self.net_client = net_client;
+    }
+
+    /// Use a custom build-system registry for the build methods
+    pub fn set_build_systems(&mut self, build_systems: Arc<BuildSystemRegistry>) {
+        self.build_systems = build_systems;
+    }
+
+    /// Look up a registered build system by name
+    fn registered_build_system(&self, name: &str) -> Result<&dyn BuildSystem, Error> {
+        self.build_systems.get(name).ok_or_else(|| {
+            BuildError::UnknownBuildSystem {
+                name: name.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Run a build system's test phase and record the results
+    ///
+    /// The phase is skipped when tests are disabled; the recorded results
+    /// then report that no tests ran.
+    async fn run_test_phase(
+        system: &dyn BuildSystem,
+        ctx: &crate::build_systems::BuildSystemContext,
+        env: &mut BuildEnvironment,
+    ) -> Result<(), Error> {
+        let results = system.run_tests(ctx).await?;
+        let failed = results.executed && !results.all_passed();
+        let (passed, total) = (results.passed, results.total);
+        env.record_test_results(results);
+
+        if failed {
+            return Err(BuildError::TestsFailed { passed, total }.into());
+        }
+        Ok(())
+    }
+
     /// Allow network access during build
     #[must_use]
     pub fn allow_network(&mut self, allow: bool) -> &mut Self {
@@ -89,6 +307,21 @@ impl BuilderApi {
     /// - The URL is invalid
     /// - The download fails
     pub async fn fetch(&mut self, url: &str) -> Result<PathBuf, Error> {
+        self.fetch_hashed(url, None).await
+    }
+
+    /// Download `url` unless it was fetched before, checking it against
+    /// `expected` as it is written
+    ///
+    /// A fresh download is written to a `.partial` sibling and only renamed
+    /// to its final name once it is complete and its hash matches, so an
+    /// interrupted or corrupt download never takes the place of a good one.
+    /// A download reused from an earlier fetch is returned unchecked.
+    async fn fetch_hashed(
+        &mut self,
+        url: &str,
+        expected: Option<(SourceHashAlgorithm, &str)>,
+    ) -> Result<PathBuf, Error> {
         // Fetch operations always have network access - they're source fetching, not build operations
 
         // Acquire a download permit
@@ -99,35 +332,178 @@ impl BuilderApi {
             return Ok(path.clone());
         }
 
-        // Extract filename from URL
+        let download_path = self.download_path(url)?;
+        let partial = partial_path(&download_path);
+        let digest = self
+            .download_with_retries(url, &partial, expected.map(|(algorithm, _)| algorithm))
+            .await;
+        let digest = match digest {
+            Ok(digest) => digest,
+            Err(err) => {
+                let _ = fs::remove_file(&partial).await;
+                return Err(err);
+            }
+        };
+
+        if let (Some((algorithm, expected)), Some(actual)) = (expected, digest) {
+            if !actual.eq_ignore_ascii_case(expected) {
+                fs::remove_file(&partial).await?;
+                return Err(hash_mismatch(&download_path, algorithm, expected, &actual));
+            }
+        }
+        fs::rename(&partial, &download_path).await?;
+
+        self.downloads
+            .insert(url.to_string(), download_path.clone());
+
+        // Note: Extraction is handled separately by extract_downloads_to() method
+
+        Ok(download_path)
+    }
+
+    /// Where a download of `url` is stored, named after its last path segment
+    fn download_path(&self, url: &str) -> Result<PathBuf, Error> {
         let filename = url
             .split('/')
             .next_back()
             .ok_or_else(|| BuildError::InvalidUrl {
                 url: url.to_string(),
             })?;
+        Ok(self.working_dir.join(filename))
+    }
 
-        let download_path = self.working_dir.join(filename);
+    /// Download a file from the first of several mirrors that serves it
+    /// with the expected hash
+    ///
+    /// Mirrors are tried in order. A mirror that fails to download or serves
+    /// content with the wrong hash is skipped with a warning. The result is
+    /// stored and cached under the first URL whichever mirror served it.
+    /// `expected_hash` takes the same forms as in [`Self::fetch_with_hash`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No URLs are given or the hash prefix is unsupported
+    /// - Every mirror fails to download or serves the wrong content
+    pub async fn fetch_mirrors(
+        &mut self,
+        urls: &[String],
+        expected_hash: &str,
+    ) -> Result<PathBuf, Error> {
+        let Some(primary) = urls.first() else {
+            return Err(BuildError::RecipeError {
+                message: "fetch_mirrors requires at least one URL".to_string(),
+            }
+            .into());
+        };
+        if let Some(path) = self.downloads.get(primary) {
+            return Ok(path.clone());
+        }
 
-        // Download file using the download module
-        // For builder, we don't have an event sender, so we'll use the client directly
-        let response = self.net_client.get(url).await?;
-        let bytes = response
-            .bytes()
-            .await
-            .map_err(|_e| BuildError::FetchFailed {
-                url: url.to_string(),
-            })?;
-        fs::write(&download_path, &bytes).await?;
+        let (algorithm, expected) = SourceHashAlgorithm::parse(expected_hash)?;
+        let primary_path = self.download_path(primary)?;
 
-        // No hash verification - files are downloaded without validation
+        for url in urls {
+            match self.fetch_verified(url, algorithm, expected).await {
+                Ok(path) => {
+                    if url != primary {
+                        self.downloads.remove(url);
+                        if path != primary_path {
+                            fs::rename(&path, &primary_path).await?;
+                        }
+                    }
+                    self.downloads.insert(primary.clone(), primary_path.clone());
+                    return Ok(primary_path);
+                }
+                Err(err) => {
+                    self.emit_warning_with_context(
+                        format!("Skipping mirror {url}"),
+                        err.to_string(),
+                    );
+                }
+            }
+        }
 
-        self.downloads
-            .insert(url.to_string(), download_path.clone());
+        Err(BuildError::FetchFailed {
+            url: primary.clone(),
+        }
+        .into())
+    }
 
-        // Note: Extraction is handled separately by extract_downloads_to() method
+    /// Download `url`, retrying transient failures with exponential backoff
+    ///
+    /// Timeouts, dropped connections and 5xx responses are retried up to
+    /// [`FETCH_ATTEMPTS`] times in total; any other failure, such as a 404,
+    /// is returned immediately. Each attempt is a single request, so this is
+    /// the only layer that retries.
+    async fn download_with_retries(
+        &self,
+        url: &str,
+        path: &Path,
+        algorithm: Option<SourceHashAlgorithm>,
+    ) -> Result<Option<String>, Error> {
+        let mut attempt = 1;
+        loop {
+            match self.download_once(url, path, algorithm).await {
+                Ok(digest) => return Ok(digest),
+                Err(err) if attempt < FETCH_ATTEMPTS && is_transient_fetch_error(&err) => {
+                    let delay = fetch_retry_delay(self.fetch_retry_base, attempt);
+                    self.emit_debug_with_context(
+                        format!(
+                            "Fetch attempt {attempt}/{FETCH_ATTEMPTS} for {url} failed: {err}; \
+                             retrying in {}ms",
+                            delay.as_millis()
+                        ),
+                        HashMap::from([
+                            ("url".to_string(), url.to_string()),
+                            ("attempt".to_string(), attempt.to_string()),
+                        ]),
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
 
-        Ok(download_path)
+    /// Make a single download attempt, treating non-success statuses as errors
+    ///
+    /// The body is streamed to `path` and fed to the hasher for `algorithm`
+    /// chunk by chunk, so the digest is known without reading the file back.
+    async fn download_once(
+        &self,
+        url: &str,
+        path: &Path,
+        algorithm: Option<SourceHashAlgorithm>,
+    ) -> Result<Option<String>, Error> {
+        use futures::StreamExt;
+
+        let response = self.net_client.get_once(url).await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(NetworkError::HttpError {
+                status: status.as_u16(),
+                message: status.to_string(),
+            }
+            .into());
+        }
+
+        let mut file = fs::File::create(path).await?;
+        let mut hasher = algorithm.map(SourceHashAlgorithm::hasher);
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|_e| BuildError::FetchFailed {
+                url: url.to_string(),
+            })?;
+            if let Some(hasher) = &mut hasher {
+                hasher.update(&chunk);
+            }
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+
+        Ok(hasher.map(SourceHasher::finalize))
     }
 
     /// Download and verify a file with MD5 hash
@@ -177,29 +553,8 @@ impl BuilderApi {
         url: &str,
         expected_sha256: &str,
     ) -> Result<PathBuf, Error> {
-        let download_path = self.fetch(url).await?;
-
-        // Verify SHA256 hash
-        let bytes = tokio::fs::read(&download_path).await?;
-        let mut hasher = Sha256::new();
-        Sha2Digest::update(&mut hasher, &bytes);
-        let actual_sha256 = format!("{:x}", hasher.finalize());
-
-        if actual_sha256.to_lowercase() != expected_sha256.to_lowercase() {
-            tokio::fs::remove_file(&download_path).await?;
-            let filename = download_path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown");
-            return Err(BuildError::HashMismatch {
-                file: filename.to_string(),
-                expected: expected_sha256.to_string(),
-                actual: actual_sha256,
-            }
-            .into());
-        }
-
-        Ok(download_path)
+        self.fetch_verified(url, SourceHashAlgorithm::Sha256, expected_sha256)
+            .await
     }
 
     /// Download and verify a file with BLAKE3 hash
@@ -215,24 +570,78 @@ impl BuilderApi {
         url: &str,
         expected_blake3: &str,
     ) -> Result<PathBuf, Error> {
-        let download_path = self.fetch(url).await?;
+        self.fetch_verified(url, SourceHashAlgorithm::Blake3, expected_blake3)
+            .await
+    }
 
-        // Verify BLAKE3 hash specifically for download verification
-        let actual_hash = Hash::blake3_hash_file(&download_path).await?;
-        let actual_blake3 = actual_hash.to_hex();
+    /// Download a file, verifying it if `expected_hash` is given, without
+    /// extracting it
+    ///
+    /// Source fetch steps extract archives as soon as they are downloaded;
+    /// this returns the archive itself for recipes that repackage it or hand
+    /// it to another tool. `expected_hash` takes the same forms as in
+    /// [`Self::fetch_with_hash`]. The download is recorded like any other, so
+    /// a later [`Self::extract_downloads`] still extracts it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The hash prefix names an unsupported algorithm
+    /// - The URL is invalid
+    /// - The download fails
+    /// - The file hash doesn't match the expected hash
+    pub async fn fetch_raw(
+        &mut self,
+        url: &str,
+        expected_hash: Option<&str>,
+    ) -> Result<PathBuf, Error> {
+        match expected_hash {
+            Some(expected_hash) => self.fetch_with_hash(url, expected_hash).await,
+            None => self.fetch(url).await,
+        }
+    }
 
-        if actual_blake3.to_lowercase() != expected_blake3.to_lowercase() {
+    /// Download and verify a file against a possibly prefixed hash
+    ///
+    /// `expected_hash` may name its algorithm, as in `sha256:<hex>` or
+    /// `blake3:<hex>`; a bare hex digest is taken to be BLAKE3.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The hash prefix names an unsupported algorithm
+    /// - The URL is invalid
+    /// - The download fails
+    /// - The file hash doesn't match the expected hash
+    pub async fn fetch_with_hash(
+        &mut self,
+        url: &str,
+        expected_hash: &str,
+    ) -> Result<PathBuf, Error> {
+        let (algorithm, expected) = SourceHashAlgorithm::parse(expected_hash)?;
+        self.fetch_verified(url, algorithm, expected).await
+    }
+
+    /// Download `url` and check it against `expected` under `algorithm`
+    ///
+    /// On mismatch the download is removed and both digests are reported
+    /// with the algorithm prefix.
+    async fn fetch_verified(
+        &mut self,
+        url: &str,
+        algorithm: SourceHashAlgorithm,
+        expected: &str,
+    ) -> Result<PathBuf, Error> {
+        // Only a download reused from an earlier fetch is read back
+        let Some(download_path) = self.downloads.get(url).cloned() else {
+            return self.fetch_hashed(url, Some((algorithm, expected))).await;
+        };
+
+        let actual = algorithm.hash_file(&download_path).await?;
+        if !actual.eq_ignore_ascii_case(expected) {
             tokio::fs::remove_file(&download_path).await?;
-            let filename = download_path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown");
-            return Err(BuildError::HashMismatch {
-                file: filename.to_string(),
-                expected: expected_blake3.to_string(),
-                actual: actual_blake3,
-            }
-            .into());
+            self.downloads.remove(url);
+            return Err(hash_mismatch(&download_path, algorithm, expected, &actual));
         }
 
         Ok(download_path)
@@ -240,13 +649,22 @@ impl BuilderApi {
 
     /// Clone a git repository
     ///
+    /// `depth` limits the history fetched; `None` clones everything. A full
+    /// 40-character commit SHA as `ref_` is checked out after cloning, and a
+    /// shallow clone that cannot reach it is deepened to the full history.
+    ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - Network access is disabled
     /// - The URL is invalid
-    /// - The git clone fails
-    pub async fn git(&mut self, url: &str, ref_: &str) -> Result<PathBuf, Error> {
+    /// - The git clone or checkout fails
+    pub async fn git(
+        &mut self,
+        url: &str,
+        ref_: &str,
+        depth: Option<u32>,
+    ) -> Result<PathBuf, Error> {
         // Git operations always have network access - they're source fetching, not build operations
 
         // Check if already cloned
@@ -264,39 +682,54 @@ impl BuilderApi {
             })?;
 
         let clone_path = self.working_dir.join(repo_name);
+        let commit = is_commit_sha(ref_);
+
+        // Clone using git command (better compatibility than git2 crate)
+        let depth_arg = depth.map(|depth| depth.to_string());
+        let mut args = vec!["clone"];
+        if let Some(depth) = &depth_arg {
+            args.extend(["--depth", depth.as_str()]);
+        }
+        // HEAD and commit SHAs clone the default branch; --branch only
+        // accepts branch and tag names
+        if ref_ != "HEAD" && !commit {
+            args.extend(["--branch", ref_]);
+        }
+        let clone_target = clone_path.display().to_string();
+        args.extend([url, clone_target.as_str()]);
+        self.run_git(url, &args, &self.working_dir).await?;
+
+        if commit {
+            if let Err(err) = self.run_git(url, &["checkout", ref_], &clone_path).await {
+                if depth.is_none() {
+                    return Err(err);
+                }
+                // The commit is outside the shallow history
+                self.run_git(url, &["fetch", "--unshallow", "--tags"], &clone_path)
+                    .await?;
+                self.run_git(url, &["checkout", ref_], &clone_path).await?;
+            }
+        }
 
+        self.downloads.insert(url.to_string(), clone_path.clone());
+
+        // Update working directory to the cloned path so subsequent operations
+        // (like cargo build) work in the correct directory
+        self.set_working_dir(clone_path.clone());
+
+        Ok(clone_path)
+    }
+
+    /// Run a git command in `dir`, surfacing its stderr on failure
+    async fn run_git(&self, url: &str, args: &[&str], dir: &Path) -> Result<(), Error> {
         // Use platform abstraction for process execution
         let platform = PlatformManager::instance().platform();
         let context = PlatformContext::new(None);
 
-        // Clone using git command (better compatibility than git2 crate)
-        let output = if ref_ == "HEAD" {
-            // For HEAD, don't use --branch flag
-            let mut cmd = platform.process().create_command("git");
-            cmd.args([
-                "clone",
-                "--depth",
-                "1",
-                url,
-                &clone_path.display().to_string(),
-            ]);
-            cmd.current_dir(&self.working_dir);
-            platform.process().execute_command(&context, cmd).await?
-        } else {
-            // For specific branches/tags, use --branch
-            let mut cmd = platform.process().create_command("git");
-            cmd.args([
-                "clone",
-                "--depth",
-                "1",
-                "--branch",
-                ref_,
-                url,
-                &clone_path.display().to_string(),
-            ]);
-            cmd.current_dir(&self.working_dir);
-            platform.process().execute_command(&context, cmd).await?
-        };
+        let mut cmd = platform.process().create_command("git");
+        cmd.args(args);
+        cmd.current_dir(dir);
+        let output = platform.process().execute_command(&context, cmd).await?;
 
         if !output.status.success() {
             return Err(BuildError::GitCloneFailed {
@@ -308,14 +741,7 @@ impl BuilderApi {
             }
             .into());
         }
-
-        self.downloads.insert(url.to_string(), clone_path.clone());
-
-        // Update working directory to the cloned path so subsequent operations
-        // (like cargo build) work in the correct directory
-        self.set_working_dir(clone_path.clone());
-
-        Ok(clone_path)
+        Ok(())
     }
 
     /// Apply a patch file
@@ -328,12 +754,46 @@ impl BuilderApi {
         patch_path: &Path,
         env: &BuildEnvironment,
     ) -> Result<BuildCommandResult, Error> {
-        env.execute_command(
-            "patch",
-            &["-p1", "-i", &patch_path.display().to_string()],
-            Some(&self.working_dir),
-        )
-        .await
+        self.apply_patch_with_level(patch_path, 1, env).await
+    }
+
+    /// Apply a patch file, stripping `strip` leading path components
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the patch command fails.
+    pub async fn apply_patch_with_level(
+        &self,
+        patch_path: &Path,
+        strip: u8,
+        env: &BuildEnvironment,
+    ) -> Result<BuildCommandResult, Error> {
+        let options = PatchOptions {
+            strip,
+            ..PatchOptions::default()
+        };
+        self.apply_patch_with_options(patch_path, options, env)
+            .await
+    }
+
+    /// Apply a patch file with explicit strip level, fuzz and dry-run options
+    ///
+    /// With `dry_run` set nothing is changed, so the result tells whether the
+    /// patch would apply cleanly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the patch command fails.
+    pub async fn apply_patch_with_options(
+        &self,
+        patch_path: &Path,
+        options: PatchOptions,
+        env: &BuildEnvironment,
+    ) -> Result<BuildCommandResult, Error> {
+        let args = options.patch_args(patch_path);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        env.execute_command("patch", &args, Some(&self.working_dir))
+            .await
     }
 
     /// Configure with autotools
@@ -346,7 +806,26 @@ impl BuilderApi {
         args: &[String],
         env: &mut BuildEnvironment,
     ) -> Result<BuildCommandResult, Error> {
-        use crate::build_systems::{AutotoolsBuildSystem, BuildSystem, BuildSystemContext};
+        self.autotools_with_options(args, AutotoolsOptions::default(), env)
+            .await
+    }
+
+    /// Build with autotools, optionally out of the source tree
+    ///
+    /// With `out_of_source`, configure is run from a `build/` directory
+    /// and make runs there too. `--prefix` is only added when `args` do not
+    /// set one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configure or make commands fail.
+    pub async fn autotools_with_options(
+        &self,
+        args: &[String],
+        options: AutotoolsOptions,
+        env: &mut BuildEnvironment,
+    ) -> Result<BuildCommandResult, Error> {
+        use crate::build_systems::BuildSystemContext;
 
         // Record that we're using autotools build system
         env.record_build_system("autotools");
@@ -354,10 +833,16 @@ impl BuilderApi {
         // Extract source archive first if needed
         self.extract_downloads().await?;
 
-        // Create build system context
+        // Create build system context, with a separate build directory for
+        // out-of-source builds
         let mut ctx = BuildSystemContext::new(env.clone(), self.working_dir.clone());
+        if options.out_of_source {
+            let build_dir = self.working_dir.join("build");
+            fs::create_dir_all(&build_dir).await?;
+            ctx.build_dir = build_dir;
+        }
         ctx.network_allowed = self.allow_network;
-        let autotools_system = AutotoolsBuildSystem::new();
+        let autotools_system = self.registered_build_system("autotools")?;
 
         // Configure
         autotools_system.configure(&ctx, args).await?;
@@ -365,6 +850,9 @@ impl BuilderApi {
         // Build
         autotools_system.build(&ctx, &[]).await?;
 
+        // Test (skipped when the build context or recipe disables tests)
+        Self::run_test_phase(autotools_system, &ctx, env).await?;
+
         // Install - this will also adjust staged files
         autotools_system.install(&ctx).await?;
 
@@ -386,7 +874,35 @@ impl BuilderApi {
         args: &[String],
         env: &mut BuildEnvironment,
     ) -> Result<BuildCommandResult, Error> {
-        use crate::build_systems::{BuildSystem, BuildSystemContext, CMakeBuildSystem};
+        self.run_cmake(args, None, env).await
+    }
+
+    /// Build with `CMake` using a preset from the project's `CMakePresets.json`
+    ///
+    /// Configures with `cmake --preset <name>` and builds with the build
+    /// preset of the same name when there is one. Install prefix and build
+    /// type are only injected if the preset leaves them unset.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project has no presets file, the preset is
+    /// not defined, or the cmake command fails.
+    pub async fn cmake_with_preset(
+        &self,
+        preset: &str,
+        env: &mut BuildEnvironment,
+    ) -> Result<BuildCommandResult, Error> {
+        self.run_cmake(&[], Some(preset), env).await
+    }
+
+    /// Configure, build, test and install with `CMake`
+    async fn run_cmake(
+        &self,
+        args: &[String],
+        preset: Option<&str>,
+        env: &mut BuildEnvironment,
+    ) -> Result<BuildCommandResult, Error> {
+        use crate::build_systems::{BuildSystemContext, CMakePreset};
 
         // Record that we're using cmake build system
         env.record_build_system("cmake");
@@ -394,21 +910,38 @@ impl BuilderApi {
         // Extract source archive first if needed
         self.extract_downloads().await?;
 
-        // Create build system context with out-of-source build directory
-        let build_dir = self.working_dir.join("build");
+        // Create build system context with out-of-source build directory,
+        // unless the preset chooses its own
+        let (build_dir, preset_args) = match preset {
+            Some(name) => {
+                let preset = CMakePreset::load(&self.working_dir, name).await?;
+                let build_dir = preset
+                    .binary_dir
+                    .unwrap_or_else(|| self.working_dir.join("build"));
+                // Remembered so later test runs use the preset's build tree
+                env.set_build_metadata(CMAKE_PRESET_KEY.to_string(), preset.name.clone());
+                (build_dir, vec!["--preset".to_string(), preset.name])
+            }
+            None => (self.working_dir.join("build"), Vec::new()),
+        };
         fs::create_dir_all(&build_dir).await?;
 
         let mut ctx = BuildSystemContext::new(env.clone(), self.working_dir.clone());
         ctx.build_dir = build_dir;
         ctx.network_allowed = self.allow_network;
 
-        let cmake_system = CMakeBuildSystem::new();
+        let cmake_system = self.registered_build_system("cmake")?;
 
         // Configure
-        cmake_system.configure(&ctx, args).await?;
+        let mut configure_args = preset_args.clone();
+        configure_args.extend_from_slice(args);
+        cmake_system.configure(&ctx, &configure_args).await?;
 
         // Build
-        cmake_system.build(&ctx, &[]).await?;
+        cmake_system.build(&ctx, &preset_args).await?;
+
+        // Test (skipped when the build context or recipe disables tests)
+        Self::run_test_phase(cmake_system, &ctx, env).await?;
 
         // Install - this will also adjust staged files
         cmake_system.install(&ctx).await?;
@@ -431,7 +964,7 @@ impl BuilderApi {
         args: &[String],
         env: &mut BuildEnvironment,
     ) -> Result<BuildCommandResult, Error> {
-        use crate::build_systems::{BuildSystem, BuildSystemContext, MesonBuildSystem};
+        use crate::build_systems::BuildSystemContext;
 
         // Record that we're using meson build system
         env.record_build_system("meson");
@@ -446,7 +979,7 @@ impl BuilderApi {
         ctx.build_dir = build_dir;
         ctx.network_allowed = self.allow_network;
 
-        let meson_system = MesonBuildSystem::new();
+        let meson_system = self.registered_build_system("meson")?;
 
         // Configure
         meson_system.configure(&ctx, args).await?;
@@ -454,6 +987,9 @@ impl BuilderApi {
         // Build
         meson_system.build(&ctx, &[]).await?;
 
+        // Test (skipped when the build context or recipe disables tests)
+        Self::run_test_phase(meson_system, &ctx, env).await?;
+
         // Install - this will also adjust staged files
         meson_system.install(&ctx).await?;
 
@@ -465,21 +1001,250 @@ impl BuilderApi {
         })
     }
 
-    /// Build with Cargo
+    /// Build with a shipped `build.ninja`
+    ///
+    /// `args` are passed to the build step, for example to select targets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ninja commands fail.
+    pub async fn ninja(
+        &self,
+        args: &[String],
+        env: &mut BuildEnvironment,
+    ) -> Result<BuildCommandResult, Error> {
+        use crate::build_systems::BuildSystemContext;
+
+        // Record that we're using ninja build system
+        env.record_build_system("ninja");
+
+        // Extract source archive first if needed
+        self.extract_downloads().await?;
+
+        // build.ninja lives in the source tree, so build in place
+        let mut ctx = BuildSystemContext::new(env.clone(), self.working_dir.clone());
+        ctx.network_allowed = self.allow_network;
+
+        let ninja_system = self.registered_build_system("ninja")?;
+
+        // Build
+        ninja_system.build(&ctx, args).await?;
+
+        // Test (skipped when the build context or recipe disables tests)
+        Self::run_test_phase(ninja_system, &ctx, env).await?;
+
+        // Install
+        ninja_system.install(&ctx).await?;
+
+        Ok(BuildCommandResult {
+            success: true,
+            exit_code: Some(0),
+            stdout: "Ninja build completed successfully".to_string(),
+            stderr: String::new(),
+        })
+    }
+
+    /// Build with any registered build system, including ones an embedder
+    /// added to the registry
+    ///
+    /// Runs the system's configure, build, test and install phases, building
+    /// out of source when the system prefers it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no build system is registered under `name` or
+    /// one of its phases fails.
+    pub async fn build_with(
+        &self,
+        name: &str,
+        args: &[String],
+        env: &mut BuildEnvironment,
+    ) -> Result<BuildCommandResult, Error> {
+        let system = self.registered_build_system(name)?;
+
+        // Extract source archive first if needed
+        self.extract_downloads().await?;
+
+        self.run_build_system(name, system, args, env).await
+    }
+
+    /// Build with the build system detected in the working directory
+    ///
+    /// Downloaded sources are extracted first so their build files take
+    /// part in detection; the choice is reported as in
+    /// [`Self::detect_build_system`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no build system is detected or one of its phases
+    /// fails.
+    pub async fn build_auto(
+        &self,
+        args: &[String],
+        env: &mut BuildEnvironment,
+    ) -> Result<BuildCommandResult, Error> {
+        self.extract_downloads().await?;
+        let detection = self
+            .build_systems
+            .detect_with_events(&self.working_dir, self.event_sender())
+            .await?;
+        self.run_build_system(&detection.name, detection.system, args, env)
+            .await
+    }
+
+    /// Detect the build system of the working directory
+    ///
+    /// Embedder-registered systems take part in detection. The decision,
+    /// the files behind it and any runner-ups are reported as a
+    /// `BuildSystemDetected` diagnostic through the event sender.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no build system is detected.
+    pub async fn detect_build_system(&self) -> Result<String, Error> {
+        let detection = self
+            .build_systems
+            .detect_with_events(&self.working_dir, self.event_sender())
+            .await?;
+        Ok(detection.name)
+    }
+
+    /// Run the configure, build, test and install phases of `system`
+    async fn run_build_system(
+        &self,
+        name: &str,
+        system: &dyn BuildSystem,
+        args: &[String],
+        env: &mut BuildEnvironment,
+    ) -> Result<BuildCommandResult, Error> {
+        use crate::build_systems::BuildSystemContext;
+
+        env.record_build_system(name);
+
+        let mut ctx = BuildSystemContext::new(env.clone(), self.working_dir.clone());
+        if system.prefers_out_of_source_build() {
+            let build_dir = self.working_dir.join("build");
+            fs::create_dir_all(&build_dir).await?;
+            ctx.build_dir = build_dir;
+        }
+        ctx.network_allowed = self.allow_network;
+
+        system.configure(&ctx, args).await?;
+        system.build(&ctx, &[]).await?;
+
+        // Test (skipped when the build context or recipe disables tests)
+        Self::run_test_phase(system, &ctx, env).await?;
+
+        system.install(&ctx).await?;
+
+        Ok(BuildCommandResult {
+            success: true,
+            exit_code: Some(0),
+            stdout: format!("{name} build completed successfully"),
+            stderr: String::new(),
+        })
+    }
+
+    /// Run the test suite of the active build system and return its results
+    ///
+    /// The active build system is the one the last build method ran. Tests
+    /// run even if the recipe disabled the automatic test phase, and failing
+    /// tests are reported in the results rather than as an error, so the
+    /// caller decides what to do with them. The results are also recorded on
+    /// `env`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no build method has run yet, the active build
+    /// system is not registered, or the test command cannot be run.
+    pub async fn run_tests(&self, env: &mut BuildEnvironment) -> Result<TestResults, Error> {
+        use crate::build_systems::BuildSystemContext;
+
+        let name = env
+            .active_build_system()
+            .ok_or_else(|| BuildError::NoBuildSystemDetected {
+                path: self.working_dir.display().to_string(),
+            })?
+            .to_string();
+        let system = self.registered_build_system(&name)?;
+
+        let mut ctx = BuildSystemContext::new(env.clone(), self.working_dir.clone());
+        if let Some(build_dir) = self.test_build_dir(system, env).await? {
+            ctx.build_dir = build_dir;
+        }
+        ctx.network_allowed = self.allow_network;
+
+        let results = system.test(&ctx).await?;
+        env.record_test_results(results.clone());
+        Ok(results)
+    }
+
+    /// Build directory the tests of `system` run in, or `None` to test in
+    /// the source directory
+    ///
+    /// A `CMake` build configured from a preset is tested in the preset's
+    /// `binaryDir`.
+    async fn test_build_dir(
+        &self,
+        system: &dyn BuildSystem,
+        env: &BuildEnvironment,
+    ) -> Result<Option<PathBuf>, Error> {
+        use crate::build_systems::CMakePreset;
+
+        if let Some(name) = env.build_metadata().get(CMAKE_PRESET_KEY) {
+            if system.name() == "cmake" {
+                let preset = CMakePreset::load(&self.working_dir, name).await?;
+                if let Some(binary_dir) = preset.binary_dir {
+                    return Ok(Some(binary_dir));
+                }
+            }
+        }
+        Ok(system
+            .prefers_out_of_source_build()
+            .then(|| self.working_dir.join(system.build_directory_name())))
+    }
+
+    /// Build with Cargo
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cargo command fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the binary filename cannot be extracted from the path.
+    pub async fn cargo(
+        &self,
+        args: &[String],
+        env: &mut BuildEnvironment,
+    ) -> Result<BuildCommandResult, Error> {
+        self.cargo_with_options(args, &CargoOptions::default(), env)
+            .await
+    }
+
+    /// Build with Cargo, selecting features and profile through `options`
+    ///
+    /// The flags from `options` come before the raw `args`.
     ///
     /// # Errors
     ///
-    /// Returns an error if the cargo command fails.
+    /// Returns an error if `options` combines `all_features` with an explicit
+    /// feature list, or if the cargo command fails.
     ///
     /// # Panics
     ///
     /// Panics if the binary filename cannot be extracted from the path.
-    pub async fn cargo(
+    pub async fn cargo_with_options(
         &self,
         args: &[String],
+        options: &CargoOptions,
         env: &mut BuildEnvironment,
     ) -> Result<BuildCommandResult, Error> {
-        use crate::build_systems::{BuildSystem, BuildSystemContext, CargoBuildSystem};
+        use crate::build_systems::BuildSystemContext;
+
+        let mut cargo_args = options.cargo_args()?;
+        cargo_args.extend_from_slice(args);
+        let args = cargo_args.as_slice();
 
         // Record that we're using cargo build system
         env.record_build_system("cargo");
@@ -490,7 +1255,7 @@ impl BuilderApi {
         // Create build system context
         let mut ctx = BuildSystemContext::new(env.clone(), self.working_dir.clone());
         ctx.network_allowed = self.allow_network;
-        let cargo_system = CargoBuildSystem::new();
+        let cargo_system = self.registered_build_system("cargo")?;
 
         // Configure (checks Cargo.toml, sets up environment)
         cargo_system.configure(&ctx, args).await?;
@@ -498,6 +1263,9 @@ impl BuilderApi {
         // Build
         cargo_system.build(&ctx, args).await?;
 
+        // Test (skipped when the build context or recipe disables tests)
+        Self::run_test_phase(cargo_system, &ctx, env).await?;
+
         // Install - this will copy binaries to staging/bin
         cargo_system.install(&ctx).await?;
 
@@ -519,7 +1287,28 @@ impl BuilderApi {
         args: &[String],
         env: &mut BuildEnvironment,
     ) -> Result<BuildCommandResult, Error> {
-        use crate::build_systems::{BuildSystem, BuildSystemContext, GoBuildSystem};
+        self.go_with_options(args, &GoOptions::default(), env).await
+    }
+
+    /// Build with Go, setting build tags, linker flags and `-trimpath`
+    /// through `options`
+    ///
+    /// The flags from `options` follow the go subcommand, if `args` starts
+    /// with one, and come before the remaining `args`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the go command fails.
+    pub async fn go_with_options(
+        &self,
+        args: &[String],
+        options: &GoOptions,
+        env: &mut BuildEnvironment,
+    ) -> Result<BuildCommandResult, Error> {
+        use crate::build_systems::BuildSystemContext;
+
+        let args = options.apply_to(args);
+        let args = args.as_slice();
 
         // Record that we're using go build system
         env.record_build_system("go");
@@ -530,7 +1319,7 @@ impl BuilderApi {
         // Create build system context
         let mut ctx = BuildSystemContext::new(env.clone(), self.working_dir.clone());
         ctx.network_allowed = self.allow_network;
-        let go_system = GoBuildSystem::new();
+        let go_system = self.registered_build_system("go")?;
 
         // Configure if needed (this will handle go mod vendor, etc)
         go_system.configure(&ctx, args).await?;
@@ -538,6 +1327,9 @@ impl BuilderApi {
         // Build the project - this will output to staging/bin automatically
         go_system.build(&ctx, args).await?;
 
+        // Test (skipped when the build context or recipe disables tests)
+        Self::run_test_phase(go_system, &ctx, env).await?;
+
         // Install (verifies binaries and sets permissions)
         go_system.install(&ctx).await?;
 
@@ -559,7 +1351,7 @@ impl BuilderApi {
         args: &[String],
         env: &mut BuildEnvironment,
     ) -> Result<BuildCommandResult, Error> {
-        use crate::build_systems::{BuildSystem, BuildSystemContext, PythonBuildSystem};
+        use crate::build_systems::BuildSystemContext;
 
         // Record that we're using python build system
         env.record_build_system("python");
@@ -570,7 +1362,7 @@ impl BuilderApi {
         // Create build system context
         let mut ctx = BuildSystemContext::new(env.clone(), self.working_dir.clone());
         ctx.network_allowed = self.allow_network;
-        let python_system = PythonBuildSystem::new();
+        let python_system = self.registered_build_system("python")?;
 
         // Configure (detects build backend, sets up environment)
         python_system.configure(&ctx, args).await?;
@@ -578,6 +1370,9 @@ impl BuilderApi {
         // Build (builds wheel or runs setup.py)
         python_system.build(&ctx, args).await?;
 
+        // Test (skipped when the build context or recipe disables tests)
+        Self::run_test_phase(python_system, &ctx, env).await?;
+
         // Install (installs to staging with BUILD_PREFIX)
         python_system.install(&ctx).await?;
 
@@ -608,7 +1403,7 @@ impl BuilderApi {
         args: &[String],
         env: &mut BuildEnvironment,
     ) -> Result<BuildCommandResult, Error> {
-        use crate::build_systems::{BuildSystem, BuildSystemContext, NodeJsBuildSystem};
+        use crate::build_systems::BuildSystemContext;
 
         // Record that we're using nodejs build system
         env.record_build_system("nodejs");
@@ -619,7 +1414,7 @@ impl BuilderApi {
         // Create build system context
         let mut ctx = BuildSystemContext::new(env.clone(), self.working_dir.clone());
         ctx.network_allowed = self.allow_network;
-        let nodejs_system = NodeJsBuildSystem::new();
+        let nodejs_system = self.registered_build_system("nodejs")?;
 
         // Configure (detects package manager, sets up environment)
         nodejs_system.configure(&ctx, args).await?;
@@ -627,13 +1422,27 @@ impl BuilderApi {
         // Build (installs dependencies if needed, runs build scripts)
         nodejs_system.build(&ctx, args).await?;
 
+        // Test (skipped when the build context or recipe disables tests)
+        Self::run_test_phase(nodejs_system, &ctx, env).await?;
+
         // Install (copies built artifacts and bin entries to staging)
         nodejs_system.install(&ctx).await?;
 
+        // Report the package manager picked during configure
+        let package_manager = ctx
+            .extra_env
+            .read()
+            .ok()
+            .and_then(|extra_env| extra_env.get("NODE_PACKAGE_MANAGER").cloned())
+            .unwrap_or_else(|| "unknown".to_string());
+
         Ok(BuildCommandResult {
             success: true,
             exit_code: Some(0),
-            stdout: "Node.js build completed successfully".to_string(),
+            stdout: format!(
+                "Node.js build completed successfully (package manager: {})",
+                package_manager.to_lowercase()
+            ),
             stderr: String::new(),
         })
     }
@@ -655,10 +1464,7 @@ impl BuilderApi {
 
         // Add prefix if not already specified
         let mut configure_args = args.to_vec();
-        if !configure_args
-            .iter()
-            .any(|arg| arg.starts_with("--prefix="))
-        {
+        if !has_prefix_arg(&configure_args) {
             configure_args.insert(
                 0,
                 format!("--prefix={}", sps2_config::fixed_paths::LIVE_DIR),
@@ -812,20 +1618,46 @@ impl BuilderApi {
         &self,
         path: &Path,
         extract_to: Option<&str>,
+    ) -> Result<(), Error> {
+        self.extract_single_download_with_options(path, extract_to, None)
+            .await
+    }
+
+    /// Extract a single downloaded file, stripping `strip_components`
+    /// leading path components from every entry
+    ///
+    /// `None` keeps the defaults: tarballs lose their first component and
+    /// zip archives are stripped only if everything is under one directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if archive extraction fails.
+    pub async fn extract_single_download_with_options(
+        &self,
+        path: &Path,
+        extract_to: Option<&str>,
+        strip_components: Option<usize>,
     ) -> Result<(), Error> {
         if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
             match ext {
                 "gz" | "tgz" => {
-                    self.extract_tar_gz(path, extract_to).await?;
+                    self.extract_tar_gz(path, extract_to, strip_components)
+                        .await?;
                 }
                 "bz2" => {
-                    self.extract_tar_bz2(path, extract_to).await?;
+                    self.extract_tar_bz2(path, extract_to, strip_components)
+                        .await?;
                 }
                 "xz" => {
-                    self.extract_tar_xz(path, extract_to).await?;
+                    self.extract_tar_xz(path, extract_to, strip_components)
+                        .await?;
+                }
+                "zst" | "tzst" => {
+                    self.extract_tar_zst(path, extract_to, strip_components)
+                        .await?;
                 }
                 "zip" => {
-                    self.extract_zip(path, extract_to).await?;
+                    self.extract_zip(path, extract_to, strip_components).await?;
                 }
                 _ => {
                     // Unknown format, skip extraction
@@ -838,15 +1670,22 @@ impl BuilderApi {
                 let magic = &file_bytes[0..4];
                 // Check for gzip magic number (1f 8b)
                 if magic[0] == 0x1f && magic[1] == 0x8b {
-                    self.extract_tar_gz(path, extract_to).await?;
+                    self.extract_tar_gz(path, extract_to, strip_components)
+                        .await?;
                 }
                 // Check for ZIP magic number (50 4b)
                 else if magic[0] == 0x50 && magic[1] == 0x4b {
-                    self.extract_zip(path, extract_to).await?;
+                    self.extract_zip(path, extract_to, strip_components).await?;
                 }
                 // Check for bzip2 magic number (42 5a)
                 else if magic[0] == 0x42 && magic[1] == 0x5a {
-                    self.extract_tar_bz2(path, extract_to).await?;
+                    self.extract_tar_bz2(path, extract_to, strip_components)
+                        .await?;
+                }
+                // Check for zstd magic number (28 b5 2f fd)
+                else if magic == [0x28, 0xb5, 0x2f, 0xfd] {
+                    self.extract_tar_zst(path, extract_to, strip_components)
+                        .await?;
                 }
             }
         }
@@ -858,8 +1697,13 @@ impl BuilderApi {
     /// # Errors
     ///
     /// Returns an error if extraction fails.
-    async fn extract_tar_gz(&self, path: &Path, extract_to: Option<&str>) -> Result<(), Error> {
-        self.extract_compressed_tar(path, CompressionType::Gzip, extract_to)
+    async fn extract_tar_gz(
+        &self,
+        path: &Path,
+        extract_to: Option<&str>,
+        strip_components: Option<usize>,
+    ) -> Result<(), Error> {
+        self.extract_compressed_tar(path, CompressionType::Gzip, extract_to, strip_components)
             .await
     }
 
@@ -868,8 +1712,13 @@ impl BuilderApi {
     /// # Errors
     ///
     /// Returns an error if extraction fails.
-    async fn extract_tar_bz2(&self, path: &Path, extract_to: Option<&str>) -> Result<(), Error> {
-        self.extract_compressed_tar(path, CompressionType::Bzip2, extract_to)
+    async fn extract_tar_bz2(
+        &self,
+        path: &Path,
+        extract_to: Option<&str>,
+        strip_components: Option<usize>,
+    ) -> Result<(), Error> {
+        self.extract_compressed_tar(path, CompressionType::Bzip2, extract_to, strip_components)
             .await
     }
 
@@ -878,8 +1727,28 @@ impl BuilderApi {
     /// # Errors
     ///
     /// Returns an error if extraction fails.
-    async fn extract_tar_xz(&self, path: &Path, extract_to: Option<&str>) -> Result<(), Error> {
-        self.extract_compressed_tar(path, CompressionType::Xz, extract_to)
+    async fn extract_tar_xz(
+        &self,
+        path: &Path,
+        extract_to: Option<&str>,
+        strip_components: Option<usize>,
+    ) -> Result<(), Error> {
+        self.extract_compressed_tar(path, CompressionType::Xz, extract_to, strip_components)
+            .await
+    }
+
+    /// Extract tar.zst archive
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if extraction fails.
+    async fn extract_tar_zst(
+        &self,
+        path: &Path,
+        extract_to: Option<&str>,
+        strip_components: Option<usize>,
+    ) -> Result<(), Error> {
+        self.extract_compressed_tar(path, CompressionType::Zstd, extract_to, strip_components)
             .await
     }
 
@@ -888,7 +1757,12 @@ impl BuilderApi {
     /// # Errors
     ///
     /// Returns an error if extraction fails.
-    async fn extract_zip(&self, path: &Path, extract_to: Option<&str>) -> Result<(), Error> {
+    async fn extract_zip(
+        &self,
+        path: &Path,
+        extract_to: Option<&str>,
+        strip_components: Option<usize>,
+    ) -> Result<(), Error> {
         let base_dir = if let Some(extract_to) = extract_to {
             // For multi-source builds, extract_to should be relative to the parent of working_dir
             if let Some(parent) = self.working_dir.parent() {
@@ -912,8 +1786,11 @@ impl BuilderApi {
                 message: format!("Failed to read zip archive: {e}"),
             })?;
 
-            // Check if archive has a single top-level directory
-            let strip_components = usize::from(should_strip_zip_components(&mut archive)?);
+            // Unless told otherwise, strip a single top-level directory
+            let strip_components = match strip_components {
+                Some(count) => count,
+                None => usize::from(should_strip_zip_components(&mut archive)?),
+            };
 
             for i in 0..archive.len() {
                 let mut file = archive
@@ -990,8 +1867,9 @@ impl BuilderApi {
         path: &Path,
         compression: CompressionType,
         extract_to: Option<&str>,
+        strip_components: Option<usize>,
     ) -> Result<(), Error> {
-        use async_compression::tokio::bufread::{BzDecoder, GzipDecoder, XzDecoder};
+        use async_compression::tokio::bufread::{BzDecoder, GzipDecoder, XzDecoder, ZstdDecoder};
         use tokio::io::{AsyncWriteExt, BufReader};
 
         // Create a temporary file to decompress to
@@ -1044,6 +1922,14 @@ impl BuilderApi {
                             message: format!("Failed to decompress xz archive: {e}"),
                         })?;
                 }
+                CompressionType::Zstd => {
+                    let mut decoder = ZstdDecoder::new(reader);
+                    tokio::io::copy(&mut decoder, &mut output_file)
+                        .await
+                        .map_err(|e| BuildError::ExtractionFailed {
+                            message: format!("Failed to decompress zstd archive: {e}"),
+                        })?;
+                }
             }
 
             output_file
@@ -1055,7 +1941,9 @@ impl BuilderApi {
         }
 
         // Extract the decompressed tar file (keep temp_dir alive)
-        let result = self.extract_tar_from_temp(&temp_path, extract_to).await;
+        let result = self
+            .extract_tar_from_temp(&temp_path, extract_to, strip_components.unwrap_or(1))
+            .await;
 
         // temp_dir will be automatically cleaned up when it goes out of scope
         drop(temp_dir);
@@ -1063,11 +1951,13 @@ impl BuilderApi {
         result
     }
 
-    /// Extract tar archive from temporary file
+    /// Extract tar archive from temporary file, dropping the first
+    /// `strip_components` path components of each entry
     async fn extract_tar_from_temp(
         &self,
         temp_path: &Path,
         extract_to: Option<&str>,
+        strip_components: usize,
     ) -> Result<(), Error> {
         let base_dir = if let Some(extract_to) = extract_to {
             // For multi-source builds, extract_to should be relative to the parent of working_dir
@@ -1091,19 +1981,18 @@ impl BuilderApi {
                 })?;
             let mut archive = Archive::new(tar);
 
-            // Strip the first component (common for source archives)
+            // Strip leading components (source archives usually wrap everything in one)
             for entry in archive.entries()? {
                 let mut entry = entry?;
                 let path = entry.path()?;
 
-                // Skip if path has no components or only one component
+                // Skip entries at or above the stripped level
                 let components: Vec<_> = path.components().collect();
-                if components.len() <= 1 {
+                if components.len() <= strip_components {
                     continue;
                 }
 
-                // Create new path without first component
-                let new_path = components[1..].iter().collect::<PathBuf>();
+                let new_path = components[strip_components..].iter().collect::<PathBuf>();
                 let dest_path = base_dir.join(&new_path);
 
                 // Ensure parent directory exists
@@ -1528,6 +2417,7 @@ enum CompressionType {
     Gzip,
     Bzip2,
     Xz,
+    Zstd,
 }
 
 /// Check if a zip archive should have its first component stripped
@@ -1563,3 +2453,818 @@ fn should_strip_zip_components(
     // Strip if there's exactly one directory at top level and no files
     Ok(top_level_dirs.len() == 1 && !has_files_at_root)
 }
+
+/// Digest algorithms accepted for verifying downloaded sources
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SourceHashAlgorithm {
+    Blake3,
+    Sha256,
+}
+
+impl SourceHashAlgorithm {
+    /// Split an optional `algorithm:` prefix off `hash`, defaulting to BLAKE3
+    fn parse(hash: &str) -> Result<(Self, &str), Error> {
+        let Some((prefix, digest)) = hash.split_once(':') else {
+            return Ok((Self::Blake3, hash));
+        };
+        let algorithm = match prefix.to_ascii_lowercase().as_str() {
+            "blake3" => Self::Blake3,
+            "sha256" => Self::Sha256,
+            other => {
+                return Err(BuildError::RecipeError {
+                    message: format!(
+                        "unsupported hash algorithm '{other}' (expected sha256 or blake3)"
+                    ),
+                }
+                .into())
+            }
+        };
+        Ok((algorithm, digest))
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Blake3 => "blake3",
+            Self::Sha256 => "sha256",
+        }
+    }
+
+    /// Incremental hasher for this algorithm
+    fn hasher(self) -> SourceHasher {
+        match self {
+            Self::Blake3 => SourceHasher::Blake3(Box::new(blake3::Hasher::new())),
+            Self::Sha256 => SourceHasher::Sha256(Sha256::new()),
+        }
+    }
+
+    /// Hex digest of the file at `path`
+    async fn hash_file(self, path: &Path) -> Result<String, Error> {
+        match self {
+            Self::Blake3 => Ok(Hash::blake3_hash_file(path).await?.to_hex()),
+            Self::Sha256 => {
+                let bytes = tokio::fs::read(path).await?;
+                let mut hasher = Sha256::new();
+                Sha2Digest::update(&mut hasher, &bytes);
+                Ok(format!("{:x}", hasher.finalize()))
+            }
+        }
+    }
+}
+
+/// Digest of a source download in progress
+enum SourceHasher {
+    Blake3(Box<blake3::Hasher>),
+    Sha256(Sha256),
+}
+
+impl SourceHasher {
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Blake3(hasher) => {
+                hasher.update(data);
+            }
+            Self::Sha256(hasher) => Sha2Digest::update(hasher, data),
+        }
+    }
+
+    /// Hex digest, formatted like [`SourceHashAlgorithm::hash_file`]
+    fn finalize(self) -> String {
+        match self {
+            Self::Blake3(hasher) => Hash::from_blake3_bytes(*hasher.finalize().as_bytes()).to_hex(),
+            Self::Sha256(hasher) => format!("{:x}", hasher.finalize()),
+        }
+    }
+}
+
+/// Whether `ref_` is a full commit SHA rather than a branch or tag name
+fn is_commit_sha(ref_: &str) -> bool {
+    ref_.len() == 40 && ref_.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Whether a failed fetch is worth retrying
+///
+/// Server errors and interrupted transfers usually clear up on their own;
+/// client errors such as 404 will not.
+fn is_transient_fetch_error(err: &Error) -> bool {
+    match err {
+        Error::Network(NetworkError::HttpError { status, .. }) => *status >= 500,
+        Error::Network(err) => err.is_retryable(),
+        Error::Build(BuildError::FetchFailed { .. }) => true,
+        _ => false,
+    }
+}
+
+/// Error for a download of `path` whose `algorithm` digest is `actual`
+/// rather than `expected`
+fn hash_mismatch(
+    path: &Path,
+    algorithm: SourceHashAlgorithm,
+    expected: &str,
+    actual: &str,
+) -> Error {
+    let filename = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown");
+    let name = algorithm.name();
+    BuildError::HashMismatch {
+        file: filename.to_string(),
+        expected: format!("{name}:{expected}"),
+        actual: format!("{name}:{actual}"),
+    }
+    .into()
+}
+
+/// Backoff before retry number `attempt`, counting from 1
+fn fetch_retry_delay(base: Duration, attempt: u32) -> Duration {
+    base * 2u32.pow(attempt.saturating_sub(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::demo_environment;
+    use tempfile::TempDir;
+
+    /// Write a zstd-compressed tarball of `entries` (path, contents)
+    fn write_tar_zst(path: &Path, entries: &[(&str, &str)]) {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, contents) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, contents.as_bytes())
+                .unwrap();
+        }
+        let tar = builder.into_inner().unwrap();
+        std::fs::write(path, zstd::encode_all(tar.as_slice(), 3).unwrap()).unwrap();
+    }
+
+    /// Extract `entries` as a tarball into a fresh working directory
+    async fn extract(
+        temp: &TempDir,
+        name: &str,
+        entries: &[(&str, &str)],
+        strip_components: Option<usize>,
+    ) -> PathBuf {
+        let working_dir = temp.path().join(format!("src-{name}"));
+        std::fs::create_dir_all(&working_dir).unwrap();
+        let archive = temp.path().join(name);
+        write_tar_zst(&archive, entries);
+
+        let api =
+            BuilderApi::new(working_dir.clone(), Arc::new(ResourceManager::default())).unwrap();
+        api.extract_single_download_with_options(&archive, None, strip_components)
+            .await
+            .unwrap();
+        working_dir
+    }
+
+    const WRAPPED: [(&str, &str); 2] = [
+        ("foo-1.2/configure", "#!/bin/sh\n"),
+        ("foo-1.2/src/main.c", "int main(void) { return 0; }\n"),
+    ];
+
+    #[tokio::test]
+    async fn zstd_tarballs_extract_like_gzip() {
+        let temp = TempDir::new().unwrap();
+        for name in ["foo-1.2.tar.zst", "foo-1.2.tzst"] {
+            let working_dir = extract(&temp, name, &WRAPPED, None).await;
+
+            // The top-level directory is stripped
+            assert_eq!(
+                std::fs::read_to_string(working_dir.join("configure")).unwrap(),
+                "#!/bin/sh\n"
+            );
+            assert!(working_dir.join("src/main.c").is_file());
+            assert!(!working_dir.join("foo-1.2").exists());
+        }
+    }
+
+    #[tokio::test]
+    async fn tarball_without_common_prefix_needs_strip_zero() {
+        let temp = TempDir::new().unwrap();
+        let flat = [("configure", "#!/bin/sh\n"), ("src/main.c", "int x;\n")];
+
+        // The default strips one component and loses top-level files
+        let default = extract(&temp, "default.tar.zst", &flat, None).await;
+        assert!(!default.join("configure").exists());
+        assert!(default.join("main.c").is_file());
+
+        let kept = extract(&temp, "kept.tar.zst", &flat, Some(0)).await;
+        assert!(kept.join("configure").is_file());
+        assert!(kept.join("src/main.c").is_file());
+    }
+
+    #[tokio::test]
+    async fn strip_components_removes_nested_wrappers() {
+        let temp = TempDir::new().unwrap();
+        let nested = [
+            ("release/foo-1.2/configure", "#!/bin/sh\n"),
+            ("release/foo-1.2/src/main.c", "int x;\n"),
+        ];
+
+        let working_dir = extract(&temp, "nested.tar.zst", &nested, Some(2)).await;
+        assert!(working_dir.join("configure").is_file());
+        assert!(working_dir.join("src/main.c").is_file());
+        assert!(!working_dir.join("foo-1.2").exists());
+    }
+
+    #[test]
+    fn only_transient_fetch_errors_are_retried() {
+        let http = |status| {
+            Error::from(NetworkError::HttpError {
+                status,
+                message: String::new(),
+            })
+        };
+        assert!(is_transient_fetch_error(&http(503)));
+        assert!(!is_transient_fetch_error(&http(404)));
+        assert!(is_transient_fetch_error(&Error::from(
+            NetworkError::Timeout {
+                url: "https://example.com/src.tar.gz".to_string(),
+            }
+        )));
+        assert!(!is_transient_fetch_error(&Error::from(
+            BuildError::HashMismatch {
+                file: "src.tar.gz".to_string(),
+                expected: "aa".to_string(),
+                actual: "bb".to_string(),
+            }
+        )));
+    }
+
+    #[test]
+    fn source_hash_prefix_selects_algorithm() {
+        let (algorithm, digest) = SourceHashAlgorithm::parse("sha256:abc123").unwrap();
+        assert_eq!(algorithm, SourceHashAlgorithm::Sha256);
+        assert_eq!(digest, "abc123");
+
+        let (algorithm, digest) = SourceHashAlgorithm::parse("BLAKE3:def456").unwrap();
+        assert_eq!(algorithm, SourceHashAlgorithm::Blake3);
+        assert_eq!(digest, "def456");
+
+        let (algorithm, digest) = SourceHashAlgorithm::parse("def456").unwrap();
+        assert_eq!(algorithm, SourceHashAlgorithm::Blake3);
+        assert_eq!(digest, "def456");
+
+        assert!(SourceHashAlgorithm::parse("crc32:0000").is_err());
+    }
+
+    #[tokio::test]
+    async fn sha256_source_hash_matches_sha256sum() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("src.tar.gz");
+        fs::write(&path, b"hello\n").await.unwrap();
+
+        let digest = SourceHashAlgorithm::Sha256.hash_file(&path).await.unwrap();
+        assert_eq!(
+            digest,
+            "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03"
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_verified_hashes_while_downloading() {
+        use httpmock::prelude::*;
+
+        let temp = TempDir::new().unwrap();
+        let content = b"hello\n";
+        let sha256 = "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/src.tar.gz");
+            then.status(200).body(content);
+        });
+
+        let mut api = BuilderApi::new(
+            temp.path().to_path_buf(),
+            Arc::new(ResourceManager::default()),
+        )
+        .unwrap();
+        api.net_client = NetClient::new_without_proxies(NetConfig::default()).unwrap();
+        let url = server.url("/src.tar.gz");
+
+        let path = api.fetch_sha256(&url, sha256).await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), content);
+
+        // The streamed digests match hashing the file afterwards
+        for algorithm in [SourceHashAlgorithm::Sha256, SourceHashAlgorithm::Blake3] {
+            let mut hasher = algorithm.hasher();
+            hasher.update(content);
+            assert_eq!(hasher.finalize(), algorithm.hash_file(&path).await.unwrap());
+        }
+
+        // A reused download is still checked, by reading it back
+        let blake3 = Hash::blake3_from_data(content).to_hex();
+        assert_eq!(api.fetch_blake3(&url, &blake3).await.unwrap(), path);
+        let err = api.fetch_blake3(&url, &"0".repeat(64)).await.unwrap_err();
+        assert!(matches!(err, Error::Build(BuildError::HashMismatch { .. })));
+        assert!(!path.exists());
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn fetch_retries_only_at_one_layer() {
+        use httpmock::prelude::*;
+
+        let temp = TempDir::new().unwrap();
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/src.tar.gz");
+            then.status(503);
+        });
+
+        // Transport failures would be retried by the client as well
+        let refused = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let attempts = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let counter = attempts.clone();
+        let config = NetConfig {
+            retry_delay: Duration::from_millis(1),
+            ..NetConfig::default()
+        }
+        .with_request_observer(sps2_net::RequestObserver::new(move |_| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }));
+
+        let mut api = BuilderApi::new(
+            temp.path().to_path_buf(),
+            Arc::new(ResourceManager::default()),
+        )
+        .unwrap();
+        api.net_client = NetClient::new_without_proxies(config).unwrap();
+        api.fetch_retry_base = Duration::ZERO;
+
+        assert!(api.fetch(&server.url("/src.tar.gz")).await.is_err());
+        mock.assert_hits(FETCH_ATTEMPTS as usize);
+
+        attempts.store(0, std::sync::atomic::Ordering::SeqCst);
+        let url = format!("http://127.0.0.1:{refused}/src.tar.gz");
+        assert!(api.fetch(&url).await.is_err());
+        assert_eq!(
+            attempts.load(std::sync::atomic::Ordering::SeqCst),
+            FETCH_ATTEMPTS
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_renames_only_verified_downloads() {
+        use httpmock::prelude::*;
+
+        let temp = TempDir::new().unwrap();
+        let content = b"hello\n";
+        let sha256 = "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/src.tar.gz");
+            then.status(200).body(content);
+        });
+
+        let mut api = BuilderApi::new(
+            temp.path().to_path_buf(),
+            Arc::new(ResourceManager::default()),
+        )
+        .unwrap();
+        api.net_client = NetClient::new_without_proxies(NetConfig::default()).unwrap();
+        let url = server.url("/src.tar.gz");
+        let path = temp.path().join("src.tar.gz");
+        let partial = partial_path(&path);
+
+        // A leftover from an interrupted fetch is neither trusted nor kept
+        std::fs::write(&partial, b"hel").unwrap();
+        let err = api.fetch_sha256(&url, &"0".repeat(64)).await.unwrap_err();
+        assert!(matches!(err, Error::Build(BuildError::HashMismatch { .. })));
+        assert!(!path.exists() && !partial.exists());
+
+        std::fs::write(&partial, b"hel").unwrap();
+        assert_eq!(api.fetch_sha256(&url, sha256).await.unwrap(), path);
+        assert_eq!(std::fs::read(&path).unwrap(), content);
+        assert!(!partial.exists());
+    }
+
+    #[tokio::test]
+    async fn raw_fetches_stay_unextracted() {
+        use httpmock::prelude::*;
+
+        let temp = TempDir::new().unwrap();
+        let archive = temp.path().join("served.tar.zst");
+        write_tar_zst(&archive, &WRAPPED);
+        let content = std::fs::read(&archive).unwrap();
+        let expected = format!("blake3:{}", Hash::blake3_from_data(&content).to_hex());
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/foo-1.2.tar.zst");
+            then.status(200).body(&content);
+        });
+
+        let working_dir = temp.path().join("src");
+        std::fs::create_dir_all(&working_dir).unwrap();
+        let mut api =
+            BuilderApi::new(working_dir.clone(), Arc::new(ResourceManager::default())).unwrap();
+        api.net_client = NetClient::new_without_proxies(NetConfig::default()).unwrap();
+
+        let url = server.url("/foo-1.2.tar.zst");
+        let path = api.fetch_raw(&url, Some(&expected)).await.unwrap();
+        assert_eq!(path, working_dir.join("foo-1.2.tar.zst"));
+        assert_eq!(std::fs::read(&path).unwrap(), content);
+        assert!(!working_dir.join("configure").exists());
+        assert_eq!(api.downloads.get(&url), Some(&path));
+
+        // The recorded download can still be extracted explicitly
+        api.extract_downloads().await.unwrap();
+        assert!(working_dir.join("configure").is_file());
+    }
+
+    #[test]
+    fn commit_refs_are_full_hex_shas() {
+        assert!(is_commit_sha("0123456789abcdef0123456789ABCDEF01234567"));
+        assert!(!is_commit_sha("v1.2.3"));
+        assert!(!is_commit_sha("0123456"));
+        assert!(!is_commit_sha("g123456789abcdef0123456789abcdef01234567"));
+    }
+
+    #[tokio::test]
+    async fn fetch_mirrors_skips_bad_mirrors() {
+        use httpmock::prelude::*;
+
+        let temp = TempDir::new().unwrap();
+        let content = b"source tarball contents";
+        let expected = format!("blake3:{}", Hash::blake3_from_data(content).to_hex());
+
+        let missing = MockServer::start();
+        let corrupt = MockServer::start();
+        let good = MockServer::start();
+        missing.mock(|when, then| {
+            when.method(GET).path("/foo-1.0.tar.gz");
+            then.status(404);
+        });
+        corrupt.mock(|when, then| {
+            when.method(GET).path("/foo-1.0.tar.gz");
+            then.status(200).body("truncated");
+        });
+        good.mock(|when, then| {
+            when.method(GET).path("/mirror/foo-1.0-src.tar.gz");
+            then.status(200).body(content);
+        });
+
+        let mut api = BuilderApi::new(
+            temp.path().to_path_buf(),
+            Arc::new(ResourceManager::default()),
+        )
+        .unwrap();
+        api.net_client = NetClient::new_without_proxies(NetConfig::default()).unwrap();
+
+        let urls = [
+            missing.url("/foo-1.0.tar.gz"),
+            corrupt.url("/foo-1.0.tar.gz"),
+            good.url("/mirror/foo-1.0-src.tar.gz"),
+        ];
+        let path = api.fetch_mirrors(&urls, &expected).await.unwrap();
+
+        assert_eq!(path, temp.path().join("foo-1.0.tar.gz"));
+        assert_eq!(std::fs::read(&path).unwrap(), content);
+        assert!(!temp.path().join("foo-1.0-src.tar.gz").exists());
+        assert_eq!(api.downloads.get(&urls[0]), Some(&path));
+        assert_eq!(api.downloads.len(), 1);
+
+        // Every mirror failing is a fetch failure
+        std::fs::create_dir(temp.path().join("other")).unwrap();
+        let mut api = BuilderApi::new(
+            temp.path().join("other"),
+            Arc::new(ResourceManager::default()),
+        )
+        .unwrap();
+        api.net_client = NetClient::new_without_proxies(NetConfig::default()).unwrap();
+        let err = api.fetch_mirrors(&urls[..2], &expected).await.unwrap_err();
+        assert!(matches!(err, Error::Build(BuildError::FetchFailed { .. })));
+    }
+
+    #[test]
+    fn patch_options_map_to_patch_flags() {
+        let patch = Path::new("/tmp/fix.patch");
+        assert_eq!(
+            PatchOptions::default().patch_args(patch),
+            ["-p1", "-i", "/tmp/fix.patch"]
+        );
+
+        let options = PatchOptions {
+            strip: 0,
+            fuzz: Some(3),
+            dry_run: true,
+        };
+        assert_eq!(
+            options.patch_args(patch),
+            ["-p0", "--fuzz=3", "--dry-run", "-i", "/tmp/fix.patch"]
+        );
+    }
+
+    #[test]
+    fn cargo_options_map_to_cargo_flags() {
+        assert!(CargoOptions::default().cargo_args().unwrap().is_empty());
+
+        let options = CargoOptions {
+            features: vec!["tls".to_string(), "zstd".to_string()],
+            no_default_features: true,
+            all_features: false,
+            profile: Some("dist".to_string()),
+        };
+        assert_eq!(
+            options.cargo_args().unwrap(),
+            [
+                "--features",
+                "tls,zstd",
+                "--no-default-features",
+                "--profile",
+                "dist"
+            ]
+        );
+
+        let conflicting = CargoOptions {
+            all_features: true,
+            ..options
+        };
+        assert!(matches!(
+            conflicting.cargo_args(),
+            Err(Error::Build(BuildError::RecipeError { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn autotools_builds_out_of_source() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("src");
+        std::fs::create_dir_all(&source).unwrap();
+
+        // A configure script that refuses to run in its own directory
+        let configure = source.join("configure");
+        std::fs::write(
+            &configure,
+            "#!/bin/sh\n\
+             srcdir=$(cd \"$(dirname \"$0\")\" && pwd)\n\
+             if [ \"$srcdir\" = \"$(pwd)\" ]; then\n\
+             \x20 echo 'in-source builds are not supported' >&2\n\
+             \x20 exit 1\n\
+             fi\n\
+             echo \"$@\" > configure.args\n\
+             printf 'all:\\n\\ttouch built\\ninstall:\\n' > Makefile\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&configure, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let api = BuilderApi::new(source.clone(), Arc::new(ResourceManager::default())).unwrap();
+        let mut env = demo_environment(temp.path());
+
+        assert!(api.autotools(&[], &mut env).await.is_err());
+
+        let options = AutotoolsOptions {
+            out_of_source: true,
+        };
+        let result = api
+            .autotools_with_options(&["--prefix=/usr/local".to_string()], options, &mut env)
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(source.join("build/built").exists());
+        assert!(!source.join("Makefile").exists());
+
+        let configure_args = std::fs::read_to_string(source.join("build/configure.args")).unwrap();
+        assert_eq!(configure_args.matches("--prefix").count(), 1);
+        assert!(configure_args.contains("--prefix=/usr/local"));
+    }
+
+    /// Build system that records the phases it runs
+    struct RecordingBuildSystem(Arc<std::sync::Mutex<Vec<&'static str>>>);
+
+    #[async_trait::async_trait]
+    impl BuildSystem for RecordingBuildSystem {
+        async fn detect(&self, source_dir: &Path) -> Result<bool, Error> {
+            Ok(source_dir.join("xmake.lua").exists())
+        }
+
+        fn get_config_options(&self) -> crate::build_systems::BuildSystemConfig {
+            crate::build_systems::BuildSystemConfig::default()
+        }
+
+        async fn configure(
+            &self,
+            _ctx: &crate::build_systems::BuildSystemContext,
+            args: &[String],
+        ) -> Result<(), Error> {
+            assert_eq!(args, ["--fast"]);
+            self.0.lock().unwrap().push("configure");
+            Ok(())
+        }
+
+        async fn build(
+            &self,
+            _ctx: &crate::build_systems::BuildSystemContext,
+            _args: &[String],
+        ) -> Result<(), Error> {
+            self.0.lock().unwrap().push("build");
+            Ok(())
+        }
+
+        async fn test(
+            &self,
+            _ctx: &crate::build_systems::BuildSystemContext,
+        ) -> Result<crate::build_systems::TestResults, Error> {
+            self.0.lock().unwrap().push("test");
+            Ok(crate::build_systems::TestResults::not_run())
+        }
+
+        async fn install(
+            &self,
+            _ctx: &crate::build_systems::BuildSystemContext,
+        ) -> Result<(), Error> {
+            self.0.lock().unwrap().push("install");
+            Ok(())
+        }
+
+        fn get_env_vars(
+            &self,
+            _ctx: &crate::build_systems::BuildSystemContext,
+        ) -> HashMap<String, String> {
+            HashMap::new()
+        }
+
+        fn name(&self) -> &'static str {
+            "xmake"
+        }
+    }
+
+    #[tokio::test]
+    async fn build_with_runs_an_injected_build_system() {
+        let temp = TempDir::new().unwrap();
+        let phases = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut registry = BuildSystemRegistry::new();
+        registry.register("xmake", Box::new(RecordingBuildSystem(phases.clone())));
+
+        let mut api = BuilderApi::new(
+            temp.path().to_path_buf(),
+            Arc::new(ResourceManager::default()),
+        )
+        .unwrap();
+        api.set_build_systems(Arc::new(registry));
+
+        let mut env = demo_environment(temp.path());
+
+        let result = api
+            .build_with("xmake", &["--fast".to_string()], &mut env)
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(
+            *phases.lock().unwrap(),
+            ["configure", "build", "test", "install"]
+        );
+
+        assert!(api.build_with("scons", &[], &mut env).await.is_err());
+
+        // Detection considers the registered systems
+        assert!(api.detect_build_system().await.is_err());
+        fs::write(temp.path().join("xmake.lua"), "").await.unwrap();
+        assert_eq!(api.detect_build_system().await.unwrap(), "xmake");
+
+        // Tests can be rerun on demand against the build system used last
+        phases.lock().unwrap().clear();
+        let results = api.run_tests(&mut env).await.unwrap();
+        assert!(!results.executed);
+        assert_eq!(*phases.lock().unwrap(), ["test"]);
+        assert_eq!(env.test_results().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn cmake_tests_run_in_the_preset_binary_dir() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("CMakePresets.json"),
+            r#"{"version": 3, "configurePresets": [
+                {"name": "release", "binaryDir": "${sourceDir}/out/${presetName}"}
+            ]}"#,
+        )
+        .await
+        .unwrap();
+        let api = BuilderApi::new(
+            temp.path().to_path_buf(),
+            Arc::new(ResourceManager::default()),
+        )
+        .unwrap();
+        let cmake = api.registered_build_system("cmake").unwrap();
+        let mut env = demo_environment(temp.path());
+
+        assert_eq!(
+            api.test_build_dir(cmake, &env).await.unwrap(),
+            Some(temp.path().join("build"))
+        );
+
+        env.set_build_metadata(CMAKE_PRESET_KEY.to_string(), "release".to_string());
+        assert_eq!(
+            api.test_build_dir(cmake, &env).await.unwrap(),
+            Some(temp.path().join("out/release"))
+        );
+    }
+
+    #[tokio::test]
+    async fn detection_reports_the_chosen_build_system() {
+        let temp = TempDir::new().unwrap();
+        for file in ["CMakeLists.txt", "meson.build"] {
+            fs::write(temp.path().join(file), "").await.unwrap();
+        }
+        let mut api = BuilderApi::new(
+            temp.path().to_path_buf(),
+            Arc::new(ResourceManager::default()),
+        )
+        .unwrap();
+        let (tx, mut rx) = sps2_events::channel();
+        api.set_event_sender(Some(tx));
+
+        assert_eq!(api.detect_build_system().await.unwrap(), "cmake");
+        let message = rx.try_recv().expect("detection event");
+        assert!(matches!(
+            message.event,
+            AppEvent::Build(sps2_events::BuildEvent::Diagnostic(
+                sps2_events::BuildDiagnostic::BuildSystemDetected { ref system, .. }
+            )) if system == "cmake"
+        ));
+    }
+
+    #[tokio::test]
+    async fn run_tests_requires_a_build_system() {
+        let temp = TempDir::new().unwrap();
+        let api = BuilderApi::new(
+            temp.path().to_path_buf(),
+            Arc::new(ResourceManager::default()),
+        )
+        .unwrap();
+        let mut env = demo_environment(temp.path());
+
+        assert!(matches!(
+            api.run_tests(&mut env).await,
+            Err(Error::Build(BuildError::NoBuildSystemDetected { .. }))
+        ));
+    }
+
+    #[test]
+    fn go_options_follow_the_subcommand() {
+        let options = GoOptions {
+            tags: vec!["netgo".to_string(), "osusergo".to_string()],
+            ldflags: vec![
+                "-s".to_string(),
+                "-w".to_string(),
+                "-X main.version=1.0".to_string(),
+            ],
+            trimpath: true,
+        };
+        assert_eq!(
+            options.apply_to(&["build".to_string(), "./cmd/tool".to_string()]),
+            [
+                "build",
+                "-tags",
+                "netgo,osusergo",
+                "-ldflags=-s -w -X main.version=1.0",
+                "-trimpath",
+                "./cmd/tool"
+            ]
+        );
+
+        assert_eq!(GoOptions::default().apply_to(&[]), ["-trimpath"]);
+        let plain = GoOptions {
+            trimpath: false,
+            ..GoOptions::default()
+        };
+        assert_eq!(plain.apply_to(&["-v".to_string()]), ["-v"]);
+
+        for command in ["install", "test"] {
+            assert_eq!(
+                GoOptions::default().apply_to(&[command.to_string()]),
+                [command, "-trimpath"]
+            );
+        }
+        let tidy = ["mod".to_string(), "tidy".to_string()];
+        assert_eq!(options.apply_to(&tidy), tidy);
+    }
+
+    #[test]
+    fn fetch_retry_delay_doubles() {
+        assert_eq!(
+            fetch_retry_delay(FETCH_RETRY_BASE, 1),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            fetch_retry_delay(FETCH_RETRY_BASE, 2),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            fetch_retry_delay(FETCH_RETRY_BASE, 3),
+            Duration::from_secs(2)
+        );
+    }
+}
@@ -3,9 +3,10 @@
 use super::context::BuildContext;
 use crate::artifact_qa::run_quality_pipeline;
 use crate::config::BuildConfig;
+use crate::environment::validate_root_dir;
 use crate::packaging::create_and_sign_package;
 use crate::packaging::manifest::generate_sbom_and_manifest;
-use crate::recipe::execute_recipe;
+use crate::recipe::{execute_recipe, RecipeResult};
 use crate::utils::events::send_event;
 use crate::{BuildEnvironment, BuildResult};
 use sps2_errors::Error;
@@ -103,12 +104,23 @@ impl Builder {
         let mut environment = self.setup_build_environment(&context).await?;
 
         // Execute recipe and setup dependencies
-        let (runtime_deps, recipe_metadata, install_requested, qa_pipeline) = self
+        let (recipe_result, recipe_metadata) = self
             .execute_recipe_and_setup_deps(&context, &mut environment)
             .await?;
 
         // Run quality checks
-        run_quality_pipeline(&context, &environment, Some(qa_pipeline)).await?;
+        let patchers = environment
+            .context
+            .patchers
+            .merged_with(&recipe_result.patchers);
+        run_quality_pipeline(
+            &context,
+            &environment,
+            Some(recipe_result.qa_pipeline),
+            &patchers,
+            recipe_result.rpath_style,
+        )
+        .await?;
 
         // If fix_permissions was requested in the recipe, run it now as final step
         if let Some(paths) = &environment.fix_permissions_request {
@@ -148,7 +160,7 @@ impl Builder {
             &self.config,
             &context,
             &environment,
-            runtime_deps,
+            recipe_result.runtime_deps,
             &recipe_metadata,
         )
         .await?;
@@ -164,7 +176,10 @@ impl Builder {
         // Cleanup and finalize
         Self::cleanup_and_finalize(&updated_context, &environment, &package_path);
 
-        Ok(BuildResult::new(package_path).with_install_requested(install_requested))
+        Ok(BuildResult::new(package_path)
+            .with_install_requested(recipe_result.install_requested)
+            .with_steps(recipe_result.steps)
+            .with_test_results(environment.test_results().to_vec()))
     }
 
     /// Setup build environment with full isolation
@@ -175,7 +190,23 @@ impl Builder {
         // Create build environment with full isolation setup
         // Use the configured build_root from BuildConfig (defaults to /opt/pm/build)
         let build_root = self.config.build_root();
-        let mut environment = BuildEnvironment::new(context.clone(), build_root)?;
+        // A missing build root is created by initialize(); an existing one must be usable
+        if build_root.exists() {
+            validate_root_dir(build_root)?;
+        }
+        // Configured per-system job counts apply unless the context sets its own
+        let mut build_context = context.clone();
+        for (system, jobs) in self.config.system_jobs() {
+            build_context
+                .jobs_overrides
+                .entry(system.clone())
+                .or_insert(*jobs);
+        }
+        build_context.patchers = self.config.patchers().merged_with(&context.patchers);
+        let mut environment = BuildEnvironment::new(build_context, build_root)?;
+        if let Some(staging_root) = self.config.staging_root() {
+            environment = environment.with_staging_root(staging_root)?;
+        }
 
         // Configure environment with resolver, store, and net client if available
         if let Some(resolver) = &self.resolver {
@@ -212,17 +243,15 @@ impl Builder {
         &self,
         context: &BuildContext,
         environment: &mut BuildEnvironment,
-    ) -> Result<
-        (
-            Vec<String>,
-            crate::yaml::RecipeMetadata,
-            bool,
-            sps2_types::QaPipelineOverride,
-        ),
-        Error,
-    > {
+    ) -> Result<(RecipeResult, crate::yaml::RecipeMetadata), Error> {
         // Parse YAML recipe for metadata
         let yaml_recipe = crate::recipe::parser::parse_yaml_recipe(&context.recipe_path).await?;
+        for deprecation in &yaml_recipe.metadata.deprecations {
+            send_event(
+                context,
+                AppEvent::General(GeneralEvent::warning(deprecation.clone())),
+            );
+        }
         let recipe_metadata = crate::yaml::RecipeMetadata {
             name: yaml_recipe.metadata.name.clone(),
             version: yaml_recipe.metadata.version.clone(),
@@ -231,6 +260,10 @@ impl Builder {
             license: Some(yaml_recipe.metadata.license.clone()),
             runtime_deps: yaml_recipe.metadata.dependencies.runtime.clone(),
             build_deps: yaml_recipe.metadata.dependencies.build.clone(),
+            weak_deps: yaml_recipe.metadata.dependencies.weak.clone(),
+            conflicts: yaml_recipe.metadata.dependencies.conflicts.clone(),
+            replaces: yaml_recipe.metadata.dependencies.replaces.clone(),
+            runtime_env: yaml_recipe.metadata.runtime_env.clone(),
         };
 
         // Extract build dependencies as PackageSpec
@@ -262,22 +295,48 @@ impl Builder {
             );
         }
 
+        // Fail before any step runs if a required tool is unavailable
+        if !yaml_recipe.metadata.build_tools.is_empty() {
+            let search_path = environment
+                .env_vars()
+                .get("PATH")
+                .map(std::ffi::OsString::from)
+                .or_else(|| std::env::var_os("PATH"))
+                .unwrap_or_default();
+            crate::environment::check_build_requirements(
+                &yaml_recipe.metadata.build_tools,
+                &search_path,
+            )
+            .await?;
+        }
+
         // Use the build config as-is (it already has sps2_config from ops/build.rs)
         let build_config = self.config.clone();
 
         // Now execute the recipe with build dependencies already set up
-        let (runtime_deps, _build_deps, _metadata, install_requested, qa_pipeline) =
-            execute_recipe(&build_config, context, environment).await?;
+        let recipe_result = execute_recipe(&build_config, context, environment).await?;
+        if let Some(step) = recipe_result.failed_step() {
+            let log = step
+                .log_path
+                .as_ref()
+                .map(|path| format!(" (log: {})", path.display()))
+                .unwrap_or_default();
+            send_event(
+                context,
+                AppEvent::General(GeneralEvent::debug(format!(
+                    "{} step `{}` failed after {:.1}s{log}",
+                    step.stage,
+                    step.name,
+                    step.duration.as_secs_f64()
+                ))),
+            );
+        }
+        let recipe_result = recipe_result.into_result()?;
 
         // Note: YAML recipes using staged execution have isolation already applied
         // during the environment configuration stage in staged_executor.rs.
 
-        Ok((
-            runtime_deps,
-            recipe_metadata,
-            install_requested,
-            qa_pipeline,
-        ))
+        Ok((recipe_result, recipe_metadata))
     }
 
     /// Cleanup build environment and finalize
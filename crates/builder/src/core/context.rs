@@ -1,8 +1,15 @@
 //! Build context for package building
 
+use crate::build_systems::MesonWrapMode;
+use crate::packaging::archive::source_date_epoch_from_env;
 use sps2_events::{EventEmitter, EventSender};
-use sps2_types::Version;
-use std::path::PathBuf;
+use sps2_platform::{PlatformContext, PlatformManager};
+use sps2_types::{PatcherSelection, Version};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Default `SOURCE_DATE_EPOCH` (2024-01-01T00:00:00Z) used when none is configured
+pub const DEFAULT_SOURCE_DATE_EPOCH: u64 = 1_704_067_200;
 
 /// Build context for package building
 #[derive(Clone, Debug)]
@@ -25,6 +32,41 @@ pub struct BuildContext {
     pub package_path: Option<PathBuf>,
     /// Optional session identifier used for correlating events.
     pub session_id: Option<String>,
+    /// `SOURCE_DATE_EPOCH` applied to every build subprocess and artifact
+    pub source_date_epoch: Option<u64>,
+    /// Whether build systems run their test phase
+    pub run_tests: bool,
+    /// Parallel job counts for specific build systems (`cmake`, `go`, ...)
+    pub jobs_overrides: HashMap<String, usize>,
+    /// Meson wrap mode; when unset it follows network access
+    pub meson_wrap_mode: Option<MesonWrapMode>,
+    /// Post-validation patcher order and opt-outs
+    pub patchers: PatcherSelection,
+}
+
+/// Commit time of the last commit touching `recipe_path`
+async fn recipe_commit_epoch(recipe_path: &Path) -> Option<u64> {
+    let dir = recipe_path.parent()?;
+    let file_name = recipe_path.file_name()?;
+
+    let platform = PlatformManager::instance().platform();
+    let context = PlatformContext::new(None);
+    let mut cmd = platform.process().create_command("git");
+    cmd.args(["log", "-1", "--format=%ct", "--", file_name.to_str()?]);
+    cmd.current_dir(if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    });
+    let output = platform
+        .process()
+        .execute_command(&context, cmd)
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
 }
 
 impl EventEmitter for BuildContext {
@@ -35,6 +77,9 @@ impl EventEmitter for BuildContext {
 
 impl BuildContext {
     /// Create new build context
+    ///
+    /// A `SOURCE_DATE_EPOCH` exported by the caller seeds the context's epoch;
+    /// [`Self::with_source_date_epoch`] replaces it.
     #[must_use]
     pub fn new(name: String, version: Version, recipe_path: PathBuf, output_dir: PathBuf) -> Self {
         Self {
@@ -47,6 +92,11 @@ impl BuildContext {
             event_sender: None,
             package_path: None,
             session_id: None,
+            source_date_epoch: source_date_epoch_from_env(),
+            run_tests: true,
+            jobs_overrides: HashMap::new(),
+            meson_wrap_mode: None,
+            patchers: PatcherSelection::default(),
         }
     }
 
@@ -78,6 +128,66 @@ impl BuildContext {
         self
     }
 
+    /// Set the `SOURCE_DATE_EPOCH` used for reproducible timestamps
+    #[must_use]
+    pub fn with_source_date_epoch(mut self, epoch: u64) -> Self {
+        self.source_date_epoch = Some(epoch);
+        self
+    }
+
+    /// Enable or disable the build-system test phase (enabled by default)
+    #[must_use]
+    pub fn with_run_tests(mut self, run_tests: bool) -> Self {
+        self.run_tests = run_tests;
+        self
+    }
+
+    /// Override the parallel job count for one build system
+    ///
+    /// Systems without an override use the global job count.
+    #[must_use]
+    pub fn with_jobs_for(mut self, system: impl Into<String>, jobs: usize) -> Self {
+        self.jobs_overrides.insert(system.into(), jobs);
+        self
+    }
+
+    /// Pin the Meson wrap mode instead of deriving it from network access
+    #[must_use]
+    pub fn with_meson_wrap_mode(mut self, wrap_mode: MesonWrapMode) -> Self {
+        self.meson_wrap_mode = Some(wrap_mode);
+        self
+    }
+
+    /// Choose which post-validation patchers run and in what order
+    #[must_use]
+    pub fn with_patchers(mut self, patchers: PatcherSelection) -> Self {
+        self.patchers = patchers;
+        self
+    }
+
+    /// Derive the `SOURCE_DATE_EPOCH` from the recipe's last git commit
+    ///
+    /// Leaves the context unchanged when an epoch is already set (explicitly
+    /// or from an exported `SOURCE_DATE_EPOCH`) or the recipe is not tracked
+    /// by git.
+    pub async fn with_recipe_commit_epoch(self) -> Self {
+        if self.source_date_epoch.is_some() {
+            return self;
+        }
+        match recipe_commit_epoch(&self.recipe_path).await {
+            Some(epoch) => self.with_source_date_epoch(epoch),
+            None => self,
+        }
+    }
+
+    /// Get the effective `SOURCE_DATE_EPOCH`
+    ///
+    /// The epoch set on the context, or the fixed default.
+    #[must_use]
+    pub fn source_date_epoch(&self) -> u64 {
+        self.source_date_epoch.unwrap_or(DEFAULT_SOURCE_DATE_EPOCH)
+    }
+
     /// Retrieve the session identifier or derive a deterministic fallback.
     #[must_use]
     pub fn session_id(&self) -> String {
@@ -1,7 +1,10 @@
 //! Core `BuildEnvironment` struct and construction
 
-use crate::BuildContext;
-use sps2_errors::Error;
+use crate::build_systems::TestResults;
+use crate::stages::record::STEP_LOG_DIR;
+use crate::stages::{BuildStage, BuildStepRecord};
+use crate::{BuildCommandResult, BuildContext};
+use sps2_errors::{BuildError, Error};
 use sps2_events::{AppEvent, EventEmitter, EventSender, GeneralEvent};
 use sps2_install::Installer;
 use sps2_net::NetClient;
@@ -10,6 +13,7 @@ use sps2_store::PackageStore;
 use sps2_types::Version;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 /// Live prefix where packages are installed at runtime
 pub const LIVE_PREFIX: &str = sps2_config::fixed_paths::LIVE_DIR;
@@ -23,6 +27,8 @@ pub struct BuildEnvironment {
     pub(crate) build_prefix: PathBuf,
     /// Staging directory for installation
     pub(crate) staging_dir: PathBuf,
+    /// Configured root the staging directory lives under, if not the build prefix
+    pub(crate) staging_root: Option<PathBuf>,
     /// Environment variables
     pub(crate) env_vars: HashMap<String, String>,
     /// Build metadata from build systems (e.g., Python wheel path)
@@ -39,10 +45,16 @@ pub struct BuildEnvironment {
     pub(crate) with_defaults_called: bool,
     /// Build systems used during the build process
     pub(crate) used_build_systems: HashSet<String>,
+    /// Build system recorded most recently
+    pub(crate) active_build_system: Option<String>,
     /// Fix permissions requests (None if not requested, Some(paths) if requested)
     pub(crate) fix_permissions_request: Option<Vec<String>>,
     /// Current isolation level
     pub(crate) isolation_level: crate::environment::IsolationLevel,
+    /// Records of the recipe steps run so far, in execution order
+    pub(crate) step_records: Vec<BuildStepRecord>,
+    /// Results of each build-system test phase, in execution order
+    pub(crate) test_results: Vec<TestResults>,
 }
 
 impl EventEmitter for BuildEnvironment {
@@ -73,6 +85,7 @@ impl BuildEnvironment {
             context,
             build_prefix,
             staging_dir,
+            staging_root: None,
             env_vars,
             build_metadata: HashMap::new(),
             resolver: None,
@@ -81,8 +94,11 @@ impl BuildEnvironment {
             net: None,
             with_defaults_called: false,
             used_build_systems: HashSet::new(),
+            active_build_system: None,
             fix_permissions_request: None,
             isolation_level: crate::environment::IsolationLevel::default(),
+            step_records: Vec::new(),
+            test_results: Vec::new(),
         })
     }
 
@@ -114,6 +130,27 @@ impl BuildEnvironment {
         self
     }
 
+    /// Stage installed files under `staging_root` instead of the build prefix
+    ///
+    /// The staging directory becomes `<staging_root>/<name>/<version>` and
+    /// `DESTDIR` is updated to match.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `staging_root` is not an existing, writable
+    /// directory.
+    pub fn with_staging_root(mut self, staging_root: &Path) -> Result<Self, Error> {
+        validate_root_dir(staging_root)?;
+        self.staging_dir =
+            Self::get_build_prefix_path(staging_root, &self.context.name, &self.context.version);
+        self.env_vars.insert(
+            "DESTDIR".to_string(),
+            self.staging_dir.display().to_string(),
+        );
+        self.staging_root = Some(staging_root.to_path_buf());
+        Ok(self)
+    }
+
     /// Get staging directory
     #[must_use]
     pub fn staging_dir(&self) -> &Path {
@@ -204,9 +241,52 @@ impl BuildEnvironment {
         &self.build_metadata
     }
 
+    /// Record the outcome of a recipe step started at `started`
+    pub(crate) fn record_step(
+        &mut self,
+        stage: BuildStage,
+        name: String,
+        started: Instant,
+        outcome: &Result<Option<BuildCommandResult>, Error>,
+    ) {
+        let record = BuildStepRecord::capture(
+            self.step_records.len(),
+            stage,
+            name,
+            started.elapsed(),
+            outcome,
+            &self.build_prefix.join(STEP_LOG_DIR),
+        );
+        self.step_records.push(record);
+    }
+
+    /// Records of the recipe steps run so far, in execution order
+    #[must_use]
+    pub fn step_records(&self) -> &[BuildStepRecord] {
+        &self.step_records
+    }
+
+    /// Record the results of a build-system test phase
+    pub(crate) fn record_test_results(&mut self, results: TestResults) {
+        self.test_results.push(results);
+    }
+
+    /// Results of the build-system test phases run so far
+    #[must_use]
+    pub fn test_results(&self) -> &[TestResults] {
+        &self.test_results
+    }
+
     /// Record that a build system was used during the build
     pub fn record_build_system(&mut self, build_system: &str) {
         self.used_build_systems.insert(build_system.to_string());
+        self.active_build_system = Some(build_system.to_string());
+    }
+
+    /// Build system recorded most recently, if any
+    #[must_use]
+    pub fn active_build_system(&self) -> Option<&str> {
+        self.active_build_system.as_deref()
     }
 
     /// Get all build systems used during the build
@@ -252,6 +332,16 @@ impl BuildEnvironment {
         build_root.join(name).join(version.to_string())
     }
 
+    /// Root directories that may contain the staging directory
+    pub(crate) fn staging_roots(&self) -> Vec<PathBuf> {
+        let mut roots = vec![
+            self.build_prefix.clone(),
+            PathBuf::from(sps2_config::fixed_paths::PREFIX), // sps2 system directory
+        ];
+        roots.extend(self.staging_root.clone());
+        roots
+    }
+
     /// Get CPU count for parallel builds
     #[must_use]
     pub(crate) fn cpu_count() -> usize {
@@ -379,3 +469,23 @@ impl BuildEnvironment {
         self.env_vars.insert("NO_PROXY".to_string(), String::new());
     }
 }
+
+/// Check that a configured build or staging root is a writable directory
+///
+/// # Errors
+///
+/// Returns an error if `path` does not exist, is not a directory, or a file
+/// cannot be created in it.
+pub(crate) fn validate_root_dir(path: &Path) -> Result<(), Error> {
+    let invalid = |reason: String| BuildError::InvalidPath {
+        path: path.display().to_string(),
+        reason,
+    };
+
+    let metadata = std::fs::metadata(path).map_err(|e| invalid(e.to_string()))?;
+    if !metadata.is_dir() {
+        return Err(invalid("not a directory".to_string()).into());
+    }
+    tempfile::tempfile_in(path).map_err(|e| invalid(format!("not writable: {e}")))?;
+    Ok(())
+}
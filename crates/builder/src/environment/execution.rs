@@ -8,10 +8,26 @@ use std::collections::HashMap;
 use std::path::Path;
 use uuid::Uuid;
 
+/// Variables expanded in command arguments before execution
+const PATH_PLACEHOLDERS: [&str; 3] = ["DESTDIR", "PREFIX", "BUILD_PREFIX"];
+
 impl BuildEnvironment {
-    /// Convert command arguments to strings (no placeholder replacement needed)
-    fn convert_args_to_strings(args: &[&str]) -> Vec<String> {
-        args.iter().map(|arg| (*arg).to_string()).collect()
+    /// Convert command arguments to strings, expanding path placeholders
+    ///
+    /// `${DESTDIR}`, `${PREFIX}` and `${BUILD_PREFIX}` are replaced with their
+    /// values in `env`, so commands that do not go through a shell still see
+    /// the configured staging directory.
+    fn convert_args_to_strings(args: &[&str], env: &HashMap<String, String>) -> Vec<String> {
+        args.iter()
+            .map(|arg| {
+                PATH_PLACEHOLDERS
+                    .iter()
+                    .fold((*arg).to_string(), |arg, name| match env.get(*name) {
+                        Some(value) => arg.replace(&format!("${{{name}}}"), value),
+                        None => arg,
+                    })
+            })
+            .collect()
     }
 
     /// Get environment variables for execution (no placeholder replacement needed)
@@ -58,9 +74,15 @@ impl BuildEnvironment {
         let mut cmd = platform.process().create_command(program);
 
         // Replace placeholders in command arguments
-        let converted_args = Self::convert_args_to_strings(args);
+        let converted_args = Self::convert_args_to_strings(args, env);
         cmd.args(&converted_args);
 
+        // Pin SOURCE_DATE_EPOCH for reproducible output; an explicit value in `env` wins
+        cmd.env(
+            "SOURCE_DATE_EPOCH",
+            self.context.source_date_epoch().to_string(),
+        );
+
         // Apply explicit environment
         cmd.envs(env);
 
@@ -258,3 +280,62 @@ impl BuildEnvironment {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::demo_environment;
+    use tempfile::TempDir;
+
+    #[test]
+    fn placeholders_use_configured_staging_root() {
+        let build_root = TempDir::new().unwrap();
+        let staging_root = TempDir::new().unwrap();
+        let env = demo_environment(build_root.path())
+            .with_staging_root(staging_root.path())
+            .unwrap();
+
+        let expected_stage = staging_root.path().join("demo").join("1.0.0");
+        assert_eq!(env.staging_dir(), expected_stage);
+
+        let args = BuildEnvironment::convert_args_to_strings(
+            &["${DESTDIR}${PREFIX}/bin", "--name=${NAME}"],
+            &env.get_execution_env(),
+        );
+        assert_eq!(
+            args,
+            [
+                format!(
+                    "{}{}/bin",
+                    expected_stage.display(),
+                    sps2_config::fixed_paths::LIVE_DIR
+                ),
+                "--name=${NAME}".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn commands_see_configured_staging_root() {
+        let build_root = TempDir::new().unwrap();
+        let staging_root = TempDir::new().unwrap();
+        let env = demo_environment(build_root.path())
+            .with_staging_root(staging_root.path())
+            .unwrap();
+
+        let result = env
+            .execute_command("echo", &["${DESTDIR}"], Some(build_root.path()))
+            .await
+            .unwrap();
+        assert_eq!(result.stdout, env.staging_dir().display().to_string());
+    }
+
+    #[test]
+    fn staging_root_must_exist() {
+        let build_root = TempDir::new().unwrap();
+        let missing = build_root.path().join("missing");
+        assert!(demo_environment(build_root.path())
+            .with_staging_root(&missing)
+            .is_err());
+    }
+}
@@ -6,7 +6,6 @@
 
 use super::core::BuildEnvironment;
 use sps2_errors::{BuildError, Error};
-use std::path::PathBuf;
 
 impl BuildEnvironment {
     /// Verify build environment isolation is properly set up
@@ -204,10 +203,7 @@ impl BuildEnvironment {
     /// Verify no access to system paths outside allowed directories
     fn verify_system_path_isolation(&self) -> Result<(), Error> {
         // Check that build directories are within allowed paths
-        let allowed_prefixes = vec![
-            self.build_prefix.clone(),
-            PathBuf::from(sps2_config::fixed_paths::PREFIX), // sps2 system directory
-        ];
+        let allowed_prefixes = self.staging_roots();
 
         // Verify staging directory is isolated
         let mut staging_allowed = false;
@@ -10,9 +10,12 @@ mod directories;
 mod execution;
 mod hermetic;
 mod isolation;
+mod requirements;
 mod types;
 mod variables;
 
 // Re-export public API
+pub(crate) use core::validate_root_dir;
 pub use core::BuildEnvironment;
+pub use requirements::{check_build_requirements, BuildToolRequirement};
 pub use types::{BuildCommandResult, BuildResult, IsolationLevel};
@@ -0,0 +1,262 @@
+//! Pre-build checks for the tools a recipe needs on PATH
+//!
+//! Recipes list tools in `metadata.build_tools` using the same syntax as
+//! dependencies (`cmake>=3.20.0`, `ninja`). Before any build step runs, each
+//! tool is looked up on PATH and, when a version constraint is given, its
+//! `--version` output is matched against the constraint.
+
+use sps2_errors::{BuildError, Error};
+use sps2_types::{package::PackageSpec, Version, VersionSpec};
+use std::ffi::OsStr;
+use std::fmt;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// How long a tool may take to report its version
+const VERSION_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A tool the build expects to find on PATH
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildToolRequirement {
+    /// Executable name
+    pub name: String,
+    /// Accepted versions; any version when unconstrained
+    pub version: VersionSpec,
+}
+
+impl BuildToolRequirement {
+    /// Parse a requirement such as `cmake>=3.20.0`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version constraint is malformed or the name is
+    /// not a bare executable name.
+    pub fn parse(requirement: &str) -> Result<Self, Error> {
+        let spec = PackageSpec::parse(requirement)?;
+        if spec.name.contains(['/', ' ']) {
+            return Err(BuildError::RecipeError {
+                message: format!(
+                    "metadata.build_tools: '{requirement}' must name an executable, not a path"
+                ),
+            }
+            .into());
+        }
+        Ok(Self {
+            name: spec.name,
+            version: spec.version_spec,
+        })
+    }
+}
+
+impl fmt::Display for BuildToolRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.version.is_any() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{}{}", self.name, self.version)
+        }
+    }
+}
+
+/// Why a requirement is not met
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum UnmetRequirement {
+    /// The tool is not on PATH
+    Missing(BuildToolRequirement),
+    /// The tool is present but its version could not be determined
+    UnknownVersion(BuildToolRequirement),
+    /// The tool is present at a version outside the constraint
+    WrongVersion {
+        requirement: BuildToolRequirement,
+        found: Version,
+    },
+}
+
+impl fmt::Display for UnmetRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(req) => write!(f, "{req} (not found on PATH)"),
+            Self::UnknownVersion(req) => write!(f, "{req} (version could not be determined)"),
+            Self::WrongVersion { requirement, found } => {
+                write!(f, "{requirement} (found {found})")
+            }
+        }
+    }
+}
+
+/// Verify that every required build tool is available on `search_path`
+///
+/// All requirements are checked so the error lists everything that is
+/// missing at once.
+///
+/// # Errors
+///
+/// Returns [`BuildError::MissingBuildTools`] listing each unmet requirement,
+/// or a recipe error if a requirement cannot be parsed.
+pub async fn check_build_requirements(
+    requirements: &[String],
+    search_path: &OsStr,
+) -> Result<(), Error> {
+    let mut unmet = Vec::new();
+    for requirement in requirements {
+        let requirement = BuildToolRequirement::parse(requirement)?;
+        if let Some(problem) = check_requirement(requirement, search_path).await {
+            unmet.push(problem.to_string());
+        }
+    }
+
+    if unmet.is_empty() {
+        Ok(())
+    } else {
+        Err(BuildError::MissingBuildTools { tools: unmet }.into())
+    }
+}
+
+async fn check_requirement(
+    requirement: BuildToolRequirement,
+    search_path: &OsStr,
+) -> Option<UnmetRequirement> {
+    let Some(executable) = find_executable(&requirement.name, search_path) else {
+        return Some(UnmetRequirement::Missing(requirement));
+    };
+    if requirement.version.is_any() {
+        return None;
+    }
+
+    match tool_version(&executable).await {
+        Some(found) if requirement.version.matches(&found) => None,
+        Some(found) => Some(UnmetRequirement::WrongVersion { requirement, found }),
+        None => Some(UnmetRequirement::UnknownVersion(requirement)),
+    }
+}
+
+/// First executable file called `name` in the directories of `search_path`
+fn find_executable(name: &str, search_path: &OsStr) -> Option<PathBuf> {
+    std::env::split_paths(search_path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| is_executable(candidate))
+}
+
+fn is_executable(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+/// Ask a tool for its version with `--version`
+async fn tool_version(executable: &Path) -> Option<Version> {
+    let output = tokio::time::timeout(
+        VERSION_PROBE_TIMEOUT,
+        Command::new(executable)
+            .arg("--version")
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+
+    // Some tools print their version on stderr
+    let text = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    parse_tool_version(&text)
+}
+
+/// Extract the first dotted version number from `--version` output
+///
+/// Missing minor or patch components are treated as zero so `3.20` matches
+/// `>=3.20.0`.
+fn parse_tool_version(text: &str) -> Option<Version> {
+    text.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .map(|token| token.trim_matches('.'))
+        .filter(|token| token.contains('.'))
+        .find_map(|token| {
+            let mut parts = token.split('.').map(str::parse::<u64>);
+            let major = parts.next()?.ok()?;
+            let minor = parts.next()?.ok()?;
+            let patch = parts.next().and_then(Result::ok).unwrap_or(0);
+            Some(Version::new(major, minor, patch))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn fake_tool(dir: &Path, name: &str, version_output: &str) {
+        let path = dir.join(name);
+        std::fs::write(&path, format!("#!/bin/sh\necho '{version_output}'\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    fn requirements(items: &[&str]) -> Vec<String> {
+        items.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn version_is_found_in_typical_output() {
+        assert_eq!(
+            parse_tool_version("cmake version 3.28.1\n\nCMake suite"),
+            Some(Version::new(3, 28, 1))
+        );
+        assert_eq!(parse_tool_version("1.4"), Some(Version::new(1, 4, 0)));
+        assert_eq!(
+            parse_tool_version("Apple clang version 15.0.0 (clang-1500.1.0.2.5)"),
+            Some(Version::new(15, 0, 0))
+        );
+        assert_eq!(parse_tool_version("no version here"), None);
+    }
+
+    #[tokio::test]
+    async fn present_tools_pass() {
+        let bin = TempDir::new().unwrap();
+        fake_tool(bin.path(), "cmake", "cmake version 3.28.1");
+        fake_tool(bin.path(), "ninja", "1.11.1");
+
+        check_build_requirements(
+            &requirements(&["cmake>=3.20.0", "ninja"]),
+            bin.path().as_os_str(),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn missing_and_wrong_version_tools_are_all_reported() {
+        let bin = TempDir::new().unwrap();
+        fake_tool(bin.path(), "cmake", "cmake version 3.16.3");
+        fake_tool(bin.path(), "meson", "1.3.0");
+        // Present but not executable
+        std::fs::write(bin.path().join("ninja"), "").unwrap();
+
+        let err = check_build_requirements(
+            &requirements(&["cmake>=3.20.0", "meson>=1.0.0", "ninja", "autoconf"]),
+            bin.path().as_os_str(),
+        )
+        .await
+        .unwrap_err();
+
+        let Error::Build(BuildError::MissingBuildTools { tools }) = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(
+            tools,
+            [
+                "cmake>=3.20.0 (found 3.16.3)",
+                "ninja (not found on PATH)",
+                "autoconf (not found on PATH)",
+            ]
+        );
+    }
+
+    #[test]
+    fn paths_are_not_accepted_as_tool_names() {
+        assert!(BuildToolRequirement::parse("/usr/bin/cmake").is_err());
+        assert!(BuildToolRequirement::parse("cmake>=bogus").is_err());
+    }
+}
@@ -2,6 +2,8 @@
 
 use std::fmt;
 
+use crate::build_systems::TestResults;
+use crate::stages::BuildStepRecord;
 use serde::de::{self, IgnoredAny, MapAccess, Unexpected, Visitor};
 use std::path::PathBuf;
 
@@ -29,6 +31,11 @@ pub struct BuildResult {
     pub build_log: String,
     /// Whether the recipe requested the package be installed after building
     pub install_requested: bool,
+    /// Records of the recipe steps that ran, in execution order
+    pub steps: Vec<BuildStepRecord>,
+    /// Results of each build-system test phase; skipped phases report
+    /// `executed: false`
+    pub test_results: Vec<TestResults>,
 }
 
 impl BuildResult {
@@ -40,6 +47,8 @@ impl BuildResult {
             sbom_files: Vec::new(),
             build_log: String::new(),
             install_requested: false,
+            steps: Vec::new(),
+            test_results: Vec::new(),
         }
     }
 
@@ -59,6 +68,20 @@ impl BuildResult {
         self.install_requested = install_requested;
         self
     }
+
+    /// Set the recipe step records
+    #[must_use]
+    pub fn with_steps(mut self, steps: Vec<BuildStepRecord>) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    /// Set the build-system test results
+    #[must_use]
+    pub fn with_test_results(mut self, test_results: Vec<TestResults>) -> Self {
+        self.test_results = test_results;
+        self
+    }
 }
 
 /// Build isolation level
@@ -15,31 +15,39 @@ mod packaging;
 mod recipe;
 mod security;
 mod stages;
+#[cfg(test)]
+mod test_support;
 mod utils;
 mod validation;
 mod yaml;
 
 pub use build_systems::{
-    detect_build_system, AutotoolsBuildSystem, BuildSystem, BuildSystemConfig, BuildSystemContext,
-    BuildSystemRegistry, CMakeBuildSystem, CargoBuildSystem, GoBuildSystem, MesonBuildSystem,
-    NodeJsBuildSystem, PythonBuildSystem, TestFailure, TestResults,
+    detect_build_system, detect_build_system_with_events, AutotoolsBuildSystem, BuildSystem,
+    BuildSystemConfig, BuildSystemContext, BuildSystemDetection, BuildSystemRegistry,
+    CMakeBuildSystem, CargoBuildSystem, GoBuildSystem, MesonBuildSystem, MesonWrapMode,
+    NinjaBuildSystem, NodeJsBuildSystem, PythonBuildSystem, TestFailure, TestResults, TestTiming,
+    BUILTIN_BUILD_SYSTEMS,
 };
 pub use cache::{
     BuildCache, CacheStatistics, CompilerCache, CompilerCacheType, IncrementalBuildTracker,
     SourceCache,
 };
 pub use config::BuildConfig;
-pub use core::api::BuilderApi;
+pub use core::api::{AutotoolsOptions, BuilderApi, CargoOptions, GoOptions, PatchOptions};
 pub use core::builder::Builder;
-pub use environment::{BuildCommandResult, BuildEnvironment, BuildResult};
+pub use environment::{
+    check_build_requirements, BuildCommandResult, BuildEnvironment, BuildResult,
+    BuildToolRequirement,
+};
 pub use utils::format::{detect_compression_format, CompressionFormatInfo};
 
 // Re-export packaging types
 pub use packaging::archive::{create_deterministic_tar_archive, get_deterministic_timestamp};
 pub use packaging::compression::compress_with_zstd;
 pub use packaging::manifest::generate_sbom_and_manifest;
-pub use packaging::sbom::{SbomFiles, SbomGenerator};
+pub use packaging::sbom::{SbomComponent, SbomFiles, SbomFormat, SbomGenerator};
 pub use packaging::signing::PackageSigner;
+pub use packaging::vulnerability::{Vulnerability, VulnerabilityScanner};
 pub use packaging::{create_and_sign_package, create_package};
 
 // Re-export config types for backward compatibility
@@ -53,6 +61,7 @@ pub use recipe::model::{
     RpathPatchOption, SourceMethod, YamlRecipe,
 };
 pub use recipe::parser::parse_yaml_recipe;
+pub use recipe::RecipeResult;
 
 pub use core::context::BuildContext;
 
@@ -61,3 +70,4 @@ pub use build_plan::BuildPlan;
 pub use security::SecurityContext;
 pub use stages::build::BuildCommand;
 pub use stages::executors::execute_post_step_with_security;
+pub use stages::record::{BuildStage, BuildStepRecord};
@@ -1,12 +1,10 @@
 //! Deterministic TAR archive creation for reproducible builds
 
+use crate::core::context::DEFAULT_SOURCE_DATE_EPOCH;
 use sps2_errors::{BuildError, Error};
 use std::path::{Path, PathBuf};
 use tokio::fs::File;
 
-/// Default deterministic timestamp (Unix epoch) for reproducible builds
-const DETERMINISTIC_TIMESTAMP: u64 = 0;
-
 /// Environment variable for `SOURCE_DATE_EPOCH` (standard for reproducible builds)
 const SOURCE_DATE_EPOCH_VAR: &str = "SOURCE_DATE_EPOCH";
 
@@ -173,13 +171,18 @@ fn add_directory_to_tar_with_timestamp(
 }
 
 /// Get deterministic timestamp for reproducible builds
-/// Uses `SOURCE_DATE_EPOCH` if set, otherwise uses epoch (0)
+/// Uses `SOURCE_DATE_EPOCH` if set, otherwise the same default as
+/// `BuildContext::source_date_epoch`
 #[must_use]
 pub fn get_deterministic_timestamp() -> u64 {
+    source_date_epoch_from_env().unwrap_or(DEFAULT_SOURCE_DATE_EPOCH)
+}
+
+/// `SOURCE_DATE_EPOCH` exported by the caller, if set and valid
+pub(crate) fn source_date_epoch_from_env() -> Option<u64> {
     std::env::var(SOURCE_DATE_EPOCH_VAR)
         .ok()
         .and_then(|val| val.parse::<u64>().ok())
-        .unwrap_or(DETERMINISTIC_TIMESTAMP)
 }
 
 /// Normalize file permissions for deterministic output
@@ -202,3 +205,42 @@ fn normalize_file_permissions(metadata: &std::fs::Metadata) -> u32 {
         0o644 // Default for other file types
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sps2_hash::Hash;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn identical_staging_trees_produce_identical_archives() {
+        let temp = TempDir::new().unwrap();
+        let staging = temp.path().join("staging");
+        std::fs::create_dir_all(staging.join("opt/pm/live/bin")).unwrap();
+        for name in ["zeta", "alpha", "mid"] {
+            std::fs::write(staging.join("opt/pm/live/bin").join(name), name).unwrap();
+        }
+        std::os::unix::fs::symlink("alpha", staging.join("opt/pm/live/bin/link")).unwrap();
+
+        let first = temp.path().join("first.tar");
+        create_deterministic_tar_archive_with_timestamp(&staging, &first, 0)
+            .await
+            .unwrap();
+
+        filetime::set_file_mtime(
+            staging.join("opt/pm/live/bin/mid"),
+            filetime::FileTime::from_unix_time(1_700_000_000, 0),
+        )
+        .unwrap();
+
+        let second = temp.path().join("second.tar");
+        create_deterministic_tar_archive_with_timestamp(&staging, &second, 0)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            Hash::blake3_hash_file(&first).await.unwrap(),
+            Hash::blake3_hash_file(&second).await.unwrap()
+        );
+    }
+}
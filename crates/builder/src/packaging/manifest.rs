@@ -84,7 +84,8 @@ pub async fn generate_sbom(
         sbom_config,
         environment.package_name().to_string(),
         environment.context.version.to_string(),
-    );
+    )
+    .with_source_date_epoch(environment.context.source_date_epoch());
 
     let staging_dir = environment.staging_dir();
     let sbom_dir = environment.build_prefix().join("sbom");
@@ -111,6 +112,7 @@ pub fn create_manifest(
     let sbom_info = sbom_files.spdx_hash.as_ref().map(|spdx_hash| SbomInfo {
         spdx: spdx_hash.clone(),
         cyclonedx: sbom_files.cyclonedx_hash.clone(),
+        spdx_tv: sbom_files.spdx_tv_hash.clone(),
     });
 
     // Create compression info
@@ -142,9 +144,13 @@ pub fn create_manifest(
         dependencies: Dependencies {
             runtime: runtime_deps,
             build: Vec::new(), // Build deps not included in final manifest
+            weak: recipe_metadata.weak_deps.clone(),
+            conflicts: recipe_metadata.conflicts.clone(),
+            replaces: recipe_metadata.replaces.clone(),
         },
         sbom: sbom_info,
         python: python_metadata,
+        runtime_env: recipe_metadata.runtime_env.clone(),
     }
 }
 
@@ -173,3 +179,28 @@ fn create_python_metadata_from_env(
         executables,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{demo_context, demo_environment};
+
+    #[test]
+    fn tag_value_sbom_hash_is_recorded() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut sbom_files = SbomFiles::new();
+        sbom_files.spdx_hash = Some("aa".to_string());
+        sbom_files.spdx_tv_hash = Some("bb".to_string());
+
+        let manifest = create_manifest(
+            &demo_context(temp.path()),
+            Vec::new(),
+            &sbom_files,
+            &RecipeMetadata::default(),
+            &demo_environment(temp.path()),
+        );
+        let sbom = manifest.sbom.unwrap();
+        assert_eq!(sbom.spdx, "aa");
+        assert_eq!(sbom.spdx_tv.as_deref(), Some("bb"));
+    }
+}
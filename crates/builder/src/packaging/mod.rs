@@ -5,11 +5,13 @@ pub mod compression;
 pub mod manifest;
 pub mod sbom;
 pub mod signing;
+pub mod vulnerability;
 
-use self::archive::create_deterministic_tar_archive;
+use self::archive::create_deterministic_tar_archive_with_timestamp;
 use self::compression::compress_with_zstd;
 use self::sbom::{SbomFiles, SbomGenerator};
 use self::signing::PackageSigner;
+use self::vulnerability::{at_or_above, VulnerabilityScanner};
 use crate::utils::events::send_event;
 use crate::utils::fileops::copy_directory_strip_live_prefix;
 use crate::{BuildConfig, BuildContext, BuildEnvironment};
@@ -81,7 +83,8 @@ async fn generate_sbom_files(
         config.packaging_settings().sbom.clone(),
         manifest.package.name.clone(),
         manifest.version().unwrap().to_string(),
-    );
+    )
+    .with_source_date_epoch(context.source_date_epoch());
 
     let sbom_files = sbom_generator
         .generate_sbom(
@@ -98,9 +101,79 @@ async fn generate_sbom_files(
         }),
     );
 
+    if config.packaging_settings().sbom.scan_vulnerabilities {
+        scan_sbom_vulnerabilities(config, context, &sbom_files).await?;
+    }
+
     Ok(sbom_files)
 }
 
+/// Scan the generated SBOM with grype, failing the build on findings at or
+/// above the configured severity
+///
+/// A missing grype only produces a warning, so builds keep working on hosts
+/// without it.
+async fn scan_sbom_vulnerabilities(
+    config: &BuildConfig,
+    context: &BuildContext,
+    sbom_files: &SbomFiles,
+) -> Result<(), Error> {
+    let Some(sbom_path) = sbom_files
+        .cyclonedx_path
+        .as_ref()
+        .or(sbom_files.spdx_path.as_ref())
+    else {
+        return Ok(());
+    };
+
+    let scanner = VulnerabilityScanner::new();
+    if !scanner.check_grype_available().await? {
+        send_event(
+            context,
+            AppEvent::General(GeneralEvent::warning(
+                "grype not found - skipping SBOM vulnerability scan",
+            )),
+        );
+        return Ok(());
+    }
+
+    let vulnerabilities = scanner.scan(sbom_path).await?;
+    for vulnerability in &vulnerabilities {
+        send_event(
+            context,
+            AppEvent::General(GeneralEvent::warning(format!(
+                "{} ({:?}) in {}{}",
+                vulnerability.id,
+                vulnerability.severity,
+                vulnerability.package,
+                vulnerability
+                    .fixed_version
+                    .as_ref()
+                    .map(|fixed| format!(", fixed in {fixed}"))
+                    .unwrap_or_default()
+            ))),
+        );
+    }
+
+    if let Some(threshold) = config.packaging_settings().sbom.fail_on_severity {
+        let blocking = at_or_above(&vulnerabilities, threshold);
+        if !blocking.is_empty() {
+            let ids: Vec<&str> = blocking.iter().map(|v| v.id.as_str()).collect();
+            return Err(BuildError::SecurityVulnerability {
+                scanner: "grype".to_string(),
+                message: format!(
+                    "{} vulnerabilities at or above {threshold:?}: {}",
+                    blocking.len(),
+                    ids.join(", ")
+                ),
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
 /// Create the final package
 ///
 /// # Errors
@@ -184,6 +257,11 @@ pub async fn create_sp_package(
         fs::copy(cdx_path, &dst_path).await?;
     }
 
+    if let Some(spdx_tv_path) = &sbom_files.spdx_tv_path {
+        let dst_path = package_temp_dir.join("sbom.spdx");
+        fs::copy(spdx_tv_path, &dst_path).await?;
+    }
+
     // Step 3: Copy staging directory contents as package files
     send_event(
         context,
@@ -224,10 +302,13 @@ pub async fn create_sp_package(
 
     let tar_path = package_temp_dir.join("package.tar");
 
+    // Same epoch the build environment exported as SOURCE_DATE_EPOCH
+    let timestamp = context.source_date_epoch();
+
     // Add timeout for tar creation to prevent hanging
     let tar_result = tokio::time::timeout(
         std::time::Duration::from_secs(30),
-        create_deterministic_tar_archive(&package_temp_dir, &tar_path),
+        create_deterministic_tar_archive_with_timestamp(&package_temp_dir, &tar_path, timestamp),
     )
     .await;
 
@@ -398,3 +479,117 @@ async fn create_python_package_structure(
         executables,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::DEFAULT_SOURCE_DATE_EPOCH;
+    use crate::test_support::demo_context;
+    use tempfile::TempDir;
+
+    /// Context with no epoch, whatever the test process exported
+    fn context(recipe_path: PathBuf, output_dir: &Path) -> BuildContext {
+        BuildContext {
+            recipe_path,
+            source_date_epoch: None,
+            ..demo_context(output_dir)
+        }
+    }
+
+    /// Stage the same files under `root`, with mtimes at `mtime`
+    fn stage(root: &Path, mtime: i64) -> PathBuf {
+        let staging = root.join("staging");
+        let bin = staging.join("opt/pm/live/bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::write(bin.join("demo"), "#!/bin/sh\n").unwrap();
+        filetime::set_file_mtime(
+            bin.join("demo"),
+            filetime::FileTime::from_unix_time(mtime, 0),
+        )
+        .unwrap();
+        staging
+    }
+
+    async fn package(context: &BuildContext, staging: &Path) -> Vec<u8> {
+        let output = staging.with_file_name("demo.sp");
+        create_sp_package(
+            &BuildConfig::default(),
+            context,
+            staging,
+            &output,
+            "[package]\nname = \"demo\"\n",
+            &SbomFiles::new(),
+        )
+        .await
+        .unwrap();
+        std::fs::read(output).unwrap()
+    }
+
+    #[tokio::test]
+    async fn packages_are_reproducible_and_honor_source_date_epoch() {
+        let temp = TempDir::new().unwrap();
+        let ctx = context(temp.path().join("recipe.yml"), temp.path());
+
+        // Same content staged at different times gives the same bytes
+        let first = package(&ctx, &stage(&temp.path().join("a"), 1_600_000_000)).await;
+        let second = package(&ctx, &stage(&temp.path().join("b"), 1_700_000_000)).await;
+        assert_eq!(first, second);
+
+        // Without any epoch the archive uses the same default as the build
+        let defaulted = ctx
+            .clone()
+            .with_source_date_epoch(DEFAULT_SOURCE_DATE_EPOCH);
+        let default_pinned = package(&defaulted, &stage(&temp.path().join("e"), 0)).await;
+        assert_eq!(first, default_pinned);
+
+        // The context's epoch sets every archive mtime
+        let pinned = ctx.clone().with_source_date_epoch(1_650_000_000);
+        let explicit = package(&pinned, &stage(&temp.path().join("c"), 0)).await;
+        assert_ne!(explicit, first);
+        let again = package(&pinned, &stage(&temp.path().join("d"), 1_600_000_000)).await;
+        assert_eq!(explicit, again);
+        assert_eq!(ctx.source_date_epoch(), DEFAULT_SOURCE_DATE_EPOCH);
+    }
+
+    #[tokio::test]
+    async fn epoch_is_derived_from_the_recipe_commit() {
+        let temp = TempDir::new().unwrap();
+        let recipe = temp.path().join("demo.yml");
+        std::fs::write(&recipe, "metadata: {}\n").unwrap();
+
+        // Untracked recipes keep the default
+        let ctx = context(recipe.clone(), temp.path())
+            .with_recipe_commit_epoch()
+            .await;
+        assert_eq!(ctx.source_date_epoch, None);
+
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(temp.path())
+                .env("GIT_AUTHOR_NAME", "sps2")
+                .env("GIT_AUTHOR_EMAIL", "sps2@example.com")
+                .env("GIT_COMMITTER_NAME", "sps2")
+                .env("GIT_COMMITTER_EMAIL", "sps2@example.com")
+                .env("GIT_COMMITTER_DATE", "1690000000 +0000")
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["add", "demo.yml"]);
+        git(&["commit", "-q", "-m", "demo"]);
+
+        let ctx = context(recipe, temp.path())
+            .with_recipe_commit_epoch()
+            .await;
+        assert_eq!(ctx.source_date_epoch(), 1_690_000_000);
+
+        // An explicit epoch is kept
+        let ctx = ctx
+            .with_source_date_epoch(5)
+            .with_recipe_commit_epoch()
+            .await;
+        assert_eq!(ctx.source_date_epoch(), 5);
+    }
+}
@@ -1,9 +1,13 @@
 //! SBOM generation using Syft
 
+use crate::core::context::DEFAULT_SOURCE_DATE_EPOCH;
+use serde::Serialize;
+use serde_json::Value;
 use sps2_config::builder::SbomSettings;
 use sps2_errors::{BuildError, Error};
 use sps2_hash::Hash;
 use sps2_platform::{PlatformContext, PlatformManager};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::path::{Path, PathBuf};
 
 /// SBOM generator using Syft
@@ -16,6 +20,8 @@ pub struct SbomGenerator {
     package_name: String,
     /// Package version from the recipe metadata
     package_version: String,
+    /// `SOURCE_DATE_EPOCH` passed to Syft for deterministic timestamps
+    source_date_epoch: u64,
 }
 
 /// Generated SBOM files
@@ -29,6 +35,10 @@ pub struct SbomFiles {
     pub cyclonedx_path: Option<PathBuf>,
     /// `CycloneDX` file hash
     pub cyclonedx_hash: Option<String>,
+    /// SPDX tag-value file path
+    pub spdx_tv_path: Option<PathBuf>,
+    /// SPDX tag-value file hash
+    pub spdx_tv_hash: Option<String>,
 }
 
 impl SbomFiles {
@@ -40,13 +50,15 @@ impl SbomFiles {
             spdx_hash: None,
             cyclonedx_path: None,
             cyclonedx_hash: None,
+            spdx_tv_path: None,
+            spdx_tv_hash: None,
         }
     }
 
     /// Check if any SBOM files were generated
     #[must_use]
     pub fn has_files(&self) -> bool {
-        self.spdx_path.is_some() || self.cyclonedx_path.is_some()
+        self.spdx_path.is_some() || self.cyclonedx_path.is_some() || self.spdx_tv_path.is_some()
     }
 }
 
@@ -56,6 +68,32 @@ impl Default for SbomFiles {
     }
 }
 
+/// SBOM format a component was listed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SbomFormat {
+    /// SPDX JSON
+    Spdx,
+    /// `CycloneDX` JSON
+    CycloneDx,
+}
+
+/// A package listed in the generated SBOMs, independent of format
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct SbomComponent {
+    /// Package name
+    pub name: String,
+    /// Package version, if the SBOM records one
+    pub version: Option<String>,
+    /// Package URL, if the SBOM records one
+    pub purl: Option<String>,
+    /// Formats that listed this component
+    pub formats: Vec<SbomFormat>,
+}
+
+/// Identity of a component when merging: (name, version, purl)
+type ComponentKey = (String, Option<String>, Option<String>);
+
 impl SbomGenerator {
     /// Create new SBOM generator.
     ///
@@ -71,6 +109,7 @@ impl SbomGenerator {
             settings,
             package_name,
             package_version,
+            source_date_epoch: DEFAULT_SOURCE_DATE_EPOCH,
         }
     }
 
@@ -81,6 +120,23 @@ impl SbomGenerator {
         self
     }
 
+    /// Use the build's `SOURCE_DATE_EPOCH` instead of the fixed default.
+    #[must_use]
+    pub fn with_source_date_epoch(mut self, epoch: u64) -> Self {
+        self.source_date_epoch = epoch;
+        self
+    }
+
+    /// Format the configured epoch as an RFC 3339 UTC timestamp for Syft
+    fn creation_timestamp(&self) -> String {
+        i64::try_from(self.source_date_epoch)
+            .ok()
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+            .unwrap_or_default()
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string()
+    }
+
     /// Check if Syft is available
     ///
     /// # Errors
@@ -120,9 +176,10 @@ impl SbomGenerator {
         }
 
         let mut sbom_files = SbomFiles::new();
+        let all = self.settings.format == "all";
 
         // Generate SPDX format
-        if self.settings.format == "spdx-json" || self.settings.format == "all" {
+        if self.settings.format == "spdx-json" || all {
             let spdx_path = output_dir.join("sbom.spdx.json");
             self.generate_spdx(source_dir, &spdx_path).await?;
 
@@ -132,7 +189,7 @@ impl SbomGenerator {
         }
 
         // Generate CycloneDX format
-        if self.settings.format == "cyclone-dx" || self.settings.format == "all" {
+        if self.settings.format == "cyclone-dx" || all {
             let cdx_path = output_dir.join("sbom.cdx.json");
             self.generate_cyclonedx(source_dir, &cdx_path).await?;
 
@@ -141,6 +198,17 @@ impl SbomGenerator {
             sbom_files.cyclonedx_hash = Some(hash.to_hex());
         }
 
+        // Generate SPDX tag-value format
+        if self.settings.format == "spdx-tag-value" || all {
+            let spdx_tv_path = output_dir.join("sbom.spdx");
+            self.generate_spdx_tag_value(source_dir, &spdx_tv_path)
+                .await?;
+
+            let hash = Hash::hash_file(&spdx_tv_path).await?;
+            sbom_files.spdx_tv_path = Some(spdx_tv_path);
+            sbom_files.spdx_tv_hash = Some(hash.to_hex());
+        }
+
         // Verify deterministic output by regenerating
         // Deterministic verification disabled due to syft non-determinism (upstream issue)
         // self.verify_deterministic(&sbom_files, source_dir).await?;
@@ -148,57 +216,53 @@ impl SbomGenerator {
         Ok(sbom_files)
     }
 
-    /// Generate SPDX format SBOM
+    /// Merge the components of every generated SBOM into one list
+    ///
+    /// Components are deduplicated by name, version and purl, and sorted by
+    /// the same. Each records the formats that listed it. SPDX packages the
+    /// document describes (the scanned directory itself) and `CycloneDX`
+    /// file components are left out so both formats report the same kind
+    /// of entry.
     ///
     /// # Errors
     ///
-    /// Returns an error if Syft execution fails or returns a non-zero exit code.
-    async fn generate_spdx(&self, source_dir: &Path, output_path: &Path) -> Result<(), Error> {
-        let mut args = vec![
-            "scan".to_string(),
-            "-o".to_string(),
-            format!("spdx-json={}", output_path.display()),
-            source_dir.display().to_string(),
-        ];
+    /// Returns an error if an SBOM file cannot be read or is not valid JSON.
+    pub async fn merge_components(files: &SbomFiles) -> Result<Vec<SbomComponent>, Error> {
+        let mut merged: BTreeMap<ComponentKey, BTreeSet<SbomFormat>> = BTreeMap::new();
 
-        // Add package name and version
-        args.push("--source-name".to_string());
-        args.push(self.package_name.clone());
-        args.push("--source-version".to_string());
-        args.push(self.package_version.clone());
-
-        // Add exclusions
-        for pattern in &self.settings.exclusions {
-            args.push("--exclude".to_string());
-            args.push(pattern.clone());
+        if let Some(path) = &files.spdx_path {
+            for key in spdx_components(&read_sbom_json(path).await?) {
+                merged.entry(key).or_default().insert(SbomFormat::Spdx);
+            }
         }
-
-        // Use platform abstraction for process execution
-        let platform = PlatformManager::instance().platform();
-        let context = PlatformContext::new(None);
-
-        let mut cmd = platform.process().create_command(&self.syft_path);
-        cmd.args(&args);
-        cmd.env("SYFT_SPDX_CREATION_INFO_CREATED", "2024-01-01T00:00:00Z");
-        cmd.env("SOURCE_DATE_EPOCH", "1704067200");
-        cmd.env("SYFT_DISABLE_METADATA_TIMESTAMP", "true");
-
-        let output = platform
-            .process()
-            .execute_command(&context, cmd)
-            .await
-            .map_err(|e| BuildError::SbomError {
-                message: format!("failed to run syft: {e}"),
-            })?;
-
-        if !output.status.success() {
-            return Err(BuildError::SbomError {
-                message: format!("syft failed: {}", String::from_utf8_lossy(&output.stderr)),
+        if let Some(path) = &files.cyclonedx_path {
+            let mut keys = Vec::new();
+            if let Some(components) = read_sbom_json(path).await?.get("components") {
+                collect_cyclonedx_components(components, &mut keys);
+            }
+            for key in keys {
+                merged.entry(key).or_default().insert(SbomFormat::CycloneDx);
             }
-            .into());
         }
 
-        Ok(())
+        Ok(merged
+            .into_iter()
+            .map(|((name, version, purl), formats)| SbomComponent {
+                name,
+                version,
+                purl,
+                formats: formats.into_iter().collect(),
+            })
+            .collect())
+    }
+
+    /// Generate SPDX format SBOM
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Syft execution fails or returns a non-zero exit code.
+    async fn generate_spdx(&self, source_dir: &Path, output_path: &Path) -> Result<(), Error> {
+        self.run_syft(source_dir, "spdx-json", output_path).await
     }
 
     /// Generate `CycloneDX` format SBOM
@@ -207,10 +271,39 @@ impl SbomGenerator {
     ///
     /// Returns an error if Syft execution fails or returns a non-zero exit code.
     async fn generate_cyclonedx(&self, source_dir: &Path, output_path: &Path) -> Result<(), Error> {
+        self.run_syft(source_dir, "cyclonedx-json", output_path)
+            .await
+    }
+
+    /// Generate SPDX tag-value format SBOM
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Syft execution fails or returns a non-zero exit code.
+    async fn generate_spdx_tag_value(
+        &self,
+        source_dir: &Path,
+        output_path: &Path,
+    ) -> Result<(), Error> {
+        self.run_syft(source_dir, "spdx-tag-value", output_path)
+            .await
+    }
+
+    /// Run `syft scan` writing `syft_format` output to `output_path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Syft execution fails or returns a non-zero exit code.
+    async fn run_syft(
+        &self,
+        source_dir: &Path,
+        syft_format: &str,
+        output_path: &Path,
+    ) -> Result<(), Error> {
         let mut args = vec![
             "scan".to_string(),
             "-o".to_string(),
-            format!("cyclonedx-json={}", output_path.display()),
+            format!("{syft_format}={}", output_path.display()),
             source_dir.display().to_string(),
         ];
 
@@ -232,8 +325,8 @@ impl SbomGenerator {
 
         let mut cmd = platform.process().create_command(&self.syft_path);
         cmd.args(&args);
-        cmd.env("SYFT_SPDX_CREATION_INFO_CREATED", "2024-01-01T00:00:00Z");
-        cmd.env("SOURCE_DATE_EPOCH", "1704067200");
+        cmd.env("SYFT_SPDX_CREATION_INFO_CREATED", self.creation_timestamp());
+        cmd.env("SOURCE_DATE_EPOCH", self.source_date_epoch.to_string());
         cmd.env("SYFT_DISABLE_METADATA_TIMESTAMP", "true");
 
         let output = platform
@@ -319,3 +412,172 @@ impl SbomGenerator {
         Ok(())
     }
 }
+
+/// Read and parse an SBOM JSON file
+async fn read_sbom_json(path: &Path) -> Result<Value, Error> {
+    let content = tokio::fs::read_to_string(path).await?;
+    serde_json::from_str(&content).map_err(|e| {
+        BuildError::SbomError {
+            message: format!("failed to parse {}: {e}", path.display()),
+        }
+        .into()
+    })
+}
+
+/// Non-empty string field of a JSON object
+fn string_field(value: &Value, field: &str) -> Option<String> {
+    value
+        .get(field)
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Package keys from an SPDX document, without the described root packages
+fn spdx_components(document: &Value) -> Vec<ComponentKey> {
+    let mut described: HashSet<&str> = document
+        .get("documentDescribes")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    described.extend(
+        document
+            .get("relationships")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|rel| {
+                rel.get("spdxElementId").and_then(Value::as_str) == Some("SPDXRef-DOCUMENT")
+                    && rel.get("relationshipType").and_then(Value::as_str) == Some("DESCRIBES")
+            })
+            .filter_map(|rel| rel.get("relatedSpdxElement").and_then(Value::as_str)),
+    );
+
+    document
+        .get("packages")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|package| {
+            package
+                .get("SPDXID")
+                .and_then(Value::as_str)
+                .is_none_or(|id| !described.contains(id))
+        })
+        .filter_map(|package| {
+            let name = string_field(package, "name")?;
+            let purl = package
+                .get("externalRefs")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .find(|r| r.get("referenceType").and_then(Value::as_str) == Some("purl"))
+                .and_then(|r| string_field(r, "referenceLocator"));
+            Some((name, string_field(package, "versionInfo"), purl))
+        })
+        .collect()
+}
+
+/// Component keys from a `CycloneDX` component list, including nested ones
+fn collect_cyclonedx_components(components: &Value, keys: &mut Vec<ComponentKey>) {
+    for component in components.as_array().into_iter().flatten() {
+        let is_file = component.get("type").and_then(Value::as_str) == Some("file");
+        if let (false, Some(name)) = (is_file, string_field(component, "name")) {
+            keys.push((
+                name,
+                string_field(component, "version"),
+                string_field(component, "purl"),
+            ));
+        }
+        if let Some(nested) = component.get("components") {
+            collect_cyclonedx_components(nested, keys);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn tag_value_output_counts_as_sbom_files() {
+        let mut files = SbomFiles::new();
+        assert!(!files.has_files());
+        files.spdx_tv_path = Some(PathBuf::from("sbom.spdx"));
+        assert!(files.has_files());
+    }
+
+    #[tokio::test]
+    async fn merges_spdx_and_cyclonedx_components() {
+        let temp = TempDir::new().unwrap();
+        let spdx_path = temp.path().join("sbom.spdx.json");
+        let cdx_path = temp.path().join("sbom.cdx.json");
+        std::fs::write(
+            &spdx_path,
+            serde_json::json!({
+                "packages": [
+                    {"SPDXID": "SPDXRef-root", "name": "demo", "versionInfo": "1.0"},
+                    {
+                        "SPDXID": "SPDXRef-zlib",
+                        "name": "zlib",
+                        "versionInfo": "1.3.1",
+                        "externalRefs": [{
+                            "referenceType": "purl",
+                            "referenceLocator": "pkg:generic/zlib@1.3.1"
+                        }]
+                    },
+                    {"SPDXID": "SPDXRef-six", "name": "six", "versionInfo": "1.16.0"}
+                ],
+                "relationships": [{
+                    "spdxElementId": "SPDXRef-DOCUMENT",
+                    "relationshipType": "DESCRIBES",
+                    "relatedSpdxElement": "SPDXRef-root"
+                }]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        std::fs::write(
+            &cdx_path,
+            serde_json::json!({
+                "components": [
+                    {"type": "library", "name": "zlib", "version": "1.3.1",
+                     "purl": "pkg:generic/zlib@1.3.1"},
+                    {"type": "file", "name": "/opt/pm/live/lib/libz.dylib"},
+                    {"type": "library", "name": "bzip2", "version": "1.0.8",
+                     "components": [{"type": "library", "name": "bzip2-libs"}]}
+                ]
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let files = SbomFiles {
+            spdx_path: Some(spdx_path),
+            cyclonedx_path: Some(cdx_path),
+            ..SbomFiles::new()
+        };
+        let components = SbomGenerator::merge_components(&files).await.unwrap();
+
+        let summary: Vec<(&str, &[SbomFormat])> = components
+            .iter()
+            .map(|c| (c.name.as_str(), c.formats.as_slice()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("bzip2", &[SbomFormat::CycloneDx][..]),
+                ("bzip2-libs", &[SbomFormat::CycloneDx][..]),
+                ("six", &[SbomFormat::Spdx][..]),
+                ("zlib", &[SbomFormat::Spdx, SbomFormat::CycloneDx][..]),
+            ]
+        );
+        assert_eq!(
+            components[3].purl.as_deref(),
+            Some("pkg:generic/zlib@1.3.1")
+        );
+    }
+}
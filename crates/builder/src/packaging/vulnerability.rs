@@ -0,0 +1,218 @@
+//! Vulnerability scanning of generated SBOMs using grype
+
+use serde_json::Value;
+use sps2_config::builder::Severity;
+use sps2_errors::{BuildError, Error};
+use sps2_platform::{PlatformContext, PlatformManager};
+use std::path::Path;
+
+/// Vulnerability scanner using grype
+pub struct VulnerabilityScanner {
+    /// Grype binary path
+    grype_path: String,
+}
+
+/// A known vulnerability matched against a package in the SBOM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vulnerability {
+    /// Vulnerability identifier (`CVE-...`, `GHSA-...`)
+    pub id: String,
+    /// Severity from grype's vulnerability database
+    pub severity: Severity,
+    /// Affected package as `name@version`, or just the name if unversioned
+    pub package: String,
+    /// First version with a fix, if one is known
+    pub fixed_version: Option<String>,
+}
+
+impl VulnerabilityScanner {
+    /// Create new vulnerability scanner
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            grype_path: "grype".to_string(),
+        }
+    }
+
+    /// Create with custom grype path.
+    #[must_use]
+    pub fn with_grype_path(mut self, grype_path: String) -> Self {
+        self.grype_path = grype_path;
+        self
+    }
+
+    /// Check if grype is available
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if grype cannot be executed.
+    pub async fn check_grype_available(&self) -> Result<bool, Error> {
+        let platform = PlatformManager::instance().platform();
+        let context = PlatformContext::new(None);
+
+        let mut cmd = platform.process().create_command(&self.grype_path);
+        cmd.arg("version");
+
+        let output = platform.process().execute_command(&context, cmd).await;
+
+        match output {
+            Ok(output) => Ok(output.status.success()),
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Scan an SPDX or `CycloneDX` SBOM for known vulnerabilities
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if grype fails to run or its report cannot be parsed.
+    pub async fn scan(&self, sbom_path: &Path) -> Result<Vec<Vulnerability>, Error> {
+        let platform = PlatformManager::instance().platform();
+        let context = PlatformContext::new(None);
+
+        let mut cmd = platform.process().create_command(&self.grype_path);
+        cmd.args([
+            format!("sbom:{}", sbom_path.display()),
+            "-o".to_string(),
+            "json".to_string(),
+        ]);
+
+        let output = platform
+            .process()
+            .execute_command(&context, cmd)
+            .await
+            .map_err(|e| BuildError::SbomError {
+                message: format!("failed to run grype: {e}"),
+            })?;
+
+        if !output.status.success() {
+            return Err(BuildError::SbomError {
+                message: format!("grype failed: {}", String::from_utf8_lossy(&output.stderr)),
+            }
+            .into());
+        }
+
+        parse_grype_report(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+impl Default for VulnerabilityScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Vulnerabilities at or above `threshold`
+#[must_use]
+pub fn at_or_above(vulnerabilities: &[Vulnerability], threshold: Severity) -> Vec<&Vulnerability> {
+    vulnerabilities
+        .iter()
+        .filter(|vulnerability| vulnerability.severity >= threshold)
+        .collect()
+}
+
+/// Parse the matches of a grype JSON report
+fn parse_grype_report(report: &str) -> Result<Vec<Vulnerability>, Error> {
+    let report: Value = serde_json::from_str(report).map_err(|e| BuildError::SbomError {
+        message: format!("failed to parse grype report: {e}"),
+    })?;
+
+    let matches = report
+        .get("matches")
+        .and_then(Value::as_array)
+        .map_or(&[][..], Vec::as_slice);
+
+    Ok(matches
+        .iter()
+        .filter_map(|entry| {
+            let vulnerability = entry.get("vulnerability")?;
+            let artifact = entry.get("artifact")?;
+            let id = vulnerability.get("id")?.as_str()?.to_string();
+            let name = artifact.get("name")?.as_str()?;
+            let package = match artifact.get("version").and_then(Value::as_str) {
+                Some(version) if !version.is_empty() => format!("{name}@{version}"),
+                _ => name.to_string(),
+            };
+            let fixed_version = vulnerability
+                .get("fix")
+                .and_then(|fix| fix.get("versions"))
+                .and_then(Value::as_array)
+                .and_then(|versions| versions.first())
+                .and_then(Value::as_str)
+                .map(str::to_string);
+
+            Some(Vulnerability {
+                id,
+                severity: parse_severity(vulnerability.get("severity").and_then(Value::as_str)),
+                package,
+                fixed_version,
+            })
+        })
+        .collect())
+}
+
+/// Map grype's capitalized severity names onto [`Severity`]
+fn parse_severity(severity: Option<&str>) -> Severity {
+    match severity.map(str::to_ascii_lowercase).as_deref() {
+        Some("negligible") => Severity::Negligible,
+        Some("low") => Severity::Low,
+        Some("medium") => Severity::Medium,
+        Some("high") => Severity::High,
+        Some("critical") => Severity::Critical,
+        _ => Severity::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_grype_matches() {
+        let report = serde_json::json!({
+            "matches": [
+                {
+                    "vulnerability": {
+                        "id": "CVE-2022-37434",
+                        "severity": "Critical",
+                        "fix": {"versions": ["1.2.12"], "state": "fixed"}
+                    },
+                    "artifact": {"name": "zlib", "version": "1.2.11"}
+                },
+                {
+                    "vulnerability": {
+                        "id": "GHSA-xxxx-yyyy-zzzz",
+                        "severity": "Negligible",
+                        "fix": {"versions": [], "state": "not-fixed"}
+                    },
+                    "artifact": {"name": "six", "version": ""}
+                }
+            ]
+        })
+        .to_string();
+
+        let vulnerabilities = parse_grype_report(&report).unwrap();
+        assert_eq!(
+            vulnerabilities,
+            [
+                Vulnerability {
+                    id: "CVE-2022-37434".to_string(),
+                    severity: Severity::Critical,
+                    package: "zlib@1.2.11".to_string(),
+                    fixed_version: Some("1.2.12".to_string()),
+                },
+                Vulnerability {
+                    id: "GHSA-xxxx-yyyy-zzzz".to_string(),
+                    severity: Severity::Negligible,
+                    package: "six".to_string(),
+                    fixed_version: None,
+                },
+            ]
+        );
+
+        let blocking = at_or_above(&vulnerabilities, Severity::High);
+        assert_eq!(blocking.len(), 1);
+        assert_eq!(blocking[0].id, "CVE-2022-37434");
+        assert!(parse_grype_report("not json").is_err());
+    }
+}
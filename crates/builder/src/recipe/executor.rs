@@ -1,25 +1,115 @@
 //! YAML recipe execution
 
+use crate::stages::BuildStepRecord;
 use crate::yaml::RecipeMetadata;
 use crate::{BuildConfig, BuildContext, BuildEnvironment};
 use sps2_errors::Error;
 use sps2_types::package::PackageSpec;
+use sps2_types::{PatcherSelection, QaPipelineOverride, RpathStyle};
 
-/// Execute the YAML recipe and return dependencies, metadata, install request status, and `qa_pipeline`
+/// Outcome of executing a recipe's stages
+#[derive(Debug, Clone)]
+pub struct RecipeResult {
+    /// Runtime dependencies declared by the recipe
+    pub runtime_deps: Vec<String>,
+    /// Build dependencies declared by the recipe
+    pub build_deps: Vec<PackageSpec>,
+    /// Recipe metadata
+    pub metadata: RecipeMetadata,
+    /// Whether the recipe requested the package be installed after building
+    pub install_requested: bool,
+    /// QA pipeline override requested by the recipe
+    pub qa_pipeline: QaPipelineOverride,
+    /// QA patcher order and opt-outs requested by the recipe
+    pub patchers: PatcherSelection,
+    /// Rpath style requested by the recipe
+    pub rpath_style: RpathStyle,
+    /// One record per step that ran, in execution order
+    pub steps: Vec<BuildStepRecord>,
+    /// Error that stopped the recipe, if a stage failed
+    pub error: Option<Error>,
+}
+
+impl RecipeResult {
+    /// The first step that failed, if any
+    #[must_use]
+    pub fn failed_step(&self) -> Option<&BuildStepRecord> {
+        self.steps.iter().find(|step| !step.success)
+    }
+
+    /// Return the error that stopped the recipe, or the result if it completed
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecipeResult::error`] when a stage failed.
+    pub fn into_result(self) -> Result<Self, Error> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self),
+        }
+    }
+}
+
+/// Execute the YAML recipe and return its dependencies, metadata and step records
+///
+/// A failing stage does not make this return `Err`: the result keeps the
+/// records of every step up to and including the failed one, with the error
+/// in [`RecipeResult::error`]. Errors before any stage runs, such as an
+/// unparseable recipe, are returned directly.
+///
+/// # Errors
+///
+/// Returns an error if the recipe cannot be parsed or planned.
 pub async fn execute_recipe(
     config: &BuildConfig,
     context: &BuildContext,
     environment: &mut BuildEnvironment,
-) -> Result<
-    (
-        Vec<String>,
-        Vec<PackageSpec>,
-        RecipeMetadata,
-        bool,
-        sps2_types::QaPipelineOverride,
-    ),
-    Error,
-> {
+) -> Result<RecipeResult, Error> {
     // Execute YAML recipe using staged execution
     crate::utils::executor::execute_staged_build(config, context, environment).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stages::BuildStage;
+    use crate::test_support::demo_context;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn failed_steps_are_reported_with_the_result() {
+        let temp = TempDir::new().unwrap();
+        let recipe = temp.path().join("demo.yml");
+        std::fs::write(
+            &recipe,
+            "metadata:\n  name: demo\n  version: 1.0.0\n  description: Demo\n  license: MIT\n\n\
+             source:\n  local:\n    path: ./missing\n\n\
+             build:\n  steps:\n    - command: echo never\n",
+        )
+        .unwrap();
+        let context = BuildContext {
+            recipe_path: recipe,
+            ..demo_context(temp.path())
+        };
+        let mut environment = BuildEnvironment::new(context.clone(), temp.path()).unwrap();
+
+        let config = BuildConfig::default().with_sps2_config(sps2_config::Config::default());
+        let result = execute_recipe(&config, &context, &mut environment)
+            .await
+            .unwrap();
+
+        let names: Vec<_> = result.steps.iter().map(|step| step.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["cleanup", "copy"],
+            "steps after the failure must not run"
+        );
+        let failed = result.failed_step().unwrap();
+        assert_eq!(
+            (failed.stage, failed.name.as_str()),
+            (BuildStage::Source, "copy")
+        );
+        assert!(failed.error.as_ref().unwrap().contains("./missing"));
+        assert!(result.into_result().is_err());
+    }
+}
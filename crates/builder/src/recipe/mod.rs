@@ -5,4 +5,4 @@ pub mod model;
 pub mod parser;
 
 // Re-export commonly used items
-pub use executor::execute_recipe;
+pub use executor::{execute_recipe, RecipeResult};
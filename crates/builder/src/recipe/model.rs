@@ -3,9 +3,11 @@
 //! This module provides a declarative YAML-based recipe format that replaces
 //! the Starlark-based system with proper staged execution.
 
+use crate::build_systems::MesonWrapMode;
 use crate::environment::IsolationLevel;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sps2_types::RpathStyle;
+use std::collections::{BTreeMap, HashMap};
 
 /// Complete YAML recipe structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +41,10 @@ pub struct YamlRecipe {
 /// Package metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Metadata {
+    /// Metadata schema version the recipe was written against
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
+
     pub name: String,
     pub version: String,
     pub description: String,
@@ -49,6 +55,22 @@ pub struct Metadata {
 
     #[serde(default)]
     pub dependencies: Dependencies,
+
+    /// Tools that must be on PATH before the build starts, e.g. `cmake>=3.20.0`
+    #[serde(default)]
+    pub build_tools: Vec<String>,
+
+    /// Environment variables to export while the package is installed
+    #[serde(default)]
+    pub runtime_env: BTreeMap<String, String>,
+
+    /// Deprecation warnings for legacy fields the recipe still uses
+    #[serde(skip)]
+    pub deprecations: Vec<String>,
+}
+
+fn default_schema_version() -> u32 {
+    1
 }
 
 /// Dependencies specification
@@ -59,6 +81,20 @@ pub struct Dependencies {
 
     #[serde(default)]
     pub build: Vec<String>,
+
+    /// Optional runtime dependencies, only installed alongside this package
+    /// when they are already part of the resolution or explicitly requested
+    #[serde(default)]
+    pub weak: Vec<String>,
+
+    /// Packages that can never be installed alongside this package
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+
+    /// Packages this package supersedes; files they also provide are not
+    /// treated as conflicts when the resolver allows it
+    #[serde(default)]
+    pub replaces: Vec<String>,
 }
 
 /// Environment setup stage
@@ -76,6 +112,15 @@ pub struct Environment {
     #[serde(default)]
     pub network: bool,
 
+    /// Run build-system test phases
+    #[serde(default = "default_run_tests")]
+    pub run_tests: bool,
+
+    /// Meson wrap mode (`nodownload`, `nofallback`, `forcefallback`,
+    /// `nopromote`); defaults to `nodownload` without network access
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meson_wrap_mode: Option<MesonWrapMode>,
+
     /// Environment variables
     #[serde(default)]
     pub variables: HashMap<String, String>,
@@ -85,12 +130,18 @@ fn default_isolation() -> IsolationLevel {
     IsolationLevel::Default
 }
 
+fn default_run_tests() -> bool {
+    true
+}
+
 impl Default for Environment {
     fn default() -> Self {
         Self {
             isolation: default_isolation(),
             defaults: false,
             network: false,
+            run_tests: default_run_tests(),
+            meson_wrap_mode: None,
             variables: HashMap::new(),
         }
     }
@@ -139,6 +190,9 @@ pub struct GitSource {
     pub url: String,
     #[serde(rename = "ref")]
     pub git_ref: String,
+    /// Commits of history to clone (default: 1; 0 clones the full history)
+    #[serde(default)]
+    pub depth: Option<u32>,
 }
 
 /// Fetch source specification
@@ -150,6 +204,18 @@ pub struct FetchSource {
     /// Where to extract relative to build directory (optional)
     #[serde(default)]
     pub extract_to: Option<String>,
+    /// Leading path components to strip when extracting (default: 1 for
+    /// tarballs, a single top-level directory for zip archives)
+    #[serde(default)]
+    pub strip_components: Option<usize>,
+    /// Extract the download after fetching (default: true); when false the
+    /// archive is left as downloaded for the recipe to use directly
+    #[serde(default = "default_extract")]
+    pub extract: bool,
+}
+
+fn default_extract() -> bool {
+    true
 }
 
 /// Checksum specification
@@ -234,6 +300,10 @@ pub struct Post {
     #[serde(default)]
     pub qa_pipeline: sps2_types::QaPipelineOverride,
 
+    /// Which QA patchers run and in what order
+    #[serde(default)]
+    pub patchers: sps2_types::PatcherSelection,
+
     /// Custom post-processing commands
     #[serde(default)]
     pub commands: Vec<PostCommand>,
@@ -276,6 +346,17 @@ pub enum RpathPatchOption {
     Skip,
 }
 
+impl RpathPatchOption {
+    /// Rpath style for patchers run on the recipe's output
+    #[must_use]
+    pub fn style(&self) -> RpathStyle {
+        match self {
+            Self::Absolute => RpathStyle::Absolute,
+            Self::Default | Self::Skip => RpathStyle::Modern,
+        }
+    }
+}
+
 /// Installation behavior
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Install {
@@ -354,4 +435,86 @@ post:
             "aarch64-apple-darwin24"
         );
     }
+
+    #[test]
+    fn run_tests_defaults_on_and_can_be_disabled() {
+        let yaml = r"
+metadata:
+  name: zlib
+  version: 1.3.1
+  description: Compression library
+  license: Zlib
+
+source:
+  local:
+    path: ./src
+
+build:
+  system: cmake
+";
+        let recipe: YamlRecipe = serde_yaml2::from_str(yaml).unwrap();
+        assert!(recipe.environment.run_tests);
+
+        let yaml = yaml.replace("build:", "environment:\n  run_tests: false\n\nbuild:");
+        let recipe: YamlRecipe = serde_yaml2::from_str(&yaml).unwrap();
+        assert!(!recipe.environment.run_tests);
+    }
+
+    #[test]
+    fn meson_wrap_mode_is_validated() {
+        let yaml = r"
+metadata:
+  name: glib
+  version: 2.80.0
+  description: Core application library
+  license: LGPL-2.1-or-later
+
+environment:
+  meson_wrap_mode: nofallback
+
+source:
+  local:
+    path: ./src
+
+build:
+  system: meson
+";
+        let recipe: YamlRecipe = serde_yaml2::from_str(yaml).unwrap();
+        assert_eq!(
+            recipe.environment.meson_wrap_mode,
+            Some(MesonWrapMode::NoFallback)
+        );
+
+        let yaml = yaml.replace("nofallback", "sometimes");
+        assert!(serde_yaml2::from_str::<YamlRecipe>(&yaml).is_err());
+    }
+
+    #[test]
+    fn fetch_extract_defaults_on_and_can_be_disabled() {
+        let yaml = r"
+metadata:
+  name: zlib
+  version: 1.3.1
+  description: Compression library
+  license: Zlib
+
+source:
+  fetch:
+    url: https://example.com/zlib-1.3.1.tar.gz
+
+build:
+  system: cmake
+";
+        let extract = |yaml: &str| {
+            let recipe: YamlRecipe = serde_yaml2::from_str(yaml).unwrap();
+            match recipe.source.method {
+                Some(SourceMethod::Fetch { fetch }) => fetch.extract,
+                other => panic!("expected a fetch source, got {other:?}"),
+            }
+        };
+        assert!(extract(yaml));
+
+        let yaml = yaml.replace(".tar.gz\n", ".tar.gz\n    extract: false\n");
+        assert!(!extract(&yaml));
+    }
 }
@@ -1,10 +1,63 @@
 //! YAML recipe parser with validation and variable expansion
 
 use super::model::{Build, ParsedStep, PostCommand, PostOption, YamlRecipe};
+use serde::de::IgnoredAny;
+use serde::Deserialize;
 use sps2_errors::{BuildError, Error};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
+/// Newest recipe metadata schema version understood by this sps2
+pub const RECIPE_SCHEMA_VERSION: u32 = 1;
+
+/// Required and known `metadata` fields for a schema version
+struct MetadataSchema {
+    required: &'static [&'static str],
+    known: &'static [&'static str],
+    /// Legacy fields that are accepted but ignored, with their replacement
+    deprecated: &'static [(&'static str, &'static str)],
+}
+
+/// Look up the metadata schema for a version, if this sps2 supports it
+fn metadata_schema(version: u32) -> Option<MetadataSchema> {
+    match version {
+        1 => Some(MetadataSchema {
+            required: &["name", "version", "description", "license"],
+            known: &[
+                "name",
+                "version",
+                "description",
+                "license",
+                "homepage",
+                "dependencies",
+                "build_tools",
+                "runtime_env",
+            ],
+            deprecated: &[
+                ("build_deps", "dependencies.build"),
+                ("runtime_deps", "dependencies.runtime"),
+            ],
+        }),
+        _ => None,
+    }
+}
+
+/// Untyped view of the recipe, used to check `metadata` field names
+#[derive(Deserialize)]
+struct RawRecipe {
+    #[serde(default)]
+    metadata: RawMetadata,
+}
+
+#[derive(Default, Deserialize)]
+struct RawMetadata {
+    schema_version: Option<u32>,
+    // Only the field names matter here
+    #[allow(clippy::zero_sized_map_values)]
+    #[serde(flatten)]
+    fields: BTreeMap<String, IgnoredAny>,
+}
+
 /// Parse a YAML recipe from a file
 ///
 /// # Errors
@@ -33,10 +86,15 @@ pub async fn parse_yaml_recipe(path: &Path) -> Result<YamlRecipe, Error> {
 /// - Required fields are missing
 /// - Validation fails
 pub fn parse_yaml_recipe_from_string(content: &str) -> Result<YamlRecipe, Error> {
+    // Check metadata against its schema first so missing or unknown fields
+    // get precise errors instead of a generic deserialization failure
+    let deprecations = validate_metadata_schema(content)?;
+
     let mut recipe: YamlRecipe =
         serde_yaml2::from_str(content).map_err(|e| BuildError::RecipeError {
             message: format!("failed to parse YAML: {e}"),
         })?;
+    recipe.metadata.deprecations = deprecations;
 
     // Validate the recipe
     validate_recipe(&recipe)?;
@@ -47,6 +105,74 @@ pub fn parse_yaml_recipe_from_string(content: &str) -> Result<YamlRecipe, Error>
     Ok(recipe)
 }
 
+/// Validate recipe metadata fields against the declared schema version
+///
+/// Returns a warning for each deprecated field the recipe uses.
+fn validate_metadata_schema(content: &str) -> Result<Vec<String>, Error> {
+    let raw: RawRecipe = serde_yaml2::from_str(content).map_err(|e| BuildError::RecipeError {
+        message: format!("failed to parse YAML: {e}"),
+    })?;
+
+    let version = raw.metadata.schema_version.unwrap_or(1);
+
+    let Some(schema) = metadata_schema(version) else {
+        let message = if version > RECIPE_SCHEMA_VERSION {
+            format!(
+                "recipe requires a newer sps2 (metadata schema version {version}, \
+                 this sps2 supports up to {RECIPE_SCHEMA_VERSION})"
+            )
+        } else {
+            format!("unsupported metadata schema version {version}")
+        };
+        return Err(BuildError::RecipeError { message }.into());
+    };
+
+    let missing: Vec<&str> = schema
+        .required
+        .iter()
+        .copied()
+        .filter(|field| !raw.metadata.fields.contains_key(*field))
+        .collect();
+    if !missing.is_empty() {
+        return Err(BuildError::RecipeError {
+            message: format!(
+                "metadata is missing required field(s) for schema version {version}: {}",
+                missing.join(", ")
+            ),
+        }
+        .into());
+    }
+
+    let unknown: Vec<&str> = raw
+        .metadata
+        .fields
+        .keys()
+        .map(String::as_str)
+        .filter(|field| {
+            !schema.known.contains(field)
+                && !schema.deprecated.iter().any(|(name, _)| name == field)
+        })
+        .collect();
+    if !unknown.is_empty() {
+        return Err(BuildError::RecipeError {
+            message: format!(
+                "metadata has unknown field(s) for schema version {version}: {}",
+                unknown.join(", ")
+            ),
+        }
+        .into());
+    }
+
+    Ok(schema
+        .deprecated
+        .iter()
+        .filter(|(name, _)| raw.metadata.fields.contains_key(*name))
+        .map(|(name, replacement)| {
+            format!("metadata.{name} is deprecated and ignored; use metadata.{replacement}")
+        })
+        .collect())
+}
+
 /// Validate a parsed recipe
 fn validate_recipe(recipe: &YamlRecipe) -> Result<(), Error> {
     // Validate metadata
@@ -64,6 +190,23 @@ fn validate_recipe(recipe: &YamlRecipe) -> Result<(), Error> {
         .into());
     }
 
+    for (name, value) in &recipe.metadata.runtime_env {
+        if let Some(problem) = sps2_types::runtime_env_var_error(name, value) {
+            return Err(BuildError::RecipeError {
+                message: format!("metadata.runtime_env: {problem}"),
+            }
+            .into());
+        }
+    }
+
+    for tool in &recipe.metadata.build_tools {
+        crate::environment::BuildToolRequirement::parse(tool).map_err(|e| {
+            BuildError::RecipeError {
+                message: format!("metadata.build_tools: invalid requirement '{tool}': {e}"),
+            }
+        })?;
+    }
+
     // Validate build stage
     match &recipe.build {
         Build::System { system, args: _ } => {
@@ -114,6 +257,11 @@ fn expand_variables(recipe: &mut YamlRecipe) {
         context.insert(key.clone(), value.clone());
     }
 
+    // Expand variables in runtime environment values (e.g. ${PREFIX})
+    for value in recipe.metadata.runtime_env.values_mut() {
+        *value = expand_string(value, &context);
+    }
+
     // Expand variables in build steps
     match &mut recipe.build {
         Build::System { system: _, args } => {
@@ -215,3 +363,109 @@ fn expand_build_step(step: &mut ParsedStep, context: &HashMap<String, String>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe_with_metadata(metadata: &str) -> String {
+        format!(
+            "metadata:\n{metadata}\nsource:\n  fetch:\n    url: https://example.com/hello-1.0.tar.gz\n\nbuild:\n  system: autotools\n"
+        )
+    }
+
+    fn error_message(result: Result<YamlRecipe, Error>) -> String {
+        match result {
+            Err(Error::Build(BuildError::RecipeError { message })) => message,
+            other => panic!("expected recipe error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn valid_recipe_passes_schema_validation() {
+        let yaml = recipe_with_metadata(
+            "  schema_version: 1\n  name: hello\n  version: \"1.0\"\n  description: Greeter\n  license: MIT\n  dependencies:\n    runtime:\n      - zlib",
+        );
+        let recipe = parse_yaml_recipe_from_string(&yaml).unwrap();
+        assert_eq!(recipe.metadata.schema_version, 1);
+        assert_eq!(recipe.metadata.dependencies.runtime, vec!["zlib"]);
+    }
+
+    #[test]
+    fn missing_and_unknown_fields_are_named() {
+        let missing = recipe_with_metadata("  name: hello\n  version: \"1.0\"\n  license: MIT");
+        let message = error_message(parse_yaml_recipe_from_string(&missing));
+        assert!(message.contains("missing required field(s)"), "{message}");
+        assert!(message.ends_with(": description"), "{message}");
+
+        let unknown = recipe_with_metadata(
+            "  name: hello\n  version: \"1.0\"\n  description: Greeter\n  license: MIT\n  maintainer: someone",
+        );
+        let message = error_message(parse_yaml_recipe_from_string(&unknown));
+        assert!(
+            message.ends_with("unknown field(s) for schema version 1: maintainer"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn build_tools_are_validated() {
+        let yaml = recipe_with_metadata(
+            "  name: hello\n  version: \"1.0\"\n  description: Greeter\n  license: MIT\n  build_tools:\n    - cmake>=3.20.0\n    - ninja",
+        );
+        let recipe = parse_yaml_recipe_from_string(&yaml).unwrap();
+        assert_eq!(recipe.metadata.build_tools, vec!["cmake>=3.20.0", "ninja"]);
+
+        let yaml = recipe_with_metadata(
+            "  name: hello\n  version: \"1.0\"\n  description: Greeter\n  license: MIT\n  build_tools:\n    - cmake>=three",
+        );
+        let message = error_message(parse_yaml_recipe_from_string(&yaml));
+        assert!(message.starts_with("metadata.build_tools"), "{message}");
+    }
+
+    #[test]
+    fn runtime_env_is_expanded_and_loader_variables_rejected() {
+        let yaml = recipe_with_metadata(
+            "  name: gettext\n  version: \"0.22\"\n  description: i18n\n  license: GPL-3.0\n  runtime_env:\n    GETTEXTDATADIR: ${PREFIX}/share/gettext",
+        );
+        let recipe = parse_yaml_recipe_from_string(&yaml).unwrap();
+        assert_eq!(
+            recipe.metadata.runtime_env["GETTEXTDATADIR"],
+            format!("{}/share/gettext", sps2_config::fixed_paths::LIVE_DIR)
+        );
+
+        let yaml = recipe_with_metadata(
+            "  name: evil\n  version: \"1.0\"\n  description: x\n  license: MIT\n  runtime_env:\n    LD_PRELOAD: /tmp/evil.dylib",
+        );
+        let message = error_message(parse_yaml_recipe_from_string(&yaml));
+        assert!(message.contains("LD_PRELOAD"), "{message}");
+    }
+
+    #[test]
+    fn legacy_dependency_fields_are_accepted_with_a_warning() {
+        let yaml = recipe_with_metadata(
+            "  name: hello\n  version: \"1.0\"\n  description: Greeter\n  license: MIT\n  build_deps:\n    - pkgconf\n  runtime_deps: []",
+        );
+        let recipe = parse_yaml_recipe_from_string(&yaml).unwrap();
+        assert!(recipe.metadata.dependencies.build.is_empty());
+        assert_eq!(
+            recipe.metadata.deprecations,
+            [
+                "metadata.build_deps is deprecated and ignored; use metadata.dependencies.build",
+                "metadata.runtime_deps is deprecated and ignored; use metadata.dependencies.runtime",
+            ]
+        );
+    }
+
+    #[test]
+    fn future_schema_version_requires_newer_sps2() {
+        let yaml = recipe_with_metadata(
+            "  schema_version: 99\n  name: hello\n  version: \"1.0\"\n  description: Greeter\n  license: MIT",
+        );
+        let message = error_message(parse_yaml_recipe_from_string(&yaml));
+        assert!(
+            message.starts_with("recipe requires a newer sps2"),
+            "{message}"
+        );
+    }
+}
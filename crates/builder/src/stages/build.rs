@@ -36,5 +36,24 @@ pub enum BuildCommand {
     Command { program: String, args: Vec<String> },
 }
 
+impl BuildCommand {
+    /// Short name identifying the command in step records
+    #[must_use]
+    pub fn name(&self) -> String {
+        match self {
+            Self::Configure { .. } => "configure".to_string(),
+            Self::Make { .. } => "make".to_string(),
+            Self::Autotools { .. } => "autotools".to_string(),
+            Self::Cmake { .. } => "cmake".to_string(),
+            Self::Meson { .. } => "meson".to_string(),
+            Self::Cargo { .. } => "cargo".to_string(),
+            Self::Go { .. } => "go".to_string(),
+            Self::Python { .. } => "python".to_string(),
+            Self::NodeJs { .. } => "nodejs".to_string(),
+            Self::Command { program, .. } => format!("command {program}"),
+        }
+    }
+}
+
 // Note: ParsedBuild is recipe::model::Build
 // Note: ParsedStep is recipe::model::ParsedStep
@@ -1,18 +1,19 @@
 //! Stage-specific execution functions
 
 use crate::security::SecurityContext;
-use crate::stages::{BuildCommand, EnvironmentStep, PostStep, SourceStep};
+use crate::stages::{BuildCommand, BuildStage, EnvironmentStep, PostStep, SourceStep};
 use crate::utils::events::send_event;
 use crate::{BuildCommandResult, BuildContext, BuildEnvironment, BuilderApi};
 use sps2_errors::Error;
 use sps2_events::{AppEvent, GeneralEvent};
 use std::path::Path;
+use std::time::Instant;
 use tokio::fs;
 
 /// Check if a file is an archive that should be extracted
 fn is_archive(path: &Path) -> bool {
     if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-        matches!(ext, "gz" | "tgz" | "bz2" | "xz" | "zip")
+        matches!(ext, "gz" | "tgz" | "bz2" | "xz" | "zst" | "tzst" | "zip")
     } else {
         // For files without extensions (like GitHub API downloads), check the file content
         use std::fs::File;
@@ -33,6 +34,10 @@ fn is_archive(path: &Path) -> bool {
                 if magic[0] == 0x42 && magic[1] == 0x5a {
                     return true;
                 }
+                // Check for zstd magic number (28 b5 2f fd)
+                if magic == [0x28, 0xb5, 0x2f, 0xfd] {
+                    return true;
+                }
             }
         }
         false
@@ -40,74 +45,103 @@ fn is_archive(path: &Path) -> bool {
 }
 
 /// Execute a source step
+///
+/// Returns the command output for steps that run a command (patches).
 pub async fn execute_source_step(
     step: &SourceStep,
     api: &mut BuilderApi,
     environment: &mut BuildEnvironment,
-) -> Result<(), Error> {
+) -> Result<Option<BuildCommandResult>, Error> {
     match step {
         SourceStep::Cleanup => {
             cleanup_directories(api, environment).await?;
         }
-        SourceStep::Fetch { url, extract_to } => {
+        SourceStep::Fetch {
+            url,
+            extract_to,
+            strip_components,
+            extract,
+        } => {
             let download_path = api.fetch(url).await?;
             // Extract immediately after download
-            if is_archive(&download_path) {
-                api.extract_single_download(&download_path, extract_to.as_deref())
-                    .await?;
+            if *extract && is_archive(&download_path) {
+                api.extract_single_download_with_options(
+                    &download_path,
+                    extract_to.as_deref(),
+                    *strip_components,
+                )
+                .await?;
             }
         }
         SourceStep::FetchMd5 {
             url,
             md5,
             extract_to,
+            strip_components,
+            extract,
         } => {
             let download_path = api.fetch_md5(url, md5).await?;
             // Extract immediately after download and verification
-            if is_archive(&download_path) {
-                api.extract_single_download(&download_path, extract_to.as_deref())
-                    .await?;
+            if *extract && is_archive(&download_path) {
+                api.extract_single_download_with_options(
+                    &download_path,
+                    extract_to.as_deref(),
+                    *strip_components,
+                )
+                .await?;
             }
         }
         SourceStep::FetchSha256 {
             url,
             sha256,
             extract_to,
+            strip_components,
+            extract,
         } => {
             let download_path = api.fetch_sha256(url, sha256).await?;
             // Extract immediately after download and verification
-            if is_archive(&download_path) {
-                api.extract_single_download(&download_path, extract_to.as_deref())
-                    .await?;
+            if *extract && is_archive(&download_path) {
+                api.extract_single_download_with_options(
+                    &download_path,
+                    extract_to.as_deref(),
+                    *strip_components,
+                )
+                .await?;
             }
         }
         SourceStep::FetchBlake3 {
             url,
             blake3,
             extract_to,
+            strip_components,
+            extract,
         } => {
-            let download_path = api.fetch_blake3(url, blake3).await?;
+            let download_path = api.fetch_with_hash(url, blake3).await?;
             // Extract immediately after download and verification
-            if is_archive(&download_path) {
-                api.extract_single_download(&download_path, extract_to.as_deref())
-                    .await?;
+            if *extract && is_archive(&download_path) {
+                api.extract_single_download_with_options(
+                    &download_path,
+                    extract_to.as_deref(),
+                    *strip_components,
+                )
+                .await?;
             }
         }
         SourceStep::Extract { extract_to } => {
             api.extract_downloads_to(extract_to.as_deref()).await?;
         }
-        SourceStep::Git { url, ref_ } => {
-            api.git(url, ref_).await?;
+        SourceStep::Git { url, ref_, depth } => {
+            api.git(url, ref_, *depth).await?;
         }
         SourceStep::Copy { src_path } => {
             api.copy(src_path.as_deref(), &environment.context).await?;
         }
         SourceStep::ApplyPatch { path } => {
             let patch_path = environment.build_prefix().join("src").join(path);
-            api.apply_patch(&patch_path, environment).await?;
+            return api.apply_patch(&patch_path, environment).await.map(Some);
         }
     }
-    Ok(())
+    Ok(None)
 }
 
 /// Execute a build command
@@ -115,40 +149,21 @@ pub async fn execute_build_command(
     command: &BuildCommand,
     api: &mut BuilderApi,
     environment: &mut BuildEnvironment,
-) -> Result<(), Error> {
+) -> Result<BuildCommandResult, Error> {
     match command {
-        BuildCommand::Configure { args } => {
-            api.configure(args, environment).await?;
-        }
-        BuildCommand::Make { args } => {
-            api.make(args, environment).await?;
-        }
-        BuildCommand::Autotools { args } => {
-            api.autotools(args, environment).await?;
-        }
-        BuildCommand::Cmake { args } => {
-            api.cmake(args, environment).await?;
-        }
-        BuildCommand::Meson { args } => {
-            api.meson(args, environment).await?;
-        }
-        BuildCommand::Cargo { args } => {
-            api.cargo(args, environment).await?;
-        }
-        BuildCommand::Go { args } => {
-            api.go(args, environment).await?;
-        }
-        BuildCommand::Python { args } => {
-            api.python(args, environment).await?;
-        }
-        BuildCommand::NodeJs { args } => {
-            api.nodejs(args, environment).await?;
-        }
+        BuildCommand::Configure { args } => api.configure(args, environment).await,
+        BuildCommand::Make { args } => api.make(args, environment).await,
+        BuildCommand::Autotools { args } => api.autotools(args, environment).await,
+        BuildCommand::Cmake { args } => api.cmake(args, environment).await,
+        BuildCommand::Meson { args } => api.meson(args, environment).await,
+        BuildCommand::Cargo { args } => api.cargo(args, environment).await,
+        BuildCommand::Go { args } => api.go(args, environment).await,
+        BuildCommand::Python { args } => api.python(args, environment).await,
+        BuildCommand::NodeJs { args } => api.nodejs(args, environment).await,
         BuildCommand::Command { program, args } => {
-            execute_command(program, args, api, environment).await?;
+            execute_command(program, args, api, environment).await
         }
     }
-    Ok(())
 }
 
 /// Execute a build command with security context
@@ -158,7 +173,7 @@ pub async fn execute_build_command_with_security(
     environment: &mut BuildEnvironment,
     security_context: &mut SecurityContext,
     sps2_config: Option<&sps2_config::Config>,
-) -> Result<(), Error> {
+) -> Result<BuildCommandResult, Error> {
     match command {
         BuildCommand::Command { program, args } => {
             // For shell commands, validate through security context
@@ -184,18 +199,17 @@ pub async fn execute_build_command_with_security(
                 }
 
                 // Execute the validated command
-                execute_command(program, args, api, environment).await?;
+                execute_command(program, args, api, environment).await
             } else {
                 // For direct commands, validate and execute
                 let full_cmd = format!("{} {}", program, args.join(" "));
                 security_context.execute_command(&full_cmd)?;
-                execute_command(program, args, api, environment).await?;
+                execute_command(program, args, api, environment).await
             }
         }
         // For build system commands, pass through normally (they're already sandboxed)
-        _ => execute_build_command(command, api, environment).await?,
+        _ => execute_build_command(command, api, environment).await,
     }
-    Ok(())
 }
 
 /// Execute a post-processing step
@@ -203,19 +217,16 @@ pub async fn execute_post_step(
     step: &PostStep,
     api: &mut BuilderApi,
     environment: &mut BuildEnvironment,
-) -> Result<(), Error> {
+) -> Result<BuildCommandResult, Error> {
     match step {
         PostStep::PatchRpaths { style, paths } => {
-            api.patch_rpaths(*style, paths, environment).await?;
-        }
-        PostStep::FixPermissions { paths } => {
-            api.fix_permissions(paths, environment)?;
+            api.patch_rpaths(*style, paths, environment).await
         }
+        PostStep::FixPermissions { paths } => api.fix_permissions(paths, environment),
         PostStep::Command { program, args } => {
-            execute_command(program, args, api, environment).await?;
+            execute_command(program, args, api, environment).await
         }
     }
-    Ok(())
 }
 
 /// Execute a post-processing step with security context
@@ -233,7 +244,7 @@ pub async fn execute_post_step_with_security(
     environment: &mut BuildEnvironment,
     security_context: &mut SecurityContext,
     sps2_config: Option<&sps2_config::Config>,
-) -> Result<(), Error> {
+) -> Result<BuildCommandResult, Error> {
     match step {
         PostStep::Command { program, args } => {
             // Validate command through security context
@@ -257,17 +268,16 @@ pub async fn execute_post_step_with_security(
                     }
                 }
 
-                execute_command(program, args, api, environment).await?;
+                execute_command(program, args, api, environment).await
             } else {
                 let full_cmd = format!("{} {}", program, args.join(" "));
                 security_context.execute_command(&full_cmd)?;
-                execute_command(program, args, api, environment).await?;
+                execute_command(program, args, api, environment).await
             }
         }
         // Other post steps don't need security validation
-        _ => execute_post_step(step, api, environment).await?,
+        _ => execute_post_step(step, api, environment).await,
     }
-    Ok(())
 }
 
 /// Execute an environment step
@@ -358,17 +368,127 @@ pub async fn execute_build_commands_list_with_security(
     sps2_config: Option<&sps2_config::Config>,
 ) -> Result<(), Error> {
     for command in build_commands {
-        execute_build_command_with_security(
+        let started = Instant::now();
+        let outcome = execute_build_command_with_security(
             command,
             api,
             environment,
             security_context,
             sps2_config,
         )
-        .await?;
-
-        // Command completed - duration tracking removed as per architectural decision
+        .await
+        .map(Some);
+        environment.record_step(BuildStage::Build, command.name(), started, &outcome);
+        outcome?;
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BuildConfig;
+    use crate::test_support::{demo_context, demo_environment};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn shell(script: &str) -> BuildCommand {
+        BuildCommand::Command {
+            program: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn fetched_zstd_tarballs_are_extracted() {
+        use httpmock::prelude::*;
+
+        let mut tar = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, "foo-1.2/README", &b"hello"[..])
+            .unwrap();
+        let archive = zstd::encode_all(tar.into_inner().unwrap().as_slice(), 3).unwrap();
+
+        let server = MockServer::start();
+        for path in ["/foo-1.2.tar.zst", "/download"] {
+            server.mock(|when, then| {
+                when.method(GET).path(path);
+                then.status(200).body(&archive);
+            });
+        }
+
+        // By extension, and by magic number for a download without one
+        for (path, extract_to) in [("/foo-1.2.tar.zst", "by-ext"), ("/download", "by-magic")] {
+            let temp = TempDir::new().unwrap();
+            let mut environment = demo_environment(temp.path());
+            let working_dir = environment.build_prefix().join("src");
+            std::fs::create_dir_all(&working_dir).unwrap();
+            let mut api =
+                BuilderApi::new(working_dir.clone(), BuildConfig::default().resources).unwrap();
+            api.set_net_client(
+                sps2_net::NetClient::new_without_proxies(sps2_net::NetConfig::default()).unwrap(),
+            );
+
+            let step = SourceStep::Fetch {
+                url: server.url(path),
+                extract_to: Some(extract_to.to_string()),
+                strip_components: None,
+                extract: true,
+            };
+            execute_source_step(&step, &mut api, &mut environment)
+                .await
+                .unwrap();
+            // `extract_to` is a sibling of the main source directory
+            let extracted = environment.build_prefix().join(extract_to).join("README");
+            assert_eq!(std::fs::read_to_string(extracted).unwrap(), "hello");
+        }
+    }
+
+    #[tokio::test]
+    async fn build_steps_are_recorded_in_order() {
+        let temp = TempDir::new().unwrap();
+        let context = demo_context(temp.path());
+        let mut environment = BuildEnvironment::new(context.clone(), temp.path()).unwrap();
+        let working_dir = environment.build_prefix().join("src");
+        std::fs::create_dir_all(&working_dir).unwrap();
+        let mut api =
+            BuilderApi::new(working_dir.clone(), BuildConfig::default().resources).unwrap();
+        let mut security_context = SecurityContext::new(working_dir, HashMap::new());
+
+        let commands = [
+            shell("echo configured"),
+            shell("echo built"),
+            shell("echo broken >&2; exit 3"),
+            shell("echo never"),
+        ];
+        let result = execute_build_commands_list_with_security(
+            &context,
+            &commands,
+            &mut api,
+            &mut environment,
+            &mut security_context,
+            None,
+        )
+        .await;
+        assert!(result.is_err());
+
+        let records = environment.step_records();
+        assert_eq!(records.len(), 3, "steps after the failure must not run");
+        assert!(records
+            .iter()
+            .all(|r| r.stage == BuildStage::Build && r.name == "command sh"));
+        assert!(records[0].success && records[1].success);
+        assert!(!records[2].success);
+        assert!(records[2].error.is_some());
+
+        let first_log = records[0].log_path.as_ref().unwrap();
+        assert!(first_log.ends_with("000-build-command-sh.log"));
+        assert_eq!(std::fs::read_to_string(first_log).unwrap(), "configured");
+        let second_log = records[1].log_path.as_ref().unwrap();
+        assert_eq!(std::fs::read_to_string(second_log).unwrap(), "built");
+    }
+}
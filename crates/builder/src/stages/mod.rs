@@ -7,12 +7,14 @@ pub mod build;
 pub mod environment;
 pub mod executors;
 pub mod post;
+pub mod record;
 pub mod source;
 
 // Re-export execution types
 pub use build::BuildCommand;
 pub use environment::EnvironmentStep;
 pub use post::PostStep;
+pub use record::{BuildStage, BuildStepRecord};
 pub use source::SourceStep;
 
 // The executors are used internally by utils/executor.rs
@@ -19,4 +19,16 @@ pub enum PostStep {
     Command { program: String, args: Vec<String> },
 }
 
+impl PostStep {
+    /// Short name identifying the step in step records
+    #[must_use]
+    pub fn name(&self) -> String {
+        match self {
+            Self::PatchRpaths { .. } => "patch_rpaths".to_string(),
+            Self::FixPermissions { .. } => "fix_permissions".to_string(),
+            Self::Command { program, .. } => format!("command {program}"),
+        }
+    }
+}
+
 // Note: ParsedPost is recipe::model::Post
@@ -0,0 +1,123 @@
+//! Per-step records collected while a recipe runs
+//!
+//! Each source, build and post step produces a [`BuildStepRecord`] in the
+//! order it ran, including steps that failed. Command output is written to
+//! a log file under the build prefix and the record keeps its path, so
+//! tooling can report timings and attribute failures without scraping the
+//! event stream.
+
+use crate::BuildCommandResult;
+use serde::Serialize;
+use sps2_errors::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Directory under the build prefix holding per-step output logs
+pub(crate) const STEP_LOG_DIR: &str = "logs";
+
+/// Recipe stage a step belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildStage {
+    /// Source acquisition (fetch, git, patches)
+    Source,
+    /// Build commands and build systems
+    Build,
+    /// Post-processing (rpaths, permissions, commands)
+    Post,
+}
+
+impl fmt::Display for BuildStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Source => write!(f, "source"),
+            Self::Build => write!(f, "build"),
+            Self::Post => write!(f, "post"),
+        }
+    }
+}
+
+/// Outcome of a single recipe step
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildStepRecord {
+    /// Stage the step ran in
+    pub stage: BuildStage,
+    /// Step name, e.g. `cmake` or `command sh`
+    pub name: String,
+    /// Whether the step completed successfully
+    pub success: bool,
+    /// Wall-clock time spent in the step
+    pub duration: Duration,
+    /// Exit code of the step's command, if it ran one
+    pub exit_code: Option<i32>,
+    /// Log file holding the step's stdout and stderr, if it produced output
+    pub log_path: Option<PathBuf>,
+    /// Error message when the step failed
+    pub error: Option<String>,
+}
+
+impl BuildStepRecord {
+    /// Build a record from a finished step, writing its output to `log_dir`
+    ///
+    /// `index` is the step's position in the recipe run and prefixes the log
+    /// file name so logs sort in execution order. Failing to write the log
+    /// only drops the output reference; the record itself is always kept.
+    pub(crate) fn capture(
+        index: usize,
+        stage: BuildStage,
+        name: String,
+        duration: Duration,
+        outcome: &Result<Option<BuildCommandResult>, Error>,
+        log_dir: &Path,
+    ) -> Self {
+        let (success, exit_code, log_path, error) = match outcome {
+            Ok(output) => (
+                output.as_ref().is_none_or(|result| result.success),
+                output.as_ref().and_then(|result| result.exit_code),
+                output.as_ref().and_then(|result| {
+                    write_step_log(log_dir, &log_file_name(index, stage, &name), result)
+                }),
+                None,
+            ),
+            Err(e) => (false, None, None, Some(e.to_string())),
+        };
+
+        Self {
+            stage,
+            name,
+            success,
+            duration,
+            exit_code,
+            log_path,
+            error,
+        }
+    }
+}
+
+fn log_file_name(index: usize, stage: BuildStage, name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("{index:03}-{stage}-{name}.log")
+}
+
+fn write_step_log(log_dir: &Path, file_name: &str, result: &BuildCommandResult) -> Option<PathBuf> {
+    if result.stdout.is_empty() && result.stderr.is_empty() {
+        return None;
+    }
+
+    let mut contents = result.stdout.clone();
+    if !result.stderr.is_empty() {
+        if !contents.is_empty() {
+            contents.push('\n');
+        }
+        contents.push_str(&result.stderr);
+    }
+
+    let path = log_dir.join(file_name);
+    std::fs::create_dir_all(log_dir).ok()?;
+    std::fs::write(&path, contents).ok()?;
+    Some(path)
+}
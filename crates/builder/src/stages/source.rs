@@ -12,6 +12,10 @@ pub enum SourceStep {
     Fetch {
         url: String,
         extract_to: Option<String>,
+        #[serde(default)]
+        strip_components: Option<usize>,
+        #[serde(default = "default_extract")]
+        extract: bool,
     },
 
     /// Fetch with MD5 verification
@@ -19,6 +23,10 @@ pub enum SourceStep {
         url: String,
         md5: String,
         extract_to: Option<String>,
+        #[serde(default)]
+        strip_components: Option<usize>,
+        #[serde(default = "default_extract")]
+        extract: bool,
     },
 
     /// Fetch with SHA256 verification
@@ -26,20 +34,37 @@ pub enum SourceStep {
         url: String,
         sha256: String,
         extract_to: Option<String>,
+        #[serde(default)]
+        strip_components: Option<usize>,
+        #[serde(default = "default_extract")]
+        extract: bool,
     },
 
     /// Fetch with BLAKE3 verification
+    ///
+    /// The hash may name another algorithm with a prefix such as `sha256:`.
     FetchBlake3 {
         url: String,
         blake3: String,
         extract_to: Option<String>,
+        #[serde(default)]
+        strip_components: Option<usize>,
+        /// Extract the download once fetched; otherwise it stays an archive
+        #[serde(default = "default_extract")]
+        extract: bool,
     },
 
     /// Extract downloaded archives
     Extract { extract_to: Option<String> },
 
     /// Clone from git
-    Git { url: String, ref_: String },
+    Git {
+        url: String,
+        ref_: String,
+        /// Commits of history to clone (`None` clones the full history)
+        #[serde(default = "default_git_depth")]
+        depth: Option<u32>,
+    },
 
     /// Copy local files
     Copy { src_path: Option<String> },
@@ -48,4 +73,32 @@ pub enum SourceStep {
     ApplyPatch { path: String },
 }
 
+impl SourceStep {
+    /// Short name identifying the step in step records
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cleanup => "cleanup",
+            Self::Fetch { .. } => "fetch",
+            Self::FetchMd5 { .. } => "fetch_md5",
+            Self::FetchSha256 { .. } => "fetch_sha256",
+            Self::FetchBlake3 { .. } => "fetch_blake3",
+            Self::Extract { .. } => "extract",
+            Self::Git { .. } => "git",
+            Self::Copy { .. } => "copy",
+            Self::ApplyPatch { .. } => "apply_patch",
+        }
+    }
+}
+
 // Note: ParsedSource is recipe::model::Source
+
+/// Clone depth for serialized git steps recorded before depth was configurable
+#[allow(clippy::unnecessary_wraps)]
+fn default_git_depth() -> Option<u32> {
+    Some(1)
+}
+
+fn default_extract() -> bool {
+    true
+}
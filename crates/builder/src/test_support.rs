@@ -0,0 +1,20 @@
+//! Fixtures shared by the crate's unit tests
+
+use crate::{BuildContext, BuildEnvironment};
+use sps2_types::Version;
+use std::path::Path;
+
+/// Context for building `demo` 1.0.0 from `root/recipe.yml` into `root`
+pub(crate) fn demo_context(root: &Path) -> BuildContext {
+    BuildContext::new(
+        "demo".to_string(),
+        Version::new(1, 0, 0),
+        root.join("recipe.yml"),
+        root.to_path_buf(),
+    )
+}
+
+/// Build environment rooted at `root` for [`demo_context`]
+pub(crate) fn demo_environment(root: &Path) -> BuildEnvironment {
+    BuildEnvironment::new(demo_context(root), root).unwrap()
+}
@@ -3,16 +3,18 @@
 use crate::build_plan::{BuildPlan, EnvironmentConfig};
 use crate::environment::BuildEnvironment;
 use crate::recipe::parser::parse_yaml_recipe;
+use crate::recipe::RecipeResult;
 use crate::security::SecurityContext;
 use crate::stages::executors::{
     execute_build_commands_list_with_security, execute_post_step_with_security, execute_source_step,
 };
+use crate::stages::{BuildStage, SourceStep};
 use crate::utils::events::send_event;
-use crate::yaml::RecipeMetadata;
 use crate::{BuildConfig, BuildContext, BuilderApi};
 use sps2_errors::Error;
 use sps2_events::{AppEvent, GeneralEvent};
 use std::collections::HashMap;
+use std::time::Instant;
 use tokio::fs;
 
 /// Execute a build using staged execution model
@@ -20,16 +22,7 @@ pub async fn execute_staged_build(
     config: &BuildConfig,
     context: &BuildContext,
     environment: &mut BuildEnvironment,
-) -> Result<
-    (
-        Vec<String>,
-        Vec<sps2_types::package::PackageSpec>,
-        RecipeMetadata,
-        bool,
-        sps2_types::QaPipelineOverride,
-    ),
-    Error,
-> {
+) -> Result<RecipeResult, Error> {
     // Stage 0: Parse and analyze recipe
     let yaml_recipe = parse_yaml_recipe(&context.recipe_path).await?;
     let build_plan = BuildPlan::from_yaml(
@@ -53,31 +46,16 @@ pub async fn execute_staged_build(
 
     let mut security_context = SecurityContext::new(build_root, initial_vars);
 
-    // Stage 1: Apply environment configuration
-    apply_environment_config(context, environment, &build_plan.environment).await?;
-
-    // Stage 2: Execute source operations
-    execute_source_stage(config, context, environment, &build_plan).await?;
-
-    // Stage 3: Execute build operations (with security context)
-    execute_build_stage_with_security(
+    // A failed stage is reported alongside the steps recorded so far
+    let error = execute_stages(
         config,
         context,
         environment,
         &build_plan,
         &mut security_context,
     )
-    .await?;
-
-    // Stage 4: Execute post-processing operations (with security context)
-    execute_post_stage_with_security(
-        config,
-        context,
-        environment,
-        &build_plan,
-        &mut security_context,
-    )
-    .await?;
+    .await
+    .err();
 
     // Extract dependencies
     let runtime_deps = build_plan.metadata.runtime_deps.clone();
@@ -88,13 +66,40 @@ pub async fn execute_staged_build(
         .map(|dep| sps2_types::package::PackageSpec::parse(dep))
         .collect::<Result<Vec<_>, _>>()?;
 
-    Ok((
+    Ok(RecipeResult {
         runtime_deps,
         build_deps,
-        build_plan.metadata,
-        build_plan.auto_install,
-        build_plan.qa_pipeline,
-    ))
+        metadata: build_plan.metadata,
+        install_requested: build_plan.auto_install,
+        qa_pipeline: build_plan.qa_pipeline,
+        patchers: build_plan.patchers,
+        rpath_style: build_plan.rpath_style,
+        steps: environment.step_records().to_vec(),
+        error,
+    })
+}
+
+/// Run the environment, source, build and post stages in order
+async fn execute_stages(
+    config: &BuildConfig,
+    context: &BuildContext,
+    environment: &mut BuildEnvironment,
+    build_plan: &BuildPlan,
+    security_context: &mut SecurityContext,
+) -> Result<(), Error> {
+    // Stage 1: Apply environment configuration
+    apply_environment_config(context, environment, &build_plan.environment).await?;
+
+    // Stage 2: Execute source operations
+    execute_source_stage(config, context, environment, build_plan).await?;
+
+    // Stage 3: Execute build operations (with security context)
+    execute_build_stage_with_security(config, context, environment, build_plan, security_context)
+        .await?;
+
+    // Stage 4: Execute post-processing operations (with security context)
+    execute_post_stage_with_security(config, context, environment, build_plan, security_context)
+        .await
 }
 
 /// Apply environment configuration before any build steps
@@ -143,6 +148,20 @@ async fn apply_environment_config(
         environment.apply_default_compiler_flags();
     }
 
+    // A recipe can opt out of tests; it cannot re-enable them once the
+    // build context has disabled them
+    if !config.run_tests {
+        send_event(
+            context,
+            AppEvent::General(GeneralEvent::debug("Test phase disabled by recipe")),
+        );
+        environment.context.run_tests = false;
+    }
+
+    if let Some(wrap_mode) = config.meson_wrap_mode {
+        environment.context.meson_wrap_mode = Some(wrap_mode);
+    }
+
     // Set environment variables
     for (key, value) in &config.variables {
         environment.set_env_var(key.clone(), value.clone())?;
@@ -176,8 +195,24 @@ async fn execute_source_stage(
     let working_dir = environment.build_prefix().join("src");
     fs::create_dir_all(&working_dir).await?;
 
+    // Drop downloads an interrupted earlier build left unfinished
+    let removed = sps2_net::clean_partial_downloads(&working_dir).await?;
+    if removed > 0 {
+        send_event(
+            context,
+            AppEvent::General(GeneralEvent::debug(format!(
+                "Removed {removed} incomplete download(s)"
+            ))),
+        );
+    }
+
     // Create builder API
     let mut api = BuilderApi::new(working_dir.clone(), config.resources.clone())?;
+    api.set_build_systems(config.build_systems.clone());
+    api.set_event_sender(context.event_sender.clone());
+    if let Some(net) = &environment.net {
+        api.set_net_client(net.clone());
+    }
     // Source stage always allows network for fetching
     let _result = api.allow_network(true);
 
@@ -188,13 +223,19 @@ async fn execute_source_stage(
     );
 
     // Cleanup is handled as the first source step
-    execute_source_step(&crate::stages::SourceStep::Cleanup, &mut api, environment).await?;
+    let cleanup = std::iter::once(&SourceStep::Cleanup);
 
     // Execute source steps
-    for step in &build_plan.source_steps {
-        execute_source_step(step, &mut api, environment).await?;
-
-        // Command completed - duration tracking removed as per architectural decision
+    for step in cleanup.chain(&build_plan.source_steps) {
+        let started = Instant::now();
+        let outcome = execute_source_step(step, &mut api, environment).await;
+        environment.record_step(
+            BuildStage::Source,
+            step.name().to_string(),
+            started,
+            &outcome,
+        );
+        outcome?;
     }
 
     send_event(
@@ -230,6 +271,10 @@ async fn execute_build_stage_with_security(
 
     // Create builder API
     let mut api = BuilderApi::new(working_dir, config.resources.clone())?;
+    api.set_build_systems(config.build_systems.clone());
+    if let Some(net) = &environment.net {
+        api.set_net_client(net.clone());
+    }
     // Use network setting from YAML recipe's environment config
     let _result = api.allow_network(build_plan.environment.network);
 
@@ -286,19 +331,22 @@ async fn execute_post_stage_with_security(
 
     // Create builder API
     let mut api = BuilderApi::new(working_dir, config.resources.clone())?;
+    api.set_build_systems(config.build_systems.clone());
 
     // Execute post-processing steps
     for step in &build_plan.post_steps {
-        execute_post_step_with_security(
+        let started = Instant::now();
+        let outcome = execute_post_step_with_security(
             step,
             &mut api,
             environment,
             security_context,
             config.sps2_config.as_ref(),
         )
-        .await?;
-
-        // Command completed - duration tracking removed as per architectural decision
+        .await
+        .map(Some);
+        environment.record_step(BuildStage::Post, step.name(), started, &outcome);
+        outcome?;
     }
 
     send_event(
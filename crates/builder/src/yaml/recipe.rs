@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use sps2_types::RpathStyle;
+use std::collections::BTreeMap;
 
 /// Recipe metadata collected from `metadata()` function
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -13,6 +14,18 @@ pub struct RecipeMetadata {
     pub license: Option<String>,
     pub runtime_deps: Vec<String>,
     pub build_deps: Vec<String>,
+    /// Optional runtime dependencies that never pull in their own closure
+    #[serde(default)]
+    pub weak_deps: Vec<String>,
+    /// Packages that can never be installed alongside this one
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+    /// Packages this one supersedes
+    #[serde(default)]
+    pub replaces: Vec<String>,
+    /// Environment variables to export while the package is installed
+    #[serde(default)]
+    pub runtime_env: BTreeMap<String, String>,
 }
 
 /// A build step from the `build()` function
@@ -25,10 +25,16 @@ pub struct BuilderConfig {
 pub struct BuildSettings {
     #[serde(default = "default_build_jobs")]
     pub build_jobs: usize, // 0 = auto-detect, can be overridden per recipe
+    #[serde(default)]
+    pub system_jobs: std::collections::HashMap<String, usize>, // Per build system, e.g. cmake = 4
+    #[serde(default)]
+    pub patchers: sps2_types::PatcherSelection, // Post-validation patcher order and opt-outs
     #[serde(default = "default_timeout_seconds")]
     pub timeout_seconds: u64, // Default timeout, can be overridden per recipe
     #[serde(default = "default_build_root")]
     pub build_root: PathBuf, // Global build directory
+    #[serde(default)]
+    pub staging_root: Option<PathBuf>, // Stage under this root instead of the build directory
     #[serde(default = "default_cleanup_on_success")]
     pub cleanup_on_success: bool,
     #[serde(default = "default_strict_mode")]
@@ -43,9 +49,12 @@ pub struct BuildSettings {
 impl Default for BuildSettings {
     fn default() -> Self {
         Self {
-            build_jobs: 0,         // 0 = auto-detect
+            build_jobs: 0, // 0 = auto-detect
+            system_jobs: std::collections::HashMap::new(),
+            patchers: sps2_types::PatcherSelection::default(),
             timeout_seconds: 3600, // 1 hour
             build_root: PathBuf::from("/opt/pm/build"),
+            staging_root: None,
             cleanup_on_success: true,
             strict_mode: true,
             default_isolation_level: "default".to_string(),
@@ -67,17 +76,24 @@ pub struct PackagingSettings {
 
 /// SBOM (Software Bill of Materials) generation settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)] // independent config toggles
 pub struct SbomSettings {
     #[serde(default = "default_sbom_enabled")]
     pub enabled: bool,
     #[serde(default = "default_sbom_format")]
-    pub format: String, // "spdx-json", "cyclone-dx", etc.
+    pub format: String, // "spdx-json", "cyclone-dx", "spdx-tag-value" or "all"
     #[serde(default = "default_include_build_info")]
     pub include_build_info: bool,
     #[serde(default = "default_include_dependencies")]
     pub include_dependencies: bool,
     #[serde(default)]
     pub exclusions: Vec<String>,
+    /// Scan the generated SBOM for known vulnerabilities with grype
+    #[serde(default)]
+    pub scan_vulnerabilities: bool,
+    /// Fail the build when a vulnerability at or above this severity is found
+    #[serde(default)]
+    pub fail_on_severity: Option<Severity>,
 }
 
 impl Default for SbomSettings {
@@ -88,10 +104,25 @@ impl Default for SbomSettings {
             include_build_info: true,
             include_dependencies: true,
             exclusions: Vec::new(),
+            scan_vulnerabilities: false,
+            fail_on_severity: None,
         }
     }
 }
 
+/// Vulnerability severity reported by the scanner, from least to most
+/// severe; `Unknown` sorts lowest so it never reaches a threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Unknown,
+    Negligible,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
 /// Code signing settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SigningSettings {
@@ -2,7 +2,7 @@
 
 use super::repository::Repositories;
 use serde::{Deserialize, Serialize};
-use sps2_types::{ColorChoice, OutputFormat};
+use sps2_types::{ColorChoice, OutputFormat, Secret};
 use std::path::PathBuf;
 
 /// General application configuration
@@ -14,6 +14,15 @@ pub struct GeneralConfig {
     pub color: ColorChoice,
     #[serde(default = "default_parallel_downloads")]
     pub parallel_downloads: usize,
+    /// Most packages installed in one execution batch; unlimited if unset
+    #[serde(default)]
+    pub max_batch_size: Option<usize>,
+    /// Report paths shared with a replaced package as warnings, not errors
+    #[serde(default)]
+    pub replaced_overlaps_as_warnings: bool,
+    /// Keep downloads and staging of failed installs under `failed-installs`
+    #[serde(default)]
+    pub keep_on_failure: bool,
 }
 
 impl Default for GeneralConfig {
@@ -22,6 +31,9 @@ impl Default for GeneralConfig {
             default_output: OutputFormat::Tty,
             color: ColorChoice::Auto,
             parallel_downloads: 4,
+            max_batch_size: None,
+            replaced_overlaps_as_warnings: false,
+            keep_on_failure: false,
         }
     }
 }
@@ -119,6 +131,28 @@ pub struct NetworkConfig {
     pub retries: u32,
     #[serde(default = "default_retry_delay")]
     pub retry_delay: u64, // seconds
+    /// Log every HTTP request attempt at debug level
+    #[serde(default)]
+    pub log_requests: bool,
+    /// Credentials for private repositories and mirrors
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub credentials: Vec<NetworkCredential>,
+}
+
+/// Credentials sent to one origin (`scheme://host[:port]`)
+///
+/// Either `token` (bearer authentication) or `username` (basic
+/// authentication) must be set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkCredential {
+    /// Any URL on the origin the credentials apply to
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<Secret>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<Secret>,
 }
 
 impl Default for NetworkConfig {
@@ -127,6 +161,8 @@ impl Default for NetworkConfig {
             timeout: 300, // 5 minutes
             retries: 3,
             retry_delay: 1, // 1 second
+            log_requests: false,
+            credentials: Vec::new(),
         }
     }
 }
@@ -43,6 +43,37 @@ impl Default for DiscrepancyHandling {
     }
 }
 
+/// Lowest discrepancy severity that counts as a verification failure
+///
+/// Lets `report_only` and exit-status checks ignore minor findings, e.g. only
+/// fail on `critical` discrepancies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureThreshold {
+    /// Only critical discrepancies fail verification
+    Critical,
+    /// High and critical discrepancies fail verification
+    High,
+    /// Medium and more severe discrepancies fail verification
+    Medium,
+    /// Any discrepancy fails verification
+    #[default]
+    Low,
+}
+
+impl FailureThreshold {
+    /// Discrepancy severity corresponding to this threshold
+    #[must_use]
+    pub fn severity(self) -> sps2_errors::DiscrepancySeverity {
+        match self {
+            Self::Critical => sps2_errors::DiscrepancySeverity::Critical,
+            Self::High => sps2_errors::DiscrepancySeverity::High,
+            Self::Medium => sps2_errors::DiscrepancySeverity::Medium,
+            Self::Low => sps2_errors::DiscrepancySeverity::Low,
+        }
+    }
+}
+
 /// Policy for handling user files
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -61,6 +92,26 @@ impl Default for UserFilePolicy {
     }
 }
 
+/// Rules that mark files as user files
+///
+/// Matching untracked files are categorized as user-created, so
+/// [`UserFilePolicy`] decides what healing does with them, and matching
+/// package files that no longer hash as installed are preserved rather than
+/// restored. These rules extend the built-in heuristics (config extensions,
+/// `data/` and `var/` directories).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserFileRules {
+    /// Subtrees, relative to the live prefix, whose files are user files
+    #[serde(default)]
+    pub paths: Vec<PathBuf>,
+    /// Glob patterns matched against the path relative to the live prefix
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Treat files modified after the active state was created as user files
+    #[serde(default)]
+    pub modified_after_install: bool,
+}
+
 /// Performance configuration for guard operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerformanceConfigToml {
@@ -70,6 +121,17 @@ pub struct PerformanceConfigToml {
     pub max_concurrent_tasks: usize,
     #[serde(default = "default_verification_timeout_seconds")]
     pub verification_timeout_seconds: u64,
+    /// Tune concurrency for throughput, using `max_concurrent_tasks` as the ceiling
+    #[serde(default)]
+    pub adaptive_concurrency: bool,
+    /// Size in bytes below which Quick verification hashes files (0 keeps
+    /// Quick existence-only; unset uses the guard default)
+    #[serde(default)]
+    pub quick_hash_threshold: Option<u64>,
+    /// Longest time in seconds incremental verification goes without a full
+    /// sweep (unset uses the guard default)
+    #[serde(default)]
+    pub full_sweep_interval_seconds: Option<u64>,
 }
 
 impl Default for PerformanceConfigToml {
@@ -78,6 +140,9 @@ impl Default for PerformanceConfigToml {
             progressive_verification: default_progressive_verification(),
             max_concurrent_tasks: default_max_concurrent_tasks(),
             verification_timeout_seconds: default_verification_timeout_seconds(),
+            adaptive_concurrency: false,
+            quick_hash_threshold: None,
+            full_sweep_interval_seconds: None,
         }
     }
 }
@@ -109,12 +174,20 @@ pub struct VerificationConfig {
     pub level: String, // "quick", "standard", or "full"
     #[serde(default)]
     pub discrepancy_handling: DiscrepancyHandling,
+    #[serde(default)]
+    pub failure_threshold: FailureThreshold,
     #[serde(default = "default_orphaned_file_action")]
-    pub orphaned_file_action: String, // "remove", "preserve", or "backup"
+    pub orphaned_file_action: String, // "remove", "preserve", "backup", or "quarantine"
     #[serde(default = "default_orphaned_backup_dir")]
     pub orphaned_backup_dir: PathBuf,
     #[serde(default)]
     pub user_file_policy: UserFilePolicy,
+    #[serde(default)]
+    pub user_files: UserFileRules,
+    /// Glob patterns, relative to the live prefix, for untracked files the
+    /// orphan scan reports as whitelisted
+    #[serde(default)]
+    pub orphan_whitelist: Vec<String>,
 
     // Enhanced guard configuration
     #[serde(default)]
@@ -137,9 +210,12 @@ impl Default for VerificationConfig {
             enabled: false, // Disabled by default during development
             level: "standard".to_string(),
             discrepancy_handling: DiscrepancyHandling::default(),
+            failure_threshold: FailureThreshold::default(),
             orphaned_file_action: "preserve".to_string(),
             orphaned_backup_dir: PathBuf::from("/opt/pm/orphaned-backup"),
             user_file_policy: UserFilePolicy::default(),
+            user_files: UserFileRules::default(),
+            orphan_whitelist: Vec::new(),
             guard: GuardConfigToml::default(),
             performance: PerformanceConfigToml::default(),
             fail_on_discrepancy: None,
@@ -176,6 +252,23 @@ pub struct GuardPerformanceConfig {
     pub max_concurrent_tasks: usize,
     #[serde(default = "default_verification_timeout_seconds")]
     pub verification_timeout_seconds: u64,
+    /// Maximum packages covered by post-operation verification (0 = unlimited)
+    #[serde(default)]
+    pub post_verification_max_packages: usize,
+    /// Maximum package bytes covered by post-operation verification (0 = unlimited)
+    #[serde(default)]
+    pub post_verification_max_bytes: u64,
+    /// Tune concurrency for throughput, using `max_concurrent_tasks` as the ceiling
+    #[serde(default)]
+    pub adaptive_concurrency: bool,
+    /// Size in bytes below which Quick verification hashes files (0 keeps
+    /// Quick existence-only; unset uses the guard default)
+    #[serde(default)]
+    pub quick_hash_threshold: Option<u64>,
+    /// Longest time in seconds incremental verification goes without a full
+    /// sweep (unset uses the guard default)
+    #[serde(default)]
+    pub full_sweep_interval_seconds: Option<u64>,
 }
 
 impl Default for GuardPerformanceConfig {
@@ -184,6 +277,11 @@ impl Default for GuardPerformanceConfig {
             progressive_verification: default_progressive_verification(),
             max_concurrent_tasks: default_max_concurrent_tasks(),
             verification_timeout_seconds: default_verification_timeout_seconds(),
+            post_verification_max_packages: 0,
+            post_verification_max_bytes: 0,
+            adaptive_concurrency: false,
+            quick_hash_threshold: None,
+            full_sweep_interval_seconds: None,
         }
     }
 }
@@ -243,13 +341,21 @@ pub struct GuardConfiguration {
     #[serde(default)]
     pub discrepancy_handling: DiscrepancyHandling,
     #[serde(default)]
+    pub failure_threshold: FailureThreshold,
+    #[serde(default)]
     pub symlink_policy: GuardSymlinkPolicy,
     #[serde(default = "default_orphaned_file_action")]
-    pub orphaned_file_action: String, // "remove", "preserve", or "backup"
+    pub orphaned_file_action: String, // "remove", "preserve", "backup", or "quarantine"
     #[serde(default = "default_orphaned_backup_dir")]
     pub orphaned_backup_dir: PathBuf,
     #[serde(default)]
     pub user_file_policy: UserFilePolicy,
+    #[serde(default)]
+    pub user_files: UserFileRules,
+    /// Glob patterns, relative to the live prefix, for untracked files the
+    /// orphan scan reports as whitelisted
+    #[serde(default)]
+    pub orphan_whitelist: Vec<String>,
 
     // Nested configuration sections
     #[serde(default)]
@@ -274,10 +380,13 @@ impl Default for GuardConfiguration {
             enabled: default_guard_enabled(),
             verification_level: default_verification_level(),
             discrepancy_handling: DiscrepancyHandling::default(),
+            failure_threshold: FailureThreshold::default(),
             symlink_policy: GuardSymlinkPolicy::default(),
             orphaned_file_action: default_orphaned_file_action(),
             orphaned_backup_dir: default_orphaned_backup_dir(),
             user_file_policy: UserFilePolicy::default(),
+            user_files: UserFileRules::default(),
+            orphan_whitelist: Vec::new(),
             performance: GuardPerformanceConfig::default(),
             store_verification: StoreVerificationConfig::default(),
             lenient_symlink_directories: default_guard_lenient_symlink_directories(),
@@ -19,11 +19,13 @@ pub mod repository;
 // Re-export main types for convenience
 pub use builder::BuilderConfig;
 pub use constants as fixed_paths;
-pub use core::{GeneralConfig, NetworkConfig, PathConfig, SecurityConfig, StateConfig};
+pub use core::{
+    GeneralConfig, NetworkConfig, NetworkCredential, PathConfig, SecurityConfig, StateConfig,
+};
 pub use guard::{
-    DiscrepancyHandling, GuardConfiguration, GuardDirectoryConfig, GuardPerformanceConfig,
-    GuardSymlinkPolicy, PerformanceConfigToml, SymlinkPolicyConfig, UserFilePolicy,
-    VerificationConfig,
+    DiscrepancyHandling, FailureThreshold, GuardConfiguration, GuardDirectoryConfig,
+    GuardPerformanceConfig, GuardSymlinkPolicy, PerformanceConfigToml, SymlinkPolicyConfig,
+    UserFilePolicy, UserFileRules, VerificationConfig,
 };
 pub use repository::{Repositories, RepositoryConfig};
 
@@ -439,6 +441,11 @@ impl Config {
             &self.verification.guard.lenient_symlink_directories,
             "verification.guard.lenient_symlink_directories",
         )?;
+        Self::validate_user_file_rules(&self.verification.user_files, "verification.user_files")?;
+        Self::validate_glob_patterns(
+            &self.verification.orphan_whitelist,
+            "verification.orphan_whitelist",
+        )?;
         Ok(())
     }
 
@@ -456,6 +463,8 @@ impl Config {
             &guard_config.lenient_symlink_directories,
             "guard.lenient_symlink_directories",
         )?;
+        Self::validate_user_file_rules(&guard_config.user_files, "guard.user_files")?;
+        Self::validate_glob_patterns(&guard_config.orphan_whitelist, "guard.orphan_whitelist")?;
         Ok(())
     }
 
@@ -472,7 +481,7 @@ impl Config {
 
     fn validate_orphaned_file_action(action: &str, field_name: &str) -> Result<(), Error> {
         match action {
-            "remove" | "preserve" | "backup" => Ok(()),
+            "remove" | "preserve" | "backup" | "quarantine" => Ok(()),
             _ => Err(ConfigError::InvalidValue {
                 field: field_name.to_string(),
                 value: action.to_string(),
@@ -540,6 +549,34 @@ impl Config {
         Ok(())
     }
 
+    fn validate_user_file_rules(
+        rules: &guard::UserFileRules,
+        field_prefix: &str,
+    ) -> Result<(), Error> {
+        if let Some(path) = rules.paths.iter().find(|path| path.is_absolute()) {
+            return Err(ConfigError::InvalidValue {
+                field: format!("{field_prefix}.paths"),
+                value: path.display().to_string(),
+            }
+            .into());
+        }
+        Self::validate_glob_patterns(&rules.patterns, &format!("{field_prefix}.patterns"))
+    }
+
+    fn validate_glob_patterns(patterns: &[String], field_name: &str) -> Result<(), Error> {
+        if let Some(pattern) = patterns
+            .iter()
+            .find(|pattern| globset::Glob::new(pattern).is_err())
+        {
+            return Err(ConfigError::InvalidValue {
+                field: field_name.to_string(),
+                value: pattern.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
     fn validate_guard_symlink_directories(
         dirs: &[guard::GuardDirectoryConfig],
         field_name: &str,
@@ -9,6 +9,9 @@ pub struct RepositoryConfig {
     pub algorithm: String, // "minisign" | "openpgp" (future)
     #[serde(default)]
     pub key_ids: Vec<String>,
+    /// Alternate base URLs serving the same content, ranked by health at runtime
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
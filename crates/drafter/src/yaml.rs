@@ -97,6 +97,7 @@ fn convert_source(source: &SourceLocation) -> Source {
             git: GitSource {
                 url: url.clone(),
                 git_ref: "HEAD".to_string(), // TODO: Support specific refs
+                depth: None,
             },
         },
         SourceLocation::Url(url) => SourceMethod::Fetch {
@@ -104,6 +105,8 @@ fn convert_source(source: &SourceLocation) -> Source {
                 url: url.clone(),
                 checksum: None, // TODO: Add checksum support
                 extract_to: None,
+                strip_components: None,
+                extract: None,
             },
         },
         SourceLocation::Local(path) | SourceLocation::Archive(path) => SourceMethod::Local {
@@ -73,6 +73,9 @@ pub enum BuildError {
     #[error("no build system detected in {path}")]
     NoBuildSystemDetected { path: String },
 
+    #[error("build system '{name}' is not registered")]
+    UnknownBuildSystem { name: String },
+
     #[error("dependency conflict: {message}")]
     DependencyConflict { message: String },
 
@@ -151,6 +154,9 @@ pub enum BuildError {
 
     #[error("disallowed command: {command}")]
     DisallowedCommand { command: String },
+
+    #[error("missing build tools: {}", tools.join(", "))]
+    MissingBuildTools { tools: Vec<String> },
 }
 
 impl UserFacingError for BuildError {
@@ -163,6 +169,9 @@ impl UserFacingError for BuildError {
             Self::MissingBuildDep { .. } => {
                 Some("Install the missing build dependency or declare it in the recipe.")
             }
+            Self::MissingBuildTools { .. } => Some(
+                "Install the listed tools or add packages providing them to build dependencies.",
+            ),
             Self::FetchFailed { .. } | Self::InvalidUrl { .. } | Self::NetworkDisabled { .. } => {
                 Some("Check network access or provide local source artifacts for the build.")
             }
@@ -215,6 +224,7 @@ impl UserFacingError for BuildError {
             Self::InvalidUrl { .. } => "build.invalid_url",
             Self::SigningError { .. } => "build.signing_error",
             Self::NoBuildSystemDetected { .. } => "build.no_build_system_detected",
+            Self::UnknownBuildSystem { .. } => "build.unknown_build_system",
             Self::DependencyConflict { .. } => "build.dependency_conflict",
             Self::CompilationFailed { .. } => "build.compilation_failed",
             Self::TestsFailed { .. } => "build.tests_failed",
@@ -240,6 +250,7 @@ impl UserFacingError for BuildError {
             Self::TooManySymlinks { .. } => "build.too_many_symlinks",
             Self::PathTraversalAttempt { .. } => "build.path_traversal_attempt",
             Self::DisallowedCommand { .. } => "build.disallowed_command",
+            Self::MissingBuildTools { .. } => "build.missing_build_tools",
         };
         Some(code)
     }
@@ -75,6 +75,9 @@ pub enum InstallError {
 
     #[error("no progress detected: {message}")]
     NoProgress { message: String },
+
+    #[error("file conflict: {path} is provided by {}", packages.join(", "))]
+    FileConflict { path: String, packages: Vec<String> },
 }
 
 impl UserFacingError for InstallError {
@@ -90,6 +93,9 @@ impl UserFacingError for InstallError {
             Self::MissingDownloadUrl { .. } | Self::MissingLocalPath { .. } => {
                 Some("Ensure the package manifest includes a valid source.")
             }
+            Self::FileConflict { .. } => {
+                Some("Install only one of the conflicting packages, or uninstall the other first.")
+            }
             _ => None,
         }
     }
@@ -124,6 +130,7 @@ impl UserFacingError for InstallError {
             Self::TempFileError { .. } => "install.temp_file_error",
             Self::OperationTimeout { .. } => "install.operation_timeout",
             Self::NoProgress { .. } => "install.no_progress",
+            Self::FileConflict { .. } => "install.file_conflict",
         };
         Some(code)
     }
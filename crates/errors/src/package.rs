@@ -42,8 +42,21 @@ pub enum PackageError {
     #[error("already installed: {name} {version}")]
     AlreadyInstalled { name: String, version: String },
 
-    #[error("dependency cycle detected: {package}")]
-    DependencyCycle { package: String },
+    /// Each package in `packages` is a dependency of the next, and the last
+    /// of the first
+    #[error(
+        "dependency cycle detected: {} -> {}",
+        packages.join(" -> "),
+        packages.first().map_or("", String::as_str)
+    )]
+    DependencyCycle { packages: Vec<String> },
+
+    #[error("{a} conflicts with {b}: {reason}")]
+    PackageConflict {
+        a: String,
+        b: String,
+        reason: String,
+    },
 
     #[error("incompatible package format version {version}: {reason}")]
     IncompatibleFormat { version: String, reason: String },
@@ -53,6 +66,9 @@ pub enum PackageError {
 
     #[error("source not available: {package}")]
     SourceNotAvailable { package: String },
+
+    #[error("pinned index snapshot not available: {pin}")]
+    IndexSnapshotUnavailable { pin: String },
 }
 
 impl UserFacingError for PackageError {
@@ -66,9 +82,15 @@ impl UserFacingError for PackageError {
             Self::MissingDependency { .. } => {
                 Some("Add the missing dependency to your install request or build recipe.")
             }
-            Self::DependencyConflict { .. } | Self::DependencyCycle { .. } => {
+            Self::DependencyConflict { .. } => {
                 Some("Adjust your requested package versions to resolve the dependency conflict.")
             }
+            Self::DependencyCycle { .. } => {
+                Some("Remove one of the listed dependencies from its recipe to break the cycle.")
+            }
+            Self::PackageConflict { .. } => {
+                Some("Install only one of the conflicting packages, or remove the other first.")
+            }
             Self::SignatureVerificationFailed { .. } | Self::UnsignedPackage => {
                 Some("Verify the package signature or supply trusted keys before proceeding.")
             }
@@ -81,6 +103,9 @@ impl UserFacingError for PackageError {
             Self::SourceNotAvailable { .. } => {
                 Some("Ensure the source repository is reachable or configured.")
             }
+            Self::IndexSnapshotUnavailable { .. } => {
+                Some("Sync the pinned index snapshot into the cache or remove the index pin.")
+            }
             _ => None,
         }
     }
@@ -106,9 +131,11 @@ impl UserFacingError for PackageError {
             Self::SbomError { .. } => "package.sbom_error",
             Self::AlreadyInstalled { .. } => "package.already_installed",
             Self::DependencyCycle { .. } => "package.dependency_cycle",
+            Self::PackageConflict { .. } => "package.conflict",
             Self::IncompatibleFormat { .. } => "package.incompatible_format",
             Self::ResolutionTimeout { .. } => "package.resolution_timeout",
             Self::SourceNotAvailable { .. } => "package.source_not_available",
+            Self::IndexSnapshotUnavailable { .. } => "package.index_snapshot_unavailable",
         };
         Some(code)
     }
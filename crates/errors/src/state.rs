@@ -35,6 +35,12 @@ pub enum StateError {
 
     #[error("migration failed: {message}")]
     MigrationFailed { message: String },
+
+    #[error("unsupported state manifest version {found} (supported: {supported})")]
+    UnsupportedManifestVersion { found: u32, supported: u32 },
+
+    #[error("state manifest references unavailable content: {missing}")]
+    ManifestContentUnavailable { missing: String },
 }
 
 impl UserFacingError for StateError {
@@ -52,6 +58,12 @@ impl UserFacingError for StateError {
             Self::MigrationFailed { .. } => {
                 Some("Review the migration logs and rerun `sps2 check-health`.")
             }
+            Self::UnsupportedManifestVersion { .. } => {
+                Some("Export the state again with a matching sps2 version.")
+            }
+            Self::ManifestContentUnavailable { .. } => Some(
+                "Publish the listed packages in the package index, or add them to the store, then retry.",
+            ),
             _ => None,
         }
     }
@@ -71,6 +83,8 @@ impl UserFacingError for StateError {
             Self::RollbackFailed { .. } => "state.rollback_failed",
             Self::ActiveStateMissing => "state.active_state_missing",
             Self::MigrationFailed { .. } => "state.migration_failed",
+            Self::UnsupportedManifestVersion { .. } => "state.unsupported_manifest_version",
+            Self::ManifestContentUnavailable { .. } => "state.manifest_content_unavailable",
         };
         Some(code)
     }
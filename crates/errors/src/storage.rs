@@ -44,6 +44,9 @@ pub enum StorageError {
 
     #[error("package not found: {hash}")]
     PackageNotFound { hash: String },
+
+    #[error("store layout migration required: {message}")]
+    MigrationRequired { message: String },
 }
 
 impl From<std::io::Error> for StorageError {
@@ -90,6 +93,9 @@ impl UserFacingError for StorageError {
             Self::LockFailed { .. } => {
                 Some("Wait for other package-manager operations to finish, then retry.")
             }
+            Self::MigrationRequired { .. } => {
+                Some("Run `sps2 cleanup --compact` to migrate the store.")
+            }
             _ => None,
         }
     }
@@ -112,6 +118,7 @@ impl UserFacingError for StorageError {
             Self::ApfsCloneFailed { .. } => "storage.apfs_clone_failed",
             Self::AtomicRenameFailed { .. } => "storage.atomic_rename_failed",
             Self::PackageNotFound { .. } => "storage.package_not_found",
+            Self::MigrationRequired { .. } => "storage.migration_required",
         };
         Some(code)
     }
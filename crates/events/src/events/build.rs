@@ -87,6 +87,26 @@ pub enum BuildDiagnostic {
         removed_items: usize,
         freed_bytes: u64,
     },
+    /// Auto-detection picked a build system for a source directory.
+    BuildSystemDetected {
+        source_dir: PathBuf,
+        system: String,
+        /// Files that made the chosen system match.
+        markers: Vec<String>,
+        /// Other systems that also matched but were not chosen.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        skipped: Vec<SkippedBuildSystem>,
+    },
+}
+
+/// A build system that matched during auto-detection but was not chosen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedBuildSystem {
+    pub name: String,
+    /// Files that made this system match.
+    pub markers: Vec<String>,
+    /// Why it was passed over.
+    pub reason: String,
 }
 
 /// Build-specific events consumed by the CLI and logging pipeline.
@@ -35,6 +35,9 @@ pub enum GeneralEvent {
         failure: super::FailureContext,
     },
 
+    /// Operation cancelled on request before it committed any changes
+    OperationCancelled { operation: String },
+
     /// Check mode preview of planned action
     CheckModePreview {
         operation: String,
@@ -61,6 +61,7 @@ pub mod progress;
 pub mod qa;
 pub mod repo;
 pub mod resolver;
+pub mod resource;
 pub mod state;
 pub mod uninstall;
 pub mod update;
@@ -79,6 +80,7 @@ pub use progress::*;
 pub use qa::*;
 pub use repo::*;
 pub use resolver::*;
+pub use resource::*;
 pub use state::*;
 pub use uninstall::*;
 pub use update::*;
@@ -134,6 +136,9 @@ pub enum AppEvent {
 
     /// Platform-specific operation events (binary, filesystem, process operations)
     Platform(PlatformEvent),
+
+    /// Resource manager events (permit contention)
+    Resource(ResourceEvent),
 }
 
 impl AppEvent {
@@ -157,6 +162,7 @@ impl AppEvent {
             AppEvent::Audit(_) => EventSource::AUDIT,
             AppEvent::Package(_) => EventSource::PACKAGE,
             AppEvent::Platform(_) => EventSource::PLATFORM,
+            AppEvent::Resource(_) => EventSource::RESOURCE,
         }
     }
 
@@ -188,7 +194,9 @@ impl AppEvent {
             ) => Level::ERROR,
 
             // Warning-level events
-            AppEvent::General(GeneralEvent::Warning { .. })
+            AppEvent::General(
+                GeneralEvent::Warning { .. } | GeneralEvent::OperationCancelled { .. },
+            )
             | AppEvent::Build(BuildEvent::Diagnostic(build::BuildDiagnostic::Warning { .. })) => {
                 Level::WARN
             }
@@ -199,6 +207,7 @@ impl AppEvent {
                 ..
             }))
             | AppEvent::Progress(ProgressEvent::Updated { .. })
+            | AppEvent::Resource(_)
             | AppEvent::Qa(QaEvent::CheckEvaluated { .. }) => Level::DEBUG,
 
             // Trace-level events (very detailed internal operations)
@@ -231,6 +240,7 @@ impl AppEvent {
             AppEvent::Audit(_) => "sps2::events::audit",
             AppEvent::Package(_) => "sps2::events::package",
             AppEvent::Platform(_) => "sps2::events::platform",
+            AppEvent::Resource(_) => "sps2::events::resource",
         }
     }
 
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Concurrency pool managed by the resource manager
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    Download,
+    Decompression,
+    Installation,
+}
+
+/// Resource domain events for permit contention
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResourceEvent {
+    /// A permit was granted
+    PermitAcquired {
+        kind: ResourceKind,
+        /// Permits still free after this one was granted
+        available: usize,
+        /// Total permits in the pool
+        capacity: usize,
+    },
+
+    /// A permit was returned to its pool
+    PermitReleased {
+        kind: ResourceKind,
+        /// Permits free after this one was returned
+        available: usize,
+        /// Total permits in the pool
+        capacity: usize,
+    },
+}
@@ -60,6 +60,8 @@ pub use events::{
     QaEvent,
     RepoEvent,
     ResolverEvent,
+    ResourceEvent,
+    ResourceKind,
     RollbackContext,
     RollbackSummary,
     StateEvent,
@@ -189,6 +191,13 @@ pub trait EventEmitter {
         )));
     }
 
+    /// Emit an operation cancelled event
+    fn emit_operation_cancelled(&self, operation: impl Into<String>) {
+        self.emit(AppEvent::General(GeneralEvent::OperationCancelled {
+            operation: operation.into(),
+        }));
+    }
+
     /// Emit a download started event
     fn emit_download_started(
         &self,
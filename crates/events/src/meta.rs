@@ -132,6 +132,7 @@ impl EventSource {
 
     pub const PACKAGE: Self = Self::const_str("package");
     pub const PLATFORM: Self = Self::const_str("platform");
+    pub const RESOURCE: Self = Self::const_str("resource");
 
     const fn const_str(value: &'static str) -> Self {
         Self(Cow::Borrowed(value))
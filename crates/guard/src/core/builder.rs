@@ -55,6 +55,20 @@ impl StateVerificationGuardBuilder {
         self
     }
 
+    /// Report healing actions instead of performing them
+    #[must_use]
+    pub fn with_healing_dry_run(mut self, dry_run: bool) -> Self {
+        self.config.healing_dry_run = dry_run;
+        self
+    }
+
+    /// Re-hash every file instead of trusting the on-disk hash cache
+    #[must_use]
+    pub fn with_force_full(mut self, force_full: bool) -> Self {
+        self.config.force_full = force_full;
+        self
+    }
+
     /// Set the guard configuration
     #[must_use]
     pub fn with_config(mut self, config: GuardConfig) -> Self {
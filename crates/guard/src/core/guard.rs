@@ -1,22 +1,53 @@
 //! Main StateVerificationGuard implementation
 
 use crate::error_context::{GuardErrorContext, VerbosityLevel};
+use crate::healing::backup::OrphanBackup;
+use crate::healing::quarantine::OrphanQuarantine;
+use crate::orphan::categorization::{OrphanWhitelist, UserFileMatcher};
 use crate::types::{
-    Discrepancy, GuardConfig, HealingContext, OperationType, VerificationLevel, VerificationResult,
-    VerificationScope,
+    Discrepancy, GuardConfig, HealingAction, HealingContext, OperationType, SymlinkPolicy,
+    VerificationLevel, VerificationResult, VerificationScope, VERIFIED_MODE_BITS,
 };
 use crate::verification;
-use sps2_errors::Error;
+use crate::verification::concurrency::{self, ConcurrencyTuner};
+use crate::verification::hash_cache::{FileStamp, HashCache, HASH_CACHE_FILE};
+use crate::verification::symlinks::find_symlink_cycle;
+use sps2_errors::{Error, InstallError};
 use sps2_events::{
-    AppEvent, EventEmitter, EventSender, FailureContext, GuardEvent, GuardHealingPlan, GuardScope,
+    config::ProgressPhase, AppEvent, EventEmitter, EventSender, FailureContext, GuardEvent,
+    GuardHealingPlan, GuardScope, ProgressEvent,
 };
 use sps2_hash::Hash;
 use sps2_state::{queries, PackageFileEntry, StateManager};
 use sps2_store::PackageStore;
 use std::collections::{HashMap, HashSet};
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use uuid;
 
+/// Progress identifier for byte-based content verification
+const VERIFY_PROGRESS_ID: &str = "guard-verification";
+
+/// Progress identifier for file counts and verification phases
+const VERIFY_FILES_PROGRESS_ID: &str = "guard-verification-files";
+
+/// How often byte and file progress is reported while hashing
+const VERIFY_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Phases reported under [`VERIFY_FILES_PROGRESS_ID`]
+const PHASE_SCOPE: usize = 0;
+const PHASE_HASHING: usize = 1;
+const PHASE_ORPHAN_SCAN: usize = 2;
+
+/// Names and descriptions of the verification phases, by index
+const VERIFY_PHASES: [(&str, &str); 3] = [
+    ("pre-operation scope", "Resolving packages and file entries"),
+    ("hashing", "Checking installed files"),
+    ("orphan scan", "Looking for files no package owns"),
+];
+
 /// Check if a file path represents a Python runtime file that gets modified during execution
 fn is_python_runtime_file(file_path: &str) -> bool {
     // Python symlinks that get created/modified during runtime
@@ -46,16 +77,44 @@ fn is_python_runtime_file(file_path: &str) -> bool {
 struct SinglePackageResult {
     discrepancies: Vec<Discrepancy>,
     tracked_files: HashSet<std::path::PathBuf>,
-    mtime_updates: Vec<MTimeUpdate>,
+    hash_updates: Vec<HashUpdate>,
     cache_hits: usize,
     cache_misses: usize,
+    /// Files whose content hash was computed
+    deep_checked: usize,
+    /// Files checked for existence (and size) only
+    shallow_checked: usize,
 }
 
-/// MTime update to be applied after parallel verification
+/// Freshly computed hash to record in the hash cache after verification
 #[derive(Debug, Clone)]
-struct MTimeUpdate {
+struct HashUpdate {
+    file_path: String,
+    stamp: FileStamp,
+    hash: Hash,
+}
+
+/// A file whose content hash is checked under a verification permit
+#[derive(Debug)]
+struct HashCheck {
     file_path: String,
-    verified_mtime: i64,
+    full_path: PathBuf,
+    expected_hash: Hash,
+    /// Stored size, credited to byte progress once hashed
+    size: u64,
+    /// Modification time and size the hash is cached under
+    stamp: FileStamp,
+}
+
+/// Result of healing a set of discrepancies
+#[derive(Debug, Default)]
+struct HealingOutcome {
+    /// Discrepancies healed, or that would be in a dry run
+    healed: usize,
+    /// Discrepancies that could not be healed
+    failed: Vec<Discrepancy>,
+    /// What healing did (or would do) for each healed discrepancy
+    actions: Vec<HealingAction>,
 }
 
 /// Pre-fetched data for a package
@@ -63,26 +122,134 @@ struct MTimeUpdate {
 struct PackageData {
     package: sps2_state::Package,
     file_entries: Vec<PackageFileEntry>,
-    mtime_trackers: HashMap<String, i64>, // file_path -> last_verified_mtime
+    file_sizes: HashMap<String, u64>, // file_path -> stored size (Full only)
+}
+
+/// Build a byte-based progress update with speed and ETA
+fn byte_progress_event(done: u64, total: u64, elapsed: Duration) -> ProgressEvent {
+    let secs = elapsed.as_secs_f64();
+    let speed = (secs > 0.0 && done > 0).then(|| done as f64 / secs);
+    let eta = speed.map(|bytes_per_sec| {
+        Duration::from_secs_f64(total.saturating_sub(done) as f64 / bytes_per_sec)
+    });
+
+    ProgressEvent::Updated {
+        id: VERIFY_PROGRESS_ID.to_string(),
+        current: done,
+        total: Some(total),
+        phase: None,
+        speed,
+        eta,
+        efficiency: None,
+    }
+}
+
+/// Build a file-count progress update for `phase`
+fn file_progress_event(done: u64, total: Option<u64>, phase: usize) -> ProgressEvent {
+    ProgressEvent::Updated {
+        id: VERIFY_FILES_PROGRESS_ID.to_string(),
+        current: done,
+        total,
+        phase: Some(phase),
+        speed: None,
+        eta: None,
+        efficiency: None,
+    }
+}
+
+/// Decide whether Quick verification should hash a file
+///
+/// Files smaller than `threshold` bytes are hashed outright. Larger files are
+/// only size-checked, unless their size no longer matches the stored size, in
+/// which case they are hashed to report the corruption. A threshold of 0
+/// keeps Quick verification existence-only.
+fn quick_needs_hash(stored_size: Option<u64>, actual_size: u64, threshold: u64) -> bool {
+    if threshold == 0 {
+        return false;
+    }
+    match stored_size {
+        Some(size) => size < threshold || size != actual_size,
+        None => actual_size < threshold,
+    }
+}
+
+/// Whether a symlink cycle at `full_path` fails verification, or `None`
+/// when the symlink policy ignores symlinks
+///
+/// Under [`SymlinkPolicy::Strict`], cycles inside the configured lenient
+/// directories are still only reported.
+fn symlink_cycle_is_fatal(config: &GuardConfig, full_path: &Path) -> Option<bool> {
+    match config.symlink_policy {
+        SymlinkPolicy::Strict => Some(
+            !config
+                .lenient_symlink_directories
+                .iter()
+                .any(|dir| full_path.starts_with(dir)),
+        ),
+        SymlinkPolicy::Lenient => Some(false),
+        SymlinkPolicy::Ignore => None,
+    }
+}
+
+/// Compare the recorded mode of `entry` with the mode on disk
+///
+/// Only [`VERIFIED_MODE_BITS`] are compared, but a mismatch reports the
+/// full permission bits of both modes.
+fn permission_mismatch(
+    package: &sps2_state::Package,
+    entry: &PackageFileEntry,
+    metadata: &std::fs::Metadata,
+) -> Option<Discrepancy> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let expected_mode = u32::try_from(entry.permissions).ok()? & 0o7777;
+    let actual_mode = metadata.permissions().mode() & 0o7777;
+    if expected_mode & VERIFIED_MODE_BITS == actual_mode & VERIFIED_MODE_BITS {
+        return None;
+    }
+    Some(Discrepancy::PermissionMismatch {
+        package_name: package.name.clone(),
+        package_version: package.version.clone(),
+        file_path: entry.relative_path.clone(),
+        expected_mode,
+        actual_mode,
+    })
 }
 
 /// Verify a single package with pre-fetched data (for parallel verification)
+///
+/// Content hashes run concurrently, each holding a permit from
+/// `hash_permits`, which is shared with every other package being verified.
+/// Files whose stamp matches an entry in `hash_cache` holding the expected
+/// hash are not read again; any other cached hash is re-checked from disk
+/// before the file is reported as corrupted.
+#[allow(clippy::too_many_arguments)]
 async fn verify_single_package_with_data(
     _state_manager: &StateManager,
     store: &PackageStore,
     package_data: PackageData,
     level: VerificationLevel,
-    _guard_config: &GuardConfig,
+    guard_config: &GuardConfig,
     live_path: &std::path::Path,
     _state_id: &uuid::Uuid,
+    bytes_done: &Arc<AtomicU64>,
+    files_done: &Arc<AtomicU64>,
+    hash_permits: &Arc<tokio::sync::Semaphore>,
+    hash_cache: Option<&HashCache>,
 ) -> Result<(String, String, SinglePackageResult), Error> {
     let package = &package_data.package;
     let file_entries = &package_data.file_entries;
     let mut discrepancies = Vec::new();
     let mut tracked_files: HashSet<std::path::PathBuf> = HashSet::new();
-    let mut mtime_updates = Vec::new();
+    let mut hash_updates = Vec::new();
     let mut cache_hits = 0;
     let mut cache_misses = 0;
+    let mut present_files = 0;
+    let mut deep_checked = 0;
+    let mut hash_checks = Vec::new();
+    // Checks resolved from the hash cache, with the cached hash
+    let mut cached = Vec::new();
+    let quick_hash_threshold = guard_config.performance.quick_hash_threshold;
 
     // Get package manifest from store
     let package_hash =
@@ -92,7 +259,9 @@ async fn verify_single_package_with_data(
     let store_path = store.package_path(&package_hash);
 
     if !store_path.exists() {
-        // Package content missing - can't verify files
+        // Package content missing - can't verify files, but its bytes are accounted for
+        bytes_done.fetch_add(package_data.file_sizes.values().sum(), Ordering::Relaxed);
+        files_done.fetch_add(file_entries.len() as u64, Ordering::Relaxed);
         discrepancies.push(Discrepancy::MissingPackageContent {
             package_name: package.name.clone(),
             package_version: package.version.clone(),
@@ -103,9 +272,11 @@ async fn verify_single_package_with_data(
             SinglePackageResult {
                 discrepancies,
                 tracked_files,
-                mtime_updates,
+                hash_updates,
                 cache_hits,
                 cache_misses,
+                deep_checked,
+                shallow_checked: 0,
             },
         ));
     }
@@ -113,124 +284,205 @@ async fn verify_single_package_with_data(
     // Verify package exists in store (but we already have file entries from pre-fetch)
     let _ = sps2_store::StoredPackage::load(&store_path).await?;
 
+    // Bytes and the file count of the previous entry are credited once the
+    // loop moves past it, so every exit path (skip, cache hit) counts toward
+    // progress; queued hashes credit theirs once they finish
+    let mut pending_bytes = 0;
+    let mut pending_files = 0;
+
     // Process all files from pre-fetched file entries to ensure they're all tracked
     for entry in file_entries {
+        bytes_done.fetch_add(pending_bytes, Ordering::Relaxed);
+        files_done.fetch_add(std::mem::replace(&mut pending_files, 1), Ordering::Relaxed);
+        pending_bytes = package_data
+            .file_sizes
+            .get(&entry.relative_path)
+            .copied()
+            .unwrap_or(0);
+
         let file_path = &entry.relative_path;
 
         tracked_files.insert(std::path::PathBuf::from(file_path));
         let full_path = live_path.join(file_path);
 
-        // Basic existence check
-        if !full_path.exists() {
+        // Basic existence check; a symlink must also resolve
+        let metadata = match tokio::fs::symlink_metadata(&full_path).await {
+            Ok(metadata) if metadata.is_symlink() => {
+                if let Some(fatal) = symlink_cycle_is_fatal(guard_config, &full_path) {
+                    if let Some(chain) = find_symlink_cycle(&full_path).await {
+                        // The chain has canonical parents, so compare against
+                        // the canonical prefix (/var is /private/var on macOS)
+                        let canonical_live = tokio::fs::canonicalize(live_path)
+                            .await
+                            .unwrap_or_else(|_| live_path.to_path_buf());
+                        discrepancies.push(Discrepancy::SymlinkCycle {
+                            package_name: package.name.clone(),
+                            package_version: package.version.clone(),
+                            file_path: file_path.to_string(),
+                            chain: chain
+                                .iter()
+                                .map(|link| {
+                                    link.strip_prefix(&canonical_live)
+                                        .unwrap_or(link)
+                                        .display()
+                                        .to_string()
+                                })
+                                .collect(),
+                            fatal,
+                        });
+                        continue;
+                    }
+                }
+                full_path.exists().then_some(metadata)
+            }
+            Ok(metadata) => Some(metadata),
+            Err(_) => None,
+        };
+        let Some(metadata) = metadata else {
             discrepancies.push(Discrepancy::MissingFile {
                 package_name: package.name.clone(),
                 package_version: package.version.clone(),
                 file_path: file_path.to_string(),
             });
             continue;
+        };
+        present_files += 1;
+
+        if level == VerificationLevel::Quick && quick_hash_threshold == 0 {
+            continue;
         }
 
-        // For Full verification, check content hash
-        if level == VerificationLevel::Full {
-            // Skip hash verification for directories and symlinks
-            let metadata = tokio::fs::symlink_metadata(&full_path).await?;
-            if metadata.is_dir() || metadata.is_symlink() {
-                continue;
+        // Standard and Full also check the mode of regular files
+        if level >= VerificationLevel::Standard && metadata.is_file() {
+            if let Some(discrepancy) = permission_mismatch(package, entry, &metadata) {
+                discrepancies.push(discrepancy);
             }
+        }
 
-            // Check for special file types that require custom handling
-            if let Some(special_type) = crate::types::SpecialFileType::from_metadata(&metadata) {
-                if special_type.should_skip_verification() {
-                    // Log the special file for tracking but skip content verification
-                    discrepancies.push(crate::types::Discrepancy::UnsupportedSpecialFile {
-                        package_name: package.name.clone(),
-                        package_version: package.version.clone(),
-                        file_path: file_path.to_string(),
-                        file_type: special_type,
-                    });
+        match level {
+            // For Full verification, check content hash
+            VerificationLevel::Full => {
+                // Skip hash verification for directories and symlinks
+                if metadata.is_dir() || metadata.is_symlink() {
                     continue;
                 }
-            }
-
-            // Skip Python bytecode files and cache directories from hash verification
-            if file_path.ends_with(".pyc") || file_path.contains("__pycache__") {
-                continue;
-            }
 
-            // Skip Python runtime-generated files that get modified during execution
-            if is_python_runtime_file(file_path) {
-                continue;
-            }
-
-            // Find the file entry for this path
-            let file_entry = file_entries
-                .iter()
-                .find(|entry| entry.relative_path == *file_path);
-
-            if let Some(entry) = file_entry {
-                let expected_hash = Hash::from_hex(&entry.file_hash).map_err(|e| {
-                    sps2_errors::OpsError::OperationFailed {
-                        message: format!("Invalid file hash in database: {e}"),
-                    }
-                })?;
-
-                // MTIME-ONLY OPTIMIZATION: Only verify if file has been modified
-                let needs_verification = {
-                    // Get current file modification time
-                    let file_mtime = metadata
-                        .modified()
-                        .ok()
-                        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                        .map(|d| d.as_secs() as i64)
-                        .unwrap_or(0);
-
-                    // Check if we have a stored mtime for this file
-                    if let Some(&last_verified_mtime) = package_data.mtime_trackers.get(file_path) {
-                        if file_mtime > last_verified_mtime {
-                            // File has been modified since last verification
-                            cache_misses += 1;
-                            true
-                        } else {
-                            // File unchanged since last verification - skip
-                            cache_hits += 1;
-                            false
-                        }
-                    } else {
-                        // No mtime stored - need to verify
-                        cache_misses += 1;
-                        true
-                    }
-                };
-
-                if needs_verification {
-                    // Get current file mtime for tracking
-                    let file_mtime = metadata
-                        .modified()
-                        .ok()
-                        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                        .map(|d| d.as_secs() as i64)
-                        .unwrap_or(0);
-
-                    // Calculate actual hash
-                    let actual_hash = Hash::hash_file(&full_path).await?;
-
-                    if actual_hash != expected_hash {
-                        discrepancies.push(Discrepancy::CorruptedFile {
+                // Check for special file types that require custom handling
+                if let Some(special_type) = crate::types::SpecialFileType::from_metadata(&metadata)
+                {
+                    if special_type.should_skip_verification() {
+                        // Log the special file for tracking but skip content verification
+                        discrepancies.push(crate::types::Discrepancy::UnsupportedSpecialFile {
                             package_name: package.name.clone(),
                             package_version: package.version.clone(),
                             file_path: file_path.to_string(),
-                            expected_hash: expected_hash.to_hex(),
-                            actual_hash: actual_hash.to_hex(),
+                            file_type: special_type,
                         });
+                        continue;
                     }
+                }
 
-                    // Always update mtime tracker after verification (success or failure)
-                    mtime_updates.push(MTimeUpdate {
-                        file_path: file_path.to_string(),
-                        verified_mtime: file_mtime,
-                    });
+                // Skip Python bytecode files and cache directories from hash verification
+                if file_path.ends_with(".pyc") || file_path.contains("__pycache__") {
+                    continue;
+                }
+
+                // Skip Python runtime-generated files that get modified during execution
+                if is_python_runtime_file(file_path) {
+                    continue;
+                }
+            }
+            // Quick sampling: hash small files, size-check large ones
+            VerificationLevel::Quick => {
+                if !metadata.is_file()
+                    || file_path.ends_with(".pyc")
+                    || file_path.contains("__pycache__")
+                    || is_python_runtime_file(file_path)
+                {
+                    continue;
+                }
+
+                let stored_size = package_data.file_sizes.get(file_path).copied();
+                if !quick_needs_hash(stored_size, metadata.len(), quick_hash_threshold) {
+                    continue;
                 }
             }
+            VerificationLevel::Standard => continue,
+        }
+
+        let expected_hash = Hash::from_hex(&entry.file_hash).map_err(|e| {
+            sps2_errors::OpsError::OperationFailed {
+                message: format!("Invalid file hash in database: {e}"),
+            }
+        })?;
+        let check = HashCheck {
+            file_path: file_path.to_string(),
+            full_path,
+            expected_hash,
+            size: 0,
+            stamp: FileStamp::from_metadata(&metadata),
+        };
+        deep_checked += 1;
+
+        // Unchanged files whose cached hash still matches are not read; a
+        // stale entry, e.g. from before an upgrade, is re-hashed
+        let cached_hash = hash_cache
+            .and_then(|cache| {
+                cache.lookup(
+                    &check.file_path,
+                    check.stamp,
+                    check.expected_hash.algorithm(),
+                )
+            })
+            .filter(|hash| *hash == check.expected_hash);
+        if let Some(hash) = cached_hash {
+            cache_hits += 1;
+            cached.push((check, hash));
+        } else {
+            cache_misses += 1;
+            hash_checks.push(HashCheck {
+                size: std::mem::take(&mut pending_bytes),
+                ..check
+            });
+            pending_files = 0;
+        }
+    }
+
+    bytes_done.fetch_add(pending_bytes, Ordering::Relaxed);
+    files_done.fetch_add(pending_files, Ordering::Relaxed);
+
+    let bytes = Arc::clone(bytes_done);
+    let files = Arc::clone(files_done);
+    let hashed = concurrency::run_bounded(hash_checks, hash_permits, move |check: HashCheck| {
+        let bytes = Arc::clone(&bytes);
+        let files = Arc::clone(&files);
+        async move {
+            let actual_hash =
+                Hash::hash_file_with_algorithm(&check.full_path, check.expected_hash.algorithm())
+                    .await?;
+            bytes.fetch_add(check.size, Ordering::Relaxed);
+            files.fetch_add(1, Ordering::Relaxed);
+            Ok((check, actual_hash))
+        }
+    })
+    .await?;
+
+    for (check, actual_hash) in &hashed {
+        hash_updates.push(HashUpdate {
+            file_path: check.file_path.clone(),
+            stamp: check.stamp,
+            hash: actual_hash.clone(),
+        });
+    }
+    for (check, actual_hash) in hashed.into_iter().chain(cached) {
+        if actual_hash != check.expected_hash {
+            discrepancies.push(Discrepancy::CorruptedFile {
+                package_name: package.name.clone(),
+                package_version: package.version.clone(),
+                file_path: check.file_path.clone(),
+                expected_hash: check.expected_hash.to_hex(),
+                actual_hash: actual_hash.to_hex(),
+            });
         }
     }
 
@@ -251,9 +503,11 @@ async fn verify_single_package_with_data(
         SinglePackageResult {
             discrepancies,
             tracked_files,
-            mtime_updates,
+            hash_updates,
             cache_hits,
             cache_misses,
+            deep_checked,
+            shallow_checked: present_files - deep_checked,
         },
     ))
 }
@@ -375,7 +629,7 @@ impl StateVerificationGuard {
     /// # Errors
     ///
     /// Returns an error if state verification fails or database operations fail.
-    pub async fn verify_only(&mut self) -> Result<VerificationResult, Error> {
+    pub async fn verify_only(&self) -> Result<VerificationResult, Error> {
         let state_id = self.state_manager.get_active_state().await?;
 
         // Get all installed packages from current state
@@ -407,6 +661,9 @@ impl StateVerificationGuard {
             .await
         {
             Ok(verification_result) => {
+                if verification_result.is_valid {
+                    self.record_verified_state(&state_id, true).await;
+                }
                 let coverage_percent = verification_result
                     .coverage
                     .as_ref()
@@ -444,9 +701,13 @@ impl StateVerificationGuard {
     ///
     /// Returns an error if state verification fails or database operations fail.
     pub async fn verify_with_scope(
-        &mut self,
+        &self,
         scope: &VerificationScope,
     ) -> Result<VerificationResult, Error> {
+        if let VerificationScope::Paths { paths } = scope {
+            return self.verify_paths(paths).await;
+        }
+
         let state_id = self.state_manager.get_active_state().await?;
 
         // Emit verification started event
@@ -454,10 +715,46 @@ impl StateVerificationGuard {
             "Starting scoped state verification for state {state_id} (scope: {scope:?})"
         ));
 
+        // Incremental runs narrow the scope to packages changed since the last verified state
+        let plan = match scope {
+            VerificationScope::Incremental { force_full } => Some(
+                verification::scope::resolve_incremental_scope(
+                    &self.state_manager,
+                    &state_id,
+                    *force_full,
+                    self.config.performance.full_sweep_interval,
+                )
+                .await?,
+            ),
+            _ => None,
+        };
+        let resolved_scope = plan.as_ref().map_or_else(
+            || scope.clone(),
+            verification::scope::IncrementalPlan::scope,
+        );
+        if let Some(plan) = &plan {
+            self.emit_debug(format!(
+                "Incremental verification: {} changed, {} unchanged, full sweep: {}",
+                plan.changes.len(),
+                plan.skipped_unchanged.len(),
+                plan.full_sweep
+            ));
+        }
+        // Partial incremental runs keep the incremental scope so orphan detection is skipped
+        let verify_scope = if plan.as_ref().is_some_and(|p| !p.full_sweep) {
+            scope
+        } else {
+            &resolved_scope
+        };
+
         // Get packages based on scope
         let (packages_to_verify, total_packages, total_files) =
-            verification::scope::get_packages_for_scope(&self.state_manager, &state_id, scope)
-                .await?;
+            verification::scope::get_packages_for_scope(
+                &self.state_manager,
+                &state_id,
+                &resolved_scope,
+            )
+            .await?;
 
         // Always use parallel verification (better performance with batched DB writes)
         self.emit_debug(format!(
@@ -469,7 +766,7 @@ impl StateVerificationGuard {
 
         // Use parallel verification
         let mut result = self
-            .verify_packages_parallel(&packages_to_verify, scope)
+            .verify_packages_parallel(&packages_to_verify, verify_scope)
             .await?;
 
         // Update coverage with scope-specific totals
@@ -488,16 +785,277 @@ impl StateVerificationGuard {
             };
         }
 
+        if let Some(plan) = plan {
+            result.skipped_unchanged = plan.skipped_unchanged;
+            if result.is_valid {
+                self.record_verified_state(&state_id, plan.full_sweep).await;
+            }
+        } else if result.is_valid && matches!(scope, VerificationScope::Full) {
+            self.record_verified_state(&state_id, true).await;
+        }
+
+        Ok(result)
+    }
+
+    /// Verify exactly the given files without healing
+    ///
+    /// Paths may be absolute inside the live prefix or relative to it. Each
+    /// tracked path is checked at the configured level; paths no installed
+    /// package tracks are reported in [`VerificationResult::untracked_paths`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database operations fail or a file cannot be read.
+    pub async fn verify_paths(&self, paths: &[PathBuf]) -> Result<VerificationResult, Error> {
+        let start_time = Instant::now();
+        let state_id = self.state_manager.get_active_state().await?;
+        let live_path = self.state_manager.live_path().to_path_buf();
+
+        // Tracked entries store paths relative to the live prefix
+        let requested: Vec<(&PathBuf, String)> = paths
+            .iter()
+            .map(|path| {
+                let relative = path.strip_prefix(&live_path).unwrap_or(path);
+                (path, relative.to_string_lossy().into_owned())
+            })
+            .collect();
+        let wanted: HashSet<&str> = requested.iter().map(|(_, rel)| rel.as_str()).collect();
+
+        let mut package_data_list = Vec::new();
+        let mut matched: HashSet<String> = HashSet::new();
+        let mut db_tx = self.state_manager.begin_transaction().await?;
+        for package in queries::get_state_packages(&mut db_tx, &state_id).await? {
+            let file_entries: Vec<PackageFileEntry> = queries::get_package_file_entries_by_name(
+                &mut db_tx,
+                &state_id,
+                &package.name,
+                &package.version,
+            )
+            .await?
+            .into_iter()
+            .filter(|entry| wanted.contains(entry.relative_path.as_str()))
+            .collect();
+            if file_entries.is_empty() {
+                continue;
+            }
+
+            let mut file_sizes = HashMap::new();
+            for entry in &file_entries {
+                if let Ok(hash) = Hash::from_hex(&entry.file_hash) {
+                    if let Some(object) = queries::get_file_object(&mut db_tx, &hash).await? {
+                        file_sizes.insert(
+                            entry.relative_path.clone(),
+                            u64::try_from(object.size).unwrap_or(0),
+                        );
+                    }
+                }
+                matched.insert(entry.relative_path.clone());
+            }
+
+            package_data_list.push(PackageData {
+                package,
+                file_entries,
+                file_sizes,
+            });
+        }
+        db_tx.commit().await?;
+
+        let untracked_paths: Vec<PathBuf> = requested
+            .iter()
+            .filter(|(_, rel)| !matched.contains(rel))
+            .map(|(path, _)| (*path).clone())
+            .collect();
+
+        self.emit_debug(format!(
+            "Verifying {} tracked paths across {} packages ({} untracked)",
+            matched.len(),
+            package_data_list.len(),
+            untracked_paths.len()
+        ));
+
+        let total_packages = package_data_list.len();
+        let bytes_done = Arc::new(AtomicU64::new(0));
+        let files_done = Arc::new(AtomicU64::new(0));
+        let hash_permits = Arc::new(tokio::sync::Semaphore::new(
+            self.config.performance.max_concurrent_tasks.max(1),
+        ));
+        let hash_cache = self.load_hash_cache().await;
+        let mut discrepancies = Vec::new();
+        let mut hash_updates = Vec::new();
+        let mut cache_hits = 0;
+        let mut cache_misses = 0;
+        let mut deep_checked_files = 0;
+        let mut shallow_checked_files = 0;
+        for package_data in package_data_list {
+            let (_, _, package_result) = verify_single_package_with_data(
+                &self.state_manager,
+                &self.store,
+                package_data,
+                self.level(),
+                &self.config,
+                &live_path,
+                &state_id,
+                &bytes_done,
+                &files_done,
+                &hash_permits,
+                (!self.config.force_full).then_some(&hash_cache),
+            )
+            .await?;
+            discrepancies.extend(package_result.discrepancies);
+            hash_updates.extend(package_result.hash_updates);
+            cache_hits += package_result.cache_hits;
+            cache_misses += package_result.cache_misses;
+            deep_checked_files += package_result.deep_checked;
+            shallow_checked_files += package_result.shallow_checked;
+        }
+        self.update_hash_cache(hash_cache, hash_updates, None).await;
+
+        let coverage = crate::types::VerificationCoverage::new(
+            total_packages,
+            total_packages,
+            paths.len(),
+            matched.len(),
+            vec![],
+            false,
+        )
+        .with_check_depth(deep_checked_files, shallow_checked_files);
+        let cache_hit_rate = if cache_hits + cache_misses > 0 {
+            cache_hits as f64 / (cache_hits + cache_misses) as f64
+        } else {
+            0.0
+        };
+        let duration_ms = u64::try_from(start_time.elapsed().as_millis()).unwrap_or(u64::MAX);
+
+        let mut result = VerificationResult::with_coverage_and_cache(
+            state_id,
+            discrepancies,
+            duration_ms,
+            coverage,
+            cache_hit_rate,
+        );
+        result.untracked_paths = untracked_paths;
         Ok(result)
     }
 
+    /// Verify the installed files of the package `name` without healing
+    ///
+    /// The version installed in the active state is checked at the
+    /// configured level. No orphan scan is performed, so the cost is
+    /// proportional to the package's own files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `name` is not installed in the active state, or if
+    /// verification or database operations fail.
+    pub async fn verify_package(&self, name: &str) -> Result<VerificationResult, Error> {
+        let state_id = self.state_manager.get_active_state().await?;
+        let mut tx = self.state_manager.begin_transaction().await?;
+        let packages = queries::get_state_packages(&mut tx, &state_id).await?;
+        tx.commit().await?;
+
+        let package = packages
+            .into_iter()
+            .find(|package| package.name == name)
+            .ok_or_else(|| InstallError::PackageNotInstalled {
+                package: name.to_string(),
+            })?;
+        self.verify_with_scope(&VerificationScope::Package {
+            name: package.name,
+            version: package.version,
+        })
+        .await
+    }
+
+    /// Move quarantined orphans back into the live prefix
+    ///
+    /// `manifest_path` is the `manifest.json` of a quarantine run. Files
+    /// that exist again in the live prefix are left alone. Returns the
+    /// number of entries restored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest cannot be read or a quarantined file
+    /// is missing, altered or cannot be moved back.
+    pub async fn restore_quarantined(&self, manifest_path: &Path) -> Result<usize, Error> {
+        let restored = crate::healing::quarantine::restore_quarantine(
+            manifest_path,
+            self.state_manager.live_path(),
+        )
+        .await?;
+        self.emit_debug(format!(
+            "Restored {restored} quarantined orphans from {}",
+            manifest_path.display()
+        ));
+        Ok(restored)
+    }
+
+    /// Build the user-file matcher for orphan categorization and healing
+    ///
+    /// The active state's creation time anchors the "modified after install" rule.
+    async fn user_file_matcher(&self, state_id: &uuid::Uuid) -> Result<UserFileMatcher, Error> {
+        let rules = &self.config.user_files;
+        let installed_at = if rules.modified_after_install {
+            let mut tx = self.state_manager.begin_transaction().await?;
+            let state = queries::get_state(&mut tx, state_id).await?;
+            tx.commit().await?;
+            state
+                .and_then(|state| u64::try_from(state.created_at).ok())
+                .map(|secs| std::time::UNIX_EPOCH + Duration::from_secs(secs))
+        } else {
+            None
+        };
+        Ok(UserFileMatcher::new(rules, installed_at))
+    }
+
+    /// Load the hash cache kept in the state directory
+    async fn load_hash_cache(&self) -> HashCache {
+        HashCache::load(self.state_manager.state_path().join(HASH_CACHE_FILE)).await
+    }
+
+    /// Record freshly computed hashes in the hash cache and save it
+    ///
+    /// When `tracked` is given, entries for other files are dropped. Failing
+    /// to save only costs re-hashing on the next run, so errors are logged.
+    async fn update_hash_cache(
+        &self,
+        mut cache: HashCache,
+        updates: Vec<HashUpdate>,
+        tracked: Option<&HashSet<PathBuf>>,
+    ) {
+        for update in updates {
+            cache.insert(update.file_path, update.stamp, &update.hash);
+        }
+        if let Some(tracked) = tracked {
+            cache.retain(|file_path| tracked.contains(file_path));
+        }
+        if let Err(e) = cache.save().await {
+            self.emit_debug(format!("Failed to save verification hash cache: {e}"));
+        }
+    }
+
+    /// Remember `state_id` as verified so incremental runs can skip unchanged packages
+    ///
+    /// Failing to record the marker only costs a future full sweep, so errors are logged.
+    async fn record_verified_state(&self, state_id: &uuid::Uuid, full_sweep: bool) {
+        let outcome = async {
+            let mut tx = self.state_manager.begin_transaction().await?;
+            queries::set_guard_verification_marker(&mut tx, state_id, full_sweep).await?;
+            tx.commit().await?;
+            Ok::<(), Error>(())
+        }
+        .await;
+        if let Err(e) = outcome {
+            self.emit_debug(format!("Failed to record verified state {state_id}: {e}"));
+        }
+    }
+
     /// Verify current state and optionally heal discrepancies
     ///
     /// # Errors
     ///
     /// Returns an error if state verification fails or database operations fail.
     pub async fn verify_and_heal(
-        &mut self,
+        &self,
         config: &sps2_config::Config,
     ) -> Result<VerificationResult, Error> {
         // First, run verification to detect discrepancies
@@ -545,153 +1103,63 @@ impl StateVerificationGuard {
         let manual_intervention_count =
             verification_result.discrepancies.len() - auto_heal_count - confirmation_required;
 
-        self.emit(AppEvent::Guard(GuardEvent::HealingStarted {
+        if !self.config.healing_dry_run {
+            self.emit(AppEvent::Guard(GuardEvent::HealingStarted {
+                operation_id: healing_ctx_events.operation_id().to_string(),
+                plan: GuardHealingPlan {
+                    total: verification_result.discrepancies.len(),
+                    auto_heal: auto_heal_count,
+                    confirmation_required,
+                    manual_only: manual_intervention_count,
+                },
+            }));
+        }
+
+        let HealingOutcome {
+            healed: healed_count,
+            failed: failed_healings,
+            actions,
+        } = self
+            .heal_discrepancies(
+                config,
+                &verification_result.discrepancies,
+                &healing_ctx_events,
+            )
+            .await;
+        verification_result.healing_actions = actions;
+        if self.config.healing_dry_run {
+            return Ok(verification_result);
+        }
+
+        // Update verification result with healing results
+        verification_result.discrepancies = failed_healings.clone();
+        verification_result.is_valid = verification_result
+            .discrepancies
+            .iter()
+            .all(Discrepancy::is_advisory);
+
+        let duration_ms = u64::try_from(
+            healing_ctx_events
+                .get_summary_stats()
+                .operation_duration
+                .as_millis(),
+        )
+        .unwrap_or(u64::MAX);
+        verification_result.duration_ms = duration_ms;
+
+        // Emit healing completion event
+        self.emit(AppEvent::Guard(GuardEvent::HealingCompleted {
             operation_id: healing_ctx_events.operation_id().to_string(),
-            plan: GuardHealingPlan {
-                total: verification_result.discrepancies.len(),
-                auto_heal: auto_heal_count,
-                confirmation_required,
-                manual_only: manual_intervention_count,
-            },
+            healed: healed_count,
+            failed: failed_healings.len(),
+            duration_ms,
         }));
 
-        // Create healing context
-        let healing_ctx = HealingContext {
-            state_manager: &self.state_manager,
-            store: &self.store,
-            tx: &self.tx,
-        };
-
-        // Track healing results
-        let mut healed_count = 0;
-        let mut failed_healings = Vec::new();
-
-        // Process each discrepancy
-        let discrepancies = verification_result.discrepancies.clone();
-        for discrepancy in discrepancies.iter() {
-            match discrepancy {
-                Discrepancy::MissingFile {
-                    package_name,
-                    package_version,
-                    file_path,
-                } => {
-                    match crate::healing::files::restore_missing_file(
-                        &healing_ctx,
-                        package_name,
-                        package_version,
-                        file_path,
-                    )
-                    .await
-                    {
-                        Ok(()) => {
-                            healed_count += 1;
-                            healing_ctx_events.emit_healing_result(
-                                "MissingFile",
-                                file_path,
-                                true,
-                                "file restored from store",
-                                None,
-                            );
-                        }
-                        Err(e) => {
-                            failed_healings.push(discrepancy.clone());
-                            healing_ctx_events.emit_healing_result(
-                                "MissingFile",
-                                file_path,
-                                false,
-                                "file restoration failed",
-                                Some(e.to_string()),
-                            );
-                        }
-                    }
-                }
-                Discrepancy::OrphanedFile {
-                    file_path,
-                    category,
-                } => {
-                    match crate::healing::orphans::handle_orphaned_file(
-                        &self.state_manager,
-                        &self.tx,
-                        file_path,
-                        category,
-                        config,
-                    )
-                    .await
-                    {
-                        Ok(()) => {
-                            healed_count += 1;
-                            self.emit_debug(format!("Successfully handled orphaned file: {file_path} (category: {category:?})"));
-                        }
-                        Err(e) => {
-                            failed_healings.push(discrepancy.clone());
-                            self.emit_debug(format!(
-                                "Failed to handle orphaned file {file_path}: {e}"
-                            ));
-                        }
-                    }
-                }
-                Discrepancy::CorruptedFile {
-                    package_name,
-                    package_version,
-                    file_path,
-                    expected_hash,
-                    actual_hash,
-                } => {
-                    match crate::healing::files::heal_corrupted_file(
-                        &healing_ctx,
-                        package_name,
-                        package_version,
-                        file_path,
-                        expected_hash,
-                        actual_hash,
-                    )
-                    .await
-                    {
-                        Ok(()) => {
-                            healed_count += 1;
-                            self.emit_debug(format!("Successfully restored corrupted file: {file_path} for {package_name}-{package_version}"));
-                        }
-                        Err(e) => {
-                            failed_healings.push(discrepancy.clone());
-                            self.emit_debug(format!(
-                                "Failed to restore corrupted file {file_path}: {e}"
-                            ));
-                        }
-                    }
-                }
-                // Handle other discrepancy types as needed
-                _ => {
-                    failed_healings.push(discrepancy.clone());
-                }
-            }
-        }
-
-        // Update verification result with healing results
-        verification_result.discrepancies = failed_healings.clone();
-        verification_result.is_valid = verification_result.discrepancies.is_empty();
-
-        let duration_ms = u64::try_from(
-            healing_ctx_events
-                .get_summary_stats()
-                .operation_duration
-                .as_millis(),
-        )
-        .unwrap_or(u64::MAX);
-        verification_result.duration_ms = duration_ms;
-
-        // Emit healing completion event
-        self.emit(AppEvent::Guard(GuardEvent::HealingCompleted {
-            operation_id: healing_ctx_events.operation_id().to_string(),
-            healed: healed_count,
-            failed: failed_healings.len(),
-            duration_ms,
-        }));
-
-        // Record healing results in context and emit summary
-        for discrepancy in &failed_healings {
-            healing_ctx_events.record_discrepancy(discrepancy.clone());
-        }
-        healing_ctx_events.emit_error_summary();
+        // Record healing results in context and emit summary
+        for discrepancy in &failed_healings {
+            healing_ctx_events.record_discrepancy(discrepancy.clone());
+        }
+        healing_ctx_events.emit_error_summary();
 
         // Optional refcount synchronization after healing completes
         if let Some(guard_cfg) = &config.guard {
@@ -718,7 +1186,7 @@ impl StateVerificationGuard {
     ///
     /// Returns an error if state verification fails or database operations fail.
     pub async fn verify_and_heal_scoped(
-        &mut self,
+        &self,
         config: &sps2_config::Config,
         scope: &VerificationScope,
     ) -> Result<VerificationResult, Error> {
@@ -769,89 +1237,152 @@ impl StateVerificationGuard {
         let manual_intervention_count =
             verification_result.discrepancies.len() - auto_heal_count - confirmation_required;
 
-        self.emit(AppEvent::Guard(GuardEvent::HealingStarted {
+        if !self.config.healing_dry_run {
+            self.emit(AppEvent::Guard(GuardEvent::HealingStarted {
+                operation_id: healing_ctx_events.operation_id().to_string(),
+                plan: GuardHealingPlan {
+                    total: verification_result.discrepancies.len(),
+                    auto_heal: auto_heal_count,
+                    confirmation_required,
+                    manual_only: manual_intervention_count,
+                },
+            }));
+        }
+
+        let HealingOutcome {
+            healed: healed_count,
+            failed: failed_healings,
+            actions,
+        } = self
+            .heal_discrepancies(
+                config,
+                &verification_result.discrepancies,
+                &healing_ctx_events,
+            )
+            .await;
+        verification_result.healing_actions = actions;
+        if self.config.healing_dry_run {
+            return Ok(verification_result);
+        }
+
+        // Update verification result with healing results
+        verification_result.discrepancies = failed_healings;
+        verification_result.is_valid = verification_result
+            .discrepancies
+            .iter()
+            .all(Discrepancy::is_advisory);
+
+        let duration_ms = u64::try_from(start_time.elapsed().as_millis()).unwrap_or(u64::MAX);
+        verification_result.duration_ms = duration_ms;
+
+        self.emit(AppEvent::Guard(GuardEvent::HealingCompleted {
             operation_id: healing_ctx_events.operation_id().to_string(),
-            plan: GuardHealingPlan {
-                total: verification_result.discrepancies.len(),
-                auto_heal: auto_heal_count,
-                confirmation_required,
-                manual_only: manual_intervention_count,
-            },
+            healed: healed_count,
+            failed: verification_result.discrepancies.len(),
+            duration_ms,
         }));
 
-        // Create healing context
+        self.emit_debug(format!(
+            "Scoped healing completed: {} healed, {} failed in {}ms",
+            healed_count,
+            verification_result.discrepancies.len(),
+            duration_ms
+        ));
+
+        // Optional refcount synchronization after healing completes
+        if let Some(guard_cfg) = &config.guard {
+            if guard_cfg.store_verification.sync_refcounts {
+                match self.sync_refcounts_active_state().await {
+                    Ok((s, f)) => {
+                        self.emit_debug(format!(
+                            "Guard refcount sync (active-state): store {s}, files {f}"
+                        ));
+                    }
+                    Err(e) => {
+                        self.emit_debug(format!("Guard refcount sync failed: {e}"));
+                    }
+                }
+            }
+        }
+
+        Ok(verification_result)
+    }
+
+    /// Heal each discrepancy, or work out the healing actions in a dry run
+    ///
+    /// Discrepancies that cannot be healed (or would fail to heal) are
+    /// returned in [`HealingOutcome::failed`].
+    async fn heal_discrepancies(
+        &self,
+        config: &sps2_config::Config,
+        discrepancies: &[Discrepancy],
+        events: &GuardErrorContext,
+    ) -> HealingOutcome {
+        let dry_run = self.config.healing_dry_run;
+        let user_files = match self.state_manager.get_active_state().await {
+            Ok(state_id) => self.user_file_matcher(&state_id).await.ok(),
+            Err(_) => None,
+        }
+        .unwrap_or_else(|| UserFileMatcher::new(&self.config.user_files, None));
         let healing_ctx = HealingContext {
             state_manager: &self.state_manager,
             store: &self.store,
             tx: &self.tx,
+            dry_run,
+            user_files: &user_files,
         };
 
-        // Track healing results - use same healing logic as full verification
-        let mut healed_count = 0;
-        let mut failed_healings = Vec::new();
+        let mut outcome = HealingOutcome::default();
+        let mut orphan_backup = OrphanBackup::new(&config.verification.orphaned_backup_dir);
+        let mut orphan_quarantine = OrphanQuarantine::new(&config.verification.orphaned_backup_dir);
 
-        let discrepancies = verification_result.discrepancies.clone();
-        for discrepancy in &discrepancies {
-            match discrepancy {
+        for discrepancy in discrepancies {
+            let result = match discrepancy {
                 Discrepancy::MissingFile {
                     package_name,
                     package_version,
                     file_path,
                 } => {
-                    match crate::healing::files::restore_missing_file(
+                    let result = crate::healing::files::restore_missing_file(
                         &healing_ctx,
                         package_name,
                         package_version,
                         file_path,
                     )
-                    .await
-                    {
-                        Ok(()) => {
-                            healed_count += 1;
-                            healing_ctx_events.emit_healing_result(
+                    .await;
+                    if !dry_run {
+                        match &result {
+                            Ok(_) => events.emit_healing_result(
                                 "MissingFile",
                                 file_path,
                                 true,
                                 "file restored from store",
                                 None,
-                            );
-                        }
-                        Err(e) => {
-                            failed_healings.push(discrepancy.clone());
-                            healing_ctx_events.emit_healing_result(
+                            ),
+                            Err(e) => events.emit_healing_result(
                                 "MissingFile",
                                 file_path,
                                 false,
                                 "file restoration failed",
                                 Some(e.to_string()),
-                            );
+                            ),
                         }
                     }
+                    result
                 }
                 Discrepancy::OrphanedFile {
                     file_path,
                     category,
                 } => {
-                    match crate::healing::orphans::handle_orphaned_file(
-                        &self.state_manager,
-                        &self.tx,
+                    crate::healing::orphans::handle_orphaned_file(
+                        &healing_ctx,
                         file_path,
                         category,
                         config,
+                        &mut orphan_backup,
+                        &mut orphan_quarantine,
                     )
                     .await
-                    {
-                        Ok(()) => {
-                            healed_count += 1;
-                            self.emit_debug(format!("Successfully handled orphaned file: {file_path} (category: {category:?})"));
-                        }
-                        Err(e) => {
-                            failed_healings.push(discrepancy.clone());
-                            self.emit_debug(format!(
-                                "Failed to handle orphaned file {file_path}: {e}"
-                            ));
-                        }
-                    }
                 }
                 Discrepancy::CorruptedFile {
                     package_name,
@@ -860,7 +1391,7 @@ impl StateVerificationGuard {
                     expected_hash,
                     actual_hash,
                 } => {
-                    match crate::healing::files::heal_corrupted_file(
+                    crate::healing::files::heal_corrupted_file(
                         &healing_ctx,
                         package_name,
                         package_version,
@@ -869,63 +1400,52 @@ impl StateVerificationGuard {
                         actual_hash,
                     )
                     .await
-                    {
-                        Ok(()) => {
-                            healed_count += 1;
-                            self.emit_debug(format!("Successfully restored corrupted file: {file_path} for {package_name}-{package_version}"));
-                        }
-                        Err(e) => {
-                            failed_healings.push(discrepancy.clone());
-                            self.emit_debug(format!(
-                                "Failed to restore corrupted file {file_path}: {e}"
-                            ));
-                        }
-                    }
                 }
+                Discrepancy::PermissionMismatch {
+                    file_path,
+                    expected_mode,
+                    actual_mode,
+                    ..
+                } => {
+                    crate::healing::files::restore_file_mode(
+                        &healing_ctx,
+                        file_path,
+                        *expected_mode,
+                        *actual_mode,
+                    )
+                    .await
+                }
+                // Handle other discrepancy types as needed
                 _ => {
-                    failed_healings.push(discrepancy.clone());
+                    outcome.failed.push(discrepancy.clone());
+                    continue;
                 }
-            }
-        }
-
-        // Update verification result with healing results
-        verification_result.discrepancies = failed_healings;
-        verification_result.is_valid = verification_result.discrepancies.is_empty();
-
-        let duration_ms = u64::try_from(start_time.elapsed().as_millis()).unwrap_or(u64::MAX);
-        verification_result.duration_ms = duration_ms;
-
-        self.emit(AppEvent::Guard(GuardEvent::HealingCompleted {
-            operation_id: healing_ctx_events.operation_id().to_string(),
-            healed: healed_count,
-            failed: verification_result.discrepancies.len(),
-            duration_ms,
-        }));
-
-        self.emit_debug(format!(
-            "Scoped healing completed: {} healed, {} failed in {}ms",
-            healed_count,
-            verification_result.discrepancies.len(),
-            duration_ms
-        ));
+            };
 
-        // Optional refcount synchronization after healing completes
-        if let Some(guard_cfg) = &config.guard {
-            if guard_cfg.store_verification.sync_refcounts {
-                match self.sync_refcounts_active_state().await {
-                    Ok((s, f)) => {
-                        self.emit_debug(format!(
-                            "Guard refcount sync (active-state): store {s}, files {f}"
-                        ));
-                    }
-                    Err(e) => {
-                        self.emit_debug(format!("Guard refcount sync failed: {e}"));
-                    }
+            match result {
+                Ok(action) => {
+                    self.emit_debug(format!(
+                        "{} {action:?}",
+                        if dry_run { "Would heal:" } else { "Healed:" }
+                    ));
+                    outcome.healed += 1;
+                    outcome.actions.push(action);
+                }
+                Err(e) => {
+                    self.emit_debug(format!("Failed to heal {}: {e}", discrepancy.file_path()));
+                    outcome.failed.push(discrepancy.clone());
                 }
             }
         }
 
-        Ok(verification_result)
+        if dry_run {
+            self.emit_debug(format!(
+                "Healing dry run: {} actions planned, {} discrepancies cannot be healed",
+                outcome.actions.len(),
+                outcome.failed.len()
+            ));
+        }
+        outcome
     }
 
     /// Progressive verification with automatic escalation
@@ -1016,7 +1536,7 @@ impl StateVerificationGuard {
     ///
     /// Returns an error if verification fails.
     pub async fn verify_packages_parallel(
-        &mut self,
+        &self,
         packages: &[sps2_state::Package],
         scope: &VerificationScope,
     ) -> Result<VerificationResult, Error> {
@@ -1032,12 +1552,32 @@ impl StateVerificationGuard {
             "Starting parallel verification for {} packages",
             packages.len()
         ));
+        self.emit(AppEvent::Progress(ProgressEvent::started_with_phases(
+            VERIFY_FILES_PROGRESS_ID,
+            "Verifying installed files",
+            None,
+            VERIFY_PHASES
+                .iter()
+                .map(|(name, description)| ProgressPhase::new(name, description))
+                .collect(),
+        )));
+        self.emit(AppEvent::Progress(file_progress_event(
+            0,
+            None,
+            PHASE_SCOPE,
+        )));
 
         // Pre-fetch all data from database to avoid locking issues
         self.emit_debug("Pre-fetching package data and verification cache...");
 
         let mut package_data_list = Vec::new();
         let mut all_file_hashes = HashSet::new();
+        // Sizes are needed for Full hashing and for Quick size sampling
+        let prescan_sizes = self.level() == VerificationLevel::Full
+            || (self.level() == VerificationLevel::Quick
+                && self.config.performance.quick_hash_threshold > 0);
+        let mut stored_sizes: HashMap<String, u64> = HashMap::new();
+        let mut total_bytes = 0u64;
 
         // Pre-fetch all package file entries
         let mut db_tx = self.state_manager.begin_transaction().await?;
@@ -1066,20 +1606,32 @@ impl StateVerificationGuard {
                 all_file_hashes.insert(entry.file_hash.clone());
             }
 
-            // Get mtime trackers from database
-            let mtime_tracker_list =
-                queries::get_package_file_mtimes(&mut db_tx, &package.name, &package.version)
-                    .await?;
-
-            let mtime_trackers: HashMap<String, i64> = mtime_tracker_list
-                .into_iter()
-                .map(|tracker| (tracker.file_path, tracker.last_verified_mtime))
-                .collect();
+            // Pre-scan stored sizes so progress can be reported in bytes
+            let mut file_sizes = HashMap::new();
+            if prescan_sizes {
+                for entry in &file_entries {
+                    let size = match stored_sizes.get(&entry.file_hash) {
+                        Some(size) => *size,
+                        None => {
+                            let size = match Hash::from_hex(&entry.file_hash) {
+                                Ok(hash) => queries::get_file_object(&mut db_tx, &hash)
+                                    .await?
+                                    .map_or(0, |object| u64::try_from(object.size).unwrap_or(0)),
+                                Err(_) => 0,
+                            };
+                            stored_sizes.insert(entry.file_hash.clone(), size);
+                            size
+                        }
+                    };
+                    total_bytes += size;
+                    file_sizes.insert(entry.relative_path.clone(), size);
+                }
+            }
 
             package_data_list.push(PackageData {
                 package: package.clone(),
                 file_entries,
-                mtime_trackers,
+                file_sizes,
             });
         }
 
@@ -1090,13 +1642,81 @@ impl StateVerificationGuard {
             package_data_list.len(),
             all_file_hashes.len()
         ));
+        let total_file_count: u64 = package_data_list
+            .iter()
+            .map(|data| data.file_entries.len() as u64)
+            .sum();
 
-        // Prepare shared data for parallel tasks
-        let max_concurrent = self.config.performance.max_concurrent_tasks;
+        // Prepare shared data for parallel tasks; a zero limit would leave
+        // every semaphore below without permits
+        let max_concurrent = self.config.performance.max_concurrent_tasks.max(1);
         let verification_level = self.config.verification_level;
         let guard_config = self.config.clone();
 
-        // Create tasks for parallel verification
+        // Report file (and, when hashing, byte) progress periodically; the
+        // reports double as a heartbeat for long runs
+        let bytes_done = Arc::new(AtomicU64::new(0));
+        let files_done = Arc::new(AtomicU64::new(0));
+        self.emit(AppEvent::Progress(ProgressEvent::PhaseChanged {
+            id: VERIFY_FILES_PROGRESS_ID.to_string(),
+            phase: PHASE_HASHING,
+            phase_name: VERIFY_PHASES[PHASE_HASHING].0.to_string(),
+        }));
+        if total_bytes > 0 {
+            self.emit(AppEvent::Progress(ProgressEvent::started(
+                VERIFY_PROGRESS_ID,
+                "Verifying file contents",
+                Some(total_bytes),
+            )));
+        }
+        let reporter = {
+            let tx = self.tx.clone();
+            let bytes_done = Arc::clone(&bytes_done);
+            let files_done = Arc::clone(&files_done);
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(VERIFY_PROGRESS_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    tx.emit(AppEvent::Progress(file_progress_event(
+                        files_done.load(Ordering::Relaxed),
+                        Some(total_file_count),
+                        PHASE_HASHING,
+                    )));
+                    if total_bytes > 0 {
+                        tx.emit(AppEvent::Progress(byte_progress_event(
+                            bytes_done.load(Ordering::Relaxed),
+                            total_bytes,
+                            start_time.elapsed(),
+                        )));
+                    }
+                }
+            })
+        };
+
+        // Every content hash holds one of these permits, so no more than the
+        // configured maximum are in flight across all packages. With adaptive
+        // concurrency the tuner grows or shrinks the permits below that
+        // maximum as it measures hashing throughput
+        let tuner = (self.config.performance.adaptive_concurrency && total_bytes > 0)
+            .then(|| Arc::new(Mutex::new(ConcurrencyTuner::new(max_concurrent))));
+        let initial_permits = tuner
+            .as_ref()
+            .and_then(|tuner| tuner.lock().ok().map(|tuner| tuner.current()))
+            .unwrap_or(max_concurrent);
+        let hash_permits = Arc::new(tokio::sync::Semaphore::new(initial_permits));
+        let tuning = tuner.as_ref().map(|tuner| {
+            concurrency::spawn_tuner(
+                Arc::clone(tuner),
+                Arc::clone(&hash_permits),
+                Arc::clone(&bytes_done),
+            )
+        });
+
+        // With `force_full` every file is re-hashed, refreshing the cache
+        let hash_cache = Arc::new(self.load_hash_cache().await);
+
+        // Package tasks are bounded separately, so a single large package
+        // can still use every hash permit
         let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(max_concurrent));
         let mut tasks = Vec::new();
 
@@ -1108,6 +1728,10 @@ impl StateVerificationGuard {
             let config = guard_config.clone();
             let live_path_clone = live_path.clone();
             let state_id_clone = state_id;
+            let bytes_done = Arc::clone(&bytes_done);
+            let files_done = Arc::clone(&files_done);
+            let hash_permits = Arc::clone(&hash_permits);
+            let hash_cache = (!guard_config.force_full).then(|| Arc::clone(&hash_cache));
 
             let task = tokio::spawn(async move {
                 let _permit = permit; // Hold permit for duration of task
@@ -1121,6 +1745,10 @@ impl StateVerificationGuard {
                     &config,
                     &live_path_clone,
                     &state_id_clone,
+                    &bytes_done,
+                    &files_done,
+                    &hash_permits,
+                    hash_cache.as_deref(),
                 )
                 .await;
 
@@ -1133,10 +1761,12 @@ impl StateVerificationGuard {
         // Collect results from all tasks
         let mut all_discrepancies = Vec::new();
         let mut tracked_files = HashSet::new();
-        let mut all_mtime_updates = Vec::new();
+        let mut all_hash_updates = Vec::new();
         let mut successful_verifications = 0;
         let mut total_cache_hits = 0;
         let mut total_cache_misses = 0;
+        let mut deep_checked_files = 0;
+        let mut shallow_checked_files = 0;
 
         for task in tasks {
             match task.await {
@@ -1145,9 +1775,11 @@ impl StateVerificationGuard {
                     let files_count = package_result.tracked_files.len();
                     all_discrepancies.extend(package_result.discrepancies);
                     tracked_files.extend(package_result.tracked_files);
-                    all_mtime_updates.extend(package_result.mtime_updates);
+                    all_hash_updates.extend(package_result.hash_updates);
                     total_cache_hits += package_result.cache_hits;
                     total_cache_misses += package_result.cache_misses;
+                    deep_checked_files += package_result.deep_checked;
+                    shallow_checked_files += package_result.shallow_checked;
 
                     self.emit_debug(format!(
                         "Successfully verified package {package_name}-{package_version} ({files_count} files)"
@@ -1163,35 +1795,81 @@ impl StateVerificationGuard {
             }
         }
 
-        // Apply all mtime updates in a single transaction
-        if !all_mtime_updates.is_empty() {
+        let settled_concurrency = tuning.and_then(|tuning| {
+            tuning.abort();
+            let settled = tuner?.lock().ok()?.current();
             self.emit_debug(format!(
-                "Applying {} mtime updates...",
-                all_mtime_updates.len()
+                "Adaptive verification concurrency settled at {settled} of {max_concurrent}"
             ));
+            Some(settled)
+        });
 
-            // Apply mtime updates to database
-            let mut db_tx = self.state_manager.begin_transaction().await?;
-            for update in &all_mtime_updates {
-                queries::update_file_mtime(&mut db_tx, &update.file_path, update.verified_mtime)
-                    .await?;
-            }
-            db_tx.commit().await?;
-
-            self.emit_debug(format!(
-                "Applied {} mtime updates to database",
-                all_mtime_updates.len()
-            ));
+        reporter.abort();
+        let checked_files = files_done.load(Ordering::Relaxed);
+        self.emit(AppEvent::Progress(file_progress_event(
+            checked_files,
+            Some(total_file_count),
+            PHASE_HASHING,
+        )));
+
+        let verified_bytes = bytes_done.load(Ordering::Relaxed);
+        if total_bytes > 0 {
+            let elapsed = start_time.elapsed();
+            self.emit(AppEvent::Progress(byte_progress_event(
+                verified_bytes,
+                total_bytes,
+                elapsed,
+            )));
+            self.emit(AppEvent::Progress(ProgressEvent::Completed {
+                id: VERIFY_PROGRESS_ID.to_string(),
+                duration: elapsed,
+                final_speed: (elapsed.as_secs_f64() > 0.0)
+                    .then(|| verified_bytes as f64 / elapsed.as_secs_f64()),
+                total_processed: verified_bytes,
+            }));
         }
 
-        // Check for orphaned files if not in Quick mode
-        if self.level() != VerificationLevel::Quick {
+        // Full runs also drop cache entries for files no longer installed
+        self.update_hash_cache(
+            Arc::unwrap_or_clone(hash_cache),
+            all_hash_updates,
+            matches!(scope, VerificationScope::Full).then_some(&tracked_files),
+        )
+        .await;
+
+        // Check for orphaned files if not in Quick mode; partial incremental runs leave
+        // orphan detection to the periodic full sweep
+        // Package-limited scopes only know their own files, so every other
+        // package's files would look orphaned
+        let check_orphans = self.level() != VerificationLevel::Quick
+            && !matches!(
+                scope,
+                VerificationScope::Incremental { .. }
+                    | VerificationScope::Package { .. }
+                    | VerificationScope::Packages { .. }
+            );
+        if check_orphans {
+            self.emit(AppEvent::Progress(ProgressEvent::PhaseChanged {
+                id: VERIFY_FILES_PROGRESS_ID.to_string(),
+                phase: PHASE_ORPHAN_SCAN,
+                phase_name: VERIFY_PHASES[PHASE_ORPHAN_SCAN].0.to_string(),
+            }));
+            let user_files = self.user_file_matcher(&state_id).await?;
+            let whitelist = OrphanWhitelist::new(&self.config.orphan_whitelist);
             crate::orphan::detection::find_orphaned_files(
                 &live_path,
                 &tracked_files,
+                &user_files,
+                &whitelist,
                 &mut all_discrepancies,
             );
         }
+        self.emit(AppEvent::Progress(ProgressEvent::Completed {
+            id: VERIFY_FILES_PROGRESS_ID.to_string(),
+            duration: start_time.elapsed(),
+            final_speed: None,
+            total_processed: checked_files,
+        }));
 
         let duration_ms = u64::try_from(start_time.elapsed().as_millis()).unwrap_or(u64::MAX);
 
@@ -1201,7 +1879,7 @@ impl StateVerificationGuard {
         let total_files = tracked_files.len(); // Approximation
         let verified_files = tracked_files.len();
 
-        let orphan_checked_directories = if self.level() != VerificationLevel::Quick {
+        let orphan_checked_directories = if check_orphans {
             vec![live_path.clone()]
         } else {
             vec![]
@@ -1214,7 +1892,9 @@ impl StateVerificationGuard {
             verified_files,
             orphan_checked_directories,
             matches!(scope, VerificationScope::Full),
-        );
+        )
+        .with_bytes(total_bytes, verified_bytes)
+        .with_check_depth(deep_checked_files, shallow_checked_files);
 
         // Calculate cache hit rate
         let cache_hit_rate = if total_cache_hits + total_cache_misses > 0 {
@@ -1230,13 +1910,15 @@ impl StateVerificationGuard {
             cache_hit_rate * 100.0, total_cache_hits, total_cache_hits + total_cache_misses
         ));
 
-        Ok(VerificationResult::with_coverage_and_cache(
+        let mut result = VerificationResult::with_coverage_and_cache(
             state_id,
             all_discrepancies,
             duration_ms,
             coverage,
             cache_hit_rate,
-        ))
+        );
+        result.settled_concurrency = settled_concurrency;
+        Ok(result)
     }
 
     /// Determine if Full verification is needed based on Standard verification results
@@ -1297,7 +1979,7 @@ mod tests {
         dbtx.commit().await.unwrap();
 
         // Build guard with sync enabled
-        let mut guard = StateVerificationGuard::builder()
+        let guard = StateVerificationGuard::builder()
             .with_state_manager(state.clone())
             .with_store(store.clone())
             .with_event_sender(tx)
@@ -1318,4 +2000,484 @@ mod tests {
         let got = rows.into_iter().find(|r| r.hash == pkg_hash).unwrap();
         assert_eq!(got.ref_count, 1);
     }
+
+    #[tokio::test]
+    async fn incremental_scope_skips_packages_unchanged_since_last_run() {
+        use crate::verification::scope::resolve_incremental_scope;
+        use std::time::Duration;
+
+        let (_td, state, _store, _tx) = mk_env().await;
+        let week = Duration::from_secs(7 * 24 * 60 * 60);
+        let hash_a = sps2_hash::Hash::from_data(b"pkg-A").to_hex();
+
+        let mut dbtx = state.begin_transaction().await.unwrap();
+        let verified = sps2_state::queries::get_active_state(&mut dbtx)
+            .await
+            .unwrap();
+        sps2_state::queries::add_package(&mut dbtx, &verified, "A", "1.0.0", &hash_a, 1)
+            .await
+            .unwrap();
+        dbtx.commit().await.unwrap();
+
+        // Nothing recorded yet: first run is a full sweep
+        let plan = resolve_incremental_scope(&state, &verified, false, week)
+            .await
+            .unwrap();
+        assert!(plan.full_sweep);
+
+        // Next state keeps A and adds B
+        let next = uuid::Uuid::new_v4();
+        let mut dbtx = state.begin_transaction().await.unwrap();
+        sps2_state::queries::set_guard_verification_marker(&mut dbtx, &verified, true)
+            .await
+            .unwrap();
+        sps2_state::queries::create_state(&mut dbtx, &next, Some(&verified), "install")
+            .await
+            .unwrap();
+        sps2_state::queries::add_package(&mut dbtx, &next, "A", "1.0.0", &hash_a, 1)
+            .await
+            .unwrap();
+        let hash_b = sps2_hash::Hash::from_data(b"pkg-B").to_hex();
+        sps2_state::queries::add_package(&mut dbtx, &next, "B", "2.0.0", &hash_b, 1)
+            .await
+            .unwrap();
+        dbtx.commit().await.unwrap();
+
+        let plan = resolve_incremental_scope(&state, &next, false, week)
+            .await
+            .unwrap();
+        assert!(!plan.full_sweep);
+        assert_eq!(
+            plan.skipped_unchanged,
+            vec![("A".to_string(), "1.0.0".to_string())]
+        );
+        assert_eq!(plan.changes.len(), 1);
+        assert_eq!(plan.changes[0].name, "B");
+        assert_eq!(plan.changes[0].from_version, None);
+        assert_eq!(
+            plan.scope(),
+            VerificationScope::Packages {
+                packages: vec![("B".to_string(), "2.0.0".to_string())]
+            }
+        );
+
+        // Forcing, or an elapsed sweep interval, verifies everything again
+        let forced = resolve_incremental_scope(&state, &next, true, week)
+            .await
+            .unwrap();
+        assert!(forced.full_sweep);
+        let due = resolve_incremental_scope(&state, &next, false, Duration::ZERO)
+            .await
+            .unwrap();
+        assert!(due.full_sweep);
+    }
+
+    #[test]
+    fn quick_sampling_hashes_only_below_threshold() {
+        let threshold = 1024;
+
+        // Just under the threshold is hashed, at or over it is size-checked
+        assert!(quick_needs_hash(Some(1023), 1023, threshold));
+        assert!(!quick_needs_hash(Some(1024), 1024, threshold));
+        assert!(!quick_needs_hash(Some(4096), 4096, threshold));
+
+        // A large file whose size changed is hashed to report the corruption
+        assert!(quick_needs_hash(Some(4096), 4095, threshold));
+
+        // Without a stored size, fall back to the size on disk
+        assert!(quick_needs_hash(None, 1023, threshold));
+        assert!(!quick_needs_hash(None, 1024, threshold));
+
+        // A zero threshold keeps Quick existence-only
+        assert!(!quick_needs_hash(Some(1), 2, 0));
+    }
+
+    #[test]
+    fn byte_progress_reports_speed_and_eta() {
+        match byte_progress_event(50, 200, Duration::from_secs(1)) {
+            ProgressEvent::Updated {
+                current,
+                total,
+                speed,
+                eta,
+                ..
+            } => {
+                assert_eq!(current, 50);
+                assert_eq!(total, Some(200));
+                assert_eq!(speed, Some(50.0));
+                assert_eq!(eta, Some(Duration::from_secs(3)));
+            }
+            other => panic!("expected progress update, got {other:?}"),
+        }
+
+        // No speed (and therefore no ETA) until bytes have been hashed
+        match byte_progress_event(0, 200, Duration::from_secs(1)) {
+            ProgressEvent::Updated { speed, eta, .. } => {
+                assert!(speed.is_none());
+                assert!(eta.is_none());
+            }
+            other => panic!("expected progress update, got {other:?}"),
+        }
+    }
+
+    /// Install a single `demo` package and return a guard plus the tracked file path
+    async fn installed_guard(root: impl AsRef<Path>) -> (StateVerificationGuard, PathBuf) {
+        let root = root.as_ref();
+        let state = sps2_state::StateManager::new(root).await.unwrap();
+        let store_base = root.join("store");
+        afs::create_dir_all(&store_base).await.unwrap();
+        let store = sps2_store::PackageStore::new(store_base);
+        let (tx, _rx) = sps2_events::channel();
+        sps2_install::test_support::install_demo_package(&state, &store).await;
+
+        let mut dbtx = state.begin_transaction().await.unwrap();
+        let sid = queries::get_active_state(&mut dbtx).await.unwrap();
+        let tracked = queries::get_package_file_entries_by_name(&mut dbtx, &sid, "demo", "1.0.0")
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|entry| entry.relative_path.ends_with("file.txt"))
+            .unwrap()
+            .relative_path;
+        dbtx.commit().await.unwrap();
+
+        let guard = StateVerificationGuard::builder()
+            .with_state_manager(state)
+            .with_store(store)
+            .with_event_sender(tx)
+            .with_level(crate::types::VerificationLevel::Full)
+            .build()
+            .unwrap();
+        (guard, PathBuf::from(tracked))
+    }
+
+    #[tokio::test]
+    async fn verify_paths_checks_tracked_file() {
+        let td = TempDir::new().unwrap();
+        let (guard, tracked) = installed_guard(&td).await;
+
+        let result = guard
+            .verify_paths(std::slice::from_ref(&tracked))
+            .await
+            .unwrap();
+        assert!(result.is_valid, "{:?}", result.discrepancies);
+        assert!(result.untracked_paths.is_empty());
+        assert_eq!(result.coverage.as_ref().unwrap().verified_files, 1);
+
+        // Absolute paths inside the live prefix resolve to the same entry
+        let live_file = guard.state_manager.live_path().join(&tracked);
+        afs::write(&live_file, b"tampered").await.unwrap();
+        // Move the mtime past the one recorded above so the cache does not skip it
+        std::fs::File::options()
+            .write(true)
+            .open(&live_file)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() + Duration::from_secs(3600))
+            .unwrap();
+        let result = guard
+            .verify_with_scope(&VerificationScope::paths(vec![live_file]))
+            .await
+            .unwrap();
+        assert!(
+            matches!(
+                result.discrepancies.as_slice(),
+                [Discrepancy::CorruptedFile { file_path, .. }] if *file_path == tracked.to_string_lossy()
+            ),
+            "{:?}",
+            result.discrepancies
+        );
+    }
+
+    #[tokio::test]
+    async fn repeat_verification_trusts_cached_hashes_until_forced() {
+        let td = TempDir::new().unwrap();
+        let (mut guard, tracked) = installed_guard(&td).await;
+        let live_file = guard.state_manager.live_path().join(&tracked);
+        let cache_path = guard.state_manager.state_path().join(HASH_CACHE_FILE);
+        let paths = [tracked.clone()];
+
+        let first = guard.verify_paths(&paths).await.unwrap();
+        assert!(first.is_valid, "{:?}", first.discrepancies);
+        assert!(cache_path.exists());
+
+        // An unchanged file is checked against its cached hash without being read
+        let cached = guard.verify_paths(&paths).await.unwrap();
+        assert!(cached.is_valid, "{:?}", cached.discrepancies);
+        assert!((cached.cache_hit_rate - 1.0).abs() < f64::EPSILON);
+
+        // A cached hash that no longer matches, as after an upgrade that kept
+        // the size and archive mtime, is re-checked rather than reported
+        let mut json: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&cache_path).unwrap()).unwrap();
+        for entry in json["entries"].as_object_mut().unwrap().values_mut() {
+            let hash = entry["hash"].as_str().unwrap();
+            let stale = if hash.ends_with('0') { "1" } else { "0" };
+            entry["hash"] = format!("{}{stale}", &hash[..hash.len() - 1]).into();
+        }
+        std::fs::write(&cache_path, serde_json::to_vec(&json).unwrap()).unwrap();
+        let rehashed = guard.verify_paths(&paths).await.unwrap();
+        assert!(rehashed.is_valid, "{:?}", rehashed.discrepancies);
+        assert!(rehashed.cache_hit_rate.abs() < f64::EPSILON);
+
+        // Same size and mtime, but the rewrite changed the file's ctime
+        let corrupted = |result: &VerificationResult| {
+            matches!(
+                result.discrepancies.as_slice(),
+                [Discrepancy::CorruptedFile { file_path, .. }] if *file_path == tracked.to_string_lossy()
+            )
+        };
+        let mtime = std::fs::metadata(&live_file).unwrap().modified().unwrap();
+        afs::write(&live_file, b"DEMO CONTENTS").await.unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&live_file)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        let tampered = guard.verify_paths(&paths).await.unwrap();
+        assert!(corrupted(&tampered), "{:?}", tampered.discrepancies);
+        assert!(tampered.cache_hit_rate.abs() < f64::EPSILON);
+
+        guard.config.force_full = true;
+        let forced = guard.verify_paths(&paths).await.unwrap();
+        assert!(corrupted(&forced), "{:?}", forced.discrepancies);
+        assert!(forced.cache_hit_rate.abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn verify_paths_reports_untracked_file() {
+        let td = TempDir::new().unwrap();
+        let (guard, tracked) = installed_guard(&td).await;
+
+        // An existing file no package owns is reported, not treated as an orphan
+        let extra = tracked.with_file_name("extra.txt");
+        afs::write(guard.state_manager.live_path().join(&extra), b"mine")
+            .await
+            .unwrap();
+
+        let result = guard.verify_paths(&[tracked, extra.clone()]).await.unwrap();
+        assert!(result.is_valid, "{:?}", result.discrepancies);
+        assert_eq!(result.untracked_paths, vec![extra]);
+        assert_eq!(result.coverage.as_ref().unwrap().verified_files, 1);
+    }
+
+    #[tokio::test]
+    async fn verify_paths_reports_nonexistent_path() {
+        let td = TempDir::new().unwrap();
+        let (guard, _) = installed_guard(&td).await;
+
+        let missing = PathBuf::from("opt/pm/live/share/does-not-exist");
+        let result = guard
+            .verify_paths(std::slice::from_ref(&missing))
+            .await
+            .unwrap();
+        assert!(result.is_valid);
+        assert!(result.discrepancies.is_empty());
+        assert_eq!(result.untracked_paths, vec![missing]);
+    }
+
+    #[tokio::test]
+    async fn verification_reports_file_progress_by_phase() {
+        let td = TempDir::new().unwrap();
+        let (mut guard, _) = installed_guard(&td).await;
+        let (tx, mut rx) = sps2_events::channel();
+        guard.tx = tx;
+
+        guard.verify_only().await.unwrap();
+
+        let events: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|message| match message.event {
+                AppEvent::Progress(event) => Some(event),
+                _ => None,
+            })
+            .filter(|event| match event {
+                ProgressEvent::Started { id, .. }
+                | ProgressEvent::Updated { id, .. }
+                | ProgressEvent::PhaseChanged { id, .. }
+                | ProgressEvent::Completed { id, .. } => id == VERIFY_FILES_PROGRESS_ID,
+                _ => false,
+            })
+            .collect();
+
+        assert!(matches!(
+            events.first(),
+            Some(ProgressEvent::Started { phases, .. }) if phases.len() == 3
+        ));
+        let phases: Vec<usize> = events
+            .iter()
+            .filter_map(|event| match event {
+                ProgressEvent::PhaseChanged { phase, .. } => Some(*phase),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(phases, [PHASE_HASHING, PHASE_ORPHAN_SCAN]);
+        assert!(events.iter().any(|event| matches!(
+            event,
+            ProgressEvent::Updated { current, total: Some(total), .. }
+                if current == total && *total > 0
+        )));
+        assert!(matches!(
+            events.last(),
+            Some(ProgressEvent::Completed { total_processed, .. }) if *total_processed > 0
+        ));
+    }
+
+    #[tokio::test]
+    async fn healing_dry_run_reports_actions_without_applying_them() {
+        let td = TempDir::new().unwrap();
+        let (mut guard, tracked) = installed_guard(&td).await;
+        let live_file = guard.state_manager.live_path().join(&tracked);
+        afs::remove_file(&live_file).await.unwrap();
+        let config = Config::default();
+
+        guard.config.healing_dry_run = true;
+        let planned = guard.verify_and_heal(&config).await.unwrap();
+        assert!(!live_file.exists());
+        assert!(!planned.is_valid);
+        assert!(planned.healing_actions.iter().any(|action| matches!(
+            action,
+            HealingAction::RestoreFile { file_path, .. } if *file_path == tracked.to_string_lossy()
+        )));
+
+        guard.config.healing_dry_run = false;
+        let healed = guard.verify_and_heal(&config).await.unwrap();
+        assert!(live_file.exists());
+        assert_eq!(healed.healing_actions, planned.healing_actions);
+    }
+
+    #[tokio::test]
+    async fn healing_preserves_modified_user_files() {
+        let td = TempDir::new().unwrap();
+        let (mut guard, tracked) = installed_guard(&td).await;
+        let live_file = guard.state_manager.live_path().join(&tracked);
+        afs::write(&live_file, b"local edits").await.unwrap();
+        guard.config.verification_level = VerificationLevel::Full;
+        guard.config.user_files.patterns = vec!["**/file.txt".to_string()];
+
+        let result = guard.verify_and_heal(&Config::default()).await.unwrap();
+        assert!(result.healing_actions.iter().any(|action| matches!(
+            action,
+            HealingAction::Preserve { file_path, .. } if *file_path == tracked.to_string_lossy()
+        )));
+        assert_eq!(afs::read(&live_file).await.unwrap(), b"local edits");
+    }
+
+    #[tokio::test]
+    async fn mode_changes_are_reported_above_quick_and_healed() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let td = TempDir::new().unwrap();
+        let (mut guard, tracked) = installed_guard(&td).await;
+        let live_file = guard.state_manager.live_path().join(&tracked);
+        let paths = [tracked.clone()];
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o7777;
+        let installed_mode = mode(&live_file);
+
+        // Write bits alone are not compared
+        std::fs::set_permissions(&live_file, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let result = guard.verify_paths(&paths).await.unwrap();
+        assert!(result.is_valid, "{:?}", result.discrepancies);
+
+        std::fs::set_permissions(&live_file, std::fs::Permissions::from_mode(0o755)).unwrap();
+        for level in [VerificationLevel::Standard, VerificationLevel::Full] {
+            guard.config.verification_level = level;
+            let result = guard.verify_paths(&paths).await.unwrap();
+            assert!(
+                matches!(
+                    result.discrepancies.as_slice(),
+                    [Discrepancy::PermissionMismatch { file_path, actual_mode: 0o755, .. }]
+                        if *file_path == tracked.to_string_lossy()
+                ),
+                "{level:?}: {:?}",
+                result.discrepancies
+            );
+        }
+        guard.config.verification_level = VerificationLevel::Quick;
+        let result = guard.verify_paths(&paths).await.unwrap();
+        assert!(result.is_valid, "{:?}", result.discrepancies);
+
+        guard.config.verification_level = VerificationLevel::Standard;
+        let healed = guard.verify_and_heal(&Config::default()).await.unwrap();
+        assert!(healed.healing_actions.iter().any(|action| matches!(
+            action,
+            HealingAction::RestoreMode { file_path, mode: 0o644 } if *file_path == tracked.to_string_lossy()
+        )));
+        assert_eq!(mode(&live_file) & 0o555, installed_mode & 0o555);
+        let result = guard.verify_paths(&paths).await.unwrap();
+        assert!(result.is_valid, "{:?}", result.discrepancies);
+    }
+
+    #[tokio::test]
+    async fn symlink_cycles_are_reported_per_policy() {
+        // Reached through a symlink, as temp dirs under /var are on macOS
+        let td = TempDir::new().unwrap();
+        std::fs::create_dir(td.path().join("real")).unwrap();
+        std::os::unix::fs::symlink("real", td.path().join("link")).unwrap();
+        let (mut guard, tracked) = installed_guard(td.path().join("link")).await;
+        let live_file = guard.state_manager.live_path().join(&tracked);
+        let other = tracked.with_file_name("other");
+        // file.txt -> other -> file.txt
+        afs::remove_file(&live_file).await.unwrap();
+        std::os::unix::fs::symlink("other", &live_file).unwrap();
+        std::os::unix::fs::symlink("file.txt", live_file.with_file_name("other")).unwrap();
+        let paths = [tracked.clone()];
+
+        guard.config.symlink_policy = SymlinkPolicy::Strict;
+        let result = guard.verify_paths(&paths).await.unwrap();
+        assert!(!result.is_valid);
+        let tracked_str = tracked.display().to_string();
+        let other_str = other.display().to_string();
+        match result.discrepancies.as_slice() {
+            [Discrepancy::SymlinkCycle {
+                file_path,
+                chain,
+                fatal: true,
+                ..
+            }] => {
+                assert_eq!(*file_path, tracked_str);
+                assert_eq!(*chain, [tracked_str.clone(), other_str, tracked_str]);
+            }
+            other => panic!("{other:?}"),
+        }
+
+        guard.config.symlink_policy = SymlinkPolicy::Lenient;
+        let result = guard.verify_paths(&paths).await.unwrap();
+        assert!(result.is_valid, "{:?}", result.discrepancies);
+        assert!(matches!(
+            result.discrepancies.as_slice(),
+            [Discrepancy::SymlinkCycle { fatal: false, .. }]
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_package_checks_only_that_package() {
+        let td = TempDir::new().unwrap();
+        let (guard, tracked) = installed_guard(&td).await;
+        let live_path = guard.state_manager.live_path().to_path_buf();
+        // Not an orphan for a single-package scope
+        afs::write(live_path.join(tracked.with_file_name("extra.txt")), b"mine")
+            .await
+            .unwrap();
+
+        let result = guard.verify_package("demo").await.unwrap();
+        assert!(result.is_valid, "{:?}", result.discrepancies);
+        let coverage = result.coverage.as_ref().unwrap();
+        assert_eq!(coverage.verified_packages, 1);
+        assert!(coverage.verified_files > 0);
+
+        afs::write(live_path.join(&tracked), b"tampered")
+            .await
+            .unwrap();
+        let result = guard.verify_package("demo").await.unwrap();
+        assert!(
+            matches!(
+                result.discrepancies.as_slice(),
+                [Discrepancy::CorruptedFile { file_path, .. }] if *file_path == tracked.to_string_lossy()
+            ),
+            "{:?}",
+            result.discrepancies
+        );
+
+        assert!(guard.verify_package("absent").await.is_err());
+    }
 }
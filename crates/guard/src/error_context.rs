@@ -433,6 +433,18 @@ impl GuardErrorContext {
                 package_version,
                 file_path,
                 ..
+            }
+            | Discrepancy::PermissionMismatch {
+                package_name,
+                package_version,
+                file_path,
+                ..
+            }
+            | Discrepancy::SymlinkCycle {
+                package_name,
+                package_version,
+                file_path,
+                ..
             } => (
                 Some(file_path.clone()),
                 Some(package_name.clone()),
@@ -440,6 +452,8 @@ impl GuardErrorContext {
                 String::from(match discrepancy {
                     Discrepancy::MissingFile { .. } => "missing_file",
                     Discrepancy::TypeMismatch { .. } => "type_mismatch",
+                    Discrepancy::PermissionMismatch { .. } => "permission_mismatch",
+                    Discrepancy::SymlinkCycle { .. } => "symlink_cycle",
                     _ => "corrupted_file",
                 }),
             ),
@@ -0,0 +1,397 @@
+//! Content-addressed backup area for orphaned files
+//!
+//! Backed-up orphans are stored once per unique content under
+//! `objects/<hash>`, mirroring the package store. Each healing run writes a
+//! manifest under `manifests/<run-id>.json` mapping the original live paths
+//! to their objects, so backing up an unchanged orphan again only adds a
+//! manifest entry. Restores read a run's manifest to put the files back.
+
+use serde::{Deserialize, Serialize};
+use sps2_errors::{Error, OpsError};
+use sps2_hash::Hash;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const OBJECTS_DIR: &str = "objects";
+const MANIFESTS_DIR: &str = "manifests";
+
+/// What a manifest entry restores
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum BackupEntryKind {
+    /// Regular file stored under `objects/<hash>`
+    File { hash: String, mode: u32 },
+    /// Symbolic link pointing at `target`
+    Symlink { target: PathBuf },
+    /// Empty directory
+    Directory,
+}
+
+/// One backed-up orphan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupEntry {
+    /// Path relative to the live prefix
+    pub path: String,
+    /// How to restore it
+    #[serde(flatten)]
+    pub kind: BackupEntryKind,
+}
+
+/// Per-run record of backed-up orphans
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// Entries in the order they were backed up
+    pub entries: Vec<BackupEntry>,
+}
+
+/// Backup session for one healing run
+///
+/// The run's manifest is rewritten after every file so an interrupted run
+/// still records everything it moved out of the live prefix.
+#[derive(Debug)]
+pub struct OrphanBackup {
+    root: PathBuf,
+    run_id: String,
+    manifest: BackupManifest,
+}
+
+impl OrphanBackup {
+    /// Start a new backup run under `root`
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        Self {
+            root: root.into(),
+            run_id: format!("{secs}-{}", &suffix[..8]),
+            manifest: BackupManifest::default(),
+        }
+    }
+
+    /// Identifier of this run, used to name its manifest
+    #[must_use]
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// Path of this run's manifest
+    #[must_use]
+    pub fn manifest_path(&self) -> PathBuf {
+        manifest_path(&self.root, &self.run_id)
+    }
+
+    /// Move an orphan out of the live prefix into the backup area
+    ///
+    /// Returns `false` without touching the path if it is a non-empty
+    /// directory; its contents are backed up as orphans of their own.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the orphan cannot be read, stored or removed, or
+    /// the manifest cannot be written.
+    pub async fn backup(&mut self, full_path: &Path, relative_path: &str) -> Result<bool, Error> {
+        let metadata = tokio::fs::symlink_metadata(full_path).await?;
+
+        let kind = if metadata.file_type().is_symlink() {
+            let target = tokio::fs::read_link(full_path).await?;
+            tokio::fs::remove_file(full_path).await?;
+            BackupEntryKind::Symlink { target }
+        } else if metadata.is_dir() {
+            let mut entries = tokio::fs::read_dir(full_path).await?;
+            if entries.next_entry().await?.is_some() {
+                return Ok(false);
+            }
+            tokio::fs::remove_dir(full_path).await?;
+            BackupEntryKind::Directory
+        } else {
+            let hash = Hash::hash_file(full_path).await?.to_hex();
+            self.store_object(full_path, &hash).await?;
+            BackupEntryKind::File {
+                hash,
+                mode: metadata.permissions().mode() & 0o7777,
+            }
+        };
+
+        self.manifest.entries.push(BackupEntry {
+            path: relative_path.to_string(),
+            kind,
+        });
+        self.write_manifest().await?;
+        Ok(true)
+    }
+
+    /// Move `source` to `objects/<hash>`, or drop it if that object exists
+    async fn store_object(&self, source: &Path, hash: &str) -> Result<(), Error> {
+        let objects = self.root.join(OBJECTS_DIR);
+        tokio::fs::create_dir_all(&objects)
+            .await
+            .map_err(|e| OpsError::OperationFailed {
+                message: format!("Failed to create backup directory: {e}"),
+            })?;
+
+        let object = objects.join(hash);
+        if tokio::fs::try_exists(&object).await? {
+            tokio::fs::remove_file(source).await?;
+            return Ok(());
+        }
+
+        if tokio::fs::rename(source, &object).await.is_err() {
+            // Backup area on another filesystem
+            let staging = objects.join(format!(".{hash}.tmp"));
+            tokio::fs::copy(source, &staging).await?;
+            tokio::fs::rename(&staging, &object).await?;
+            tokio::fs::remove_file(source).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_manifest(&self) -> Result<(), Error> {
+        let path = self.manifest_path();
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let json =
+            serde_json::to_vec_pretty(&self.manifest).map_err(|e| OpsError::OperationFailed {
+                message: format!("Failed to serialize backup manifest: {e}"),
+            })?;
+        let staging = path.with_extension("json.tmp");
+        tokio::fs::write(&staging, json).await?;
+        tokio::fs::rename(&staging, &path).await?;
+        Ok(())
+    }
+}
+
+fn manifest_path(root: &Path, run_id: &str) -> PathBuf {
+    root.join(MANIFESTS_DIR).join(format!("{run_id}.json"))
+}
+
+/// Whether `path` is relative and has only plain components, so joining it
+/// to a directory cannot leave that directory
+pub(crate) fn is_contained_path(path: &Path) -> bool {
+    let mut components = path.components().peekable();
+    components.peek().is_some() && components.all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Identifiers of the backup runs under `root`, oldest first
+///
+/// # Errors
+///
+/// Returns an error if the manifest directory exists but cannot be read.
+pub async fn list_backup_runs(root: &Path) -> Result<Vec<String>, Error> {
+    let dir = root.join(MANIFESTS_DIR);
+    if !tokio::fs::try_exists(&dir).await? {
+        return Ok(Vec::new());
+    }
+
+    let mut runs = Vec::new();
+    let mut entries = tokio::fs::read_dir(&dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name();
+        if let Some(run_id) = name.to_str().and_then(|n| n.strip_suffix(".json")) {
+            runs.push(run_id.to_string());
+        }
+    }
+    // Run ids start with the creation time in seconds
+    runs.sort_by_key(|run| {
+        let secs = run.split('-').next().and_then(|s| s.parse::<u64>().ok());
+        (secs, run.clone())
+    });
+    Ok(runs)
+}
+
+/// Restore the orphans backed up by `run_id` into `live_path`
+///
+/// Objects stay in the backup area since other runs may reference them.
+/// Paths that already exist in the live prefix are left alone. Returns the
+/// number of entries restored.
+///
+/// # Errors
+///
+/// Returns an error if the manifest cannot be read, an entry's path or hash
+/// would lead outside the live prefix or backup area, or an entry cannot be
+/// restored.
+pub async fn restore_orphan_backup(
+    root: &Path,
+    run_id: &str,
+    live_path: &Path,
+) -> Result<usize, Error> {
+    if !is_contained_path(Path::new(run_id)) {
+        return Err(OpsError::OperationFailed {
+            message: format!("Invalid backup run id '{run_id}'"),
+        }
+        .into());
+    }
+    let path = manifest_path(root, run_id);
+    let data = tokio::fs::read(&path)
+        .await
+        .map_err(|e| OpsError::OperationFailed {
+            message: format!("Failed to read backup manifest {}: {e}", path.display()),
+        })?;
+    let manifest: BackupManifest =
+        serde_json::from_slice(&data).map_err(|e| OpsError::OperationFailed {
+            message: format!("Invalid backup manifest {}: {e}", path.display()),
+        })?;
+
+    // Check every entry first so a bad manifest restores nothing
+    for entry in &manifest.entries {
+        let object_ok = match &entry.kind {
+            BackupEntryKind::File { hash, .. } => Hash::from_hex(hash).is_ok(),
+            BackupEntryKind::Symlink { .. } | BackupEntryKind::Directory => true,
+        };
+        if !is_contained_path(Path::new(&entry.path)) || !object_ok {
+            return Err(OpsError::OperationFailed {
+                message: format!(
+                    "Backup manifest {} has an unsafe entry for '{}'",
+                    path.display(),
+                    entry.path
+                ),
+            }
+            .into());
+        }
+    }
+
+    let mut restored = 0;
+    for entry in &manifest.entries {
+        let destination = live_path.join(&entry.path);
+        if tokio::fs::symlink_metadata(&destination).await.is_ok() {
+            continue;
+        }
+        if let Some(parent) = destination.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        match &entry.kind {
+            BackupEntryKind::File { hash, mode } => {
+                let object = root.join(OBJECTS_DIR).join(hash);
+                tokio::fs::copy(&object, &destination).await.map_err(|e| {
+                    OpsError::OperationFailed {
+                        message: format!(
+                            "Failed to restore {} from {}: {e}",
+                            entry.path,
+                            object.display()
+                        ),
+                    }
+                })?;
+                tokio::fs::set_permissions(&destination, std::fs::Permissions::from_mode(*mode))
+                    .await?;
+            }
+            BackupEntryKind::Symlink { target } => {
+                tokio::fs::symlink(target, &destination).await?;
+            }
+            BackupEntryKind::Directory => {
+                tokio::fs::create_dir_all(&destination).await?;
+            }
+        }
+        restored += 1;
+    }
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn object_count(root: &Path) -> usize {
+        let mut count = 0;
+        let mut entries = tokio::fs::read_dir(root.join(OBJECTS_DIR)).await.unwrap();
+        while entries.next_entry().await.unwrap().is_some() {
+            count += 1;
+        }
+        count
+    }
+
+    #[tokio::test]
+    async fn repeated_backups_store_identical_orphans_once() {
+        let live = TempDir::new().unwrap();
+        let backups = TempDir::new().unwrap();
+        let orphan = live.path().join("etc/leftover.conf");
+        std::fs::create_dir_all(orphan.parent().unwrap()).unwrap();
+
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            std::fs::write(&orphan, "setting = 1\n").unwrap();
+            let mut backup = OrphanBackup::new(backups.path());
+            assert!(backup.backup(&orphan, "etc/leftover.conf").await.unwrap());
+            assert!(!orphan.exists());
+            runs.push(backup.run_id().to_string());
+        }
+
+        assert_eq!(object_count(backups.path()).await, 1);
+        let listed = list_backup_runs(backups.path()).await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(runs.iter().all(|run| listed.contains(run)));
+    }
+
+    #[tokio::test]
+    async fn restore_reconstructs_files_and_links_from_manifest() {
+        let live = TempDir::new().unwrap();
+        let backups = TempDir::new().unwrap();
+        let script = live.path().join("bin/tool");
+        std::fs::create_dir_all(script.parent().unwrap()).unwrap();
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let copy = live.path().join("bin/tool-copy");
+        std::fs::copy(&script, &copy).unwrap();
+        std::os::unix::fs::symlink("tool", live.path().join("bin/tool-link")).unwrap();
+
+        let mut backup = OrphanBackup::new(backups.path());
+        for path in ["bin/tool", "bin/tool-copy", "bin/tool-link"] {
+            backup.backup(&live.path().join(path), path).await.unwrap();
+        }
+        // Non-empty directories are left for their contents to be handled
+        std::fs::write(live.path().join("bin/kept"), "").unwrap();
+        assert!(!backup
+            .backup(&live.path().join("bin"), "bin")
+            .await
+            .unwrap());
+        assert_eq!(object_count(backups.path()).await, 1);
+
+        let restored = restore_orphan_backup(backups.path(), backup.run_id(), live.path())
+            .await
+            .unwrap();
+        assert_eq!(restored, 3);
+        assert_eq!(std::fs::read_to_string(&script).unwrap(), "#!/bin/sh\n");
+        assert_eq!(std::fs::read_to_string(&copy).unwrap(), "#!/bin/sh\n");
+        assert_eq!(
+            std::fs::metadata(&script).unwrap().permissions().mode() & 0o777,
+            0o755
+        );
+        assert_eq!(
+            std::fs::read_link(live.path().join("bin/tool-link")).unwrap(),
+            Path::new("tool")
+        );
+    }
+
+    #[tokio::test]
+    async fn restore_rejects_paths_leaving_the_live_prefix() {
+        let root = TempDir::new().unwrap();
+        let live = root.path().join("live");
+        let backups = root.path().join("backups");
+        std::fs::create_dir_all(&live).unwrap();
+
+        for path in ["../escaped", "bin/../../escaped", "/tmp/escaped", ""] {
+            let manifest = BackupManifest {
+                entries: vec![BackupEntry {
+                    path: path.to_string(),
+                    kind: BackupEntryKind::Directory,
+                }],
+            };
+            let manifest_file = manifest_path(&backups, "run");
+            std::fs::create_dir_all(manifest_file.parent().unwrap()).unwrap();
+            std::fs::write(&manifest_file, serde_json::to_vec(&manifest).unwrap()).unwrap();
+
+            assert!(
+                restore_orphan_backup(&backups, "run", &live).await.is_err(),
+                "{path:?} was restored"
+            );
+        }
+        assert!(!root.path().join("escaped").exists());
+        assert!(restore_orphan_backup(&backups, "../run", &live)
+            .await
+            .is_err());
+    }
+}
@@ -1,17 +1,19 @@
 //! File restoration and healing logic
 
-use crate::types::HealingContext;
-use sps2_errors::{Error, OpsError};
+use crate::types::{HealingAction, HealingContext, VERIFIED_MODE_BITS};
+use sps2_errors::{Error, OpsError, StorageError};
 use sps2_events::{EventEmitter, EventSender};
 use sps2_hash::Hash;
 use sps2_platform::PlatformManager;
 use sps2_state::queries;
 use sps2_store::StoredPackage;
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Restore a missing file from the package store
 ///
+/// In a dry run the store source is resolved but nothing is written.
+///
 /// # Errors
 ///
 /// Returns an error if:
@@ -23,7 +25,7 @@ pub async fn restore_missing_file(
     package_name: &str,
     package_version: &str,
     file_path: &str,
-) -> Result<(), Error> {
+) -> Result<HealingAction, Error> {
     ctx.emit_debug(format!(
         "restore_missing_file starting for {package_name}/{package_version} - {file_path}"
     ));
@@ -40,7 +42,7 @@ async fn restore_missing_file_impl(
     package_name: &str,
     package_version: &str,
     file_path: &str,
-) -> Result<(), Error> {
+) -> Result<HealingAction, Error> {
     // Get package hash from database
     let mut state_tx = ctx.state_manager.begin_transaction().await?;
     let state_id = ctx.state_manager.get_active_state().await?;
@@ -117,19 +119,15 @@ async fn restore_missing_file_impl(
                 message: format!("Invalid file hash in database: {e}"),
             })?;
 
-        let file_store_path = ctx.store.file_path(&file_hash_obj);
-
-        if !file_store_path.exists() {
-            return Err(OpsError::OperationFailed {
+        // Re-inflates the object if the store has been compacted
+        stored_file(ctx, &file_hash_obj)
+            .await
+            .map_err(|_| OpsError::OperationFailed {
                 message: format!(
                     "File content missing from file store for hash {}",
                     file_entry.file_hash
                 ),
-            }
-            .into());
-        }
-
-        file_store_path
+            })?
     } else {
         // Legacy package - file is in the package's files directory
         let source_file = stored_package.files_path().join(file_path);
@@ -146,6 +144,20 @@ async fn restore_missing_file_impl(
         source_file
     };
 
+    let action = HealingAction::RestoreFile {
+        package_name: package_name.to_string(),
+        package_version: package_version.to_string(),
+        file_path: file_path.to_string(),
+        source: source_file.clone(),
+    };
+    if ctx.dry_run {
+        ctx.emit_debug(format!(
+            "Dry run: would restore {file_path} from {}",
+            source_file.display()
+        ));
+        return Ok(action);
+    }
+
     // Determine target path
     let live_path = ctx.state_manager.live_path();
     let target_path = live_path.join(file_path);
@@ -221,29 +233,14 @@ async fn restore_missing_file_impl(
         target_path.display()
     ));
 
-    // Clear the mtime trackers for this package so the healed file is re-verified
-    ctx.emit_debug(format!(
-        "About to clear mtime trackers for {package_name}-{package_version}"
-    ));
-
-    let mut state_tx = ctx.state_manager.begin_transaction().await?;
-    let cleared = sps2_state::queries::clear_package_mtime_trackers(
-        &mut state_tx,
-        package_name,
-        package_version,
-    )
-    .await?;
-    state_tx.commit().await?;
-
-    ctx.emit_debug(format!(
-        "Cleared {cleared} mtime tracker entries for {package_name}-{package_version}"
-    ));
-
-    Ok(())
+    Ok(action)
 }
 
 /// Heal a corrupted file by restoring it from the package store
 ///
+/// In a dry run the store source is resolved and checked but the corrupted
+/// file is left in place.
+///
 /// # Errors
 ///
 /// Returns an error if:
@@ -257,7 +254,7 @@ pub async fn heal_corrupted_file(
     file_path: &str,
     expected_hash: &str,
     actual_hash: &str,
-) -> Result<(), Error> {
+) -> Result<HealingAction, Error> {
     let live_path = ctx.state_manager.live_path();
     let full_path = live_path.join(file_path);
 
@@ -267,12 +264,19 @@ pub async fn heal_corrupted_file(
     ));
 
     // First, check if this might be a legitimate user modification
-    if is_user_modified_file(ctx.tx, &full_path, file_path).await? {
+    if is_user_modified_file(ctx.tx, &full_path, file_path).await?
+        || ctx
+            .user_files
+            .is_user_file(Path::new(file_path), &full_path)
+    {
         // Preserve user modifications
         ctx.emit_debug(format!(
             "Preserving user-modified file: {file_path} (hash mismatch: expected {expected_hash}, got {actual_hash})"
         ));
-        return Ok(());
+        return Ok(HealingAction::Preserve {
+            file_path: file_path.to_string(),
+            reason: "user-modified".to_string(),
+        });
     }
 
     ctx.emit_debug(format!(
@@ -317,17 +321,12 @@ pub async fn heal_corrupted_file(
                 message: format!("Invalid expected hash: {e}"),
             })?;
 
-        // Get the file path from the store
-        let file_store_path = ctx.store.file_path(&expected_hash_obj);
-
-        if !file_store_path.exists() {
-            return Err(OpsError::OperationFailed {
+        // Get the file path from the store, re-inflating packed objects
+        stored_file(ctx, &expected_hash_obj)
+            .await
+            .map_err(|_| OpsError::OperationFailed {
                 message: format!("File content missing from file store for hash {expected_hash}"),
-            }
-            .into());
-        }
-
-        file_store_path
+            })?
     } else {
         // Legacy package - file is in the package's files directory
         let source_file = stored_package.files_path().join(file_path);
@@ -363,6 +362,20 @@ pub async fn heal_corrupted_file(
         source_file
     };
 
+    let action = HealingAction::ReplaceFile {
+        package_name: package_name.to_string(),
+        package_version: package_version.to_string(),
+        file_path: file_path.to_string(),
+        source: source_file.clone(),
+    };
+    if ctx.dry_run {
+        ctx.emit_debug(format!(
+            "Dry run: would replace corrupted {file_path} from {}",
+            source_file.display()
+        ));
+        return Ok(action);
+    }
+
     // Remove the corrupted file
     tokio::fs::remove_file(&full_path)
         .await
@@ -418,22 +431,79 @@ pub async fn heal_corrupted_file(
             })?;
     }
 
-    // Clear the mtime trackers for this package so the healed file is re-verified
-    let mut state_tx = ctx.state_manager.begin_transaction().await?;
-    let cleared = sps2_state::queries::clear_package_mtime_trackers(
-        &mut state_tx,
-        package_name,
-        package_version,
-    )
-    .await?;
-    state_tx.commit().await?;
-
     // Emit success event
+    ctx.emit_debug(format!("Restored corrupted file: {file_path}"));
+
+    Ok(action)
+}
+
+/// Change a file's mode back to its recorded permission bits
+///
+/// The read and execute bits are taken from `expected_mode`; any write bits
+/// the file has are kept. In a dry run the file is left alone.
+///
+/// # Errors
+///
+/// Returns an error if the file is no longer a regular file or its mode
+/// cannot be changed.
+pub async fn restore_file_mode(
+    ctx: &HealingContext<'_>,
+    file_path: &str,
+    expected_mode: u32,
+    actual_mode: u32,
+) -> Result<HealingAction, Error> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let full_path = ctx.state_manager.live_path().join(file_path);
+    let metadata = tokio::fs::symlink_metadata(&full_path).await?;
+    if !metadata.is_file() {
+        return Err(OpsError::OperationFailed {
+            message: format!("Cannot restore mode of {file_path}: not a regular file"),
+        }
+        .into());
+    }
+
+    let mode = (actual_mode & !VERIFIED_MODE_BITS) | (expected_mode & VERIFIED_MODE_BITS);
+    let action = HealingAction::RestoreMode {
+        file_path: file_path.to_string(),
+        mode,
+    };
+    if ctx.dry_run {
+        ctx.emit_debug(format!(
+            "Dry run: would change mode of {file_path} from {actual_mode:o} to {mode:o}"
+        ));
+        return Ok(action);
+    }
+
+    tokio::fs::set_permissions(&full_path, std::fs::Permissions::from_mode(mode))
+        .await
+        .map_err(|e| OpsError::OperationFailed {
+            message: format!("Failed to restore mode of {file_path}: {e}"),
+        })?;
     ctx.emit_debug(format!(
-        "Restored corrupted file: {file_path}, cleared {cleared} mtime tracker entries"
+        "Restored mode of {file_path} from {actual_mode:o} to {mode:o}"
     ));
 
-    Ok(())
+    Ok(action)
+}
+
+/// Store path of the file object `hash`
+///
+/// Packed objects are re-inflated, except in a dry run, which only checks
+/// that the object exists and returns the path it would be inflated to.
+async fn stored_file(ctx: &HealingContext<'_>, hash: &Hash) -> Result<PathBuf, Error> {
+    let file_store = ctx.store.file_store();
+    if !ctx.dry_run {
+        return file_store.inflate(hash).await;
+    }
+    if file_store.has_file(hash).await {
+        Ok(file_store.file_path(hash))
+    } else {
+        Err(StorageError::PathNotFound {
+            path: file_store.file_path(hash).display().to_string(),
+        }
+        .into())
+    }
 }
 
 /// Check if a file appears to be user-modified
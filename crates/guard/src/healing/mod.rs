@@ -1,6 +1,8 @@
 //! Healing functionality for state discrepancies
 
+pub mod backup;
 pub mod files;
 pub mod orphans;
+pub mod quarantine;
 
 // Re-export key functions
@@ -1,54 +1,113 @@
 //! Orphaned file handling logic
 
-use crate::types::{OrphanedFileAction, OrphanedFileCategory};
+use crate::healing::backup::OrphanBackup;
+use crate::healing::quarantine::OrphanQuarantine;
+use crate::types::{HealingAction, HealingContext, OrphanedFileAction, OrphanedFileCategory};
 use sps2_errors::{Error, OpsError};
 use sps2_events::{EventEmitter, EventSender};
-use sps2_state::StateManager;
 
 use std::path::Path;
 
 /// Handle an orphaned file based on configuration and category
 ///
+/// Backed-up and quarantined files are recorded in `backup` and
+/// `quarantine`, which should be shared by all orphans handled in the same
+/// healing run. In a dry run the action is only reported.
+///
 /// # Errors
 ///
 /// Returns an error if:
 /// - File operations fail
 /// - Backup directory creation fails
 pub async fn handle_orphaned_file(
-    state_manager: &StateManager,
-    tx: &EventSender,
+    ctx: &HealingContext<'_>,
     file_path: &str,
     category: &OrphanedFileCategory,
     config: &sps2_config::Config,
-) -> Result<(), Error> {
-    let live_path = state_manager.live_path();
+    backup: &mut OrphanBackup,
+    quarantine: &mut OrphanQuarantine,
+) -> Result<HealingAction, Error> {
+    let live_path = ctx.state_manager.live_path();
     let full_path = live_path.join(file_path);
 
     // Determine action based on configuration and category
     let action = determine_orphaned_file_action(category, config);
 
     // Emit event about the action
-    tx.emit_debug(format!(
+    ctx.emit_debug(format!(
         "Handling orphaned file: {file_path} (category: {category:?}, action: {action:?})"
     ));
 
+    // Non-empty directories are never removed; remove follows symlinks when
+    // checking, backup and quarantine do not
+    let non_empty_dir = match action {
+        OrphanedFileAction::Preserve => false,
+        OrphanedFileAction::Remove => is_non_empty_dir(&full_path, true).await?,
+        OrphanedFileAction::Backup | OrphanedFileAction::Quarantine => {
+            is_non_empty_dir(&full_path, false).await?
+        }
+    };
+
     match action {
         OrphanedFileAction::Preserve => {
             // Just log that we're preserving it
-            tx.emit_debug(format!("Preserving orphaned file: {file_path}"));
-            Ok(())
+            ctx.emit_debug(format!("Preserving orphaned file: {file_path}"));
+            Ok(HealingAction::Preserve {
+                file_path: file_path.to_string(),
+                reason: format!("{category:?} orphan preserved by policy"),
+            })
+        }
+        _ if non_empty_dir => {
+            ctx.emit_debug(format!(
+                "Preserving non-empty orphaned directory: {file_path}"
+            ));
+            Ok(HealingAction::Preserve {
+                file_path: file_path.to_string(),
+                reason: "non-empty directory".to_string(),
+            })
+        }
+        OrphanedFileAction::Remove => {
+            if !ctx.dry_run {
+                remove_orphaned_file(ctx.tx, &full_path, file_path).await?;
+            }
+            Ok(HealingAction::RemoveOrphan {
+                file_path: file_path.to_string(),
+            })
         }
-        OrphanedFileAction::Remove => remove_orphaned_file(tx, &full_path, file_path).await,
         OrphanedFileAction::Backup => {
-            backup_and_remove_orphaned_file(
-                tx,
-                &full_path,
-                file_path,
-                &config.verification.orphaned_backup_dir,
-            )
-            .await
+            if !ctx.dry_run {
+                backup_and_remove_orphaned_file(ctx.tx, backup, &full_path, file_path).await?;
+            }
+            Ok(HealingAction::BackupOrphan {
+                file_path: file_path.to_string(),
+            })
         }
+        OrphanedFileAction::Quarantine => {
+            if !ctx.dry_run {
+                quarantine_orphaned_file(ctx.tx, quarantine, &full_path, file_path).await?;
+            }
+            Ok(HealingAction::QuarantineOrphan {
+                file_path: file_path.to_string(),
+            })
+        }
+    }
+}
+
+/// Whether `path` is a directory with entries
+async fn is_non_empty_dir(path: &Path, follow_symlinks: bool) -> Result<bool, Error> {
+    let metadata = if follow_symlinks {
+        tokio::fs::metadata(path).await?
+    } else {
+        tokio::fs::symlink_metadata(path).await?
+    };
+    if !metadata.is_dir() {
+        return Ok(false);
     }
+    Ok(tokio::fs::read_dir(path)
+        .await?
+        .next_entry()
+        .await?
+        .is_some())
 }
 
 /// Determine what action to take for an orphaned file
@@ -56,8 +115,11 @@ pub fn determine_orphaned_file_action(
     category: &OrphanedFileCategory,
     config: &sps2_config::Config,
 ) -> OrphanedFileAction {
-    // System files are always preserved
-    if matches!(category, OrphanedFileCategory::System) {
+    // System and whitelisted files are always preserved
+    if matches!(
+        category,
+        OrphanedFileCategory::System | OrphanedFileCategory::Whitelisted
+    ) {
         return OrphanedFileAction::Preserve;
     }
 
@@ -74,6 +136,7 @@ pub fn determine_orphaned_file_action(
     match config.verification.orphaned_file_action.as_str() {
         "remove" => OrphanedFileAction::Remove,
         "backup" => OrphanedFileAction::Backup,
+        "quarantine" => OrphanedFileAction::Quarantine,
         _ => OrphanedFileAction::Preserve, // Default to preserve for safety
     }
 }
@@ -126,37 +189,59 @@ pub async fn remove_orphaned_file(
     Ok(())
 }
 
-/// Backup an orphaned file then remove it
+/// Backup an orphaned file into the content-addressed backup area
 pub async fn backup_and_remove_orphaned_file(
     tx: &EventSender,
+    backup: &mut OrphanBackup,
     full_path: &Path,
     relative_path: &str,
-    backup_dir: &Path,
 ) -> Result<(), Error> {
-    // Create backup directory structure
-    let backup_path = backup_dir.join(relative_path);
-    if let Some(parent) = backup_path.parent() {
-        tokio::fs::create_dir_all(parent)
+    let backed_up =
+        backup
+            .backup(full_path, relative_path)
             .await
             .map_err(|e| OpsError::OperationFailed {
-                message: format!("Failed to create backup directory: {e}"),
+                message: format!("Failed to backup file {relative_path}: {e}"),
             })?;
+
+    if backed_up {
+        tx.emit_debug(format!(
+            "Backed up orphaned file: {relative_path} (manifest {})",
+            backup.manifest_path().display()
+        ));
+    } else {
+        tx.emit_debug(format!(
+            "Preserving non-empty orphaned directory: {relative_path}"
+        ));
     }
 
-    // Move file to backup location
-    tokio::fs::rename(full_path, &backup_path)
+    Ok(())
+}
+
+/// Move an orphaned file into the quarantine area
+pub async fn quarantine_orphaned_file(
+    tx: &EventSender,
+    quarantine: &mut OrphanQuarantine,
+    full_path: &Path,
+    relative_path: &str,
+) -> Result<(), Error> {
+    let quarantined = quarantine
+        .quarantine(full_path, relative_path)
         .await
         .map_err(|e| OpsError::OperationFailed {
-            message: format!(
-                "Failed to backup file {relative_path} to {}: {e}",
-                backup_path.display()
-            ),
+            message: format!("Failed to quarantine file {relative_path}: {e}"),
         })?;
 
-    tx.emit_debug(format!(
-        "Backed up orphaned file: {relative_path} -> {}",
-        backup_path.display()
-    ));
+    if quarantined {
+        tx.emit_debug(format!(
+            "Quarantined orphaned file: {relative_path} (manifest {})",
+            quarantine.manifest_path().display()
+        ));
+    } else {
+        tx.emit_debug(format!(
+            "Preserving non-empty orphaned directory: {relative_path}"
+        ));
+    }
 
     Ok(())
 }
@@ -0,0 +1,403 @@
+//! Quarantine area for orphaned files
+//!
+//! Unlike the content-addressed backup area, quarantine keeps the layout of
+//! the live prefix: each healing run moves its orphans into
+//! `quarantine/<run-id>/files/<relative path>`, so equally named files in
+//! different directories never collide. The run's `manifest.json` records
+//! each file's original location, size and hash, which is all a restore
+//! needs.
+
+use super::backup::is_contained_path;
+use serde::{Deserialize, Serialize};
+use sps2_errors::{Error, OpsError};
+use sps2_hash::Hash;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const QUARANTINE_DIR: &str = "quarantine";
+const FILES_DIR: &str = "files";
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// What a quarantine entry restores
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum QuarantineEntryKind {
+    /// Regular file moved into the quarantine subtree
+    File { size: u64, hash: String },
+    /// Symbolic link moved into the quarantine subtree
+    Symlink { target: PathBuf },
+    /// Empty directory, removed from the live prefix
+    Directory,
+}
+
+/// One quarantined orphan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantineEntry {
+    /// Absolute path the orphan was found at
+    pub original_path: PathBuf,
+    /// Path relative to the live prefix, and to the run's `files` directory
+    pub path: String,
+    /// How to restore it
+    #[serde(flatten)]
+    pub kind: QuarantineEntryKind,
+}
+
+/// Per-run record of quarantined orphans
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantineManifest {
+    /// Entries in the order they were quarantined
+    pub entries: Vec<QuarantineEntry>,
+}
+
+/// Quarantine session for one healing run
+///
+/// Nothing is created on disk until the first orphan is quarantined. The
+/// manifest is rewritten after every file so an interrupted run still
+/// records everything it moved out of the live prefix.
+#[derive(Debug)]
+pub struct OrphanQuarantine {
+    run_dir: PathBuf,
+    manifest: QuarantineManifest,
+}
+
+impl OrphanQuarantine {
+    /// Start a new quarantine run under `root`
+    #[must_use]
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        Self {
+            run_dir: root
+                .as_ref()
+                .join(QUARANTINE_DIR)
+                .join(format!("{secs}-{}", &suffix[..8])),
+            manifest: QuarantineManifest::default(),
+        }
+    }
+
+    /// Path of this run's manifest
+    #[must_use]
+    pub fn manifest_path(&self) -> PathBuf {
+        self.run_dir.join(MANIFEST_FILE)
+    }
+
+    /// Move an orphan out of the live prefix into this run's subtree
+    ///
+    /// Returns `false` without touching the path if it is a non-empty
+    /// directory; its contents are quarantined as orphans of their own.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the orphan cannot be read or moved, or the
+    /// manifest cannot be written.
+    pub async fn quarantine(
+        &mut self,
+        full_path: &Path,
+        relative_path: &str,
+    ) -> Result<bool, Error> {
+        let metadata = tokio::fs::symlink_metadata(full_path).await?;
+        let destination = self.run_dir.join(FILES_DIR).join(relative_path);
+
+        let kind = if metadata.file_type().is_symlink() {
+            let target = tokio::fs::read_link(full_path).await?;
+            move_path(full_path, &destination, None).await?;
+            QuarantineEntryKind::Symlink { target }
+        } else if metadata.is_dir() {
+            let mut entries = tokio::fs::read_dir(full_path).await?;
+            if entries.next_entry().await?.is_some() {
+                return Ok(false);
+            }
+            tokio::fs::remove_dir(full_path).await?;
+            QuarantineEntryKind::Directory
+        } else {
+            let hash = Hash::blake3_hash_file(full_path).await?;
+            move_path(full_path, &destination, Some(&hash)).await?;
+            QuarantineEntryKind::File {
+                size: metadata.len(),
+                hash: hash.to_hex(),
+            }
+        };
+
+        self.manifest.entries.push(QuarantineEntry {
+            original_path: full_path.to_path_buf(),
+            path: relative_path.to_string(),
+            kind,
+        });
+        self.write_manifest().await?;
+        Ok(true)
+    }
+
+    async fn write_manifest(&self) -> Result<(), Error> {
+        tokio::fs::create_dir_all(&self.run_dir).await?;
+        let json =
+            serde_json::to_vec_pretty(&self.manifest).map_err(|e| OpsError::OperationFailed {
+                message: format!("Failed to serialize quarantine manifest: {e}"),
+            })?;
+        let path = self.manifest_path();
+        let staging = path.with_extension("json.tmp");
+        tokio::fs::write(&staging, json).await?;
+        tokio::fs::rename(&staging, &path).await?;
+        Ok(())
+    }
+}
+
+/// Move `source` to `destination`, creating its parent directories
+///
+/// When a rename is not possible (the destination is on another
+/// filesystem), a regular file is copied and, once the copy is known to
+/// match `hash`, the source is deleted; a symlink is recreated.
+async fn move_path(source: &Path, destination: &Path, hash: Option<&Hash>) -> Result<(), Error> {
+    if let Some(parent) = destination.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| OpsError::OperationFailed {
+                message: format!("Failed to create {}: {e}", parent.display()),
+            })?;
+    }
+    if tokio::fs::rename(source, destination).await.is_ok() {
+        return Ok(());
+    }
+
+    let staging = destination.with_extension("quarantine.tmp");
+    match hash {
+        Some(hash) => {
+            tokio::fs::copy(source, &staging).await?;
+            let copied = Hash::hash_file_with_algorithm(&staging, hash.algorithm()).await?;
+            if copied != *hash {
+                let _ = tokio::fs::remove_file(&staging).await;
+                return Err(OpsError::OperationFailed {
+                    message: format!(
+                        "Copy of {} does not match the original, leaving it in place",
+                        source.display()
+                    ),
+                }
+                .into());
+            }
+        }
+        None => {
+            let target = tokio::fs::read_link(source).await?;
+            tokio::fs::symlink(target, &staging).await?;
+        }
+    }
+    tokio::fs::rename(&staging, destination).await?;
+    tokio::fs::remove_file(source).await?;
+    Ok(())
+}
+
+/// Move the orphans recorded in the quarantine manifest at `manifest_path`
+/// back to their original locations
+///
+/// Entries outside `live_path`, and paths that exist again in the live
+/// prefix, are left alone. Quarantined files must still match their
+/// recorded hash. Returns the number of entries restored.
+///
+/// # Errors
+///
+/// Returns an error if the manifest cannot be read, an entry's paths could
+/// lead outside the quarantine run or live prefix, a quarantined file is
+/// missing or altered, or an entry cannot be restored.
+pub async fn restore_quarantine(manifest_path: &Path, live_path: &Path) -> Result<usize, Error> {
+    let data = tokio::fs::read(manifest_path)
+        .await
+        .map_err(|e| OpsError::OperationFailed {
+            message: format!(
+                "Failed to read quarantine manifest {}: {e}",
+                manifest_path.display()
+            ),
+        })?;
+    let manifest: QuarantineManifest =
+        serde_json::from_slice(&data).map_err(|e| OpsError::OperationFailed {
+            message: format!(
+                "Invalid quarantine manifest {}: {e}",
+                manifest_path.display()
+            ),
+        })?;
+    let files_dir = manifest_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(FILES_DIR);
+
+    // Check every entry first so a bad manifest restores nothing
+    for entry in &manifest.entries {
+        let escapes_live = entry
+            .original_path
+            .strip_prefix(live_path)
+            .is_ok_and(|relative| !is_contained_path(relative));
+        if !is_contained_path(Path::new(&entry.path)) || escapes_live {
+            return Err(OpsError::OperationFailed {
+                message: format!(
+                    "Quarantine manifest {} has an unsafe entry for '{}'",
+                    manifest_path.display(),
+                    entry.path
+                ),
+            }
+            .into());
+        }
+    }
+
+    let mut restored = 0;
+    for entry in &manifest.entries {
+        let destination = &entry.original_path;
+        if !destination.starts_with(live_path)
+            || tokio::fs::symlink_metadata(destination).await.is_ok()
+        {
+            continue;
+        }
+
+        let quarantined = files_dir.join(&entry.path);
+        match &entry.kind {
+            QuarantineEntryKind::File { hash, .. } => {
+                let expected = Hash::from_hex(hash)?;
+                let actual = Hash::hash_file_with_algorithm(&quarantined, expected.algorithm())
+                    .await
+                    .map_err(|e| OpsError::OperationFailed {
+                        message: format!(
+                            "Failed to read quarantined {}: {e}",
+                            quarantined.display()
+                        ),
+                    })?;
+                if actual != expected {
+                    return Err(OpsError::OperationFailed {
+                        message: format!(
+                            "Quarantined {} no longer matches its recorded hash",
+                            quarantined.display()
+                        ),
+                    }
+                    .into());
+                }
+                move_path(&quarantined, destination, Some(&expected)).await?;
+            }
+            QuarantineEntryKind::Symlink { .. } => {
+                move_path(&quarantined, destination, None).await?;
+            }
+            QuarantineEntryKind::Directory => {
+                tokio::fs::create_dir_all(destination).await?;
+            }
+        }
+        restored += 1;
+    }
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn quarantine_keeps_relative_paths_and_restores_them() {
+        let live = TempDir::new().unwrap();
+        let backups = TempDir::new().unwrap();
+        // Same file name in two directories must not collide
+        let paths = ["etc/a/settings.conf", "etc/b/settings.conf", "bin/link"];
+        for (index, path) in paths[..2].iter().enumerate() {
+            let full = live.path().join(path);
+            std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+            std::fs::write(&full, format!("value = {index}\n")).unwrap();
+        }
+        std::fs::create_dir_all(live.path().join("bin")).unwrap();
+        std::os::unix::fs::symlink("../etc", live.path().join("bin/link")).unwrap();
+
+        let mut quarantine = OrphanQuarantine::new(backups.path());
+        for path in paths {
+            let full = live.path().join(path);
+            assert!(quarantine.quarantine(&full, path).await.unwrap());
+            assert!(std::fs::symlink_metadata(&full).is_err());
+        }
+        assert!(!quarantine
+            .quarantine(&live.path().join("etc"), "etc")
+            .await
+            .unwrap());
+
+        let manifest: QuarantineManifest =
+            serde_json::from_slice(&std::fs::read(quarantine.manifest_path()).unwrap()).unwrap();
+        assert_eq!(manifest.entries.len(), 3);
+        assert_eq!(
+            manifest.entries[0].original_path,
+            live.path().join(paths[0])
+        );
+        assert_eq!(
+            manifest.entries[0].kind,
+            QuarantineEntryKind::File {
+                size: 10,
+                hash: Hash::blake3_from_data(b"value = 0\n").to_hex(),
+            }
+        );
+        let files = quarantine.manifest_path().with_file_name(FILES_DIR);
+        assert_eq!(
+            std::fs::read_to_string(files.join(paths[1])).unwrap(),
+            "value = 1\n"
+        );
+
+        // A path recreated since is left alone
+        std::fs::write(live.path().join(paths[1]), "mine\n").unwrap();
+        let restored = restore_quarantine(&quarantine.manifest_path(), live.path())
+            .await
+            .unwrap();
+        assert_eq!(restored, 2);
+        assert_eq!(
+            std::fs::read_to_string(live.path().join(paths[0])).unwrap(),
+            "value = 0\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(live.path().join(paths[1])).unwrap(),
+            "mine\n"
+        );
+        assert_eq!(
+            std::fs::read_link(live.path().join("bin/link")).unwrap(),
+            Path::new("../etc")
+        );
+    }
+
+    #[tokio::test]
+    async fn restore_rejects_altered_files() {
+        let live = TempDir::new().unwrap();
+        let backups = TempDir::new().unwrap();
+        let orphan = live.path().join("leftover");
+        std::fs::write(&orphan, "original").unwrap();
+
+        let mut quarantine = OrphanQuarantine::new(backups.path());
+        quarantine.quarantine(&orphan, "leftover").await.unwrap();
+        let files = quarantine.manifest_path().with_file_name(FILES_DIR);
+        std::fs::write(files.join("leftover"), "tampered").unwrap();
+
+        assert!(restore_quarantine(&quarantine.manifest_path(), live.path())
+            .await
+            .is_err());
+        assert!(!orphan.exists());
+    }
+
+    #[tokio::test]
+    async fn restore_rejects_paths_leaving_their_directories() {
+        let root = TempDir::new().unwrap();
+        let live = root.path().join("live");
+        std::fs::create_dir_all(&live).unwrap();
+        let manifest_file = root.path().join("quarantine/run/manifest.json");
+        std::fs::create_dir_all(manifest_file.parent().unwrap()).unwrap();
+
+        let cases = [
+            ("../../escaped", live.join("escaped")),
+            ("/tmp/escaped", live.join("escaped")),
+            ("escaped", live.join("../escaped")),
+        ];
+        for (path, original_path) in cases {
+            let manifest = QuarantineManifest {
+                entries: vec![QuarantineEntry {
+                    original_path,
+                    path: path.to_string(),
+                    kind: QuarantineEntryKind::Directory,
+                }],
+            };
+            std::fs::write(&manifest_file, serde_json::to_vec(&manifest).unwrap()).unwrap();
+
+            assert!(
+                restore_quarantine(&manifest_file, &live).await.is_err(),
+                "{path:?} was restored"
+            );
+        }
+        assert!(!root.path().join("escaped").exists());
+        assert!(!live.join("escaped").exists());
+    }
+}
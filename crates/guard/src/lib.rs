@@ -15,11 +15,19 @@ pub use diagnostics::{DiscrepancyContext, GuardErrorExt, GuardErrorSummary, Reco
 pub use error_context::{
     ContextSummaryStats, GuardErrorContext, VerbosityLevel, VerbosityLevelExt,
 };
+pub use healing::backup::{
+    list_backup_runs, restore_orphan_backup, BackupEntry, BackupEntryKind, BackupManifest,
+    OrphanBackup,
+};
+pub use healing::quarantine::{
+    restore_quarantine, OrphanQuarantine, QuarantineEntry, QuarantineEntryKind, QuarantineManifest,
+};
 pub use store_verification::{StoreVerificationConfig, StoreVerificationStats, StoreVerifier};
 pub use types::{
-    derive_post_operation_scope, derive_pre_operation_scope, select_smart_scope, Discrepancy,
-    GuardConfig, HealingContext, OperationImpact, OperationResult, OperationType,
-    OrphanedFileAction, OrphanedFileCategory, PackageChange, PerformanceConfig, SymlinkPolicy,
+    derive_post_operation_scope, derive_post_operation_scope_with_budget,
+    derive_pre_operation_scope, select_smart_scope, Discrepancy, GuardConfig, HealingAction,
+    HealingContext, OperationImpact, OperationResult, OperationType, OrphanedFileAction,
+    OrphanedFileCategory, PackageChange, PerformanceConfig, ScopeBudget, ScopeTrim, SymlinkPolicy,
     VerificationContext, VerificationCoverage, VerificationLevel, VerificationResult,
     VerificationScope,
 };
@@ -1,11 +1,95 @@
 //! Orphaned file categorization logic
 
 use crate::types::OrphanedFileCategory;
-use std::path::Path;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use sps2_config::UserFileRules;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Configured rules identifying user files among untracked files
+#[derive(Debug, Clone, Default)]
+pub struct UserFileMatcher {
+    paths: Vec<PathBuf>,
+    patterns: GlobSet,
+    installed_at: Option<SystemTime>,
+}
+
+impl UserFileMatcher {
+    /// Build a matcher from configured rules
+    ///
+    /// `installed_at` is the creation time of the active state and is only
+    /// used when `modified_after_install` is enabled. Invalid glob patterns
+    /// are skipped; configuration validation reports them.
+    #[must_use]
+    pub fn new(rules: &UserFileRules, installed_at: Option<SystemTime>) -> Self {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &rules.patterns {
+            if let Ok(glob) = Glob::new(pattern) {
+                builder.add(glob);
+            }
+        }
+        Self {
+            paths: rules.paths.clone(),
+            patterns: builder.build().unwrap_or_default(),
+            installed_at: installed_at.filter(|_| rules.modified_after_install),
+        }
+    }
+
+    /// Check whether an untracked path (relative to the live prefix) is a user file
+    #[must_use]
+    pub fn is_user_file(&self, relative: &Path, full_path: &Path) -> bool {
+        if self.paths.iter().any(|dir| relative.starts_with(dir)) {
+            return true;
+        }
+        if self.patterns.is_match(relative) {
+            return true;
+        }
+        self.installed_at.is_some_and(|installed_at| {
+            std::fs::symlink_metadata(full_path)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified > installed_at)
+        })
+    }
+}
+
+/// Configured glob patterns for untracked files operators manage themselves
+#[derive(Debug, Clone, Default)]
+pub struct OrphanWhitelist {
+    patterns: GlobSet,
+}
+
+impl OrphanWhitelist {
+    /// Compile the whitelist patterns
+    ///
+    /// Invalid glob patterns are skipped; configuration validation reports
+    /// them.
+    #[must_use]
+    pub fn new(patterns: &[String]) -> Self {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            if let Ok(glob) = Glob::new(pattern) {
+                builder.add(glob);
+            }
+        }
+        Self {
+            patterns: builder.build().unwrap_or_default(),
+        }
+    }
+
+    /// Check whether an untracked path (relative to the live prefix) is whitelisted
+    #[must_use]
+    pub fn is_match(&self, relative: &Path) -> bool {
+        self.patterns.is_match(relative)
+    }
+}
 
 /// Categorize an orphaned file based on its path and characteristics
 #[allow(clippy::case_sensitive_file_extension_comparisons)] // macOS filesystem is case-sensitive
-pub fn categorize_orphaned_file(path_str: &str, full_path: &Path) -> OrphanedFileCategory {
+pub fn categorize_orphaned_file(
+    path_str: &str,
+    full_path: &Path,
+    user_files: &UserFileMatcher,
+) -> OrphanedFileCategory {
     // Python runtime-generated artifacts that should be ignored during verification
     if is_python_runtime_artifact(path_str, full_path) {
         return OrphanedFileCategory::RuntimeGenerated;
@@ -21,6 +105,11 @@ pub fn categorize_orphaned_file(path_str: &str, full_path: &Path) -> OrphanedFil
         return OrphanedFileCategory::System;
     }
 
+    // Files the configured rules mark as user files
+    if user_files.is_user_file(Path::new(path_str), full_path) {
+        return OrphanedFileCategory::UserCreated;
+    }
+
     // Temporary files
     if path_str.ends_with(".tmp")
         || path_str.ends_with(".temp")
@@ -136,3 +225,160 @@ fn is_python_runtime_artifact(path_str: &str, full_path: &Path) -> bool {
 
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::healing::orphans::determine_orphaned_file_action;
+    use crate::types::OrphanedFileAction;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, relative: &str) -> PathBuf {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, b"content").unwrap();
+        path
+    }
+
+    #[test]
+    fn configured_rules_separate_user_config_from_orphan() {
+        let temp = TempDir::new().unwrap();
+        let edited = write(temp.path(), "etc/myapp/settings");
+        let local = write(temp.path(), "share/myapp/theme.local");
+        let orphan = write(temp.path(), "lib/libold.dylib");
+
+        // Without rules none of these look like user files
+        let none = UserFileMatcher::default();
+        assert_eq!(
+            categorize_orphaned_file("etc/myapp/settings", &edited, &none),
+            OrphanedFileCategory::Unknown
+        );
+
+        let rules = UserFileRules {
+            paths: vec![PathBuf::from("etc")],
+            patterns: vec!["**/*.local".to_string()],
+            modified_after_install: false,
+        };
+        let matcher = UserFileMatcher::new(&rules, None);
+        assert_eq!(
+            categorize_orphaned_file("etc/myapp/settings", &edited, &matcher),
+            OrphanedFileCategory::UserCreated
+        );
+        assert_eq!(
+            categorize_orphaned_file("share/myapp/theme.local", &local, &matcher),
+            OrphanedFileCategory::UserCreated
+        );
+        assert_eq!(
+            categorize_orphaned_file("lib/libold.dylib", &orphan, &matcher),
+            OrphanedFileCategory::Leftover
+        );
+
+        // Healing removes the orphan but preserves the user's config
+        let mut config = sps2_config::Config::default();
+        config.verification.orphaned_file_action = "remove".to_string();
+        assert_eq!(
+            determine_orphaned_file_action(&OrphanedFileCategory::UserCreated, &config),
+            OrphanedFileAction::Preserve
+        );
+        assert_eq!(
+            determine_orphaned_file_action(&OrphanedFileCategory::Leftover, &config),
+            OrphanedFileAction::Remove
+        );
+    }
+
+    #[test]
+    fn files_modified_after_install_are_user_files() {
+        let temp = TempDir::new().unwrap();
+        let installed_at = SystemTime::now() - Duration::from_secs(3600);
+        let created = write(temp.path(), "share/myapp/notes");
+        let leftover = write(temp.path(), "lib/libold.dylib");
+        std::fs::File::options()
+            .write(true)
+            .open(&leftover)
+            .unwrap()
+            .set_modified(installed_at - Duration::from_secs(3600))
+            .unwrap();
+
+        let rules = UserFileRules {
+            modified_after_install: true,
+            ..UserFileRules::default()
+        };
+        let matcher = UserFileMatcher::new(&rules, Some(installed_at));
+        assert_eq!(
+            categorize_orphaned_file("share/myapp/notes", &created, &matcher),
+            OrphanedFileCategory::UserCreated
+        );
+        assert_eq!(
+            categorize_orphaned_file("lib/libold.dylib", &leftover, &matcher),
+            OrphanedFileCategory::Leftover
+        );
+
+        // The mtime rule stays off unless enabled
+        let disabled = UserFileMatcher::new(&UserFileRules::default(), Some(installed_at));
+        assert!(!disabled.is_user_file(Path::new("share/myapp/notes"), &created));
+    }
+
+    #[test]
+    fn whitelisted_orphans_are_reported_but_do_not_fail_verification() {
+        use crate::orphan::detection::find_orphaned_files;
+        use crate::types::{Discrepancy, VerificationResult};
+        use std::collections::HashSet;
+
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "etc/myapp/nested/settings.conf");
+        write(temp.path(), "var/cache/myapp.db");
+        write(temp.path(), "lib/libold.dylib");
+
+        let whitelist = OrphanWhitelist::new(&[
+            "etc/myapp/**".to_string(),
+            "**/*.db".to_string(),
+            "[invalid".to_string(),
+        ]);
+        let mut discrepancies = Vec::new();
+        find_orphaned_files(
+            temp.path(),
+            &HashSet::new(),
+            &UserFileMatcher::default(),
+            &whitelist,
+            &mut discrepancies,
+        );
+
+        let category = |path: &str| {
+            discrepancies.iter().find_map(|d| match d {
+                Discrepancy::OrphanedFile {
+                    file_path,
+                    category,
+                } if file_path == path => Some(category.clone()),
+                _ => None,
+            })
+        };
+        assert_eq!(
+            category("etc/myapp/nested/settings.conf"),
+            Some(OrphanedFileCategory::Whitelisted)
+        );
+        assert_eq!(
+            category("var/cache/myapp.db"),
+            Some(OrphanedFileCategory::Whitelisted)
+        );
+        assert_eq!(
+            category("lib/libold.dylib"),
+            Some(OrphanedFileCategory::Leftover)
+        );
+
+        let mut config = sps2_config::Config::default();
+        config.verification.orphaned_file_action = "remove".to_string();
+        assert_eq!(
+            determine_orphaned_file_action(&OrphanedFileCategory::Whitelisted, &config),
+            OrphanedFileAction::Preserve
+        );
+
+        let whitelisted: Vec<Discrepancy> = discrepancies
+            .into_iter()
+            .filter(Discrepancy::is_whitelisted)
+            .collect();
+        // `etc/myapp/nested` itself matches `etc/myapp/**` too
+        assert_eq!(whitelisted.len(), 3);
+        assert!(VerificationResult::new(uuid::Uuid::nil(), whitelisted, 0).is_valid);
+    }
+}
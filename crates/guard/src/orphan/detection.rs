@@ -1,14 +1,19 @@
 //! Orphaned file detection logic
 
-use crate::orphan::categorization::categorize_orphaned_file;
+use crate::orphan::categorization::{categorize_orphaned_file, OrphanWhitelist, UserFileMatcher};
 use crate::types::{Discrepancy, OrphanedFileCategory};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
 /// Find orphaned files (files in live but not tracked in DB)
+///
+/// Files matching `whitelist` are reported as
+/// [`OrphanedFileCategory::Whitelisted`] without being categorized further.
 pub fn find_orphaned_files(
     live_path: &Path,
     tracked_files: &HashSet<PathBuf>,
+    user_files: &UserFileMatcher,
+    whitelist: &OrphanWhitelist,
     discrepancies: &mut Vec<Discrepancy>,
 ) {
     use walkdir::WalkDir;
@@ -33,7 +38,11 @@ pub fn find_orphaned_files(
                 let path_str = relative_path.to_string_lossy();
 
                 // Categorize the orphaned file
-                let category = categorize_orphaned_file(&path_str, path);
+                let category = if whitelist.is_match(&relative_path) {
+                    OrphanedFileCategory::Whitelisted
+                } else {
+                    categorize_orphaned_file(&path_str, path, user_files)
+                };
 
                 // Skip files that should be ignored during verification
                 if matches!(
@@ -39,6 +39,25 @@ pub enum VerificationScope {
         packages: Vec<(String, String)>,
         directories: Vec<PathBuf>,
     },
+    /// Verify only packages added or changed since the last state that passed
+    /// verification
+    ///
+    /// Falls back to a full sweep when `force_full` is set, when no earlier run
+    /// is recorded, or when the periodic full sweep is due.
+    Incremental { force_full: bool },
+    /// Verify exactly the given tracked files
+    ///
+    /// Paths may be absolute inside the live prefix or relative to it. No
+    /// orphan detection is performed.
+    Paths { paths: Vec<PathBuf> },
+}
+
+impl VerificationScope {
+    /// Scope covering only the given file paths
+    #[must_use]
+    pub fn paths(paths: Vec<PathBuf>) -> Self {
+        Self::Paths { paths }
+    }
 }
 
 impl Default for VerificationLevel {
@@ -54,7 +73,7 @@ impl Default for VerificationScope {
 }
 
 /// Coverage information for a scoped verification
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct VerificationCoverage {
     /// Total number of packages in state
     pub total_packages: usize,
@@ -72,6 +91,18 @@ pub struct VerificationCoverage {
     pub orphan_checked_directories: Vec<PathBuf>,
     /// Whether full orphan detection was performed
     pub full_orphan_detection: bool,
+    /// Total bytes scheduled for content hashing (Full verification only)
+    #[serde(default)]
+    pub total_bytes: u64,
+    /// Bytes accounted for by the end of the run
+    #[serde(default)]
+    pub verified_bytes: u64,
+    /// Files whose content hash was computed
+    #[serde(default)]
+    pub deep_checked_files: usize,
+    /// Files checked for existence (and size) without hashing
+    #[serde(default)]
+    pub shallow_checked_files: usize,
 }
 
 impl VerificationCoverage {
@@ -106,12 +137,39 @@ impl VerificationCoverage {
             file_coverage_percent,
             orphan_checked_directories,
             full_orphan_detection,
+            total_bytes: 0,
+            verified_bytes: 0,
+            deep_checked_files: 0,
+            shallow_checked_files: 0,
         }
     }
+
+    /// Attach byte totals from a content-hashing run
+    #[must_use]
+    pub fn with_bytes(mut self, total_bytes: u64, verified_bytes: u64) -> Self {
+        self.total_bytes = total_bytes;
+        self.verified_bytes = verified_bytes;
+        self
+    }
+
+    /// Attach counts of deep (hashed) and shallow (existence/size) file checks
+    #[must_use]
+    pub fn with_check_depth(
+        mut self,
+        deep_checked_files: usize,
+        shallow_checked_files: usize,
+    ) -> Self {
+        self.deep_checked_files = deep_checked_files;
+        self.shallow_checked_files = shallow_checked_files;
+        self
+    }
 }
 
 /// Category of orphaned file
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+///
+/// Serialized as a snake_case string (`"user_created"`).
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum OrphanedFileCategory {
     /// Leftover from previous package versions
     Leftover,
@@ -123,6 +181,8 @@ pub enum OrphanedFileCategory {
     System,
     /// Runtime-generated file that should be ignored during verification
     RuntimeGenerated,
+    /// Matched by the configured orphan whitelist; reported but preserved
+    Whitelisted,
     /// Unknown category - needs investigation
     Unknown,
 }
@@ -136,10 +196,15 @@ pub enum OrphanedFileAction {
     Preserve,
     /// Backup the file then remove
     Backup,
+    /// Move the file into a per-run quarantine subtree, keeping its path
+    Quarantine,
 }
 
 /// Types of special files that may require custom handling
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+///
+/// Serialized as a snake_case string (`"char_device"`), or `{"other": ..}`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SpecialFileType {
     /// Device file (block device)
     BlockDevice,
@@ -152,8 +217,19 @@ pub enum SpecialFileType {
     /// Other special file type
     Other(String),
 }
+/// Permission bits compared by verification
+///
+/// Installed files are linked from read-only store objects, so write bits
+/// (and setuid/setgid, which the store drops) never match the recorded mode.
+pub(crate) const VERIFIED_MODE_BITS: u32 = 0o555;
+
 /// Type of discrepancy found during verification
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+///
+/// Serialized as an object whose `kind` field names the variant in
+/// snake_case (`"missing_file"`), next to the variant's fields. Consumers
+/// should ignore kinds they do not know, as new ones may be added.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum Discrepancy {
     /// File expected but not found
     MissingFile {
@@ -177,6 +253,29 @@ pub enum Discrepancy {
         expected_hash: String,
         actual_hash: String,
     },
+    /// File mode differs from the recorded mode
+    ///
+    /// Modes hold the permission bits only (`0o755`); only the read and
+    /// execute bits are compared.
+    PermissionMismatch {
+        package_name: String,
+        package_version: String,
+        file_path: String,
+        expected_mode: u32,
+        actual_mode: u32,
+    },
+    /// Tracked path is a symlink whose chain loops back on itself
+    ///
+    /// `chain` lists the links followed, ending with the first one seen
+    /// twice. `fatal` is false when the symlink policy only reports the
+    /// cycle, in which case it does not fail verification.
+    SymlinkCycle {
+        package_name: String,
+        package_version: String,
+        file_path: String,
+        chain: Vec<String>,
+        fatal: bool,
+    },
     /// File exists but not tracked in database
     OrphanedFile {
         file_path: String,
@@ -320,6 +419,49 @@ impl Discrepancy {
                 ])
                 .with_estimated_fix_time(Duration::from_secs(60))
             }
+            Self::PermissionMismatch { package_name, package_version, file_path, expected_mode, actual_mode } => {
+                DiscrepancyContext::new(
+                    DiscrepancySeverity::Medium,
+                    RecommendedAction::AutoHeal,
+                    format!(
+                        "File '{file_path}' from package '{package_name}' v{package_version} has mode {actual_mode:o} instead of {expected_mode:o}. Executables may no longer run."
+                    ),
+                    format!(
+                        "Mode mismatch for {file_path}: expected {expected_mode:o} but got {actual_mode:o}"
+                    ),
+                )
+                .with_manual_steps(vec![
+                    format!("Restore the mode: chmod {expected_mode:o} '{file_path}'"),
+                    "Verify system integrity: sps2 verify".to_string(),
+                ])
+                .with_prevention_tips(vec![
+                    "Avoid changing permissions of package files".to_string(),
+                ])
+                .with_estimated_fix_time(Duration::from_secs(10))
+            }
+            Self::SymlinkCycle { package_name, package_version, file_path, chain, fatal } => {
+                let (severity, action) = if *fatal {
+                    (DiscrepancySeverity::High, RecommendedAction::ManualIntervention)
+                } else {
+                    (DiscrepancySeverity::Low, RecommendedAction::Ignore)
+                };
+                DiscrepancyContext::new(
+                    severity,
+                    action,
+                    format!(
+                        "Symlink '{file_path}' from package '{package_name}' v{package_version} points back to itself and can never be resolved."
+                    ),
+                    format!("Symlink cycle at {file_path}: {}", chain.join(" -> ")),
+                )
+                .with_manual_steps(vec![
+                    format!("Inspect the links: ls -l {}", chain.join(" ")),
+                    format!("Reinstall package: sps2 install {}:{} --force", package_name, package_version),
+                ])
+                .with_prevention_tips(vec![
+                    "Avoid repointing symlinks inside the package directory".to_string(),
+                ])
+                .with_estimated_fix_time(Duration::from_secs(60))
+            }
             Self::OrphanedFile { file_path, category } => {
                 let (severity, action, message) = match category {
                     OrphanedFileCategory::Leftover => (
@@ -347,6 +489,11 @@ impl Discrepancy {
                         RecommendedAction::Ignore,
                         format!("Runtime-generated file '{file_path}' (Python bytecode, caches, etc.). Ignoring during verification.")
                     ),
+                    OrphanedFileCategory::Whitelisted => (
+                        DiscrepancySeverity::Low,
+                        RecommendedAction::Ignore,
+                        format!("Whitelisted file '{file_path}' is managed outside sps2. Preserving.")
+                    ),
                     OrphanedFileCategory::Unknown => (
                         DiscrepancySeverity::Medium,
                         RecommendedAction::UserConfirmation,
@@ -472,6 +619,30 @@ impl Discrepancy {
         matches!(self.recommended_action(), RecommendedAction::AutoHeal)
     }
 
+    /// Whether this is an orphan matched by the configured whitelist
+    ///
+    /// Whitelisted orphans are reported for auditing but do not fail
+    /// verification.
+    #[must_use]
+    pub fn is_whitelisted(&self) -> bool {
+        matches!(
+            self,
+            Self::OrphanedFile {
+                category: OrphanedFileCategory::Whitelisted,
+                ..
+            }
+        )
+    }
+
+    /// Whether this discrepancy is reported without failing verification
+    ///
+    /// True for whitelisted orphans and for symlink cycles the symlink
+    /// policy only reports.
+    #[must_use]
+    pub fn is_advisory(&self) -> bool {
+        self.is_whitelisted() || matches!(self, Self::SymlinkCycle { fatal: false, .. })
+    }
+
     /// Check if this discrepancy requires user confirmation before healing
     #[must_use]
     pub fn requires_confirmation(&self) -> bool {
@@ -499,6 +670,15 @@ impl Discrepancy {
                 format!("Wrong type for {file_path}: expected {expected}")
             }
             Self::CorruptedFile { file_path, .. } => format!("Corrupted file: {file_path}"),
+            Self::PermissionMismatch {
+                file_path,
+                expected_mode,
+                actual_mode,
+                ..
+            } => {
+                format!("Wrong mode for {file_path}: {actual_mode:o} instead of {expected_mode:o}")
+            }
+            Self::SymlinkCycle { file_path, .. } => format!("Symlink cycle: {file_path}"),
             Self::OrphanedFile {
                 file_path,
                 category,
@@ -529,6 +709,8 @@ impl Discrepancy {
             Self::MissingFile { file_path, .. }
             | Self::TypeMismatch { file_path, .. }
             | Self::CorruptedFile { file_path, .. }
+            | Self::PermissionMismatch { file_path, .. }
+            | Self::SymlinkCycle { file_path, .. }
             | Self::OrphanedFile { file_path, .. }
             | Self::UnsupportedSpecialFile { file_path, .. } => file_path,
             Self::MissingVenv { venv_path, .. } => venv_path,
@@ -543,6 +725,8 @@ impl Discrepancy {
             Self::MissingFile { package_name, .. }
             | Self::TypeMismatch { package_name, .. }
             | Self::CorruptedFile { package_name, .. }
+            | Self::PermissionMismatch { package_name, .. }
+            | Self::SymlinkCycle { package_name, .. }
             | Self::MissingVenv { package_name, .. }
             | Self::MissingPackageContent { package_name, .. }
             | Self::UnsupportedSpecialFile { package_name, .. } => Some(package_name),
@@ -563,6 +747,12 @@ impl Discrepancy {
             | Self::CorruptedFile {
                 package_version, ..
             }
+            | Self::PermissionMismatch {
+                package_version, ..
+            }
+            | Self::SymlinkCycle {
+                package_version, ..
+            }
             | Self::MissingVenv {
                 package_version, ..
             }
@@ -578,13 +768,16 @@ impl Discrepancy {
 }
 
 /// Result of verification check
-#[derive(Debug, Clone, serde::Serialize)]
+///
+/// [`VerificationResult::to_json`] is the machine-readable export; field
+/// names are stable and fields added later are optional when reading.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct VerificationResult {
     /// State ID that was verified
     pub state_id: Uuid,
     /// List of discrepancies found
     pub discrepancies: Vec<Discrepancy>,
-    /// Whether verification passed (no discrepancies)
+    /// Whether verification passed (no discrepancies besides advisory ones)
     pub is_valid: bool,
     /// Time taken for verification in milliseconds
     pub duration_ms: u64,
@@ -592,13 +785,25 @@ pub struct VerificationResult {
     pub coverage: Option<VerificationCoverage>,
     /// Cache hit rate as a fraction between 0.0 and 1.0
     pub cache_hit_rate: f64,
+    /// Packages (name, version) skipped as unchanged by incremental verification
+    #[serde(default)]
+    pub skipped_unchanged: Vec<(String, String)>,
+    /// Requested paths not tracked by any installed package (path scope only)
+    #[serde(default)]
+    pub untracked_paths: Vec<PathBuf>,
+    /// Concurrency the adaptive tuner settled on, when it was enabled
+    #[serde(default)]
+    pub settled_concurrency: Option<usize>,
+    /// Actions healing took, or would take in a dry run
+    #[serde(default)]
+    pub healing_actions: Vec<HealingAction>,
 }
 
 impl VerificationResult {
     /// Create a new verification result
     #[must_use]
     pub fn new(state_id: Uuid, discrepancies: Vec<Discrepancy>, duration_ms: u64) -> Self {
-        let is_valid = discrepancies.is_empty();
+        let is_valid = discrepancies.iter().all(Discrepancy::is_advisory);
         Self {
             state_id,
             discrepancies,
@@ -606,6 +811,10 @@ impl VerificationResult {
             duration_ms,
             coverage: None,
             cache_hit_rate: 0.0,
+            skipped_unchanged: Vec::new(),
+            untracked_paths: Vec::new(),
+            settled_concurrency: None,
+            healing_actions: Vec::new(),
         }
     }
 
@@ -617,7 +826,7 @@ impl VerificationResult {
         duration_ms: u64,
         coverage: VerificationCoverage,
     ) -> Self {
-        let is_valid = discrepancies.is_empty();
+        let is_valid = discrepancies.iter().all(Discrepancy::is_advisory);
         Self {
             state_id,
             discrepancies,
@@ -625,6 +834,10 @@ impl VerificationResult {
             duration_ms,
             coverage: Some(coverage),
             cache_hit_rate: 0.0,
+            skipped_unchanged: Vec::new(),
+            untracked_paths: Vec::new(),
+            settled_concurrency: None,
+            healing_actions: Vec::new(),
         }
     }
 
@@ -637,7 +850,7 @@ impl VerificationResult {
         coverage: VerificationCoverage,
         cache_hit_rate: f64,
     ) -> Self {
-        let is_valid = discrepancies.is_empty();
+        let is_valid = discrepancies.iter().all(Discrepancy::is_advisory);
         Self {
             state_id,
             discrepancies,
@@ -645,8 +858,45 @@ impl VerificationResult {
             duration_ms,
             coverage: Some(coverage),
             cache_hit_rate,
+            skipped_unchanged: Vec::new(),
+            untracked_paths: Vec::new(),
+            settled_concurrency: None,
+            healing_actions: Vec::new(),
         }
     }
+
+    /// Number of discrepancies at least as severe as `severity`
+    #[must_use]
+    pub fn count_at_or_above(&self, severity: DiscrepancySeverity) -> usize {
+        // Severities order from most to least severe
+        self.discrepancies
+            .iter()
+            .filter(|d| d.severity() <= severity)
+            .count()
+    }
+
+    /// Whether any discrepancy is at least as severe as `severity`
+    ///
+    /// Unlike `is_valid`, this ignores findings below the threshold, so
+    /// callers can fail only on e.g. critical discrepancies.
+    #[must_use]
+    pub fn has_failures_at_or_above(&self, severity: DiscrepancySeverity) -> bool {
+        self.discrepancies.iter().any(|d| d.severity() <= severity)
+    }
+
+    /// Convert to a pretty-printed JSON string for external tooling
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if JSON serialization fails.
+    pub fn to_json(&self) -> Result<String, sps2_errors::Error> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            sps2_errors::OpsError::SerializationError {
+                message: e.to_string(),
+            }
+            .into()
+        })
+    }
 }
 
 /// Context for verification operations to reduce argument count
@@ -677,6 +927,59 @@ pub struct HealingContext<'a> {
     pub store: &'a sps2_store::PackageStore,
     /// Event sender for progress reporting
     pub tx: &'a sps2_events::EventSender,
+    /// Work out healing actions without touching the filesystem
+    pub dry_run: bool,
+    /// Configured rules for files the user manages, which are preserved
+    pub user_files: &'a crate::orphan::categorization::UserFileMatcher,
+}
+
+/// A change made by healing, or planned by a dry run
+///
+/// Paths are relative to the live prefix. A dry run resolves the same store
+/// sources as a real run, so the two lists can be compared directly.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum HealingAction {
+    /// Re-link a missing file from the store
+    RestoreFile {
+        package_name: String,
+        package_version: String,
+        file_path: String,
+        source: PathBuf,
+    },
+    /// Replace a corrupted file with its stored content
+    ReplaceFile {
+        package_name: String,
+        package_version: String,
+        file_path: String,
+        source: PathBuf,
+    },
+    /// Remove an orphaned file or empty directory
+    RemoveOrphan { file_path: String },
+    /// Move an orphaned file into the backup area, then remove it
+    BackupOrphan { file_path: String },
+    /// Move an orphaned file into the quarantine area
+    QuarantineOrphan { file_path: String },
+    /// Change a file's mode back to the recorded permission bits
+    RestoreMode { file_path: String, mode: u32 },
+    /// Leave a file in place
+    Preserve { file_path: String, reason: String },
+}
+
+impl HealingAction {
+    /// Path of the affected file, relative to the live prefix
+    #[must_use]
+    pub fn file_path(&self) -> &str {
+        match self {
+            Self::RestoreFile { file_path, .. }
+            | Self::ReplaceFile { file_path, .. }
+            | Self::RemoveOrphan { file_path }
+            | Self::BackupOrphan { file_path }
+            | Self::QuarantineOrphan { file_path }
+            | Self::RestoreMode { file_path, .. }
+            | Self::Preserve { file_path, .. } => file_path,
+        }
+    }
 }
 
 impl<'a> EventEmitter for HealingContext<'a> {
@@ -703,6 +1006,12 @@ impl Default for SymlinkPolicy {
     }
 }
 
+/// Default maximum time between full sweeps for incremental verification (one week)
+pub const DEFAULT_FULL_SWEEP_INTERVAL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Default size below which Quick verification hashes files (64 KiB)
+pub const DEFAULT_QUICK_HASH_THRESHOLD: u64 = 64 * 1024;
+
 /// Performance configuration for guard operations
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PerformanceConfig {
@@ -710,10 +1019,90 @@ pub struct PerformanceConfig {
     pub progressive_verification: bool,
     /// Maximum number of concurrent verification tasks
     pub max_concurrent_tasks: usize,
+    /// Tune the number of concurrent tasks for hashing throughput, with
+    /// `max_concurrent_tasks` as the ceiling
+    #[serde(default)]
+    pub adaptive_concurrency: bool,
     /// Timeout for individual verification operations
     pub verification_timeout: Duration,
     /// Number of files to process in each chunk
     pub file_chunk_size: usize,
+    /// Maximum time between full sweeps when using incremental verification
+    pub full_sweep_interval: Duration,
+    /// Quick verification hashes files smaller than this many bytes and only
+    /// size-checks larger ones (0 keeps Quick existence-only)
+    pub quick_hash_threshold: u64,
+    /// Cap on what post-operation verification covers
+    #[serde(default)]
+    pub post_verification_budget: ScopeBudget,
+}
+
+/// Cap on the packages a derived verification scope may cover
+///
+/// A zero limit means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ScopeBudget {
+    /// Maximum number of packages to verify
+    #[serde(default)]
+    pub max_packages: usize,
+    /// Maximum combined package size in bytes to verify
+    #[serde(default)]
+    pub max_bytes: u64,
+}
+
+impl ScopeBudget {
+    /// Whether neither limit is set
+    #[must_use]
+    pub fn is_unlimited(&self) -> bool {
+        self.max_packages == 0 && self.max_bytes == 0
+    }
+
+    /// Keep packages in order while they fit the budget
+    ///
+    /// A package that does not fit is skipped, but later smaller packages
+    /// may still be kept.
+    fn trim(
+        &self,
+        packages: Vec<(String, String)>,
+        size_of: impl Fn(&(String, String)) -> u64,
+    ) -> (Vec<(String, String)>, ScopeTrim) {
+        let mut kept = Vec::new();
+        let mut kept_bytes = 0u64;
+        let mut trim = ScopeTrim::default();
+
+        for package in packages {
+            let size = size_of(&package);
+            let fits_count = self.max_packages == 0 || kept.len() < self.max_packages;
+            let fits_bytes =
+                self.max_bytes == 0 || kept_bytes.saturating_add(size) <= self.max_bytes;
+            if fits_count && fits_bytes {
+                kept_bytes = kept_bytes.saturating_add(size);
+                kept.push(package);
+            } else {
+                trim.skipped_bytes = trim.skipped_bytes.saturating_add(size);
+                trim.skipped.push(package);
+            }
+        }
+
+        (kept, trim)
+    }
+}
+
+/// Packages left out of a verification scope to stay within a [`ScopeBudget`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ScopeTrim {
+    /// Packages (name, version) that were not verified
+    pub skipped: Vec<(String, String)>,
+    /// Combined size in bytes of the skipped packages
+    pub skipped_bytes: u64,
+}
+
+impl ScopeTrim {
+    /// Whether nothing was trimmed
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.skipped.is_empty()
+    }
 }
 
 impl Default for PerformanceConfig {
@@ -721,8 +1110,12 @@ impl Default for PerformanceConfig {
         Self {
             progressive_verification: true,
             max_concurrent_tasks: 8,
+            adaptive_concurrency: false,
             verification_timeout: Duration::from_secs(300), // 5 minutes
             file_chunk_size: 100,                           // Process 100 files per chunk
+            full_sweep_interval: DEFAULT_FULL_SWEEP_INTERVAL,
+            quick_hash_threshold: DEFAULT_QUICK_HASH_THRESHOLD,
+            post_verification_budget: ScopeBudget::default(),
         }
     }
 }
@@ -734,12 +1127,26 @@ pub struct GuardConfig {
     pub verification_level: VerificationLevel,
     /// How to handle discrepancies
     pub discrepancy_handling: DiscrepancyHandling,
+    /// Lowest discrepancy severity that counts as a failure
+    pub failure_threshold: sps2_config::FailureThreshold,
     /// Policy for handling symlinks
     pub symlink_policy: SymlinkPolicy,
     /// Performance configuration
     pub performance: PerformanceConfig,
     /// Directories where symlinks should be handled leniently
     pub lenient_symlink_directories: Vec<PathBuf>,
+    /// Rules that mark untracked files as user files
+    pub user_files: sps2_config::UserFileRules,
+    /// Glob patterns, relative to the live prefix, for untracked files
+    /// reported as [`OrphanedFileCategory::Whitelisted`]
+    #[serde(default)]
+    pub orphan_whitelist: Vec<String>,
+    /// Report healing actions instead of performing them
+    #[serde(default)]
+    pub healing_dry_run: bool,
+    /// Re-hash every file instead of trusting the on-disk hash cache
+    #[serde(default)]
+    pub force_full: bool,
 }
 
 impl Default for GuardConfig {
@@ -747,12 +1154,17 @@ impl Default for GuardConfig {
         Self {
             verification_level: VerificationLevel::Standard,
             discrepancy_handling: DiscrepancyHandling::AutoHeal,
+            failure_threshold: sps2_config::FailureThreshold::default(),
             symlink_policy: SymlinkPolicy::Lenient,
             performance: PerformanceConfig::default(),
             lenient_symlink_directories: vec![
                 PathBuf::from(sps2_config::fixed_paths::BIN_DIR),
                 PathBuf::from(format!("{}/sbin", sps2_config::fixed_paths::LIVE_DIR)),
             ],
+            user_files: sps2_config::UserFileRules::default(),
+            orphan_whitelist: Vec::new(),
+            healing_dry_run: false,
+            force_full: false,
         }
     }
 }
@@ -773,6 +1185,15 @@ impl GuardConfig {
             DiscrepancyHandling::AutoHeal | DiscrepancyHandling::AutoHealOrFail
         )
     }
+
+    /// Whether `result` has discrepancies at or above the failure threshold
+    ///
+    /// Independent of `discrepancy_handling`, so it stays meaningful for
+    /// report-only verification.
+    #[must_use]
+    pub fn is_failure(&self, result: &VerificationResult) -> bool {
+        result.has_failures_at_or_above(self.failure_threshold.severity())
+    }
 }
 
 /// Type of package operation being performed
@@ -867,7 +1288,10 @@ impl OperationType {
 }
 
 /// Impact level of an operation
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Serialized as a snake_case string (`"high"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum OperationImpact {
     /// High impact - affects many packages or system-wide changes
     High,
@@ -1084,6 +1508,72 @@ pub fn derive_post_operation_scope(
     }
 }
 
+/// Derive post-operation verification scope capped by a [`ScopeBudget`]
+///
+/// Starts from [`derive_post_operation_scope`] and, when the budget is
+/// limited, drops affected packages that would exceed it. Upgrades and
+/// updates that would otherwise escalate to a full verification are
+/// contracted to their affected packages so a large transaction cannot
+/// trigger an unbounded post-verification. Packages left out are returned
+/// in the [`ScopeTrim`].
+pub fn derive_post_operation_scope_with_budget(
+    operation: &OperationType,
+    result: &OperationResult,
+    budget: &ScopeBudget,
+) -> (VerificationScope, ScopeTrim) {
+    let scope = derive_post_operation_scope(operation, result);
+    if budget.is_unlimited() {
+        return (scope, ScopeTrim::default());
+    }
+
+    let sized: Vec<((String, String), u64)> = result
+        .installed
+        .iter()
+        .chain(&result.updated)
+        .filter_map(|p| {
+            let version = p.to_version.clone()?;
+            Some(((p.name.clone(), version), p.size.unwrap_or(0)))
+        })
+        .collect();
+    let size_of = |package: &(String, String)| {
+        sized
+            .iter()
+            .find(|(key, _)| key == package)
+            .map_or(0, |(_, size)| *size)
+    };
+
+    match scope {
+        VerificationScope::Packages { packages } => {
+            let (packages, trim) = budget.trim(packages, size_of);
+            (VerificationScope::Packages { packages }, trim)
+        }
+        VerificationScope::Mixed {
+            packages,
+            directories,
+        } => {
+            let (packages, trim) = budget.trim(packages, size_of);
+            (
+                VerificationScope::Mixed {
+                    packages,
+                    directories,
+                },
+                trim,
+            )
+        }
+        VerificationScope::Full
+            if matches!(
+                operation,
+                OperationType::Upgrade { .. } | OperationType::Update { .. }
+            ) && !sized.is_empty() =>
+        {
+            let packages = sized.iter().map(|(key, _)| key.clone()).collect();
+            let (packages, trim) = budget.trim(packages, size_of);
+            (VerificationScope::Packages { packages }, trim)
+        }
+        other => (other, ScopeTrim::default()),
+    }
+}
+
 /// Smart scope selection based on operation impact and system state
 ///
 /// This function provides intelligent scope selection that balances
@@ -1161,8 +1651,16 @@ impl From<&sps2_config::PerformanceConfigToml> for PerformanceConfig {
         Self {
             progressive_verification: config.progressive_verification,
             max_concurrent_tasks: config.max_concurrent_tasks,
+            adaptive_concurrency: config.adaptive_concurrency,
             verification_timeout: Duration::from_secs(config.verification_timeout_seconds),
             file_chunk_size: 100, // Use default chunk size
+            full_sweep_interval: config
+                .full_sweep_interval_seconds
+                .map_or(DEFAULT_FULL_SWEEP_INTERVAL, Duration::from_secs),
+            quick_hash_threshold: config
+                .quick_hash_threshold
+                .unwrap_or(DEFAULT_QUICK_HASH_THRESHOLD),
+            post_verification_budget: ScopeBudget::default(),
         }
     }
 }
@@ -1189,9 +1687,14 @@ impl From<&sps2_config::VerificationConfig> for GuardConfig {
         Self {
             verification_level,
             discrepancy_handling: config.discrepancy_handling,
+            failure_threshold: config.failure_threshold,
             symlink_policy,
             performance: (&config.performance).into(),
             lenient_symlink_directories: config.guard.lenient_symlink_directories.clone(),
+            user_files: config.user_files.clone(),
+            orphan_whitelist: config.orphan_whitelist.clone(),
+            healing_dry_run: false,
+            force_full: false,
         }
     }
 }
@@ -1215,8 +1718,19 @@ impl From<&sps2_config::GuardPerformanceConfig> for PerformanceConfig {
         Self {
             progressive_verification: config.progressive_verification,
             max_concurrent_tasks: config.max_concurrent_tasks,
+            adaptive_concurrency: config.adaptive_concurrency,
             verification_timeout: Duration::from_secs(config.verification_timeout_seconds),
             file_chunk_size: 100, // Use default chunk size
+            full_sweep_interval: config
+                .full_sweep_interval_seconds
+                .map_or(DEFAULT_FULL_SWEEP_INTERVAL, Duration::from_secs),
+            quick_hash_threshold: config
+                .quick_hash_threshold
+                .unwrap_or(DEFAULT_QUICK_HASH_THRESHOLD),
+            post_verification_budget: ScopeBudget {
+                max_packages: config.post_verification_max_packages,
+                max_bytes: config.post_verification_max_bytes,
+            },
         }
     }
 }
@@ -1234,6 +1748,7 @@ impl From<&sps2_config::GuardConfiguration> for GuardConfig {
         Self {
             verification_level,
             discrepancy_handling: config.discrepancy_handling,
+            failure_threshold: config.failure_threshold,
             symlink_policy: config.symlink_policy.into(),
             performance: (&config.performance).into(),
             lenient_symlink_directories: config
@@ -1241,6 +1756,331 @@ impl From<&sps2_config::GuardConfiguration> for GuardConfig {
                 .iter()
                 .map(|dir_config| dir_config.path.clone())
                 .collect(),
+            user_files: config.user_files.clone(),
+            orphan_whitelist: config.orphan_whitelist.clone(),
+            healing_dry_run: false,
+            force_full: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(name: &str, version: &str, size: u64) -> PackageChange {
+        PackageChange {
+            name: name.to_string(),
+            from_version: None,
+            to_version: Some(version.to_string()),
+            size: Some(size),
+        }
+    }
+
+    fn install_result(changes: Vec<PackageChange>) -> OperationResult {
+        OperationResult {
+            installed: changes,
+            updated: vec![],
+            removed: vec![],
+            state_id: Uuid::nil(),
+            duration_ms: 0,
+            modified_directories: vec![],
+            install_triggered: false,
+        }
+    }
+
+    fn install_op() -> OperationType {
+        OperationType::Install {
+            package_specs: vec!["a".to_string(), "b".to_string(), "c".to_string()],
         }
     }
+
+    fn names(scope: &VerificationScope) -> Vec<&str> {
+        match scope {
+            VerificationScope::Packages { packages } => {
+                packages.iter().map(|(name, _)| name.as_str()).collect()
+            }
+            other => panic!("expected package scope, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn unlimited_budget_keeps_derived_scope() {
+        let result = install_result(vec![change("a", "1.0.0", 10), change("b", "1.0.0", 20)]);
+        let (scope, trim) = derive_post_operation_scope_with_budget(
+            &install_op(),
+            &result,
+            &ScopeBudget::default(),
+        );
+        assert_eq!(names(&scope), ["a", "b"]);
+        assert!(trim.is_empty());
+    }
+
+    #[test]
+    fn package_budget_trims_in_order_and_reports_skipped() {
+        let result = install_result(vec![
+            change("a", "1.0.0", 10),
+            change("b", "1.0.0", 20),
+            change("c", "1.0.0", 30),
+        ]);
+        let budget = ScopeBudget {
+            max_packages: 2,
+            max_bytes: 0,
+        };
+        let (scope, trim) =
+            derive_post_operation_scope_with_budget(&install_op(), &result, &budget);
+        assert_eq!(names(&scope), ["a", "b"]);
+        assert_eq!(trim.skipped, [("c".to_string(), "1.0.0".to_string())]);
+        assert_eq!(trim.skipped_bytes, 30);
+    }
+
+    #[test]
+    fn byte_budget_skips_oversized_packages_but_keeps_smaller_ones() {
+        let result = install_result(vec![
+            change("a", "1.0.0", 40),
+            change("b", "1.0.0", 100),
+            change("c", "1.0.0", 50),
+        ]);
+        let budget = ScopeBudget {
+            max_packages: 0,
+            max_bytes: 100,
+        };
+        let (scope, trim) =
+            derive_post_operation_scope_with_budget(&install_op(), &result, &budget);
+        assert_eq!(names(&scope), ["a", "c"]);
+        assert_eq!(trim.skipped, [("b".to_string(), "1.0.0".to_string())]);
+        assert_eq!(trim.skipped_bytes, 100);
+    }
+
+    #[test]
+    fn budget_contracts_large_upgrade_from_full_verification() {
+        let changes: Vec<_> = (0..12)
+            .map(|i| change(&format!("pkg{i}"), "2.0.0", 1))
+            .collect();
+        let mut result = install_result(vec![]);
+        result.updated = changes;
+        let operation = OperationType::Upgrade {
+            package_names: vec!["pkg0".to_string()],
+        };
+
+        let (unbounded, _) =
+            derive_post_operation_scope_with_budget(&operation, &result, &ScopeBudget::default());
+        assert!(matches!(unbounded, VerificationScope::Full));
+
+        let budget = ScopeBudget {
+            max_packages: 5,
+            max_bytes: 0,
+        };
+        let (scope, trim) = derive_post_operation_scope_with_budget(&operation, &result, &budget);
+        assert_eq!(names(&scope).len(), 5);
+        assert_eq!(trim.skipped.len(), 7);
+    }
+
+    fn orphan(category: OrphanedFileCategory) -> Discrepancy {
+        Discrepancy::OrphanedFile {
+            file_path: "share/leftover".to_string(),
+            category,
+        }
+    }
+
+    fn corrupted() -> Discrepancy {
+        Discrepancy::CorruptedFile {
+            package_name: "a".to_string(),
+            package_version: "1.0.0".to_string(),
+            file_path: "bin/a".to_string(),
+            expected_hash: "00".to_string(),
+            actual_hash: "ff".to_string(),
+        }
+    }
+
+    fn missing() -> Discrepancy {
+        Discrepancy::MissingFile {
+            package_name: "a".to_string(),
+            package_version: "1.0.0".to_string(),
+            file_path: "lib/liba.dylib".to_string(),
+        }
+    }
+
+    #[test]
+    fn severity_gate_counts_only_discrepancies_at_or_above_threshold() {
+        let result = VerificationResult::new(
+            Uuid::nil(),
+            vec![
+                orphan(OrphanedFileCategory::Temporary),
+                orphan(OrphanedFileCategory::Unknown),
+                missing(),
+            ],
+            0,
+        );
+        assert!(!result.is_valid);
+        assert!(result.has_failures_at_or_above(DiscrepancySeverity::Low));
+        assert!(result.has_failures_at_or_above(DiscrepancySeverity::High));
+        assert!(!result.has_failures_at_or_above(DiscrepancySeverity::Critical));
+        assert_eq!(result.count_at_or_above(DiscrepancySeverity::Low), 3);
+        assert_eq!(result.count_at_or_above(DiscrepancySeverity::Medium), 2);
+        assert_eq!(result.count_at_or_above(DiscrepancySeverity::High), 1);
+        assert_eq!(result.count_at_or_above(DiscrepancySeverity::Critical), 0);
+
+        let minor_only = VerificationResult::new(
+            Uuid::nil(),
+            vec![orphan(OrphanedFileCategory::UserCreated)],
+            0,
+        );
+        assert!(minor_only.has_failures_at_or_above(DiscrepancySeverity::Low));
+        assert!(!minor_only.has_failures_at_or_above(DiscrepancySeverity::Medium));
+
+        let clean = VerificationResult::new(Uuid::nil(), vec![], 0);
+        assert!(!clean.has_failures_at_or_above(DiscrepancySeverity::Low));
+    }
+
+    #[test]
+    fn configured_threshold_decides_failure_independent_of_handling() {
+        let mut config = GuardConfig {
+            discrepancy_handling: DiscrepancyHandling::ReportOnly,
+            failure_threshold: sps2_config::FailureThreshold::Critical,
+            ..GuardConfig::default()
+        };
+        let high = VerificationResult::new(Uuid::nil(), vec![missing()], 0);
+        let critical = VerificationResult::new(Uuid::nil(), vec![missing(), corrupted()], 0);
+
+        assert!(!config.should_fail_on_discrepancy());
+        assert!(!config.is_failure(&high));
+        assert!(config.is_failure(&critical));
+
+        config.failure_threshold = sps2_config::FailureThreshold::High;
+        assert!(config.is_failure(&high));
+
+        let toml_config = sps2_config::VerificationConfig {
+            failure_threshold: sps2_config::FailureThreshold::Medium,
+            ..sps2_config::VerificationConfig::default()
+        };
+        let config = GuardConfig::from(&toml_config);
+        assert!(!config.is_failure(&VerificationResult::new(
+            Uuid::nil(),
+            vec![orphan(OrphanedFileCategory::System)],
+            0,
+        )));
+        assert!(config.is_failure(&VerificationResult::new(
+            Uuid::nil(),
+            vec![orphan(OrphanedFileCategory::Leftover)],
+            0,
+        )));
+    }
+
+    #[test]
+    fn quick_hash_threshold_comes_from_config() {
+        let toml_config = sps2_config::VerificationConfig::default();
+        assert_eq!(
+            GuardConfig::from(&toml_config)
+                .performance
+                .quick_hash_threshold,
+            DEFAULT_QUICK_HASH_THRESHOLD
+        );
+
+        let mut guard_config = sps2_config::GuardConfiguration::default();
+        guard_config.performance.quick_hash_threshold = Some(0);
+        assert_eq!(
+            GuardConfig::from(&guard_config)
+                .performance
+                .quick_hash_threshold,
+            0
+        );
+
+        let mut toml_config = sps2_config::VerificationConfig::default();
+        toml_config.performance.quick_hash_threshold = Some(4096);
+        assert_eq!(
+            GuardConfig::from(&toml_config)
+                .performance
+                .quick_hash_threshold,
+            4096
+        );
+    }
+
+    #[test]
+    fn full_sweep_interval_comes_from_config() {
+        let toml_config = sps2_config::VerificationConfig::default();
+        assert_eq!(
+            GuardConfig::from(&toml_config)
+                .performance
+                .full_sweep_interval,
+            DEFAULT_FULL_SWEEP_INTERVAL
+        );
+
+        let mut guard_config = sps2_config::GuardConfiguration::default();
+        guard_config.performance.full_sweep_interval_seconds = Some(3600);
+        assert_eq!(
+            GuardConfig::from(&guard_config)
+                .performance
+                .full_sweep_interval,
+            Duration::from_secs(3600)
+        );
+
+        let mut toml_config = sps2_config::VerificationConfig::default();
+        toml_config.performance.full_sweep_interval_seconds = Some(60);
+        assert_eq!(
+            GuardConfig::from(&toml_config)
+                .performance
+                .full_sweep_interval,
+            Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn verification_result_round_trips_through_json() {
+        let mut result = VerificationResult::with_coverage(
+            Uuid::new_v4(),
+            vec![
+                missing(),
+                corrupted(),
+                orphan(OrphanedFileCategory::UserCreated),
+                Discrepancy::UnsupportedSpecialFile {
+                    package_name: "a".to_string(),
+                    package_version: "1.0.0".to_string(),
+                    file_path: "dev/tty".to_string(),
+                    file_type: SpecialFileType::CharDevice,
+                },
+            ],
+            12,
+            VerificationCoverage::new(4, 2, 40, 30, vec![PathBuf::from("bin")], false),
+        );
+        result.healing_actions = vec![HealingAction::RemoveOrphan {
+            file_path: "share/leftover".to_string(),
+        }];
+
+        let json = result.to_json().unwrap();
+        assert!(json.contains(r#""kind": "corrupted_file""#));
+        assert!(json.contains(r#""category": "user_created""#));
+        assert!(json.contains(r#""file_type": "char_device""#));
+
+        let parsed: VerificationResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.state_id, result.state_id);
+        assert_eq!(parsed.discrepancies, result.discrepancies);
+        assert_eq!(parsed.healing_actions, result.healing_actions);
+        assert_eq!(parsed.to_json().unwrap(), json);
+
+        let impact = serde_json::to_string(&OperationImpact::High).unwrap();
+        assert_eq!(impact, r#""high""#);
+        assert_eq!(
+            serde_json::from_str::<OperationImpact>(&impact).unwrap(),
+            OperationImpact::High
+        );
+    }
+
+    #[test]
+    fn coverage_without_byte_counts_deserializes() {
+        let json = r#"{
+            "total_packages": 4,
+            "verified_packages": 2,
+            "total_files": 40,
+            "verified_files": 30,
+            "package_coverage_percent": 50.0,
+            "file_coverage_percent": 75.0,
+            "orphan_checked_directories": ["bin"],
+            "full_orphan_detection": false
+        }"#;
+        let coverage: VerificationCoverage = serde_json::from_str(json).unwrap();
+        assert_eq!(coverage.verified_files, 30);
+        assert_eq!(coverage.total_bytes, 0);
+        assert_eq!(coverage.deep_checked_files, 0);
+    }
 }
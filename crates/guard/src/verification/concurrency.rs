@@ -0,0 +1,434 @@
+//! Bounded and adaptive concurrency for content verification
+//!
+//! Every content hash runs under a permit from a shared semaphore sized by
+//! `max_concurrent_tasks`, whichever package the file belongs to.
+//!
+//! The best number of concurrent hashing tasks depends on the machine and
+//! the storage behind the live prefix: fast NVMe keeps scaling with more
+//! tasks, slow network volumes only thrash. When enabled, the tuner starts
+//! at half the configured ceiling and hill-climbs one task at a time on the
+//! measured throughput, settling once it has turned around twice.
+
+use sps2_errors::{Error, OpsError};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::{JoinError, JoinHandle, JoinSet};
+
+/// How long each concurrency level is measured
+const TUNE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Relative throughput change treated as noise
+const NOISE_MARGIN: f64 = 0.05;
+
+/// Direction changes before the tuner settles
+const MAX_REVERSALS: u8 = 2;
+
+/// Hill-climbing tuner for the number of concurrent verification tasks
+///
+/// Adding a task must improve throughput by more than [`NOISE_MARGIN`] to
+/// keep climbing, while dropping one only has to keep throughput within the
+/// margin, so equal throughput settles on fewer tasks.
+#[derive(Debug, Clone)]
+pub(crate) struct ConcurrencyTuner {
+    ceiling: usize,
+    current: usize,
+    increasing: bool,
+    last_throughput: Option<f64>,
+    /// Best (concurrency, throughput) seen so far
+    best: Option<(usize, f64)>,
+    reversals: u8,
+    settled: bool,
+}
+
+impl ConcurrencyTuner {
+    /// Start tuning below `ceiling`, which is never exceeded
+    pub(crate) fn new(ceiling: usize) -> Self {
+        let ceiling = ceiling.max(1);
+        Self {
+            ceiling,
+            current: (ceiling / 2).max(1),
+            increasing: true,
+            last_throughput: None,
+            best: None,
+            reversals: 0,
+            settled: ceiling == 1,
+        }
+    }
+
+    /// Concurrency to run with now
+    pub(crate) fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Whether the tuner has stopped adjusting
+    pub(crate) fn is_settled(&self) -> bool {
+        self.settled
+    }
+
+    /// Record the throughput measured at the current concurrency and return
+    /// the concurrency to use next
+    pub(crate) fn record(&mut self, throughput: f64) -> usize {
+        if self.settled {
+            return self.current;
+        }
+
+        let better_than_best = self.best.is_none_or(|(best, best_throughput)| {
+            throughput > best_throughput * (1.0 + NOISE_MARGIN)
+                || (self.current < best && throughput >= best_throughput * (1.0 - NOISE_MARGIN))
+        });
+        if better_than_best {
+            self.best = Some((self.current, throughput));
+        }
+
+        if let Some(last) = self.last_throughput {
+            let keep_going = if self.increasing {
+                throughput > last * (1.0 + NOISE_MARGIN)
+            } else {
+                throughput >= last * (1.0 - NOISE_MARGIN)
+            };
+            if !keep_going && self.reverse() {
+                return self.current;
+            }
+        }
+        self.last_throughput = Some(throughput);
+
+        if !self.can_step() && self.reverse() {
+            return self.current;
+        }
+        if self.can_step() {
+            self.current = if self.increasing {
+                self.current + 1
+            } else {
+                self.current - 1
+            };
+        }
+        self.current
+    }
+
+    fn can_step(&self) -> bool {
+        if self.increasing {
+            self.current < self.ceiling
+        } else {
+            self.current > 1
+        }
+    }
+
+    /// Turn around, settling on the best concurrency seen once the tuner
+    /// has reversed often enough; returns whether it settled
+    fn reverse(&mut self) -> bool {
+        self.increasing = !self.increasing;
+        self.reversals += 1;
+        if self.reversals >= MAX_REVERSALS {
+            if let Some((best, _)) = self.best {
+                self.current = best;
+            }
+            self.settled = true;
+        }
+        self.settled
+    }
+}
+
+/// Run `tuner` against the byte counter of a verification run
+///
+/// The semaphore must start with [`ConcurrencyTuner::current`] permits.
+/// Permits are added as the tuner climbs; when it drops, permits are taken
+/// back as running tasks release them. Abort the returned task once
+/// verification is done and read the settled value from `tuner`.
+pub(crate) fn spawn_tuner(
+    tuner: Arc<Mutex<ConcurrencyTuner>>,
+    semaphore: Arc<Semaphore>,
+    bytes_done: Arc<AtomicU64>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut granted = tuner.lock().map_or(1, |tuner| tuner.current());
+        let mut last_bytes = bytes_done.load(Ordering::Relaxed);
+        let mut last_tick = tokio::time::Instant::now();
+        let mut interval = tokio::time::interval(TUNE_INTERVAL);
+        // A shrink can wait for busy permits; measure full windows after it
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            let bytes = bytes_done.load(Ordering::Relaxed);
+            let elapsed = last_tick.elapsed().as_secs_f64();
+            last_tick = tokio::time::Instant::now();
+            let delta = bytes.saturating_sub(last_bytes);
+            last_bytes = bytes;
+
+            let target = {
+                let Ok(mut tuner) = tuner.lock() else {
+                    return;
+                };
+                // Windows without hashing (cache hits, metadata checks) say
+                // nothing about throughput
+                if delta > 0 && elapsed > 0.0 && !tuner.is_settled() {
+                    tuner.record(delta as f64 / elapsed);
+                }
+                tuner.current()
+            };
+
+            granted = resize(&semaphore, granted, target).await;
+        }
+    })
+}
+
+/// Move `semaphore` from `granted` to `target` permits, returning the new
+/// number of permits
+///
+/// Growing is immediate. Shrinking queues for the excess permits, so it
+/// waits for running tasks to release them, then forgets them.
+async fn resize(semaphore: &Arc<Semaphore>, granted: usize, target: usize) -> usize {
+    if target > granted {
+        semaphore.add_permits(target - granted);
+        return target;
+    }
+    let Ok(excess) = u32::try_from(granted - target) else {
+        return granted;
+    };
+    if excess == 0 {
+        return granted;
+    }
+    match Arc::clone(semaphore).acquire_many_owned(excess).await {
+        Ok(permits) => {
+            permits.forget();
+            target
+        }
+        Err(_) => granted,
+    }
+}
+
+/// Run `check` on every item, each holding a permit from `permits`
+///
+/// At most as many checks as the semaphore has permits are in flight at
+/// once, across all callers sharing it; a check is only spawned once it has
+/// its permit. Results come back in item order. The first failed check fails
+/// the whole batch and aborts the checks still running.
+pub(crate) async fn run_bounded<T, R, F, Fut>(
+    items: Vec<T>,
+    permits: &Arc<Semaphore>,
+    check: F,
+) -> Result<Vec<R>, Error>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = Result<R, Error>> + Send,
+{
+    // Returning early drops the set, which aborts every task left in it
+    let mut tasks = JoinSet::new();
+    let mut results: Vec<Option<R>> = items.iter().map(|_| None).collect();
+
+    for (index, item) in items.into_iter().enumerate() {
+        let permit = loop {
+            tokio::select! {
+                permit = Arc::clone(permits).acquire_owned() => {
+                    break permit.map_err(|e| OpsError::OperationFailed {
+                        message: format!("verification permits closed: {e}"),
+                    })?;
+                }
+                Some(joined) = tasks.join_next() => store_result(&mut results, joined)?,
+            }
+        };
+        let check = check.clone();
+        tasks.spawn(async move {
+            let _permit = permit;
+            (index, check(item).await)
+        });
+    }
+    while let Some(joined) = tasks.join_next().await {
+        store_result(&mut results, joined)?;
+    }
+
+    results
+        .into_iter()
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| {
+            OpsError::OperationFailed {
+                message: "verification task produced no result".to_string(),
+            }
+            .into()
+        })
+}
+
+/// Record the outcome of one [`run_bounded`] task in its item's slot
+fn store_result<R>(
+    results: &mut [Option<R>],
+    joined: Result<(usize, Result<R, Error>), JoinError>,
+) -> Result<(), Error> {
+    let (index, result) = joined.map_err(|e| OpsError::OperationFailed {
+        message: format!("verification task failed: {e}"),
+    })?;
+    results[index] = Some(result?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run the tuner against a throughput curve, checking the ceiling holds
+    fn converge(ceiling: usize, throughput: impl Fn(usize) -> f64) -> usize {
+        let mut tuner = ConcurrencyTuner::new(ceiling);
+        for _ in 0..100 {
+            assert!((1..=ceiling).contains(&tuner.current()));
+            if tuner.is_settled() {
+                return tuner.current();
+            }
+            tuner.record(throughput(tuner.current()));
+        }
+        panic!("tuner did not settle");
+    }
+
+    #[test]
+    fn settles_at_the_throughput_knee() {
+        // Scales linearly up to six tasks, then the disk is saturated
+        let settled = converge(16, |tasks| tasks.min(6) as f64 * 100.0);
+        assert_eq!(settled, 6);
+    }
+
+    #[test]
+    fn never_exceeds_the_ceiling() {
+        // Throughput keeps growing, so only the ceiling stops the climb
+        assert_eq!(converge(4, |tasks| tasks as f64 * 100.0), 4);
+        assert_eq!(converge(1, |tasks| tasks as f64 * 100.0), 1);
+    }
+
+    #[test]
+    fn backs_off_when_contention_hurts() {
+        // A slow volume that is fastest with two tasks
+        let settled = converge(8, |tasks| 100.0 / (1.0 + (tasks as f64 - 2.0).abs()));
+        assert_eq!(settled, 2);
+    }
+
+    /// Run checks under `limit` permits, returning the most checks seen in
+    /// flight at once
+    ///
+    /// Each check waits at a barrier sized to the limit, so the run only
+    /// finishes if `limit` checks really do run together; `items` must be a
+    /// multiple of `limit`.
+    async fn bounded_run(limit: usize, items: usize) -> usize {
+        use std::sync::atomic::AtomicUsize;
+        use tokio::sync::Barrier;
+
+        let permits = Arc::new(Semaphore::new(limit));
+        let barrier = Arc::new(Barrier::new(limit));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let results = run_bounded((0..items).collect(), &permits, {
+            let barrier = Arc::clone(&barrier);
+            let in_flight = Arc::clone(&in_flight);
+            let peak = Arc::clone(&peak);
+            move |item: usize| {
+                let barrier = Arc::clone(&barrier);
+                let in_flight = Arc::clone(&in_flight);
+                let peak = Arc::clone(&peak);
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    barrier.wait().await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    Ok(item * 2)
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(results, (0..items).map(|item| item * 2).collect::<Vec<_>>());
+        peak.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn tuner_task_resizes_permits_from_measured_throughput() {
+        // Synthetic disk that scales up to six tasks, then saturates
+        let window_bytes = |tasks: usize| tasks.min(6) as u64 * 1000;
+        let tuner = Arc::new(Mutex::new(ConcurrencyTuner::new(8)));
+        let semaphore = Arc::new(Semaphore::new(4));
+        let bytes_done = Arc::new(AtomicU64::new(0));
+        let task = spawn_tuner(
+            Arc::clone(&tuner),
+            Arc::clone(&semaphore),
+            Arc::clone(&bytes_done),
+        );
+        tokio::task::yield_now().await;
+
+        let mut permits = vec![semaphore.available_permits()];
+        while !tuner.lock().unwrap().is_settled() {
+            let current = tuner.lock().unwrap().current();
+            bytes_done.fetch_add(window_bytes(current), Ordering::Relaxed);
+            tokio::time::advance(TUNE_INTERVAL).await;
+            for _ in 0..10 {
+                tokio::task::yield_now().await;
+            }
+            permits.push(semaphore.available_permits());
+        }
+        task.abort();
+
+        // Climbs past the knee, backs off, then settles on the best level
+        assert_eq!(permits, [4, 5, 6, 7, 6, 5, 6]);
+    }
+
+    #[tokio::test]
+    async fn shrinking_waits_for_busy_permits() {
+        let semaphore = Arc::new(Semaphore::new(4));
+        let busy = Arc::clone(&semaphore).acquire_many_owned(4).await.unwrap();
+
+        let shrink = tokio::spawn({
+            let semaphore = Arc::clone(&semaphore);
+            async move { resize(&semaphore, 4, 2).await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!shrink.is_finished());
+
+        // Once the running tasks finish, only the new capacity is left
+        drop(busy);
+        assert_eq!(shrink.await.unwrap(), 2);
+        assert_eq!(semaphore.available_permits(), 2);
+
+        assert_eq!(resize(&semaphore, 2, 3).await, 3);
+        assert_eq!(semaphore.available_permits(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_failed_check_aborts_the_rest() {
+        use std::sync::atomic::AtomicUsize;
+
+        let permits = Arc::new(Semaphore::new(8));
+        let completed = Arc::new(AtomicUsize::new(0));
+        let result = run_bounded((0..8).collect(), &permits, {
+            let completed = Arc::clone(&completed);
+            move |item: usize| {
+                let completed = Arc::clone(&completed);
+                async move {
+                    if item == 3 {
+                        return Err(OpsError::OperationFailed {
+                            message: "corrupt".to_string(),
+                        }
+                        .into());
+                    }
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    completed.fetch_add(1, Ordering::SeqCst);
+                    Ok(item)
+                }
+            }
+        })
+        .await;
+
+        assert!(result.is_err());
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(completed.load(Ordering::SeqCst), 0);
+        assert_eq!(permits.available_permits(), 8);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn bounded_checks_respect_the_limit() {
+        let run = |limit| tokio::time::timeout(Duration::from_secs(10), bounded_run(limit, 40));
+
+        assert_eq!(run(1).await.unwrap(), 1);
+        assert_eq!(run(8).await.unwrap(), 8);
+    }
+}
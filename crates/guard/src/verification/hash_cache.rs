@@ -0,0 +1,223 @@
+//! On-disk cache of installed file hashes
+//!
+//! Entries are keyed by a file's path relative to the live prefix and are
+//! trusted only while the file's inode, change time, modification time and
+//! size still match, so repeat verifications only read files that changed
+//! since they were hashed. Installed files carry deterministic archive
+//! mtimes, so the inode and change time are what catch a file replaced by an
+//! upgrade or rewritten in place.
+
+use serde::{Deserialize, Serialize};
+use sps2_errors::Error;
+use sps2_hash::{Hash, HashAlgorithm};
+use std::collections::HashMap;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// File name of the cache inside the state directory
+pub(crate) const HASH_CACHE_FILE: &str = "guard-hash-cache.json";
+
+/// Header identifying the cache file format
+const CACHE_FORMAT: &str = "sps2-guard-hash-cache";
+
+/// Layout version; caches with any other version are discarded
+const CACHE_VERSION: u32 = 2;
+
+/// Identity, change time, modification time and size a hash was computed for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct FileStamp {
+    inode: u64,
+    /// Status change time in nanoseconds since the Unix epoch
+    ctime_ns: u64,
+    /// Modification time in nanoseconds since the Unix epoch
+    mtime_ns: u64,
+    size: u64,
+}
+
+impl FileStamp {
+    pub(crate) fn from_metadata(metadata: &std::fs::Metadata) -> Self {
+        let mtime_ns = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since| {
+                u64::try_from(since.as_nanos()).unwrap_or(u64::MAX)
+            });
+        let ctime_ns = u64::try_from(metadata.ctime())
+            .unwrap_or(0)
+            .saturating_mul(1_000_000_000)
+            .saturating_add(u64::try_from(metadata.ctime_nsec()).unwrap_or(0));
+        Self {
+            inode: metadata.ino(),
+            ctime_ns,
+            mtime_ns,
+            size: metadata.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CacheEntry {
+    #[serde(flatten)]
+    stamp: FileStamp,
+    hash: String,
+}
+
+/// Serialized form of the cache
+#[derive(Deserialize)]
+struct CacheFile {
+    format: String,
+    version: u32,
+    entries: HashMap<String, CacheEntry>,
+}
+
+/// Cached hashes of installed files
+#[derive(Debug, Clone)]
+pub(crate) struct HashCache {
+    path: PathBuf,
+    entries: HashMap<String, CacheEntry>,
+    dirty: bool,
+}
+
+impl HashCache {
+    /// Load the cache stored at `path`
+    ///
+    /// A missing, unreadable or corrupt cache, or one written with another
+    /// format version, starts out empty and is replaced on the next save.
+    pub(crate) async fn load(path: PathBuf) -> Self {
+        let entries = tokio::fs::read(&path)
+            .await
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<CacheFile>(&bytes).ok())
+            .filter(|file| file.format == CACHE_FORMAT && file.version == CACHE_VERSION)
+            .map(|file| file.entries)
+            .unwrap_or_default();
+        Self {
+            path,
+            entries,
+            dirty: false,
+        }
+    }
+
+    /// The cached hash of `file_path`, if it was computed with `algorithm`
+    /// for a file with the same `stamp`
+    pub(crate) fn lookup(
+        &self,
+        file_path: &str,
+        stamp: FileStamp,
+        algorithm: HashAlgorithm,
+    ) -> Option<Hash> {
+        let entry = self.entries.get(file_path)?;
+        if entry.stamp != stamp {
+            return None;
+        }
+        Hash::from_hex(&entry.hash)
+            .ok()
+            .filter(|hash| hash.algorithm() == algorithm)
+    }
+
+    /// Remember the hash of `file_path` as of `stamp`
+    pub(crate) fn insert(&mut self, file_path: String, stamp: FileStamp, hash: &Hash) {
+        let entry = CacheEntry {
+            stamp,
+            hash: hash.to_hex(),
+        };
+        if self.entries.get(&file_path) != Some(&entry) {
+            self.entries.insert(file_path, entry);
+            self.dirty = true;
+        }
+    }
+
+    /// Drop entries for files no longer tracked
+    pub(crate) fn retain(&mut self, mut tracked: impl FnMut(&Path) -> bool) {
+        let before = self.entries.len();
+        self.entries
+            .retain(|file_path, _| tracked(Path::new(file_path)));
+        self.dirty |= self.entries.len() != before;
+    }
+
+    /// Write the cache back if it changed since it was loaded
+    ///
+    /// The file is replaced atomically, so an interrupted save leaves the
+    /// previous cache in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache cannot be serialized or written.
+    pub(crate) async fn save(&mut self) -> Result<(), Error> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let json = serde_json::to_vec(&serde_json::json!({
+            "format": CACHE_FORMAT,
+            "version": CACHE_VERSION,
+            "entries": &self.entries,
+        }))
+        .map_err(|e| sps2_errors::OpsError::SerializationError {
+            message: e.to_string(),
+        })?;
+
+        let temp_path = self.path.with_extension("json.tmp");
+        tokio::fs::write(&temp_path, json).await?;
+        tokio::fs::rename(&temp_path, &self.path).await?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cache_round_trips_and_rejects_foreign_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("states").join(HASH_CACHE_FILE);
+        let file = temp.path().join("file.txt");
+        std::fs::write(&file, b"contents").unwrap();
+        let stamp = FileStamp::from_metadata(&std::fs::metadata(&file).unwrap());
+        let hash = Hash::blake3_from_data(b"contents");
+
+        let mut cache = HashCache::load(path.clone()).await;
+        cache.insert("share/file.txt".to_string(), stamp, &hash);
+        cache.save().await.unwrap();
+
+        let cache = HashCache::load(path.clone()).await;
+        let algorithm = hash.algorithm();
+        assert_eq!(
+            cache.lookup("share/file.txt", stamp, algorithm),
+            Some(hash.clone())
+        );
+        let resized = FileStamp {
+            size: stamp.size + 1,
+            ..stamp
+        };
+        assert_eq!(cache.lookup("share/file.txt", resized, algorithm), None);
+        let replaced = FileStamp {
+            inode: stamp.inode + 1,
+            ..stamp
+        };
+        assert_eq!(cache.lookup("share/file.txt", replaced, algorithm), None);
+        assert_eq!(cache.lookup("share/other.txt", stamp, algorithm), None);
+
+        // A newer layout or a corrupt file is discarded rather than trusted
+        let json = std::fs::read_to_string(&path).unwrap();
+        let bumped = json.replace(
+            &format!("\"version\":{CACHE_VERSION}"),
+            &format!("\"version\":{}", CACHE_VERSION + 1),
+        );
+        assert_ne!(bumped, json);
+        std::fs::write(&path, bumped).unwrap();
+        let cache = HashCache::load(path.clone()).await;
+        assert_eq!(cache.lookup("share/file.txt", stamp, algorithm), None);
+
+        std::fs::write(&path, &json[..json.len() / 2]).unwrap();
+        let cache = HashCache::load(path).await;
+        assert_eq!(cache.lookup("share/file.txt", stamp, algorithm), None);
+    }
+}
@@ -1,5 +1,8 @@
 //! Verification logic for packages and files
 
+pub(crate) mod concurrency;
+pub(crate) mod hash_cache;
 pub mod scope;
+pub(crate) mod symlinks;
 
 // Re-export key functions
@@ -1,11 +1,116 @@
 //! Scoped verification helpers
 
-use crate::types::VerificationScope;
+use crate::types::{PackageChange, VerificationScope};
 use sps2_errors::Error;
 use sps2_state::{queries, StateManager};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 use uuid::Uuid;
 
+/// Outcome of resolving a [`VerificationScope::Incremental`] request
+#[derive(Debug, Clone)]
+pub struct IncrementalPlan {
+    /// Whether every package must be verified (forced, first run, or sweep due)
+    pub full_sweep: bool,
+    /// Packages added or changed since the last verified state
+    pub changes: Vec<PackageChange>,
+    /// Packages (name, version) left untouched since the last verified state
+    pub skipped_unchanged: Vec<(String, String)>,
+}
+
+impl IncrementalPlan {
+    /// Concrete scope covering the packages that need verification
+    #[must_use]
+    pub fn scope(&self) -> VerificationScope {
+        if self.full_sweep {
+            return VerificationScope::Full;
+        }
+        VerificationScope::Packages {
+            packages: self
+                .changes
+                .iter()
+                .filter_map(|change| {
+                    change
+                        .to_version
+                        .as_ref()
+                        .map(|version| (change.name.clone(), version.clone()))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Decide which packages an incremental verification run has to cover
+///
+/// Packages are compared by name, version and content hash against the state
+/// recorded by the last successful verification.
+pub async fn resolve_incremental_scope(
+    state_manager: &StateManager,
+    state_id: &Uuid,
+    force_full: bool,
+    full_sweep_interval: Duration,
+) -> Result<IncrementalPlan, Error> {
+    let mut tx = state_manager.begin_transaction().await?;
+    let current = queries::get_state_packages(&mut tx, state_id).await?;
+    let marker = queries::get_guard_verification_marker(&mut tx).await?;
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(i64::MAX));
+    let sweep_due = marker.as_ref().is_none_or(|m| {
+        let interval = i64::try_from(full_sweep_interval.as_secs()).unwrap_or(i64::MAX);
+        now.saturating_sub(m.full_sweep_at) >= interval
+    });
+
+    let previous = match marker {
+        Some(marker) if !force_full && !sweep_due => {
+            let previous_id = marker.state_id();
+            if queries::state_exists(&mut tx, &previous_id).await? {
+                Some(queries::get_state_packages(&mut tx, &previous_id).await?)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+    tx.commit().await?;
+
+    let Some(previous) = previous else {
+        return Ok(IncrementalPlan {
+            full_sweep: true,
+            changes: Vec::new(),
+            skipped_unchanged: Vec::new(),
+        });
+    };
+
+    let previous: HashMap<&str, (&str, &str)> = previous
+        .iter()
+        .map(|p| (p.name.as_str(), (p.version.as_str(), p.hash.as_str())))
+        .collect();
+
+    let mut changes = Vec::new();
+    let mut skipped_unchanged = Vec::new();
+    for package in &current {
+        match previous.get(package.name.as_str()) {
+            Some((version, hash)) if *version == package.version && *hash == package.hash => {
+                skipped_unchanged.push((package.name.clone(), package.version.clone()));
+            }
+            prior => changes.push(PackageChange {
+                name: package.name.clone(),
+                from_version: prior.map(|(version, _)| (*version).to_string()),
+                to_version: Some(package.version.clone()),
+                size: u64::try_from(package.size).ok(),
+            }),
+        }
+    }
+
+    Ok(IncrementalPlan {
+        full_sweep: false,
+        changes,
+        skipped_unchanged,
+    })
+}
+
 /// Get packages to verify based on verification scope
 ///
 /// Returns (packages_to_verify, total_packages, total_files_in_packages_to_verify)
@@ -58,6 +163,13 @@ pub async fn get_packages_for_scope(
 
             Ok((filtered_packages, packages.len(), total_files))
         }
+        VerificationScope::Incremental { .. } => {
+            // Callers resolve incremental runs first; on its own this covers everything
+            let all_packages = queries::get_state_packages(&mut tx, state_id).await?;
+            tx.commit().await?;
+            let total_files = count_total_files(state_manager, state_id, &all_packages).await?;
+            Ok((all_packages.clone(), all_packages.len(), total_files))
+        }
         VerificationScope::Directory { path: _ } => {
             // For directory-only scope, we still need to get all packages to check which files
             // belong to the directory, but we'll filter during verification
@@ -73,6 +185,13 @@ pub async fn get_packages_for_scope(
             let total_files = count_total_files(state_manager, state_id, &all_packages).await?;
             Ok((all_packages.clone(), all_packages.len(), total_files))
         }
+        VerificationScope::Paths { paths: _ } => {
+            // Individual files are resolved to their packages by `verify_paths`
+            let all_packages = queries::get_state_packages(&mut tx, state_id).await?;
+            tx.commit().await?;
+            let total_files = count_total_files(state_manager, state_id, &all_packages).await?;
+            Ok((all_packages.clone(), all_packages.len(), total_files))
+        }
         VerificationScope::Mixed {
             packages,
             directories: _,
@@ -0,0 +1,89 @@
+//! Symlink chain resolution with cycle detection
+//!
+//! Following a link whose chain loops back on itself fails with `ELOOP`,
+//! which the rest of verification would only see as a missing file. The
+//! chain is walked one link at a time instead, so the loop can be reported
+//! with every link that takes part in it.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Links followed before a chain is treated as a cycle, as the kernel does
+const MAX_SYMLINK_HOPS: usize = 40;
+
+/// Walk the symlink chain starting at `path`
+///
+/// Returns the links visited when the chain loops, ending with the first
+/// link seen twice, or when it is longer than [`MAX_SYMLINK_HOPS`]. Returns
+/// `None` when it ends at something other than a symlink, including a
+/// dangling target.
+///
+/// Each link is identified by its canonical parent directory plus its file
+/// name, so the same link reached through different relative targets is
+/// still recognised.
+pub(crate) async fn find_symlink_cycle(path: &Path) -> Option<Vec<PathBuf>> {
+    let mut visited = HashSet::new();
+    let mut chain = Vec::new();
+    let mut current = canonical_link_path(path).await?;
+
+    loop {
+        let metadata = tokio::fs::symlink_metadata(&current).await.ok()?;
+        if !metadata.is_symlink() {
+            return None;
+        }
+        chain.push(current.clone());
+        if !visited.insert(current.clone()) || chain.len() > MAX_SYMLINK_HOPS {
+            return Some(chain);
+        }
+
+        let target = tokio::fs::read_link(&current).await.ok()?;
+        let next = match current.parent() {
+            Some(parent) => parent.join(target),
+            None => target,
+        };
+        current = canonical_link_path(&next).await?;
+    }
+}
+
+/// `path` with its parent directory canonicalized, leaving the final
+/// component unresolved so the link itself is not followed
+async fn canonical_link_path(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    Some(tokio::fs::canonicalize(parent).await.ok()?.join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    #[tokio::test]
+    async fn reports_two_link_cycle() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = tokio::fs::canonicalize(temp.path()).await.unwrap();
+        std::fs::create_dir(root.join("sub")).unwrap();
+        symlink("sub/b", root.join("a")).unwrap();
+        symlink("../a", root.join("sub/b")).unwrap();
+
+        let chain = find_symlink_cycle(&root.join("a")).await.unwrap();
+        assert_eq!(
+            chain,
+            vec![root.join("a"), root.join("sub/b"), root.join("a")]
+        );
+        let chain = find_symlink_cycle(&root.join("sub/b")).await.unwrap();
+        assert_eq!(chain.first(), chain.last());
+
+        // Chains ending in a file, or dangling, are not cycles
+        std::fs::write(root.join("file"), b"x").unwrap();
+        symlink("file", root.join("to-file")).unwrap();
+        symlink("to-file", root.join("to-link")).unwrap();
+        symlink("nowhere", root.join("dangling")).unwrap();
+        assert_eq!(find_symlink_cycle(&root.join("to-link")).await, None);
+        assert_eq!(find_symlink_cycle(&root.join("dangling")).await, None);
+        assert_eq!(find_symlink_cycle(&root.join("file")).await, None);
+    }
+}
@@ -23,6 +23,9 @@ use xxhash_rust::xxh3::Xxh3;
 /// Size of chunks for streaming hash computation
 const CHUNK_SIZE: usize = 64 * 1024; // 64KB
 
+/// Files at least this large are BLAKE3-hashed memory-mapped and multi-threaded
+pub const PARALLEL_HASH_THRESHOLD: u64 = 128 * 1024 * 1024; // 128MB
+
 /// Hash algorithm type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum HashAlgorithm {
@@ -188,18 +191,10 @@ impl Hash {
 
         match algorithm {
             HashAlgorithm::Blake3 => {
-                let mut hasher = Blake3Hasher::new();
-                let mut buffer = vec![0; CHUNK_SIZE];
-
-                loop {
-                    let n = file.read(&mut buffer).await?;
-                    if n == 0 {
-                        break;
-                    }
-                    hasher.update(&buffer[..n]);
+                if file.metadata().await?.len() >= PARALLEL_HASH_THRESHOLD {
+                    return Self::blake3_hash_file_parallel(path).await;
                 }
-
-                Ok(Self::from_blake3_bytes(*hasher.finalize().as_bytes()))
+                Self::blake3_hash_stream(&mut file).await
             }
             HashAlgorithm::XxHash128 => {
                 let mut hasher = Xxh3::new();
@@ -219,6 +214,42 @@ impl Hash {
         }
     }
 
+    /// Stream a file through BLAKE3 in fixed-size chunks
+    async fn blake3_hash_stream(file: &mut File) -> Result<Self, Error> {
+        let mut hasher = Blake3Hasher::new();
+        let mut buffer = vec![0; CHUNK_SIZE];
+
+        loop {
+            let n = file.read(&mut buffer).await?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buffer[..n]);
+        }
+
+        Ok(Self::from_blake3_bytes(*hasher.finalize().as_bytes()))
+    }
+
+    /// Hash a memory-mapped file with BLAKE3 across the rayon thread pool
+    ///
+    /// Memory use stays bounded because pages are mapped, not read into a buffer.
+    async fn blake3_hash_file_parallel(path: &Path) -> Result<Self, Error> {
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let mut hasher = Blake3Hasher::new();
+            hasher
+                .update_mmap_rayon(&path)
+                .map_err(|e| StorageError::IoError {
+                    message: format!("failed to hash {}: {e}", path.display()),
+                })?;
+            Ok(Self::from_blake3_bytes(*hasher.finalize().as_bytes()))
+        })
+        .await
+        .map_err(|e| StorageError::IoError {
+            message: format!("hash task failed: {e}"),
+        })?
+    }
+
     /// Compute BLAKE3 hash of a file (for download verification)
     ///
     /// # Errors
@@ -325,79 +356,129 @@ impl Hash {
         let mut files = BTreeMap::new();
         collect_files(dir_path, dir_path, &mut files).await?;
 
+        let mut entries = BTreeMap::new();
+        for (rel_path, (full_path, metadata)) in files {
+            #[cfg(unix)]
+            let mode = {
+                use std::os::unix::fs::PermissionsExt;
+                metadata.permissions().mode()
+            };
+            #[cfg(not(unix))]
+            let mode = 0;
+
+            let entry = if metadata.is_file() {
+                TreeEntry::File {
+                    mode,
+                    hash: Self::hash_file_with_algorithm(&full_path, algorithm).await?,
+                }
+            } else if metadata.is_symlink() {
+                TreeEntry::Symlink {
+                    mode,
+                    target: tokio::fs::read_link(&full_path)
+                        .await?
+                        .to_string_lossy()
+                        .to_string(),
+                }
+            } else {
+                TreeEntry::Other { mode }
+            };
+            entries.insert(rel_path, entry);
+        }
+
+        Ok(Self::hash_tree(&entries, algorithm))
+    }
+
+    /// Compute the directory hash of a tree described entry by entry
+    ///
+    /// Gives the same result as [`Hash::hash_directory_with_algorithm`] on a
+    /// directory with these entries, for callers that never materialize the
+    /// tree (e.g. streaming an archive into the store). File hashes must use
+    /// `algorithm` as well.
+    #[must_use]
+    pub fn hash_tree(entries: &BTreeMap<String, TreeEntry>, algorithm: HashAlgorithm) -> Self {
         match algorithm {
             HashAlgorithm::Blake3 => {
-                // Create a hasher for the entire directory
                 let mut dir_hasher = Blake3Hasher::new();
-
-                // Process files in sorted order
-                for (rel_path, (full_path, metadata)) in files {
-                    // Hash the relative path
-                    dir_hasher.update(rel_path.as_bytes());
-                    dir_hasher.update(b"\0"); // null separator
-
-                    // Hash file type and permissions
-                    #[cfg(unix)]
-                    {
-                        use std::os::unix::fs::PermissionsExt;
-                        let mode = metadata.permissions().mode();
-                        dir_hasher.update(&mode.to_le_bytes());
-                    }
-
-                    if metadata.is_file() {
-                        // Hash file contents
-                        let file_hash =
-                            Self::hash_file_with_algorithm(&full_path, algorithm).await?;
-                        dir_hasher.update(file_hash.as_bytes());
-                    } else if metadata.is_symlink() {
-                        // Hash symlink target
-                        let target = tokio::fs::read_link(&full_path).await?;
-                        dir_hasher.update(target.to_string_lossy().as_bytes());
-                    }
-
-                    // Add another separator
-                    dir_hasher.update(b"\0");
+                for (rel_path, entry) in entries {
+                    entry.hash_into(rel_path, |bytes| {
+                        dir_hasher.update(bytes);
+                    });
                 }
-
-                Ok(Self::from_blake3_bytes(*dir_hasher.finalize().as_bytes()))
+                Self::from_blake3_bytes(*dir_hasher.finalize().as_bytes())
             }
             HashAlgorithm::XxHash128 => {
-                // Create a hasher for the entire directory
                 let mut dir_hasher = Xxh3::new();
+                for (rel_path, entry) in entries {
+                    entry.hash_into(rel_path, |bytes| dir_hasher.update(bytes));
+                }
+                Self::from_xxhash128_bytes(dir_hasher.digest128().to_le_bytes())
+            }
+        }
+    }
 
-                // Process files in sorted order
-                for (rel_path, (full_path, metadata)) in files {
-                    // Hash the relative path
-                    dir_hasher.update(rel_path.as_bytes());
-                    dir_hasher.update(b"\0"); // null separator
-
-                    // Hash file type and permissions
-                    #[cfg(unix)]
-                    {
-                        use std::os::unix::fs::PermissionsExt;
-                        let mode = metadata.permissions().mode();
-                        dir_hasher.update(&mode.to_le_bytes());
-                    }
+    /// Compute hash while copying between blocking I/O handles using default algorithm (xxHash128)
+    ///
+    /// # Errors
+    /// Returns an error if reading from the reader or writing to the writer fails.
+    pub fn hash_and_copy_blocking<R, W>(mut reader: R, mut writer: W) -> Result<(Self, u64), Error>
+    where
+        R: std::io::Read,
+        W: std::io::Write,
+    {
+        let mut buffer = vec![0; CHUNK_SIZE];
+        let mut total_bytes = 0u64;
+        let mut hasher = Xxh3::new();
 
-                    if metadata.is_file() {
-                        // Hash file contents
-                        let file_hash =
-                            Self::hash_file_with_algorithm(&full_path, algorithm).await?;
-                        dir_hasher.update(file_hash.as_bytes());
-                    } else if metadata.is_symlink() {
-                        // Hash symlink target
-                        let target = tokio::fs::read_link(&full_path).await?;
-                        dir_hasher.update(target.to_string_lossy().as_bytes());
-                    }
+        loop {
+            let n = reader.read(&mut buffer)?;
+            if n == 0 {
+                break;
+            }
 
-                    // Add another separator
-                    dir_hasher.update(b"\0");
-                }
+            hasher.update(&buffer[..n]);
+            writer.write_all(&buffer[..n])?;
+            total_bytes += n as u64;
+        }
 
-                let hash_result = dir_hasher.digest128();
-                Ok(Self::from_xxhash128_bytes(hash_result.to_le_bytes()))
-            }
+        writer.flush()?;
+        let hash_result = hasher.digest128();
+        Ok((
+            Self::from_xxhash128_bytes(hash_result.to_le_bytes()),
+            total_bytes,
+        ))
+    }
+}
+
+/// One entry of a directory tree, as hashed by [`Hash::hash_tree`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeEntry {
+    /// Regular file with the hash of its contents
+    File { mode: u32, hash: Hash },
+    /// Symbolic link pointing at `target`
+    Symlink { mode: u32, target: String },
+    /// Directory or any other entry without hashed contents
+    Other { mode: u32 },
+}
+
+impl TreeEntry {
+    /// Feed this entry into a directory hasher: path, mode (unix only), contents
+    fn hash_into(&self, rel_path: &str, mut update: impl FnMut(&[u8])) {
+        update(rel_path.as_bytes());
+        update(b"\0"); // null separator
+
+        let (Self::File { mode, .. } | Self::Symlink { mode, .. } | Self::Other { mode }) = self;
+        #[cfg(unix)]
+        update(&mode.to_le_bytes());
+        #[cfg(not(unix))]
+        let _ = mode;
+
+        match self {
+            Self::File { hash, .. } => update(hash.as_bytes()),
+            Self::Symlink { target, .. } => update(target.as_bytes()),
+            Self::Other { .. } => {}
         }
+
+        update(b"\0");
     }
 }
 
@@ -472,3 +553,28 @@ async fn collect_files(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn parallel_and_streaming_blake3_agree() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("large.bin");
+        // Several MB of non-repeating data so rayon splits the input across chunks
+        let data: Vec<u8> = (0..5 * 1024 * 1024 + 17)
+            .map(|i: u32| i.wrapping_mul(2_654_435_761).to_le_bytes()[1])
+            .collect();
+        tokio::fs::write(&path, &data).await.unwrap();
+
+        let mut file = File::open(&path).await.unwrap();
+        let streamed = Hash::blake3_hash_stream(&mut file).await.unwrap();
+        let parallel = Hash::blake3_hash_file_parallel(&path).await.unwrap();
+
+        assert_eq!(streamed, parallel);
+        assert_eq!(parallel, Hash::blake3_from_data(&data));
+        assert_eq!(Hash::blake3_hash_file(&path).await.unwrap(), streamed);
+    }
+}
@@ -1,9 +1,109 @@
 //! Index caching functionality
 
 use crate::models::Index;
-use sps2_errors::{Error, StorageError};
+use async_compression::tokio::bufread::ZstdDecoder;
+use async_compression::tokio::write::ZstdEncoder;
+use serde::{Deserialize, Serialize};
+use sps2_errors::{Error, PackageError, StorageError};
+use sps2_hash::Hash;
+use std::fmt;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+
+/// Number of index generations kept in the cache
+pub const MAX_INDEX_GENERATIONS: usize = 5;
+
+/// Key prefixes of the lines in the index metadata file
+const ETAG_KEY: &str = "etag: ";
+const LAST_MODIFIED_KEY: &str = "last-modified: ";
+
+/// HTTP validators recorded for the cached index
+///
+/// Stored one `key: value` line each. Files written before keys were
+/// introduced hold a bare `ETag` on their first line, which is still read.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct CacheValidators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    fn parse(content: &str) -> Self {
+        let mut validators = Self::default();
+        for (number, line) in content.lines().enumerate() {
+            if let Some(etag) = line.strip_prefix(ETAG_KEY) {
+                validators.etag = Some(etag.to_string());
+            } else if let Some(last_modified) = line.strip_prefix(LAST_MODIFIED_KEY) {
+                validators.last_modified = Some(last_modified.to_string());
+            } else if number == 0 && !line.is_empty() {
+                validators.etag = Some(line.to_string());
+            }
+        }
+        validators
+    }
+
+    fn render(&self) -> String {
+        let etag = self.etag.iter().map(|etag| format!("{ETAG_KEY}{etag}\n"));
+        let last_modified = self
+            .last_modified
+            .iter()
+            .map(|value| format!("{LAST_MODIFIED_KEY}{value}\n"));
+        etag.chain(last_modified).collect()
+    }
+}
+
+/// Identifies an exact cached index snapshot to resolve against
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexPin {
+    /// Cache generation number
+    Generation(u64),
+    /// BLAKE3 hash (hex) of the index JSON
+    Hash(String),
+}
+
+impl fmt::Display for IndexPin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Generation(generation) => write!(f, "generation {generation}"),
+            Self::Hash(hash) => write!(f, "hash {hash}"),
+        }
+    }
+}
+
+/// A cached index generation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexSnapshot {
+    /// Monotonic generation number, starting at 1
+    pub generation: u64,
+    /// BLAKE3 hash (hex) of the index JSON
+    pub hash: String,
+}
+
+impl IndexSnapshot {
+    fn file_name(&self) -> String {
+        format!("{}-{}.json", self.generation, self.hash)
+    }
+
+    fn parse_file_name(name: &str) -> Option<Self> {
+        let (generation, hash) = name.strip_suffix(".json")?.split_once('-')?;
+        Some(Self {
+            generation: generation.parse().ok()?,
+            hash: hash.to_string(),
+        })
+    }
+
+    /// Whether this snapshot is the one `pin` refers to
+    #[must_use]
+    pub fn matches(&self, pin: &IndexPin) -> bool {
+        match pin {
+            IndexPin::Generation(generation) => self.generation == *generation,
+            IndexPin::Hash(hash) => self.hash.eq_ignore_ascii_case(hash),
+        }
+    }
+}
 
 /// Index cache manager
 #[derive(Clone, Debug)]
@@ -21,9 +121,48 @@ impl IndexCache {
 
     /// Get the index cache file path
     fn index_path(&self) -> PathBuf {
+        self.cache_dir.join("index.json.zst")
+    }
+
+    /// Get the uncompressed cache file path written by older releases
+    fn legacy_index_path(&self) -> PathBuf {
         self.cache_dir.join("index.json")
     }
 
+    /// Open the cached index, preferring the compressed file
+    ///
+    /// Returns the file and whether it is compressed, or `None` if neither
+    /// file exists.
+    async fn open_index(&self) -> Option<(fs::File, bool)> {
+        if let Ok(file) = fs::File::open(self.index_path()).await {
+            return Some((file, true));
+        }
+        let file = fs::File::open(self.legacy_index_path()).await.ok()?;
+        Some((file, false))
+    }
+
+    /// Read and parse an index opened with [`Self::open_index`]
+    async fn read_index(file: fs::File, compressed: bool) -> Result<Index, Error> {
+        let mut content = String::new();
+        let read = if compressed {
+            ZstdDecoder::new(BufReader::new(file))
+                .read_to_string(&mut content)
+                .await
+        } else {
+            BufReader::new(file).read_to_string(&mut content).await
+        };
+        read.map_err(|e| StorageError::IoError {
+            message: format!("failed to read cache: {e}"),
+        })?;
+
+        Index::from_json(&content)
+    }
+
+    /// Get the directory holding index generations
+    fn generations_dir(&self) -> PathBuf {
+        self.cache_dir.join("generations")
+    }
+
     /// Get the index metadata file path (for `ETag`, etc.)
     fn metadata_path(&self) -> PathBuf {
         self.cache_dir.join("index.meta")
@@ -31,19 +170,21 @@ impl IndexCache {
 
     /// Load index from cache
     ///
+    /// Falls back to the uncompressed `index.json` of older releases when no
+    /// compressed cache exists.
+    ///
     /// # Errors
     ///
     /// Returns an error if the cache file doesn't exist or contains invalid data.
     pub async fn load(&self) -> Result<Index, Error> {
-        let path = self.index_path();
-
-        let content = fs::read_to_string(&path)
-            .await
-            .map_err(|_e| StorageError::PathNotFound {
-                path: path.display().to_string(),
-            })?;
+        let (file, compressed) =
+            self.open_index()
+                .await
+                .ok_or_else(|| StorageError::PathNotFound {
+                    path: self.index_path().display().to_string(),
+                })?;
 
-        Index::from_json(&content)
+        Self::read_index(file, compressed).await
     }
 
     /// Save index to cache
@@ -62,9 +203,13 @@ impl IndexCache {
         let path = self.index_path();
         let json = index.to_json()?;
 
+        let mut encoder = ZstdEncoder::new(Vec::new());
+        encoder.write_all(json.as_bytes()).await?;
+        encoder.shutdown().await?;
+
         // Write to temporary file first
         let temp_path = path.with_extension("tmp");
-        fs::write(&temp_path, &json)
+        fs::write(&temp_path, encoder.into_inner())
             .await
             .map_err(|e| StorageError::IoError {
                 message: format!("failed to write cache: {e}"),
@@ -77,12 +222,122 @@ impl IndexCache {
                 message: format!("failed to rename cache file: {e}"),
             })?;
 
+        // The compressed cache now takes precedence over any legacy copy
+        let _ = fs::remove_file(self.legacy_index_path()).await;
+
+        self.save_generation(&json).await?;
+
         Ok(())
     }
 
+    /// Record `json` as a new index generation unless it matches the latest
+    async fn save_generation(&self, json: &str) -> Result<(), Error> {
+        let hash = Hash::blake3_from_data(json.as_bytes()).to_hex();
+        let mut snapshots = self.snapshots().await?;
+        if snapshots.last().is_some_and(|latest| latest.hash == hash) {
+            return Ok(());
+        }
+
+        let snapshot = IndexSnapshot {
+            generation: snapshots.last().map_or(1, |latest| latest.generation + 1),
+            hash,
+        };
+        let dir = self.generations_dir();
+        fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| StorageError::IoError {
+                message: format!("failed to create index generations dir: {e}"),
+            })?;
+        let path = dir.join(snapshot.file_name());
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, json)
+            .await
+            .map_err(|e| StorageError::IoError {
+                message: format!("failed to write index generation: {e}"),
+            })?;
+        fs::rename(&temp_path, &path)
+            .await
+            .map_err(|e| StorageError::IoError {
+                message: format!("failed to rename index generation: {e}"),
+            })?;
+
+        // Drop the oldest generations beyond the retention limit
+        snapshots.push(snapshot);
+        let excess = snapshots.len().saturating_sub(MAX_INDEX_GENERATIONS);
+        for old in &snapshots[..excess] {
+            let _ = fs::remove_file(dir.join(old.file_name())).await;
+        }
+
+        Ok(())
+    }
+
+    /// List cached index generations, oldest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the generations directory cannot be read.
+    pub async fn snapshots(&self) -> Result<Vec<IndexSnapshot>, Error> {
+        let mut entries = match fs::read_dir(self.generations_dir()).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(StorageError::IoError {
+                    message: format!("failed to read index generations: {e}"),
+                }
+                .into())
+            }
+        };
+
+        let mut snapshots = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            if let Some(snapshot) = entry
+                .file_name()
+                .to_str()
+                .and_then(IndexSnapshot::parse_file_name)
+            {
+                snapshots.push(snapshot);
+            }
+        }
+        snapshots.sort_by_key(|snapshot| snapshot.generation);
+        Ok(snapshots)
+    }
+
+    /// Load the exact index snapshot identified by `pin`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pinned snapshot is not cached, or its content
+    /// no longer matches its hash.
+    pub async fn load_pinned(&self, pin: &IndexPin) -> Result<Index, Error> {
+        let unavailable = || PackageError::IndexSnapshotUnavailable {
+            pin: pin.to_string(),
+        };
+        let snapshot = self
+            .snapshots()
+            .await?
+            .into_iter()
+            .find(|snapshot| snapshot.matches(pin))
+            .ok_or_else(unavailable)?;
+
+        let content = fs::read_to_string(self.generations_dir().join(snapshot.file_name()))
+            .await
+            .map_err(|_| unavailable())?;
+        if Hash::blake3_from_data(content.as_bytes()).to_hex() != snapshot.hash {
+            return Err(StorageError::CorruptedData {
+                message: format!(
+                    "index generation {} does not match its hash",
+                    snapshot.generation
+                ),
+            }
+            .into());
+        }
+
+        Index::from_json(&content)
+    }
+
     /// Check if cache exists
     pub async fn exists(&self) -> bool {
-        fs::metadata(self.index_path()).await.is_ok()
+        self.open_index().await.is_some()
     }
 
     /// Get cache age in seconds
@@ -91,23 +346,52 @@ impl IndexCache {
     ///
     /// Returns an error if file metadata cannot be read or timestamps are invalid.
     pub async fn age(&self) -> Result<Option<u64>, Error> {
-        let path = self.index_path();
-
-        match fs::metadata(&path).await {
-            Ok(metadata) => {
-                let modified = metadata.modified().map_err(|e| StorageError::IoError {
-                    message: format!("failed to get modification time: {e}"),
-                })?;
+        match self.open_index().await {
+            Some((file, _)) => Ok(Some(Self::age_of(&file.metadata().await?)?.as_secs())),
+            None => Ok(None),
+        }
+    }
 
-                let age = std::time::SystemTime::now()
-                    .duration_since(modified)
-                    .map(|d| d.as_secs())
-                    .unwrap_or(0);
+    /// Check whether the cache is missing or older than `max_age`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the modification time of the cache cannot be read.
+    pub async fn is_stale(&self, max_age: Duration) -> Result<bool, Error> {
+        match self.open_index().await {
+            Some((file, _)) => Ok(Self::age_of(&file.metadata().await?)? > max_age),
+            None => Ok(true),
+        }
+    }
 
-                Ok(Some(age))
-            }
-            Err(_) => Ok(None),
+    /// Load the index from cache unless it is missing or older than `max_age`
+    ///
+    /// The age is taken from the same open file that is read, so a refresh
+    /// replacing the cache in between cannot pair a fresh age with stale
+    /// content.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache cannot be read or contains invalid data.
+    pub async fn load_if_fresh(&self, max_age: Duration) -> Result<Option<Index>, Error> {
+        let Some((file, compressed)) = self.open_index().await else {
+            return Ok(None);
+        };
+        if Self::age_of(&file.metadata().await?)? > max_age {
+            return Ok(None);
         }
+
+        Self::read_index(file, compressed).await.map(Some)
+    }
+
+    /// Time since a cache file was last written; zero if it is in the future
+    fn age_of(metadata: &std::fs::Metadata) -> Result<Duration, Error> {
+        let modified = metadata.modified().map_err(|e| StorageError::IoError {
+            message: format!("failed to get modification time: {e}"),
+        })?;
+        Ok(SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default())
     }
 
     /// Clear the cache
@@ -117,7 +401,35 @@ impl IndexCache {
     /// This function does not return errors as file removal failures are ignored.
     pub async fn clear(&self) -> Result<(), Error> {
         let _ = fs::remove_file(self.index_path()).await;
+        let _ = fs::remove_file(self.legacy_index_path()).await;
         let _ = fs::remove_file(self.metadata_path()).await;
+        let _ = fs::remove_dir_all(self.generations_dir()).await;
+        Ok(())
+    }
+
+    /// Read the validators recorded in the metadata file
+    async fn load_validators(&self) -> CacheValidators {
+        fs::read_to_string(self.metadata_path())
+            .await
+            .map(|content| CacheValidators::parse(&content))
+            .unwrap_or_default()
+    }
+
+    /// Update the metadata file, keeping the validators `update` leaves alone
+    async fn update_validators(
+        &self,
+        what: &str,
+        update: impl FnOnce(&mut CacheValidators),
+    ) -> Result<(), Error> {
+        let mut validators = self.load_validators().await;
+        update(&mut validators);
+
+        fs::write(self.metadata_path(), validators.render())
+            .await
+            .map_err(|e| StorageError::IoError {
+                message: format!("failed to save {what}: {e}"),
+            })?;
+
         Ok(())
     }
 
@@ -127,15 +439,7 @@ impl IndexCache {
     ///
     /// Does not return errors - missing files return `None`.
     pub async fn load_etag(&self) -> Result<Option<String>, Error> {
-        let path = self.metadata_path();
-
-        match fs::read_to_string(&path).await {
-            Ok(content) => {
-                // Simple format: first line is ETag
-                Ok(content.lines().next().map(String::from))
-            }
-            Err(_) => Ok(None),
-        }
+        Ok(self.load_validators().await.etag)
     }
 
     /// Save `ETag`
@@ -144,14 +448,165 @@ impl IndexCache {
     ///
     /// Returns an error if the metadata file cannot be written.
     pub async fn save_etag(&self, etag: &str) -> Result<(), Error> {
-        let path = self.metadata_path();
+        self.update_validators("ETag", |validators| {
+            validators.etag = Some(etag.to_string());
+        })
+        .await
+    }
+
+    /// Load cached `Last-Modified` value
+    ///
+    /// # Errors
+    ///
+    /// Does not return errors - missing files return `None`.
+    pub async fn load_last_modified(&self) -> Result<Option<String>, Error> {
+        Ok(self.load_validators().await.last_modified)
+    }
+
+    /// Replace the cached `ETag` and `Last-Modified` values
+    ///
+    /// Pass the validators of the latest response; a value it omits is
+    /// cleared so a stale one is never sent in a later conditional request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the metadata file cannot be written.
+    pub async fn save_validators(
+        &self,
+        etag: Option<&str>,
+        last_modified: Option<&str>,
+    ) -> Result<(), Error> {
+        self.update_validators("cache validators", |validators| {
+            validators.etag = etag.map(str::to_string);
+            validators.last_modified = last_modified.map(str::to_string);
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn validators_are_stored_by_key() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache = IndexCache::new(temp.path());
+        assert_eq!(cache.load_etag().await.unwrap(), None);
 
-        fs::write(&path, etag)
+        // Bare ETag written by older releases
+        std::fs::write(cache.metadata_path(), "\"abc\"\n").unwrap();
+        assert_eq!(cache.load_etag().await.unwrap().as_deref(), Some("\"abc\""));
+        assert_eq!(cache.load_last_modified().await.unwrap(), None);
+
+        let last_modified = "Wed, 21 Oct 2026 07:28:00 GMT";
+        cache
+            .save_validators(Some("\"abc\""), Some(last_modified))
             .await
-            .map_err(|e| StorageError::IoError {
-                message: format!("failed to save ETag: {e}"),
-            })?;
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(cache.metadata_path()).unwrap(),
+            format!("etag: \"abc\"\nlast-modified: {last_modified}\n")
+        );
 
-        Ok(())
+        cache.save_etag("W/\"def\"").await.unwrap();
+        assert_eq!(
+            cache.load_etag().await.unwrap().as_deref(),
+            Some("W/\"def\"")
+        );
+        assert_eq!(
+            cache.load_last_modified().await.unwrap().as_deref(),
+            Some(last_modified)
+        );
+
+        // Only a Last-Modified value, as from mirrors without ETags
+        std::fs::write(
+            cache.metadata_path(),
+            format!("last-modified: {last_modified}\n"),
+        )
+        .unwrap();
+        assert_eq!(cache.load_etag().await.unwrap(), None);
+        assert_eq!(
+            cache.load_last_modified().await.unwrap().as_deref(),
+            Some(last_modified)
+        );
+
+        // Validators missing from the latest response are cleared
+        cache.save_validators(Some("\"ghi\""), None).await.unwrap();
+        assert_eq!(cache.load_etag().await.unwrap().as_deref(), Some("\"ghi\""));
+        assert_eq!(cache.load_last_modified().await.unwrap(), None);
+        cache.save_validators(None, None).await.unwrap();
+        assert_eq!(cache.load_etag().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn staleness_follows_the_cache_file_age() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache = IndexCache::new(temp.path());
+        let hour = Duration::from_secs(3600);
+
+        assert!(cache.is_stale(hour).await.unwrap());
+        assert!(cache.load_if_fresh(hour).await.unwrap().is_none());
+
+        cache.save(&Index::new()).await.unwrap();
+        assert!(!cache.is_stale(hour).await.unwrap());
+        assert!(cache.load_if_fresh(hour).await.unwrap().is_some());
+
+        let two_hours_ago = SystemTime::now() - 2 * hour;
+        std::fs::File::options()
+            .write(true)
+            .open(cache.index_path())
+            .unwrap()
+            .set_modified(two_hours_ago)
+            .unwrap();
+        assert!(cache.is_stale(hour).await.unwrap());
+        assert!(cache.load_if_fresh(hour).await.unwrap().is_none());
+        assert!(!cache.is_stale(3 * hour).await.unwrap());
+        assert!(cache.load_if_fresh(3 * hour).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn cache_is_compressed_and_reads_legacy_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache = IndexCache::new(temp.path());
+        let index = Index::new();
+        let json = index.to_json().unwrap();
+
+        // A plain cache from an older release still loads
+        std::fs::write(cache.legacy_index_path(), &json).unwrap();
+        assert!(cache.exists().await);
+        assert_eq!(cache.load().await.unwrap().to_json().unwrap(), json);
+
+        // Saving replaces it with a zstd-compressed file
+        cache.save(&index).await.unwrap();
+        assert!(!cache.legacy_index_path().exists());
+        let compressed = std::fs::read(cache.index_path()).unwrap();
+        assert_eq!(compressed[..4], [0x28, 0xb5, 0x2f, 0xfd]);
+        assert_eq!(cache.load().await.unwrap().to_json().unwrap(), json);
+
+        std::fs::write(cache.legacy_index_path(), &json).unwrap();
+        cache.clear().await.unwrap();
+        assert!(!cache.exists().await);
+        assert!(!cache.index_path().exists() && !cache.legacy_index_path().exists());
+    }
+
+    #[tokio::test]
+    async fn reloaded_index_does_not_add_a_generation() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache = IndexCache::new(temp.path());
+        let mut index = Index::new();
+        for i in 0..32 {
+            index
+                .packages
+                .insert(format!("pkg-{i}"), crate::models::PackageEntry::default());
+        }
+        cache.save(&index).await.unwrap();
+
+        // Each load builds fresh hash maps with their own iteration order
+        for _ in 0..4 {
+            let reloaded = cache.load().await.unwrap();
+            cache.save(&reloaded).await.unwrap();
+        }
+        assert_eq!(cache.snapshots().await.unwrap().len(), 1);
     }
 }
@@ -10,9 +10,10 @@
 mod cache;
 mod models;
 
-pub use cache::IndexCache;
+pub use cache::{IndexCache, IndexPin, IndexSnapshot, MAX_INDEX_GENERATIONS};
 pub use models::{
-    DependencyInfo, Index, IndexMetadata, PackageEntry, SbomEntry, SbomInfo, VersionEntry,
+    DependencyInfo, Index, IndexMetadata, PackageEntry, SbomEntry, SbomInfo, ShadowedEntry,
+    VersionEntry,
 };
 
 use chrono::Utc;
@@ -62,6 +63,22 @@ impl IndexManager {
         Ok(())
     }
 
+    /// Load an exact cached index snapshot instead of the latest index
+    ///
+    /// Resolving against a pinned snapshot makes installs reproducible
+    /// across machines that share the same index history.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pinned snapshot is not cached or fails validation.
+    pub async fn load_pinned(&mut self, pin: &IndexPin) -> Result<(), Error> {
+        let index = self.cache.load_pinned(pin).await?;
+        index.validate()?;
+
+        self.index = Some(index);
+        Ok(())
+    }
+
     /// Save current index to cache
     ///
     /// # Errors
@@ -149,6 +166,11 @@ impl IndexManager {
     }
 
     /// Find the best version matching a spec, returning both version string and entry
+    ///
+    /// Picks the highest matching version. Pre-releases are only picked when
+    /// the spec names a pre-release, as in `==2.0.0-rc1` or `>=2.0.0-rc1`;
+    /// otherwise `>=1.0.0` selects the newest stable release even when a
+    /// newer pre-release is in the index.
     #[must_use]
     pub fn find_best_version_with_string(
         &self,
@@ -157,27 +179,20 @@ impl IndexManager {
         let index = self.index.as_ref()?;
         let package = index.packages.get(&spec.name)?;
 
-        // Collect versions with their version strings, sort by version descending
-        let mut versions: Vec<(&String, &VersionEntry)> = package.versions.iter().collect();
-        versions.sort_by(|a, b| {
-            // Sort by version descending (newest first)
-            Version::parse(b.0)
-                .unwrap_or_else(|_| Version::new(0, 0, 0))
-                .cmp(&Version::parse(a.0).unwrap_or_else(|_| Version::new(0, 0, 0)))
-        });
+        let parsed: Vec<(Version, &String, &VersionEntry)> = package
+            .versions
+            .iter()
+            .filter_map(|(version_str, entry)| {
+                Some((Version::parse(version_str).ok()?, version_str, entry))
+            })
+            .collect();
+        let versions: Vec<Version> = parsed.iter().map(|(v, _, _)| v.clone()).collect();
 
-        // Find highest version that satisfies the spec
-        versions.into_iter().find_map(|(version_str, entry)| {
-            if let Ok(version) = Version::parse(version_str) {
-                if spec.version_spec.matches(&version) {
-                    Some((version_str.as_str(), entry))
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        })
+        let best = spec.version_spec.best_match(&versions)?;
+        parsed
+            .iter()
+            .find(|(version, _, _)| version == best)
+            .map(|(_, version_str, entry)| (version_str.as_str(), *entry))
     }
 
     /// Get a specific version entry
@@ -218,3 +233,169 @@ impl IndexManager {
         self.index = Some(index);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(version: &str) -> VersionEntry {
+        VersionEntry {
+            revision: 1,
+            arch: "arm64".to_string(),
+            blake3: "00".repeat(32),
+            download_url: format!("https://example.com/curl-{version}.sp"),
+            minisig_url: format!("https://example.com/curl-{version}.sp.minisig"),
+            dependencies: DependencyInfo::default(),
+            sbom: None,
+            description: None,
+            homepage: None,
+            license: None,
+            source: None,
+            files: Vec::new(),
+        }
+    }
+
+    fn repository(host: &str, packages: &[(&str, &str)]) -> Index {
+        let mut index = Index::new();
+        for (name, version) in packages {
+            let mut entry = entry(version);
+            entry.download_url = format!("https://{host}/{name}-{version}.sp");
+            index.add_version((*name).to_string(), (*version).to_string(), entry);
+        }
+        index
+    }
+
+    #[test]
+    fn merge_prefers_earlier_repositories() {
+        let main = repository("main.example", &[("curl", "8.0.0"), ("curl", "8.1.0")]);
+        let mut extra = repository("extra.example", &[("curl", "8.1.0"), ("curl", "8.2.0")]);
+        extra.metadata.minimum_client = "0.2.0".to_string();
+
+        let merged = Index::merge(&[main.clone(), extra.clone()]).unwrap();
+        let versions = &merged.packages["curl"].versions;
+        assert_eq!(versions.len(), 3);
+        assert_eq!(versions["8.1.0"].source, Some(0));
+        assert_eq!(
+            versions["8.1.0"].download_url,
+            "https://main.example/curl-8.1.0.sp"
+        );
+        assert_eq!(versions["8.2.0"].source, Some(1));
+        assert_eq!(
+            versions["8.2.0"].download_url,
+            "https://extra.example/curl-8.2.0.sp"
+        );
+        assert_eq!(
+            merged.shadowed,
+            [ShadowedEntry {
+                name: "curl".to_string(),
+                version: "8.1.0".to_string(),
+                source: 1,
+                kept_from: 0,
+            }]
+        );
+        assert_eq!(merged.metadata.minimum_client, "0.2.0");
+        assert_eq!(
+            merged.metadata.timestamp,
+            main.metadata.timestamp.min(extra.metadata.timestamp)
+        );
+
+        // Reversing the order reverses the precedence
+        let reversed = Index::merge(&[extra, main]).unwrap();
+        assert_eq!(reversed.packages["curl"].versions["8.1.0"].source, Some(0));
+        assert_eq!(
+            reversed.packages["curl"].versions["8.1.0"].download_url,
+            "https://extra.example/curl-8.1.0.sp"
+        );
+    }
+
+    #[test]
+    fn merge_combines_disjoint_repositories() {
+        let merged = Index::merge(&[
+            repository("main.example", &[("curl", "8.0.0")]),
+            repository("extra.example", &[("jq", "1.7.1")]),
+        ])
+        .unwrap();
+        assert_eq!(merged.package_count(), 2);
+        assert_eq!(merged.version_count(), 2);
+        assert!(merged.shadowed.is_empty());
+        assert_eq!(merged.packages["jq"].versions["1.7.1"].source, Some(1));
+        merged.validate().unwrap();
+
+        // Round-trips through JSON with the provenance intact
+        let parsed = Index::from_json(&merged.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.packages["jq"].versions["1.7.1"].source, Some(1));
+
+        let mut broken = repository("broken.example", &[("jq", "1.7.1")]);
+        broken.metadata.minimum_client = "latest".to_string();
+        assert!(Index::merge(&[broken]).is_err());
+        assert_eq!(Index::merge(&[]).unwrap().package_count(), 0);
+    }
+
+    #[test]
+    fn pre_releases_are_picked_only_when_requested() {
+        let mut manager = IndexManager::new(tempfile::tempdir().unwrap().path());
+        manager.set_index(repository(
+            "main.example",
+            &[("curl", "1.5.0"), ("curl", "2.0.0-rc1")],
+        ));
+        let best = |spec: &str| {
+            manager
+                .find_best_version_with_string(&PackageSpec::parse(spec).unwrap())
+                .map(|(version, _)| version.to_string())
+        };
+
+        assert_eq!(best("curl").as_deref(), Some("1.5.0"));
+        assert_eq!(best("curl>=1.0.0").as_deref(), Some("1.5.0"));
+        assert_eq!(best("curl==2.0.0-rc1").as_deref(), Some("2.0.0-rc1"));
+        assert_eq!(best("curl>=2.0.0-rc1").as_deref(), Some("2.0.0-rc1"));
+        assert_eq!(best("curl>=2.0.0"), None);
+    }
+
+    #[tokio::test]
+    async fn resolves_against_pinned_older_generation() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = IndexCache::new(dir.path());
+
+        let mut index = Index::new();
+        index.add_version("curl".to_string(), "8.0.0".to_string(), entry("8.0.0"));
+        cache.save(&index).await.unwrap();
+        index.add_version("curl".to_string(), "8.1.0".to_string(), entry("8.1.0"));
+        cache.save(&index).await.unwrap();
+
+        let snapshots = cache.snapshots().await.unwrap();
+        assert_eq!(
+            snapshots.iter().map(|s| s.generation).collect::<Vec<_>>(),
+            [1, 2]
+        );
+
+        let spec = PackageSpec::parse("curl").unwrap();
+        let mut manager = IndexManager::new(dir.path());
+        manager.load(None).await.unwrap();
+        assert_eq!(
+            manager.find_best_version_with_string(&spec).unwrap().0,
+            "8.1.0"
+        );
+
+        manager.load_pinned(&IndexPin::Generation(1)).await.unwrap();
+        assert_eq!(
+            manager.find_best_version_with_string(&spec).unwrap().0,
+            "8.0.0"
+        );
+
+        let pin = IndexPin::Hash(snapshots[1].hash.clone());
+        manager.load_pinned(&pin).await.unwrap();
+        assert_eq!(
+            manager.find_best_version_with_string(&spec).unwrap().0,
+            "8.1.0"
+        );
+
+        let err = manager
+            .load_pinned(&IndexPin::Generation(7))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Package(sps2_errors::PackageError::IndexSnapshotUnavailable { .. })
+        ));
+    }
+}
@@ -3,7 +3,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sps2_errors::{Error, PackageError};
-use sps2_types::Arch;
+use sps2_types::{Arch, Version};
 use std::collections::HashMap;
 
 /// Repository index
@@ -12,6 +12,20 @@ pub struct Index {
     #[serde(flatten)]
     pub metadata: IndexMetadata,
     pub packages: HashMap<String, PackageEntry>,
+    /// Entries hidden by an earlier repository when indexes were merged
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shadowed: Vec<ShadowedEntry>,
+}
+
+/// Version entry dropped by [`Index::merge`] in favour of an earlier repository
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShadowedEntry {
+    pub name: String,
+    pub version: String,
+    /// Position of the repository whose entry was dropped
+    pub source: usize,
+    /// Position of the repository whose entry was kept
+    pub kept_from: usize,
 }
 
 /// Index metadata
@@ -45,6 +59,12 @@ pub struct VersionEntry {
     pub homepage: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub license: Option<String>,
+    /// Position of the repository this entry came from in a merged index
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<usize>,
+    /// Paths the package provides, when the repository publishes them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
 }
 
 /// Dependency information
@@ -54,6 +74,15 @@ pub struct DependencyInfo {
     pub runtime: Vec<String>,
     #[serde(default)]
     pub build: Vec<String>,
+    /// Optional runtime dependencies that never pull in their own closure
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weak: Vec<String>,
+    /// Packages that can never be installed alongside this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<String>,
+    /// Packages this one supersedes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replaces: Vec<String>,
 }
 
 /// SBOM information
@@ -88,9 +117,82 @@ impl Index {
                 timestamp: Utc::now(),
             },
             packages: HashMap::new(),
+            shadowed: Vec::new(),
         }
     }
 
+    /// Combine the indexes of several repositories into one
+    ///
+    /// Repositories are given in order of precedence: when more than one
+    /// provides the same package version, the earliest one wins and the
+    /// others are recorded in [`Index::shadowed`]. Every merged entry keeps
+    /// its own download and signature URLs and records the position of its
+    /// repository in [`VersionEntry::source`].
+    ///
+    /// The merged metadata takes the newest format version, the highest
+    /// minimum client version and the oldest timestamp, so freshness checks
+    /// reflect the stalest repository.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any index fails validation or has an invalid
+    /// minimum client version.
+    pub fn merge(indexes: &[Index]) -> Result<Index, Error> {
+        let mut merged = Self::new();
+        let mut minimum_client: Option<Version> = None;
+
+        for (source, index) in indexes.iter().enumerate() {
+            index.validate()?;
+
+            let client = Version::parse(&index.metadata.minimum_client).map_err(|e| {
+                PackageError::InvalidFormat {
+                    message: format!(
+                        "invalid minimum client version {}: {e}",
+                        index.metadata.minimum_client
+                    ),
+                }
+            })?;
+            if minimum_client
+                .as_ref()
+                .is_none_or(|current| client > *current)
+            {
+                minimum_client = Some(client);
+            }
+            if source == 0 {
+                merged.metadata = index.metadata.clone();
+            } else {
+                merged.metadata.version = merged.metadata.version.max(index.metadata.version);
+                merged.metadata.timestamp = merged.metadata.timestamp.min(index.metadata.timestamp);
+            }
+
+            for (name, package) in &index.packages {
+                let versions = &mut merged.packages.entry(name.clone()).or_default().versions;
+                for (version, entry) in &package.versions {
+                    if let Some(kept) = versions.get(version) {
+                        merged.shadowed.push(ShadowedEntry {
+                            name: name.clone(),
+                            version: version.clone(),
+                            source,
+                            kept_from: kept.source.unwrap_or_default(),
+                        });
+                        continue;
+                    }
+                    let mut entry = entry.clone();
+                    entry.source = Some(source);
+                    versions.insert(version.clone(), entry);
+                }
+            }
+        }
+
+        if let Some(client) = minimum_client {
+            merged.metadata.minimum_client = client.to_string();
+        }
+        merged
+            .shadowed
+            .sort_by(|a, b| (&a.name, &a.version, a.source).cmp(&(&b.name, &b.version, b.source)));
+        Ok(merged)
+    }
+
     /// Parse index from JSON
     ///
     /// # Errors
@@ -107,16 +209,22 @@ impl Index {
 
     /// Serialize index to JSON
     ///
+    /// Object keys are sorted, so the same index always serializes to the
+    /// same bytes and can be identified by its hash.
+    ///
     /// # Errors
     ///
     /// Returns an error if the index cannot be serialized to JSON.
     pub fn to_json(&self) -> Result<String, Error> {
-        serde_json::to_string_pretty(self).map_err(|e| {
-            PackageError::InvalidFormat {
-                message: format!("failed to serialize index: {e}"),
-            }
-            .into()
-        })
+        // `Value` keeps its objects in sorted maps, unlike the `HashMap`s here
+        serde_json::to_value(self)
+            .and_then(|value| serde_json::to_string_pretty(&value))
+            .map_err(|e| {
+                PackageError::InvalidFormat {
+                    message: format!("failed to serialize index: {e}"),
+                }
+                .into()
+            })
     }
 
     /// Validate index format and version
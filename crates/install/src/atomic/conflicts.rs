@@ -0,0 +1,89 @@
+//! File conflict detection for packages installed in one transition
+//!
+//! Conflicts are computed from the per-file hashes recorded in the store, so
+//! the check runs before the staging directory is created or anything in the
+//! live prefix is touched.
+
+use sps2_errors::InstallError;
+use sps2_hash::FileHashResult;
+use sps2_resolver::PackageId;
+use sps2_store::is_installed_entry;
+use std::collections::BTreeMap;
+
+/// How to treat a path provided by more than one package
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileConflictPolicy {
+    /// Allow packages to provide the same path when the content is identical
+    #[default]
+    AllowIdentical,
+    /// Reject any path provided by more than one package
+    Strict,
+}
+
+/// Check the files of incoming packages for conflicting paths
+///
+/// Incoming packages are checked against each other and against the
+/// `installed` packages they are added alongside; overlaps among installed
+/// packages alone are left as they are. Directories never conflict.
+/// Conflicts are reported for the lexicographically first offending path so
+/// results are deterministic.
+///
+/// # Errors
+///
+/// Returns `InstallError::FileConflict` if two packages claim the same path
+/// and the policy does not allow it.
+pub(crate) fn check_file_conflicts<'a>(
+    incoming: impl IntoIterator<Item = (&'a PackageId, &'a [FileHashResult])>,
+    installed: impl IntoIterator<Item = (&'a PackageId, &'a [FileHashResult])>,
+    policy: FileConflictPolicy,
+) -> Result<(), InstallError> {
+    let mut claims: BTreeMap<&str, Vec<(&PackageId, &FileHashResult)>> = BTreeMap::new();
+
+    for (package_id, files) in incoming {
+        for file in files {
+            if file.is_directory || !is_installed_entry(&file.relative_path) {
+                continue;
+            }
+            claims
+                .entry(file.relative_path.as_str())
+                .or_default()
+                .push((package_id, file));
+        }
+    }
+
+    for (package_id, files) in installed {
+        for file in files {
+            if file.is_directory {
+                continue;
+            }
+            if let Some(owners) = claims.get_mut(file.relative_path.as_str()) {
+                owners.push((package_id, file));
+            }
+        }
+    }
+
+    for (path, owners) in claims {
+        if owners.len() < 2 {
+            continue;
+        }
+
+        let identical = owners.windows(2).all(|pair| {
+            pair[0].1.hash == pair[1].1.hash && pair[0].1.is_symlink == pair[1].1.is_symlink
+        });
+
+        if policy == FileConflictPolicy::AllowIdentical && identical {
+            continue;
+        }
+
+        let mut names: Vec<String> = owners.iter().map(|(id, _)| id.to_string()).collect();
+        names.sort();
+        names.dedup();
+
+        return Err(InstallError::FileConflict {
+            path: path.to_string(),
+            packages: names,
+        });
+    }
+
+    Ok(())
+}
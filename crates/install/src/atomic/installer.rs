@@ -1,6 +1,11 @@
 //! Atomic installer implementation using APFS optimizations
 
+use crate::atomic::conflicts::{check_file_conflicts, FileConflictPolicy};
 use crate::atomic::transition::StateTransition;
+use crate::common::{
+    ensure_not_cancelled, failed_install_dir, prune_failed_installs, KEPT_FAILED_INSTALLS,
+};
+use crate::explain::load_installed_graph;
 use std::sync::Arc;
 // Removed Python venv handling - Python packages are now handled like regular packages
 use crate::{InstallContext, InstallResult, PreparedPackage, StagingManager};
@@ -18,6 +23,7 @@ use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::path::{Path, PathBuf};
 use std::time::Instant;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 /// Implement EventEmitter for InstallContext
@@ -49,6 +55,10 @@ pub struct AtomicInstaller {
     live_path: PathBuf,
     /// Content-addressable package store
     store: PackageStore,
+    /// How to treat paths provided by more than one package
+    conflict_policy: FileConflictPolicy,
+    /// Keep the staging directory and packages of a failed install for debugging
+    keep_on_failure: bool,
 }
 
 impl AtomicInstaller {
@@ -146,6 +156,8 @@ impl AtomicInstaller {
             package_files: &transition.package_files,
             file_references: &transition.file_references,
             pending_file_hashes: &transition.pending_file_hashes,
+            requested_packages: &transition.requested_packages,
+            runtime_env: &transition.runtime_env,
         };
 
         let journal = match self
@@ -257,6 +269,16 @@ impl AtomicInstaller {
         let mut transition =
             StateTransition::new(&self.state_manager, operation.to_string()).await?;
 
+        // States from before requests were recorded have none; carry their
+        // leaves forward so earlier installs don't all become unneeded
+        if transition.requested_packages.is_empty() {
+            if let Some(parent_id) = transition.parent_id {
+                let (_, roots) =
+                    load_installed_graph(&self.state_manager, &self.store, &parent_id).await?;
+                transition.requested_packages = roots;
+            }
+        }
+
         // Set event sender on transition
         transition.event_sender = context.event_sender().cloned();
 
@@ -272,6 +294,27 @@ impl AtomicInstaller {
         Ok(transition)
     }
 
+    /// Abort an uncommitted transition if cancellation was requested
+    ///
+    /// The staging directory is discarded so the live prefix and active state
+    /// are left exactly as they were.
+    async fn abort_if_cancelled<T: EventEmitter>(
+        &self,
+        transition: &StateTransition,
+        context: &T,
+        token: Option<&CancellationToken>,
+    ) -> Result<(), Error> {
+        if let Err(e) = ensure_not_cancelled(context, token, &transition.operation) {
+            if let Err(cleanup_err) = transition.cleanup(&self.state_manager).await {
+                context.emit_warning(format!(
+                    "failed to remove staging directory after cancellation: {cleanup_err}"
+                ));
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
     /// Create new atomic installer
     ///
     /// # Errors
@@ -289,24 +332,261 @@ impl AtomicInstaller {
             state_manager,
             live_path,
             store,
+            conflict_policy: FileConflictPolicy::default(),
+            keep_on_failure: false,
         })
     }
 
+    /// Set the policy for paths provided by more than one package
+    #[must_use]
+    pub fn with_conflict_policy(mut self, policy: FileConflictPolicy) -> Self {
+        self.conflict_policy = policy;
+        self
+    }
+
+    /// Keep the artifacts of a failed install
+    ///
+    /// Instead of being removed, the staging directory and the packages being
+    /// installed are kept in `<state>/failed-installs/<staging id>`. Only the
+    /// most recent failed installs are kept.
+    #[must_use]
+    pub fn with_keep_on_failure(mut self, keep: bool) -> Self {
+        self.keep_on_failure = keep;
+        self
+    }
+
+    /// Discard or preserve the staging directory of a failed install
+    async fn handle_staging_failure<T: EventEmitter>(
+        &self,
+        transition: &StateTransition,
+        prepared_packages: Option<&HashMap<PackageId, PreparedPackage>>,
+        context: &T,
+    ) {
+        if !self.keep_on_failure {
+            if let Err(e) = transition.cleanup(&self.state_manager).await {
+                context.emit_warning(format!(
+                    "failed to remove staging directory after failed install: {e}"
+                ));
+            }
+            return;
+        }
+        self.keep_failure_artifacts(transition, prepared_packages, false, context)
+            .await;
+    }
+
+    /// Keep the artifacts of a failed install and prune older ones
+    ///
+    /// A failed commit can leave a journal that recovery completes from the
+    /// staging directory, so `copy_staging` keeps a copy instead of moving it.
+    async fn keep_failure_artifacts<T: EventEmitter>(
+        &self,
+        transition: &StateTransition,
+        prepared_packages: Option<&HashMap<PackageId, PreparedPackage>>,
+        copy_staging: bool,
+        context: &T,
+    ) {
+        let state_path = self.state_manager.state_path();
+        let debug_dir = failed_install_dir(state_path, transition.staging_id);
+        match self
+            .preserve_failure_artifacts(transition, prepared_packages, copy_staging, &debug_dir)
+            .await
+        {
+            Ok(()) => context.emit_warning(format!(
+                "install failed; artifacts kept for debugging in {}",
+                debug_dir.display()
+            )),
+            Err(e) => context.emit_warning(format!(
+                "install failed; could not keep artifacts in {}: {e}",
+                debug_dir.display()
+            )),
+        }
+        if let Err(e) = prune_failed_installs(state_path, KEPT_FAILED_INSTALLS).await {
+            context.emit_warning(format!("failed to prune old failed-install artifacts: {e}"));
+        }
+    }
+
+    /// Move or copy the staging directory and copy prepared packages into `debug_dir`
+    async fn preserve_failure_artifacts(
+        &self,
+        transition: &StateTransition,
+        prepared_packages: Option<&HashMap<PackageId, PreparedPackage>>,
+        copy_staging: bool,
+        debug_dir: &Path,
+    ) -> Result<(), Error> {
+        let (platform, ctx) = self.create_platform_context();
+        let fs_error = |operation: &str, path: &Path, e: sps2_errors::PlatformError| {
+            InstallError::FilesystemError {
+                operation: operation.to_string(),
+                path: path.display().to_string(),
+                message: e.to_string(),
+            }
+        };
+
+        let packages_dir = debug_dir.join("packages");
+        platform
+            .filesystem()
+            .create_dir_all(&ctx, &packages_dir)
+            .await
+            .map_err(|e| fs_error("create_dir_all", &packages_dir, e))?;
+
+        if platform
+            .filesystem()
+            .exists(&ctx, &transition.staging_path)
+            .await
+        {
+            let kept_staging = debug_dir.join("staging");
+            if copy_staging {
+                platform
+                    .filesystem()
+                    .clone_directory(&ctx, &transition.staging_path, &kept_staging)
+                    .await
+                    .map_err(|e| fs_error("clone_directory", &kept_staging, e))?;
+            } else {
+                platform
+                    .filesystem()
+                    .atomic_rename(&ctx, &transition.staging_path, &kept_staging)
+                    .await
+                    .map_err(|e| fs_error("atomic_rename", &kept_staging, e))?;
+            }
+        }
+
+        // Store packages may be garbage collected by a later run, so keep copies
+        for (package_id, prepared) in prepared_packages.into_iter().flatten() {
+            let dest = packages_dir.join(format!("{}-{}", package_id.name, package_id.version));
+            platform
+                .filesystem()
+                .clone_directory(&ctx, &prepared.store_path, &dest)
+                .await
+                .map_err(|e| fs_error("clone_directory", &dest, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Detect file conflicts between the packages being installed and the
+    /// packages already installed in the active state
+    ///
+    /// Installed packages being replaced by a new version are not compared.
+    /// Runs before the staging directory is created so a conflict leaves the
+    /// filesystem untouched. Packages without recorded file hashes are skipped.
+    async fn detect_file_conflicts(
+        &self,
+        prepared_packages: &HashMap<PackageId, PreparedPackage>,
+    ) -> Result<(), Error> {
+        fn file_lists(
+            packages: &[(PackageId, StoredPackage)],
+        ) -> impl Iterator<Item = (&PackageId, &[sps2_hash::FileHashResult])> {
+            packages
+                .iter()
+                .filter_map(|(id, stored)| stored.file_hashes().map(|files| (id, files)))
+        }
+
+        let mut stored_packages = Vec::with_capacity(prepared_packages.len());
+        for (package_id, prepared) in prepared_packages {
+            let stored = StoredPackage::load(&prepared.store_path).await?;
+            stored_packages.push((package_id.clone(), stored));
+        }
+
+        let replaced: HashSet<&str> = prepared_packages
+            .keys()
+            .map(|package_id| package_id.name.as_str())
+            .collect();
+        let mut installed_packages = Vec::new();
+        for package in self.state_manager.get_installed_packages().await? {
+            if replaced.contains(package.name.as_str()) {
+                continue;
+            }
+            let store_path = self.store.package_path(&package.hash());
+            let Ok(stored) = StoredPackage::load(&store_path).await else {
+                continue;
+            };
+            installed_packages.push((
+                PackageId::new(package.name.clone(), package.version()),
+                stored,
+            ));
+        }
+
+        check_file_conflicts(
+            file_lists(&stored_packages),
+            file_lists(&installed_packages),
+            self.conflict_policy,
+        )?;
+
+        Ok(())
+    }
+
     /// Perform atomic installation
     ///
+    /// Cancellation through the context's token is honoured until the two-phase
+    /// commit begins; after that the operation always runs to completion.
+    ///
     /// # Errors
     ///
     /// Returns an error if state transition fails, package installation fails,
-    /// or filesystem operations fail.
+    /// filesystem operations fail, or the operation is cancelled before commit.
     pub async fn install(
         &mut self,
         context: &InstallContext,
         resolved_packages: &HashMap<PackageId, ResolvedNode>,
         prepared_packages: Option<&HashMap<PackageId, PreparedPackage>>,
     ) -> Result<InstallResult, Error> {
+        let cancellation_token = context.cancellation_token.as_ref();
+        ensure_not_cancelled(context, cancellation_token, "install")?;
+
+        // Reject conflicting files before any filesystem mutation
+        if let Some(prepared) = prepared_packages {
+            self.detect_file_conflicts(prepared).await?;
+        }
+
         // Setup state transition and staging directory
         let mut transition = self.setup_state_transition("install", context).await?;
 
+        let mut result = InstallResult::new(transition.staging_id);
+        if let Err(e) = self
+            .stage_install(
+                &mut transition,
+                context,
+                resolved_packages,
+                prepared_packages,
+                &mut result,
+            )
+            .await
+        {
+            // Cancellation has already discarded the staging directory
+            if !matches!(e, Error::Cancelled) {
+                self.handle_staging_failure(&transition, prepared_packages, context)
+                    .await;
+            }
+            return Err(e);
+        }
+
+        // Last chance to cancel; once the commit starts the operation runs to completion
+        self.abort_if_cancelled(&transition, context, cancellation_token)
+            .await?;
+
+        // Execute two-phase commit
+        if let Err(e) = self.execute_two_phase_commit(&transition, context).await {
+            if self.keep_on_failure {
+                self.keep_failure_artifacts(&transition, prepared_packages, true, context)
+                    .await;
+            }
+            return Err(e);
+        }
+
+        Ok(result)
+    }
+
+    /// Carry forward untouched packages and link the new ones into staging
+    async fn stage_install(
+        &self,
+        transition: &mut StateTransition,
+        context: &InstallContext,
+        resolved_packages: &HashMap<PackageId, ResolvedNode>,
+        prepared_packages: Option<&HashMap<PackageId, PreparedPackage>>,
+        result: &mut InstallResult,
+    ) -> Result<(), Error> {
+        let cancellation_token = context.cancellation_token.as_ref();
+
         // Collect the current state's packages so we can carry forward untouched entries and
         // detect in-place upgrades cleanly.
         let parent_packages = if let Some(parent_id) = transition.parent_id {
@@ -328,29 +608,44 @@ impl AtomicInstaller {
             .keys()
             .map(|pkg| pkg.name.clone())
             .collect();
-        self.carry_forward_packages(&mut transition, &parent_packages, &exclude_names)
+        self.carry_forward_packages(transition, &parent_packages, &exclude_names)
             .await?;
 
         // Apply package changes to staging
-        let mut result = InstallResult::new(transition.staging_id);
-
         for (package_id, node) in resolved_packages {
+            self.abort_if_cancelled(transition, context, cancellation_token)
+                .await?;
+
             let prepared_package = prepared_packages.and_then(|packages| packages.get(package_id));
             self.install_package_to_staging(
-                &mut transition,
+                transition,
                 package_id,
                 node,
                 prepared_package,
                 parent_lookup.get(&package_id.name),
-                &mut result,
+                result,
             )
             .await?;
         }
 
-        // Execute two-phase commit
-        self.execute_two_phase_commit(&transition, context).await?;
+        // Packages new to this state that the user named become requested; updates of
+        // installed packages (which also arrive as install requests) keep their status
+        let requested: Vec<String> = context
+            .packages
+            .iter()
+            .map(|spec| &spec.name)
+            .chain(
+                resolved_packages
+                    .iter()
+                    .filter(|(_, node)| matches!(node.action, sps2_resolver::NodeAction::Local))
+                    .map(|(package_id, _)| &package_id.name),
+            )
+            .filter(|name| !parent_lookup.contains_key(*name))
+            .cloned()
+            .collect();
+        transition.add_requested(requested);
 
-        Ok(result)
+        Ok(())
     }
 
     /// Install a single package to staging directory
@@ -395,7 +690,17 @@ impl AtomicInstaller {
         }
 
         // Load package from the prepared store path
-        let _stored_package = StoredPackage::load(store_path).await?;
+        let stored_package = StoredPackage::load(store_path).await?;
+
+        // Record the variables the package exports while it is installed
+        let runtime_env = &stored_package.manifest().runtime_env;
+        if runtime_env.is_empty() {
+            transition.runtime_env.remove(&package_id.name);
+        } else {
+            transition
+                .runtime_env
+                .insert(package_id.name.clone(), runtime_env.clone());
+        }
 
         // Ensure store_refs entry exists before adding to package_map
         self.state_manager
@@ -515,6 +820,8 @@ impl AtomicInstaller {
         packages_to_remove: &[PackageId],
         context: &crate::UninstallContext,
     ) -> Result<InstallResult, Error> {
+        ensure_not_cancelled(context, context.cancellation_token.as_ref(), "uninstall")?;
+
         // Setup state transition and staging directory
         let mut transition = self.setup_state_transition("uninstall", context).await?;
 
@@ -561,6 +868,16 @@ impl AtomicInstaller {
             .collect();
         self.carry_forward_packages(&mut transition, &parent_packages, &exclude_names)
             .await?;
+        transition
+            .requested_packages
+            .retain(|name| !exclude_names.contains(name));
+        transition
+            .runtime_env
+            .retain(|name, _| !exclude_names.contains(name));
+
+        // Last chance to cancel; once the commit starts the operation runs to completion
+        self.abort_if_cancelled(&transition, context, context.cancellation_token.as_ref())
+            .await?;
 
         // Execute two-phase commit
         self.execute_two_phase_commit(&transition, context).await?;
@@ -810,6 +1127,7 @@ impl AtomicInstaller {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{add_package, install_inputs, install_packages, StoredPackage};
     use sps2_store::create_package;
     use sps2_types::{Arch, Manifest, Version};
     use std::collections::HashMap;
@@ -835,15 +1153,28 @@ mod tests {
         std::path::PathBuf,
         u64,
         Vec<sps2_hash::Hash>,
+    ) {
+        let v = Version::parse(version).unwrap();
+        let m = Manifest::new(name.to_string(), &v, 1, &Arch::Arm64);
+        make_sp_with_manifest(store, &m, files).await
+    }
+
+    async fn make_sp_with_manifest(
+        store: &sps2_store::PackageStore,
+        m: &Manifest,
+        files: &[(&str, &str)],
+    ) -> (
+        sps2_hash::Hash,
+        std::path::PathBuf,
+        u64,
+        Vec<sps2_hash::Hash>,
     ) {
         let td = TempDir::new().unwrap();
         let src = td.path().join("src");
         afs::create_dir_all(&src).await.unwrap();
         // manifest
-        let v = Version::parse(version).unwrap();
-        let m = Manifest::new(name.to_string(), &v, 1, &Arch::Arm64);
         let manifest_path = src.join("manifest.toml");
-        sps2_store::manifest_io::write_manifest(&manifest_path, &m)
+        sps2_store::manifest_io::write_manifest(&manifest_path, m)
             .await
             .unwrap();
         // files under opt/pm/live
@@ -964,6 +1295,7 @@ mod tests {
             local_files: vec![],
             force: false,
             event_sender: None,
+            cancellation_token: None,
         };
         let _ = ai.install(&ctx, &resolved, Some(&prepared)).await.unwrap();
 
@@ -1011,6 +1343,7 @@ mod tests {
             local_files: vec![],
             force: true,
             event_sender: None,
+            cancellation_token: None,
         };
         let update_result = ai
             .install(&update_ctx, &resolved_update, Some(&prepared_update))
@@ -1085,6 +1418,7 @@ mod tests {
             local_files: vec![],
             force: false,
             event_sender: None,
+            cancellation_token: None,
         };
         let _res = ai.install(&ctx, &resolved, Some(&prepared)).await.unwrap();
 
@@ -1100,6 +1434,7 @@ mod tests {
             autoremove: false,
             force: true,
             event_sender: None,
+            cancellation_token: None,
         };
         let _u = ai
             .uninstall(
@@ -1118,6 +1453,55 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn runtime_env_is_recorded_with_the_package() {
+        let (_td, state, store) = mk_env().await;
+        let v = Version::parse("0.22.0").unwrap();
+        let mut manifest = Manifest::new("gettext".to_string(), &v, 1, &Arch::Arm64);
+        manifest.runtime_env.insert(
+            "GETTEXTDATADIR".to_string(),
+            "/opt/pm/live/share/gettext".to_string(),
+        );
+        let (hash, store_path, size, _) =
+            make_sp_with_manifest(&store, &manifest, &[("share/gettext/its", "x")]).await;
+
+        let mut ai = AtomicInstaller::new(state.clone(), store.clone())
+            .await
+            .unwrap();
+        let pid = PackageId::new("gettext".to_string(), v.clone());
+        let resolved = HashMap::from([(
+            pid.clone(),
+            ResolvedNode::local("gettext".to_string(), v, store_path.clone(), vec![]),
+        )]);
+        let prepared = HashMap::from([(
+            pid,
+            crate::PreparedPackage {
+                hash,
+                size,
+                store_path,
+                is_local: true,
+            },
+        )]);
+        ai.install(&crate::InstallContext::new(), &resolved, Some(&prepared))
+            .await
+            .unwrap();
+
+        let active = state.get_active_state().await.unwrap();
+        let env = state.get_runtime_env(&active).await.unwrap();
+        assert_eq!(
+            env["gettext"]["GETTEXTDATADIR"],
+            "/opt/pm/live/share/gettext"
+        );
+
+        // The next state starts from the recorded variables
+        let transition = ai
+            .setup_state_transition("install", &crate::InstallContext::new())
+            .await
+            .unwrap();
+        assert_eq!(transition.runtime_env, env);
+        transition.cleanup(&state).await.unwrap();
+    }
+
     #[tokio::test]
     async fn shared_file_uninstall_decrements_but_not_zero() {
         let (_td, state, store) = mk_env().await;
@@ -1199,6 +1583,7 @@ mod tests {
             local_files: vec![],
             force: false,
             event_sender: None,
+            cancellation_token: None,
         };
         let _res = ai.install(&ctx, &resolved, Some(&prepared)).await.unwrap();
 
@@ -1208,10 +1593,320 @@ mod tests {
             autoremove: false,
             force: true,
             event_sender: None,
+            cancellation_token: None,
         };
         let _u = ai.uninstall(&[pid_a.clone()], &uctx).await.unwrap();
 
         // Shared file remains referenced by B
         assert!(refcount_file(&state, &h_same.to_hex()).await > 0);
     }
+
+    /// Store `A` and `B`, both shipping `bin/foo`
+    async fn conflicting_pair(
+        store: &sps2_store::PackageStore,
+        content_a: &str,
+        content_b: &str,
+    ) -> [StoredPackage; 2] {
+        [
+            add_package(
+                store,
+                "A",
+                "1.0.0",
+                None,
+                &[("bin/foo", content_a.as_bytes())],
+            )
+            .await,
+            add_package(
+                store,
+                "B",
+                "1.0.0",
+                None,
+                &[("bin/foo", content_b.as_bytes())],
+            )
+            .await,
+        ]
+    }
+
+    #[tokio::test]
+    async fn install_rejects_conflicting_files_before_staging() {
+        let (_td, state, store) = mk_env().await;
+        let (resolved, prepared) =
+            install_inputs(&conflicting_pair(&store, "from A", "from B").await);
+
+        let mut ai = AtomicInstaller::new(state.clone(), store.clone())
+            .await
+            .unwrap();
+        let err = ai
+            .install(&crate::InstallContext::new(), &resolved, Some(&prepared))
+            .await
+            .unwrap_err();
+
+        match err {
+            Error::Install(InstallError::FileConflict { path, packages }) => {
+                assert!(path.ends_with("bin/foo"), "unexpected path {path}");
+                assert_eq!(packages, vec!["A-1.0.0".to_string(), "B-1.0.0".to_string()]);
+            }
+            other => panic!("expected file conflict, got {other:?}"),
+        }
+
+        // Nothing was staged or swapped into place
+        assert!(!state.live_path().exists());
+    }
+
+    #[tokio::test]
+    async fn identical_files_conflict_only_under_strict_policy() {
+        let (_td, state, store) = mk_env().await;
+        let (resolved, prepared) = install_inputs(&conflicting_pair(&store, "same", "same").await);
+
+        let mut strict = AtomicInstaller::new(state.clone(), store.clone())
+            .await
+            .unwrap()
+            .with_conflict_policy(FileConflictPolicy::Strict);
+        let err = strict
+            .install(&crate::InstallContext::new(), &resolved, Some(&prepared))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Install(InstallError::FileConflict { .. })
+        ));
+
+        let lenient = AtomicInstaller::new(state.clone(), store.clone())
+            .await
+            .unwrap();
+        assert!(lenient.detect_file_conflicts(&prepared).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn install_rejects_files_owned_by_installed_packages() {
+        let (_td, state, store) = mk_env().await;
+        let [a, b] = conflicting_pair(&store, "from A", "from B").await;
+        install_packages(&state, &store, &[a]).await;
+
+        let mut ai = AtomicInstaller::new(state.clone(), store.clone())
+            .await
+            .unwrap();
+        let (resolved_b, prepared_b) = install_inputs(&[b]);
+        let err = ai
+            .install(
+                &crate::InstallContext::new(),
+                &resolved_b,
+                Some(&prepared_b),
+            )
+            .await
+            .unwrap_err();
+        match err {
+            Error::Install(InstallError::FileConflict { path, packages }) => {
+                assert!(path.ends_with("bin/foo"), "unexpected path {path}");
+                assert_eq!(packages, vec!["A-1.0.0".to_string(), "B-1.0.0".to_string()]);
+            }
+            other => panic!("expected file conflict, got {other:?}"),
+        }
+
+        // A new version of an installed package replaces its files
+        let a2 = add_package(&store, "A", "2.0.0", None, &[("bin/foo", b"from A2")]).await;
+        let (_, update) = install_inputs(&[a2]);
+        assert!(ai.detect_file_conflicts(&update).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn states_without_request_records_keep_their_leaves_requested() {
+        let (_td, state, store) = mk_env().await;
+        let a = add_package(&store, "A", "1.0.0", None, &[("A", b"A")]).await;
+        let b = add_package(&store, "B", "1.0.0", None, &[("B", b"B")]).await;
+        install_packages(&state, &store, &[a, b]).await;
+
+        // Drop the request records, as for a state from before migration 0011
+        let mut tx = state.begin_transaction().await.unwrap();
+        sqlx::query("DELETE FROM requested_packages")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let ai = AtomicInstaller::new(state.clone(), store.clone())
+            .await
+            .unwrap();
+        let transition = ai
+            .setup_state_transition("install", &crate::InstallContext::new())
+            .await
+            .unwrap();
+        assert_eq!(transition.requested_packages, ["A", "B"]);
+        transition.cleanup(&state).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn cancelled_install_leaves_live_state_untouched() {
+        let (_td, state, store) = mk_env().await;
+        let (resolved, prepared) = install_inputs(&conflicting_pair(&store, "same", "same").await);
+        let before = state.get_active_state().await.unwrap();
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let ctx = crate::InstallContext::new().with_cancellation_token(token);
+
+        let mut ai = AtomicInstaller::new(state.clone(), store.clone())
+            .await
+            .unwrap();
+        let err = ai
+            .install(&ctx, &resolved, Some(&prepared))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, Error::Cancelled));
+        assert_eq!(state.get_active_state().await.unwrap(), before);
+        assert!(!state.live_path().exists());
+    }
+
+    /// Resolve `A` (prepared) and `B` (never prepared) so staging fails part way
+    async fn failing_install_inputs(
+        store: &sps2_store::PackageStore,
+    ) -> (
+        HashMap<PackageId, ResolvedNode>,
+        HashMap<PackageId, crate::PreparedPackage>,
+    ) {
+        let a = add_package(store, "A", "1.0.0", None, &[("share/a.txt", b"a")]).await;
+        let (mut resolved, prepared) = install_inputs(std::slice::from_ref(&a));
+        let version = Version::parse("1.0.0").unwrap();
+        resolved.insert(
+            PackageId::new("B".to_string(), version.clone()),
+            ResolvedNode::local("B".to_string(), version, a.store_path, vec![]),
+        );
+        (resolved, prepared)
+    }
+
+    fn staging_dirs(state: &StateManager) -> Vec<std::path::PathBuf> {
+        std::fs::read_dir(state.state_path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with("staging-"))
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn failed_install_removes_staging_by_default() {
+        let (_td, state, store) = mk_env().await;
+        let (resolved, prepared) = failing_install_inputs(&store).await;
+
+        let mut ai = AtomicInstaller::new(state.clone(), store.clone())
+            .await
+            .unwrap();
+        ai.install(&crate::InstallContext::new(), &resolved, Some(&prepared))
+            .await
+            .unwrap_err();
+
+        assert!(staging_dirs(&state).is_empty());
+        assert!(!state
+            .state_path()
+            .join(crate::common::FAILED_INSTALLS_DIR)
+            .exists());
+    }
+
+    #[tokio::test]
+    async fn failed_install_keeps_artifacts_when_requested() {
+        let (_td, state, store) = mk_env().await;
+        let (resolved, prepared) = failing_install_inputs(&store).await;
+        let before = state.get_active_state().await.unwrap();
+
+        let mut ai = AtomicInstaller::new(state.clone(), store.clone())
+            .await
+            .unwrap()
+            .with_keep_on_failure(true);
+        ai.install(&crate::InstallContext::new(), &resolved, Some(&prepared))
+            .await
+            .unwrap_err();
+
+        // Artifacts moved out of the staging namespace so the next run ignores them
+        assert!(staging_dirs(&state).is_empty());
+        let kept: Vec<_> =
+            std::fs::read_dir(state.state_path().join(crate::common::FAILED_INSTALLS_DIR))
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .collect();
+        assert_eq!(kept.len(), 1);
+        assert!(kept[0].join("staging").is_dir());
+        assert!(kept[0].join("packages/A-1.0.0/manifest.toml").is_file());
+        assert_eq!(state.get_active_state().await.unwrap(), before);
+        assert!(!state.live_path().exists());
+
+        // A later install still succeeds
+        let pid = PackageId::new("A".to_string(), Version::parse("1.0.0").unwrap());
+        let resolved_ok: HashMap<_, _> =
+            resolved.into_iter().filter(|(id, _)| *id == pid).collect();
+        ai.install(&crate::InstallContext::new(), &resolved_ok, Some(&prepared))
+            .await
+            .unwrap();
+        assert!(collect_relative_files(state.live_path())
+            .iter()
+            .any(|p| p.ends_with("share/a.txt")));
+    }
+
+    #[tokio::test]
+    async fn failed_commit_keeps_a_copy_of_staging() {
+        let (td, _, store) = mk_env().await;
+        // A regular file where the live prefix's parent should be fails the swap
+        std::fs::write(td.path().join("blocker"), "").unwrap();
+        let pool = sps2_state::create_pool(&td.path().join("state.sqlite"))
+            .await
+            .unwrap();
+        let state = StateManager::with_pool(
+            pool,
+            td.path().join("states"),
+            td.path().join("blocker/live"),
+            sps2_events::channel().0,
+        );
+        let (mut resolved, mut prepared) = failing_install_inputs(&store).await;
+        let pid = PackageId::new("A".to_string(), Version::parse("1.0.0").unwrap());
+        resolved.retain(|id, _| *id == pid);
+        prepared.retain(|id, _| *id == pid);
+
+        let mut ai = AtomicInstaller::new(state.clone(), store.clone())
+            .await
+            .unwrap()
+            .with_keep_on_failure(true);
+        ai.install(&crate::InstallContext::new(), &resolved, Some(&prepared))
+            .await
+            .unwrap_err();
+
+        // Staging stays in place for journal recovery; the kept copy is separate
+        assert_eq!(staging_dirs(&state).len(), 1);
+        let kept: Vec<_> =
+            std::fs::read_dir(state.state_path().join(crate::common::FAILED_INSTALLS_DIR))
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .collect();
+        assert_eq!(kept.len(), 1);
+        assert!(collect_relative_files(&kept[0].join("staging"))
+            .iter()
+            .any(|p| p.ends_with("share/a.txt")));
+        assert!(kept[0].join("packages/A-1.0.0/manifest.toml").is_file());
+    }
+
+    #[tokio::test]
+    async fn only_recent_failed_installs_are_kept() {
+        let td = TempDir::new().unwrap();
+        let root = td.path().join(crate::common::FAILED_INSTALLS_DIR);
+        for i in 0..4 {
+            std::fs::create_dir_all(root.join(format!("run-{i}/staging"))).unwrap();
+            let modified = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(i);
+            std::fs::File::open(root.join(format!("run-{i}")))
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+
+        crate::common::prune_failed_installs(td.path(), 2)
+            .await
+            .unwrap();
+
+        let mut left: Vec<_> = std::fs::read_dir(&root)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["run-2", "run-3"]);
+    }
 }
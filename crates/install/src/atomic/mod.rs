@@ -6,9 +6,11 @@
 //! - State transitions with rollback support
 //! - Platform-specific filesystem optimizations
 
+pub mod conflicts;
 pub mod installer;
 pub mod transition;
 
 // Re-export main public API
+pub use conflicts::FileConflictPolicy;
 pub use installer::AtomicInstaller;
 pub use transition::StateTransition;
@@ -4,6 +4,7 @@ use sps2_events::EventSender;
 use sps2_hash::FileHashResult;
 use sps2_platform::{core::PlatformContext, PlatformManager};
 use sps2_state::{FileReference, PackageRef, StateManager};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use uuid::Uuid;
 
@@ -32,6 +33,15 @@ pub struct StateTransition {
     pub event_sender: Option<EventSender>,
     /// Operation type (install, uninstall, etc.)
     pub operation: String,
+    /// Names of the packages explicitly requested in the new state
+    ///
+    /// Starts as the parent state's requests; operations add or drop names.
+    pub requested_packages: Vec<String>,
+    /// Runtime environment variables declared by the new state's packages
+    ///
+    /// Starts as the parent state's variables; installs replace a package's
+    /// entry and removals drop it.
+    pub runtime_env: BTreeMap<String, BTreeMap<String, String>>,
 }
 
 impl StateTransition {
@@ -49,6 +59,8 @@ impl StateTransition {
         let staging_path = state_manager
             .state_path()
             .join(format!("staging-{staging_id}"));
+        let requested_packages = state_manager.get_requested_packages(&parent_id).await?;
+        let runtime_env = state_manager.get_runtime_env(&parent_id).await?;
 
         Ok(Self {
             staging_id,
@@ -60,9 +72,18 @@ impl StateTransition {
             pending_file_hashes: Vec::new(),
             event_sender: None,
             operation,
+            requested_packages,
+            runtime_env,
         })
     }
 
+    /// Mark packages as explicitly requested in the new state
+    pub fn add_requested(&mut self, names: impl IntoIterator<Item = String>) {
+        self.requested_packages.extend(names);
+        self.requested_packages.sort();
+        self.requested_packages.dedup();
+    }
+
     /// Create a platform context for filesystem operations
     fn create_platform_context(&self) -> (&'static sps2_platform::Platform, PlatformContext) {
         let platform = PlatformManager::instance().platform();
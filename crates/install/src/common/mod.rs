@@ -27,6 +27,7 @@ macro_rules! context_builder {
                     Self {
                         $($field: Default::default(),)*
                         event_sender: None,
+                        cancellation_token: None,
                     }
                 }
 
@@ -42,6 +43,16 @@ macro_rules! context_builder {
                     self.event_sender = Some(sender);
                     self
                 }
+
+                /// Set the token used to request graceful cancellation
+                #[must_use]
+                pub fn with_cancellation_token(
+                    mut self,
+                    token: tokio_util::sync::CancellationToken,
+                ) -> Self {
+                    self.cancellation_token = Some(token);
+                    self
+                }
             }
 
             impl Default for $name {
@@ -52,3 +63,67 @@ macro_rules! context_builder {
         }
     };
 }
+
+/// Abort with `Error::Cancelled` if cancellation has been requested
+///
+/// Emits an `OperationCancelled` event for `operation` when aborting.
+pub(crate) fn ensure_not_cancelled<E: sps2_events::EventEmitter>(
+    emitter: &E,
+    token: Option<&tokio_util::sync::CancellationToken>,
+    operation: &str,
+) -> Result<(), sps2_errors::Error> {
+    if token.is_some_and(tokio_util::sync::CancellationToken::is_cancelled) {
+        emitter.emit_operation_cancelled(operation);
+        return Err(sps2_errors::Error::Cancelled);
+    }
+    Ok(())
+}
+
+/// Directory under the state path that holds artifacts kept from failed installs
+pub(crate) const FAILED_INSTALLS_DIR: &str = "failed-installs";
+
+/// Debug location for the artifacts of one failed install
+///
+/// Lives beside, not inside, the staging area so nothing kept here is picked
+/// up by staging recovery or reused by a later run.
+pub(crate) fn failed_install_dir(
+    state_path: &std::path::Path,
+    id: uuid::Uuid,
+) -> std::path::PathBuf {
+    state_path.join(FAILED_INSTALLS_DIR).join(id.to_string())
+}
+
+/// Number of failed installs whose artifacts are kept at a time
+pub(crate) const KEPT_FAILED_INSTALLS: usize = 5;
+
+/// Remove all but the `keep` most recently modified failed-install directories
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be listed or an entry cannot be removed.
+pub(crate) async fn prune_failed_installs(
+    state_path: &std::path::Path,
+    keep: usize,
+) -> Result<(), sps2_errors::Error> {
+    let root = state_path.join(FAILED_INSTALLS_DIR);
+    let mut entries = match tokio::fs::read_dir(&root).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut kept = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let modified = entry
+            .metadata()
+            .await?
+            .modified()
+            .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
+        kept.push((modified, entry.path()));
+    }
+    kept.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in kept.into_iter().skip(keep) {
+        tokio::fs::remove_dir_all(&path).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,254 @@
+//! Explain why installed packages are present
+//!
+//! The installed packages of a state form a graph whose edges come from each
+//! package's manifest dependencies. Walking it backwards from a package to
+//! the packages the user requested yields the chains that keep it installed.
+
+use sps2_errors::Error;
+use sps2_resolver::PackageId;
+use sps2_state::StateManager;
+use sps2_store::PackageStore;
+use sps2_types::PackageSpec;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Upper bound on reported paths, so densely connected graphs stay cheap
+const MAX_PATHS: usize = 32;
+
+/// Dependency edges between the packages installed in one state
+#[derive(Debug, Default)]
+pub(crate) struct InstalledGraph {
+    /// Installed packages by name
+    packages: HashMap<String, PackageId>,
+    /// Names of installed packages depending on each package
+    dependents: HashMap<String, Vec<String>>,
+}
+
+impl InstalledGraph {
+    /// Build the graph from installed packages and their dependency names
+    ///
+    /// Dependencies that are not installed (e.g. skipped weak dependencies)
+    /// are ignored.
+    pub(crate) fn new(packages: &[(PackageId, Vec<String>)]) -> Self {
+        let installed: HashMap<String, PackageId> = packages
+            .iter()
+            .map(|(id, _)| (id.name.clone(), id.clone()))
+            .collect();
+
+        let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
+        for (id, deps) in packages {
+            for dep in deps {
+                if installed.contains_key(dep) && dep != &id.name {
+                    dependents
+                        .entry(dep.clone())
+                        .or_default()
+                        .push(id.name.clone());
+                }
+            }
+        }
+        for names in dependents.values_mut() {
+            names.sort();
+            names.dedup();
+        }
+
+        Self {
+            packages: installed,
+            dependents,
+        }
+    }
+
+    /// Whether `name` is installed
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.packages.contains_key(name)
+    }
+
+    /// Installed packages in name order
+    pub(crate) fn packages(&self) -> Vec<&PackageId> {
+        let mut packages: Vec<_> = self.packages.values().collect();
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+        packages
+    }
+
+    /// Packages nothing else depends on, used as roots for states that
+    /// predate request tracking
+    pub(crate) fn leaves(&self) -> Vec<String> {
+        let mut leaves: Vec<String> = self
+            .packages
+            .keys()
+            .filter(|name| !self.dependents.contains_key(*name))
+            .cloned()
+            .collect();
+        leaves.sort();
+        leaves
+    }
+
+    /// Dependency paths from `target` up to each requested root
+    ///
+    /// Each path starts with `target` and ends with a requested package, so
+    /// `[foo, bar, baz]` reads "foo is needed by bar, which is needed by baz".
+    /// A requested package yields a path containing only itself. An empty
+    /// result means nothing requested needs `target` any more.
+    pub(crate) fn paths_to_roots(&self, target: &str, roots: &[String]) -> Vec<Vec<PackageId>> {
+        let roots: HashSet<&str> = roots.iter().map(String::as_str).collect();
+        let mut paths = Vec::new();
+        let mut current = vec![target.to_string()];
+        self.walk(&roots, &mut current, &mut paths);
+
+        paths.sort_by(|a: &Vec<String>, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        paths
+            .into_iter()
+            .map(|path| {
+                path.iter()
+                    .filter_map(|name| self.packages.get(name).cloned())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn walk(&self, roots: &HashSet<&str>, current: &mut Vec<String>, paths: &mut Vec<Vec<String>>) {
+        if paths.len() >= MAX_PATHS {
+            return;
+        }
+        let Some(last) = current.last() else {
+            return;
+        };
+        if roots.contains(last.as_str()) {
+            paths.push(current.clone());
+            return;
+        }
+
+        let Some(dependents) = self.dependents.get(last) else {
+            return;
+        };
+        for dependent in dependents.clone() {
+            // Dependency cycles must not loop forever
+            if current.contains(&dependent) {
+                continue;
+            }
+            current.push(dependent);
+            self.walk(roots, current, paths);
+            current.pop();
+        }
+    }
+}
+
+/// Dependency graph of the packages installed in `state_id` and the names
+/// it treats as requested
+///
+/// States created before requests were recorded have none; their leaves
+/// stand in for them.
+///
+/// # Errors
+///
+/// Returns an error if the state or package manifests cannot be read.
+pub(crate) async fn load_installed_graph(
+    state_manager: &StateManager,
+    store: &PackageStore,
+    state_id: &Uuid,
+) -> Result<(InstalledGraph, Vec<String>), Error> {
+    let packages = state_manager
+        .get_installed_packages_in_state(state_id)
+        .await?;
+
+    let mut nodes = Vec::with_capacity(packages.len());
+    for package in &packages {
+        let hash = sps2_hash::Hash::from_hex(&package.hash)?;
+        let manifest_path = store.package_path(&hash).join("manifest.toml");
+        let manifest = sps2_store::manifest_io::read_manifest(&manifest_path).await?;
+        let deps = manifest
+            .dependencies
+            .runtime
+            .iter()
+            .chain(&manifest.dependencies.weak)
+            .filter_map(|dep| PackageSpec::parse(dep).ok())
+            .map(|spec| spec.name)
+            .collect();
+        nodes.push((
+            PackageId::new(package.name.clone(), package.version()),
+            deps,
+        ));
+    }
+    let graph = InstalledGraph::new(&nodes);
+
+    let mut roots = state_manager.get_requested_packages(state_id).await?;
+    if roots.is_empty() {
+        roots = graph.leaves();
+    }
+    Ok((graph, roots))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sps2_types::Version;
+
+    fn pkg(name: &str, deps: &[&str]) -> (PackageId, Vec<String>) {
+        (
+            PackageId::new(name.to_string(), Version::new(1, 0, 0)),
+            deps.iter().map(ToString::to_string).collect(),
+        )
+    }
+
+    fn names(paths: &[Vec<PackageId>]) -> Vec<Vec<&str>> {
+        paths
+            .iter()
+            .map(|path| path.iter().map(|id| id.name.as_str()).collect())
+            .collect()
+    }
+
+    /// baz -> bar -> foo, app -> foo and libc, cycle between a and b, lonely orphan
+    fn graph() -> InstalledGraph {
+        InstalledGraph::new(&[
+            pkg("foo", &["libc"]),
+            pkg("bar", &["foo"]),
+            pkg("baz", &["bar", "not-installed"]),
+            pkg("app", &["foo", "libc"]),
+            pkg("libc", &[]),
+            pkg("a", &["b"]),
+            pkg("b", &["a"]),
+            pkg("orphan", &[]),
+        ])
+    }
+
+    fn requested() -> Vec<String> {
+        vec!["baz".to_string(), "app".to_string()]
+    }
+
+    #[test]
+    fn reason_chain_leads_from_package_to_requested_roots() {
+        let graph = graph();
+        assert_eq!(
+            names(&graph.paths_to_roots("foo", &requested())),
+            [vec!["foo", "app"], vec!["foo", "bar", "baz"]]
+        );
+        assert_eq!(
+            names(&graph.paths_to_roots("libc", &requested())),
+            [
+                vec!["libc", "app"],
+                vec!["libc", "foo", "app"],
+                vec!["libc", "foo", "bar", "baz"],
+            ]
+        );
+    }
+
+    #[test]
+    fn requested_package_explains_itself() {
+        assert_eq!(
+            names(&graph().paths_to_roots("baz", &requested())),
+            [vec!["baz"]]
+        );
+    }
+
+    #[test]
+    fn unneeded_packages_have_no_paths() {
+        let graph = graph();
+        assert!(graph.paths_to_roots("orphan", &requested()).is_empty());
+        // A dependency cycle does not keep itself installed
+        assert!(graph.paths_to_roots("a", &requested()).is_empty());
+    }
+
+    #[test]
+    fn leaves_stand_in_for_missing_request_records() {
+        assert_eq!(graph().leaves(), ["app", "baz", "orphan"]);
+    }
+}
@@ -1,12 +1,13 @@
 //! Main installer implementation
 
+use crate::explain::{load_installed_graph, InstalledGraph};
 use crate::{
     InstallContext, InstallOperation, InstallResult, UninstallContext, UninstallOperation,
     UpdateContext, UpdateOperation,
 };
 use sps2_errors::{Error, InstallError};
 // EventSender not used directly in this module but imported for potential future use
-use sps2_resolver::Resolver;
+use sps2_resolver::{PackageId, Resolver};
 use sps2_state::StateManager;
 use sps2_store::PackageStore;
 use uuid::Uuid;
@@ -22,6 +23,10 @@ pub struct InstallConfig {
     pub enable_apfs: bool,
     /// State retention policy (number of states to keep)
     pub state_retention: usize,
+    /// Keep downloaded packages and staging of a failed install for debugging
+    pub keep_on_failure: bool,
+    /// Most packages per execution batch; `None` keeps each layer whole
+    pub max_batch_size: Option<usize>,
 }
 
 impl Default for InstallConfig {
@@ -31,6 +36,8 @@ impl Default for InstallConfig {
             download_timeout: 300, // 5 minutes
             enable_apfs: cfg!(target_os = "macos"),
             state_retention: 10,
+            keep_on_failure: false,
+            max_batch_size: None,
         }
     }
 }
@@ -63,6 +70,20 @@ impl InstallConfig {
         self.state_retention = count;
         self
     }
+
+    /// Keep failed-install artifacts under `<state>/failed-installs`
+    #[must_use]
+    pub fn with_keep_on_failure(mut self, keep: bool) -> Self {
+        self.keep_on_failure = keep;
+        self
+    }
+
+    /// Cap the number of packages per execution batch
+    #[must_use]
+    pub fn with_max_batch_size(mut self, max_batch_size: Option<usize>) -> Self {
+        self.max_batch_size = max_batch_size;
+        self
+    }
 }
 
 /// Main installer for sps2 packages
@@ -117,7 +138,9 @@ impl Installer {
             self.resolver.clone(),
             self.state_manager.clone(),
             self.store.clone(),
-        )?;
+        )?
+        .with_keep_on_failure(self.config.keep_on_failure)
+        .with_max_batch_size(self.config.max_batch_size);
 
         // Execute installation
         let result = operation.execute(context).await?;
@@ -163,7 +186,9 @@ impl Installer {
             self.resolver.clone(),
             self.state_manager.clone(),
             self.store.clone(),
-        )?;
+        )?
+        .with_keep_on_failure(self.config.keep_on_failure)
+        .with_max_batch_size(self.config.max_batch_size);
 
         // Execute update
         let result = operation.execute(context).await?;
@@ -231,6 +256,54 @@ impl Installer {
             })
     }
 
+    /// Explain why a package is installed
+    ///
+    /// Returns the dependency paths from `name` to the packages the user
+    /// explicitly requested in the active state, each starting with `name`
+    /// and ending with a requested package: `[foo, bar, baz]` means foo is
+    /// needed by bar, which is needed by the requested baz. A requested package
+    /// yields a path with only itself. An empty result flags the package as
+    /// no longer needed by anything requested.
+    ///
+    /// States recorded before requests were tracked treat packages nothing
+    /// else depends on as requested.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the package is not installed or the state or
+    /// package manifests cannot be read.
+    pub async fn why(&self, name: &str) -> Result<Vec<Vec<PackageId>>, Error> {
+        let (graph, roots) = self.installed_graph().await?;
+        if !graph.contains(name) {
+            return Err(InstallError::PackageNotInstalled {
+                package: name.to_string(),
+            }
+            .into());
+        }
+        Ok(graph.paths_to_roots(name, &roots))
+    }
+
+    /// Installed packages that nothing requested depends on any more
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state or package manifests cannot be read.
+    pub async fn unneeded_packages(&self) -> Result<Vec<PackageId>, Error> {
+        let (graph, roots) = self.installed_graph().await?;
+        Ok(graph
+            .packages()
+            .into_iter()
+            .filter(|id| graph.paths_to_roots(&id.name, &roots).is_empty())
+            .cloned()
+            .collect())
+    }
+
+    /// Dependency graph of the active state and its requested packages
+    async fn installed_graph(&self) -> Result<(InstalledGraph, Vec<String>), Error> {
+        let state_id = self.state_manager.get_active_state().await?;
+        load_installed_graph(&self.state_manager, &self.store, &state_id).await
+    }
+
     /// Cleanup old states according to retention policy
     async fn cleanup_old_states(&self) -> Result<(), Error> {
         self.state_manager
@@ -425,6 +498,7 @@ mod tests {
             local_files: vec![],
             force: false,
             event_sender: None,
+            cancellation_token: None,
         };
 
         let mut atomic = AtomicInstaller::new(state.clone(), store.clone())
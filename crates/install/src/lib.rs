@@ -46,6 +46,7 @@
 
 mod atomic;
 mod common;
+mod explain;
 mod installer;
 mod operations;
 mod parallel;
@@ -53,7 +54,10 @@ mod pipeline;
 mod staging;
 pub mod validation;
 
-pub use atomic::{AtomicInstaller, StateTransition};
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+
+pub use atomic::{AtomicInstaller, FileConflictPolicy, StateTransition};
 pub use installer::{InstallConfig, Installer};
 pub use operations::{InstallOperation, UninstallOperation, UpdateOperation};
 pub use parallel::SecurityPolicy;
@@ -78,6 +82,8 @@ use uuid::Uuid;
 
 // Re-export EventSender for use by macros
 pub use sps2_events::EventSender;
+// Re-export CancellationToken so callers don't need a direct tokio-util dependency
+pub use tokio_util::sync::CancellationToken;
 
 // PreparedPackage will be exported by the pub struct declaration below
 
@@ -93,6 +99,8 @@ pub struct InstallContext {
 
     /// Event sender for progress reporting
     pub event_sender: Option<EventSender>,
+    /// Token to request graceful cancellation before the atomic commit
+    pub cancellation_token: Option<CancellationToken>,
 }
 
 context_builder! {
@@ -164,6 +172,8 @@ pub struct UninstallContext {
 
     /// Event sender for progress reporting
     pub event_sender: Option<EventSender>,
+    /// Token to request graceful cancellation before the atomic commit
+    pub cancellation_token: Option<CancellationToken>,
 }
 
 context_builder! {
@@ -186,6 +196,8 @@ pub struct UpdateContext {
 
     /// Event sender for progress reporting
     pub event_sender: Option<EventSender>,
+    /// Token to request graceful cancellation before the atomic commit
+    pub cancellation_token: Option<CancellationToken>,
 }
 
 context_builder! {
@@ -1,5 +1,8 @@
 //! High-level installation operations
 
+use crate::common::{
+    ensure_not_cancelled, failed_install_dir, prune_failed_installs, KEPT_FAILED_INSTALLS,
+};
 use crate::parallel::SecurityPolicy;
 use crate::{
     AtomicInstaller, ExecutionContext, InstallContext, InstallResult, ParallelExecutor,
@@ -14,6 +17,7 @@ use sps2_state::StateManager;
 use sps2_store::PackageStore;
 use sps2_types::PackageSpec;
 use std::sync::Arc;
+use uuid::Uuid;
 
 /// Install operation
 pub struct InstallOperation {
@@ -25,6 +29,10 @@ pub struct InstallOperation {
     store: PackageStore,
     /// Parallel executor
     executor: ParallelExecutor,
+    /// Keep downloads and staging of a failed install for debugging
+    keep_on_failure: bool,
+    /// Most packages per execution batch
+    max_batch_size: Option<usize>,
 }
 
 impl InstallOperation {
@@ -47,9 +55,25 @@ impl InstallOperation {
             state_manager,
             store,
             executor,
+            keep_on_failure: false,
+            max_batch_size: None,
         })
     }
 
+    /// Keep failed-install artifacts under `<state>/failed-installs` instead of removing them
+    #[must_use]
+    pub fn with_keep_on_failure(mut self, keep: bool) -> Self {
+        self.keep_on_failure = keep;
+        self
+    }
+
+    /// Cap the number of packages per execution batch
+    #[must_use]
+    pub fn with_max_batch_size(mut self, max_batch_size: Option<usize>) -> Self {
+        self.max_batch_size = max_batch_size;
+        self
+    }
+
     /// Execute installation
     ///
     /// # Errors
@@ -62,13 +86,18 @@ impl InstallOperation {
         // Check for already installed packages (handled during atomic install)
 
         // Resolve dependencies
-        let resolution = self.resolve_dependencies(&context).await?;
+        let mut resolution = self.resolve_dependencies(&context).await?;
+        resolution.execution_plan = resolution
+            .execution_plan
+            .with_max_batch_size(self.max_batch_size);
 
         // Check for already installed packages after resolution
         self.check_already_installed_resolved(&resolution)?;
 
+        ensure_not_cancelled(&context, context.cancellation_token.as_ref(), "install")?;
+
         // Execute parallel downloads
-        let exec_context = ExecutionContext::new()
+        let mut exec_context = ExecutionContext::new()
             .with_event_sender(
                 context
                     .event_sender
@@ -79,6 +108,18 @@ impl InstallOperation {
                 verify_signatures: true, // default to verify in this path
                 allow_unsigned: false,
             });
+        if let Some(token) = &context.cancellation_token {
+            exec_context = exec_context.with_cancellation_token(token.clone());
+        }
+        if self.keep_on_failure {
+            if let Err(e) =
+                prune_failed_installs(self.state_manager.state_path(), KEPT_FAILED_INSTALLS).await
+            {
+                context.emit_warning(format!("failed to prune old failed-install artifacts: {e}"));
+            }
+            let debug_dir = failed_install_dir(self.state_manager.state_path(), Uuid::new_v4());
+            exec_context = exec_context.with_keep_failed_downloads(debug_dir.join("downloads"));
+        }
 
         // Debug: Check what packages we're trying to process
         context.emit_debug(format!(
@@ -101,7 +142,9 @@ impl InstallOperation {
 
         // Perform atomic installation
         let mut atomic_installer =
-            AtomicInstaller::new(self.state_manager.clone(), self.store.clone()).await?;
+            AtomicInstaller::new(self.state_manager.clone(), self.store.clone())
+                .await?
+                .with_keep_on_failure(self.keep_on_failure);
 
         let result = atomic_installer
             .install(&context, &resolution.nodes, Some(&prepared_packages))
@@ -297,6 +340,20 @@ impl UpdateOperation {
         })
     }
 
+    /// Keep failed-install artifacts under `<state>/failed-installs` instead of removing them
+    #[must_use]
+    pub fn with_keep_on_failure(mut self, keep: bool) -> Self {
+        self.install_operation = self.install_operation.with_keep_on_failure(keep);
+        self
+    }
+
+    /// Cap the number of packages per execution batch
+    #[must_use]
+    pub fn with_max_batch_size(mut self, max_batch_size: Option<usize>) -> Self {
+        self.install_operation = self.install_operation.with_max_batch_size(max_batch_size);
+        self
+    }
+
     /// Execute update
     ///
     /// # Errors
@@ -531,6 +588,9 @@ impl UpdateOperation {
         if let Some(sender) = &context.event_sender {
             install_context = install_context.with_event_sender(sender.clone());
         }
+        if let Some(token) = &context.cancellation_token {
+            install_context = install_context.with_cancellation_token(token.clone());
+        }
 
         // Execute installation (which handles updates)
         let result = self.install_operation.execute(install_context).await?;
@@ -11,17 +11,18 @@ use sps2_events::{
     AcquisitionEvent, AppEvent, EventEmitter, EventSender, FailureContext, GeneralEvent,
     InstallEvent,
 };
-use sps2_net::{PackageDownloadConfig, PackageDownloader};
+use sps2_net::{MirrorRanking, PackageDownloadConfig, PackageDownloader};
 use sps2_resolver::{ExecutionPlan, NodeAction, PackageId, ResolvedNode};
-use sps2_resources::ResourceManager;
+use sps2_resources::{ResourceManager, ResourcePermit};
 use sps2_state::StateManager;
 use sps2_store::PackageStore;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
-use tokio::sync::OwnedSemaphorePermit;
 use tokio::task::JoinHandle;
 use tokio::time::{timeout, Duration, Instant};
+use tokio_util::sync::CancellationToken;
 
 struct ProcessPackageArgs {
     package_id: PackageId,
@@ -31,7 +32,7 @@ struct ProcessPackageArgs {
     state_manager: StateManager,
     timeout_duration: Duration,
     prepared_packages: Arc<DashMap<PackageId, PreparedPackage>>,
-    permit: OwnedSemaphorePermit,
+    permit: ResourcePermit,
 }
 
 /// Parallel executor for package operations
@@ -194,8 +195,19 @@ impl ParallelExecutor {
                 no_progress_iterations = 0;
                 last_completed_count = current_completed;
             }
-            // Try to start new tasks from ready queue
-            while let Some(package_id) = ready_queue.pop() {
+            // On cancellation stop launching new work and let in-flight tasks reach a safe point
+            if context.is_cancelled() {
+                Self::drain_inflight(&inflight).await;
+                context.emit_operation_cancelled("package acquisition");
+                return Err(Error::Cancelled);
+            }
+
+            // Try to start new tasks from ready queue, up to the plan's cap
+            let max_in_flight = execution_plan.max_in_flight().unwrap_or(usize::MAX);
+            while inflight.len() < max_in_flight {
+                let Some(package_id) = ready_queue.pop() else {
+                    break;
+                };
                 context.emit(AppEvent::General(GeneralEvent::DebugLog {
                     message: format!(
                         "DEBUG: Popped package {}-{} from ready queue",
@@ -244,7 +256,14 @@ impl ParallelExecutor {
 
             // Wait for at least one task to complete
             if !inflight.is_empty() {
-                let completed_package = self.wait_for_completion(&inflight).await?;
+                let completed_package = match self.wait_for_completion(&inflight, context).await {
+                    Err(Error::Cancelled) => {
+                        Self::drain_inflight(&inflight).await;
+                        context.emit_operation_cancelled("package acquisition");
+                        return Err(Error::Cancelled);
+                    }
+                    result => result?,
+                };
 
                 // Notify execution plan and get newly ready packages
                 let newly_ready = execution_plan.complete_package(&completed_package);
@@ -306,7 +325,7 @@ impl ParallelExecutor {
         package_id: PackageId,
         node: ResolvedNode,
         context: ExecutionContext,
-        permit: OwnedSemaphorePermit,
+        permit: ResourcePermit,
         prepared_packages: Arc<DashMap<PackageId, PreparedPackage>>,
     ) -> JoinHandle<Result<PackageId, Error>> {
         let store = self.store.clone();
@@ -460,7 +479,7 @@ impl ParallelExecutor {
                     }));
 
                     // For local packages, add to store and prepare data
-                    let stored_package = store.add_package(path).await?;
+                    let stored_package = store.add_package_streaming(path).await?;
 
                     if let Some(hash) = stored_package.hash() {
                         let size = stored_package.size().await?;
@@ -524,15 +543,6 @@ impl ParallelExecutor {
         context: &ExecutionContext,
         prepared_packages: &Arc<DashMap<PackageId, PreparedPackage>>,
     ) -> Result<u64, Error> {
-        context.emit(AppEvent::Acquisition(AcquisitionEvent::Started {
-            package: package_id.name.clone(),
-            version: package_id.version.clone(),
-            source: AcquisitionSource::Remote {
-                url: url.to_string(),
-                mirror_priority: 0,
-            },
-        }));
-
         // Create a temporary directory for the download
         let temp_dir = tempfile::tempdir().map_err(|e| InstallError::TempFileError {
             message: e.to_string(),
@@ -549,20 +559,101 @@ impl ParallelExecutor {
             .cloned()
             .unwrap_or_else(|| sps2_events::channel().0);
 
-        let download_result = downloader
-            .download_package(
-                &package_id.name,
-                &package_id.version,
-                url,
-                node.signature_url.as_deref(),
-                temp_dir.path(),
-                node.expected_hash.as_ref(),
-                String::new(), // internal tracker
-                None,
-                &tx,
-            )
-            .await?;
+        // Try mirrors best-first; only network failures move on to the next one
+        let candidates = context
+            .mirrors
+            .as_ref()
+            .map_or_else(|| vec![url.to_string()], |mirrors| mirrors.candidates(url));
+        let mut last_error = None;
+        let mut fetched = None;
+        for (priority, candidate) in candidates.iter().enumerate() {
+            context.emit(AppEvent::Acquisition(AcquisitionEvent::Started {
+                package: package_id.name.clone(),
+                version: package_id.version.clone(),
+                source: AcquisitionSource::Remote {
+                    url: candidate.clone(),
+                    mirror_priority: u8::try_from(priority).unwrap_or(u8::MAX),
+                },
+            }));
 
+            let signature_url = node.signature_url.as_deref().map(|signature| {
+                context
+                    .mirrors
+                    .as_ref()
+                    .map_or_else(|| signature.to_string(), |m| m.rebase(signature, candidate))
+            });
+            let started = Instant::now();
+            let attempt = downloader
+                .download_package(
+                    &package_id.name,
+                    &package_id.version,
+                    candidate,
+                    signature_url.as_deref(),
+                    temp_dir.path(),
+                    node.expected_hash.as_ref(),
+                    String::new(), // internal tracker
+                    None,
+                    &tx,
+                )
+                .await;
+
+            match attempt {
+                Ok(result) => {
+                    if let Some(mirrors) = &context.mirrors {
+                        mirrors.record_success(candidate, started.elapsed());
+                    }
+                    fetched = Some(result);
+                    break;
+                }
+                Err(e @ Error::Network(_)) => {
+                    if let Some(mirrors) = &context.mirrors {
+                        mirrors.record_failure(candidate);
+                    }
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        let download_result = match (fetched, last_error) {
+            (Some(result), _) => result,
+            (None, Some(e)) => return Err(e),
+            (None, None) => {
+                return Err(InstallError::TaskError {
+                    message: format!("no download source for {}", package_id.name),
+                }
+                .into())
+            }
+        };
+
+        let stored = Self::store_downloaded_package(
+            &download_result,
+            package_id,
+            node,
+            store,
+            context,
+            prepared_packages,
+        )
+        .await;
+
+        // The temporary directory is removed on return; keep the download if asked to
+        if stored.is_err() {
+            if let Some(dir) = &context.keep_failed_downloads {
+                Self::keep_failed_download(&download_result.package_path, dir, context).await;
+            }
+        }
+
+        stored
+    }
+
+    /// Enforce the signature policy and add a downloaded package to the store
+    async fn store_downloaded_package(
+        download_result: &sps2_net::PackageDownloadResult,
+        package_id: &PackageId,
+        node: &ResolvedNode,
+        store: &PackageStore,
+        context: &ExecutionContext,
+        prepared_packages: &Arc<DashMap<PackageId, PreparedPackage>>,
+    ) -> Result<u64, Error> {
         // Enforce signature policy if configured
         if let Some(policy) = context.security_policy {
             if policy.verify_signatures && !policy.allow_unsigned {
@@ -625,10 +716,50 @@ impl ParallelExecutor {
         }
     }
 
+    /// Copy a downloaded package that could not be stored into `dir`
+    async fn keep_failed_download(package_path: &Path, dir: &Path, context: &ExecutionContext) {
+        let Some(file_name) = package_path.file_name() else {
+            return;
+        };
+        let dest = dir.join(file_name);
+        let kept = async {
+            tokio::fs::create_dir_all(dir).await?;
+            tokio::fs::copy(package_path, &dest).await
+        }
+        .await;
+
+        match kept {
+            Ok(_) => {
+                context.emit_warning(format!("download kept for debugging at {}", dest.display()));
+            }
+            Err(e) => context.emit_warning(format!(
+                "could not keep failed download at {}: {e}",
+                dest.display()
+            )),
+        }
+    }
+
+    /// Await every in-flight task, discarding results
+    ///
+    /// Used on cancellation so downloads finish writing before the caller returns.
+    async fn drain_inflight(inflight: &DashMap<PackageId, JoinHandle<Result<PackageId, Error>>>) {
+        let package_ids: Vec<PackageId> =
+            inflight.iter().map(|entry| entry.key().clone()).collect();
+        for package_id in package_ids {
+            if let Some((_, handle)) = inflight.remove(&package_id) {
+                let _ = handle.await;
+            }
+        }
+    }
+
     /// Wait for at least one task to complete
+    ///
+    /// Returns `Error::Cancelled` as soon as the context is cancelled, leaving
+    /// the in-flight tasks to the caller.
     async fn wait_for_completion(
         &self,
         inflight: &DashMap<PackageId, JoinHandle<Result<PackageId, Error>>>,
+        context: &ExecutionContext,
     ) -> Result<PackageId, Error> {
         let timeout_duration = Duration::from_secs(300); // 5 minutes per task
         let start_time = Instant::now();
@@ -670,8 +801,15 @@ impl ParallelExecutor {
                 }
             }
 
-            // Small delay before checking again
-            tokio::time::sleep(Duration::from_millis(50)).await;
+            // Small delay before checking again, cut short by cancellation
+            let delay = tokio::time::sleep(Duration::from_millis(50));
+            match &context.cancellation_token {
+                Some(token) => tokio::select! {
+                    () = token.cancelled() => return Err(Error::Cancelled),
+                    () = delay => {}
+                },
+                None => delay.await,
+            }
         }
     }
 }
@@ -683,6 +821,12 @@ pub struct ExecutionContext {
     event_sender: Option<EventSender>,
     /// Optional security policy for signature enforcement
     security_policy: Option<SecurityPolicy>,
+    /// Token to stop launching new work
+    cancellation_token: Option<CancellationToken>,
+    /// Directory to copy downloads into when they fail after being fetched
+    keep_failed_downloads: Option<PathBuf>,
+    /// Mirror ranking consulted for download URLs
+    mirrors: Option<MirrorRanking>,
 }
 
 impl ExecutionContext {
@@ -692,6 +836,9 @@ impl ExecutionContext {
         Self {
             event_sender: None,
             security_policy: None,
+            cancellation_token: None,
+            keep_failed_downloads: None,
+            mirrors: None,
         }
     }
 
@@ -708,6 +855,35 @@ impl ExecutionContext {
         self.security_policy = Some(policy);
         self
     }
+
+    /// Set cancellation token
+    #[must_use]
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Keep downloads that fail signature checks or storing in `dir`
+    #[must_use]
+    pub fn with_keep_failed_downloads(mut self, dir: PathBuf) -> Self {
+        self.keep_failed_downloads = Some(dir);
+        self
+    }
+
+    /// Try download URLs served by these mirrors in ranked order
+    #[must_use]
+    pub fn with_mirrors(mut self, mirrors: MirrorRanking) -> Self {
+        self.mirrors = Some(mirrors);
+        self
+    }
+
+    /// Check whether cancellation has been requested
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation_token
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
 }
 
 impl EventEmitter for ExecutionContext {
@@ -789,6 +965,38 @@ mod tests {
         (td, sp_path)
     }
 
+    #[tokio::test]
+    async fn waiting_for_tasks_honors_cancellation() {
+        let (_td, state, store) = mk_env().await;
+        let resources = Arc::new(sps2_resources::ResourceManager::default());
+        let executor = ParallelExecutor::new(store, state, resources).expect("parallel executor");
+
+        let inflight = DashMap::new();
+        inflight.insert(
+            PackageId::new("slow".to_string(), Version::parse("1.0.0").unwrap()),
+            tokio::spawn(async {
+                tokio::time::sleep(Duration::from_secs(600)).await;
+                Err(Error::Cancelled)
+            }),
+        );
+        let token = CancellationToken::new();
+        let context = ExecutionContext::new().with_cancellation_token(token.clone());
+
+        let canceller = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            token.cancel();
+        });
+        let result = timeout(
+            Duration::from_secs(10),
+            executor.wait_for_completion(&inflight, &context),
+        )
+        .await
+        .expect("cancellation ends the wait");
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert_eq!(inflight.len(), 1);
+        canceller.await.unwrap();
+    }
+
     #[tokio::test]
     async fn download_permit_limits_parallelism() {
         let (_td, state, store) = mk_env().await;
@@ -821,7 +1029,8 @@ mod tests {
         graph.add_node(node2);
 
         let sorted = vec![pkg1_id.clone(), pkg2_id.clone()];
-        let execution_plan = ExecutionPlan::from_sorted_packages(&sorted, &graph);
+        let execution_plan =
+            ExecutionPlan::from_sorted_packages(&sorted, &graph).expect("acyclic plan");
 
         let limits = sps2_resources::limits::ResourceLimits {
             concurrent_downloads: 1,
@@ -877,4 +1086,54 @@ mod tests {
             "second package should only start after first completes"
         );
     }
+
+    #[tokio::test]
+    async fn max_batch_size_caps_packages_in_flight() {
+        let (_td, state, store) = mk_env().await;
+
+        let mut package_dirs = Vec::new();
+        let mut resolved_packages = HashMap::new();
+        let mut graph = DependencyGraph::new();
+        let mut sorted = Vec::new();
+        for name in ["pkg-a", "pkg-b", "pkg-c", "pkg-d", "pkg-e"] {
+            let (dir, sp) = create_sp(name, "1.0.0").await;
+            package_dirs.push(dir);
+            let node = ResolvedNode::local(
+                name.to_string(),
+                Version::parse("1.0.0").unwrap(),
+                sp,
+                vec![],
+            );
+            sorted.push(node.package_id());
+            resolved_packages.insert(node.package_id(), node.clone());
+            graph.add_node(node);
+        }
+        let execution_plan = ExecutionPlan::from_sorted_packages(&sorted, &graph)
+            .expect("acyclic plan")
+            .with_max_batch_size(Some(2));
+
+        let resources = Arc::new(sps2_resources::ResourceManager::default());
+        let executor = ParallelExecutor::new(store, state, resources).expect("parallel executor");
+        let (tx, mut rx) = sps2_events::channel();
+        let context = ExecutionContext::new().with_event_sender(tx);
+
+        let prepared = executor
+            .execute_parallel(&execution_plan, &resolved_packages, &context)
+            .await
+            .expect("execute parallel");
+        assert_eq!(prepared.len(), 5);
+
+        let (mut running, mut peak) = (0usize, 0usize);
+        while let Ok(message) = rx.try_recv() {
+            match message.event {
+                AppEvent::Install(InstallEvent::Started { .. }) => {
+                    running += 1;
+                    peak = peak.max(running);
+                }
+                AppEvent::Install(InstallEvent::Completed { .. }) => running -= 1,
+                _ => {}
+            }
+        }
+        assert!(peak <= 2, "{peak} packages ran at once");
+    }
 }
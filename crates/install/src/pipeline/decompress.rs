@@ -9,7 +9,6 @@ use sps2_hash::Hash;
 use sps2_resolver::PackageId;
 use sps2_resources::ResourceManager;
 use std::path::PathBuf;
-use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use tokio::fs::{File, OpenOptions};
 use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
@@ -158,28 +157,20 @@ impl DecompressPipeline {
         let buffer_size = self.buffer_size;
 
         tokio::spawn(async move {
-            let _decompress_permit = resources.acquire_decompression_permit().await?;
-
-            // Track memory usage for decompression
+            // Reserve memory for decompression, waiting while the limit is reached
             let decompress_memory = buffer_size as u64 * 4; // Estimate 4x buffer for decompression
-            resources
-                .memory_usage
-                .fetch_add(decompress_memory, Ordering::Relaxed);
+            let _decompress_permit = resources
+                .acquire_decompression_permit_with_memory(decompress_memory)
+                .await?;
 
             // Create streaming decompression pipeline
-            let result = Self::streaming_decompress_validate(
+            Self::streaming_decompress_validate(
                 &download_result,
                 buffer_size,
                 &resources.installation_semaphore,
                 &tx,
             )
-            .await;
-
-            resources
-                .memory_usage
-                .fetch_sub(decompress_memory, Ordering::Relaxed);
-
-            result
+            .await
         })
     }
 
@@ -0,0 +1,146 @@
+//! Package fixtures for tests that need a real installed state
+//!
+//! Other crates enable these with the `test-support` feature in their
+//! dev-dependencies.
+
+use crate::{AtomicInstaller, InstallContext, PreparedPackage};
+use sps2_hash::Hash;
+use sps2_resolver::{PackageId, ResolvedNode};
+use sps2_state::StateManager;
+use sps2_store::{create_package, PackageStore};
+use sps2_types::{Arch, Manifest, Version};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tempfile::TempDir;
+use tokio::fs as afs;
+
+/// Contents of `share/file.txt` in the package from [`install_demo_package`]
+pub const DEMO_FILE_CONTENTS: &[u8] = b"demo contents";
+
+/// A package added to a store by [`add_package`]
+#[derive(Debug, Clone)]
+pub struct StoredPackage {
+    pub name: String,
+    pub version: Version,
+    pub hash: Hash,
+    pub store_path: PathBuf,
+    pub size: u64,
+}
+
+/// Build a package with `files` under the live prefix and add it to `store`
+///
+/// # Panics
+///
+/// Panics if the package cannot be built or stored.
+pub async fn add_package(
+    store: &PackageStore,
+    name: &str,
+    version: &str,
+    description: Option<&str>,
+    files: &[(&str, &[u8])],
+) -> StoredPackage {
+    let td = TempDir::new().expect("package tempdir");
+    let src = td.path().join("src");
+    afs::create_dir_all(&src).await.expect("src dir");
+
+    let version = Version::parse(version).expect("version");
+    let mut manifest = Manifest::new(name.to_string(), &version, 1, &Arch::Arm64);
+    manifest.package.description = description.map(str::to_string);
+    sps2_store::manifest_io::write_manifest(&src.join("manifest.toml"), &manifest)
+        .await
+        .expect("write manifest");
+
+    for (relative, contents) in files {
+        let path = src.join("opt/pm/live").join(relative);
+        if let Some(parent) = path.parent() {
+            afs::create_dir_all(parent).await.expect("content dir");
+        }
+        afs::write(&path, contents).await.expect("write file");
+    }
+
+    let sp_path = td.path().join("pkg.sp");
+    create_package(&src, &sp_path)
+        .await
+        .expect("create package");
+
+    let stored = store.add_package(&sp_path).await.expect("add package");
+    let hash = stored.hash().expect("hash");
+    StoredPackage {
+        name: name.to_string(),
+        version,
+        store_path: store.package_path(&hash),
+        size: afs::metadata(&sp_path).await.expect("metadata").len(),
+        hash,
+    }
+}
+
+/// Resolved nodes and prepared entries for installing `packages` from the store
+#[must_use]
+pub fn install_inputs(
+    packages: &[StoredPackage],
+) -> (
+    HashMap<PackageId, ResolvedNode>,
+    HashMap<PackageId, PreparedPackage>,
+) {
+    let mut resolved_nodes = HashMap::new();
+    let mut prepared = HashMap::new();
+    for package in packages {
+        let pkg_id = PackageId::new(package.name.clone(), package.version.clone());
+        resolved_nodes.insert(
+            pkg_id.clone(),
+            ResolvedNode::local(
+                package.name.clone(),
+                package.version.clone(),
+                package.store_path.clone(),
+                vec![],
+            ),
+        );
+        prepared.insert(
+            pkg_id,
+            PreparedPackage {
+                hash: package.hash.clone(),
+                size: package.size,
+                store_path: package.store_path.clone(),
+                is_local: true,
+            },
+        );
+    }
+    (resolved_nodes, prepared)
+}
+
+/// Install `packages` together as one new state
+///
+/// # Panics
+///
+/// Panics if the installation fails.
+pub async fn install_packages(
+    state: &StateManager,
+    store: &PackageStore,
+    packages: &[StoredPackage],
+) {
+    let (resolved_nodes, prepared) = install_inputs(packages);
+    AtomicInstaller::new(state.clone(), store.clone())
+        .await
+        .expect("atomic installer")
+        .install(&InstallContext::new(), &resolved_nodes, Some(&prepared))
+        .await
+        .expect("install packages");
+}
+
+/// Install a `demo` 1.0.0 package holding `share/file.txt`
+///
+/// # Panics
+///
+/// Panics if the package cannot be built or installed.
+pub async fn install_demo_package(state: &StateManager, store: &PackageStore) -> StoredPackage {
+    let package = add_package(
+        store,
+        "demo",
+        "1.0.0",
+        Some("demo"),
+        &[("share/file.txt", DEMO_FILE_CONTENTS)],
+    )
+    .await;
+    install_packages(state, store, std::slice::from_ref(&package)).await;
+    package
+}
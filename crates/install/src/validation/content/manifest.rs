@@ -53,11 +53,8 @@ pub fn validate_manifest_content(content: &str) -> Result<ManifestValidation, Er
     }
 
     if validation.has_errors() {
-        Err(sps2_errors::PackageError::InvalidFormat {
-            message: format!(
-                "manifest validation failed: {}",
-                validation.errors.join(", ")
-            ),
+        Err(sps2_errors::PackageError::InvalidManifest {
+            message: validation.errors.join(", "),
         }
         .into())
     } else {
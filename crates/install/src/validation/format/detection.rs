@@ -2,96 +2,161 @@
 //!
 //! This module handles detection of package formats by reading magic bytes
 //! and examining file headers to determine if a file is a zstd-compressed
-//! tar archive or a plain tar archive.
+//! tar archive or a plain tar archive. Compressed files are peeked one layer
+//! deep so mismatched or doubly compressed packages get a precise error.
 
+use async_compression::tokio::bufread::{BzDecoder, GzipDecoder, XzDecoder, ZstdDecoder};
 use sps2_errors::{Error, InstallError};
 use std::path::Path;
 use tokio::fs::File;
-use tokio::io::{AsyncReadExt, BufReader};
+use tokio::io::{AsyncRead, AsyncReadExt, BufReader};
 
 use crate::validation::types::{PackageFormat, ZSTD_MAGIC};
 
+/// Bytes needed to recognize any supported format (one tar header block)
+const HEADER_LEN: usize = 512;
+
+/// Container formats recognized by their leading bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Magic {
+    Zstd,
+    Gzip,
+    Bzip2,
+    Xz,
+    Zip,
+    Tar,
+}
+
+impl Magic {
+    fn identify(header: &[u8]) -> Option<Self> {
+        if header.starts_with(&ZSTD_MAGIC) {
+            Some(Self::Zstd)
+        } else if header.starts_with(&[0x1F, 0x8B]) {
+            Some(Self::Gzip)
+        } else if header.starts_with(b"BZh") {
+            Some(Self::Bzip2)
+        } else if header.starts_with(&[0xFD, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Self::Xz)
+        } else if header.starts_with(b"PK\x03\x04") {
+            Some(Self::Zip)
+        } else if header.len() >= HEADER_LEN && &header[257..262] == b"ustar" {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Zstd => "zstd",
+            Self::Gzip => "gzip",
+            Self::Bzip2 => "bzip2",
+            Self::Xz => "xz",
+            Self::Zip => "zip",
+            Self::Tar => "tar",
+        }
+    }
+}
+
 /// Detects package format by reading magic bytes
 ///
-/// This function reads the first few bytes of a file to determine if it's
-/// a zstd-compressed archive or a plain tar archive. It handles the detection
-/// robustly with proper error handling.
+/// This function reads the first tar block of a file to determine if it's
+/// a zstd-compressed archive or a plain tar archive. Compressed files also
+/// have the start of their decompressed stream inspected, so a package that
+/// was compressed with the wrong tool, or compressed twice, is reported
+/// precisely instead of failing later during extraction.
 ///
 /// # Errors
 ///
 /// Returns an error if:
 /// - File cannot be opened or read
 /// - File is too small to determine format
+/// - File uses another compression (gzip, bzip2, xz) or is a zip archive
+/// - The zstd stream wraps another compressed stream instead of a tar
 /// - Format is unrecognized (not zstd or tar)
 pub async fn detect_package_format(file_path: &Path) -> Result<PackageFormat, Error> {
-    let file = File::open(file_path)
-        .await
-        .map_err(|e| InstallError::InvalidPackageFile {
+    let invalid = |message: String| -> Error {
+        InstallError::InvalidPackageFile {
             path: file_path.display().to_string(),
-            message: format!("failed to open file: {e}"),
-        })?;
-
-    let mut reader = BufReader::new(file);
-    let mut magic = [0u8; 4];
-
-    // Read first 4 bytes to check for zstd magic number
-    let bytes_read =
-        reader
-            .read(&mut magic)
-            .await
-            .map_err(|e| InstallError::InvalidPackageFile {
-                path: file_path.display().to_string(),
-                message: format!("failed to read magic bytes: {e}"),
-            })?;
-
-    if bytes_read < 4 {
-        return Err(InstallError::InvalidPackageFile {
-            path: file_path.display().to_string(),
-            message: "file too small to determine format".to_string(),
+            message,
         }
-        .into());
-    }
-
-    if magic == ZSTD_MAGIC {
-        return Ok(PackageFormat::ZstdCompressed);
-    }
+        .into()
+    };
 
-    // Check for tar header at the beginning
-    // Seek back to start and read tar header
-    drop(reader); // Close the reader
     let file = File::open(file_path)
         .await
-        .map_err(|e| InstallError::InvalidPackageFile {
-            path: file_path.display().to_string(),
-            message: format!("failed to reopen file: {e}"),
-        })?;
+        .map_err(|e| invalid(format!("failed to open file: {e}")))?;
+    let header = read_header(file)
+        .await
+        .map_err(|e| invalid(format!("failed to read magic bytes: {e}")))?;
 
-    let mut reader = BufReader::new(file);
-    let mut header = [0u8; 512]; // tar header is 512 bytes
+    if header.len() < 4 {
+        return Err(invalid("file too small to determine format".to_string()));
+    }
 
-    let bytes_read =
-        reader
-            .read(&mut header)
-            .await
-            .map_err(|e| InstallError::InvalidPackageFile {
-                path: file_path.display().to_string(),
-                message: format!("failed to read tar header: {e}"),
-            })?;
+    let Some(outer) = Magic::identify(&header) else {
+        if header.len() < HEADER_LEN {
+            return Ok(PackageFormat::Unknown);
+        }
+        return Err(invalid(
+            "unrecognized file format (not zstd or tar)".to_string(),
+        ));
+    };
 
-    if bytes_read < 512 {
-        return Ok(PackageFormat::Unknown);
-    }
+    let inner = match outer {
+        Magic::Tar => return Ok(PackageFormat::PlainTar),
+        Magic::Zip => {
+            return Err(invalid(
+                "expected zstd-compressed tar but found zip archive".to_string(),
+            ))
+        }
+        compressed => peek_decompressed(file_path, compressed).await,
+    };
 
-    // Check for "ustar" magic at offset 257
-    if &header[257..262] == b"ustar" {
-        return Ok(PackageFormat::PlainTar);
+    if outer == Magic::Zstd {
+        return match inner {
+            Some(Magic::Tar) | None => Ok(PackageFormat::ZstdCompressed),
+            Some(nested) => Err(invalid(format!(
+                "expected tar inside zstd stream but found {} data (package compressed twice?)",
+                nested.name()
+            ))),
+        };
     }
 
-    Err(InstallError::InvalidPackageFile {
-        path: file_path.display().to_string(),
-        message: "unrecognized file format (not zstd or tar)".to_string(),
+    let found = match inner {
+        Some(Magic::Tar) => format!("{}-compressed tar", outer.name()),
+        _ => format!("{} data", outer.name()),
+    };
+    Err(invalid(format!(
+        "expected zstd-compressed tar but found {found}"
+    )))
+}
+
+/// Read up to one tar block from the start of `reader`
+async fn read_header<R: AsyncRead + Unpin>(reader: R) -> std::io::Result<Vec<u8>> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    reader
+        .take(HEADER_LEN as u64)
+        .read_to_end(&mut header)
+        .await?;
+    Ok(header)
+}
+
+/// Identify the format of the data inside a compressed file
+///
+/// Returns `None` if the stream cannot be decompressed or its contents are
+/// not recognized; extraction reports those failures in detail.
+async fn peek_decompressed(file_path: &Path, compression: Magic) -> Option<Magic> {
+    let reader = BufReader::new(File::open(file_path).await.ok()?);
+    let header = match compression {
+        Magic::Zstd => read_header(ZstdDecoder::new(reader)).await,
+        Magic::Gzip => read_header(GzipDecoder::new(reader)).await,
+        Magic::Bzip2 => read_header(BzDecoder::new(reader)).await,
+        Magic::Xz => read_header(XzDecoder::new(reader)).await,
+        Magic::Zip | Magic::Tar => return None,
     }
-    .into())
+    .ok()?;
+    Magic::identify(&header)
 }
 
 /// Validates that the detected format is supported
@@ -108,3 +173,113 @@ pub fn validate_supported_format(format: &PackageFormat) -> Result<(), Error> {
         .into()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_compression::tokio::write::{BzEncoder, GzipEncoder, XzEncoder, ZstdEncoder};
+    use tempfile::TempDir;
+    use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+    fn tar_bytes() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        let data = b"[package]\nname = \"demo\"\n";
+        let mut header = tar::Header::new_ustar();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "manifest.toml", &data[..])
+            .unwrap();
+        builder.into_inner().unwrap()
+    }
+
+    async fn compress<W: AsyncWrite + Unpin>(mut encoder: W, data: &[u8]) -> W {
+        encoder.write_all(data).await.unwrap();
+        encoder.shutdown().await.unwrap();
+        encoder
+    }
+
+    async fn gzip(data: &[u8]) -> Vec<u8> {
+        compress(GzipEncoder::new(Vec::new()), data)
+            .await
+            .into_inner()
+    }
+
+    async fn zstd(data: &[u8]) -> Vec<u8> {
+        compress(ZstdEncoder::new(Vec::new()), data)
+            .await
+            .into_inner()
+    }
+
+    async fn detect(data: &[u8]) -> Result<PackageFormat, Error> {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("demo-1.0.0-1.arm64.sp");
+        tokio::fs::write(&path, data).await.unwrap();
+        detect_package_format(&path).await
+    }
+
+    fn message(err: &Error) -> String {
+        match err {
+            Error::Install(InstallError::InvalidPackageFile { message, .. }) => message.clone(),
+            other => panic!("unexpected error: {other}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn zstd_and_plain_tar_are_accepted() {
+        let tar = tar_bytes();
+        assert_eq!(detect(&tar).await.unwrap(), PackageFormat::PlainTar);
+        assert_eq!(
+            detect(&zstd(&tar).await).await.unwrap(),
+            PackageFormat::ZstdCompressed
+        );
+    }
+
+    #[tokio::test]
+    async fn gzip_in_disguise_is_reported_precisely() {
+        let err = detect(&gzip(&tar_bytes()).await).await.unwrap_err();
+        assert_eq!(
+            message(&err),
+            "expected zstd-compressed tar but found gzip-compressed tar"
+        );
+
+        let err = detect(&gzip(b"not an archive").await).await.unwrap_err();
+        assert_eq!(
+            message(&err),
+            "expected zstd-compressed tar but found gzip data"
+        );
+    }
+
+    #[tokio::test]
+    async fn xz_and_bzip2_are_reported() {
+        let tar = tar_bytes();
+        let xz = compress(XzEncoder::new(Vec::new()), &tar)
+            .await
+            .into_inner();
+        let err = detect(&xz).await.unwrap_err();
+        assert_eq!(
+            message(&err),
+            "expected zstd-compressed tar but found xz-compressed tar"
+        );
+
+        let bzip2 = compress(BzEncoder::new(Vec::new()), &tar)
+            .await
+            .into_inner();
+        let err = detect(&bzip2).await.unwrap_err();
+        assert_eq!(
+            message(&err),
+            "expected zstd-compressed tar but found bzip2-compressed tar"
+        );
+    }
+
+    #[tokio::test]
+    async fn doubly_compressed_package_is_reported() {
+        let nested = zstd(&gzip(&tar_bytes()).await).await;
+        let err = detect(&nested).await.unwrap_err();
+        assert_eq!(
+            message(&err),
+            "expected tar inside zstd stream but found gzip data (package compressed twice?)"
+        );
+    }
+}
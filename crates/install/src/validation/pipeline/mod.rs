@@ -18,8 +18,8 @@ use crate::validation::types::ValidationResult;
 pub use context::{ExecutionState, ExecutionSummary, PipelineContext, PipelineMetrics};
 pub use orchestrator::{quick_validate, strict_validate, ValidationOrchestrator, ValidationStats};
 pub use recovery::{
-    resilient_validation, ErrorRecoveryManager, RecoveryAction, RecoveryPresets, RecoveryStats,
-    RecoveryStrategy,
+    resilient_validation, ErrorDomain, ErrorRecoveryManager, HandlerKey, RecoveryAction,
+    RecoveryPresets, RecoveryStats, RecoveryStrategy,
 };
 
 /// Main validation pipeline entry point
@@ -4,8 +4,12 @@
 //! package validation, allowing the pipeline to continue processing
 //! even when encountering corrupted or problematic packages.
 
-use sps2_errors::Error;
+use sps2_errors::{
+    BuildError, Error, InstallError, NetworkError, PackageError, StateError, StorageError,
+    UserFacingError,
+};
 use std::collections::HashMap;
+use std::io::ErrorKind;
 
 use crate::validation::types::ValidationResult;
 
@@ -36,7 +40,220 @@ pub struct ErrorRecoveryManager {
     /// Recovery statistics
     recovery_stats: RecoveryStats,
     /// Custom recovery handlers
-    custom_handlers: HashMap<String, Box<dyn Fn(&Error) -> RecoveryAction>>,
+    custom_handlers: HashMap<HandlerKey, Box<dyn Fn(&Error) -> RecoveryAction>>,
+}
+
+/// Top-level [`Error`] variant an error belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorDomain {
+    Network,
+    Storage,
+    State,
+    Package,
+    Version,
+    Config,
+    Build,
+    Audit,
+    Install,
+    Ops,
+    Guard,
+    Platform,
+    Signing,
+    Internal,
+    Cancelled,
+    Io,
+}
+
+impl ErrorDomain {
+    const ALL: [Self; 16] = [
+        Self::Network,
+        Self::Storage,
+        Self::State,
+        Self::Package,
+        Self::Version,
+        Self::Config,
+        Self::Build,
+        Self::Audit,
+        Self::Install,
+        Self::Ops,
+        Self::Guard,
+        Self::Platform,
+        Self::Signing,
+        Self::Internal,
+        Self::Cancelled,
+        Self::Io,
+    ];
+
+    /// Domain of `error`
+    #[must_use]
+    pub fn of(error: &Error) -> Self {
+        match error {
+            Error::Network(_) => Self::Network,
+            Error::Storage(_) => Self::Storage,
+            Error::State(_) => Self::State,
+            Error::Package(_) => Self::Package,
+            Error::Version(_) => Self::Version,
+            Error::Config(_) => Self::Config,
+            Error::Build(_) => Self::Build,
+            Error::Audit(_) => Self::Audit,
+            Error::Install(_) => Self::Install,
+            Error::Ops(_) => Self::Ops,
+            Error::Guard(_) => Self::Guard,
+            Error::Platform(_) => Self::Platform,
+            Error::Signing(_) => Self::Signing,
+            Error::Internal(_) => Self::Internal,
+            Error::Cancelled => Self::Cancelled,
+            Error::Io { .. } => Self::Io,
+        }
+    }
+
+    /// Lowercase name of the domain
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Network => "network",
+            Self::Storage => "storage",
+            Self::State => "state",
+            Self::Package => "package",
+            Self::Version => "version",
+            Self::Config => "config",
+            Self::Build => "build",
+            Self::Audit => "audit",
+            Self::Install => "install",
+            Self::Ops => "ops",
+            Self::Guard => "guard",
+            Self::Platform => "platform",
+            Self::Signing => "signing",
+            Self::Internal => "internal",
+            Self::Cancelled => "cancelled",
+            Self::Io => "io",
+        }
+    }
+}
+
+/// Which errors a custom recovery handler applies to
+///
+/// Keys are matched exactly: a handler for an error code wins over one for
+/// the error's domain. Only internal errors, which carry nothing but a
+/// message, can be matched by message text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HandlerKey {
+    /// A stable error code such as `install.invalid_package_file`, see
+    /// [`UserFacingError::user_code`]
+    Code(String),
+    /// Every error of a domain
+    Domain(ErrorDomain),
+    /// Internal errors whose message contains the text
+    InternalMessage(String),
+}
+
+impl HandlerKey {
+    /// Key for a specific error code
+    #[must_use]
+    pub fn code(code: impl Into<String>) -> Self {
+        Self::Code(code.into())
+    }
+
+    /// Key for a handler registered by name before keys were typed
+    ///
+    /// A domain name such as `io` selects that domain, a dotted name an
+    /// error code, and anything else internal errors mentioning it.
+    #[must_use]
+    pub fn from_legacy(error_type: &str) -> Self {
+        if let Some(domain) = ErrorDomain::ALL
+            .into_iter()
+            .find(|domain| domain.name() == error_type)
+        {
+            Self::Domain(domain)
+        } else if error_type.contains('.') {
+            Self::Code(error_type.to_string())
+        } else {
+            Self::InternalMessage(error_type.to_string())
+        }
+    }
+}
+
+impl From<ErrorDomain> for HandlerKey {
+    fn from(domain: ErrorDomain) -> Self {
+        Self::Domain(domain)
+    }
+}
+
+/// Kind of failure an error represents, as far as recovery is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ErrorClass {
+    /// Data known to be damaged
+    Corrupted,
+    /// Package content that breaks the format or its limits
+    Invalid,
+    /// Invalid or undecodable manifest
+    Manifest,
+    /// Text that is not valid UTF-8
+    Encoding,
+    /// Content that does not match its expected hash
+    Checksum,
+    Timeout,
+    Permission,
+    /// A path longer than the filesystem allows
+    PathTooLong,
+    Other,
+}
+
+impl ErrorClass {
+    /// Classify `error` by its variant
+    ///
+    /// Only opaque errors, which carry nothing but a message, fall back to
+    /// looking at the message text.
+    fn of(error: &Error) -> Self {
+        match error {
+            Error::Package(PackageError::Corrupted { .. })
+            | Error::Storage(StorageError::CorruptedData { .. })
+            | Error::State(StateError::StateCorrupted { .. }) => Self::Corrupted,
+            Error::Install(
+                InstallError::InvalidPackageFile { .. } | InstallError::ExtractionFailed { .. },
+            )
+            | Error::Package(
+                PackageError::InvalidFormat { .. } | PackageError::IncompatibleFormat { .. },
+            ) => Self::Invalid,
+            Error::Package(PackageError::InvalidManifest { .. }) => Self::Manifest,
+            Error::Network(NetworkError::ChecksumMismatch { .. })
+            | Error::Build(BuildError::HashMismatch { .. }) => Self::Checksum,
+            Error::Network(NetworkError::Timeout { .. })
+            | Error::Install(
+                InstallError::DownloadTimeout { .. } | InstallError::OperationTimeout { .. },
+            )
+            | Error::Package(PackageError::ResolutionTimeout { .. }) => Self::Timeout,
+            Error::Storage(StorageError::PermissionDenied { .. }) => Self::Permission,
+            Error::Io { kind, message, .. } => match kind {
+                ErrorKind::InvalidData => Self::Encoding,
+                ErrorKind::TimedOut => Self::Timeout,
+                ErrorKind::PermissionDenied => Self::Permission,
+                ErrorKind::InvalidFilename => Self::PathTooLong,
+                ErrorKind::Other => Self::from_message(message),
+                _ => Self::Other,
+            },
+            Error::Internal(message) => Self::from_message(message),
+            _ => Self::Other,
+        }
+    }
+
+    /// Best-effort classification of an opaque error's message
+    fn from_message(message: &str) -> Self {
+        let message = message.to_lowercase();
+        if message.contains("corrupted") {
+            Self::Corrupted
+        } else if message.contains("utf-8") || message.contains("encoding") {
+            Self::Encoding
+        } else if message.contains("cksum") || message.contains("checksum") {
+            Self::Checksum
+        } else if message.contains("timeout") || message.contains("timed out") {
+            Self::Timeout
+        } else if message.contains("permission") {
+            Self::Permission
+        } else {
+            Self::Other
+        }
+    }
 }
 
 /// Recovery action to take for an error
@@ -91,12 +308,26 @@ impl ErrorRecoveryManager {
         self
     }
 
-    /// Add custom error handler
+    /// Add custom error handler for the errors selected by `key`
+    ///
+    /// A handler registered for the same key replaces the previous one.
+    pub fn add_handler<F>(&mut self, key: impl Into<HandlerKey>, handler: F)
+    where
+        F: Fn(&Error) -> RecoveryAction + 'static,
+    {
+        self.custom_handlers.insert(key.into(), Box::new(handler));
+    }
+
+    /// Add custom error handler by name
+    ///
+    /// `error_type` is converted with [`HandlerKey::from_legacy`].
+    #[deprecated(note = "use `add_handler` with a `HandlerKey`")]
+    #[allow(clippy::needless_pass_by_value)]
     pub fn add_custom_handler<F>(&mut self, error_type: String, handler: F)
     where
         F: Fn(&Error) -> RecoveryAction + 'static,
     {
-        self.custom_handlers.insert(error_type, Box::new(handler));
+        self.add_handler(HandlerKey::from_legacy(&error_type), handler);
     }
 
     /// Handle an error and determine recovery action
@@ -114,13 +345,10 @@ impl ErrorRecoveryManager {
         }
 
         // Try custom handlers first
-        let error_message = error.to_string();
-        for (error_type, handler) in &self.custom_handlers {
-            if error_message.contains(error_type) {
-                let action = handler(error);
-                self.apply_recovery_stats(&action);
-                return Ok(action);
-            }
+        if let Some(handler) = self.custom_handler_for(error) {
+            let action = handler(error);
+            self.apply_recovery_stats(&action);
+            return Ok(action);
         }
 
         // Apply default strategy
@@ -170,6 +398,35 @@ impl ErrorRecoveryManager {
         Ok(())
     }
 
+    /// The custom handler registered for `error`, if any
+    ///
+    /// An internal error mentioning several registered texts goes to the
+    /// longest one.
+    fn custom_handler_for(&self, error: &Error) -> Option<&dyn Fn(&Error) -> RecoveryAction> {
+        let handler = |key: &HandlerKey| self.custom_handlers.get(key).map(AsRef::as_ref);
+        let by_code = error
+            .user_code()
+            .and_then(|code| handler(&HandlerKey::code(code)));
+        let by_message = || match error {
+            Error::Internal(message) => self
+                .custom_handlers
+                .iter()
+                .filter_map(|(key, handler)| match key {
+                    HandlerKey::InternalMessage(text) if message.contains(text.as_str()) => {
+                        Some((text.len(), handler.as_ref()))
+                    }
+                    _ => None,
+                })
+                .max_by_key(|(len, _)| *len)
+                .map(|(_, handler)| handler),
+            _ => None,
+        };
+
+        by_code
+            .or_else(|| handler(&ErrorDomain::of(error).into()))
+            .or_else(by_message)
+    }
+
     /// Set reasonable defaults for recovered validation
     fn set_recovery_defaults(&self, result: &mut ValidationResult) {
         // Set minimal valid values if not already set
@@ -183,62 +440,53 @@ impl ErrorRecoveryManager {
 
     /// Determine warning action for error
     fn determine_warning_action(&self, error: &Error) -> RecoveryAction {
-        let error_msg = error.to_string().to_lowercase();
-
-        if error_msg.contains("corrupted") || error_msg.contains("invalid") {
-            RecoveryAction::ConvertToWarning(format!(
-                "Package has corruption issues but validation continuing: {}",
-                error.to_string()
-            ))
-        } else if error_msg.contains("utf-8") || error_msg.contains("encoding") {
-            RecoveryAction::ConvertToWarning(
+        match ErrorClass::of(error) {
+            ErrorClass::Corrupted | ErrorClass::Invalid => RecoveryAction::ConvertToWarning(
+                format!("Package has corruption issues but validation continuing: {error}"),
+            ),
+            ErrorClass::Encoding => RecoveryAction::ConvertToWarning(
                 "Package has encoding issues but may be usable".to_string(),
-            )
-        } else if error_msg.contains("cksum") || error_msg.contains("checksum") {
-            RecoveryAction::ConvertToWarning(
+            ),
+            ErrorClass::Checksum => RecoveryAction::ConvertToWarning(
                 "Package has checksum issues but attempting to continue".to_string(),
-            )
-        } else {
-            RecoveryAction::ConvertToWarning(format!(
-                "Non-critical validation error: {}",
-                error.to_string()
-            ))
+            ),
+            _ => {
+                RecoveryAction::ConvertToWarning(format!("Non-critical validation error: {error}"))
+            }
         }
     }
 
     /// Determine auto-recovery action for error
     fn determine_auto_recovery_action(&self, error: &Error) -> RecoveryAction {
-        let error_msg = error.to_string().to_lowercase();
-
-        if error_msg.contains("timeout") {
-            RecoveryAction::CustomFix("Extended timeout for large package".to_string())
-        } else if error_msg.contains("permission") {
-            RecoveryAction::CustomFix("Applied safe permission defaults".to_string())
-        } else if error_msg.contains("path") && error_msg.contains("long") {
-            RecoveryAction::CustomFix("Truncated overly long paths".to_string())
-        } else if error_msg.contains("corrupted") {
-            RecoveryAction::ConvertToWarning(
+        match ErrorClass::of(error) {
+            ErrorClass::Timeout => {
+                RecoveryAction::CustomFix("Extended timeout for large package".to_string())
+            }
+            ErrorClass::Permission => {
+                RecoveryAction::CustomFix("Applied safe permission defaults".to_string())
+            }
+            ErrorClass::PathTooLong => {
+                RecoveryAction::CustomFix("Truncated overly long paths".to_string())
+            }
+            ErrorClass::Corrupted => RecoveryAction::ConvertToWarning(
                 "Auto-recovery: Skipped corrupted sections".to_string(),
-            )
-        } else {
+            ),
             // Fall back to warning
-            self.determine_warning_action(error)
+            _ => self.determine_warning_action(error),
         }
     }
 
     /// Determine skip action for error
     fn determine_skip_action(&self, error: &Error) -> RecoveryAction {
-        let error_msg = error.to_string().to_lowercase();
-
-        if error_msg.contains("manifest") {
+        match ErrorClass::of(error) {
             // Don't skip manifest errors - they're critical
-            RecoveryAction::ConvertToWarning("Manifest issues detected but continuing".to_string())
-        } else if error_msg.contains("corrupted") || error_msg.contains("invalid") {
-            RecoveryAction::Skip
-        } else {
-            RecoveryAction::ConvertToWarning(
+            ErrorClass::Manifest => RecoveryAction::ConvertToWarning(
+                "Manifest issues detected but continuing".to_string(),
+            ),
+            ErrorClass::Corrupted | ErrorClass::Invalid => RecoveryAction::Skip,
+            _ => RecoveryAction::ConvertToWarning(
                 "Skipping problematic validation component".to_string(),
-            )
+            ),
         }
     }
 
@@ -357,3 +605,144 @@ impl RecoveryPresets {
         ErrorRecoveryManager::new(RecoveryStrategy::ContinueWithWarnings).with_max_errors(100)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid_file(message: &str) -> Error {
+        InstallError::InvalidPackageFile {
+            path: "pkg.sp".to_string(),
+            message: message.to_string(),
+        }
+        .into()
+    }
+
+    fn action(strategy: RecoveryStrategy, error: &Error) -> RecoveryAction {
+        ErrorRecoveryManager::new(strategy)
+            .handle_error(error)
+            .unwrap()
+    }
+
+    #[test]
+    fn recovery_follows_the_error_variant_not_its_wording() {
+        // Messages that mention other failure kinds do not change the outcome
+        for message in ["timeout while reading", "bad utf-8", "checksum", "manifest"] {
+            assert!(matches!(
+                action(RecoveryStrategy::SkipProblematic, &invalid_file(message)),
+                RecoveryAction::Skip
+            ));
+            assert!(matches!(
+                action(RecoveryStrategy::AutoRecover, &invalid_file(message)),
+                RecoveryAction::ConvertToWarning(warning) if warning.starts_with("Package has corruption")
+            ));
+        }
+
+        let timeout: Error = InstallError::OperationTimeout {
+            message: "validation".to_string(),
+        }
+        .into();
+        assert!(matches!(
+            action(RecoveryStrategy::AutoRecover, &timeout),
+            RecoveryAction::CustomFix(fix) if fix.contains("timeout")
+        ));
+
+        let checksum: Error = NetworkError::ChecksumMismatch {
+            expected: "a".to_string(),
+            actual: "b".to_string(),
+        }
+        .into();
+        assert!(matches!(
+            action(RecoveryStrategy::ContinueWithWarnings, &checksum),
+            RecoveryAction::ConvertToWarning(warning) if warning.contains("checksum")
+        ));
+
+        let manifest: Error = PackageError::InvalidManifest {
+            message: "corrupted".to_string(),
+        }
+        .into();
+        assert!(matches!(
+            action(RecoveryStrategy::SkipProblematic, &manifest),
+            RecoveryAction::ConvertToWarning(warning) if warning.starts_with("Manifest")
+        ));
+
+        let encoding: Error = std::io::Error::new(ErrorKind::InvalidData, "stream").into();
+        assert!(matches!(
+            action(RecoveryStrategy::ContinueWithWarnings, &encoding),
+            RecoveryAction::ConvertToWarning(warning) if warning.contains("encoding")
+        ));
+
+        // Opaque errors still fall back to their message
+        let opaque = Error::internal("archive corrupted");
+        assert!(matches!(
+            action(RecoveryStrategy::SkipProblematic, &opaque),
+            RecoveryAction::Skip
+        ));
+    }
+
+    #[test]
+    fn custom_handlers_dispatch_on_error_type() {
+        let mut manager = ErrorRecoveryManager::new(RecoveryStrategy::FailFast);
+        manager.add_handler(ErrorDomain::Install, |_| RecoveryAction::Retry);
+        manager.add_handler(HandlerKey::code("install.invalid_package_file"), |_| {
+            RecoveryAction::Skip
+        });
+
+        let exact = manager.handle_error(&invalid_file("anything")).unwrap();
+        assert!(matches!(exact, RecoveryAction::Skip));
+
+        let timeout: Error = InstallError::OperationTimeout {
+            message: "validation".to_string(),
+        }
+        .into();
+        assert!(matches!(
+            manager.handle_error(&timeout).unwrap(),
+            RecoveryAction::Retry
+        ));
+
+        let storage: Error = StorageError::IoError {
+            message: "install".to_string(),
+        }
+        .into();
+        assert!(matches!(
+            manager.handle_error(&storage).unwrap(),
+            RecoveryAction::Fail
+        ));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn legacy_handlers_no_longer_cross_fire() {
+        let mut manager = ErrorRecoveryManager::new(RecoveryStrategy::FailFast);
+        // "io" used to fire on any message containing "io", such as
+        // "validation" or "permission"
+        manager.add_custom_handler("io".to_string(), |_| RecoveryAction::Retry);
+        manager.add_custom_handler("permission".to_string(), |_| RecoveryAction::Skip);
+        assert_eq!(
+            HandlerKey::from_legacy("io"),
+            HandlerKey::Domain(ErrorDomain::Io)
+        );
+
+        let io: Error = std::io::Error::new(ErrorKind::NotFound, "missing").into();
+        assert!(matches!(
+            manager.handle_error(&io).unwrap(),
+            RecoveryAction::Retry
+        ));
+
+        let denied: Error = StorageError::PermissionDenied {
+            path: "/opt/pm/live".to_string(),
+        }
+        .into();
+        assert!(matches!(
+            manager.handle_error(&denied).unwrap(),
+            RecoveryAction::Fail
+        ));
+
+        // Message text only selects handlers for internal errors
+        let internal = Error::internal("permission check failed during validation");
+        assert!(matches!(
+            manager.handle_error(&internal).unwrap(),
+            RecoveryAction::Skip
+        ));
+    }
+}
@@ -23,6 +23,19 @@ pub struct PackageDownloadConfig {
     pub chunk_timeout: Duration,
     /// Minimum chunk size for resumable downloads (default: 1MB)
     pub min_chunk_size: u64,
+    /// Download speed cap in bytes per second (default: unlimited)
+    ///
+    /// The cap applies to each download on its own, so with N concurrent
+    /// downloads the aggregate rate can reach N times this value unless the
+    /// downloads also share a limiter. Zero disables the cap.
+    pub max_bytes_per_sec: Option<u64>,
+    /// Connections used to fetch a single file in byte ranges (default: 1)
+    ///
+    /// Values above 1 only take effect for fresh downloads from servers
+    /// advertising `Accept-Ranges: bytes`, and each connection after the
+    /// first needs a free download permit; otherwise the file is fetched
+    /// over one connection.
+    pub connections_per_file: usize,
     /// Resource manager
     pub resources: Arc<ResourceManager>,
 }
@@ -36,6 +49,8 @@ impl Default for PackageDownloadConfig {
             retry_config: RetryConfig::default(),
             chunk_timeout: Duration::from_secs(30),
             min_chunk_size: 1024 * 1024, // 1MB
+            max_bytes_per_sec: None,
+            connections_per_file: 1,
             resources: Arc::new(ResourceManager::default()),
         }
     }
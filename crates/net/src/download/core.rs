@@ -4,7 +4,8 @@ use super::config::{
     DownloadResult, PackageDownloadConfig, PackageDownloadRequest, PackageDownloadResult,
     StreamParams,
 };
-use super::resume::get_resume_offset;
+use super::ranged::{download_ranges, plan_ranges, ranged_path, RangePlan};
+use super::resume::{get_resume_offset, partial_path};
 use super::retry::calculate_backoff_delay;
 use super::stream::{download_file_simple, stream_download};
 use super::validation::{validate_response, validate_url};
@@ -54,6 +55,23 @@ impl PackageDownloader {
         })
     }
 
+    /// Create a downloader that sends requests through an existing client
+    ///
+    /// The client's timeouts, retries and credentials apply instead of the
+    /// download-specific defaults [`Self::new`] configures.
+    #[must_use]
+    pub fn with_client(
+        config: PackageDownloadConfig,
+        client: NetClient,
+        progress_manager: sps2_events::ProgressManager,
+    ) -> Self {
+        Self {
+            config,
+            client,
+            progress_manager,
+        }
+    }
+
     /// Create with default configuration
     ///
     /// # Errors
@@ -350,12 +368,13 @@ impl PackageDownloader {
                     // Emit retry event with progress preservation
                     {
                         // Get current progress from partial download
-                        let accumulated_bytes =
-                            if let Ok(metadata) = tokio::fs::metadata(dest_path).await {
-                                metadata.len()
-                            } else {
-                                0
-                            };
+                        let accumulated_bytes = if let Ok(metadata) =
+                            tokio::fs::metadata(partial_path(dest_path)).await
+                        {
+                            metadata.len()
+                        } else {
+                            0
+                        };
 
                         tx.emit(AppEvent::Progress(sps2_events::ProgressEvent::Paused {
                             id: progress_tracker_id.clone(),
@@ -413,42 +432,21 @@ impl PackageDownloader {
         package: Option<&str>,
         tx: &EventSender,
     ) -> Result<DownloadResult, Error> {
-        // Check if partial file exists
-        let resume_offset = get_resume_offset(&self.config, dest_path).await?;
+        // Download into a `.partial` file, resuming one left by an earlier attempt
+        let partial = partial_path(dest_path);
+        let resume_offset = get_resume_offset(&self.config, &partial).await?;
 
-        // Prepare request with range header if resuming
-        let mut headers = Vec::new();
-        if resume_offset > 0 {
-            headers.push(("Range", format!("bytes={resume_offset}-")));
-        }
-
-        // Make HTTP request
-        let response = if headers.is_empty() {
-            self.client.get(url).await?
+        // Split a fresh download across connections when configured
+        let plan = if resume_offset == 0 && self.config.connections_per_file > 1 {
+            plan_ranges(&self.client, &self.config, url).await?
         } else {
-            self.client
-                .get_with_headers(
-                    url,
-                    &headers
-                        .iter()
-                        .map(|(k, v)| (*k, v.as_str()))
-                        .collect::<Vec<_>>(),
-                )
-                .await?
+            None
         };
-
-        // Validate response
-        validate_response(&response, resume_offset > 0)?;
-
-        // Get total size information
-        let content_length = response.content_length().unwrap_or(0);
-        let total_size =
-            if resume_offset > 0 && response.status() == reqwest::StatusCode::PARTIAL_CONTENT {
-                // For partial content, content-length is the remaining bytes
-                resume_offset + content_length
-            } else {
-                content_length
-            };
+        let transfer = match plan {
+            Some(plan) => Transfer::Ranged(plan),
+            None => self.open_stream(url, &partial, resume_offset).await?,
+        };
+        let total_size = transfer.total_size();
 
         // Validate file size limits
         if total_size > self.config.max_file_size {
@@ -475,8 +473,27 @@ impl PackageDownloader {
             parent_progress_id,
             progress_manager: Some(&self.progress_manager),
         };
-        let result =
-            stream_download(&self.config, response, dest_path, resume_offset, &params).await?;
+        // Only a completed, hash-checked download gets its final name
+        let result = match transfer {
+            Transfer::Ranged(plan) => {
+                let ranged = ranged_path(dest_path);
+                let result =
+                    download_ranges(&self.client, &self.config, url, &plan, &ranged, &params)
+                        .await?;
+                tokio_fs::rename(&ranged, dest_path).await?;
+                result
+            }
+            Transfer::Stream {
+                response,
+                resume_offset,
+            } => {
+                let result =
+                    stream_download(&self.config, response, &partial, resume_offset, &params)
+                        .await?;
+                tokio_fs::rename(&partial, dest_path).await?;
+                result
+            }
+        };
 
         tx.emit(AppEvent::Download(DownloadEvent::Completed {
             url: url.to_string(),
@@ -486,6 +503,70 @@ impl PackageDownloader {
 
         Ok(result)
     }
+
+    /// Request `url` over a single connection, resuming `partial` from
+    /// `resume_offset` when it is non-zero
+    async fn open_stream(
+        &self,
+        url: &str,
+        partial: &Path,
+        resume_offset: u64,
+    ) -> Result<Transfer, Error> {
+        // Make HTTP request, with a range header if resuming
+        let response = if resume_offset > 0 {
+            self.client.get_range(url, resume_offset, None).await?
+        } else {
+            self.client.get(url).await?
+        };
+
+        // Validate response, dropping a partial file the server cannot
+        // resume so the next attempt starts from zero
+        let resume_offset = match validate_response(&response, resume_offset) {
+            Ok(offset) => offset,
+            Err(e) => {
+                if resume_offset > 0 {
+                    let _ = tokio_fs::remove_file(partial).await;
+                }
+                return Err(e);
+            }
+        };
+        if resume_offset == 0 {
+            // The server may have ignored the range and sent the whole file
+            match tokio_fs::remove_file(partial).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+
+        Ok(Transfer::Stream {
+            response,
+            resume_offset,
+        })
+    }
+}
+
+/// How a single download attempt fetches its bytes
+enum Transfer {
+    /// Several connections, one per byte range
+    Ranged(RangePlan),
+    /// One response body, appended after `resume_offset` existing bytes
+    Stream {
+        response: reqwest::Response,
+        resume_offset: u64,
+    },
+}
+
+impl Transfer {
+    fn total_size(&self) -> u64 {
+        match self {
+            Self::Ranged(plan) => plan.total_size,
+            // For partial content, content-length is the remaining bytes
+            Self::Stream {
+                response,
+                resume_offset,
+            } => resume_offset + response.content_length().unwrap_or(0),
+        }
+    }
 }
 
 impl Clone for PackageDownloader {
@@ -497,3 +578,320 @@ impl Clone for PackageDownloader {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::download::config::RetryConfig;
+    use crate::download::resume::clean_partial_downloads;
+    use httpmock::prelude::*;
+
+    fn test_downloader() -> PackageDownloader {
+        let config = PackageDownloadConfig {
+            min_chunk_size: 1,
+            retry_config: RetryConfig {
+                max_retries: 1,
+                initial_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(1),
+                ..RetryConfig::default()
+            },
+            ..PackageDownloadConfig::default()
+        };
+        let client = NetClient::new_without_proxies(NetConfig {
+            retry_count: 0,
+            ..NetConfig::default()
+        })
+        .unwrap();
+        PackageDownloader {
+            config,
+            client,
+            progress_manager: sps2_events::ProgressManager::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn leftover_partial_is_never_trusted_as_complete() {
+        let content = b"complete package contents".to_vec();
+        let expected = Hash::from_blake3_bytes(*blake3::hash(&content).as_bytes());
+        let server = MockServer::start();
+        // A resumed request appends the tail to the stale prefix
+        server.mock(|when, then| {
+            when.method(GET).path("/pkg.sp").header_exists("range");
+            then.status(206)
+                .header(
+                    "content-range",
+                    format!("bytes 4-{}/{}", content.len() - 1, content.len()),
+                )
+                .body(&content[4..]);
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/pkg.sp");
+            then.status(200).body(&content);
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("pkg.sp");
+        let partial = partial_path(&dest);
+        tokio_fs::write(&partial, b"JUNK").await.unwrap();
+
+        let (tx, _rx) = sps2_events::channel();
+        let result = test_downloader()
+            .download_with_resume(
+                &server.url("/pkg.sp"),
+                &dest,
+                Some(&expected),
+                "test".to_string(),
+                None,
+                None,
+                tx,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.hash, expected);
+        assert_eq!(tokio_fs::read(&dest).await.unwrap(), content);
+        assert!(!partial.exists());
+    }
+
+    async fn download(
+        server: &MockServer,
+        dest: &Path,
+        content: &[u8],
+    ) -> Result<DownloadResult, Error> {
+        let expected = Hash::from_blake3_bytes(*blake3::hash(content).as_bytes());
+        let (tx, _rx) = sps2_events::channel();
+        test_downloader()
+            .download_with_resume(
+                &server.url("/pkg.sp"),
+                dest,
+                Some(&expected),
+                "test".to_string(),
+                None,
+                None,
+                tx,
+            )
+            .await
+    }
+
+    #[tokio::test]
+    async fn range_ignoring_server_restarts_from_zero() {
+        let content = b"complete package contents".to_vec();
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/pkg.sp").header_exists("range");
+            then.status(200).body(&content);
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("pkg.sp");
+        tokio_fs::write(partial_path(&dest), &content[..8])
+            .await
+            .unwrap();
+
+        let result = download(&server, &dest, &content).await.unwrap();
+        mock.assert_hits(1);
+        assert_eq!(result.size, content.len() as u64);
+        assert_eq!(tokio_fs::read(&dest).await.unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn mismatched_content_range_discards_partial() {
+        let content = b"complete package contents".to_vec();
+        let server = MockServer::start();
+        let resumed = server.mock(|when, then| {
+            when.method(GET).path("/pkg.sp").header_exists("range");
+            then.status(206)
+                .header(
+                    "content-range",
+                    format!("bytes 0-{}/{}", content.len() - 1, content.len()),
+                )
+                .body(&content);
+        });
+        let fresh = server.mock(|when, then| {
+            when.method(GET).path("/pkg.sp");
+            then.status(200).body(&content);
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("pkg.sp");
+        tokio_fs::write(partial_path(&dest), &content[..8])
+            .await
+            .unwrap();
+
+        // The retry starts over instead of appending the whole body
+        download(&server, &dest, &content).await.unwrap();
+        resumed.assert_hits(1);
+        fresh.assert_hits(1);
+        assert_eq!(tokio_fs::read(&dest).await.unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn capped_download_is_paced() {
+        let content = vec![7u8; 96 * 1024];
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/pkg.sp");
+            then.status(200).body(&content);
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut downloader = test_downloader();
+        downloader.config.max_bytes_per_sec = Some(128 * 1024);
+        let (tx, _rx) = sps2_events::channel();
+
+        let started = std::time::Instant::now();
+        let result = downloader
+            .download_with_resume(
+                &server.url("/pkg.sp"),
+                &dir.path().join("pkg.sp"),
+                None,
+                "test".to_string(),
+                None,
+                None,
+                tx,
+            )
+            .await
+            .unwrap();
+
+        // 96 KiB at 128 KiB/s takes about 750ms; the throttle's own tests
+        // check the exact pacing on a paused clock
+        assert_eq!(result.size, content.len() as u64);
+        assert!(started.elapsed() >= Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn ranged_download_splits_large_files() {
+        let content: Vec<u8> = (0..4000u32).map(|i| (i % 251) as u8).collect();
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(httpmock::Method::HEAD).path("/pkg.sp");
+            then.status(200)
+                .header("accept-ranges", "bytes")
+                .header("content-length", content.len().to_string());
+        });
+        let ranges: Vec<_> = (0..4)
+            .map(|index| {
+                let (start, end) = (index * 1000, index * 1000 + 999);
+                server.mock(|when, then| {
+                    when.method(GET)
+                        .path("/pkg.sp")
+                        .header("range", format!("bytes={start}-{end}"));
+                    then.status(206)
+                        .header("content-range", format!("bytes {start}-{end}/4000"))
+                        .body(&content[start..=end]);
+                })
+            })
+            .collect();
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("pkg.sp");
+        let mut downloader = test_downloader();
+        downloader.config.connections_per_file = 4;
+        let expected = Hash::from_blake3_bytes(*blake3::hash(&content).as_bytes());
+        let (tx, _rx) = sps2_events::channel();
+        let result = downloader
+            .download_with_resume(
+                &server.url("/pkg.sp"),
+                &dest,
+                Some(&expected),
+                "test".to_string(),
+                None,
+                None,
+                tx,
+            )
+            .await
+            .unwrap();
+
+        for range in ranges {
+            range.assert_hits(1);
+        }
+        assert_eq!(result.hash, expected);
+        assert_eq!(tokio_fs::read(&dest).await.unwrap(), content);
+        assert!(!ranged_path(&dest).exists());
+        // Extra connections give their permits back
+        assert_eq!(
+            downloader
+                .config
+                .resources
+                .get_resource_availability()
+                .download,
+            4
+        );
+    }
+
+    #[tokio::test]
+    async fn ranged_download_falls_back_without_range_support() {
+        let content = b"complete package contents".to_vec();
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(httpmock::Method::HEAD).path("/pkg.sp");
+            then.status(200)
+                .header("content-length", content.len().to_string());
+        });
+        let whole = server.mock(|when, then| {
+            when.method(GET).path("/pkg.sp");
+            then.status(200).body(&content);
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("pkg.sp");
+        let mut downloader = test_downloader();
+        downloader.config.connections_per_file = 4;
+        let (tx, _rx) = sps2_events::channel();
+        downloader
+            .download_with_resume(
+                &server.url("/pkg.sp"),
+                &dest,
+                None,
+                "test".to_string(),
+                None,
+                None,
+                tx,
+            )
+            .await
+            .unwrap();
+
+        whole.assert_hits(1);
+        assert_eq!(tokio_fs::read(&dest).await.unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn failed_download_leaves_no_final_file() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/pkg.sp");
+            then.status(200).body("tampered");
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("pkg.sp");
+        let (tx, _rx) = sps2_events::channel();
+        let result = test_downloader()
+            .download_with_resume(
+                &server.url("/pkg.sp"),
+                &dest,
+                Some(&Hash::from_blake3_bytes(
+                    *blake3::hash(b"expected").as_bytes(),
+                )),
+                "test".to_string(),
+                None,
+                None,
+                tx,
+            )
+            .await;
+
+        assert!(result.is_err());
+        assert!(!dest.exists());
+
+        // Stray partials are cleaned while complete downloads are kept
+        tokio_fs::write(partial_path(&dest), b"stale")
+            .await
+            .unwrap();
+        tokio_fs::write(dir.path().join("other.sp"), b"done")
+            .await
+            .unwrap();
+        assert_eq!(clean_partial_downloads(dir.path()).await.unwrap(), 1);
+        assert!(dir.path().join("other.sp").exists());
+        assert!(!partial_path(&dest).exists());
+    }
+}
@@ -5,9 +5,11 @@
 
 mod config;
 mod core;
+mod ranged;
 mod resume;
 mod retry;
 mod stream;
+mod throttle;
 mod validation;
 
 // Re-export public types and structs
@@ -16,3 +18,4 @@ pub use config::{
     RetryConfig,
 };
 pub use core::PackageDownloader;
+pub use resume::{clean_partial_downloads, partial_path};
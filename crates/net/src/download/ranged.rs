@@ -0,0 +1,259 @@
+//! Multi-connection downloads of a single file
+//!
+//! A large file on a server that accepts byte ranges is split into ranges
+//! fetched over separate connections and written in place into a
+//! preallocated file. Ranges finish out of order, so the hash is computed
+//! once the file is assembled rather than while streaming.
+
+use super::config::{DownloadResult, PackageDownloadConfig, StreamParams};
+use super::throttle::Throttle;
+use super::validation::validate_range_response;
+use crate::client::NetClient;
+use futures::StreamExt;
+use sps2_errors::{Error, NetworkError};
+use sps2_hash::Hash;
+use sps2_resources::ResourcePermit;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::fs::{self as tokio_fs, OpenOptions};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt, SeekFrom};
+
+/// A file split into byte ranges, one per connection
+pub(super) struct RangePlan {
+    pub total_size: u64,
+    /// Inclusive `(start, end)` byte offsets
+    ranges: Vec<(u64, u64)>,
+    /// Download permits for every connection after the first
+    _permits: Vec<ResourcePermit>,
+}
+
+/// Path a ranged download of `dest_path` is assembled at
+///
+/// It differs from the sequential `.partial` file so a preallocated,
+/// partly filled file is never resumed as if it were a contiguous prefix,
+/// while still being removed by `clean_partial_downloads`.
+pub(super) fn ranged_path(dest_path: &Path) -> PathBuf {
+    let mut name = dest_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".ranged.partial");
+    dest_path.with_file_name(name)
+}
+
+/// Decide whether `url` is worth downloading over several connections
+///
+/// Returns `None`, meaning a single stream should be used, when the server
+/// does not advertise `Accept-Ranges: bytes` or a length, the file is too
+/// small to give every connection at least `min_chunk_size` bytes, or no
+/// download permit is free for a second connection. Each connection after
+/// the first holds a download permit until the plan is dropped.
+///
+/// # Errors
+///
+/// Returns an error if the advertised size exceeds the configured limit.
+pub(super) async fn plan_ranges(
+    client: &NetClient,
+    config: &PackageDownloadConfig,
+    url: &str,
+) -> Result<Option<RangePlan>, Error> {
+    let Ok(response) = client.head(url).await else {
+        return Ok(None);
+    };
+    let headers = response.headers();
+    let accepts_ranges = headers
+        .get(reqwest::header::ACCEPT_RANGES)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("bytes"));
+    let total_size = headers
+        .get(reqwest::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    let (true, Some(total_size)) = (accepts_ranges, total_size) else {
+        return Ok(None);
+    };
+
+    if total_size > config.max_file_size {
+        return Err(NetworkError::FileSizeExceeded {
+            size: total_size,
+            limit: config.max_file_size,
+        }
+        .into());
+    }
+
+    let by_size = total_size / config.min_chunk_size.max(1);
+    let wanted = usize::try_from(by_size)
+        .unwrap_or(usize::MAX)
+        .min(config.connections_per_file);
+    let mut permits = Vec::new();
+    while permits.len() + 1 < wanted {
+        match config.resources.try_acquire_download_permit()? {
+            Some(permit) => permits.push(permit),
+            None => break,
+        }
+    }
+    if permits.is_empty() {
+        return Ok(None);
+    }
+
+    let connections = permits.len() as u64 + 1;
+    let range_size = total_size.div_ceil(connections);
+    let ranges = (0..connections)
+        .map(|index| index * range_size)
+        .take_while(|start| *start < total_size)
+        .map(|start| (start, (start + range_size).min(total_size) - 1))
+        .collect();
+
+    Ok(Some(RangePlan {
+        total_size,
+        ranges,
+        _permits: permits,
+    }))
+}
+
+/// Fetch every range of `plan` concurrently into `dest_path`
+///
+/// The file is removed again if any range fails or the assembled file does
+/// not match the expected hash.
+///
+/// # Errors
+///
+/// Returns an error if a range request fails or is not answered with the
+/// requested bytes, the file cannot be written, or the hash does not match.
+pub(super) async fn download_ranges(
+    client: &NetClient,
+    config: &PackageDownloadConfig,
+    url: &str,
+    plan: &RangePlan,
+    dest_path: &Path,
+    params: &StreamParams<'_>,
+) -> Result<DownloadResult, Error> {
+    let result = assemble(client, config, url, plan, dest_path, params).await;
+    if result.is_err() {
+        let _ = tokio_fs::remove_file(dest_path).await;
+    }
+    result
+}
+
+async fn assemble(
+    client: &NetClient,
+    config: &PackageDownloadConfig,
+    url: &str,
+    plan: &RangePlan,
+    dest_path: &Path,
+    params: &StreamParams<'_>,
+) -> Result<DownloadResult, Error> {
+    let file = tokio_fs::File::create(dest_path).await?;
+    file.set_len(plan.total_size).await?;
+    drop(file);
+
+    // The speed cap applies to the download as a whole
+    let connections = plan.ranges.len() as u64;
+    let per_range_rate = config
+        .max_bytes_per_sec
+        .map(|rate| (rate / connections).max(1));
+    let downloaded = AtomicU64::new(0);
+
+    futures::future::try_join_all(plan.ranges.iter().map(|&(start, end)| {
+        fetch_range(
+            client,
+            url,
+            start,
+            end,
+            dest_path,
+            per_range_rate,
+            &downloaded,
+            params,
+        )
+    }))
+    .await?;
+
+    if let Some(progress_manager) = params.progress_manager {
+        progress_manager.update_progress(
+            &params.progress_tracker_id,
+            plan.total_size,
+            Some(params.total_size),
+            params.event_sender,
+        );
+    }
+
+    let hash = Hash::blake3_hash_file(dest_path).await?;
+    if let Some(expected) = params.expected_hash {
+        if hash != *expected {
+            return Err(NetworkError::ChecksumMismatch {
+                expected: expected.to_hex(),
+                actual: hash.to_hex(),
+            }
+            .into());
+        }
+    }
+
+    Ok(DownloadResult {
+        hash,
+        size: plan.total_size,
+    })
+}
+
+/// Fetch bytes `start..=end` of `url` into the same span of `dest_path`
+#[allow(clippy::too_many_arguments)]
+async fn fetch_range(
+    client: &NetClient,
+    url: &str,
+    start: u64,
+    end: u64,
+    dest_path: &Path,
+    bytes_per_sec: Option<u64>,
+    downloaded: &AtomicU64,
+    params: &StreamParams<'_>,
+) -> Result<(), Error> {
+    let response = client.get_range(url, start, Some(end)).await?;
+    validate_range_response(&response, start)?;
+
+    let mut file = OpenOptions::new().write(true).open(dest_path).await?;
+    file.seek(SeekFrom::Start(start)).await?;
+
+    let expected = end - start + 1;
+    let mut written = 0;
+    let mut throttle = bytes_per_sec.and_then(Throttle::new);
+    let mut last_progress_update = Instant::now();
+    let mut stream = response.bytes_stream();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| NetworkError::DownloadFailed(e.to_string()))?;
+        let len = chunk.len() as u64;
+        if written + len > expected {
+            return Err(NetworkError::ContentLengthMismatch {
+                expected,
+                actual: written + len,
+            }
+            .into());
+        }
+        file.write_all(&chunk).await?;
+        written += len;
+
+        if let Some(throttle) = &mut throttle {
+            throttle.consume(len).await;
+        }
+
+        let current = downloaded.fetch_add(len, Ordering::Relaxed) + len;
+        if last_progress_update.elapsed() >= Duration::from_millis(50) {
+            if let Some(progress_manager) = params.progress_manager {
+                progress_manager.update_progress(
+                    &params.progress_tracker_id,
+                    current,
+                    Some(params.total_size),
+                    params.event_sender,
+                );
+            }
+            last_progress_update = Instant::now();
+        }
+    }
+
+    if written != expected {
+        return Err(NetworkError::ContentLengthMismatch {
+            expected,
+            actual: written,
+        }
+        .into());
+    }
+    file.flush().await?;
+    Ok(())
+}
@@ -2,10 +2,55 @@
 
 use super::config::PackageDownloadConfig;
 use sps2_errors::Error;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::fs as tokio_fs;
 use tokio::io::AsyncReadExt;
 
+/// Extension marking an incomplete download
+const PARTIAL_EXTENSION: &str = "partial";
+
+/// Path an in-progress download of `dest_path` is written to
+///
+/// The file only gets its final name once the download has completed and
+/// its hash has been checked, so a leftover `.partial` file is never
+/// mistaken for a complete download.
+#[must_use]
+pub fn partial_path(dest_path: &Path) -> PathBuf {
+    let mut name = dest_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(PARTIAL_EXTENSION);
+    dest_path.with_file_name(name)
+}
+
+/// Remove leftover `.partial` downloads from a download directory
+///
+/// Returns the number of files removed.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be read or a partial file
+/// cannot be removed.
+pub async fn clean_partial_downloads(dir: &Path) -> Result<usize, Error> {
+    let mut removed = 0;
+    let mut entries = match tokio_fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == PARTIAL_EXTENSION)
+            && entry.file_type().await?.is_file()
+        {
+            tokio_fs::remove_file(&path).await?;
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
+
 /// Get the offset for resuming a download
 pub(super) async fn get_resume_offset(
     config: &PackageDownloadConfig,
@@ -2,6 +2,7 @@
 
 use super::config::{DownloadResult, StreamParams};
 use super::resume::calculate_existing_file_hash;
+use super::throttle::Throttle;
 use futures::StreamExt;
 use sps2_errors::{Error, NetworkError};
 
@@ -52,6 +53,9 @@ pub(super) async fn stream_download(
         hasher = existing_hash;
     }
 
+    // Pace reads when a speed cap is configured
+    let mut throttle = config.max_bytes_per_sec.and_then(Throttle::new);
+
     // Stream the response
     let mut stream = response.bytes_stream();
 
@@ -64,6 +68,10 @@ pub(super) async fn stream_download(
         // Write to file
         file.write_all(&chunk).await?;
 
+        if let Some(throttle) = &mut throttle {
+            throttle.consume(chunk.len() as u64).await;
+        }
+
         // Update progress
         let current_downloaded =
             downloaded.fetch_add(chunk.len() as u64, Ordering::Relaxed) + chunk.len() as u64;
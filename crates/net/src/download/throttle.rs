@@ -0,0 +1,74 @@
+//! Bandwidth limiting for a single download
+
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Token bucket pacing a download to a fixed number of bytes per second
+///
+/// The bucket starts empty and holds at most one second of tokens, so a
+/// download never runs ahead of its cap by more than a second's worth of
+/// data, even after the server stalls for a while.
+#[derive(Debug)]
+pub(super) struct Throttle {
+    bytes_per_sec: u64,
+    /// Available bytes; negative while the download is ahead of its budget
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl Throttle {
+    /// Create a throttle for `bytes_per_sec`, or `None` for a zero cap
+    pub(super) fn new(bytes_per_sec: u64) -> Option<Self> {
+        (bytes_per_sec > 0).then(|| Self {
+            bytes_per_sec,
+            tokens: 0.0,
+            last_refill: Instant::now(),
+        })
+    }
+
+    /// Account for `bytes` just read, sleeping until they fit the budget
+    #[allow(clippy::cast_precision_loss)]
+    pub(super) async fn consume(&mut self, bytes: u64) {
+        let rate = self.bytes_per_sec as f64;
+        let now = Instant::now();
+        let refill = now.duration_since(self.last_refill).as_secs_f64() * rate;
+        self.tokens = (self.tokens + refill).min(rate) - bytes as f64;
+        self.last_refill = now;
+
+        if self.tokens < 0.0 {
+            tokio::time::sleep(Duration::from_secs_f64(-self.tokens / rate)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn reads_are_paced_to_the_cap() {
+        let started = Instant::now();
+        let mut throttle = Throttle::new(1000).unwrap();
+        for _ in 0..4 {
+            throttle.consume(500).await;
+        }
+        assert_eq!(started.elapsed(), Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_time_banks_at_most_one_second() {
+        let mut throttle = Throttle::new(1000).unwrap();
+        tokio::time::advance(Duration::from_secs(5)).await;
+
+        let started = Instant::now();
+        throttle.consume(1000).await;
+        assert_eq!(started.elapsed(), Duration::ZERO);
+        throttle.consume(1000).await;
+        assert_eq!(started.elapsed(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn zero_cap_disables_throttling() {
+        assert!(Throttle::new(0).is_none());
+    }
+}
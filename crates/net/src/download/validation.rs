@@ -17,17 +17,27 @@ pub(super) fn validate_url(url: &str) -> Result<String, Error> {
 }
 
 /// Validate HTTP response for download
+///
+/// When resuming at `resume_offset`, the server must answer `206 Partial
+/// Content` with a `Content-Range` starting at that offset. A server that
+/// ignores the `Range` header and sends the whole file with `200 OK` is
+/// accepted as a fresh download. Returns the offset the response body
+/// starts at.
 pub(super) fn validate_response(
     response: &reqwest::Response,
-    is_resume: bool,
-) -> Result<(), Error> {
+    resume_offset: u64,
+) -> Result<u64, Error> {
     let status = response.status();
 
-    if is_resume {
-        if status != reqwest::StatusCode::PARTIAL_CONTENT {
-            return Err(NetworkError::PartialContentNotSupported.into());
+    if resume_offset > 0 {
+        if status == reqwest::StatusCode::OK {
+            return Ok(0);
         }
-    } else if !status.is_success() {
+        validate_range_response(response, resume_offset)?;
+        return Ok(resume_offset);
+    }
+
+    if !status.is_success() {
         return Err(NetworkError::HttpError {
             status: status.as_u16(),
             message: status.to_string(),
@@ -35,5 +45,38 @@ pub(super) fn validate_response(
         .into());
     }
 
-    Ok(())
+    Ok(0)
+}
+
+/// Check that `response` is a `206 Partial Content` starting at byte `start`
+pub(super) fn validate_range_response(
+    response: &reqwest::Response,
+    start: u64,
+) -> Result<(), Error> {
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        return Err(NetworkError::PartialContentNotSupported.into());
+    }
+
+    let served = response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(content_range_start);
+    match served {
+        Some(served) if served == start => Ok(()),
+        Some(served) => Err(NetworkError::RangeRequestFailed {
+            message: format!("requested byte {start} but server sent from {served}"),
+        }
+        .into()),
+        None => Err(NetworkError::RangeRequestFailed {
+            message: "missing or invalid Content-Range header".to_string(),
+        }
+        .into()),
+    }
+}
+
+/// First byte of a `Content-Range: bytes <start>-<end>/<total>` value
+fn content_range_start(value: &str) -> Option<u64> {
+    let (start, _) = value.trim().strip_prefix("bytes ")?.split_once('-')?;
+    start.trim().parse().ok()
 }
@@ -8,12 +8,16 @@
 
 mod client;
 mod download;
+mod mirrors;
 
-pub use client::{NetClient, NetConfig};
+pub use client::{
+    CredentialScope, HostCredential, NetClient, NetConfig, RequestObserver, RequestRecord,
+};
 pub use download::{
-    DownloadResult, PackageDownloadConfig, PackageDownloadRequest, PackageDownloadResult,
-    PackageDownloader, RetryConfig,
+    clean_partial_downloads, partial_path, DownloadResult, PackageDownloadConfig,
+    PackageDownloadRequest, PackageDownloadResult, PackageDownloader, RetryConfig,
 };
+pub use mirrors::{MirrorRanking, MirrorStatus};
 
 use sps2_errors::{Error, NetworkError};
 use sps2_events::{AppEvent, EventEmitter, EventSender, GeneralEvent};
@@ -76,7 +80,22 @@ pub async fn fetch_text(client: &NetClient, url: &str, tx: &EventSender) -> Resu
         .map_err(|e| NetworkError::DownloadFailed(e.to_string()).into())
 }
 
-/// Conditionally fetch text content from a URL with `ETag` support
+/// Text fetched by [`fetch_text_conditional`], with its cache validators
+#[derive(Debug, Clone)]
+pub struct ConditionalText {
+    /// Response body
+    pub content: String,
+    /// `ETag` response header, if sent
+    pub etag: Option<String>,
+    /// `Last-Modified` response header, if sent
+    pub last_modified: Option<String>,
+}
+
+/// Conditionally fetch text content from a URL with `ETag` and
+/// `Last-Modified` support
+///
+/// Sends `If-None-Match` and `If-Modified-Since` for whichever validators
+/// are given; with neither, this is a plain fetch.
 ///
 /// # Errors
 ///
@@ -86,13 +105,14 @@ pub async fn fetch_text(client: &NetClient, url: &str, tx: &EventSender) -> Resu
 /// # Returns
 ///
 /// Returns `Ok(None)` if the server responds with 304 Not Modified,
-/// `Ok(Some((content, etag)))` if new content is available.
+/// `Ok(Some(text))` if new content is available.
 pub async fn fetch_text_conditional(
     client: &NetClient,
     url: &str,
     etag: Option<&str>,
+    last_modified: Option<&str>,
     tx: &EventSender,
-) -> Result<Option<(String, Option<String>)>, Error> {
+) -> Result<Option<ConditionalText>, Error> {
     tx.emit(AppEvent::General(GeneralEvent::debug(format!(
         "Fetching text from {url} with conditional request"
     ))));
@@ -101,6 +121,9 @@ pub async fn fetch_text_conditional(
     if let Some(etag_value) = etag {
         headers.push(("If-None-Match", etag_value));
     }
+    if let Some(last_modified_value) = last_modified {
+        headers.push(("If-Modified-Since", last_modified_value));
+    }
 
     let response = client.get_with_headers(url, &headers).await?;
 
@@ -120,19 +143,27 @@ pub async fn fetch_text_conditional(
         .into());
     }
 
-    // Extract new ETag from response headers
-    let new_etag = response
-        .headers()
-        .get("etag")
-        .and_then(|v| v.to_str().ok())
-        .map(String::from);
+    // Extract new validators from response headers
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+    };
+    let etag = header("etag");
+    let last_modified = header("last-modified");
 
     let content = response
         .text()
         .await
         .map_err(|e| NetworkError::DownloadFailed(e.to_string()))?;
 
-    Ok(Some((content, new_etag)))
+    Ok(Some(ConditionalText {
+        content,
+        etag,
+        last_modified,
+    }))
 }
 
 /// Fetch binary content from a URL
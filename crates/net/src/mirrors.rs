@@ -0,0 +1,449 @@
+//! Mirror ranking by latency and health
+//!
+//! A [`MirrorRanking`] tracks every mirror configured for a repository. Probes
+//! and real requests feed it latencies and failures; callers ask it for the
+//! order in which mirrors should be tried. Failing mirrors are pushed to the
+//! back with exponential backoff until they answer again.
+
+use crate::client::NetClient;
+use serde::Serialize;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Health snapshot of one mirror, suitable for status output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MirrorStatus {
+    /// Mirror base URL
+    pub url: String,
+    /// Latency of the last successful request
+    pub latency: Option<Duration>,
+    /// When the mirror last failed
+    pub last_failure: Option<SystemTime>,
+    /// Failures since the last success
+    pub consecutive_failures: u32,
+    /// Time left before the mirror is preferred again
+    pub backoff_remaining: Option<Duration>,
+}
+
+impl MirrorStatus {
+    /// Whether the mirror is currently deprioritized
+    #[must_use]
+    pub fn is_backed_off(&self) -> bool {
+        self.backoff_remaining.is_some()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MirrorHealth {
+    url: String,
+    latency: Option<Duration>,
+    last_failure: Option<SystemTime>,
+    consecutive_failures: u32,
+    backoff_until: Option<Instant>,
+}
+
+#[derive(Debug)]
+struct RankingState {
+    /// Mirrors in configured order
+    mirrors: Vec<MirrorHealth>,
+    last_probe: Option<Instant>,
+}
+
+/// Shared ranking of the mirrors for one repository
+///
+/// Cloning is cheap; clones share the same health data.
+#[derive(Debug, Clone)]
+pub struct MirrorRanking {
+    state: Arc<Mutex<RankingState>>,
+    base_backoff: Duration,
+    max_backoff: Duration,
+    probe_interval: Duration,
+    probe_path: String,
+}
+
+impl MirrorRanking {
+    /// Create a ranking for `urls`, keeping their order until health is known
+    #[must_use]
+    pub fn new<I, S>(urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut mirrors: Vec<MirrorHealth> = Vec::new();
+        for url in urls {
+            let url = url.into().trim_end_matches('/').to_string();
+            if !mirrors.iter().any(|mirror| mirror.url == url) {
+                mirrors.push(MirrorHealth {
+                    url,
+                    latency: None,
+                    last_failure: None,
+                    consecutive_failures: 0,
+                    backoff_until: None,
+                });
+            }
+        }
+
+        Self {
+            state: Arc::new(Mutex::new(RankingState {
+                mirrors,
+                last_probe: None,
+            })),
+            base_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(30 * 60),
+            probe_interval: Duration::from_secs(10 * 60),
+            probe_path: "index.json".to_string(),
+        }
+    }
+
+    /// Set the first backoff delay and its upper bound
+    ///
+    /// Each further consecutive failure doubles the delay up to `max`.
+    #[must_use]
+    pub fn with_backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.base_backoff = base;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Set how often [`Self::probe_if_due`] re-probes the mirrors
+    #[must_use]
+    pub fn with_probe_interval(mut self, interval: Duration) -> Self {
+        self.probe_interval = interval;
+        self
+    }
+
+    /// Set the path, relative to a mirror's base URL, used for probes
+    #[must_use]
+    pub fn with_probe_path(mut self, path: impl Into<String>) -> Self {
+        self.probe_path = path.into().trim_start_matches('/').to_string();
+        self
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RankingState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Whether no mirrors are configured
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().mirrors.is_empty()
+    }
+
+    /// Record a successful request to the mirror serving `url`
+    pub fn record_success(&self, url: &str, latency: Duration) {
+        let mut state = self.lock();
+        if let Some(mirror) = find_mirror(&mut state.mirrors, url) {
+            mirror.latency = Some(latency);
+            mirror.consecutive_failures = 0;
+            mirror.backoff_until = None;
+        }
+    }
+
+    /// Record a failed request to the mirror serving `url`
+    pub fn record_failure(&self, url: &str) {
+        self.record_failure_at(url, Instant::now());
+    }
+
+    fn record_failure_at(&self, url: &str, now: Instant) {
+        let mut state = self.lock();
+        if let Some(mirror) = find_mirror(&mut state.mirrors, url) {
+            mirror.consecutive_failures = mirror.consecutive_failures.saturating_add(1);
+            mirror.last_failure = Some(SystemTime::now());
+            let exponent = (mirror.consecutive_failures - 1).min(16);
+            let backoff = self
+                .base_backoff
+                .saturating_mul(1 << exponent)
+                .min(self.max_backoff);
+            mirror.backoff_until = Some(now + backoff);
+        }
+    }
+
+    /// Mirror base URLs in the order they should be tried
+    ///
+    /// Healthy mirrors come first, fastest first, followed by mirrors that
+    /// were never measured in configured order. Backed-off mirrors are last,
+    /// soonest-available first, so a request still has somewhere to go when
+    /// every mirror is failing.
+    #[must_use]
+    pub fn ranked(&self) -> Vec<String> {
+        self.ranked_at(Instant::now())
+    }
+
+    fn ranked_at(&self, now: Instant) -> Vec<String> {
+        let state = self.lock();
+        let mut order: Vec<(usize, &MirrorHealth)> = state.mirrors.iter().enumerate().collect();
+        order.sort_by_key(|(position, mirror)| {
+            let backoff = mirror.backoff_until.filter(|until| *until > now);
+            (
+                backoff.is_some(),
+                backoff,
+                mirror.latency.is_none(),
+                mirror.latency,
+                *position,
+            )
+        });
+        order
+            .into_iter()
+            .map(|(_, mirror)| mirror.url.clone())
+            .collect()
+    }
+
+    /// Candidate URLs for `url`, rebased onto each mirror in ranked order
+    ///
+    /// URLs that do not belong to any known mirror are returned unchanged.
+    #[must_use]
+    pub fn candidates(&self, url: &str) -> Vec<String> {
+        if self.suffix_of(url).is_none() {
+            return vec![url.to_string()];
+        }
+        self.ranked()
+            .iter()
+            .map(|mirror| self.rebase(url, mirror))
+            .collect()
+    }
+
+    /// Move `url` onto the mirror serving `onto`
+    ///
+    /// Used to keep companion files, such as signatures, on the same mirror
+    /// as the candidate being tried. Returns `url` unchanged if either URL is
+    /// not served by a known mirror.
+    #[must_use]
+    pub fn rebase(&self, url: &str, onto: &str) -> String {
+        let state = self.lock();
+        let target = state
+            .mirrors
+            .iter()
+            .find(|mirror| strip_mirror(&mirror.url, onto).is_some());
+        match (target, Self::suffix_in(&state.mirrors, url)) {
+            (Some(target), Some(suffix)) => format!("{}{suffix}", target.url),
+            _ => url.to_string(),
+        }
+    }
+
+    fn suffix_of(&self, url: &str) -> Option<String> {
+        Self::suffix_in(&self.lock().mirrors, url)
+    }
+
+    fn suffix_in(mirrors: &[MirrorHealth], url: &str) -> Option<String> {
+        mirrors
+            .iter()
+            .find_map(|mirror| strip_mirror(&mirror.url, url))
+            .map(str::to_string)
+    }
+
+    /// Current health of every mirror, in ranked order
+    #[must_use]
+    pub fn status(&self) -> Vec<MirrorStatus> {
+        let now = Instant::now();
+        let ranked = self.ranked_at(now);
+        let state = self.lock();
+        ranked
+            .iter()
+            .filter_map(|url| state.mirrors.iter().find(|mirror| &mirror.url == url))
+            .map(|mirror| MirrorStatus {
+                url: mirror.url.clone(),
+                latency: mirror.latency,
+                last_failure: mirror.last_failure,
+                consecutive_failures: mirror.consecutive_failures,
+                backoff_remaining: mirror
+                    .backoff_until
+                    .and_then(|until| until.checked_duration_since(now))
+                    .filter(|remaining| !remaining.is_zero()),
+            })
+            .collect()
+    }
+
+    /// Probe every mirror with a HEAD request and record the results
+    pub async fn probe(&self, client: &NetClient) {
+        let urls: Vec<String> = self
+            .lock()
+            .mirrors
+            .iter()
+            .map(|mirror| mirror.url.clone())
+            .collect();
+
+        let probes = urls.iter().map(|base| async move {
+            let target = format!("{base}/{}", self.probe_path);
+            let started = Instant::now();
+            let healthy = client
+                .head(&target)
+                .await
+                .is_ok_and(|response| response.status().is_success());
+            (base, healthy, started.elapsed())
+        });
+
+        for (base, healthy, latency) in futures::future::join_all(probes).await {
+            if healthy {
+                self.record_success(base, latency);
+            } else {
+                self.record_failure(base);
+            }
+        }
+
+        self.lock().last_probe = Some(Instant::now());
+    }
+
+    /// Probe the mirrors if the last probe is older than the probe interval
+    pub async fn probe_if_due(&self, client: &NetClient) {
+        let due = self
+            .lock()
+            .last_probe
+            .is_none_or(|probed| probed.elapsed() >= self.probe_interval);
+        if due {
+            self.probe(client).await;
+        }
+    }
+}
+
+/// The part of `url` after `base`, if `url` is served by that mirror
+fn strip_mirror<'a>(base: &str, url: &'a str) -> Option<&'a str> {
+    url.strip_prefix(base)
+        .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+fn find_mirror<'a>(mirrors: &'a mut [MirrorHealth], url: &str) -> Option<&'a mut MirrorHealth> {
+    let url = url.trim_end_matches('/');
+    mirrors
+        .iter_mut()
+        .find(|mirror| strip_mirror(&mirror.url, url).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    fn ranking() -> MirrorRanking {
+        MirrorRanking::new([
+            "https://a.example",
+            "https://b.example",
+            "https://c.example",
+        ])
+        .with_backoff(Duration::from_secs(10), Duration::from_secs(60))
+    }
+
+    #[test]
+    fn fastest_mirror_is_preferred() {
+        let mirrors = ranking();
+        assert_eq!(
+            mirrors.ranked(),
+            [
+                "https://a.example",
+                "https://b.example",
+                "https://c.example"
+            ]
+        );
+
+        mirrors.record_success("https://a.example", Duration::from_millis(300));
+        mirrors.record_success("https://c.example/index.json", Duration::from_millis(20));
+
+        // Measured mirrors first by latency, unmeasured ones keep their order
+        assert_eq!(
+            mirrors.ranked(),
+            [
+                "https://c.example",
+                "https://a.example",
+                "https://b.example"
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_mirror_backs_off_then_recovers() {
+        let mirrors = ranking();
+        mirrors.record_success("https://a.example", Duration::from_millis(10));
+        mirrors.record_success("https://b.example", Duration::from_millis(50));
+
+        let now = Instant::now();
+        mirrors.record_failure_at("https://a.example", now);
+        assert_eq!(mirrors.ranked_at(now)[2], "https://a.example");
+        assert!(mirrors.status()[2].is_backed_off());
+
+        // The backoff expires after the base delay
+        assert_eq!(
+            mirrors.ranked_at(now + Duration::from_secs(11))[0],
+            "https://a.example"
+        );
+
+        // Consecutive failures double the delay, up to the maximum
+        mirrors.record_failure_at("https://a.example", now);
+        mirrors.record_failure_at("https://a.example", now);
+        assert_eq!(
+            mirrors.ranked_at(now + Duration::from_secs(35))[2],
+            "https://a.example"
+        );
+        for _ in 0..10 {
+            mirrors.record_failure_at("https://a.example", now);
+        }
+        assert_eq!(
+            mirrors.ranked_at(now + Duration::from_secs(61))[0],
+            "https://a.example"
+        );
+
+        // A success clears the backoff
+        mirrors.record_success("https://a.example", Duration::from_millis(10));
+        let status = &mirrors.status()[0];
+        assert_eq!(status.url, "https://a.example");
+        assert_eq!(status.consecutive_failures, 0);
+        assert!(!status.is_backed_off());
+        assert!(status.last_failure.is_some());
+    }
+
+    #[test]
+    fn candidates_follow_ranking() {
+        let mirrors = ranking();
+        mirrors.record_success("https://b.example", Duration::from_millis(5));
+
+        assert_eq!(
+            mirrors.candidates("https://a.example/packages/foo.sp"),
+            [
+                "https://b.example/packages/foo.sp",
+                "https://a.example/packages/foo.sp",
+                "https://c.example/packages/foo.sp",
+            ]
+        );
+        assert_eq!(
+            mirrors.candidates("https://a.example.evil/foo.sp"),
+            ["https://a.example.evil/foo.sp"]
+        );
+        assert_eq!(
+            mirrors.rebase(
+                "https://a.example/packages/foo.sp.minisig",
+                "https://c.example/packages/foo.sp"
+            ),
+            "https://c.example/packages/foo.sp.minisig"
+        );
+    }
+
+    #[tokio::test]
+    async fn probe_records_latency_and_failures() {
+        let fast = MockServer::start();
+        let slow = MockServer::start();
+        let broken = MockServer::start();
+        fast.mock(|when, then| {
+            when.method(httpmock::Method::HEAD).path("/index.json");
+            then.status(200);
+        });
+        slow.mock(|when, then| {
+            when.method(httpmock::Method::HEAD).path("/index.json");
+            then.status(200).delay(Duration::from_millis(200));
+        });
+        broken.mock(|when, then| {
+            when.method(httpmock::Method::HEAD).path("/index.json");
+            then.status(503);
+        });
+
+        let mirrors = MirrorRanking::new([broken.base_url(), slow.base_url(), fast.base_url()]);
+        let client = NetClient::new_without_proxies(crate::NetConfig::default()).unwrap();
+        mirrors.probe(&client).await;
+
+        assert_eq!(
+            mirrors.ranked(),
+            [fast.base_url(), slow.base_url(), broken.base_url()]
+        );
+        let status = mirrors.status();
+        assert!(status[1].latency.unwrap() >= Duration::from_millis(200));
+        assert_eq!(status[2].consecutive_failures, 1);
+        assert!(status[2].latency.is_none());
+    }
+}
@@ -50,12 +50,14 @@ pub async fn build(
         output_directory,
     )
     .with_event_sender(ctx.tx.clone())
-    .with_session_id(session_id.clone());
+    .with_session_id(session_id.clone())
+    .with_recipe_commit_epoch()
+    .await;
 
     let builder = configure_builder(ctx, network, jobs);
 
     // Use the builder with custom configuration
-    let result = match builder.build(build_context).await {
+    let result = match Box::pin(builder.build(build_context)).await {
         Ok(result) => result,
         Err(error) => {
             ctx.emit(AppEvent::Build(BuildEvent::Failed {
@@ -176,4 +178,5 @@ fn configure_builder(ctx: &OpsCtx, network: bool, jobs: Option<usize>) -> sps2_b
     sps2_builder::Builder::with_config(builder_config)
         .with_resolver(ctx.resolver.clone())
         .with_store(ctx.store.clone())
+        .with_net(ctx.net.clone())
 }
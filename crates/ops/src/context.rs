@@ -5,11 +5,12 @@ use sps2_config::Config;
 use sps2_errors::Error;
 use sps2_events::{EventEmitter, EventSender};
 use sps2_guard::{
-    derive_post_operation_scope, derive_pre_operation_scope, GuardConfig,
+    derive_post_operation_scope_with_budget, derive_pre_operation_scope, GuardConfig,
     OperationResult as GuardOperationResult, OperationType, StateVerificationGuard,
+    VerificationResult,
 };
 use sps2_index::IndexManager;
-use sps2_net::NetClient;
+use sps2_net::{MirrorRanking, NetClient};
 use sps2_resolver::Resolver;
 use sps2_state::StateManager;
 use sps2_store::PackageStore;
@@ -28,6 +29,8 @@ pub struct OpsCtx {
     pub index: IndexManager,
     /// Network client
     pub net: NetClient,
+    /// Health ranking of the repository mirrors
+    pub mirrors: MirrorRanking,
     /// Dependency resolver
     pub resolver: Resolver,
     /// Package builder
@@ -146,14 +149,10 @@ impl OpsCtx {
             };
 
             // Check pre-verification result
-            if !pre_result.is_valid && self.config.verification.should_fail_on_discrepancy() {
+            if let Some(error) = verification_failure(guard.config(), &pre_result) {
                 // Put guard back before failing
                 *self.guard.borrow_mut() = Some(guard);
-                return Err(sps2_errors::OpsError::VerificationFailed {
-                    discrepancies: pre_result.discrepancies.len(),
-                    state_id: pre_result.state_id.to_string(),
-                }
-                .into());
+                return Err(error);
             }
 
             // Phase 3: Execute the operation
@@ -169,7 +168,24 @@ impl OpsCtx {
             let (op_result, op_metadata) = operation_result;
 
             // Phase 4: Post-operation verification with result-based scoping
-            let post_scope = derive_post_operation_scope(&operation_type, &op_metadata);
+            let (post_scope, trimmed) = derive_post_operation_scope_with_budget(
+                &operation_type,
+                &op_metadata,
+                &guard.config().performance.post_verification_budget,
+            );
+            if !trimmed.is_empty() {
+                self.emit_warning(format!(
+                    "Post-operation verification budget skipped {} package(s) ({} bytes): {}",
+                    trimmed.skipped.len(),
+                    trimmed.skipped_bytes,
+                    trimmed
+                        .skipped
+                        .iter()
+                        .map(|(name, version)| format!("{name}-{version}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
             self.emit_debug(format!(
                 "Running post-operation verification with scope: {post_scope:?}"
             ));
@@ -181,14 +197,10 @@ impl OpsCtx {
             };
 
             // Check post-verification result
-            if !post_result.is_valid && self.config.verification.should_fail_on_discrepancy() {
+            if let Some(error) = verification_failure(guard.config(), &post_result) {
                 // Put guard back before failing
                 *self.guard.borrow_mut() = Some(guard);
-                return Err(sps2_errors::OpsError::VerificationFailed {
-                    discrepancies: post_result.discrepancies.len(),
-                    state_id: post_result.state_id.to_string(),
-                }
-                .into());
+                return Err(error);
             }
 
             // Put guard back and return result
@@ -220,6 +232,21 @@ impl OpsCtx {
         result
     }
 
+    /// Guard configuration derived from the user's config
+    ///
+    /// The top-level [guard] section takes precedence over the legacy
+    /// [verification.guard] section.
+    #[must_use]
+    pub fn guard_config(&self) -> GuardConfig {
+        if let Some(top_level_guard) = &self.config.guard {
+            // Use top-level [guard] configuration (OPS-64 approach)
+            top_level_guard.into()
+        } else {
+            // Use legacy [verification.guard] configuration (OPS-65 approach)
+            (&self.config.verification).into()
+        }
+    }
+
     /// Initialize the state verification guard if enabled in config
     ///
     /// This supports both configuration approaches:
@@ -259,16 +286,12 @@ impl OpsCtx {
         // Validate the guard configuration first
         self.config.validate_guard_config()?;
 
-        // Convert user configuration to guard configuration
-        let guard_config: GuardConfig = if let Some(top_level_guard) = &self.config.guard {
-            // Use top-level [guard] configuration (OPS-64 approach)
+        if self.config.guard.is_some() {
             self.emit_debug("Using top-level [guard] configuration approach");
-            top_level_guard.into()
         } else {
-            // Use legacy [verification.guard] configuration (OPS-65 approach)
             self.emit_debug("Using legacy [verification.guard] configuration approach");
-            (&self.config.verification).into()
-        };
+        }
+        let guard_config = self.guard_config();
 
         // Build the guard with the user's complete configuration
         let guard = StateVerificationGuard::builder()
@@ -293,22 +316,20 @@ impl OpsCtx {
         // Take the guard out of the RefCell to avoid holding borrow across await
         let guard_option = self.guard.borrow_mut().take();
 
-        if let Some(mut guard) = guard_option {
+        if let Some(guard) = guard_option {
             let result = if self.config.verification.should_auto_heal() {
                 guard.verify_and_heal(&self.config).await?
             } else {
                 guard.verify_only().await?
             };
 
+            let failure = verification_failure(guard.config(), &result);
+
             // Put the guard back before checking result
             *self.guard.borrow_mut() = Some(guard);
 
-            if !result.is_valid && self.config.verification.should_fail_on_discrepancy() {
-                return Err(sps2_errors::OpsError::VerificationFailed {
-                    discrepancies: result.discrepancies.len(),
-                    state_id: result.state_id.to_string(),
-                }
-                .into());
+            if let Some(error) = failure {
+                return Err(error);
             }
         }
         Ok(())
@@ -774,11 +795,14 @@ impl OpsContextBuilder {
                 component: "config".to_string(),
             })?;
 
+        let mirrors = crate::repository::mirror_ranking(&config);
+
         Ok(OpsCtx {
             store,
             state,
             index,
             net,
+            mirrors,
             resolver,
             builder,
             tx,
@@ -796,11 +820,53 @@ impl Default for OpsContextBuilder {
     }
 }
 
+/// Error failing the current operation because of `result`, if any
+///
+/// Uses the guard's own configuration, so the discrepancy handling and
+/// `failure_threshold` of a top-level `[guard]` section apply when present.
+fn verification_failure(config: &GuardConfig, result: &VerificationResult) -> Option<Error> {
+    (config.should_fail_on_discrepancy() && config.is_failure(result)).then(|| {
+        sps2_errors::OpsError::VerificationFailed {
+            discrepancies: result.count_at_or_above(config.failure_threshold.severity()),
+            state_id: result.state_id.to_string(),
+        }
+        .into()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    #[test]
+    fn guard_threshold_decides_verification_failure() {
+        let missing = sps2_guard::Discrepancy::MissingFile {
+            package_name: "a".to_string(),
+            package_version: "1.0.0".to_string(),
+            file_path: "lib/liba.dylib".to_string(),
+        };
+        let result = VerificationResult::new(uuid::Uuid::nil(), vec![missing], 0);
+        let mut config = GuardConfig {
+            discrepancy_handling: sps2_config::DiscrepancyHandling::FailFast,
+            failure_threshold: sps2_config::FailureThreshold::Critical,
+            ..GuardConfig::default()
+        };
+        assert!(verification_failure(&config, &result).is_none());
+
+        config.failure_threshold = sps2_config::FailureThreshold::High;
+        assert!(matches!(
+            verification_failure(&config, &result),
+            Some(Error::Ops(sps2_errors::OpsError::VerificationFailed {
+                discrepancies: 1,
+                ..
+            }))
+        ));
+
+        config.discrepancy_handling = sps2_config::DiscrepancyHandling::ReportOnly;
+        assert!(verification_failure(&config, &result).is_none());
+    }
+
     #[tokio::test]
     async fn test_verification_disabled_by_default() {
         let temp_dir = TempDir::new().unwrap();
@@ -1,12 +1,18 @@
 //! System Health and Diagnostics Operations
 
-use crate::{ComponentHealth, HealthCheck, HealthIssue, IssueSeverity, OpsCtx};
+use crate::{
+    ComponentHealth, HealthArea, HealthCheck, HealthIssue, HealthReport, IssueSeverity, OpsCtx,
+};
 use sps2_errors::Error;
 use sps2_events::{
     events::{HealthStatus, PackageOperation, PackageOutcome},
     AppEvent, EventEmitter, PackageEvent,
 };
-use sps2_guard::{StoreVerificationConfig, StoreVerifier};
+use sps2_guard::{
+    StateVerificationGuard, StoreVerificationConfig, StoreVerifier, VerificationLevel,
+};
+use sps2_hash::Hash;
+use sps2_store::FileVerificationResult;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::sync::Arc;
@@ -228,3 +234,437 @@ fn check_index_health(ctx: &OpsCtx, issues: &mut Vec<HealthIssue>) -> HealthStat
         HealthStatus::Healthy
     }
 }
+
+/// Maximum number of store file objects hashed by the health summary spot-check
+const SPOT_CHECK_SAMPLE: usize = 32;
+
+/// File type bits of a package file entry's mode
+const S_IFMT: i64 = 0o170_000;
+
+/// Regular file type in a package file entry's mode
+const S_IFREG: i64 = 0o100_000;
+
+/// Maximum number of findings recorded per area before they are summarised
+const MAX_AREA_DETAILS: usize = 20;
+
+/// Build a combined health summary of the installation
+///
+/// Runs three checks and folds them into one [`HealthReport`]:
+/// - **live**: Quick guard verification of the live prefix (no healing)
+/// - **store**: store integrity, verification statistics, and a hash
+///   spot-check of a sample of file objects referenced by the active state
+/// - **state**: database consistency, plus that every active package has its
+///   package directory and store reference and that every file entry points
+///   at a known file object
+///
+/// The overall status is the worst status of the three areas. A check that
+/// cannot run marks its area as [`HealthStatus::Error`] rather than failing
+/// the whole summary.
+///
+/// # Errors
+///
+/// Returns an error if the active package set cannot be read from the state
+/// database.
+pub async fn health_summary(ctx: &OpsCtx) -> Result<HealthReport, Error> {
+    let packages = ctx.state.get_installed_packages().await?;
+
+    let mut entries = Vec::new();
+    {
+        let mut tx = ctx.state.begin_transaction().await?;
+        for package in &packages {
+            for entry in
+                sps2_state::file_queries_runtime::get_package_file_entries(&mut tx, package.id)
+                    .await?
+            {
+                entries.push(ActiveFile {
+                    package: package.name.clone(),
+                    regular_file: entry.permissions & S_IFMT == S_IFREG,
+                    file_hash: entry.file_hash,
+                });
+            }
+        }
+        tx.commit().await?;
+    }
+
+    let live = check_live_area(ctx).await;
+    let store = check_store_area(ctx, &entries).await;
+    let state = check_state_area(ctx, &packages, &entries).await;
+
+    let status = worst_status(worst_status(live.status, store.status), state.status);
+
+    Ok(HealthReport {
+        status,
+        live,
+        store,
+        state,
+    })
+}
+
+/// Quick verification of live files against the active state
+async fn check_live_area(ctx: &OpsCtx) -> HealthArea {
+    let start = Instant::now();
+    let mut area = AreaBuilder::default();
+
+    let guard = StateVerificationGuard::builder()
+        .with_state_manager(ctx.state.clone())
+        .with_store(ctx.store.clone())
+        .with_event_sender(ctx.tx.clone())
+        .with_level(VerificationLevel::Quick)
+        .build();
+
+    match guard {
+        Ok(guard) => match guard.verify_only().await {
+            Ok(result) => {
+                for discrepancy in &result.discrepancies {
+                    area.push(
+                        HealthStatus::Warning,
+                        discrepancy.user_context().technical_details,
+                    );
+                }
+            }
+            Err(e) => area.push(
+                HealthStatus::Error,
+                format!("live verification failed: {e}"),
+            ),
+        },
+        Err(e) => area.push(
+            HealthStatus::Error,
+            format!("live verification unavailable: {e}"),
+        ),
+    }
+
+    area.finish(start)
+}
+
+/// Store integrity, verification statistics, and a hash spot-check
+async fn check_store_area(ctx: &OpsCtx, entries: &[ActiveFile]) -> HealthArea {
+    let start = Instant::now();
+    let mut area = AreaBuilder::default();
+
+    if let Err(e) = ctx.store.verify_integrity() {
+        area.push(
+            HealthStatus::Error,
+            format!("store integrity check failed: {e}"),
+        );
+        return area.finish(start);
+    }
+
+    let verifier = StoreVerifier::new(
+        Arc::new(ctx.state.clone()),
+        Arc::new(ctx.store.file_store().clone()),
+        StoreVerificationConfig::default(),
+    );
+    match verifier.get_stats().await {
+        Ok(stats) => {
+            if stats.failed_count > 0 {
+                area.push(
+                    HealthStatus::Warning,
+                    format!("{} store objects failed verification", stats.failed_count),
+                );
+            }
+            if stats.quarantined_count > 0 {
+                area.push(
+                    HealthStatus::Error,
+                    format!("{} store objects are quarantined", stats.quarantined_count),
+                );
+            }
+        }
+        Err(e) => area.push(
+            HealthStatus::Warning,
+            format!("store verification statistics unavailable: {e}"),
+        ),
+    }
+
+    // Spread the sample evenly over the active file set so repeated runs are
+    // deterministic and still touch every package on large installations.
+    let objects: Vec<&ActiveFile> = entries.iter().filter(|e| e.regular_file).collect();
+    let step = objects.len().div_ceil(SPOT_CHECK_SAMPLE).max(1);
+    let file_store = ctx.store.file_store();
+    for ActiveFile {
+        package, file_hash, ..
+    } in objects.iter().step_by(step)
+    {
+        let Ok(hash) = Hash::from_hex(file_hash) else {
+            area.push(
+                HealthStatus::Error,
+                format!("{package}: invalid file hash '{file_hash}'"),
+            );
+            continue;
+        };
+        match file_store.verify_file_detailed(&hash).await {
+            Ok(FileVerificationResult::Valid) => {}
+            Ok(FileVerificationResult::Missing) => area.push(
+                HealthStatus::Error,
+                format!("{package}: store object {file_hash} is missing"),
+            ),
+            Ok(FileVerificationResult::HashMismatch { actual, .. }) => area.push(
+                HealthStatus::Error,
+                format!(
+                    "{package}: store object {file_hash} is corrupted (hashes to {})",
+                    actual.to_hex()
+                ),
+            ),
+            Ok(FileVerificationResult::Error { message }) => area.push(
+                HealthStatus::Warning,
+                format!("{package}: could not verify store object {file_hash}: {message}"),
+            ),
+            Err(e) => area.push(
+                HealthStatus::Warning,
+                format!("{package}: could not verify store object {file_hash}: {e}"),
+            ),
+        }
+    }
+
+    area.finish(start)
+}
+
+/// State database consistency and references from the active state
+async fn check_state_area(
+    ctx: &OpsCtx,
+    packages: &[sps2_state::Package],
+    entries: &[ActiveFile],
+) -> HealthArea {
+    let start = Instant::now();
+    let mut area = AreaBuilder::default();
+
+    if let Err(e) = ctx.state.verify_consistency().await {
+        area.push(
+            HealthStatus::Error,
+            format!("state consistency check failed: {e}"),
+        );
+    }
+
+    let references = async {
+        let mut tx = ctx.state.begin_transaction().await?;
+        let store_refs: HashMap<String, i64> = sps2_state::queries::get_all_store_refs(&mut tx)
+            .await?
+            .into_iter()
+            .map(|r| (r.hash, r.ref_count))
+            .collect();
+        let mut dangling = Vec::new();
+        for ActiveFile {
+            package, file_hash, ..
+        } in entries
+        {
+            let known = match Hash::from_hex(file_hash) {
+                Ok(hash) => sps2_state::file_queries_runtime::get_file_object(&mut tx, &hash)
+                    .await?
+                    .is_some(),
+                Err(_) => false,
+            };
+            if !known {
+                dangling.push(format!(
+                    "{package}: file entry references unknown file object {file_hash}"
+                ));
+            }
+        }
+        tx.commit().await?;
+        Ok::<_, Error>((store_refs, dangling))
+    }
+    .await;
+
+    match references {
+        Ok((store_refs, dangling)) => {
+            for package in packages {
+                let label = format!("{}-{}", package.name, package.version);
+                match store_refs.get(&package.hash) {
+                    Some(count) if *count > 0 => {}
+                    Some(_) => area.push(
+                        HealthStatus::Error,
+                        format!(
+                            "{label}: store reference for {} has no references",
+                            package.hash
+                        ),
+                    ),
+                    None => area.push(
+                        HealthStatus::Error,
+                        format!("{label}: no store reference for {}", package.hash),
+                    ),
+                }
+                let present = match Hash::from_hex(&package.hash) {
+                    Ok(hash) => ctx.store.has_package(&hash).await,
+                    Err(_) => false,
+                };
+                if !present {
+                    area.push(
+                        HealthStatus::Error,
+                        format!(
+                            "{label}: package {} is missing from the store",
+                            package.hash
+                        ),
+                    );
+                }
+            }
+            for detail in dangling {
+                area.push(HealthStatus::Error, detail);
+            }
+        }
+        Err(e) => area.push(
+            HealthStatus::Error,
+            format!("state reference check failed: {e}"),
+        ),
+    }
+
+    area.finish(start)
+}
+
+/// File entry of a package in the active state
+struct ActiveFile {
+    package: String,
+    file_hash: String,
+    /// Directories and symlinks have entries but no store object
+    regular_file: bool,
+}
+
+/// Accumulates findings for one health area
+#[derive(Default)]
+struct AreaBuilder {
+    status: Option<HealthStatus>,
+    details: Vec<String>,
+    omitted: usize,
+}
+
+impl AreaBuilder {
+    fn push(&mut self, status: HealthStatus, detail: String) {
+        self.status = Some(worst_status(
+            self.status.unwrap_or(HealthStatus::Healthy),
+            status,
+        ));
+        if self.details.len() < MAX_AREA_DETAILS {
+            self.details.push(detail);
+        } else {
+            self.omitted += 1;
+        }
+    }
+
+    fn finish(mut self, start: Instant) -> HealthArea {
+        if self.omitted > 0 {
+            self.details.push(format!("... and {} more", self.omitted));
+        }
+        HealthArea {
+            status: self.status.unwrap_or(HealthStatus::Healthy),
+            details: self.details,
+            check_duration_ms: u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX),
+        }
+    }
+}
+
+/// Return the more severe of two health statuses
+fn worst_status(a: HealthStatus, b: HealthStatus) -> HealthStatus {
+    match (a, b) {
+        (HealthStatus::Error, _) | (_, HealthStatus::Error) => HealthStatus::Error,
+        (HealthStatus::Warning, _) | (_, HealthStatus::Warning) => HealthStatus::Warning,
+        _ => HealthStatus::Healthy,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::ops_ctx;
+    use sps2_install::test_support::install_demo_package;
+    use sps2_state::StateManager;
+    use sps2_store::PackageStore;
+    use tempfile::TempDir;
+    use tokio::fs as afs;
+
+    /// Install a single `demo` package and return the context and its hash
+    async fn installed_ctx(temp_dir: &TempDir) -> (OpsCtx, Hash) {
+        let state_dir = temp_dir.path().join("state");
+        let store_dir = temp_dir.path().join("store");
+        afs::create_dir_all(&state_dir).await.expect("state dir");
+        afs::create_dir_all(&store_dir).await.expect("store dir");
+
+        let state = StateManager::new(&state_dir).await.expect("state manager");
+        let store = PackageStore::new(store_dir);
+        let hash = install_demo_package(&state, &store).await.hash;
+        (ops_ctx(temp_dir.path(), state, store), hash)
+    }
+
+    /// Hash of the single file object installed by `installed_ctx`
+    async fn demo_file_hash(ctx: &OpsCtx) -> Hash {
+        let packages = ctx.state.get_installed_packages().await.unwrap();
+        let mut tx = ctx.state.begin_transaction().await.unwrap();
+        let entries =
+            sps2_state::file_queries_runtime::get_package_file_entries(&mut tx, packages[0].id)
+                .await
+                .unwrap();
+        tx.commit().await.unwrap();
+        let entry = entries
+            .iter()
+            .find(|e| e.relative_path.ends_with("file.txt"))
+            .expect("file entry");
+        Hash::from_hex(&entry.file_hash).unwrap()
+    }
+
+    #[tokio::test]
+    async fn healthy_installation_reports_healthy() {
+        let temp_dir = TempDir::new().unwrap();
+        let (ctx, _) = installed_ctx(&temp_dir).await;
+
+        let report = health_summary(&ctx).await.expect("health summary");
+        assert!(report.is_healthy(), "{report:?}");
+        assert_eq!(report.live.status, HealthStatus::Healthy);
+        assert_eq!(report.store.status, HealthStatus::Healthy);
+        assert_eq!(report.state.status, HealthStatus::Healthy);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["status"], "healthy");
+        assert!(json["store"]["details"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn missing_live_file_degrades_live_area() {
+        let temp_dir = TempDir::new().unwrap();
+        let (ctx, _) = installed_ctx(&temp_dir).await;
+        afs::remove_file(ctx.state.live_path().join("opt/pm/live/share/file.txt"))
+            .await
+            .expect("remove live file");
+
+        let report = health_summary(&ctx).await.expect("health summary");
+        assert_eq!(report.live.status, HealthStatus::Warning);
+        assert!(report.live.details[0].contains("file.txt"));
+        assert_eq!(report.store.status, HealthStatus::Healthy);
+        assert_eq!(report.state.status, HealthStatus::Healthy);
+        assert_eq!(report.status, HealthStatus::Warning);
+    }
+
+    #[tokio::test]
+    async fn corrupted_store_object_degrades_store_area() {
+        let temp_dir = TempDir::new().unwrap();
+        let (ctx, _) = installed_ctx(&temp_dir).await;
+        let file_hash = demo_file_hash(&ctx).await;
+
+        // Replace the object rather than writing through it so the hardlinked
+        // live file keeps its original contents.
+        let object = ctx.store.file_store().file_path(&file_hash);
+        let replacement = temp_dir.path().join("replacement");
+        afs::write(&replacement, b"tampered").await.unwrap();
+        afs::rename(&replacement, &object).await.unwrap();
+
+        let report = health_summary(&ctx).await.expect("health summary");
+        assert_eq!(report.store.status, HealthStatus::Error);
+        assert!(report.store.details[0].contains("corrupted"));
+        assert_eq!(report.state.status, HealthStatus::Healthy);
+        assert_eq!(report.status, HealthStatus::Error);
+    }
+
+    #[tokio::test]
+    async fn missing_store_package_degrades_state_area() {
+        let temp_dir = TempDir::new().unwrap();
+        let (ctx, hash) = installed_ctx(&temp_dir).await;
+        ctx.store
+            .remove_package(&hash)
+            .await
+            .expect("remove package");
+
+        let report = health_summary(&ctx).await.expect("health summary");
+        assert_eq!(report.state.status, HealthStatus::Error);
+        assert!(report
+            .state
+            .details
+            .iter()
+            .any(|d| d.contains("missing from the store")));
+        assert_eq!(report.status, HealthStatus::Error);
+    }
+}
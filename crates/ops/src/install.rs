@@ -122,18 +122,18 @@ pub async fn install(ctx: &OpsCtx, package_specs: &[String]) -> Result<InstallRe
         }
     };
 
+    let sizes = PackageSizes::load(ctx, &installed_before, &result.state_id).await?;
+
     // Convert to report format with proper change tracking
     let report = InstallReport {
         installed: result
             .installed_packages
             .iter()
-            .map(|pkg| {
-                crate::PackageChange {
-                    name: pkg.name.clone(),
-                    from_version: None,
-                    to_version: Some(pkg.version.clone()),
-                    size: None, // TODO: Get size from store when available
-                }
+            .map(|pkg| crate::PackageChange {
+                name: pkg.name.clone(),
+                from_version: None,
+                to_version: Some(pkg.version.clone()),
+                size: sizes.after(&pkg.name),
             })
             .collect(),
         updated: result
@@ -143,7 +143,7 @@ pub async fn install(ctx: &OpsCtx, package_specs: &[String]) -> Result<InstallRe
                 name: pkg.name.clone(),
                 from_version: installed_map.get(&pkg.name).cloned(),
                 to_version: Some(pkg.version.clone()),
-                size: None,
+                size: sizes.after(&pkg.name),
             })
             .collect(),
         removed: result
@@ -153,7 +153,7 @@ pub async fn install(ctx: &OpsCtx, package_specs: &[String]) -> Result<InstallRe
                 name: pkg.name.clone(),
                 from_version: Some(pkg.version.clone()),
                 to_version: None,
-                size: None,
+                size: sizes.before(&pkg.name),
             })
             .collect(),
         state_id: result.state_id,
@@ -404,7 +404,9 @@ async fn install_remote_packages_parallel(
     };
     let duration_ms = resolve_start.elapsed().as_millis();
     let duration_ms = u64::try_from(duration_ms).unwrap_or(u64::MAX);
-    let execution_plan = resolution_result.execution_plan;
+    let execution_plan = resolution_result
+        .execution_plan
+        .with_max_batch_size(ctx.config.general.max_batch_size);
     let resolved_packages = resolution_result.nodes;
     let mut downloaded_packages = 0usize;
     let mut reused_packages = 0usize;
@@ -430,7 +432,8 @@ async fn install_remote_packages_parallel(
         .with_security_policy(sps2_install::SecurityPolicy {
             verify_signatures: ctx.config.security.verify_signatures,
             allow_unsigned: ctx.config.security.allow_unsigned,
-        });
+        })
+        .with_mirrors(ctx.mirrors.clone());
 
     // Create parallel executor
     let resources = std::sync::Arc::new(sps2_resources::ResourceManager::default());
@@ -501,7 +504,9 @@ async fn install_local_packages(
     files: &[PathBuf],
 ) -> Result<sps2_install::InstallResult, Error> {
     // Create installer for local files
-    let config = InstallConfig::default();
+    let config = InstallConfig::default()
+        .with_max_batch_size(ctx.config.general.max_batch_size)
+        .with_keep_on_failure(ctx.config.general.keep_on_failure);
     let mut installer = Installer::new(
         config,
         ctx.resolver.clone(),
@@ -526,7 +531,9 @@ async fn install_mixed_packages(
 ) -> Result<sps2_install::InstallResult, Error> {
     // For mixed installs, use the regular installer for now
     // TODO: Optimize this by using pipeline for remote and merging results
-    let config = InstallConfig::default();
+    let config = InstallConfig::default()
+        .with_max_batch_size(ctx.config.general.max_batch_size)
+        .with_keep_on_failure(ctx.config.general.keep_on_failure);
     let mut installer = Installer::new(
         config,
         ctx.resolver.clone(),
@@ -569,6 +576,46 @@ fn parse_install_requests(specs: &[String]) -> Result<Vec<InstallRequest>, Error
     Ok(requests)
 }
 
+/// Package sizes recorded in the state database around an operation
+///
+/// Reports carry these so post-operation verification can apply its byte
+/// budget.
+pub(crate) struct PackageSizes {
+    before: std::collections::HashMap<String, u64>,
+    after: std::collections::HashMap<String, u64>,
+}
+
+impl PackageSizes {
+    /// Sizes from the packages installed before and the state created after
+    pub(crate) async fn load(
+        ctx: &OpsCtx,
+        installed_before: &[sps2_state::models::Package],
+        state_id: &Uuid,
+    ) -> Result<Self, Error> {
+        let by_name = |packages: &[sps2_state::models::Package]| {
+            packages
+                .iter()
+                .filter_map(|pkg| Some((pkg.name.clone(), u64::try_from(pkg.size).ok()?)))
+                .collect()
+        };
+        let installed_after = ctx.state.get_installed_packages_in_state(state_id).await?;
+        Ok(Self {
+            before: by_name(installed_before),
+            after: by_name(&installed_after),
+        })
+    }
+
+    /// Size of a package as installed before the operation
+    pub(crate) fn before(&self, name: &str) -> Option<u64> {
+        self.before.get(name).copied()
+    }
+
+    /// Size of a package as installed by the operation
+    pub(crate) fn after(&self, name: &str) -> Option<u64> {
+        self.after.get(name).copied()
+    }
+}
+
 /// Convert `InstallReport` to `GuardOperationResult` for guard integration
 fn create_guard_operation_result(report: &InstallReport) -> GuardOperationResult {
     GuardOperationResult {
@@ -19,6 +19,8 @@ mod query;
 mod repository;
 mod security;
 mod self_update;
+#[cfg(test)]
+mod test_support;
 mod types;
 
 // Import command modules
@@ -44,7 +46,8 @@ pub use sps2_types::{
 pub use sps2_events::HealthStatus;
 // Re-export ops-specific types from local types module
 pub use types::{
-    ComponentHealth, HealthCheck, HealthIssue, InstallRequest, IssueSeverity, OpReport, VulnDbStats,
+    ComponentHealth, HealthArea, HealthCheck, HealthIssue, HealthReport, InstallRequest,
+    IssueSeverity, OpReport, VulnDbStats,
 };
 
 // Re-export operation functions
@@ -53,8 +56,9 @@ pub use draft::draft_recipe;
 pub use install::{install, install_with_verification};
 pub use pack::{pack_from_directory, pack_from_recipe, pack_from_recipe_no_post};
 pub use small_ops::{
-    audit, check_health, cleanup, history, list_packages, package_info, reposync, rollback,
-    search_packages, self_update, update_vulndb, vulndb_stats,
+    audit, check_health, cleanup, compact_store, health_summary, history, import_state,
+    list_packages, mirror_status, package_info, reposync, rollback, search_packages, self_update,
+    update_vulndb, vulndb_stats,
 };
 pub use uninstall::{uninstall, uninstall_with_verification};
 pub use update::update;
@@ -64,7 +68,7 @@ pub use upgrade::{upgrade, upgrade_with_verification};
 pub use sps2_audit::{AuditReport, Severity};
 
 use sps2_errors::Error;
-use sps2_guard::{StoreVerificationConfig, StoreVerifier};
+use sps2_guard::{StoreVerificationConfig, StoreVerifier, VerificationScope};
 use std::sync::Arc;
 
 /// Verify the integrity of the current state
@@ -113,10 +117,11 @@ pub async fn verify(
         "all" => {
             // Both live and store verification
             // First verify live files
-            let mut guard = StateVerificationGuard::builder()
+            let guard = StateVerificationGuard::builder()
                 .with_state_manager(ctx.state.clone())
                 .with_store(ctx.store.clone())
                 .with_event_sender(ctx.tx.clone())
+                .with_config(ctx.guard_config())
                 .with_level(verification_level)
                 .build()?;
 
@@ -140,12 +145,30 @@ pub async fn verify(
             // Return the live verification result (store verification is reported via events)
             Ok(live_result)
         }
+        "changed" => {
+            // Live files of packages changed since the last verified state
+            let guard = StateVerificationGuard::builder()
+                .with_state_manager(ctx.state.clone())
+                .with_store(ctx.store.clone())
+                .with_event_sender(ctx.tx.clone())
+                .with_config(ctx.guard_config())
+                .with_level(verification_level)
+                .build()?;
+            let scope = VerificationScope::Incremental { force_full: false };
+
+            if heal {
+                guard.verify_and_heal_scoped(&ctx.config, &scope).await
+            } else {
+                guard.verify_with_scope(&scope).await
+            }
+        }
         _ => {
             // Default: live files only (existing behavior)
-            let mut guard = StateVerificationGuard::builder()
+            let guard = StateVerificationGuard::builder()
                 .with_state_manager(ctx.state.clone())
                 .with_store(ctx.store.clone())
                 .with_event_sender(ctx.tx.clone())
+                .with_config(ctx.guard_config())
                 .with_level(verification_level)
                 .build()?;
 
@@ -224,3 +247,50 @@ impl OperationResult {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::ops_ctx;
+    use sps2_guard::{Discrepancy, OrphanedFileCategory};
+    use sps2_install::test_support::install_demo_package;
+    use sps2_state::StateManager;
+    use sps2_store::PackageStore;
+    use tempfile::TempDir;
+    use tokio::fs as afs;
+
+    #[tokio::test]
+    async fn verify_applies_the_configured_orphan_whitelist() {
+        let temp_dir = TempDir::new().expect("ops tempdir");
+        let state_dir = temp_dir.path().join("state");
+        let store_dir = temp_dir.path().join("store");
+        afs::create_dir_all(&state_dir).await.expect("state dir");
+        afs::create_dir_all(&store_dir).await.expect("store dir");
+
+        let state = StateManager::new(&state_dir).await.expect("state manager");
+        let store = PackageStore::new(store_dir);
+        install_demo_package(&state, &store).await;
+        afs::write(state.live_path().join("share/notes.txt"), b"keep")
+            .await
+            .expect("orphan");
+
+        let mut ctx = ops_ctx(temp_dir.path(), state, store);
+        ctx.config.verification.orphan_whitelist = vec!["share/*.txt".to_string()];
+        let result = verify(&ctx, false, "standard", "live")
+            .await
+            .expect("verify");
+
+        let orphan = result
+            .discrepancies
+            .iter()
+            .find_map(|d| match d {
+                Discrepancy::OrphanedFile {
+                    file_path,
+                    category,
+                } if file_path == "share/notes.txt" => Some(category),
+                _ => None,
+            })
+            .expect("whitelisted file listed for auditing");
+        assert!(matches!(orphan, OrphanedFileCategory::Whitelisted));
+    }
+}
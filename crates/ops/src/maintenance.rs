@@ -1,12 +1,16 @@
 //! System Cleanup and State Management Operations
 
 use crate::{ChangeType, OpChange, OpsCtx, StateInfo};
-use sps2_errors::{Error, OpsError};
+use sps2_errors::{Error, InstallError, OpsError, SigningError, StateError};
 use sps2_events::{
     events::{PackageOperation, PackageOutcome},
     AppEvent, EventEmitter, GeneralEvent, PackageEvent, RollbackContext, RollbackSummary,
     StateEvent,
 };
+use sps2_hash::Hash;
+use sps2_net::{PackageDownloadConfig, PackageDownloader};
+use sps2_state::{StateManifest, StateManifestPackage};
+use sps2_types::Version;
 use std::convert::TryFrom;
 use std::time::Instant;
 
@@ -267,6 +271,45 @@ pub async fn cleanup(ctx: &OpsCtx) -> Result<String, Error> {
     Ok(message)
 }
 
+/// Compact the store by packing the small objects of each stored package
+///
+/// Packed objects stay addressable by hash; installs and verification read
+/// them from their packs, so this is purely a storage layout optimisation.
+/// Asking for compaction opts the store into the packed layout. Packages
+/// installed in the active state are left loose so the live prefix keeps
+/// sharing their objects.
+///
+/// # Errors
+///
+/// Returns an error if reading the store or writing a pack fails.
+pub async fn compact_store(ctx: &OpsCtx) -> Result<String, Error> {
+    if !ctx.store.packs_enabled().await {
+        ctx.store.migrate_to_packs().await?;
+    }
+
+    let skip_packages = ctx
+        .state
+        .get_installed_packages()
+        .await?
+        .iter()
+        .filter_map(|package| sps2_hash::Hash::from_hex(&package.hash).ok())
+        .collect();
+    let options = sps2_store::CompactionOptions {
+        skip_packages,
+        ..sps2_store::CompactionOptions::default()
+    };
+
+    let report = ctx.store.compact(&options).await?;
+    Ok(format!(
+        "Packed {} objects ({} bytes) into {} packs, dropped {} re-inflated copies, kept {} linked objects loose",
+        report.objects_packed,
+        report.bytes_packed,
+        report.packs_written,
+        report.loose_copies_removed,
+        report.objects_in_use
+    ))
+}
+
 /// Rollback to a previous state
 ///
 /// # Errors
@@ -340,6 +383,167 @@ pub async fn rollback(ctx: &OpsCtx, target_state: Option<Uuid>) -> Result<StateI
     Ok(state_info)
 }
 
+/// Download an index entry, trying the ranked mirrors best-first
+///
+/// Only network failures move on to the next mirror.
+async fn download_from_mirrors(
+    ctx: &OpsCtx,
+    downloader: &PackageDownloader,
+    name: &str,
+    version: &Version,
+    entry: &sps2_index::VersionEntry,
+    dest: &std::path::Path,
+    expected: &Hash,
+) -> Result<sps2_net::PackageDownloadResult, Error> {
+    let mut last_error = None;
+    for candidate in ctx.mirrors.candidates(&entry.download_url) {
+        let signature_url = ctx.mirrors.rebase(&entry.minisig_url, &candidate);
+        let started = Instant::now();
+        match downloader
+            .download_package(
+                name,
+                version,
+                &candidate,
+                Some(&signature_url),
+                dest,
+                Some(expected),
+                String::new(),
+                None,
+                &ctx.tx,
+            )
+            .await
+        {
+            Ok(download) => {
+                ctx.mirrors.record_success(&candidate, started.elapsed());
+                return Ok(download);
+            }
+            Err(e @ Error::Network(_)) => {
+                ctx.mirrors.record_failure(&candidate);
+                last_error = Some(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        InstallError::TaskError {
+            message: format!("no download source for {name}"),
+        }
+        .into()
+    }))
+}
+
+/// Import a state manifest, fetching packages that are not in the store
+///
+/// Packages missing from the local store, or with file objects missing from
+/// it, are looked up in the package index by name and version. Every package
+/// must be obtainable before anything is downloaded, and each fetched package
+/// must hash to the value recorded in the manifest. Downloads go through
+/// `ctx.net` and the ranked mirrors, like installs. The imported state is not
+/// activated; use [`rollback`] with the returned ID to switch to it.
+///
+/// # Errors
+///
+/// Returns an error if a package is neither in the store nor the index, a
+/// download or signature check fails, a fetched package does not match the
+/// manifest, or the state import fails.
+pub async fn import_state(ctx: &OpsCtx, manifest: &StateManifest) -> Result<Uuid, Error> {
+    let mut to_fetch = Vec::new();
+    let mut unobtainable = Vec::new();
+    for package in &manifest.packages {
+        if content_is_stored(ctx, package).await? {
+            continue;
+        }
+        match ctx.index.get_version(&package.name, &package.version) {
+            Some(entry) => to_fetch.push((package, entry.clone())),
+            None => unobtainable.push(format!("{}-{}", package.name, package.version)),
+        }
+    }
+    if !unobtainable.is_empty() {
+        return Err(StateError::ManifestContentUnavailable {
+            missing: unobtainable.join(", "),
+        }
+        .into());
+    }
+
+    if !to_fetch.is_empty() {
+        let downloader = PackageDownloader::with_client(
+            PackageDownloadConfig::default(),
+            ctx.net.clone(),
+            sps2_events::ProgressManager::new(),
+        );
+        let temp_dir = tempfile::tempdir().map_err(|e| InstallError::TempFileError {
+            message: e.to_string(),
+        })?;
+
+        for (package, entry) in to_fetch {
+            ctx.emit_debug(format!(
+                "Fetching {}-{} for state import",
+                package.name, package.version
+            ));
+            let version = Version::parse(&package.version)?;
+            let expected = Hash::from_hex(&entry.blake3)?;
+            let download = download_from_mirrors(
+                ctx,
+                &downloader,
+                &package.name,
+                &version,
+                &entry,
+                temp_dir.path(),
+                &expected,
+            )
+            .await?;
+
+            let security = &ctx.config.security;
+            if security.verify_signatures
+                && !security.allow_unsigned
+                && !download.signature_verified
+            {
+                return Err(SigningError::VerificationFailed {
+                    reason: format!(
+                        "signature for {}-{} could not be verified",
+                        package.name, package.version
+                    ),
+                }
+                .into());
+            }
+
+            let stored = ctx
+                .store
+                .add_package_from_file(&download.package_path, &package.name, &version)
+                .await?;
+            if stored.hash().map(|h| h.to_hex()).as_deref() != Some(package.hash.as_str()) {
+                return Err(StateError::ManifestContentUnavailable {
+                    missing: format!(
+                        "{}-{} (index package does not match manifest hash {})",
+                        package.name, package.version, package.hash
+                    ),
+                }
+                .into());
+            }
+        }
+    }
+
+    ctx.state.import_state(manifest, &ctx.store).await
+}
+
+/// Whether a manifest package and all of its file objects are in the store
+async fn content_is_stored(ctx: &OpsCtx, package: &StateManifestPackage) -> Result<bool, Error> {
+    if !ctx.store.has_package(&Hash::from_hex(&package.hash)?).await {
+        return Ok(false);
+    }
+    for file in package.files.iter().filter(|f| f.has_store_object()) {
+        if !ctx
+            .store
+            .file_store()
+            .has_file(&Hash::from_hex(&file.hash)?)
+            .await
+        {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
 /// Preview what would be rolled back without executing
 #[allow(clippy::too_many_lines)]
 async fn preview_rollback(ctx: &OpsCtx, target_state: Option<Uuid>) -> Result<StateInfo, Error> {
@@ -191,7 +191,9 @@ async fn pack_from_recipe_impl(
     )
     .with_revision(1)
     .with_event_sender(ctx.tx.clone())
-    .with_session_id(session_id);
+    .with_session_id(session_id)
+    .with_recipe_commit_epoch()
+    .await;
 
     // Create build environment pointing to existing staging directory
     let mut environment = BuildEnvironment::new(build_context.clone(), &build_root)?;
@@ -211,7 +213,14 @@ async fn pack_from_recipe_impl(
 
         // Run QA pipeline (same as build command)
         let qa_pipeline_override = Some(yaml_recipe.post.qa_pipeline);
-        run_quality_pipeline(&build_context, &environment, qa_pipeline_override).await?;
+        run_quality_pipeline(
+            &build_context,
+            &environment,
+            qa_pipeline_override,
+            &yaml_recipe.post.patchers,
+            yaml_recipe.post.patch_rpaths.style(),
+        )
+        .await?;
     }
 
     // Create build config (same as build command)
@@ -226,6 +235,10 @@ async fn pack_from_recipe_impl(
         license: Some(yaml_recipe.metadata.license.clone()),
         runtime_deps: yaml_recipe.metadata.dependencies.runtime.clone(),
         build_deps: yaml_recipe.metadata.dependencies.build.clone(),
+        weak_deps: yaml_recipe.metadata.dependencies.weak.clone(),
+        conflicts: yaml_recipe.metadata.dependencies.conflicts.clone(),
+        replaces: yaml_recipe.metadata.dependencies.replaces.clone(),
+        runtime_env: yaml_recipe.metadata.runtime_env.clone(),
     };
 
     // Generate SBOM and create manifest (EXACT same as build command)
@@ -237,56 +237,13 @@ pub async fn search_packages(ctx: &OpsCtx, query: &str) -> Result<Vec<SearchResu
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::OpsContextBuilder;
-    use sps2_builder::Builder;
-    use sps2_config::Config;
-    use sps2_install::{AtomicInstaller, InstallContext, PreparedPackage};
-    use sps2_net::{NetClient, NetConfig};
-    use sps2_resolver::{PackageId, ResolvedNode, Resolver};
+    use crate::test_support::ops_ctx;
+    use sps2_install::test_support::{add_package, install_packages};
     use sps2_state::StateManager;
-    use sps2_store::{create_package, PackageStore};
-    use sps2_types::{Arch, Manifest, Version};
-    use std::collections::HashMap;
+    use sps2_store::PackageStore;
     use tempfile::TempDir;
     use tokio::fs as afs;
 
-    async fn make_package(
-        store: &PackageStore,
-        name: &str,
-        version: &str,
-        description: &str,
-    ) -> (sps2_hash::Hash, std::path::PathBuf, u64) {
-        let td = TempDir::new().expect("package tempdir");
-        let src = td.path().join("src");
-        afs::create_dir_all(&src).await.expect("src dir");
-
-        let version_parsed = Version::parse(version).expect("version");
-        let mut manifest = Manifest::new(name.to_string(), &version_parsed, 1, &Arch::Arm64);
-        manifest.package.description = Some(description.to_string());
-        sps2_store::manifest_io::write_manifest(&src.join("manifest.toml"), &manifest)
-            .await
-            .expect("write manifest");
-
-        let content_dir = src.join("opt/pm/live/share");
-        afs::create_dir_all(&content_dir)
-            .await
-            .expect("content dir");
-        afs::write(content_dir.join("file.txt"), description.as_bytes())
-            .await
-            .expect("write file");
-
-        let sp_path = td.path().join("pkg.sp");
-        create_package(&src, &sp_path)
-            .await
-            .expect("create package");
-
-        let stored = store.add_package(&sp_path).await.expect("add package");
-        let hash = stored.hash().expect("hash");
-        let path = store.package_path(&hash);
-        let size = afs::metadata(&sp_path).await.expect("metadata").len();
-        (hash, path, size)
-    }
-
     #[tokio::test]
     async fn list_packages_uses_manifest_description_when_index_missing() {
         let temp_dir = TempDir::new().expect("ops tempdir");
@@ -299,62 +256,17 @@ mod tests {
         let store = PackageStore::new(store_dir.clone());
 
         let description = "Demo package description";
-        let (hash, store_path, size) = make_package(&store, "demo", "1.2.3", description).await;
-
-        let mut atomic = AtomicInstaller::new(state.clone(), store.clone())
-            .await
-            .expect("atomic installer");
-        let pkg_id = PackageId::new("demo".to_string(), Version::parse("1.2.3").unwrap());
-        let mut resolved_nodes = HashMap::new();
-        resolved_nodes.insert(
-            pkg_id.clone(),
-            ResolvedNode::local(
-                "demo".to_string(),
-                pkg_id.version.clone(),
-                store_path.clone(),
-                vec![],
-            ),
-        );
-        let mut prepared = HashMap::new();
-        prepared.insert(
-            pkg_id.clone(),
-            PreparedPackage {
-                hash,
-                size,
-                store_path,
-                is_local: true,
-            },
-        );
-        let install_ctx = InstallContext {
-            packages: vec![],
-            local_files: vec![],
-            force: false,
-            event_sender: None,
-        };
-        atomic
-            .install(&install_ctx, &resolved_nodes, Some(&prepared))
-            .await
-            .expect("install package");
-
-        let (tx, _rx) = sps2_events::channel();
-        let config = Config::default();
-        let index = sps2_index::IndexManager::new(temp_dir.path().join("index"));
-        let net = NetClient::new_without_proxies(NetConfig::default()).expect("net client");
-        let resolver_instance = Resolver::with_events(index.clone(), tx.clone());
-        let builder = Builder::new();
-
-        let ctx = OpsContextBuilder::new()
-            .with_state(state)
-            .with_store(store)
-            .with_index(index)
-            .with_net(net)
-            .with_resolver(resolver_instance)
-            .with_builder(builder)
-            .with_event_sender(tx)
-            .with_config(config)
-            .build()
-            .expect("ops ctx");
-
+        let package = add_package(
+            &store,
+            "demo",
+            "1.2.3",
+            Some(description),
+            &[("share/file.txt", description.as_bytes())],
+        )
+        .await;
+        install_packages(&state, &store, &[package]).await;
+
+        let ctx = ops_ctx(temp_dir.path(), state, store);
         let packages = list_packages(&ctx).await.expect("list packages");
         assert_eq!(packages.len(), 1);
         assert_eq!(packages[0].name, "demo");
@@ -6,6 +6,7 @@ use dialoguer::{theme::ColorfulTheme, Confirm};
 use sps2_config::{Config, RepositoryConfig};
 use sps2_errors::{ConfigError, Error, OpsError, SigningError};
 use sps2_events::{AppEvent, EventEmitter, FailureContext, GeneralEvent, RepoEvent};
+use sps2_net::{MirrorRanking, MirrorStatus};
 use std::path::PathBuf;
 use std::time::Instant;
 /// Sync repository index
@@ -21,7 +22,9 @@ pub async fn reposync(ctx: &OpsCtx, yes: bool) -> Result<String, Error> {
     let start = Instant::now();
     let _correlation = ctx.push_correlation("reposync");
 
-    let Some(base_url) = get_base_url(ctx) else {
+    ctx.mirrors.probe_if_due(&ctx.net).await;
+    let mirrors = ctx.mirrors.ranked();
+    let Some(preferred) = mirrors.first() else {
         let err = Error::Config(ConfigError::MissingField {
             field: "repositories".to_string(),
         });
@@ -33,16 +36,24 @@ pub async fn reposync(ctx: &OpsCtx, yes: bool) -> Result<String, Error> {
     };
 
     ctx.emit(AppEvent::Repo(RepoEvent::SyncStarted {
-        url: Some(base_url.to_string()),
+        url: Some(preferred.clone()),
     }));
 
-    let index_result = sync_and_verify_index(ctx, &base_url, start, yes).await;
-    let index_json = match index_result {
-        Ok(json) => json,
-        Err(e) => {
+    let (base_url, index_json) = match sync_from_mirrors(ctx, &mirrors, yes).await {
+        Ok((_, None)) => {
+            // The cached index is current; keep it as is
+            ctx.emit(AppEvent::Repo(RepoEvent::SyncCompleted {
+                packages_updated: 0,
+                duration_ms: u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX),
+                bytes_transferred: 0,
+            }));
+            return Ok("Repository index is unchanged (304 Not Modified)".to_string());
+        }
+        Ok((base_url, Some(index_json))) => (base_url, index_json),
+        Err((url, e)) => {
             let failure = FailureContext::from_error(&e);
             ctx.emit(AppEvent::Repo(RepoEvent::SyncFailed {
-                url: Some(base_url.to_string()),
+                url: Some(url),
                 failure,
             }));
             return Err(e);
@@ -64,7 +75,7 @@ pub async fn reposync(ctx: &OpsCtx, yes: bool) -> Result<String, Error> {
             }
             .into();
             ctx.emit(AppEvent::Repo(RepoEvent::SyncFailed {
-                url: Some(base_url.to_string()),
+                url: Some(base_url),
                 failure: FailureContext::from_error(&err),
             }));
             return Err(err);
@@ -74,25 +85,90 @@ pub async fn reposync(ctx: &OpsCtx, yes: bool) -> Result<String, Error> {
     finalize_index_update(ctx, &index_json, start).await
 }
 
-fn get_base_url(ctx: &OpsCtx) -> Option<String> {
-    let mut candidates: Vec<&sps2_config::RepositoryConfig> = ctx.config.repos.get_all();
+/// Mirror ranking for the preferred repository and its configured mirrors
+pub(crate) fn mirror_ranking(config: &Config) -> MirrorRanking {
+    let mut candidates: Vec<&RepositoryConfig> = config.repos.get_all();
     candidates.sort_by_key(|r| r.priority);
-    candidates.first().map(|repo| repo.url.clone())
+    let urls: Vec<String> = candidates
+        .first()
+        .map(|repo| {
+            std::iter::once(repo.url.clone())
+                .chain(repo.mirrors.iter().cloned())
+                .collect()
+        })
+        .unwrap_or_default();
+    MirrorRanking::new(urls)
 }
 
+/// Probe the repository mirrors and report their health, best first
+pub async fn mirror_status(ctx: &OpsCtx) -> Vec<MirrorStatus> {
+    ctx.mirrors.probe(&ctx.net).await;
+    ctx.mirrors.status()
+}
+
+/// Fetch the index from the first mirror that serves it
+///
+/// Network failures deprioritize the mirror and move on to the next one;
+/// any other error (such as a bad signature) stops the sync. On failure the
+/// URL of the last mirror tried is returned with the error. The index is
+/// `None` if the mirror reports that the cached index is unchanged.
+async fn sync_from_mirrors(
+    ctx: &OpsCtx,
+    mirrors: &[String],
+    yes: bool,
+) -> Result<(String, Option<String>), (String, Error)> {
+    let mut last_failure = None;
+    for base_url in mirrors {
+        let attempt = Instant::now();
+        match sync_and_verify_index(ctx, base_url, yes).await {
+            Ok(index_json) => {
+                ctx.mirrors.record_success(base_url, attempt.elapsed());
+                return Ok((base_url.clone(), index_json));
+            }
+            Err(e @ Error::Network(_)) => {
+                ctx.mirrors.record_failure(base_url);
+                ctx.emit_warning(format!("mirror {base_url} failed: {e}"));
+                last_failure = Some((base_url.clone(), e));
+            }
+            Err(e) => return Err((base_url.clone(), e)),
+        }
+    }
+
+    Err(last_failure.unwrap_or_else(|| {
+        (
+            String::new(),
+            Error::Config(ConfigError::MissingField {
+                field: "repositories".to_string(),
+            }),
+        )
+    }))
+}
+
+/// Download and verify the index from `base_url`
+///
+/// Returns `None` without fetching the signature if the server reports that
+/// the cached index is unchanged.
 async fn sync_and_verify_index(
     ctx: &OpsCtx,
     base_url: &str,
-    start: Instant,
     yes: bool,
-) -> Result<String, Error> {
+) -> Result<Option<String>, Error> {
     let index_url = format!("{base_url}/index.json");
     let index_sig_url = format!("{base_url}/index.json.minisig");
     let keys_url = format!("{base_url}/keys.json");
 
     let cached_etag = ctx.index.cache.load_etag().await.unwrap_or(None);
-    let index_json =
-        download_index_conditional(ctx, &index_url, cached_etag.as_deref(), start).await?;
+    let cached_last_modified = ctx.index.cache.load_last_modified().await.unwrap_or(None);
+    let Some(index_json) = download_index_conditional(
+        ctx,
+        &index_url,
+        cached_etag.as_deref(),
+        cached_last_modified.as_deref(),
+    )
+    .await?
+    else {
+        return Ok(None);
+    };
     let index_signature = sps2_net::fetch_text(&ctx.net, &index_sig_url, &ctx.tx).await?;
     let mut trusted_keys = fetch_and_verify_keys(ctx, &ctx.net, &keys_url, &ctx.tx).await?;
 
@@ -113,7 +189,7 @@ async fn sync_and_verify_index(
         .await?;
     }
 
-    Ok(index_json)
+    Ok(Some(index_json))
 }
 
 async fn handle_signature_verification_error(
@@ -195,6 +271,7 @@ pub async fn add_repo(_ctx: &OpsCtx, name: &str, url: &str) -> Result<String, Er
         priority: 10,
         algorithm: "minisign".to_string(),
         key_ids: vec![],
+        mirrors: vec![],
     };
     config.repos.extras.insert(name.to_string(), new_repo);
 
@@ -288,31 +365,36 @@ pub async fn remove_repo(_ctx: &OpsCtx, name: &str) -> Result<String, Error> {
     Ok(format!("Repository '{name}' removed successfully."))
 }
 
-/// Download index conditionally with `ETag` support
+/// Download index conditionally with `ETag` and `Last-Modified` support
+///
+/// Returns `None` if the server responds with 304 Not Modified.
 async fn download_index_conditional(
     ctx: &OpsCtx,
     index_url: &str,
     cached_etag: Option<&str>,
-    start: Instant,
-) -> Result<String, Error> {
-    let response =
-        sps2_net::fetch_text_conditional(&ctx.net, index_url, cached_etag, &ctx.tx).await?;
-
-    if let Some((content, new_etag)) = response {
-        if let Some(etag) = new_etag {
-            if let Err(e) = ctx.index.cache.save_etag(&etag).await {
-                ctx.emit_warning(format!("Failed to save ETag: {e}"));
-            }
-        }
-        Ok(content)
-    } else {
-        ctx.tx.emit(AppEvent::Repo(RepoEvent::SyncCompleted {
-            packages_updated: 0,
-            duration_ms: u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX),
-            bytes_transferred: 0,
-        }));
-        Ok("Repository index is unchanged (304 Not Modified)".to_string())
+    cached_last_modified: Option<&str>,
+) -> Result<Option<String>, Error> {
+    let response = sps2_net::fetch_text_conditional(
+        &ctx.net,
+        index_url,
+        cached_etag,
+        cached_last_modified,
+        &ctx.tx,
+    )
+    .await?;
+
+    let Some(fetched) = response else {
+        return Ok(None);
+    };
+    if let Err(e) = ctx
+        .index
+        .cache
+        .save_validators(fetched.etag.as_deref(), fetched.last_modified.as_deref())
+        .await
+    {
+        ctx.emit_warning(format!("Failed to save cache validators: {e}"));
     }
+    Ok(Some(fetched.content))
 }
 
 /// Process and save the new index
@@ -379,3 +461,58 @@ async fn fetch_and_verify_keys(
 
     Ok(trusted_keys)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::ops_ctx;
+    use httpmock::prelude::*;
+    use sps2_state::StateManager;
+    use sps2_store::PackageStore;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn unchanged_index_is_not_verified_or_replaced() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_dir = temp_dir.path().join("state");
+        tokio::fs::create_dir_all(&state_dir).await.unwrap();
+        let state = StateManager::new(&state_dir).await.unwrap();
+        let store = PackageStore::new(temp_dir.path().join("store"));
+        let ctx = ops_ctx(temp_dir.path(), state, store);
+
+        let last_modified = "Wed, 21 Oct 2026 07:28:00 GMT";
+        ctx.index
+            .cache
+            .save_validators(None, Some(last_modified))
+            .await
+            .unwrap();
+
+        let server = MockServer::start();
+        let index_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/index.json")
+                .header("If-Modified-Since", last_modified);
+            then.status(304);
+        });
+        let signature_mock = server.mock(|when, then| {
+            when.method(GET).path("/index.json.minisig");
+            then.status(200).body("untrusted comment: unused\n");
+        });
+
+        let synced = sync_and_verify_index(&ctx, &server.base_url(), true)
+            .await
+            .unwrap();
+        assert!(synced.is_none());
+        index_mock.assert();
+        signature_mock.assert_hits(0);
+        assert_eq!(
+            ctx.index
+                .cache
+                .load_last_modified()
+                .await
+                .unwrap()
+                .as_deref(),
+            Some(last_modified)
+        );
+    }
+}
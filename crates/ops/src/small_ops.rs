@@ -13,9 +13,9 @@ use crate::security;
 use crate::self_update as self_update_module;
 
 // Re-export all public functions to maintain API compatibility
-pub use health::check_health;
-pub use maintenance::{cleanup, history, rollback};
+pub use health::{check_health, health_summary};
+pub use maintenance::{cleanup, compact_store, history, import_state, rollback};
 pub use query::{list_packages, package_info, search_packages};
-pub use repository::{add_repo, list_repos, remove_repo, reposync};
+pub use repository::{add_repo, list_repos, mirror_status, remove_repo, reposync};
 pub use security::{audit, update_vulndb, vulndb_stats};
 pub use self_update_module::self_update;
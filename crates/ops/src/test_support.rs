@@ -0,0 +1,31 @@
+//! Fixtures shared by the crate's unit tests
+
+use crate::{OpsContextBuilder, OpsCtx};
+use sps2_builder::Builder;
+use sps2_config::Config;
+use sps2_net::{NetClient, NetConfig};
+use sps2_resolver::Resolver;
+use sps2_state::StateManager;
+use sps2_store::PackageStore;
+use std::path::Path;
+
+/// Context over `state` and `store` with the default config and an empty
+/// index under `root`
+pub(crate) fn ops_ctx(root: &Path, state: StateManager, store: PackageStore) -> OpsCtx {
+    let (tx, _rx) = sps2_events::channel();
+    let index = sps2_index::IndexManager::new(root.join("index"));
+    let net = NetClient::new_without_proxies(NetConfig::default()).expect("net client");
+    let resolver = Resolver::with_events(index.clone(), tx.clone());
+
+    OpsContextBuilder::new()
+        .with_state(state)
+        .with_store(store)
+        .with_index(index)
+        .with_net(net)
+        .with_resolver(resolver)
+        .with_builder(Builder::new())
+        .with_event_sender(tx)
+        .with_config(Config::default())
+        .build()
+        .expect("ops ctx")
+}
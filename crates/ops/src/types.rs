@@ -124,6 +124,38 @@ pub enum IssueSeverity {
     Critical,
 }
 
+/// Combined health summary of live files, store, and state database
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Overall status (the worst of the individual areas)
+    pub status: HealthStatus,
+    /// Quick verification of the live prefix
+    pub live: HealthArea,
+    /// Store integrity spot-check
+    pub store: HealthArea,
+    /// State database consistency check
+    pub state: HealthArea,
+}
+
+impl HealthReport {
+    /// Check if every area is healthy
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        self.status == HealthStatus::Healthy
+    }
+}
+
+/// Health of a single area in a [`HealthReport`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HealthArea {
+    /// Health status of this area
+    pub status: HealthStatus,
+    /// Human-readable findings (empty when healthy)
+    pub details: Vec<String>,
+    /// Check duration in milliseconds
+    pub check_duration_ms: u64,
+}
+
 /// Install request type
 #[derive(Clone, Debug)]
 pub enum InstallRequest {
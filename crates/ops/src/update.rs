@@ -3,6 +3,7 @@
 //! Handles package updates, respecting version constraints.
 //! Delegates to `sps2_install` crate for the actual update logic.
 
+use crate::install::PackageSizes;
 use crate::{InstallReport, OpsCtx};
 use sps2_errors::Error;
 use sps2_events::{
@@ -35,7 +36,9 @@ pub async fn update(ctx: &OpsCtx, package_names: &[String]) -> Result<InstallRep
     }
 
     // Create installer
-    let config = InstallConfig::default();
+    let config = InstallConfig::default()
+        .with_max_batch_size(ctx.config.general.max_batch_size)
+        .with_keep_on_failure(ctx.config.general.keep_on_failure);
     let mut installer = Installer::new(
         config,
         ctx.resolver.clone(),
@@ -110,9 +113,11 @@ pub async fn update(ctx: &OpsCtx, package_names: &[String]) -> Result<InstallRep
         }));
     })?;
 
+    let sizes = PackageSizes::load(ctx, &installed_before, &result.state_id).await?;
     let report = create_update_report(
         &result,
         &installed_map,
+        &sizes,
         start,
         ctx,
         UpdateReportContext {
@@ -135,6 +140,7 @@ struct UpdateReportContext<'a> {
 fn create_update_report(
     result: &sps2_install::InstallResult,
     installed_map: &std::collections::HashMap<String, sps2_types::Version>,
+    sizes: &PackageSizes,
     start: std::time::Instant,
     ctx: &OpsCtx,
     context: UpdateReportContext<'_>,
@@ -148,7 +154,7 @@ fn create_update_report(
                 name: pkg.name.clone(),
                 from_version: None,
                 to_version: Some(pkg.version.clone()),
-                size: None,
+                size: sizes.after(&pkg.name),
             })
             .collect(),
         updated: result
@@ -158,7 +164,7 @@ fn create_update_report(
                 name: pkg.name.clone(),
                 from_version: installed_map.get(&pkg.name).cloned(),
                 to_version: Some(pkg.version.clone()),
-                size: None,
+                size: sizes.after(&pkg.name),
             })
             .collect(),
         removed: result
@@ -168,7 +174,7 @@ fn create_update_report(
                 name: pkg.name.clone(),
                 from_version: Some(pkg.version.clone()),
                 to_version: None,
-                size: None,
+                size: sizes.before(&pkg.name),
             })
             .collect(),
         state_id: result.state_id,
@@ -3,6 +3,7 @@
 //! Handles package upgrades, ignoring version constraints to get latest versions.
 //! Delegates to `sps2_install` crate for the actual upgrade logic.
 
+use crate::install::PackageSizes;
 use crate::{InstallReport, OpsCtx};
 use sps2_errors::Error;
 use sps2_events::{
@@ -36,7 +37,9 @@ pub async fn upgrade(ctx: &OpsCtx, package_names: &[String]) -> Result<InstallRe
     }
 
     // Create installer
-    let config = InstallConfig::default();
+    let config = InstallConfig::default()
+        .with_max_batch_size(ctx.config.general.max_batch_size)
+        .with_keep_on_failure(ctx.config.general.keep_on_failure);
     let mut installer = Installer::new(
         config,
         ctx.resolver.clone(),
@@ -111,9 +114,11 @@ pub async fn upgrade(ctx: &OpsCtx, package_names: &[String]) -> Result<InstallRe
         }));
     })?;
 
+    let sizes = PackageSizes::load(ctx, &installed_before, &result.state_id).await?;
     let report = create_upgrade_report(
         &result,
         &installed_map,
+        &sizes,
         start,
         ctx,
         UpdateReportContext {
@@ -136,6 +141,7 @@ struct UpdateReportContext<'a> {
 fn create_upgrade_report(
     result: &sps2_install::InstallResult,
     installed_map: &std::collections::HashMap<String, sps2_types::Version>,
+    sizes: &PackageSizes,
     start: std::time::Instant,
     ctx: &OpsCtx,
     context: UpdateReportContext<'_>,
@@ -149,7 +155,7 @@ fn create_upgrade_report(
                 name: pkg.name.clone(),
                 from_version: None,
                 to_version: Some(pkg.version.clone()),
-                size: None,
+                size: sizes.after(&pkg.name),
             })
             .collect(),
         updated: result
@@ -159,7 +165,7 @@ fn create_upgrade_report(
                 name: pkg.name.clone(),
                 from_version: installed_map.get(&pkg.name).cloned(),
                 to_version: Some(pkg.version.clone()),
-                size: None,
+                size: sizes.after(&pkg.name),
             })
             .collect(),
         removed: result
@@ -169,7 +175,7 @@ fn create_upgrade_report(
                 name: pkg.name.clone(),
                 from_version: Some(pkg.version.clone()),
                 to_version: None,
-                size: None,
+                size: sizes.before(&pkg.name),
             })
             .collect(),
         state_id: result.state_id,
@@ -166,6 +166,8 @@ impl<S: ObjectStore> Publisher<S> {
                 description: None,
                 homepage: None,
                 license: None,
+                source: None,
+                files: Vec::new(),
             };
             index.add_version(a.name.clone(), a.version.clone(), entry);
         }
@@ -0,0 +1,219 @@
+//! Conflict detection across a resolved package set
+//!
+//! Runs once the set of packages is fixed and before an execution plan is
+//! built, so an impossible combination is rejected before anything is
+//! downloaded. Declared conflicts are checked for every package; file
+//! overlaps only where a package's file list is known up front, which is the
+//! case for local package files and for index entries that publish one.
+
+use crate::{PackageId, ResolvedNode};
+use sps2_errors::{Error, PackageError};
+use sps2_types::is_installed_entry;
+use std::collections::{BTreeMap, HashMap};
+
+/// How conflicts between resolved packages are treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConflictOptions {
+    /// Report a path provided by two packages as a warning rather than an
+    /// error when one of them replaces the other
+    pub replaced_overlaps_as_warnings: bool,
+}
+
+impl ConflictOptions {
+    /// Downgrade file overlaps between replacing packages to warnings
+    #[must_use]
+    pub fn with_replaced_overlaps_as_warnings(mut self, enabled: bool) -> Self {
+        self.replaced_overlaps_as_warnings = enabled;
+        self
+    }
+}
+
+/// Check the resolved packages against each other
+///
+/// `files` maps packages to the paths they provide; packages without an
+/// entry are only checked for declared conflicts. Packages and paths are
+/// visited in sorted order so the reported pair is deterministic. Returns
+/// the warnings for overlaps that were downgraded.
+///
+/// # Errors
+///
+/// Returns `PackageError::PackageConflict` for the first package that
+/// declares a conflict with another resolved package, or for the first path
+/// provided by two packages that the options do not allow.
+pub(crate) fn check_conflicts(
+    nodes: &HashMap<PackageId, ResolvedNode>,
+    files: &HashMap<PackageId, Vec<String>>,
+    options: ConflictOptions,
+) -> Result<Vec<String>, Error> {
+    let mut sorted: Vec<(&PackageId, &ResolvedNode)> = nodes.iter().collect();
+    sorted.sort_by(|a, b| (&a.0.name, &a.0.version).cmp(&(&b.0.name, &b.0.version)));
+
+    for (id, node) in &sorted {
+        for spec in &node.conflicts {
+            let conflicting = sorted.iter().find(|(other_id, other)| {
+                other_id != id
+                    && other.name == spec.name
+                    && spec.version_spec.matches(&other.version)
+            });
+            if let Some((other_id, _)) = conflicting {
+                return Err(PackageError::PackageConflict {
+                    a: id.to_string(),
+                    b: other_id.to_string(),
+                    reason: format!("declared conflict `{spec}`"),
+                }
+                .into());
+            }
+        }
+    }
+
+    let mut claims: BTreeMap<&str, Vec<&PackageId>> = BTreeMap::new();
+    for (id, _) in &sorted {
+        for path in files.get(*id).into_iter().flatten() {
+            if path.ends_with('/') || !is_installed_entry(path) {
+                continue;
+            }
+            claims.entry(path.as_str()).or_default().push(*id);
+        }
+    }
+
+    let mut warnings = Vec::new();
+    for (path, owners) in claims {
+        for (index, a) in owners.iter().enumerate() {
+            for b in &owners[index + 1..] {
+                let (node_a, node_b) = (&nodes[*a], &nodes[*b]);
+                let replacement = if node_a.replaces(node_b) {
+                    Some((a, b))
+                } else if node_b.replaces(node_a) {
+                    Some((b, a))
+                } else {
+                    None
+                };
+
+                match replacement {
+                    Some((newer, older)) if options.replaced_overlaps_as_warnings => {
+                        warnings.push(format!(
+                            "{a} and {b} both provide {path} ({newer} replaces {older})"
+                        ));
+                    }
+                    _ => {
+                        return Err(PackageError::PackageConflict {
+                            a: a.to_string(),
+                            b: b.to_string(),
+                            reason: format!("both provide {path}"),
+                        }
+                        .into());
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sps2_types::package::PackageSpec;
+    use sps2_types::Version;
+    use std::path::PathBuf;
+
+    fn node(name: &str, version: &str, conflicts: &[&str], replaces: &[&str]) -> ResolvedNode {
+        let parse = |specs: &[&str]| {
+            specs
+                .iter()
+                .map(|s| PackageSpec::parse(s).unwrap())
+                .collect()
+        };
+        ResolvedNode::local(
+            name.to_string(),
+            Version::parse(version).unwrap(),
+            PathBuf::from(format!("{name}.sp")),
+            Vec::new(),
+        )
+        .with_relations(parse(conflicts), parse(replaces))
+    }
+
+    fn set(nodes: Vec<ResolvedNode>) -> HashMap<PackageId, ResolvedNode> {
+        nodes.into_iter().map(|n| (n.package_id(), n)).collect()
+    }
+
+    fn conflict(result: Result<Vec<String>, Error>) -> (String, String, String) {
+        match result {
+            Err(Error::Package(PackageError::PackageConflict { a, b, reason })) => (a, b, reason),
+            other => panic!("expected a package conflict, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn declared_conflicts_match_name_and_version() {
+        let nodes = set(vec![
+            node("openssl", "3.2.0", &["libressl"], &[]),
+            node("libressl", "3.9.0", &[], &[]),
+        ]);
+        let (a, b, reason) = conflict(check_conflicts(
+            &nodes,
+            &HashMap::new(),
+            ConflictOptions::default(),
+        ));
+        assert_eq!(
+            (a.as_str(), b.as_str(), reason.as_str()),
+            (
+                "openssl-3.2.0",
+                "libressl-3.9.0",
+                "declared conflict `libressl`"
+            )
+        );
+
+        let nodes = set(vec![
+            node("openssl", "3.2.0", &["libressl<3.0.0"], &[]),
+            node("libressl", "3.9.0", &[], &[]),
+        ]);
+        let warnings = check_conflicts(&nodes, &HashMap::new(), ConflictOptions::default());
+        assert!(warnings.unwrap().is_empty());
+    }
+
+    #[test]
+    fn overlaps_are_downgraded_only_between_replacing_packages() {
+        let nodes = set(vec![
+            node("vim", "9.1.0", &[], &["vi"]),
+            node("vi", "1.0.0", &[], &[]),
+            node("nano", "8.0.0", &[], &[]),
+        ]);
+        let mut files: HashMap<PackageId, Vec<String>> = nodes
+            .keys()
+            .map(|id| {
+                let paths = vec![
+                    "manifest.toml".to_string(),
+                    "bin/".to_string(),
+                    format!("bin/{}", id.name),
+                ];
+                (id.clone(), paths)
+            })
+            .collect();
+        for name in ["vim", "vi"] {
+            let id = nodes.keys().find(|id| id.name == name).unwrap();
+            files
+                .get_mut(id)
+                .unwrap()
+                .push("share/man/man1/vi.1".to_string());
+        }
+
+        let (a, b, reason) = conflict(check_conflicts(&nodes, &files, ConflictOptions::default()));
+        assert_eq!((a.as_str(), b.as_str()), ("vi-1.0.0", "vim-9.1.0"));
+        assert_eq!(reason, "both provide share/man/man1/vi.1");
+
+        let options = ConflictOptions::default().with_replaced_overlaps_as_warnings(true);
+        let warnings = check_conflicts(&nodes, &files, options).unwrap();
+        assert_eq!(
+            warnings,
+            vec!["vi-1.0.0 and vim-9.1.0 both provide share/man/man1/vi.1 (vim-9.1.0 replaces vi-1.0.0)"]
+        );
+
+        // Unrelated packages still conflict with the option enabled
+        let id = nodes.keys().find(|id| id.name == "nano").unwrap();
+        files.get_mut(id).unwrap().push("bin/vim".to_string());
+        let (a, b, _) = conflict(check_conflicts(&nodes, &files, options));
+        assert_eq!((a.as_str(), b.as_str()), ("nano-8.0.0", "vim-9.1.0"));
+    }
+}
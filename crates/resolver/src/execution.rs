@@ -1,9 +1,12 @@
 // src/execution.rs
 //! Planning and metadata for *parallel* package installation/build.
 //!
-//! Public API is **unchanged**, but the internals are optimised
+//! Plans are layered with Kahn's algorithm, so either constructor reports
+//! cycles and unknown packages as errors, and batches can be capped with
+//! [`ExecutionPlan::with_max_batch_size`].
 
 use crate::{graph::DependencyGraph, NodeAction, PackageId};
+use sps2_errors::{Error, PackageError};
 use std::collections::{HashMap, VecDeque};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -81,6 +84,7 @@ impl NodeMeta {
 pub struct ExecutionPlan {
     batches: Vec<Vec<PackageId>>,
     metadata: HashMap<PackageId, Arc<NodeMeta>>,
+    max_batch_size: Option<usize>,
 }
 
 impl ExecutionPlan {
@@ -91,17 +95,44 @@ impl ExecutionPlan {
     /// Build a plan from an already topologically-sorted list (`sorted`) and
     /// its originating dependency graph.
     ///
-    /// # Panics
+    /// Prefer [`Self::from_graph`] unless a sorted list is already at hand.
     ///
-    /// Panics if `graph` does not contain every [`PackageId`] present in
-    /// `sorted` (the resolver guarantees this invariant).
-    #[must_use]
-    pub fn from_sorted_packages(sorted: &[PackageId], graph: &DependencyGraph) -> Self {
-        let mut metadata: HashMap<PackageId, Arc<NodeMeta>> = HashMap::with_capacity(sorted.len());
-        let mut in_degree: HashMap<&PackageId, usize> = HashMap::with_capacity(sorted.len());
+    /// # Errors
+    ///
+    /// Returns [`sps2_errors::PackageError::NotFound`] if `graph` does not
+    /// contain every [`PackageId`] present in `sorted`, and
+    /// [`sps2_errors::PackageError::DependencyCycle`] if the graph contains a
+    /// cycle.
+    pub fn from_sorted_packages(
+        sorted: &[PackageId],
+        graph: &DependencyGraph,
+    ) -> Result<Self, Error> {
+        Self::layered(sorted.iter(), graph)
+    }
+
+    /// Build a plan directly from a dependency graph.
+    ///
+    /// Packages are layered with Kahn's algorithm over `graph.edges`, so the
+    /// caller does not need to topologically sort the graph first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`sps2_errors::PackageError::DependencyCycle`] listing the packages on the
+    /// cycle if the graph contains one.
+    pub fn from_graph(graph: &DependencyGraph) -> Result<Self, Error> {
+        Self::layered(graph.nodes.keys(), graph)
+    }
+
+    /// Shared Kahn layering; fails with the packages on a cycle.
+    fn layered<'a>(
+        ids: impl ExactSizeIterator<Item = &'a PackageId> + Clone,
+        graph: &'a DependencyGraph,
+    ) -> Result<Self, Error> {
+        let mut metadata: HashMap<PackageId, Arc<NodeMeta>> = HashMap::with_capacity(ids.len());
+        let mut in_degree: HashMap<&PackageId, usize> = HashMap::with_capacity(ids.len());
 
         // 1) Pre-compute in-degrees in O(e)
-        for id in sorted {
+        for id in ids.clone() {
             in_degree.insert(id, 0);
         }
         for tos in graph.edges.values() {
@@ -113,12 +144,14 @@ impl ExecutionPlan {
         }
 
         // 2) Create NodeMeta and reverse edges
-        for id in sorted {
+        for id in ids {
             let action = graph
                 .nodes
                 .get(id)
                 .map(|n| n.action.clone())
-                .expect("package missing from graph");
+                .ok_or_else(|| PackageError::NotFound {
+                    name: id.to_string(),
+                })?;
 
             let meta = Arc::new(NodeMeta::new(
                 action,
@@ -146,6 +179,21 @@ impl ExecutionPlan {
         let mut remaining = in_degree.len();
 
         while remaining > 0 {
+            // Nothing runnable but packages left over: they sit on or behind a
+            // cycle, so report the cycle itself rather than everything stuck
+            if queue.is_empty() {
+                let cycle = graph.find_cycle().unwrap_or_else(|| {
+                    let mut stuck: Vec<PackageId> = metadata
+                        .iter()
+                        .filter(|(_, m)| m.in_degree() > 0)
+                        .map(|(id, _)| id.clone())
+                        .collect();
+                    stuck.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+                    stuck
+                });
+                return Err(DependencyGraph::cycle_error(&cycle));
+            }
+
             let mut batch: Vec<PackageId> = Vec::with_capacity(queue.len());
 
             for _ in 0..queue.len() {
@@ -156,9 +204,10 @@ impl ExecutionPlan {
                 // Decrement children
                 if let Some(children) = graph.edges.get(id) {
                     for child in children {
-                        let child_meta = metadata
-                            .get(child)
-                            .expect("child in metadata; resolver invariant");
+                        let child_meta =
+                            metadata.get(child).ok_or_else(|| PackageError::NotFound {
+                                name: child.to_string(),
+                            })?;
                         if child_meta.decrement_in_degree() == 0 {
                             queue.push_back(child);
                         }
@@ -166,16 +215,63 @@ impl ExecutionPlan {
                 }
             }
 
+            // Queue order follows hash iteration; sort so plans are reproducible
+            batch.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
             batches.push(batch);
         }
 
-        Self { batches, metadata }
+        Ok(Self {
+            batches,
+            metadata,
+            max_batch_size: None,
+        })
+    }
+
+    /// Cap the number of packages per batch.
+    ///
+    /// Batches larger than the cap are split into sequential sub-batches of
+    /// at most that many packages (a cap of 0 is treated as 1), and executors
+    /// keep at most that many packages in flight. `None` keeps every layer in
+    /// a single batch.
+    #[must_use]
+    pub fn with_max_batch_size(mut self, max_batch_size: Option<usize>) -> Self {
+        self.max_batch_size = max_batch_size.map(|cap| cap.max(1));
+        if let Some(cap) = self.max_batch_size {
+            self.split_batches(cap);
+        }
+        self
+    }
+
+    /// Split every batch larger than `cap` into consecutive chunks.
+    ///
+    /// Packages inside a layer are independent, so running its chunks one
+    /// after another keeps every dependency ahead of its dependents.
+    fn split_batches(&mut self, cap: usize) {
+        if self.batches.iter().all(|batch| batch.len() <= cap) {
+            return;
+        }
+        self.batches = std::mem::take(&mut self.batches)
+            .into_iter()
+            .flat_map(|batch| {
+                batch
+                    .chunks(cap)
+                    .map(<[PackageId]>::to_vec)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
     }
 
     // ---------------------------------------------------------------------
     // Inspection helpers
     // ---------------------------------------------------------------------
 
+    /// Most packages an executor may run at once, if capped.
+    #[inline]
+    #[must_use]
+    pub fn max_in_flight(&self) -> Option<usize> {
+        self.max_batch_size
+    }
+
     /// Layered batches; inside each slice packages are independent.
     #[inline]
     #[must_use]
@@ -183,6 +279,70 @@ impl ExecutionPlan {
         &self.batches
     }
 
+    /// Longest dependency chain through `graph`, counted in packages.
+    ///
+    /// This bounds how many steps an install takes however much runs in
+    /// parallel. The chain starts at a package with no dependencies and
+    /// ends at its last dependent; ties go to the chain found first.
+    #[must_use]
+    pub fn critical_path(&self, graph: &DependencyGraph) -> Vec<PackageId> {
+        self.critical_path_by(graph, |_| 1)
+    }
+
+    /// Most expensive dependency chain through `graph`, summing `cost` over
+    /// the packages on it.
+    #[must_use]
+    pub fn critical_path_by(
+        &self,
+        graph: &DependencyGraph,
+        cost: impl Fn(&PackageId) -> u64,
+    ) -> Vec<PackageId> {
+        self.longest_path(
+            |id| graph.edges.get(id).map_or(&[][..], Vec::as_slice),
+            cost,
+        )
+    }
+
+    /// Longest path over the batches, which are already in dependency order.
+    fn longest_path<'a>(
+        &'a self,
+        dependents: impl Fn(&PackageId) -> &'a [PackageId],
+        cost: impl Fn(&PackageId) -> u64,
+    ) -> Vec<PackageId> {
+        // Total cost of the best chain ending at each package, and its predecessor
+        let mut best: HashMap<&PackageId, (u64, Option<&PackageId>)> = self
+            .batches
+            .iter()
+            .flatten()
+            .map(|id| (id, (cost(id), None)))
+            .collect();
+
+        let mut end: Option<(&PackageId, u64)> = None;
+        for id in self.batches.iter().flatten() {
+            let total = best[id].0;
+            if end.is_none_or(|(_, longest)| total > longest) {
+                end = Some((id, total));
+            }
+            for dependent in dependents(id) {
+                if let Some(slot) = best.get_mut(dependent) {
+                    let candidate = total + cost(dependent);
+                    if candidate > slot.0 {
+                        *slot = (candidate, Some(id));
+                    }
+                }
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut current = end.map(|(id, _)| id);
+        while let Some(id) = current {
+            path.push(id.clone());
+            current = best[id].1;
+        }
+        path.reverse();
+        path
+    }
+
     /// Per-package metadata (constant during execution).
     #[inline]
     #[must_use]
@@ -266,3 +426,277 @@ impl ExecutionPlan {
 // -------------------------------------------------------------------------
 // Stats helper (unchanged public fields, lint-clean implementation)
 // -------------------------------------------------------------------------
+
+/// Summary figures for an [`ExecutionPlan`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExecutionStats {
+    /// Packages in the plan.
+    pub total_packages: usize,
+    /// Batches run one after another.
+    pub total_batches: usize,
+    /// Packages in the largest batch.
+    pub max_batch_size: usize,
+    /// Packages fetched from a repository.
+    pub download_count: usize,
+    /// Packages installed from local files.
+    pub local_count: usize,
+    /// Packages on the longest dependency chain.
+    pub critical_path_len: usize,
+}
+
+impl ExecutionPlan {
+    /// Compute summary figures for this plan.
+    #[must_use]
+    pub fn stats(&self) -> ExecutionStats {
+        let download_count = self
+            .metadata
+            .values()
+            .filter(|m| m.action == NodeAction::Download)
+            .count();
+        ExecutionStats {
+            total_packages: self.metadata.len(),
+            total_batches: self.batches.len(),
+            max_batch_size: self.batches.iter().map(Vec::len).max().unwrap_or(0),
+            download_count,
+            local_count: self.metadata.len() - download_count,
+            critical_path_len: self
+                .longest_path(|id| self.metadata[id].parents(), |_| 1)
+                .len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ResolvedNode;
+    use sps2_errors::PackageError;
+    use sps2_types::Version;
+    use std::collections::HashSet;
+
+    fn node(name: &str) -> ResolvedNode {
+        ResolvedNode::download(
+            name.to_string(),
+            Version::parse("1.0.0").unwrap(),
+            format!("https://example.invalid/{name}.sp"),
+            vec![],
+        )
+    }
+
+    fn id(name: &str) -> PackageId {
+        node(name).package_id()
+    }
+
+    /// Edges point from a dependency to its dependent: `a` feeds `b` and `c`,
+    /// which both feed `d`.
+    fn diamond() -> DependencyGraph {
+        let mut graph = DependencyGraph::new();
+        for name in ["a", "b", "c", "d"] {
+            graph.add_node(node(name));
+        }
+        graph.add_edge(&id("a"), &id("b"));
+        graph.add_edge(&id("a"), &id("c"));
+        graph.add_edge(&id("b"), &id("d"));
+        graph.add_edge(&id("c"), &id("d"));
+        graph
+    }
+
+    fn layers(plan: &ExecutionPlan) -> Vec<HashSet<String>> {
+        plan.batches()
+            .iter()
+            .map(|batch| batch.iter().map(|id| id.name.clone()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn from_graph_matches_from_sorted_packages() {
+        let graph = diamond();
+        let sorted = graph.topological_sort().unwrap();
+
+        let from_sorted = ExecutionPlan::from_sorted_packages(&sorted, &graph).unwrap();
+        let from_graph = ExecutionPlan::from_graph(&graph).unwrap();
+
+        assert_eq!(layers(&from_sorted), layers(&from_graph));
+        assert_eq!(
+            layers(&from_graph),
+            vec![
+                HashSet::from(["a".to_string()]),
+                HashSet::from(["b".to_string(), "c".to_string()]),
+                HashSet::from(["d".to_string()]),
+            ]
+        );
+        assert_eq!(from_graph.package_count(), 4);
+    }
+
+    #[test]
+    fn max_batch_size_splits_layers_in_order() {
+        // Six independent roots, all feeding one dependent
+        let mut graph = DependencyGraph::new();
+        let roots = ["r1", "r2", "r3", "r4", "r5", "r6"];
+        for name in roots.into_iter().chain(["top"]) {
+            graph.add_node(node(name));
+        }
+        for name in roots {
+            graph.add_edge(&id(name), &id("top"));
+        }
+        let uncapped = ExecutionPlan::from_graph(&graph).unwrap();
+        assert_eq!(uncapped.stats().max_batch_size, 6);
+        assert_eq!(uncapped.stats().total_batches, 2);
+
+        let capped = uncapped.clone().with_max_batch_size(Some(4));
+        let sizes: Vec<usize> = capped.batches().iter().map(Vec::len).collect();
+        assert_eq!(sizes, [4, 2, 1]);
+        assert_eq!(
+            capped.stats(),
+            ExecutionStats {
+                total_packages: 7,
+                total_batches: 3,
+                max_batch_size: 4,
+                download_count: 7,
+                local_count: 0,
+                critical_path_len: 2,
+            }
+        );
+        assert_eq!(layers(&capped)[2], HashSet::from(["top".to_string()]));
+        let mut split: Vec<PackageId> = capped.batches()[..2].concat();
+        split.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(split, roots.map(id));
+
+        let serial = uncapped.clone().with_max_batch_size(Some(0));
+        assert_eq!(serial.stats().total_batches, 7);
+        assert_eq!(serial.max_in_flight(), Some(1));
+        assert_eq!(capped.max_in_flight(), Some(4));
+        assert_eq!(uncapped.max_in_flight(), None);
+        assert_eq!(uncapped.with_max_batch_size(None).stats().total_batches, 2);
+    }
+
+    #[test]
+    fn batches_are_identical_across_builds() {
+        let names = [
+            "zlib", "openssl", "curl", "libssh2", "nghttp2", "brotli", "xz",
+        ];
+        let build = |order: &mut dyn Iterator<Item = &&str>| {
+            // Each graph gets freshly seeded hash maps
+            let mut graph = DependencyGraph::new();
+            for name in order {
+                graph.add_node(node(name));
+            }
+            graph.add_edge(&id("zlib"), &id("curl"));
+            graph.add_edge(&id("openssl"), &id("curl"));
+            ExecutionPlan::from_graph(&graph).unwrap()
+        };
+
+        let first = build(&mut names.iter());
+        let second = build(&mut names.iter().rev());
+        assert_eq!(first.batches(), second.batches());
+        assert_eq!(
+            format!("{:?}", first.batches()),
+            format!("{:?}", second.batches())
+        );
+
+        let names: Vec<&str> = first.batches()[0]
+            .iter()
+            .map(|id| id.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            ["brotli", "libssh2", "nghttp2", "openssl", "xz", "zlib"]
+        );
+    }
+
+    #[test]
+    fn critical_path_follows_the_longest_chain() {
+        let graph = diamond();
+        let plan = ExecutionPlan::from_graph(&graph).unwrap();
+
+        assert_eq!(plan.critical_path(&graph), [id("a"), id("b"), id("d")]);
+        assert_eq!(plan.stats().critical_path_len, 3);
+
+        let weighted = plan.critical_path_by(&graph, |id| if id.name == "c" { 10 } else { 1 });
+        assert_eq!(weighted, [id("a"), id("c"), id("d")]);
+
+        // A longer side chain wins over the diamond
+        let mut graph = diamond();
+        for name in ["x", "y", "z", "w"] {
+            graph.add_node(node(name));
+        }
+        graph.add_edge(&id("x"), &id("y"));
+        graph.add_edge(&id("y"), &id("z"));
+        graph.add_edge(&id("z"), &id("w"));
+        let plan = ExecutionPlan::from_graph(&graph).unwrap();
+        assert_eq!(
+            plan.critical_path(&graph),
+            [id("x"), id("y"), id("z"), id("w")]
+        );
+        assert_eq!(plan.stats().critical_path_len, 4);
+        assert!(ExecutionPlan::from_graph(&DependencyGraph::new())
+            .unwrap()
+            .critical_path(&graph)
+            .is_empty());
+    }
+
+    #[test]
+    fn from_graph_rejects_cycles() {
+        let mut graph = diamond();
+        graph.add_edge(&id("d"), &id("a"));
+
+        let err = ExecutionPlan::from_graph(&graph).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Package(PackageError::DependencyCycle { ref packages })
+                if packages.len() == 3 && packages[0] == "a-1.0.0" && packages[2] == "d-1.0.0"
+        ));
+    }
+
+    #[test]
+    fn from_sorted_packages_rejects_bad_input() {
+        let mut graph = diamond();
+        graph.add_edge(&id("d"), &id("a"));
+        let sorted = [id("a"), id("b"), id("c"), id("d")];
+        let err = ExecutionPlan::from_sorted_packages(&sorted, &graph).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Package(PackageError::DependencyCycle { .. })
+        ));
+
+        let err = ExecutionPlan::from_sorted_packages(&[id("missing")], &diamond()).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Package(PackageError::NotFound { ref name }) if name == "missing-1.0.0"
+        ));
+
+        // A dependent left out of the sorted list is reported, not a panic
+        let err = ExecutionPlan::from_sorted_packages(&[id("a")], &diamond()).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Package(PackageError::NotFound { ref name }) if name == "b-1.0.0" || name == "c-1.0.0"
+        ));
+    }
+
+    #[test]
+    fn cycle_error_names_only_the_packages_on_the_loop() {
+        // b and c depend on each other; d only depends on them and a is fine
+        let mut graph = DependencyGraph::new();
+        for name in ["a", "b", "c", "d"] {
+            graph.add_node(node(name));
+        }
+        graph.add_edge(&id("a"), &id("b"));
+        graph.add_edge(&id("b"), &id("c"));
+        graph.add_edge(&id("c"), &id("b"));
+        graph.add_edge(&id("c"), &id("d"));
+
+        let err = ExecutionPlan::from_graph(&graph).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Package(PackageError::DependencyCycle { ref packages })
+                if *packages == ["b-1.0.0", "c-1.0.0"]
+        ));
+        assert!(err
+            .to_string()
+            .ends_with("dependency cycle detected: b-1.0.0 -> c-1.0.0 -> b-1.0.0"));
+        assert_eq!(
+            graph.topological_sort().unwrap_err().to_string(),
+            err.to_string()
+        );
+    }
+}
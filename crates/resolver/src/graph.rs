@@ -1,5 +1,6 @@
 //! Dependency graph types and operations
 
+use sps2_types::package::PackageSpec;
 use sps2_types::{Version, VersionSpec};
 use std::fmt;
 use std::path::PathBuf;
@@ -52,13 +53,32 @@ pub struct DepEdge {
     pub spec: VersionSpec,
     /// Dependency kind
     pub kind: DepKind,
+    /// Weak (optional) dependency: only linked when the package is already
+    /// part of the resolution, never resolved on its own
+    pub weak: bool,
 }
 
 impl DepEdge {
     /// Create new dependency edge
     #[must_use]
     pub fn new(name: String, spec: VersionSpec, kind: DepKind) -> Self {
-        Self { name, spec, kind }
+        Self {
+            name,
+            spec,
+            kind,
+            weak: false,
+        }
+    }
+
+    /// Create new weak runtime dependency edge
+    #[must_use]
+    pub fn weak(name: String, spec: VersionSpec) -> Self {
+        Self {
+            name,
+            spec,
+            kind: DepKind::Runtime,
+            weak: true,
+        }
     }
 
     /// Check if a version satisfies this edge
@@ -87,6 +107,10 @@ pub struct ResolvedNode {
     pub signature_url: Option<String>,
     /// Expected BLAKE3 hash for integrity verification (if remote)
     pub expected_hash: Option<sps2_hash::Hash>,
+    /// Packages declared as conflicting with this one
+    pub conflicts: Vec<PackageSpec>,
+    /// Packages this one replaces
+    pub replaces: Vec<PackageSpec>,
 }
 
 impl ResolvedNode {
@@ -102,6 +126,8 @@ impl ResolvedNode {
             path: None,
             signature_url: None,
             expected_hash: None,
+            conflicts: Vec::new(),
+            replaces: Vec::new(),
         }
     }
 
@@ -117,9 +143,31 @@ impl ResolvedNode {
             path: Some(path),
             signature_url: None,
             expected_hash: None,
+            conflicts: Vec::new(),
+            replaces: Vec::new(),
         }
     }
 
+    /// Set the packages this one conflicts with and replaces
+    #[must_use]
+    pub fn with_relations(
+        mut self,
+        conflicts: Vec<PackageSpec>,
+        replaces: Vec<PackageSpec>,
+    ) -> Self {
+        self.conflicts = conflicts;
+        self.replaces = replaces;
+        self
+    }
+
+    /// Whether this package replaces `other`
+    #[must_use]
+    pub fn replaces(&self, other: &ResolvedNode) -> bool {
+        self.replaces
+            .iter()
+            .any(|spec| spec.name == other.name && spec.version_spec.matches(&other.version))
+    }
+
     /// Get package ID
     #[must_use]
     pub fn package_id(&self) -> PackageId {
@@ -173,45 +221,71 @@ impl DependencyGraph {
     /// Check for cycles using DFS
     #[must_use]
     pub fn has_cycles(&self) -> bool {
-        use std::collections::HashSet;
+        self.find_cycle().is_some()
+    }
 
-        let mut visited = HashSet::new();
-        let mut rec_stack = HashSet::new();
+    /// Find a dependency cycle using DFS
+    ///
+    /// Returns the packages on the cycle, each a dependency of the next and
+    /// the last a dependency of the first, starting from the lowest package
+    /// by name and version. Returns `None` if the graph is acyclic.
+    #[must_use]
+    pub fn find_cycle(&self) -> Option<Vec<PackageId>> {
+        use std::collections::HashSet;
 
-        for node_id in self.nodes.keys() {
-            if !visited.contains(node_id)
-                && self.has_cycle_util(node_id, &mut visited, &mut rec_stack)
-            {
-                return true;
-            }
-        }
+        let mut roots: Vec<&PackageId> = self.nodes.keys().collect();
+        roots.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
 
-        false
+        let mut finished = HashSet::new();
+        let mut path = Vec::new();
+        roots
+            .into_iter()
+            .find_map(|root| self.find_cycle_util(root, &mut finished, &mut path))
     }
 
     /// Utility function for cycle detection
-    fn has_cycle_util(
-        &self,
-        node_id: &PackageId,
-        visited: &mut std::collections::HashSet<PackageId>,
-        rec_stack: &mut std::collections::HashSet<PackageId>,
-    ) -> bool {
-        visited.insert(node_id.clone());
-        rec_stack.insert(node_id.clone());
-
-        if let Some(dependencies) = self.edges.get(node_id) {
-            for dep in dependencies {
-                if !visited.contains(dep) && self.has_cycle_util(dep, visited, rec_stack) {
-                    return true;
-                }
-                if rec_stack.contains(dep) {
-                    return true;
+    ///
+    /// `path` holds the packages on the current DFS branch; reaching one of
+    /// them again closes a cycle.
+    fn find_cycle_util<'a>(
+        &'a self,
+        node_id: &'a PackageId,
+        finished: &mut std::collections::HashSet<&'a PackageId>,
+        path: &mut Vec<&'a PackageId>,
+    ) -> Option<Vec<PackageId>> {
+        if finished.contains(node_id) {
+            return None;
+        }
+        if let Some(start) = path.iter().position(|id| *id == node_id) {
+            let mut cycle: Vec<PackageId> = path[start..].iter().map(|id| (*id).clone()).collect();
+            let lowest = (0..cycle.len())
+                .min_by(|&a, &b| {
+                    (&cycle[a].name, &cycle[a].version).cmp(&(&cycle[b].name, &cycle[b].version))
+                })
+                .unwrap_or(0);
+            cycle.rotate_left(lowest);
+            return Some(cycle);
+        }
+
+        path.push(node_id);
+        if let Some(dependents) = self.edges.get(node_id) {
+            for dependent in dependents {
+                if let Some(cycle) = self.find_cycle_util(dependent, finished, path) {
+                    return Some(cycle);
                 }
             }
         }
+        path.pop();
+        finished.insert(node_id);
+        None
+    }
 
-        rec_stack.remove(node_id);
-        false
+    /// Error describing `cycle`, as returned by [`Self::find_cycle`]
+    pub(crate) fn cycle_error(cycle: &[PackageId]) -> sps2_errors::Error {
+        sps2_errors::PackageError::DependencyCycle {
+            packages: cycle.iter().map(ToString::to_string).collect(),
+        }
+        .into()
     }
 
     /// Perform topological sort using Kahn's algorithm
@@ -222,11 +296,8 @@ impl DependencyGraph {
     pub fn topological_sort(&self) -> Result<Vec<PackageId>, sps2_errors::Error> {
         use std::collections::{HashMap, VecDeque};
 
-        if self.has_cycles() {
-            return Err(sps2_errors::PackageError::DependencyCycle {
-                package: "unknown".to_string(),
-            }
-            .into());
+        if let Some(cycle) = self.find_cycle() {
+            return Err(Self::cycle_error(&cycle));
         }
 
         // Calculate in-degrees
@@ -265,10 +336,8 @@ impl DependencyGraph {
         }
 
         if result.len() != self.nodes.len() {
-            return Err(sps2_errors::PackageError::DependencyCycle {
-                package: "unknown".to_string(),
-            }
-            .into());
+            let cycle = self.find_cycle().unwrap_or_default();
+            return Err(Self::cycle_error(&cycle));
         }
 
         Ok(result)
@@ -7,12 +7,14 @@
 //! for both installation and building operations. It implements a
 //! topological sort with concurrent execution.
 
+mod conflicts;
 mod execution;
 mod graph;
 mod resolver;
 mod sat;
 
-pub use execution::ExecutionPlan;
+pub use conflicts::ConflictOptions;
+pub use execution::{ExecutionPlan, ExecutionStats};
 pub use graph::{DepEdge, DepKind, DependencyGraph, NodeAction, PackageId, ResolvedNode};
 pub use resolver::Resolver;
 pub use sat::{solve_dependencies, DependencyProblem, DependencySolution};
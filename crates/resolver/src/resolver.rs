@@ -1,12 +1,13 @@
 //! Main dependency resolver implementation
 
+use crate::conflicts::{check_conflicts, ConflictOptions};
 use crate::graph::DependencyGraph;
 use crate::sat::{Clause, DependencyProblem, Literal, PackageVersion};
 use crate::{
     DepEdge, DepKind, ExecutionPlan, PackageId, ResolutionContext, ResolutionResult, ResolvedNode,
 };
 use semver::Version;
-use sps2_errors::{Error, PackageError};
+use sps2_errors::{Error, PackageError, VersionError};
 use sps2_events::{EventEmitter, EventSender};
 use sps2_index::{IndexManager, VersionEntry};
 use sps2_platform::{PlatformContext, PlatformManager};
@@ -27,6 +28,13 @@ struct TransitiveDependencyParams<'a> {
     dep_kind: DepKind,
 }
 
+/// Weak dependency of a package version, linked once the strong closure is known
+struct WeakDependency {
+    parent_name: String,
+    parent_version: Version,
+    dep_spec: PackageSpec,
+}
+
 /// Dependency resolver
 #[derive(Clone, Debug)]
 pub struct Resolver {
@@ -34,6 +42,8 @@ pub struct Resolver {
     index: IndexManager,
     /// Event sender for progress and status updates
     event_sender: Option<EventSender>,
+    /// How conflicts between resolved packages are treated
+    conflict_options: ConflictOptions,
 }
 
 impl Resolver {
@@ -43,6 +53,7 @@ impl Resolver {
         Self {
             index,
             event_sender: None,
+            conflict_options: ConflictOptions::default(),
         }
     }
 
@@ -52,9 +63,17 @@ impl Resolver {
         Self {
             index,
             event_sender: Some(event_sender),
+            conflict_options: ConflictOptions::default(),
         }
     }
 
+    /// Set how conflicts between resolved packages are treated
+    #[must_use]
+    pub fn with_conflict_options(mut self, options: ConflictOptions) -> Self {
+        self.conflict_options = options;
+        self
+    }
+
     /// Resolve dependencies using SAT solver for more accurate resolution
     ///
     /// This method converts the dependency problem to a SAT problem and uses
@@ -66,6 +85,8 @@ impl Resolver {
     /// Returns an error if:
     /// - A package is not found in the index
     /// - No valid solution exists (conflicting constraints)
+    /// - The constraints requested for a package contradict each other
+    /// - Two resolved packages conflict with each other
     /// - Version parsing fails
     pub async fn resolve_with_sat(
         &self,
@@ -78,6 +99,7 @@ impl Resolver {
         timeout(resolution_timeout, async {
             let mut graph = DependencyGraph::new();
             let mut already_satisfied = HashSet::new();
+            let mut file_lists = HashMap::new();
 
             // First, check installed packages for each dependency
             let mut remaining_package_deps: HashMap<String, Vec<(PackageSpec, DepKind)>> =
@@ -141,6 +163,9 @@ impl Resolver {
                 }
             }
 
+            // Fail fast on requests no version could ever satisfy
+            Self::check_satisfiable(&remaining_package_deps)?;
+
             // If we have remaining dependencies to resolve, use SAT solver
             if !remaining_package_deps.is_empty() {
                 // Create SAT problem for remaining dependencies
@@ -159,6 +184,8 @@ impl Resolver {
                 let sat_graph =
                     Self::create_dependency_graph_from_solution(&solution, &version_entries)?;
 
+                file_lists.extend(Self::published_file_lists(&sat_graph, &version_entries));
+
                 // Merge SAT results into main graph
                 for (id, node) in sat_graph.nodes {
                     graph.nodes.insert(id.clone(), node);
@@ -170,12 +197,18 @@ impl Resolver {
 
             // Handle local files
             for path in &context.local_files {
-                Self::resolve_local_file(path, &mut graph).await?;
+                let (package_id, files) = Self::resolve_local_file(path, &mut graph).await?;
+                file_lists.insert(package_id, files);
+            }
+
+            // Reject conflicting packages before planning anything
+            let warnings = check_conflicts(&graph.nodes, &file_lists, self.conflict_options)?;
+            for warning in warnings {
+                self.emit_warning(warning);
             }
 
             // Create execution plan
-            let sorted = graph.topological_sort()?;
-            let execution_plan = ExecutionPlan::from_sorted_packages(&sorted, &graph);
+            let execution_plan = ExecutionPlan::from_graph(&graph)?;
 
             Ok(ResolutionResult {
                 nodes: graph.nodes,
@@ -188,6 +221,29 @@ impl Resolver {
         })?
     }
 
+    /// Reject a package whose requested constraints contradict each other
+    fn check_satisfiable(
+        package_deps: &HashMap<String, Vec<(PackageSpec, DepKind)>>,
+    ) -> Result<(), Error> {
+        for (name, specs) in package_deps {
+            let Some(((first, _), rest)) = specs.split_first() else {
+                continue;
+            };
+            let combined = rest
+                .iter()
+                .fold(first.version_spec.clone(), |combined, (spec, _)| {
+                    combined.intersect(&spec.version_spec)
+                });
+            if !combined.is_satisfiable() {
+                return Err(VersionError::NoSatisfyingVersion {
+                    constraints: format!("{name}{combined}"),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
     /// Create SAT problem from already-collected dependencies
     fn create_sat_problem_from_deps(
         package_deps: &HashMap<String, Vec<(PackageSpec, DepKind)>>,
@@ -227,7 +283,7 @@ impl Resolver {
                             let mut dep_kind = DepKind::Runtime;
 
                             for (spec, kind) in specs {
-                                if spec.version_spec.matches(&version) {
+                                if spec.version_spec.is_candidate(&version) {
                                     satisfies_any = true;
                                     dep_kind = *kind;
                                     break;
@@ -264,6 +320,9 @@ impl Resolver {
     }
 
     /// Process transitive dependencies
+    ///
+    /// Weak dependencies are collected while walking the strong closure and
+    /// linked afterwards, so they never add packages of their own.
     fn process_transitive_dependencies<'a>(
         &'a self,
         problem: &mut DependencyProblem,
@@ -271,6 +330,7 @@ impl Resolver {
     ) {
         let mut processed = HashSet::new();
         let mut to_process: Vec<(String, Version, DepKind)> = Vec::new();
+        let mut weak_deps = Vec::new();
 
         // Initialize with direct dependencies
         for ((name, version), (_entry, kind)) in &*version_entries {
@@ -288,6 +348,17 @@ impl Resolver {
             let deps_to_process = if let Some((version_entry, _)) = version_entries.get(&key) {
                 let mut deps = Vec::new();
 
+                // Defer weak dependencies until the strong closure is complete
+                for dep_str in &version_entry.dependencies.weak {
+                    if let Ok(dep_spec) = PackageSpec::parse(dep_str) {
+                        weak_deps.push(WeakDependency {
+                            parent_name: pkg_name.clone(),
+                            parent_version: pkg_version.clone(),
+                            dep_spec,
+                        });
+                    }
+                }
+
                 // Collect runtime dependencies
                 for dep_str in &version_entry.dependencies.runtime {
                     if let Ok(dep_spec) = PackageSpec::parse(dep_str) {
@@ -325,6 +396,51 @@ impl Resolver {
                 );
             }
         }
+
+        for weak in &weak_deps {
+            Self::link_weak_dependency(problem, weak);
+        }
+    }
+
+    /// Constrain a weak dependency that is already part of the problem
+    ///
+    /// If the dependency was not pulled in by anything else it is ignored, and
+    /// no new versions are ever added for it.
+    fn link_weak_dependency(problem: &mut DependencyProblem, weak: &WeakDependency) {
+        let parent_pv = PackageVersion::new(weak.parent_name.clone(), weak.parent_version.clone());
+        let Some(parent_var) = problem.variables.get_variable(&parent_pv) else {
+            return;
+        };
+
+        let satisfying: Vec<_> = problem
+            .get_package_versions(&weak.dep_spec.name)
+            .into_iter()
+            .filter(|pv| weak.dep_spec.version_spec.is_candidate(&pv.version))
+            .filter_map(|pv| problem.variables.get_variable(pv))
+            .collect();
+        if satisfying.is_empty() {
+            return;
+        }
+
+        // parent => (dep1 OR dep2 OR ...), mirroring strong dependencies
+        let mut clause_lits = vec![Literal::negative(parent_var)];
+        clause_lits.extend(satisfying.into_iter().map(Literal::positive));
+        problem.add_clause(Clause::new(clause_lits));
+    }
+
+    /// File lists the index publishes for the packages in a graph
+    fn published_file_lists(
+        graph: &DependencyGraph,
+        version_entries: &VersionEntriesMap<'_>,
+    ) -> HashMap<PackageId, Vec<String>> {
+        graph
+            .nodes
+            .keys()
+            .filter_map(|id| {
+                let (entry, _) = version_entries.get(&(id.name.clone(), id.version.clone()))?;
+                (!entry.files.is_empty()).then(|| (id.clone(), entry.files.clone()))
+            })
+            .collect()
     }
 
     /// Create dependency graph from SAT solution
@@ -352,12 +468,27 @@ impl Resolver {
                         ));
                     }
                 }
+                // Weak edges only matter when the dependency was selected anyway
+                for dep_str in &version_entry.dependencies.weak {
+                    if let Ok(dep_spec) = PackageSpec::parse(dep_str) {
+                        if solution.selected.contains_key(&dep_spec.name) {
+                            deps.push(DepEdge::weak(dep_spec.name.clone(), dep_spec.version_spec));
+                        }
+                    }
+                }
 
+                let parse_specs = |specs: &[String]| -> Result<Vec<PackageSpec>, VersionError> {
+                    specs.iter().map(|spec| PackageSpec::parse(spec)).collect()
+                };
                 let mut node = ResolvedNode::download(
                     name.clone(),
                     version.clone(),
                     Self::resolve_download_url(&version_entry.download_url)?,
                     deps,
+                )
+                .with_relations(
+                    parse_specs(&version_entry.dependencies.conflicts)?,
+                    parse_specs(&version_entry.dependencies.replaces)?,
                 );
                 // Propagate signature URL and expected hash from index
                 node.signature_url = Some(version_entry.minisig_url.clone());
@@ -441,7 +572,7 @@ impl Resolver {
 
                 for (version_str, version_entry) in &package_info.versions {
                     if let Ok(version) = Version::parse(version_str) {
-                        if params.dep_spec.version_spec.matches(&version) {
+                        if params.dep_spec.version_spec.is_candidate(&version) {
                             let dep_pv =
                                 PackageVersion::new(params.dep_spec.name.clone(), version.clone());
                             let dep_var = problem.add_package_version(dep_pv);
@@ -480,12 +611,17 @@ impl Resolver {
     }
 
     /// Resolve a local package file
-    async fn resolve_local_file(path: &Path, graph: &mut DependencyGraph) -> Result<(), Error> {
-        // Load manifest from local .sp file
-        let manifest = Self::load_local_manifest(path).await?;
+    ///
+    /// Returns the package's ID and the paths its archive provides.
+    async fn resolve_local_file(
+        path: &Path,
+        graph: &mut DependencyGraph,
+    ) -> Result<(PackageId, Vec<String>), Error> {
+        // Load manifest and file list from local .sp file
+        let (manifest, files) = Self::load_local_manifest(path).await?;
 
         let version = Version::parse(&manifest.package.version)?;
-        let _package_id = PackageId::new(manifest.package.name.clone(), version.clone());
+        let package_id = PackageId::new(manifest.package.name.clone(), version.clone());
 
         // Create dependency edges from manifest
         let mut deps = Vec::new();
@@ -501,16 +637,27 @@ impl Resolver {
             deps.push(edge);
         }
 
+        for dep in &manifest.dependencies.weak {
+            let dep_spec = PackageSpec::parse(dep)?;
+            deps.push(DepEdge::weak(dep_spec.name.clone(), dep_spec.version_spec));
+        }
+
         // Create resolved node for local file
-        let node = ResolvedNode::local(manifest.package.name, version, path.to_path_buf(), deps);
+        let node = ResolvedNode::local(
+            manifest.package.name.clone(),
+            version,
+            path.to_path_buf(),
+            deps,
+        )
+        .with_relations(manifest.conflicts()?, manifest.replaces()?);
 
         graph.add_node(node);
 
-        Ok(())
+        Ok((package_id, files))
     }
 
-    /// Load manifest from local .sp file
-    async fn load_local_manifest(path: &Path) -> Result<Manifest, Error> {
+    /// Load manifest and file list from local .sp file
+    async fn load_local_manifest(path: &Path) -> Result<(Manifest, Vec<String>), Error> {
         use tokio::fs;
 
         // Create temporary directory for extraction
@@ -561,7 +708,41 @@ impl Resolver {
         // Step 3: Parse the manifest
         let manifest = Manifest::from_toml(&manifest_content)?;
 
-        Ok(manifest)
+        // Step 4: List the paths the package provides
+        let files = Self::list_tar_entries(&tar_path).await?;
+
+        Ok((manifest, files))
+    }
+
+    /// List the entries of a tar archive, without any leading `./`
+    async fn list_tar_entries(tar_path: &Path) -> Result<Vec<String>, Error> {
+        let platform = PlatformManager::instance().platform();
+        let context = PlatformContext::new(None);
+
+        let mut tar_cmd = platform.process().create_command("tar");
+        tar_cmd.args(["--list", "--file", &tar_path.display().to_string()]);
+
+        let tar_output = platform
+            .process()
+            .execute_command(&context, tar_cmd)
+            .await?;
+
+        if !tar_output.status.success() {
+            return Err(PackageError::InvalidFormat {
+                message: format!(
+                    "failed to list package contents: {}",
+                    String::from_utf8_lossy(&tar_output.stderr)
+                ),
+            }
+            .into());
+        }
+
+        Ok(String::from_utf8_lossy(&tar_output.stdout)
+            .lines()
+            .map(|line| line.trim_start_matches("./"))
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
     }
 
     /// Extract manifest.toml content from tar archive
@@ -672,3 +853,171 @@ impl EventEmitter for Resolver {
         self.event_sender.as_ref()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sps2_index::{DependencyInfo, Index};
+    use tempfile::TempDir;
+
+    fn entry(runtime: &[&str], weak: &[&str]) -> VersionEntry {
+        VersionEntry {
+            revision: 1,
+            arch: "arm64".to_string(),
+            blake3: "0".repeat(64),
+            download_url: "https://example.invalid/pkg.sp".to_string(),
+            minisig_url: "https://example.invalid/pkg.sp.minisig".to_string(),
+            dependencies: DependencyInfo {
+                runtime: runtime.iter().map(ToString::to_string).collect(),
+                build: Vec::new(),
+                weak: weak.iter().map(ToString::to_string).collect(),
+                conflicts: Vec::new(),
+                replaces: Vec::new(),
+            },
+            sbom: None,
+            description: None,
+            homepage: None,
+            license: None,
+            source: None,
+            files: Vec::new(),
+        }
+    }
+
+    /// `app` weakly depends on `extra`, which strongly depends on `heavy`
+    fn resolver(temp: &TempDir) -> Resolver {
+        let mut index = Index::new();
+        index.add_version("app".into(), "1.0.0".into(), entry(&[], &["extra>=1.0.0"]));
+        index.add_version("extra".into(), "1.0.0".into(), entry(&["heavy"], &[]));
+        index.add_version("heavy".into(), "1.0.0".into(), entry(&[], &[]));
+        resolver_for(temp, index)
+    }
+
+    fn resolver_for(temp: &TempDir, index: Index) -> Resolver {
+        let mut manager = IndexManager::new(temp.path());
+        manager.set_index(index);
+        Resolver::new(manager)
+    }
+
+    fn names(result: &ResolutionResult) -> Vec<String> {
+        let mut names: Vec<_> = result.nodes.keys().map(|id| id.name.clone()).collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn weak_dependency_skipped_when_absent() {
+        let temp = TempDir::new().unwrap();
+        let context = ResolutionContext::new().add_runtime_dep(PackageSpec::parse("app").unwrap());
+
+        let result = resolver(&temp).resolve_with_sat(context).await.unwrap();
+
+        assert_eq!(names(&result), ["app"]);
+        let app = result.nodes.values().next().unwrap();
+        assert!(app.deps.is_empty());
+    }
+
+    #[tokio::test]
+    async fn weak_dependency_linked_when_requested() {
+        let temp = TempDir::new().unwrap();
+        let context = ResolutionContext::new()
+            .add_runtime_dep(PackageSpec::parse("app").unwrap())
+            .add_runtime_dep(PackageSpec::parse("extra").unwrap());
+
+        let result = resolver(&temp).resolve_with_sat(context).await.unwrap();
+
+        assert_eq!(names(&result), ["app", "extra", "heavy"]);
+        let app = result
+            .nodes
+            .values()
+            .find(|node| node.name == "app")
+            .unwrap();
+        assert_eq!(app.deps.len(), 1);
+        assert_eq!(app.deps[0].name, "extra");
+        assert!(app.deps[0].weak);
+
+        // The weak edge orders `extra` before `app`
+        let order: Vec<_> = result
+            .packages_in_order()
+            .iter()
+            .map(|node| node.name.as_str())
+            .collect();
+        let position = |name| order.iter().position(|n| *n == name).unwrap();
+        assert!(position("extra") < position("app"));
+    }
+
+    #[tokio::test]
+    async fn contradictory_requests_fail_before_solving() {
+        let temp = TempDir::new().unwrap();
+        let context = ResolutionContext::new()
+            .add_runtime_dep(PackageSpec::parse("app>=2.0.0").unwrap())
+            .add_build_dep(PackageSpec::parse("app<1.0.0").unwrap());
+
+        let error = resolver(&temp).resolve_with_sat(context).await.unwrap_err();
+        assert!(
+            matches!(
+                &error,
+                Error::Version(VersionError::NoSatisfyingVersion { constraints })
+                    if constraints == "app>=2.0.0,<1.0.0"
+            ),
+            "{error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn sat_path_picks_pre_releases_like_the_index() {
+        let mut index = Index::new();
+        index.add_version("app".into(), "1.5.0".into(), entry(&[], &[]));
+        index.add_version("app".into(), "2.0.0-rc1".into(), entry(&[], &[]));
+
+        for (spec, expected) in [("app", "1.5.0"), ("app>=2.0.0-rc1", "2.0.0-rc1")] {
+            let temp = TempDir::new().unwrap();
+            let resolver = resolver_for(&temp, index.clone());
+            let spec = PackageSpec::parse(spec).unwrap();
+            let (indexed, _) = resolver.index.find_best_version_with_string(&spec).unwrap();
+            assert_eq!(indexed.to_string(), expected);
+
+            let context = ResolutionContext::new().add_runtime_dep(spec);
+            let result = resolver.resolve_with_sat(context).await.unwrap();
+            let node = result.nodes.values().next().unwrap();
+            assert_eq!(node.version.to_string(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn malformed_relation_specs_in_the_index_are_rejected() {
+        let temp = TempDir::new().unwrap();
+        let mut app = entry(&[], &[]);
+        app.dependencies.conflicts = vec![">=1.0.0".to_string()];
+        let mut index = Index::new();
+        index.add_version("app".into(), "1.0.0".into(), app);
+
+        let context = ResolutionContext::new().add_runtime_dep(PackageSpec::parse("app").unwrap());
+        let result = resolver_for(&temp, index).resolve_with_sat(context).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn overlapping_files_published_by_the_index_conflict() {
+        let temp = TempDir::new().unwrap();
+        let mut app = entry(&["lib"], &[]);
+        app.files = vec!["bin/tool".to_string()];
+        let mut lib = entry(&[], &[]);
+        lib.files = vec!["bin/".to_string(), "bin/tool".to_string()];
+        let mut index = Index::new();
+        index.add_version("app".into(), "1.0.0".into(), app);
+        index.add_version("lib".into(), "1.0.0".into(), lib);
+
+        let context = ResolutionContext::new().add_runtime_dep(PackageSpec::parse("app").unwrap());
+        let error = resolver_for(&temp, index)
+            .resolve_with_sat(context)
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(&error, Error::Package(PackageError::PackageConflict { reason, .. })
+                if reason.contains("bin/tool")),
+            "{error:?}"
+        );
+    }
+}
@@ -8,8 +8,10 @@
 
 pub mod limits;
 pub mod manager;
+mod priority;
 pub mod semaphore;
 
 pub use limits::{IntoResourceLimits, ResourceAvailability, ResourceLimits};
-pub use manager::ResourceManager;
+pub use manager::{MemoryPermit, ResourceManager, ResourcePermit};
+pub use priority::Priority;
 pub use semaphore::{acquire_semaphore_permit, create_semaphore, try_acquire_semaphore_permit};
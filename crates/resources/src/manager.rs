@@ -4,11 +4,13 @@
 //! semaphores and resource limits for concurrent operations.
 
 use crate::limits::{ResourceAvailability, ResourceLimits};
+use crate::priority::{Priority, PriorityQueue};
 use crate::semaphore::{acquire_semaphore_permit, create_semaphore, try_acquire_semaphore_permit};
 use sps2_errors::Error;
-use std::sync::atomic::AtomicU64;
+use sps2_events::{AppEvent, EventEmitter, EventSender, ResourceEvent, ResourceKind};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
 
 /// Resource manager for coordinating resource usage
 ///
@@ -26,6 +28,80 @@ pub struct ResourceManager {
     pub limits: ResourceLimits,
     /// Current memory usage
     pub memory_usage: Arc<AtomicU64>,
+    /// Wakes operations waiting for memory when a reservation is released
+    memory_released: Arc<Notify>,
+    /// Orders waiters for download permits by priority
+    download_queue: PriorityQueue,
+    /// Receives permit grants and releases, if anyone is listening
+    event_sender: Option<EventSender>,
+}
+
+/// Permit from one of the manager's pools, returned to it on drop
+///
+/// When the manager has an event sender, releasing the permit is reported
+/// with the number of permits left free in its pool.
+#[derive(Debug)]
+pub struct ResourcePermit {
+    permit: Option<OwnedSemaphorePermit>,
+    observer: Option<PermitObserver>,
+}
+
+/// Where to report a permit's release
+#[derive(Debug)]
+struct PermitObserver {
+    sender: EventSender,
+    kind: ResourceKind,
+    semaphore: Arc<Semaphore>,
+    capacity: usize,
+}
+
+impl Drop for ResourcePermit {
+    fn drop(&mut self) {
+        // Return the permit first so the reported count includes it
+        drop(self.permit.take());
+        if let Some(observer) = &self.observer {
+            observer
+                .sender
+                .emit(AppEvent::Resource(ResourceEvent::PermitReleased {
+                    kind: observer.kind,
+                    available: observer.semaphore.available_permits(),
+                    capacity: observer.capacity,
+                }));
+        }
+    }
+}
+
+/// Permit holding a memory reservation alongside a semaphore permit
+///
+/// Dropping it returns the reserved bytes to the manager and wakes any
+/// operation waiting for memory.
+#[derive(Debug)]
+pub struct MemoryPermit {
+    _permit: ResourcePermit,
+    reservation: MemoryReservation,
+}
+
+impl MemoryPermit {
+    /// Bytes reserved by this permit
+    #[must_use]
+    pub fn reserved_bytes(&self) -> u64 {
+        self.reservation.bytes
+    }
+}
+
+/// Reserved share of the memory limit, released on drop
+#[derive(Debug)]
+struct MemoryReservation {
+    bytes: u64,
+    usage: Arc<AtomicU64>,
+    released: Arc<Notify>,
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.usage.fetch_sub(self.bytes, Ordering::AcqRel);
+        self.released.notify_waiters();
+    }
 }
 
 impl ResourceManager {
@@ -37,6 +113,9 @@ impl ResourceManager {
             decompression_semaphore: create_semaphore(limits.concurrent_decompressions),
             installation_semaphore: create_semaphore(limits.concurrent_installations),
             memory_usage: Arc::new(AtomicU64::new(0)),
+            memory_released: Arc::new(Notify::new()),
+            download_queue: PriorityQueue::default(),
+            event_sender: None,
             limits,
         }
     }
@@ -47,13 +126,79 @@ impl ResourceManager {
         Self::new(ResourceLimits::from_system())
     }
 
-    /// Acquire a download permit
+    /// Report permit grants and releases to `event_sender`
+    #[must_use]
+    pub fn with_event_sender(mut self, event_sender: EventSender) -> Self {
+        self.event_sender = Some(event_sender);
+        self
+    }
+
+    /// Semaphore and permit count backing a pool
+    fn pool(&self, kind: ResourceKind) -> (&Arc<Semaphore>, usize) {
+        match kind {
+            ResourceKind::Download => (&self.download_semaphore, self.limits.concurrent_downloads),
+            ResourceKind::Decompression => (
+                &self.decompression_semaphore,
+                self.limits.concurrent_decompressions,
+            ),
+            ResourceKind::Installation => (
+                &self.installation_semaphore,
+                self.limits.concurrent_installations,
+            ),
+        }
+    }
+
+    /// Wrap a granted permit, reporting the grant when events are enabled
+    fn grant(&self, kind: ResourceKind, permit: OwnedSemaphorePermit) -> ResourcePermit {
+        let observer = self.event_sender.as_ref().map(|sender| {
+            let (semaphore, capacity) = self.pool(kind);
+            sender.emit(AppEvent::Resource(ResourceEvent::PermitAcquired {
+                kind,
+                available: semaphore.available_permits(),
+                capacity,
+            }));
+            PermitObserver {
+                sender: sender.clone(),
+                kind,
+                semaphore: semaphore.clone(),
+                capacity,
+            }
+        });
+        ResourcePermit {
+            permit: Some(permit),
+            observer,
+        }
+    }
+
+    /// Acquire a download permit at [`Priority::Normal`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the semaphore is closed or acquisition fails.
+    pub async fn acquire_download_permit(&self) -> Result<ResourcePermit, Error> {
+        self.acquire_download_permit_prioritized(Priority::Normal)
+            .await
+    }
+
+    /// Acquire a download permit ahead of less urgent requests
+    ///
+    /// Waiting requests are served strictly by priority, and in arrival
+    /// order within a priority, so a `High` request never waits behind a
+    /// backlog of `Normal` or `Low` ones. `Low` requests may wait for as long
+    /// as more urgent ones keep arriving.
     ///
     /// # Errors
     ///
     /// Returns an error if the semaphore is closed or acquisition fails.
-    pub async fn acquire_download_permit(&self) -> Result<OwnedSemaphorePermit, Error> {
-        acquire_semaphore_permit(self.download_semaphore.clone(), "download").await
+    pub async fn acquire_download_permit_prioritized(
+        &self,
+        priority: Priority,
+    ) -> Result<ResourcePermit, Error> {
+        let permit = self
+            .download_queue
+            .acquire(&self.download_semaphore, priority, "download")
+            .await?;
+        Ok(self.grant(ResourceKind::Download, permit))
     }
 
     /// Acquire a decompression permit
@@ -61,8 +206,61 @@ impl ResourceManager {
     /// # Errors
     ///
     /// Returns an error if the semaphore is closed or acquisition fails.
-    pub async fn acquire_decompression_permit(&self) -> Result<OwnedSemaphorePermit, Error> {
-        acquire_semaphore_permit(self.decompression_semaphore.clone(), "decompression").await
+    pub async fn acquire_decompression_permit(&self) -> Result<ResourcePermit, Error> {
+        let permit =
+            acquire_semaphore_permit(self.decompression_semaphore.clone(), "decompression").await?;
+        Ok(self.grant(ResourceKind::Decompression, permit))
+    }
+
+    /// Acquire a decompression permit backed by a memory reservation
+    ///
+    /// Waits for a decompression permit, then until `estimated_bytes` fit
+    /// within the memory limit, and reserves them. Only permit holders
+    /// reserve memory, so operations still queued for a permit hold none.
+    /// The reservation is released when the returned permit is dropped. An
+    /// estimate larger than the whole limit is admitted once nothing else
+    /// holds memory, so it runs alone rather than waiting forever.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the semaphore is closed or acquisition fails.
+    pub async fn acquire_decompression_permit_with_memory(
+        &self,
+        estimated_bytes: u64,
+    ) -> Result<MemoryPermit, Error> {
+        let permit = self.acquire_decompression_permit().await?;
+        let reservation = self.reserve_memory(estimated_bytes).await;
+        Ok(MemoryPermit {
+            _permit: permit,
+            reservation,
+        })
+    }
+
+    /// Wait until `bytes` fit within the memory limit and reserve them
+    async fn reserve_memory(&self, bytes: u64) -> MemoryReservation {
+        loop {
+            // Register for wake-ups before checking, so a release between the
+            // check and the wait is not missed
+            let released = self.memory_released.notified();
+            tokio::pin!(released);
+            released.as_mut().enable();
+
+            let reserved = self
+                .memory_usage
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |current| {
+                    let next = current.checked_add(bytes)?;
+                    (current == 0 || self.is_memory_within_limits(next)).then_some(next)
+                })
+                .is_ok();
+            if reserved {
+                return MemoryReservation {
+                    bytes,
+                    usage: self.memory_usage.clone(),
+                    released: self.memory_released.clone(),
+                };
+            }
+            released.await;
+        }
     }
 
     /// Acquire an installation permit
@@ -70,8 +268,10 @@ impl ResourceManager {
     /// # Errors
     ///
     /// Returns an error if the semaphore is closed or acquisition fails.
-    pub async fn acquire_installation_permit(&self) -> Result<OwnedSemaphorePermit, Error> {
-        acquire_semaphore_permit(self.installation_semaphore.clone(), "installation").await
+    pub async fn acquire_installation_permit(&self) -> Result<ResourcePermit, Error> {
+        let permit =
+            acquire_semaphore_permit(self.installation_semaphore.clone(), "installation").await?;
+        Ok(self.grant(ResourceKind::Installation, permit))
     }
 
     /// Try to acquire a download permit without blocking
@@ -79,8 +279,9 @@ impl ResourceManager {
     /// # Errors
     ///
     /// Returns an error if the semaphore is closed.
-    pub fn try_acquire_download_permit(&self) -> Result<Option<OwnedSemaphorePermit>, Error> {
-        try_acquire_semaphore_permit(&self.download_semaphore)
+    pub fn try_acquire_download_permit(&self) -> Result<Option<ResourcePermit>, Error> {
+        Ok(try_acquire_semaphore_permit(&self.download_semaphore)?
+            .map(|permit| self.grant(ResourceKind::Download, permit)))
     }
 
     /// Try to acquire a decompression permit without blocking
@@ -88,8 +289,9 @@ impl ResourceManager {
     /// # Errors
     ///
     /// Returns an error if the semaphore is closed.
-    pub fn try_acquire_decompression_permit(&self) -> Result<Option<OwnedSemaphorePermit>, Error> {
-        try_acquire_semaphore_permit(&self.decompression_semaphore)
+    pub fn try_acquire_decompression_permit(&self) -> Result<Option<ResourcePermit>, Error> {
+        Ok(try_acquire_semaphore_permit(&self.decompression_semaphore)?
+            .map(|permit| self.grant(ResourceKind::Decompression, permit)))
     }
 
     /// Try to acquire an installation permit without blocking
@@ -97,8 +299,9 @@ impl ResourceManager {
     /// # Errors
     ///
     /// Returns an error if the semaphore is closed.
-    pub fn try_acquire_installation_permit(&self) -> Result<Option<OwnedSemaphorePermit>, Error> {
-        try_acquire_semaphore_permit(&self.installation_semaphore)
+    pub fn try_acquire_installation_permit(&self) -> Result<Option<ResourcePermit>, Error> {
+        Ok(try_acquire_semaphore_permit(&self.installation_semaphore)?
+            .map(|permit| self.grant(ResourceKind::Installation, permit)))
     }
 
     /// Check if memory usage is within limits
@@ -132,8 +335,149 @@ impl ResourceManager {
     }
 }
 
+impl EventEmitter for ResourceManager {
+    fn event_sender(&self) -> Option<&EventSender> {
+        self.event_sender.as_ref()
+    }
+}
+
 impl Default for ResourceManager {
     fn default() -> Self {
         Self::new(ResourceLimits::default())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn memory_reservations_back_pressure_each_other() {
+        let manager = Arc::new(ResourceManager::new(ResourceLimits {
+            concurrent_downloads: 4,
+            concurrent_decompressions: 4,
+            concurrent_installations: 4,
+            memory_usage: Some(100),
+        }));
+
+        let first = manager
+            .acquire_decompression_permit_with_memory(60)
+            .await
+            .unwrap();
+        assert_eq!(first.reserved_bytes(), 60);
+        assert_eq!(manager.memory_usage.load(Ordering::Acquire), 60);
+
+        // A second 60-byte reservation has to wait for the first to drop
+        let waiting = tokio::spawn({
+            let manager = manager.clone();
+            async move {
+                manager
+                    .acquire_decompression_permit_with_memory(60)
+                    .await
+                    .unwrap()
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+
+        drop(first);
+        let second = tokio::time::timeout(Duration::from_secs(5), waiting)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(manager.memory_usage.load(Ordering::Acquire), 60);
+        drop(second);
+        assert_eq!(manager.memory_usage.load(Ordering::Acquire), 0);
+
+        // An estimate above the whole limit still runs once memory is free
+        let oversized = manager
+            .acquire_decompression_permit_with_memory(500)
+            .await
+            .unwrap();
+        assert_eq!(manager.memory_usage.load(Ordering::Acquire), 500);
+        drop(oversized);
+    }
+
+    #[tokio::test]
+    async fn queued_permit_waiters_hold_no_memory() {
+        let manager = Arc::new(ResourceManager::new(ResourceLimits {
+            concurrent_downloads: 1,
+            concurrent_decompressions: 1,
+            concurrent_installations: 1,
+            memory_usage: Some(100),
+        }));
+
+        let first = manager
+            .acquire_decompression_permit_with_memory(10)
+            .await
+            .unwrap();
+        let waiting = tokio::spawn({
+            let manager = manager.clone();
+            async move {
+                manager
+                    .acquire_decompression_permit_with_memory(10)
+                    .await
+                    .unwrap()
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+        assert_eq!(manager.memory_usage.load(Ordering::Acquire), 10);
+
+        drop(first);
+        let second = tokio::time::timeout(Duration::from_secs(5), waiting)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.reserved_bytes(), 10);
+        assert_eq!(manager.memory_usage.load(Ordering::Acquire), 10);
+    }
+
+    #[tokio::test]
+    async fn permits_report_grants_and_releases() {
+        let (tx, mut rx) = sps2_events::channel();
+        let manager = ResourceManager::new(ResourceLimits {
+            concurrent_downloads: 4,
+            concurrent_decompressions: 2,
+            concurrent_installations: 1,
+            memory_usage: None,
+        })
+        .with_event_sender(tx);
+
+        let first = manager.acquire_download_permit().await.unwrap();
+        let second = manager.try_acquire_download_permit().unwrap().unwrap();
+        drop(first);
+        let installing = manager.acquire_installation_permit().await.unwrap();
+        assert!(manager.try_acquire_installation_permit().unwrap().is_none());
+        drop((second, installing));
+
+        let mut seen = Vec::new();
+        while let Ok(message) = rx.try_recv() {
+            match message.event {
+                AppEvent::Resource(ResourceEvent::PermitAcquired {
+                    kind,
+                    available,
+                    capacity,
+                }) => seen.push(("acquired", kind, available, capacity)),
+                AppEvent::Resource(ResourceEvent::PermitReleased {
+                    kind,
+                    available,
+                    capacity,
+                }) => seen.push(("released", kind, available, capacity)),
+                other => panic!("unexpected event {other:?}"),
+            }
+        }
+        assert_eq!(
+            seen,
+            [
+                ("acquired", ResourceKind::Download, 3, 4),
+                ("acquired", ResourceKind::Download, 2, 4),
+                ("released", ResourceKind::Download, 3, 4),
+                ("acquired", ResourceKind::Installation, 0, 1),
+                ("released", ResourceKind::Download, 4, 4),
+                ("released", ResourceKind::Installation, 1, 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Priority ordering for semaphore permits
+//!
+//! Tokio semaphores hand out permits in arrival order, so a backlog of
+//! background requests delays anything queued after it. A
+//! [`PriorityQueue`] sits in front of a semaphore and only lets the most
+//! urgent waiter ask it for a permit.
+
+use sps2_errors::{Error, InstallError};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+
+/// Urgency of a permit request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Background work such as prefetching
+    Low,
+    /// Regular operations
+    #[default]
+    Normal,
+    /// Work a user is waiting on
+    High,
+}
+
+impl Priority {
+    fn slot(self) -> usize {
+        match self {
+            Self::High => 0,
+            Self::Normal => 1,
+            Self::Low => 2,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    next_ticket: u64,
+    /// Waiting tickets per priority, highest first
+    waiting: [VecDeque<u64>; 3],
+}
+
+impl QueueState {
+    fn head(&self) -> Option<u64> {
+        self.waiting.iter().find_map(|queue| queue.front().copied())
+    }
+}
+
+/// Waiting line in front of a semaphore, served by priority
+///
+/// Waiters of a higher priority are always served before waiters of a
+/// lower one, and waiters of the same priority in arrival order. Only the
+/// waiter at the head of the line waits on the semaphore; when a more urgent
+/// request arrives it steps back, so the next free permit goes to the new
+/// head. Lower priorities can starve while higher-priority demand lasts.
+#[derive(Debug, Default)]
+pub(crate) struct PriorityQueue {
+    state: Mutex<QueueState>,
+    changed: Notify,
+}
+
+impl PriorityQueue {
+    /// Wait for a permit from `semaphore` in priority order
+    ///
+    /// Cancelling the returned future gives up the caller's place in line.
+    pub(crate) async fn acquire(
+        &self,
+        semaphore: &Arc<Semaphore>,
+        priority: Priority,
+        operation: &str,
+    ) -> Result<OwnedSemaphorePermit, Error> {
+        let ticket = self.enqueue(priority);
+
+        loop {
+            // Register before looking at the line so no change is missed
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            if self.lock().head() == Some(ticket.id) {
+                tokio::select! {
+                    biased;
+                    permit = semaphore.clone().acquire_owned() => {
+                        return permit.map_err(|_| {
+                            InstallError::ConcurrencyError {
+                                message: format!("failed to acquire semaphore for {operation}"),
+                            }
+                            .into()
+                        });
+                    }
+                    () = &mut changed => {}
+                }
+            } else {
+                changed.await;
+            }
+        }
+    }
+
+    fn enqueue(&self, priority: Priority) -> Ticket<'_> {
+        let id = {
+            let mut state = self.lock();
+            let id = state.next_ticket;
+            state.next_ticket += 1;
+            state.waiting[priority.slot()].push_back(id);
+            id
+        };
+        self.changed.notify_waiters();
+        Ticket {
+            queue: self,
+            priority,
+            id,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Number of waiters per priority, highest first
+    #[cfg(test)]
+    fn waiting(&self) -> Vec<(Priority, usize)> {
+        let state = self.lock();
+        [Priority::High, Priority::Normal, Priority::Low]
+            .into_iter()
+            .map(|priority| (priority, state.waiting[priority.slot()].len()))
+            .collect()
+    }
+}
+
+/// Place in a [`PriorityQueue`], given up on drop
+struct Ticket<'a> {
+    queue: &'a PriorityQueue,
+    priority: Priority,
+    id: u64,
+}
+
+impl Drop for Ticket<'_> {
+    fn drop(&mut self) {
+        self.queue.lock().waiting[self.priority.slot()].retain(|id| *id != self.id);
+        self.queue.changed.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+    use std::task::Poll;
+    use tokio::sync::oneshot;
+
+    #[tokio::test]
+    async fn high_priority_overtakes_a_low_priority_backlog() {
+        let semaphore = Arc::new(Semaphore::new(1));
+        let queue = Arc::new(PriorityQueue::default());
+        let held = semaphore.clone().acquire_owned().await.unwrap();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+        // Each waiter reports once its first poll has taken a place in line,
+        // so the next one is only spawned after it
+        let spawn = |label: String, priority: Priority| {
+            let (semaphore, queue, tx) = (semaphore.clone(), queue.clone(), tx.clone());
+            let (queued_tx, queued_rx) = oneshot::channel();
+            let task = tokio::spawn(async move {
+                let mut acquire = std::pin::pin!(queue.acquire(&semaphore, priority, "test"));
+                let first = std::future::poll_fn(|cx| Poll::Ready(acquire.as_mut().poll(cx))).await;
+                queued_tx.send(()).unwrap();
+                let _permit = match first {
+                    Poll::Ready(permit) => permit,
+                    Poll::Pending => acquire.await,
+                }
+                .unwrap();
+                tx.send(label).unwrap();
+            });
+            (task, queued_rx)
+        };
+
+        let mut tasks = Vec::new();
+        let labels = (0..4)
+            .map(|index| (format!("low{index}"), Priority::Low))
+            .chain([
+                ("normal".to_string(), Priority::Normal),
+                ("high".to_string(), Priority::High),
+            ]);
+        for (label, priority) in labels {
+            let (task, queued) = spawn(label, priority);
+            queued.await.unwrap();
+            tasks.push(task);
+        }
+        assert_eq!(
+            queue.waiting(),
+            [
+                (Priority::High, 1),
+                (Priority::Normal, 1),
+                (Priority::Low, 4)
+            ]
+        );
+
+        drop(held);
+        for task in tasks {
+            task.await.unwrap();
+        }
+        let mut order = Vec::new();
+        while let Ok(label) = rx.try_recv() {
+            order.push(label);
+        }
+        assert_eq!(order, ["high", "normal", "low0", "low1", "low2", "low3"]);
+        assert!(queue.waiting().iter().all(|(_, count)| *count == 0));
+    }
+}
@@ -0,0 +1,13 @@
+-- Track the last state that passed guard verification so routine runs can
+-- skip packages that have not changed since then
+
+CREATE TABLE IF NOT EXISTS guard_verification_marker (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    state_id TEXT NOT NULL,
+    verified_at INTEGER NOT NULL,
+    full_sweep_at INTEGER NOT NULL
+);
+
+-- Bump schema version
+INSERT OR REPLACE INTO schema_version (version, applied_at)
+    VALUES (10, strftime('%s', 'now'));
@@ -0,0 +1,13 @@
+-- Record which packages the user asked for in each state, so dependencies
+-- pulled in on their behalf can be explained and flagged once unneeded
+
+CREATE TABLE IF NOT EXISTS requested_packages (
+    state_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    PRIMARY KEY (state_id, name),
+    FOREIGN KEY (state_id) REFERENCES states(id) ON DELETE CASCADE
+);
+
+-- Bump schema version
+INSERT OR REPLACE INTO schema_version (version, applied_at)
+    VALUES (11, strftime('%s', 'now'));
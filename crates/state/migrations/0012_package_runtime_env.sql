@@ -0,0 +1,15 @@
+-- Record the runtime environment variables each installed package declares,
+-- so shell integration can export them for the active state
+
+CREATE TABLE IF NOT EXISTS package_runtime_env (
+    state_id TEXT NOT NULL,
+    package_name TEXT NOT NULL,
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (state_id, package_name, name),
+    FOREIGN KEY (state_id) REFERENCES states(id) ON DELETE CASCADE
+);
+
+-- Bump schema version
+INSERT OR REPLACE INTO schema_version (version, applied_at)
+    VALUES (12, strftime('%s', 'now'));
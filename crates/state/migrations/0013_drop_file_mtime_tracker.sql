@@ -0,0 +1,9 @@
+-- Guard verification caches file hashes in the state directory, so the
+-- per-file mtime tracker is no longer read or written
+
+DROP TRIGGER IF EXISTS update_file_mtime_tracker_updated_at;
+DROP TABLE IF EXISTS file_mtime_tracker;
+
+-- Bump schema version
+INSERT OR REPLACE INTO schema_version (version, applied_at)
+    VALUES (13, strftime('%s', 'now'));
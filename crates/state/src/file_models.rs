@@ -115,13 +115,6 @@ impl InstalledFile {
     }
 }
 
-/// Simple modification time tracker for file verification optimization
-#[derive(Debug, Clone)]
-pub struct FileMTimeTracker {
-    pub file_path: String,
-    pub last_verified_mtime: i64,
-}
-
 /// Summary statistics for file-level storage
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileStorageStats {
@@ -2,8 +2,7 @@
 //! This is a temporary implementation until sqlx prepare is run
 
 use crate::file_models::{
-    DeduplicationResult, FileMTimeTracker, FileMetadata, FileObject, FileReference,
-    PackageFileEntry,
+    DeduplicationResult, FileMetadata, FileObject, FileReference, PackageFileEntry,
 };
 use sps2_errors::{Error, StateError};
 use sps2_hash::Hash;
@@ -329,6 +328,48 @@ pub async fn get_all_file_objects(
         .collect())
 }
 
+/// Get file objects no installed package references
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+pub async fn get_unreferenced_file_objects(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<Vec<FileObject>, Error> {
+    let rows = query(
+        r#"
+        SELECT 
+            hash,
+            size,
+            created_at,
+            ref_count,
+            is_executable,
+            is_symlink,
+            symlink_target
+        FROM file_objects
+        WHERE ref_count <= 0
+        "#,
+    )
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(|e| StateError::DatabaseError {
+        message: format!("failed to list unreferenced file objects: {e}"),
+    })?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| FileObject {
+            hash: r.get("hash"),
+            size: r.get("size"),
+            created_at: r.get("created_at"),
+            ref_count: r.get("ref_count"),
+            is_executable: r.get("is_executable"),
+            is_symlink: r.get("is_symlink"),
+            symlink_target: r.get("symlink_target"),
+        })
+        .collect())
+}
+
 /// Build a map of file hash -> last reference timestamp across all states
 ///
 /// # Errors
@@ -448,34 +489,6 @@ pub async fn get_file_entries_by_hash(
         .collect())
 }
 
-/// Update file modification time tracker
-///
-/// # Errors
-///
-/// Returns an error if the database operation fails
-pub async fn update_file_mtime(
-    tx: &mut Transaction<'_, Sqlite>,
-    file_path: &str,
-    verified_mtime: i64,
-) -> Result<(), Error> {
-    query(
-        r#"
-        INSERT OR REPLACE INTO file_mtime_tracker (
-            file_path, last_verified_mtime
-        ) VALUES (?, ?)
-        "#,
-    )
-    .bind(file_path)
-    .bind(verified_mtime)
-    .execute(&mut **tx)
-    .await
-    .map_err(|e| StateError::DatabaseError {
-        message: format!("failed to update file mtime tracker: {e}"),
-    })?;
-
-    Ok(())
-}
-
 /// Get package file entries by package name and version
 ///
 /// # Errors
@@ -565,37 +578,6 @@ pub async fn get_package_file_entries_all_states(
         .collect())
 }
 
-/// Get file modification time tracker entry
-///
-/// # Errors
-///
-/// Returns an error if the database operation fails
-pub async fn get_file_mtime(
-    tx: &mut Transaction<'_, Sqlite>,
-    file_path: &str,
-) -> Result<Option<FileMTimeTracker>, Error> {
-    let row = query(
-        r#"
-        SELECT 
-            file_path,
-            last_verified_mtime
-        FROM file_mtime_tracker
-        WHERE file_path = ?
-        "#,
-    )
-    .bind(file_path)
-    .fetch_optional(&mut **tx)
-    .await
-    .map_err(|e| StateError::DatabaseError {
-        message: format!("failed to get file mtime tracker: {e}"),
-    })?;
-
-    Ok(row.map(|r| FileMTimeTracker {
-        file_path: r.get("file_path"),
-        last_verified_mtime: r.get("last_verified_mtime"),
-    }))
-}
-
 /// Mark package as having file-level hashes
 ///
 /// # Errors
@@ -626,105 +608,6 @@ pub async fn mark_package_file_hashed(
     Ok(())
 }
 
-/// Get all mtime trackers for files in a package
-///
-/// # Errors
-///
-/// Returns an error if the database operation fails
-pub async fn get_package_file_mtimes(
-    tx: &mut Transaction<'_, Sqlite>,
-    package_name: &str,
-    package_version: &str,
-) -> Result<Vec<FileMTimeTracker>, Error> {
-    let rows = query(
-        r#"
-        SELECT DISTINCT
-            fmt.file_path,
-            fmt.last_verified_mtime
-        FROM file_mtime_tracker fmt
-        JOIN package_file_entries pfe ON pfe.relative_path = fmt.file_path
-        JOIN packages p ON p.id = pfe.package_id
-        WHERE p.name = ? AND p.version = ?
-        ORDER BY fmt.file_path
-        "#,
-    )
-    .bind(package_name)
-    .bind(package_version)
-    .fetch_all(&mut **tx)
-    .await
-    .map_err(|e| StateError::DatabaseError {
-        message: format!("failed to get package file mtime trackers: {e}"),
-    })?;
-
-    Ok(rows
-        .into_iter()
-        .map(|r| FileMTimeTracker {
-            file_path: r.get("file_path"),
-            last_verified_mtime: r.get("last_verified_mtime"),
-        })
-        .collect())
-}
-
-/// Clear mtime trackers for a package
-///
-/// # Errors
-///
-/// Returns an error if the database operation fails
-pub async fn clear_package_mtime_trackers(
-    tx: &mut Transaction<'_, Sqlite>,
-    package_name: &str,
-    package_version: &str,
-) -> Result<u64, Error> {
-    // Clear mtime trackers for all files associated with this package
-    let result = query(
-        r#"
-        DELETE FROM file_mtime_tracker
-        WHERE file_path IN (
-            SELECT DISTINCT pfe.relative_path
-            FROM package_file_entries pfe
-            JOIN packages p ON p.id = pfe.package_id
-            WHERE p.name = ? AND p.version = ?
-        )
-        "#,
-    )
-    .bind(package_name)
-    .bind(package_version)
-    .execute(&mut **tx)
-    .await
-    .map_err(|e| StateError::DatabaseError {
-        message: format!("failed to clear package mtime trackers: {e}"),
-    })?;
-
-    Ok(result.rows_affected())
-}
-
-/// Clear old mtime tracker entries
-///
-/// # Errors
-///
-/// Returns an error if the database operation fails
-pub async fn clear_old_mtime_trackers(
-    tx: &mut Transaction<'_, Sqlite>,
-    max_age_seconds: i64,
-) -> Result<u64, Error> {
-    let cutoff_time = chrono::Utc::now().timestamp() - max_age_seconds;
-
-    let result = query(
-        r#"
-        DELETE FROM file_mtime_tracker
-        WHERE updated_at < ?
-        "#,
-    )
-    .bind(cutoff_time)
-    .execute(&mut **tx)
-    .await
-    .map_err(|e| StateError::DatabaseError {
-        message: format!("failed to clear old mtime trackers: {e}"),
-    })?;
-
-    Ok(result.rows_affected())
-}
-
 /// Get file objects that need verification
 ///
 /// Returns objects that are either:
@@ -20,6 +20,7 @@ pub mod file_migration;
 pub mod file_models;
 pub mod file_queries_runtime;
 pub mod manager;
+pub mod manifest;
 pub mod models;
 
 #[cfg(feature = "runtime-queries")]
@@ -33,10 +34,13 @@ pub mod queries {
 mod queries_runtime;
 
 pub use file_models::{
-    DeduplicationResult, FileMTimeTracker, FileMetadata, FileObject, FileReference,
-    FileStorageStats, InstalledFile, PackageFileEntry,
+    DeduplicationResult, FileMetadata, FileObject, FileReference, FileStorageStats, InstalledFile,
+    PackageFileEntry,
 };
-pub use models::{Package, PackageRef, State, StoreRef};
+pub use manifest::{
+    StateManifest, StateManifestFile, StateManifestPackage, STATE_MANIFEST_VERSION,
+};
+pub use models::{GuardVerificationMarker, Package, PackageRef, State, StoreRef};
 
 use sps2_errors::Error;
 use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
@@ -1,15 +1,17 @@
 //! State manager implementation
 
 use crate::{
+    manifest::{StateManifest, StateManifestFile, StateManifestPackage, STATE_MANIFEST_VERSION},
     models::{Package, PackageRef, State, StoreRef},
-    queries,
+    queries, FileMetadata, FileReference,
 };
-use sps2_errors::Error;
+use sps2_errors::{Error, StateError};
 use sps2_events::{AppEvent, CleanupSummary, EventEmitter, EventSender, GeneralEvent, StateEvent};
 use sps2_hash::Hash;
 use sps2_platform::filesystem_helpers as sps2_root;
 use sps2_types::StateId;
 use sqlx::{Pool, Sqlite};
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::path::PathBuf;
 use std::time::Instant;
@@ -517,6 +519,38 @@ impl StateManager {
         })
     }
 
+    /// Get the names of the packages explicitly requested in a state
+    ///
+    /// Packages installed only as dependencies are not listed. States created
+    /// before requests were recorded have no entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database query fails.
+    pub async fn get_requested_packages(&self, state_id: &StateId) -> Result<Vec<String>, Error> {
+        let mut tx = self.pool.begin().await?;
+        let names = queries::get_requested_packages(&mut tx, state_id).await?;
+        tx.commit().await?;
+        Ok(names)
+    }
+
+    /// Get the runtime environment variables packages declare in a state
+    ///
+    /// Keyed by package name; packages without variables are absent.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database query fails.
+    pub async fn get_runtime_env(
+        &self,
+        state_id: &StateId,
+    ) -> Result<BTreeMap<String, BTreeMap<String, String>>, Error> {
+        let mut tx = self.pool.begin().await?;
+        let env = queries::get_runtime_env(&mut tx, state_id).await?;
+        tx.commit().await?;
+        Ok(env)
+    }
+
     /// Get package dependents
     ///
     /// # Errors
@@ -551,6 +585,201 @@ impl StateManager {
         Ok(hashes)
     }
 
+    /// Preview store garbage collection without removing anything
+    ///
+    /// Uses the same refcounts as [`Self::gc_store_with_removal`], plus the
+    /// file objects whose refcount is zero.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database operations or size calculation fails.
+    pub async fn gc_store_dry_run(
+        &self,
+        store: &sps2_store::PackageStore,
+    ) -> Result<sps2_store::GcPlan, Error> {
+        let mut tx = self.pool.begin().await?;
+        let packages = queries::get_unreferenced_store_items(&mut tx).await?;
+        let file_objects =
+            crate::file_queries_runtime::get_unreferenced_file_objects(&mut tx).await?;
+        tx.commit().await?;
+
+        let unreferenced = sps2_store::Unreferenced {
+            packages: packages.iter().map(StoreRef::hash).collect(),
+            file_objects: file_objects
+                .iter()
+                .map(|object| Hash::from_hex(&object.hash))
+                .collect::<Result<_, _>>()?,
+        };
+        store.garbage_collect_dry_run(&unreferenced).await
+    }
+
+    /// Export a state as a portable [`StateManifest`]
+    ///
+    /// This is read-only.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state does not exist, a file entry references
+    /// an unknown file object, or database queries fail.
+    pub async fn export_state(&self, state_id: &StateId) -> Result<StateManifest, Error> {
+        let mut tx = self.pool.begin().await?;
+        let state = queries::get_state(&mut tx, state_id)
+            .await?
+            .ok_or_else(|| StateError::StateNotFound {
+                id: state_id.to_string(),
+            })?;
+
+        let mut packages = Vec::new();
+        for package in queries::get_state_packages(&mut tx, state_id).await? {
+            let mut files = Vec::new();
+            for entry in queries::get_package_file_entries(&mut tx, package.id).await? {
+                let object = queries::get_file_object(&mut tx, &Hash::from_hex(&entry.file_hash)?)
+                    .await?
+                    .ok_or_else(|| StateError::StateCorrupted {
+                        message: format!(
+                            "{}-{}: file entry {} references unknown file object {}",
+                            package.name, package.version, entry.relative_path, entry.file_hash
+                        ),
+                    })?;
+                files.push(StateManifestFile {
+                    path: entry.relative_path,
+                    hash: entry.file_hash,
+                    permissions: entry.permissions as u32,
+                    size: object.size,
+                    is_executable: object.is_executable,
+                    is_symlink: object.is_symlink,
+                    symlink_target: object.symlink_target,
+                });
+            }
+            files.sort_by(|a, b| a.path.cmp(&b.path));
+            packages.push(StateManifestPackage {
+                name: package.name,
+                version: package.version,
+                hash: package.hash,
+                size: package.size,
+                files,
+            });
+        }
+        let requested_packages = queries::get_requested_packages(&mut tx, state_id).await?;
+        let runtime_env = queries::get_runtime_env(&mut tx, state_id).await?;
+        tx.commit().await?;
+
+        packages.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+        Ok(StateManifest {
+            format_version: STATE_MANIFEST_VERSION,
+            state_id: *state_id,
+            operation: state.operation,
+            created_at: state.created_at,
+            packages,
+            requested_packages,
+            runtime_env,
+        })
+    }
+
+    /// Re-create a state from a [`StateManifest`]
+    ///
+    /// Every package and file object the manifest references must already be
+    /// present in `store`; nothing is written unless they all are. Nothing is
+    /// fetched here; `sps2_ops::import_state` downloads missing packages from
+    /// the index first. The new state is recorded as a child of the active
+    /// state but not activated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest version is unsupported, referenced
+    /// content is missing from the store, or database operations fail.
+    pub async fn import_state(
+        &self,
+        manifest: &StateManifest,
+        store: &sps2_store::PackageStore,
+    ) -> Result<StateId, Error> {
+        if manifest.format_version != STATE_MANIFEST_VERSION {
+            return Err(StateError::UnsupportedManifestVersion {
+                found: manifest.format_version,
+                supported: STATE_MANIFEST_VERSION,
+            }
+            .into());
+        }
+
+        let mut missing = Vec::new();
+        for package in &manifest.packages {
+            let label = format!("{}-{}", package.name, package.version);
+            let present = match Hash::from_hex(&package.hash) {
+                Ok(hash) => store.has_package(&hash).await,
+                Err(_) => false,
+            };
+            if !present {
+                missing.push(label);
+                continue;
+            }
+            for file in package.files.iter().filter(|f| f.has_store_object()) {
+                let present = match Hash::from_hex(&file.hash) {
+                    Ok(hash) => store.file_store().has_file(&hash).await,
+                    Err(_) => false,
+                };
+                if !present {
+                    missing.push(format!("{label}:{}", file.path));
+                }
+            }
+        }
+        if !missing.is_empty() {
+            return Err(StateError::ManifestContentUnavailable {
+                missing: missing.join(", "),
+            }
+            .into());
+        }
+
+        let state_id = Uuid::new_v4();
+        let mut tx = self.pool.begin().await?;
+        let parent_id = queries::get_active_state(&mut tx).await?;
+        queries::create_state(&mut tx, &state_id, Some(&parent_id), "import").await?;
+
+        for package in &manifest.packages {
+            let package_id = queries::add_package(
+                &mut tx,
+                &state_id,
+                &package.name,
+                &package.version,
+                &package.hash,
+                package.size,
+            )
+            .await?;
+            queries::get_or_create_store_ref(&mut tx, &package.hash, package.size).await?;
+            queries::increment_store_ref(&mut tx, &package.hash).await?;
+
+            for file in &package.files {
+                let hash = Hash::from_hex(&file.hash)?;
+                let metadata = FileMetadata {
+                    size: file.size,
+                    permissions: file.permissions,
+                    uid: 0,
+                    gid: 0,
+                    mtime: None,
+                    is_executable: file.is_executable,
+                    is_symlink: file.is_symlink,
+                    symlink_target: file.symlink_target.clone(),
+                };
+                queries::add_file_object(&mut tx, &hash, &metadata).await?;
+                queries::add_package_file_entry(
+                    &mut tx,
+                    package_id,
+                    &FileReference {
+                        package_id,
+                        relative_path: file.path.clone(),
+                        hash,
+                        metadata,
+                    },
+                )
+                .await?;
+            }
+        }
+        queries::add_requested_packages(&mut tx, &state_id, &manifest.requested_packages).await?;
+        queries::add_runtime_env(&mut tx, &state_id, &manifest.runtime_env).await?;
+
+        tx.commit().await?;
+        Ok(state_id)
+    }
+
     /// Garbage collect unreferenced store items with file removal
     ///
     /// # Errors
@@ -1167,6 +1396,10 @@ pub struct TransactionData<'a> {
     pub file_references: &'a [(i64, crate::FileReference)], // (package_id, file_reference)
     /// Pending file hashes to be converted to file references after packages are added
     pub pending_file_hashes: &'a [(sps2_resolver::PackageId, Vec<sps2_hash::FileHashResult>)],
+    /// Names of the packages explicitly requested in the new state
+    pub requested_packages: &'a [String],
+    /// Runtime environment variables declared by the new state's packages
+    pub runtime_env: &'a BTreeMap<String, BTreeMap<String, String>>,
 }
 
 impl StateManager {
@@ -1286,6 +1519,11 @@ impl StateManager {
             .add_package_refs_and_build_id_map(&mut tx, transition_data.package_refs)
             .await?;
 
+        // Record which packages the user asked for, as opposed to their dependencies
+        queries::add_requested_packages(&mut tx, staging_id, transition_data.requested_packages)
+            .await?;
+        queries::add_runtime_env(&mut tx, staging_id, transition_data.runtime_env).await?;
+
         // Add legacy package_files rows
         self.add_legacy_package_files(&mut tx, staging_id, transition_data.package_files)
             .await?;
@@ -1491,6 +1729,8 @@ mod tests {
             package_files: &[],
             file_references: &[],
             pending_file_hashes: &[],
+            requested_packages: &[],
+            runtime_env: &BTreeMap::new(),
         };
 
         let staging_path = state.state_path().join(format!("staging-{staging_id}"));
@@ -1554,6 +1794,8 @@ mod tests {
                 ),
                 file_hashes,
             )],
+            requested_packages: &[],
+            runtime_env: &BTreeMap::new(),
         };
         let staging_path = state.state_path().join(format!("staging-{staging_id}"));
         let _ = state
@@ -1572,6 +1814,91 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn gc_dry_run_plans_unreferenced_packages_without_deleting() {
+        let (td, state) = mk_state().await;
+        let store = sps2_store::PackageStore::new(td.path().join("store"));
+
+        let live = sps2_hash::Hash::from_data(b"pkg-live").to_hex();
+        let orphan = sps2_hash::Hash::from_data(b"pkg-orphan").to_hex();
+        let missing = sps2_hash::Hash::from_data(b"pkg-missing").to_hex();
+
+        let mut tx = state.begin_transaction().await.expect("tx");
+        for hash in [&live, &orphan, &missing] {
+            queries::get_or_create_store_ref(&mut tx, hash, 1)
+                .await
+                .expect("store ref");
+        }
+        queries::increment_store_ref(&mut tx, &live)
+            .await
+            .expect("increment");
+        tx.commit().await.expect("commit");
+
+        for hash in [&live, &orphan] {
+            let dir = store.package_path(&sps2_hash::Hash::from_hex(hash).unwrap());
+            std::fs::create_dir_all(&dir).expect("package dir");
+            std::fs::write(dir.join("manifest.toml"), vec![b'x'; 4096]).expect("manifest");
+        }
+
+        let plan = state.gc_store_dry_run(&store).await.expect("dry run");
+
+        assert_eq!(plan.packages.len(), 1);
+        assert_eq!(plan.packages[0].hash, orphan);
+        assert!(plan.bytes_freed >= 4096);
+        assert_eq!(plan.bytes_freed, plan.packages[0].size);
+        let json = serde_json::to_string(&plan).expect("serialize");
+        assert!(json.contains(&orphan));
+
+        // Nothing was removed from disk or from the refcount table
+        let orphan_hash = sps2_hash::Hash::from_hex(&orphan).unwrap();
+        assert!(store.package_path(&orphan_hash).exists());
+        let mut tx = state.begin_transaction().await.expect("tx");
+        let refs = queries::get_all_store_refs(&mut tx).await.expect("refs");
+        tx.commit().await.expect("commit");
+        assert_eq!(refs.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn gc_dry_run_plans_unreferenced_file_objects_without_deleting() {
+        let (td, state) = mk_state().await;
+        let store = sps2_store::PackageStore::new(td.path().join("store"));
+
+        // One object only an unreferenced package used, one still shared
+        let mut stored = Vec::new();
+        for contents in [&b"unique to the orphan"[..], b"still shared"] {
+            let src = td.path().join("src");
+            std::fs::write(&src, contents).expect("source file");
+            let (hash, _) = store
+                .file_store()
+                .store_file_with_hash(&src)
+                .await
+                .expect("store file");
+            stored.push(hash);
+        }
+        let (unique, shared) = (&stored[0], &stored[1]);
+
+        let mut tx = state.begin_transaction().await.expect("tx");
+        for hash in [unique, shared] {
+            let meta = crate::FileMetadata::regular_file(20, 0o644);
+            fq::add_file_object(&mut tx, hash, &meta)
+                .await
+                .expect("add fo");
+        }
+        fq::set_file_object_ref_count(&mut tx, &unique.to_hex(), 0)
+            .await
+            .expect("drop refcount");
+        tx.commit().await.expect("commit");
+
+        let plan = state.gc_store_dry_run(&store).await.expect("dry run");
+
+        assert!(plan.packages.is_empty());
+        assert_eq!(plan.file_objects.len(), 1);
+        assert_eq!(plan.file_objects[0].hash, unique.to_hex());
+        assert_eq!(plan.file_objects[0].size, 20);
+        assert_eq!(plan.bytes_freed, 20);
+        assert!(store.file_store().has_file(unique).await);
+    }
+
     #[tokio::test]
     async fn t_2pc_uninstall_decrements_to_zero() {
         let (_td, state) = mk_state().await;
@@ -1584,6 +1911,8 @@ mod tests {
             package_files: &[],
             file_references: &[],
             pending_file_hashes: &[],
+            requested_packages: &[],
+            runtime_env: &BTreeMap::new(),
         };
         let staging_id = uuid::Uuid::new_v4();
         let staging_path = state.state_path().join(format!("staging-{staging_id}"));
@@ -1631,6 +1960,8 @@ mod tests {
             package_files: &[],
             file_references: &[],
             pending_file_hashes: &[(pid, vec![fh])],
+            requested_packages: &[],
+            runtime_env: &BTreeMap::new(),
         };
         let staging_path = state.state_path().join(format!("staging-{staging_id}"));
         let _ = state
@@ -1649,4 +1980,102 @@ mod tests {
             1
         );
     }
+
+    #[tokio::test]
+    async fn export_import_replicates_state_into_fresh_database() {
+        let (_td, source) = mk_state().await;
+        let pkg_hash = sps2_hash::Hash::from_data(b"pkg-A").to_hex();
+        seed_parent_with_pkg(
+            &source,
+            "A",
+            "1.0.0",
+            &pkg_hash,
+            &[("bin/a", 3), ("bin/b", 4)],
+        )
+        .await;
+        let source_id = source.get_active_state().await.expect("active");
+        let runtime_env = BTreeMap::from([(
+            "A".to_string(),
+            BTreeMap::from([("A_HOME".to_string(), "/opt/pm/live/share/a".to_string())]),
+        )]);
+        let mut tx = source.begin_transaction().await.expect("tx");
+        queries::add_requested_packages(&mut tx, &source_id, &["A".to_string()])
+            .await
+            .expect("requested");
+        queries::add_runtime_env(&mut tx, &source_id, &runtime_env)
+            .await
+            .expect("runtime env");
+        tx.commit().await.expect("commit");
+
+        let manifest = source.export_state(&source_id).await.expect("export");
+        assert_eq!(manifest.format_version, STATE_MANIFEST_VERSION);
+        assert_eq!(manifest.packages.len(), 1);
+        assert_eq!(manifest.packages[0].files.len(), 2);
+        assert_eq!(manifest.packages[0].files[0].path, "bin/a");
+
+        let json = serde_json::to_string(&manifest).expect("serialize");
+        let manifest: StateManifest = serde_json::from_str(&json).expect("deserialize");
+
+        let (_td2, target) = mk_state().await;
+        let store_dir = TempDir::new().expect("store dir");
+        let store = sps2_store::PackageStore::new(store_dir.path().to_path_buf());
+        std::fs::create_dir_all(store.package_path(&sps2_hash::Hash::from_hex(&pkg_hash).unwrap()))
+            .expect("package dir");
+
+        let imported = target
+            .import_state(&manifest, &store)
+            .await
+            .expect("import");
+        assert_ne!(imported, target.get_active_state().await.expect("active"));
+
+        let replica = target.export_state(&imported).await.expect("re-export");
+        assert_eq!(replica.operation, "import");
+        assert_eq!(replica.packages, manifest.packages);
+        assert_eq!(replica.requested_packages, ["A"]);
+        assert_eq!(replica.runtime_env, runtime_env);
+        assert_eq!(store_ref_count(&target, &pkg_hash).await, 1);
+    }
+
+    #[test]
+    fn manifest_without_requested_packages_or_runtime_env_deserializes() {
+        let json = format!(
+            r#"{{"format_version":{STATE_MANIFEST_VERSION},"state_id":"{}","operation":"install","created_at":0,"packages":[]}}"#,
+            uuid::Uuid::nil()
+        );
+        let manifest: StateManifest = serde_json::from_str(&json).expect("deserialize");
+        assert!(manifest.requested_packages.is_empty());
+        assert!(manifest.runtime_env.is_empty());
+    }
+
+    #[tokio::test]
+    async fn import_rejects_missing_content_without_writing() {
+        let (_td, state) = mk_state().await;
+        let pkg_hash = sps2_hash::Hash::from_data(b"pkg-A").to_hex();
+        seed_parent_with_pkg(&state, "A", "1.0.0", &pkg_hash, &[("bin/a", 3)]).await;
+        let active = state.get_active_state().await.expect("active");
+        let mut manifest = state.export_state(&active).await.expect("export");
+        let states_before = state.list_states().await.expect("states").len();
+
+        let store_dir = TempDir::new().expect("store dir");
+        let store = sps2_store::PackageStore::new(store_dir.path().to_path_buf());
+        let err = state
+            .import_state(&manifest, &store)
+            .await
+            .expect_err("package missing from store");
+        assert!(err.to_string().contains("A-1.0.0"), "{err}");
+        assert_eq!(
+            state.list_states().await.expect("states").len(),
+            states_before
+        );
+
+        manifest.format_version = STATE_MANIFEST_VERSION + 1;
+        let err = state
+            .import_state(&manifest, &store)
+            .await
+            .expect_err("unsupported version");
+        assert!(matches!(
+            err,
+            Error::State(StateError::UnsupportedManifestVersion { .. })
+        ));
+    }
 }
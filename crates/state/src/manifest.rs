@@ -0,0 +1,72 @@
+//! Portable state manifests for backup and replication
+//!
+//! A [`StateManifest`] captures the exact package set of one state (names,
+//! versions, package hashes, and per-file hashes) so it can be re-created on
+//! another machine with [`crate::StateManager::import_state`].
+
+use serde::{Deserialize, Serialize};
+use sps2_types::StateId;
+use std::collections::BTreeMap;
+
+/// Current state manifest format version
+pub const STATE_MANIFEST_VERSION: u32 = 1;
+
+/// Serializable description of a single state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateManifest {
+    /// Manifest format version
+    pub format_version: u32,
+    /// State the manifest was exported from
+    pub state_id: StateId,
+    /// Operation that created the exported state
+    pub operation: String,
+    /// Creation time of the exported state (unix seconds)
+    pub created_at: i64,
+    /// Packages in the state, sorted by name and version
+    pub packages: Vec<StateManifestPackage>,
+    /// Names of the packages explicitly requested, as opposed to dependencies
+    #[serde(default)]
+    pub requested_packages: Vec<String>,
+    /// Runtime environment variables recorded in the state, by package
+    #[serde(default)]
+    pub runtime_env: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// Package entry in a [`StateManifest`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateManifestPackage {
+    pub name: String,
+    pub version: String,
+    /// Store hash of the package
+    pub hash: String,
+    pub size: i64,
+    /// Files in the package, sorted by path
+    pub files: Vec<StateManifestFile>,
+}
+
+/// File entry of a [`StateManifestPackage`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateManifestFile {
+    /// Path relative to the package root
+    pub path: String,
+    /// Content hash of the file object
+    pub hash: String,
+    pub permissions: u32,
+    pub size: i64,
+    pub is_executable: bool,
+    pub is_symlink: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symlink_target: Option<String>,
+}
+
+impl StateManifestFile {
+    /// Whether this entry has a file object in the store
+    ///
+    /// Directories and symlinks are recorded in the database only.
+    #[must_use]
+    pub fn has_store_object(&self) -> bool {
+        const S_IFMT: u32 = 0o170_000;
+        const S_IFREG: u32 = 0o100_000;
+        !self.is_symlink && self.permissions & S_IFMT == S_IFREG
+    }
+}
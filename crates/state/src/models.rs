@@ -40,6 +40,26 @@ impl State {
     }
 }
 
+/// Last state that passed guard verification
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct GuardVerificationMarker {
+    pub state_id: String,
+    pub verified_at: i64,
+    pub full_sweep_at: i64,
+}
+
+impl GuardVerificationMarker {
+    /// Convert to `StateId`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stored ID is not a valid UUID.
+    #[must_use]
+    pub fn state_id(&self) -> StateId {
+        uuid::Uuid::parse_str(&self.state_id).expect("valid UUID in database")
+    }
+}
+
 /// An installed package record
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Package {
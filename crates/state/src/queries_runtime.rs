@@ -1,10 +1,10 @@
 //! Runtime SQL queries for state operations (temporary until sqlx prepare is run)
 
-use crate::models::{Package, State, StoreRef};
+use crate::models::{GuardVerificationMarker, Package, State, StoreRef};
 use sps2_errors::{Error, StateError};
 use sps2_types::StateId;
 use sqlx::{query, Row, Sqlite, Transaction};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Get the current active state
 ///
@@ -419,6 +419,35 @@ pub async fn get_all_states(tx: &mut Transaction<'_, Sqlite>) -> Result<Vec<Stat
     Ok(states)
 }
 
+/// Get a single state by ID
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+pub async fn get_state(
+    tx: &mut Transaction<'_, Sqlite>,
+    state_id: &StateId,
+) -> Result<Option<State>, Error> {
+    let row = query(
+        r"SELECT id, parent_id, created_at, operation,
+           success, rollback_of, pruned_at
+           FROM states WHERE id = ?1",
+    )
+    .bind(state_id.to_string())
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(row.map(|row| State {
+        id: row.get("id"),
+        parent_id: row.get("parent_id"),
+        created_at: row.get("created_at"),
+        operation: row.get("operation"),
+        success: row.get("success"),
+        rollback_of: row.get("rollback_of"),
+        pruned_at: row.get("pruned_at"),
+    }))
+}
+
 /// Get states for cleanup
 ///
 /// # Errors
@@ -1114,3 +1143,150 @@ pub async fn remove_package_map(
 
     Ok(())
 }
+
+/// Get the names of the packages explicitly requested in a state
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+pub async fn get_requested_packages(
+    tx: &mut Transaction<'_, Sqlite>,
+    state_id: &StateId,
+) -> Result<Vec<String>, Error> {
+    let rows = query("SELECT name FROM requested_packages WHERE state_id = ?1 ORDER BY name")
+        .bind(state_id.to_string())
+        .fetch_all(&mut **tx)
+        .await?;
+
+    Ok(rows.into_iter().map(|r| r.get("name")).collect())
+}
+
+/// Record the packages explicitly requested in a state
+///
+/// # Errors
+///
+/// Returns an error if the database insert fails.
+pub async fn add_requested_packages(
+    tx: &mut Transaction<'_, Sqlite>,
+    state_id: &StateId,
+    names: &[String],
+) -> Result<(), Error> {
+    for name in names {
+        query("INSERT OR IGNORE INTO requested_packages (state_id, name) VALUES (?1, ?2)")
+            .bind(state_id.to_string())
+            .bind(name)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Get the runtime environment variables recorded in a state, by package
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+pub async fn get_runtime_env(
+    tx: &mut Transaction<'_, Sqlite>,
+    state_id: &StateId,
+) -> Result<BTreeMap<String, BTreeMap<String, String>>, Error> {
+    let rows =
+        query("SELECT package_name, name, value FROM package_runtime_env WHERE state_id = ?1")
+            .bind(state_id.to_string())
+            .fetch_all(&mut **tx)
+            .await?;
+
+    let mut env: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    for row in rows {
+        env.entry(row.get("package_name"))
+            .or_default()
+            .insert(row.get("name"), row.get("value"));
+    }
+    Ok(env)
+}
+
+/// Record the runtime environment variables of the packages in a state
+///
+/// # Errors
+///
+/// Returns an error if the database insert fails.
+pub async fn add_runtime_env(
+    tx: &mut Transaction<'_, Sqlite>,
+    state_id: &StateId,
+    env: &BTreeMap<String, BTreeMap<String, String>>,
+) -> Result<(), Error> {
+    for (package_name, vars) in env {
+        for (name, value) in vars {
+            query(
+                "INSERT OR REPLACE INTO package_runtime_env (state_id, package_name, name, value) \
+                 VALUES (?1, ?2, ?3, ?4)",
+            )
+            .bind(state_id.to_string())
+            .bind(package_name)
+            .bind(name)
+            .bind(value)
+            .execute(&mut **tx)
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the marker for the last state that passed guard verification
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+pub async fn get_guard_verification_marker(
+    tx: &mut Transaction<'_, Sqlite>,
+) -> Result<Option<GuardVerificationMarker>, Error> {
+    let row = query(
+        "SELECT state_id, verified_at, full_sweep_at FROM guard_verification_marker WHERE id = 1",
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(row.map(|r| GuardVerificationMarker {
+        state_id: r.get("state_id"),
+        verified_at: r.get("verified_at"),
+        full_sweep_at: r.get("full_sweep_at"),
+    }))
+}
+
+/// Record that `state_id` passed guard verification
+///
+/// `full_sweep_at` only advances when `full_sweep` is set, so incremental runs
+/// do not postpone the next periodic full sweep.
+///
+/// # Errors
+///
+/// Returns an error if the database update fails.
+pub async fn set_guard_verification_marker(
+    tx: &mut Transaction<'_, Sqlite>,
+    state_id: &StateId,
+    full_sweep: bool,
+) -> Result<(), Error> {
+    let now = chrono::Utc::now().timestamp();
+    let full_sweep_at = if full_sweep { now } else { 0 };
+
+    query(
+        r#"
+        INSERT INTO guard_verification_marker (id, state_id, verified_at, full_sweep_at)
+        VALUES (1, ?1, ?2, ?3)
+        ON CONFLICT(id) DO UPDATE SET
+            state_id = excluded.state_id,
+            verified_at = excluded.verified_at,
+            full_sweep_at = CASE WHEN ?4 THEN excluded.full_sweep_at ELSE full_sweep_at END
+        "#,
+    )
+    .bind(state_id.to_string())
+    .bind(now)
+    .bind(full_sweep_at)
+    .bind(full_sweep)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
@@ -98,38 +98,6 @@ async fn test_package_file_entries() {
     tx.commit().await.unwrap();
 }
 
-#[tokio::test]
-async fn test_file_mtime_tracker() {
-    let temp_dir = TempDir::new().unwrap();
-    let db_path = temp_dir.path().join("test.db");
-
-    let pool = create_pool(&db_path).await.unwrap();
-    run_migrations(&pool).await.unwrap();
-
-    let hash = Hash::from_data(b"test file");
-    let path = "/opt/pm/live/bin/test";
-
-    // Add file object first
-    let metadata = FileMetadata::regular_file(1024, 0o755);
-    let mut tx = pool.begin().await.unwrap();
-    add_file_object(&mut tx, &hash, &metadata).await.unwrap();
-
-    // Update mtime tracker
-    let current_mtime = chrono::Utc::now().timestamp();
-    update_file_mtime(&mut tx, path, current_mtime)
-        .await
-        .unwrap();
-
-    // Retrieve mtime tracker
-    let tracker = get_file_mtime(&mut tx, path).await.unwrap();
-    tx.commit().await.unwrap();
-    assert!(tracker.is_some());
-
-    let entry = tracker.unwrap();
-    assert_eq!(entry.file_path, path);
-    assert_eq!(entry.last_verified_mtime, current_mtime);
-}
-
 #[tokio::test]
 async fn test_storage_stats() {
     let temp_dir = TempDir::new().unwrap();
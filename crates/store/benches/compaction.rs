@@ -0,0 +1,104 @@
+//! Compare loose and packed objects for a package with many tiny files
+//!
+//! Run with `cargo bench -p sps2-store --bench compaction [files]`. Reports
+//! the time to verify and link every object before and after compaction,
+//! and how many object inodes remain.
+
+use sps2_hash::Hash;
+use sps2_store::{CompactionOptions, FileStore};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const DEFAULT_FILES: usize = 5_000;
+
+fn main() {
+    let files = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_FILES);
+
+    let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+    runtime.block_on(run(files));
+}
+
+async fn run(files: usize) {
+    let temp_dir = tempfile::tempdir().expect("temp dir");
+    let store = FileStore::new(&temp_dir.path().join("store"));
+    let source = temp_dir.path().join("source");
+    tokio::fs::create_dir_all(&source).await.unwrap();
+
+    let mut hashes = Vec::with_capacity(files);
+    for i in 0..files {
+        let path = source.join(format!("{i}.h"));
+        tokio::fs::write(&path, format!("#define VALUE_{i} {i}\n"))
+            .await
+            .unwrap();
+        hashes.push(store.store_file_with_hash(&path).await.unwrap().0);
+    }
+    println!("{files} objects");
+
+    let loose_verify = verify_all(&store, &hashes).await;
+    let loose_objects = count_files(&temp_dir.path().join("store/objects"));
+    report("loose", loose_verify, loose_objects);
+
+    let options = CompactionOptions {
+        max_object_size: 64 * 1024,
+        min_objects: 1,
+        ..CompactionOptions::default()
+    };
+    store.enable_packs().await.unwrap();
+    let start = Instant::now();
+    let compaction = store
+        .compact_objects("bench", &hashes, &options)
+        .await
+        .unwrap();
+    println!(
+        "compaction: {} objects, {} bytes in {:.2?}",
+        compaction.objects_packed,
+        compaction.bytes_packed,
+        start.elapsed()
+    );
+
+    let packed_verify = verify_all(&store, &hashes).await;
+    let packed_objects = count_files(&temp_dir.path().join("store/objects"));
+    report("packed", packed_verify, packed_objects);
+
+    let live = temp_dir.path().join("live");
+    let start = Instant::now();
+    for (i, hash) in hashes.iter().enumerate() {
+        store
+            .link_file(hash, &live.join(format!("{i}.h")))
+            .await
+            .unwrap();
+    }
+    println!("link from packs (re-inflating): {:.2?}", start.elapsed());
+}
+
+async fn verify_all(store: &FileStore, hashes: &[Hash]) -> Duration {
+    let start = Instant::now();
+    for hash in hashes {
+        assert!(store.verify_file(hash).await.unwrap());
+    }
+    start.elapsed()
+}
+
+fn report(label: &str, verify: Duration, objects: usize) {
+    println!("{label}: verify {verify:.2?}, {objects} object files");
+}
+
+fn count_files(dir: &Path) -> usize {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                count_files(&path)
+            } else {
+                1
+            }
+        })
+        .sum()
+}
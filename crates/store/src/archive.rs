@@ -7,10 +7,37 @@ use sps2_errors::{Error, PackageError, StorageError};
 use sps2_events::{AppEvent, EventEmitter, EventSender, GeneralEvent};
 use sps2_platform::core::PlatformContext;
 use sps2_platform::PlatformManager;
-use std::path::Path;
-use tar::Archive;
+use sps2_types::is_installed_entry;
+use std::path::{Component, Path, PathBuf};
+use tar::{Archive, EntryType};
 use tokio::io::{AsyncWriteExt, BufReader};
 
+/// Where package payloads live inside the archive
+const LIVE_PREFIX: &str = "opt/pm/live";
+
+/// Kind of entry a package would install
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageFileType {
+    File,
+    Directory,
+    Symlink,
+}
+
+/// A single entry a package would install, read from the archive headers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageFileEntry {
+    /// Path relative to the live prefix
+    pub path: PathBuf,
+    /// Size in bytes (zero for directories and symlinks)
+    pub size: u64,
+    /// Entry type
+    pub file_type: PackageFileType,
+    /// Unix permission bits from the archive header
+    pub mode: u32,
+    /// Link target for symlinks
+    pub link_target: Option<PathBuf>,
+}
+
 /// Create a platform context for filesystem operations
 fn create_platform_context() -> (&'static sps2_platform::Platform, PlatformContext) {
     let platform = PlatformManager::instance().platform();
@@ -80,6 +107,27 @@ pub async fn list_package_contents(sp_file: &Path) -> Result<Vec<String>, Error>
     }
 }
 
+/// List the files a .sp package would install without extracting it
+///
+/// The embedded `manifest.toml` is parsed to confirm the archive is a valid
+/// package; metadata files (manifest and SBOMs) are excluded from the result.
+/// Paths are relative to the live prefix: an `opt/pm/live/` prefix in the
+/// archive is stripped and the prefix directories themselves are left out.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - Archive reading fails
+/// - The package is missing or has an invalid manifest.toml
+/// - The archive contains path traversal
+pub async fn list_package_files(sp_file: &Path) -> Result<Vec<PackageFileEntry>, Error> {
+    if crate::ingest::is_zstd_archive(sp_file).await? {
+        list_zstd_tar_files(sp_file).await
+    } else {
+        list_plain_tar_files(sp_file).await
+    }
+}
+
 /// Create a .sp package file from a directory
 ///
 /// # Errors
@@ -298,49 +346,50 @@ async fn extract_plain_tar_file(
     Ok(())
 }
 
-/// List contents of a zstd-compressed tar archive
-async fn list_zstd_tar_contents(file_path: &Path) -> Result<Vec<String>, Error> {
-    // Create a temporary file to decompress to, then list contents
+/// Decompress a zstd file into a temporary file
+async fn decompress_zstd_to_temp(file_path: &Path) -> Result<tempfile::NamedTempFile, Error> {
+    use tokio::fs::File;
+
     let temp_file = tempfile::NamedTempFile::new().map_err(|e| StorageError::IoError {
         message: format!("failed to create temp file: {e}"),
     })?;
 
-    let temp_path = temp_file.path().to_path_buf();
-
-    // Decompress the zstd file to temporary location
-    {
-        use tokio::fs::File;
+    let input_file = File::open(file_path)
+        .await
+        .map_err(|e| StorageError::IoError {
+            message: format!("failed to open compressed file: {e}"),
+        })?;
 
-        let input_file = File::open(file_path)
+    let mut output_file =
+        File::create(temp_file.path())
             .await
             .map_err(|e| StorageError::IoError {
-                message: format!("failed to open compressed file: {e}"),
+                message: format!("failed to create temp output file: {e}"),
             })?;
 
-        let mut output_file =
-            File::create(&temp_path)
-                .await
-                .map_err(|e| StorageError::IoError {
-                    message: format!("failed to create temp output file: {e}"),
-                })?;
+    let mut decoder = AsyncZstdReader::new(BufReader::new(input_file));
+    tokio::io::copy(&mut decoder, &mut output_file)
+        .await
+        .map_err(|e| StorageError::IoError {
+            message: format!("failed to decompress zstd file: {e}"),
+        })?;
 
-        let mut decoder = AsyncZstdReader::new(BufReader::new(input_file));
-        tokio::io::copy(&mut decoder, &mut output_file)
-            .await
-            .map_err(|e| StorageError::IoError {
-                message: format!("failed to decompress zstd file: {e}"),
-            })?;
+    output_file
+        .flush()
+        .await
+        .map_err(|e| StorageError::IoError {
+            message: format!("failed to flush temp file: {e}"),
+        })?;
 
-        output_file
-            .flush()
-            .await
-            .map_err(|e| StorageError::IoError {
-                message: format!("failed to flush temp file: {e}"),
-            })?;
-    }
+    Ok(temp_file)
+}
+
+/// List contents of a zstd-compressed tar archive
+async fn list_zstd_tar_contents(file_path: &Path) -> Result<Vec<String>, Error> {
+    let temp_file = decompress_zstd_to_temp(file_path).await?;
 
     // Now list the decompressed tar file contents
-    let temp_path_for_task = temp_path.clone();
+    let temp_path_for_task = temp_file.path().to_path_buf();
 
     // Keep temp_file alive until after the blocking operation completes
     let result = tokio::task::spawn_blocking(move || -> Result<Vec<String>, Error> {
@@ -392,6 +441,119 @@ async fn list_plain_tar_contents(file_path: &Path) -> Result<Vec<String>, Error>
     .map_err(|e| Error::internal(format!("plain tar list task failed: {e}")))?
 }
 
+/// List installable files of a zstd-compressed tar archive
+async fn list_zstd_tar_files(file_path: &Path) -> Result<Vec<PackageFileEntry>, Error> {
+    let temp_file = decompress_zstd_to_temp(file_path).await?;
+    let temp_path_for_task = temp_file.path().to_path_buf();
+
+    // Keep temp_file alive until after the blocking operation completes
+    let result = tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::open(&temp_path_for_task)?;
+        collect_package_file_entries(&mut Archive::new(file))
+    })
+    .await
+    .map_err(|e| Error::internal(format!("zstd list task failed: {e}")))?;
+
+    drop(temp_file);
+
+    result
+}
+
+/// List installable files of a plain tar file
+async fn list_plain_tar_files(file_path: &Path) -> Result<Vec<PackageFileEntry>, Error> {
+    let file_path = file_path.to_path_buf();
+
+    tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::open(&file_path)?;
+        collect_package_file_entries(&mut Archive::new(file))
+    })
+    .await
+    .map_err(|e| Error::internal(format!("plain tar list task failed: {e}")))?
+}
+
+/// Read archive headers into file entries, validating the embedded manifest
+fn collect_package_file_entries<R: std::io::Read>(
+    archive: &mut Archive<R>,
+) -> Result<Vec<PackageFileEntry>, Error> {
+    use std::io::Read;
+
+    let mut files = Vec::new();
+    let mut has_manifest = false;
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        let path = entry.path()?.into_owned();
+        if path.components().any(|c| c == Component::ParentDir) {
+            return Err(PackageError::InvalidFormat {
+                message: "archive contains path traversal".to_string(),
+            }
+            .into());
+        }
+
+        // Same normalization as ingest: only normal components count
+        let path: PathBuf = path
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .collect();
+
+        if path.as_os_str().is_empty() {
+            continue;
+        }
+
+        if path == Path::new("manifest.toml") {
+            let mut content = String::new();
+            entry
+                .read_to_string(&mut content)
+                .map_err(|e| PackageError::InvalidManifest {
+                    message: format!("failed to read manifest: {e}"),
+                })?;
+            sps2_types::Manifest::from_toml(&content)?;
+            has_manifest = true;
+            continue;
+        }
+
+        if path.to_str().is_some_and(|path| !is_installed_entry(path)) {
+            continue;
+        }
+        // Report paths relative to the live prefix
+        let path = path
+            .strip_prefix(LIVE_PREFIX)
+            .map_or_else(|_| path.clone(), Path::to_path_buf);
+
+        let header = entry.header();
+        let file_type = match header.entry_type() {
+            EntryType::Regular | EntryType::Continuous => PackageFileType::File,
+            EntryType::Directory => PackageFileType::Directory,
+            EntryType::Symlink => PackageFileType::Symlink,
+            // Other special files are never installed
+            _ => continue,
+        };
+
+        files.push(PackageFileEntry {
+            path,
+            size: if file_type == PackageFileType::File {
+                header.size()?
+            } else {
+                0
+            },
+            file_type,
+            mode: header.mode()? & 0o7777,
+            link_target: entry.link_name()?.map(std::borrow::Cow::into_owned),
+        });
+    }
+
+    if !has_manifest {
+        return Err(PackageError::InvalidFormat {
+            message: "missing manifest.toml in package".to_string(),
+        }
+        .into());
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
 /// Extract entries from a tar archive with security checks
 fn extract_archive_entries<R: std::io::Read>(
     archive: &mut Archive<R>,
@@ -423,20 +585,22 @@ fn extract_archive_entries<R: std::io::Read>(
 }
 
 /// Recursively add directory contents to tar
+///
+/// Entries are added sorted by name so identical trees produce identical
+/// archives regardless of filesystem iteration order.
 fn add_dir_to_tar<W: std::io::Write>(
     builder: &mut tar::Builder<W>,
     src: &Path,
     prefix: &Path,
 ) -> Result<(), Error> {
-    let entries = std::fs::read_dir(src).map_err(|e| StorageError::IoError {
-        message: e.to_string(),
-    })?;
-
-    for entry in entries {
-        let entry = entry.map_err(|e| StorageError::IoError {
+    let mut entries = std::fs::read_dir(src)
+        .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+        .map_err(|e| StorageError::IoError {
             message: e.to_string(),
         })?;
+    entries.sort_by_key(std::fs::DirEntry::file_name);
 
+    for entry in entries {
         let path = entry.path();
         let name = entry.file_name();
         let tar_path = prefix.join(&name);
@@ -473,7 +637,7 @@ fn add_dir_to_tar<W: std::io::Write>(
             })?;
 
             let mut header = tar::Header::new_gnu();
-            header.set_metadata(&metadata);
+            header.set_metadata_in_mode(&metadata, tar::HeaderMode::Deterministic);
             header.set_entry_type(tar::EntryType::Symlink);
 
             builder
@@ -486,3 +650,178 @@ fn add_dir_to_tar<W: std::io::Write>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sps2_hash::Hash;
+    use sps2_types::{Arch, Manifest, Version};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn list_package_files_reads_entries_without_extracting() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        tokio::fs::create_dir_all(src.join("bin")).await.unwrap();
+
+        let manifest = Manifest::new("foo".to_string(), &Version::new(1, 0, 0), 1, &Arch::Arm64);
+        tokio::fs::write(src.join("manifest.toml"), manifest.to_toml().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(src.join("sbom.spdx.json"), b"{}")
+            .await
+            .unwrap();
+        tokio::fs::write(src.join("bin/foo"), b"#!/bin/sh\n")
+            .await
+            .unwrap();
+        std::os::unix::fs::symlink("foo", src.join("bin/foo-alias")).unwrap();
+
+        let sp_file = temp.path().join("foo.sp");
+        create_package(&src, &sp_file).await.unwrap();
+
+        let files = list_package_files(&sp_file).await.unwrap();
+        let paths: Vec<_> = files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("bin"),
+                PathBuf::from("bin/foo"),
+                PathBuf::from("bin/foo-alias"),
+            ]
+        );
+
+        assert_eq!(files[0].file_type, PackageFileType::Directory);
+        assert_eq!(files[1].file_type, PackageFileType::File);
+        assert_eq!(files[1].size, 10);
+        assert_eq!(files[2].file_type, PackageFileType::Symlink);
+        assert_eq!(files[2].link_target, Some(PathBuf::from("foo")));
+    }
+
+    #[tokio::test]
+    async fn list_package_files_strips_the_live_prefix() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        let live = src.join("opt/pm/live");
+        tokio::fs::create_dir_all(live.join("bin")).await.unwrap();
+
+        let manifest = Manifest::new("foo".to_string(), &Version::new(1, 0, 0), 1, &Arch::Arm64);
+        tokio::fs::write(src.join("manifest.toml"), manifest.to_toml().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(live.join("bin/foo"), b"#!/bin/sh\n")
+            .await
+            .unwrap();
+
+        let sp_file = temp.path().join("foo.sp");
+        create_package(&src, &sp_file).await.unwrap();
+
+        let files = list_package_files(&sp_file).await.unwrap();
+        let paths: Vec<_> = files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(paths, [PathBuf::from("bin"), PathBuf::from("bin/foo")]);
+        assert_eq!(files[1].size, 10);
+    }
+
+    #[tokio::test]
+    async fn create_package_is_reproducible() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        tokio::fs::create_dir_all(src.join("share/doc"))
+            .await
+            .unwrap();
+
+        let manifest = Manifest::new("foo".to_string(), &Version::new(1, 0, 0), 1, &Arch::Arm64);
+        tokio::fs::write(src.join("manifest.toml"), manifest.to_toml().unwrap())
+            .await
+            .unwrap();
+        for name in ["c.txt", "a.txt", "b.txt"] {
+            tokio::fs::write(src.join("share/doc").join(name), name)
+                .await
+                .unwrap();
+        }
+        std::os::unix::fs::symlink("a.txt", src.join("share/doc/link")).unwrap();
+
+        let first = temp.path().join("first.sp");
+        create_package(&src, &first).await.unwrap();
+
+        // Touch a file so only its mtime differs between the two runs
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(3600);
+        std::fs::File::options()
+            .write(true)
+            .open(src.join("share/doc/b.txt"))
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+
+        let second = temp.path().join("second.sp");
+        create_package(&src, &second).await.unwrap();
+
+        assert_eq!(
+            Hash::blake3_hash_file(&first).await.unwrap(),
+            Hash::blake3_hash_file(&second).await.unwrap()
+        );
+
+        let files = list_package_files(&first).await.unwrap();
+        let paths: Vec<_> = files.iter().map(|f| f.path.clone()).collect();
+        let mut sorted = paths.clone();
+        sorted.sort();
+        assert_eq!(paths, sorted);
+    }
+
+    #[tokio::test]
+    async fn list_package_files_requires_manifest() {
+        let temp = TempDir::new().unwrap();
+        let tar_path = temp.path().join("bad.sp");
+
+        let file = std::fs::File::create(&tar_path).unwrap();
+        let mut builder = tar::Builder::new(file);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(3);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "bin/foo", &b"abc"[..])
+            .unwrap();
+        builder.finish().unwrap();
+
+        assert!(list_package_files(&tar_path).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn list_package_files_rejects_traversal_in_zstd_package() {
+        use async_compression::tokio::write::ZstdEncoder;
+
+        let temp = TempDir::new().unwrap();
+        let mut builder = tar::Builder::new(Vec::new());
+        let manifest = Manifest::new("foo".to_string(), &Version::new(1, 0, 0), 1, &Arch::Arm64);
+        let manifest = manifest.to_toml().unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_size(manifest.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "manifest.toml", manifest.as_bytes())
+            .unwrap();
+        // The tar writer refuses `..`, so set the raw name
+        let mut header = tar::Header::new_gnu();
+        header.as_old_mut().name[..9].copy_from_slice(b"../escape");
+        header.set_size(3);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, &b"abc"[..]).unwrap();
+        let tar_bytes = builder.into_inner().unwrap();
+
+        let mut encoder = ZstdEncoder::new(Vec::new());
+        encoder.write_all(&tar_bytes).await.unwrap();
+        encoder.shutdown().await.unwrap();
+        let sp_file = temp.path().join("escape.sp");
+        tokio::fs::write(&sp_file, encoder.into_inner())
+            .await
+            .unwrap();
+
+        let err = list_package_files(&sp_file).await.unwrap_err();
+        assert!(
+            err.to_string().contains("path traversal"),
+            "unexpected error: {err}"
+        );
+    }
+}
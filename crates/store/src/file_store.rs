@@ -2,12 +2,17 @@
 //!
 //! This module provides functionality for storing individual files
 //! by their content hash, enabling deduplication across packages.
+//! Objects are kept loose under `objects/` or, after compaction, in the
+//! packs under `packs/`; lookups by hash see both.
 
+use crate::pack::{CompactionReport, PackLocation, PackSet};
 use sps2_errors::{Error, StorageError};
 use sps2_hash::{calculate_file_storage_path, FileHashResult, FileHasher, FileHasherConfig, Hash};
 use sps2_platform::core::PlatformContext;
 use sps2_platform::PlatformManager;
+use sps2_types::is_installed_entry;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::fs;
 use uuid::Uuid;
 
@@ -31,6 +36,8 @@ pub struct FileStore {
     objects_path: PathBuf,
     /// File hasher for computing file hashes
     file_hasher: FileHasher,
+    /// Packed objects (/opt/pm/store/packs)
+    packs: Arc<PackSet>,
 }
 
 impl FileStore {
@@ -43,6 +50,7 @@ impl FileStore {
         Self {
             objects_path,
             file_hasher,
+            packs: Arc::new(PackSet::new(store_base_path)),
         }
     }
 
@@ -93,13 +101,93 @@ impl FileStore {
         self.objects_path.join(prefix).join(full_hash)
     }
 
-    /// Check if a file exists in the store
+    /// Check if a file exists in the store, loose or packed
     pub async fn has_file(&self, hash: &Hash) -> bool {
+        self.has_loose_file(hash).await || self.packed_location(hash).await.is_some()
+    }
+
+    async fn has_loose_file(&self, hash: &Hash) -> bool {
         let path = self.file_path(hash);
         let (platform, ctx) = Self::create_platform_context();
         platform.filesystem().exists(&ctx, &path).await
     }
 
+    /// Pack holding `hash`; unreadable pack indexes count as not packed
+    async fn packed_location(&self, hash: &Hash) -> Option<PackLocation> {
+        self.packs.locate(hash).await.ok().flatten()
+    }
+
+    /// Read a packed object and check it against its hash
+    async fn read_packed(&self, hash: &Hash, location: &PackLocation) -> Result<Vec<u8>, Error> {
+        let data = self.packs.read(location).await?;
+        let actual = Hash::from_data_with_algorithm(&data, hash.algorithm());
+        if actual != *hash {
+            return Err(StorageError::CorruptedData {
+                message: format!(
+                    "packed object {} in pack {} has hash {}",
+                    hash.to_hex(),
+                    location.name,
+                    actual.to_hex()
+                ),
+            }
+            .into());
+        }
+        Ok(data)
+    }
+
+    /// Make sure the object exists as a loose file and return its path
+    ///
+    /// Packed objects are re-inflated into `objects/` after verifying their
+    /// contents, so callers that need a real file (cloning into the live
+    /// prefix, healing) work the same whether or not the store is compacted.
+    ///
+    /// # Errors
+    /// Returns an error if the object is in neither place or re-inflating it
+    /// fails
+    pub async fn inflate(&self, hash: &Hash) -> Result<PathBuf, Error> {
+        let dest_path = self.file_path(hash);
+        if self.has_loose_file(hash).await {
+            return Ok(dest_path);
+        }
+
+        let Some(location) = self.packed_location(hash).await else {
+            return Err(StorageError::PathNotFound {
+                path: dest_path.display().to_string(),
+            }
+            .into());
+        };
+        let data = self.read_packed(hash, &location).await?;
+        self.write_loose(hash, &data, location.object.mode).await?;
+        Ok(dest_path)
+    }
+
+    /// Write an object's contents to its loose path
+    async fn write_loose(&self, hash: &Hash, data: &[u8], mode: u32) -> Result<(), Error> {
+        let dest_path = self.file_path(hash);
+        let parent_dir = dest_path.parent().ok_or_else(|| StorageError::IoError {
+            message: "failed to get parent directory".to_string(),
+        })?;
+        fs::create_dir_all(parent_dir).await?;
+
+        let temp_path = parent_dir.join(format!("{}.tmp", Uuid::new_v4()));
+        fs::write(&temp_path, data).await?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&temp_path, std::fs::Permissions::from_mode(mode)).await?;
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+        if let Err(e) = fs::rename(&temp_path, &dest_path).await {
+            let _ = fs::remove_file(&temp_path).await;
+            return Err(StorageError::IoError {
+                message: format!("failed to re-inflate packed object: {e}"),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
     /// Store a file by its content hash
     ///
     /// Returns true if the file was newly stored, false if it already existed
@@ -110,7 +198,7 @@ impl FileStore {
         let dest_path = self.file_path(hash);
         let (platform, ctx) = Self::create_platform_context();
 
-        if platform.filesystem().exists(&ctx, &dest_path).await {
+        if self.has_file(hash).await {
             return Ok(false);
         }
 
@@ -170,6 +258,47 @@ impl FileStore {
         }
     }
 
+    /// Move a file staged next to the store into place as object `hash`
+    ///
+    /// The staged file must already have the contents of `hash`; it is
+    /// dropped if the object exists. Returns true if the object was newly
+    /// stored.
+    ///
+    /// # Errors
+    /// Returns an error if the staged file cannot be moved into the store
+    pub(crate) async fn adopt_object(&self, staged: &Path, hash: &Hash) -> Result<bool, Error> {
+        if self.has_file(hash).await {
+            fs::remove_file(staged).await?;
+            return Ok(false);
+        }
+
+        let dest_path = self.file_path(hash);
+        if let Some(parent) = dest_path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        // Read-only like objects stored by copying
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = fs::metadata(staged).await?.permissions();
+            perms.set_mode(perms.mode() & 0o555);
+            fs::set_permissions(staged, perms).await?;
+        }
+
+        if let Err(e) = fs::rename(staged, &dest_path).await {
+            let _ = fs::remove_file(staged).await;
+            if self.has_loose_file(hash).await {
+                return Ok(false);
+            }
+            return Err(StorageError::IoError {
+                message: format!("failed to move staged object to store: {e}"),
+            }
+            .into());
+        }
+        Ok(true)
+    }
+
     /// Store a file and compute its hash
     ///
     /// # Errors
@@ -189,16 +318,9 @@ impl FileStore {
     /// # Errors
     /// Returns an error if the file doesn't exist or linking fails
     pub async fn link_file(&self, hash: &Hash, dest_path: &Path) -> Result<(), Error> {
-        let source_path = self.file_path(hash);
+        let source_path = self.inflate(hash).await?;
         let (platform, ctx) = Self::create_platform_context();
 
-        if !platform.filesystem().exists(&ctx, &source_path).await {
-            return Err(StorageError::PathNotFound {
-                path: source_path.display().to_string(),
-            }
-            .into());
-        }
-
         // Ensure parent directory exists
         if let Some(parent) = dest_path.parent() {
             platform.filesystem().create_dir_all(&ctx, parent).await?;
@@ -234,19 +356,7 @@ impl FileStore {
         let mut filtered_results = Vec::new();
 
         for result in hash_results {
-            // Skip manifest and sbom files - they should only exist in package metadata
-            if result.relative_path == "manifest.toml"
-                || result.relative_path == "sbom.spdx.json"
-                || result.relative_path == "sbom.cdx.json"
-            {
-                continue;
-            }
-
-            // Skip opt/pm/live directory entries themselves
-            if result.relative_path == "opt"
-                || result.relative_path == "opt/pm"
-                || result.relative_path == "opt/pm/live"
-            {
+            if !is_installed_entry(&result.relative_path) {
                 continue;
             }
 
@@ -323,7 +433,7 @@ impl FileStore {
         Ok(())
     }
 
-    /// Remove a file from the store
+    /// Remove a file from the store, loose and packed
     ///
     /// # Errors
     /// Returns an error if file removal fails
@@ -334,7 +444,7 @@ impl FileStore {
         if platform.filesystem().exists(&ctx, &path).await {
             platform.filesystem().remove_file(&ctx, &path).await?;
         }
-        Ok(())
+        self.packs.remove(hash).await
     }
 
     /// Get the size of a stored file
@@ -345,6 +455,11 @@ impl FileStore {
         let path = self.file_path(hash);
         let (platform, ctx) = Self::create_platform_context();
 
+        if !self.has_loose_file(hash).await {
+            if let Some(location) = self.packed_location(hash).await {
+                return Ok(location.object.len);
+            }
+        }
         platform.filesystem().size(&ctx, &path).await.map_err(|_| {
             StorageError::PathNotFound {
                 path: path.display().to_string(),
@@ -358,16 +473,10 @@ impl FileStore {
     /// # Errors
     /// Returns an error if the file doesn't exist or hashing fails
     pub async fn verify_file(&self, hash: &Hash) -> Result<bool, Error> {
-        let path = self.file_path(hash);
-        let (platform, ctx) = Self::create_platform_context();
-
-        if !platform.filesystem().exists(&ctx, &path).await {
-            return Ok(false);
-        }
-
-        // Use the same algorithm as the expected hash for verification
-        let actual_hash = Hash::hash_file_with_algorithm(&path, hash.algorithm()).await?;
-        Ok(actual_hash == *hash)
+        Ok(matches!(
+            self.verify_file_detailed(hash).await?,
+            FileVerificationResult::Valid
+        ))
     }
 
     /// Verify a stored file and return detailed result
@@ -382,7 +491,26 @@ impl FileStore {
         let (platform, ctx) = Self::create_platform_context();
 
         if !platform.filesystem().exists(&ctx, &path).await {
-            return Ok(FileVerificationResult::Missing);
+            let Some(location) = self.packed_location(hash).await else {
+                return Ok(FileVerificationResult::Missing);
+            };
+            let data = match self.packs.read(&location).await {
+                Ok(data) => data,
+                Err(e) => {
+                    return Ok(FileVerificationResult::Error {
+                        message: e.to_string(),
+                    })
+                }
+            };
+            let actual_hash = Hash::from_data_with_algorithm(&data, hash.algorithm());
+            return Ok(if actual_hash == *hash {
+                FileVerificationResult::Valid
+            } else {
+                FileVerificationResult::HashMismatch {
+                    expected: hash.clone(),
+                    actual: actual_hash,
+                }
+            });
         }
 
         // Use the same algorithm as the expected hash for verification
@@ -403,6 +531,127 @@ impl FileStore {
         }
     }
 
+    /// Move the small loose objects of one package into a pack
+    ///
+    /// `name` names the pack (the package hash). Objects larger than
+    /// `max_object_size`, missing, or already packed elsewhere are not added;
+    /// loose copies of already packed objects are dropped again. Nothing is
+    /// packed when fewer than `min_objects` qualify or a pack called `name`
+    /// already exists. Loose objects are only removed once the pack and its
+    /// index are on disk.
+    ///
+    /// Objects with more than one hardlink are left loose, as are their
+    /// re-inflated copies: removing them would not free the inode, would
+    /// store the bytes twice and would detach the live prefix from the store.
+    ///
+    /// # Errors
+    /// Returns an error if the store was not migrated to the packed layout,
+    /// or reading the loose objects or writing the pack fails
+    pub async fn compact_objects(
+        &self,
+        name: &str,
+        hashes: &[Hash],
+        options: &crate::CompactionOptions,
+    ) -> Result<CompactionReport, Error> {
+        if !self.packs.is_enabled().await {
+            return Err(StorageError::MigrationRequired {
+                message: "migrate the store to the packed layout before compacting".to_string(),
+            }
+            .into());
+        }
+
+        let mut report = CompactionReport::default();
+        let mut candidates = Vec::new();
+
+        for hash in hashes {
+            let path = self.file_path(hash);
+            let Ok(metadata) = fs::symlink_metadata(&path).await else {
+                continue;
+            };
+            #[cfg(unix)]
+            let linked = {
+                use std::os::unix::fs::MetadataExt;
+                metadata.nlink() > 1
+            };
+            #[cfg(not(unix))]
+            let linked = false;
+            if linked {
+                report.objects_in_use += 1;
+                continue;
+            }
+            if self.packed_location(hash).await.is_some() {
+                // Re-inflated earlier; the pack still has it
+                fs::remove_file(&path).await?;
+                report.loose_copies_removed += 1;
+                continue;
+            }
+            if metadata.is_file() && metadata.len() <= options.max_object_size {
+                #[cfg(unix)]
+                let mode = {
+                    use std::os::unix::fs::PermissionsExt;
+                    metadata.permissions().mode() & 0o7777
+                };
+                #[cfg(not(unix))]
+                let mode = 0o444;
+                candidates.push((hash.clone(), path, mode));
+            }
+        }
+
+        if candidates.is_empty() {
+            return Ok(report);
+        }
+        if candidates.len() < options.min_objects || self.packs.contains_pack(name).await {
+            report.packages_skipped = 1;
+            return Ok(report);
+        }
+
+        report.bytes_packed = self.packs.write_pack(name, &candidates).await?;
+        for (_, path, _) in &candidates {
+            let _ = fs::remove_file(path).await;
+        }
+        report.packs_written = 1;
+        report.objects_packed = candidates.len();
+        Ok(report)
+    }
+
+    /// Whether the store was migrated to the packed layout
+    pub async fn packs_enabled(&self) -> bool {
+        self.packs.is_enabled().await
+    }
+
+    /// Migrate the store to the packed layout, allowing compaction
+    ///
+    /// # Errors
+    /// Returns an error if the layout marker cannot be written
+    pub async fn enable_packs(&self) -> Result<(), Error> {
+        self.packs.enable().await
+    }
+
+    /// Re-inflate every packed object and delete all packs
+    ///
+    /// Returns the number of objects re-inflated. This reverts compaction,
+    /// leaving only loose objects, and migrates the store back to the
+    /// loose layout.
+    ///
+    /// # Errors
+    /// Returns an error if a packed object cannot be read, fails
+    /// verification, or cannot be written back
+    pub async fn unpack_all(&self) -> Result<usize, Error> {
+        let mut inflated = 0;
+        for name in self.packs.pack_names().await? {
+            for (hash, location) in self.packs.objects(&name).await? {
+                if !self.has_loose_file(&hash).await {
+                    let data = self.read_packed(&hash, &location).await?;
+                    self.write_loose(&hash, &data, location.object.mode).await?;
+                    inflated += 1;
+                }
+            }
+            self.packs.remove_pack(&name).await?;
+        }
+        self.packs.disable().await?;
+        Ok(inflated)
+    }
+
     /// Clean up empty prefix directories
     ///
     /// # Errors
@@ -501,4 +750,183 @@ mod tests {
         assert!(dest_dir.join("file1.txt").exists());
         assert!(dest_dir.join("subdir/file2.txt").exists());
     }
+
+    async fn store_small_files(store: &FileStore, dir: &Path, count: usize) -> Vec<Hash> {
+        let mut hashes = Vec::new();
+        for i in 0..count {
+            let path = dir.join(format!("small-{i}"));
+            fs::write(&path, format!("content {i}")).await.unwrap();
+            hashes.push(store.store_file_with_hash(&path).await.unwrap().0);
+        }
+        hashes
+    }
+
+    #[tokio::test]
+    async fn packed_objects_link_and_verify_like_loose_ones() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = FileStore::new(temp_dir.path());
+        let hashes = store_small_files(&store, temp_dir.path(), 4).await;
+
+        let options = crate::CompactionOptions {
+            max_object_size: 1024,
+            min_objects: 2,
+            ..crate::CompactionOptions::default()
+        };
+        store.enable_packs().await.unwrap();
+        let report = store
+            .compact_objects("pkg", &hashes, &options)
+            .await
+            .unwrap();
+        assert_eq!(report.objects_packed, 4);
+        assert_eq!(report.packs_written, 1);
+        for hash in &hashes {
+            assert!(!store.file_path(hash).exists());
+            assert!(store.has_file(hash).await);
+            assert!(store.verify_file(hash).await.unwrap());
+        }
+        assert_eq!(
+            store.file_size(&hashes[0]).await.unwrap(),
+            "content 0".len() as u64
+        );
+
+        // Linking re-inflates the object; compacting again drops the copy
+        let dest = temp_dir.path().join("live/small-1");
+        store.link_file(&hashes[1], &dest).await.unwrap();
+        assert_eq!(fs::read_to_string(&dest).await.unwrap(), "content 1");
+        assert!(store.file_path(&hashes[1]).exists());
+        let report = store
+            .compact_objects("pkg", &hashes, &options)
+            .await
+            .unwrap();
+        assert_eq!(report.loose_copies_removed, 1);
+        assert_eq!(report.packs_written, 0);
+        assert!(!store.file_path(&hashes[1]).exists());
+
+        // Storing identical content again does not create a loose duplicate
+        let (_, newly_stored) = store
+            .store_file_with_hash(&temp_dir.path().join("small-2"))
+            .await
+            .unwrap();
+        assert!(!newly_stored);
+    }
+
+    #[tokio::test]
+    async fn ingesting_after_compaction_does_not_reread_pack_indexes() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = FileStore::new(temp_dir.path());
+        let hashes = store_small_files(&store, temp_dir.path(), 2).await;
+        let options = crate::CompactionOptions {
+            max_object_size: 1024,
+            min_objects: 1,
+            ..crate::CompactionOptions::default()
+        };
+        store.enable_packs().await.unwrap();
+        store
+            .compact_objects("pkg", &hashes, &options)
+            .await
+            .unwrap();
+        assert!(store.has_file(&hashes[0]).await);
+        let reads = store.packs.index_reads();
+
+        for i in 0..5 {
+            let path = temp_dir.path().join(format!("new-{i}"));
+            fs::write(&path, format!("new content {i}")).await.unwrap();
+            let (hash, newly_stored) = store.store_file_with_hash(&path).await.unwrap();
+            assert!(newly_stored);
+            assert!(store.has_file(&hash).await);
+        }
+        assert_eq!(store.packs.index_reads(), reads);
+    }
+
+    #[tokio::test]
+    async fn corrupted_pack_is_detected_and_never_inflated() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = FileStore::new(temp_dir.path());
+        let hashes = store_small_files(&store, temp_dir.path(), 2).await;
+        let options = crate::CompactionOptions {
+            max_object_size: 1024,
+            min_objects: 1,
+            ..crate::CompactionOptions::default()
+        };
+        store.enable_packs().await.unwrap();
+        store
+            .compact_objects("pkg", &hashes, &options)
+            .await
+            .unwrap();
+
+        let pack = temp_dir.path().join("packs/pkg.pack");
+        let mut data = fs::read(&pack).await.unwrap();
+        data[0] ^= 0xff;
+        fs::write(&pack, data).await.unwrap();
+
+        assert!(matches!(
+            store.verify_file_detailed(&hashes[0]).await.unwrap(),
+            FileVerificationResult::HashMismatch { .. }
+        ));
+        assert!(store.verify_file(&hashes[1]).await.unwrap());
+        assert!(store.inflate(&hashes[0]).await.is_err());
+        assert!(!store.file_path(&hashes[0]).exists());
+    }
+
+    #[tokio::test]
+    async fn mostly_dead_packs_are_rewritten() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = FileStore::new(temp_dir.path());
+        let hashes = store_small_files(&store, temp_dir.path(), 4).await;
+        let options = crate::CompactionOptions {
+            max_object_size: 1024,
+            min_objects: 1,
+            ..crate::CompactionOptions::default()
+        };
+        store.enable_packs().await.unwrap();
+        store
+            .compact_objects("pkg", &hashes, &options)
+            .await
+            .unwrap();
+        let pack = temp_dir.path().join("packs/pkg.pack");
+        let object_len = "content 0".len() as u64;
+        assert_eq!(fs::metadata(&pack).await.unwrap().len(), 4 * object_len);
+
+        // Half dead is kept as is
+        for hash in &hashes[..2] {
+            store.remove_file(hash).await.unwrap();
+        }
+        assert_eq!(fs::metadata(&pack).await.unwrap().len(), 4 * object_len);
+
+        // Past the threshold only the live object is left
+        store.remove_file(&hashes[3]).await.unwrap();
+        assert_eq!(fs::metadata(&pack).await.unwrap().len(), object_len);
+        assert!(store.verify_file(&hashes[2]).await.unwrap());
+        let dest = temp_dir.path().join("live/small-2");
+        store.link_file(&hashes[2], &dest).await.unwrap();
+        assert_eq!(fs::read_to_string(&dest).await.unwrap(), "content 2");
+    }
+
+    #[tokio::test]
+    async fn removing_and_unpacking_objects() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = FileStore::new(temp_dir.path());
+        let hashes = store_small_files(&store, temp_dir.path(), 3).await;
+        let options = crate::CompactionOptions {
+            max_object_size: 1024,
+            min_objects: 1,
+            ..crate::CompactionOptions::default()
+        };
+        store.enable_packs().await.unwrap();
+        store
+            .compact_objects("pkg", &hashes, &options)
+            .await
+            .unwrap();
+
+        store.remove_file(&hashes[0]).await.unwrap();
+        assert!(!store.has_file(&hashes[0]).await);
+
+        assert_eq!(store.unpack_all().await.unwrap(), 2);
+        assert!(!temp_dir.path().join("packs/pkg.idx").exists());
+        for hash in &hashes[1..] {
+            assert!(store.file_path(hash).exists());
+            assert!(store.verify_file(hash).await.unwrap());
+        }
+        assert!(!store.has_file(&hashes[0]).await);
+    }
 }
@@ -0,0 +1,45 @@
+//! Garbage collection planning for the package store
+
+use serde::{Deserialize, Serialize};
+
+use sps2_hash::Hash;
+
+/// A stored package or file object that garbage collection would remove
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GcCandidate {
+    /// Package or file object hash (hex)
+    pub hash: String,
+    /// On-disk size in bytes
+    pub size: u64,
+}
+
+/// Store content whose refcount in the state database is zero
+#[derive(Debug, Clone, Default)]
+pub struct Unreferenced {
+    /// Packages no state references
+    pub packages: Vec<Hash>,
+    /// File objects no installed package references
+    pub file_objects: Vec<Hash>,
+}
+
+/// Preview of a garbage collection run
+///
+/// Lists exactly which packages and file objects would be removed and how
+/// much space that would free, without deleting anything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GcPlan {
+    /// Packages no state references
+    pub packages: Vec<GcCandidate>,
+    /// File objects no installed package references
+    pub file_objects: Vec<GcCandidate>,
+    /// Total bytes freed by removing `packages` and `file_objects`
+    pub bytes_freed: u64,
+}
+
+impl GcPlan {
+    /// Whether garbage collection would remove nothing
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty() && self.file_objects.is_empty()
+    }
+}
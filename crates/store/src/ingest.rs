@@ -0,0 +1,393 @@
+//! Streaming package ingestion
+//!
+//! [`PackageStore::add_package`] extracts a package to a temporary directory
+//! and then copies every file into the store, writing each file twice.
+//! Streaming ingestion reads the archive once: regular files are hashed while
+//! they are written to a staging area inside the store and then renamed into
+//! place as objects. Only package metadata, directories and symlinks are
+//! unpacked into a small staging tree. The package hash, `files.json` and the
+//! stored objects are the same as with the two-step path.
+
+use crate::{PackageStore, StoredPackage};
+use async_compression::tokio::bufread::ZstdDecoder as AsyncZstdReader;
+use sps2_errors::{Error, PackageError, StorageError};
+use sps2_hash::{FileHashResult, FileHasher, FileHasherConfig, Hash, HashAlgorithm, TreeEntry};
+use sps2_types::is_installed_entry;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use tar::Archive;
+use tokio::io::{AsyncReadExt, BufReader};
+use tokio_util::io::SyncIoBridge;
+use uuid::Uuid;
+
+/// Leading bytes of a zstd frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// File type bits of a regular file in `st_mode`
+const S_IFREG: u32 = 0o100_000;
+
+/// A regular file streamed out of the archive
+struct StreamedFile {
+    /// Path relative to the package root
+    relative_path: String,
+    hash: Hash,
+    size: u64,
+    /// `st_mode` the file would have had if extracted
+    mode: u32,
+    /// Staged contents; `None` for hard links to an earlier entry
+    staged: Option<PathBuf>,
+}
+
+impl StreamedFile {
+    fn to_result(&self) -> FileHashResult {
+        FileHashResult {
+            relative_path: self.relative_path.clone(),
+            hash: self.hash.clone(),
+            size: self.size,
+            is_directory: false,
+            is_symlink: false,
+            #[cfg(unix)]
+            mode: Some(self.mode),
+        }
+    }
+}
+
+impl PackageStore {
+    /// Add a package to the store by streaming its archive into the store
+    ///
+    /// Produces the same stored package as [`Self::add_package`] without
+    /// extracting the package first, so large files are written once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Package format is incompatible
+    /// - The archive cannot be read or contains path traversal
+    /// - The package is missing manifest.toml
+    /// - Writing objects or package metadata fails
+    pub async fn add_package_streaming(&self, sp_file: &Path) -> Result<StoredPackage, Error> {
+        self.format_validator
+            .validate_before_storage(sp_file)
+            .await?;
+
+        // Staging lives inside the store so objects can be renamed into place
+        tokio::fs::create_dir_all(&self.base_path).await?;
+        let work = tempfile::Builder::new()
+            .prefix(".ingest-")
+            .tempdir_in(&self.base_path)
+            .map_err(|e| StorageError::IoError {
+                message: format!("failed to create ingest directory: {e}"),
+            })?;
+        let tree = work.path().join("tree");
+        let incoming = work.path().join("objects");
+        tokio::fs::create_dir_all(&tree).await?;
+        tokio::fs::create_dir_all(&incoming).await?;
+
+        let reader = open_archive(sp_file).await?;
+        let streamed = {
+            let tree = tree.clone();
+            tokio::task::spawn_blocking(move || {
+                stream_entries(&mut Archive::new(reader), &tree, &incoming)
+            })
+            .await
+            .map_err(|e| Error::internal(format!("streaming ingest task failed: {e}")))??
+        };
+
+        if !tokio::fs::try_exists(tree.join("manifest.toml")).await? {
+            return Err(PackageError::InvalidFormat {
+                message: "missing manifest.toml in package".to_string(),
+            }
+            .into());
+        }
+
+        // Entries materialized in the tree are listed like `store_directory` does
+        let mut file_results = FileHasher::new(FileHasherConfig::default())
+            .hash_directory(&tree)
+            .await?;
+        let mut entries = BTreeMap::new();
+        for result in &file_results {
+            entries.insert(
+                result.relative_path.clone(),
+                tree_entry(&tree, result).await?,
+            );
+        }
+        for file in &streamed {
+            entries.insert(
+                file.relative_path.clone(),
+                TreeEntry::File {
+                    mode: file.mode,
+                    hash: file.hash.clone(),
+                },
+            );
+            file_results.push(file.to_result());
+        }
+        let package_hash = Hash::hash_tree(&entries, HashAlgorithm::default());
+
+        let package_path = self.base_path.join("packages").join(package_hash.to_hex());
+        // Objects are adopted even for a known package so that re-adding it
+        // restores any objects that went missing from the store
+        self.file_store.initialize().await?;
+        for file in &streamed {
+            if let Some(staged) = &file.staged {
+                self.file_store.adopt_object(staged, &file.hash).await?;
+            }
+        }
+        if tokio::fs::try_exists(&package_path).await? {
+            return StoredPackage::load(&package_path).await;
+        }
+
+        file_results.retain(|result| is_installed_entry(&result.relative_path));
+        file_results.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+        Self::write_package_dir(&package_path, &tree, &file_results).await
+    }
+}
+
+/// Whether a package archive is zstd-compressed, judged by its magic bytes
+pub(crate) async fn is_zstd_archive(sp_file: &Path) -> Result<bool, Error> {
+    let mut magic = [0u8; 4];
+    let mut file = tokio::fs::File::open(sp_file).await?;
+    Ok(file.read_exact(&mut magic).await.is_ok() && magic == ZSTD_MAGIC)
+}
+
+/// Open a package archive as a blocking tar stream, decompressing zstd
+async fn open_archive(sp_file: &Path) -> Result<Box<dyn Read + Send>, Error> {
+    let is_zstd = is_zstd_archive(sp_file).await?;
+
+    let file = tokio::fs::File::open(sp_file).await?;
+    if is_zstd {
+        let decoder = AsyncZstdReader::new(BufReader::new(file));
+        Ok(Box::new(SyncIoBridge::new(decoder)))
+    } else {
+        Ok(Box::new(file.into_std().await))
+    }
+}
+
+/// Describe an entry of the staging tree for the package hash
+async fn tree_entry(tree: &Path, result: &FileHashResult) -> Result<TreeEntry, Error> {
+    #[cfg(unix)]
+    let mode = result.mode.unwrap_or_default();
+    #[cfg(not(unix))]
+    let mode = 0;
+
+    Ok(if result.is_symlink {
+        let target = tokio::fs::read_link(tree.join(&result.relative_path)).await?;
+        TreeEntry::Symlink {
+            mode,
+            target: target.to_string_lossy().to_string(),
+        }
+    } else if result.is_directory {
+        TreeEntry::Other { mode }
+    } else {
+        TreeEntry::File {
+            mode,
+            hash: result.hash.clone(),
+        }
+    })
+}
+
+/// Stream archive entries: installable regular files into `incoming`,
+/// everything else into `tree`
+fn stream_entries<R: Read>(
+    archive: &mut Archive<R>,
+    tree: &Path,
+    incoming: &Path,
+) -> Result<Vec<StreamedFile>, Error> {
+    archive.set_preserve_permissions(true);
+    archive.set_preserve_mtime(true);
+    archive.set_unpack_xattrs(false);
+
+    let mut files: Vec<StreamedFile> = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        if path.components().any(|c| c == Component::ParentDir) {
+            return Err(PackageError::InvalidFormat {
+                message: "archive contains path traversal".to_string(),
+            }
+            .into());
+        }
+
+        // Same normalization as unpacking: only normal components count
+        let relative: PathBuf = path
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .collect();
+        let relative_path = relative.to_string_lossy().to_string();
+        let entry_type = entry.header().entry_type();
+
+        if relative_path.is_empty() || !is_installed_entry(&relative_path) {
+            entry.unpack_in(tree)?;
+        } else if entry_type.is_file() || entry_type.is_contiguous() {
+            if let Some(parent) = relative.parent() {
+                std::fs::create_dir_all(tree.join(parent))?;
+            }
+            let mode = S_IFREG | (entry.header().mode()? & 0o7777);
+            let staged = incoming.join(format!("{}.tmp", Uuid::new_v4()));
+            let mut writer = std::io::BufWriter::new(std::fs::File::create(&staged)?);
+            let (hash, size) = Hash::hash_and_copy_blocking(&mut entry, &mut writer)?;
+            drop(writer);
+            set_staged_mode(&staged, mode)?;
+            files.push(StreamedFile {
+                relative_path,
+                hash,
+                size,
+                mode,
+                staged: Some(staged),
+            });
+        } else if entry_type.is_hard_link() {
+            // Hard links share the inode (and mode) of an earlier entry
+            let target = entry.link_name()?.unwrap_or_default().into_owned();
+            let target: PathBuf = target
+                .components()
+                .filter(|c| matches!(c, Component::Normal(_)))
+                .collect();
+            let target = target.to_string_lossy();
+            let Some(linked) = files.iter().find(|f| f.relative_path == target) else {
+                return Err(PackageError::InvalidFormat {
+                    message: format!("hard link {relative_path} to unknown entry {target}"),
+                }
+                .into());
+            };
+            files.push(StreamedFile {
+                relative_path,
+                hash: linked.hash.clone(),
+                size: linked.size,
+                mode: linked.mode,
+                staged: None,
+            });
+        } else {
+            entry.unpack_in(tree)?;
+        }
+    }
+    Ok(files)
+}
+
+/// Give a staged object the permissions extraction would have set
+fn set_staged_mode(staged: &Path, mode: u32) -> Result<(), Error> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(staged, std::fs::Permissions::from_mode(mode & 0o7777))?;
+    }
+    #[cfg(not(unix))]
+    let _ = (staged, mode);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_package, manifest_io};
+    use sps2_types::{Arch, Manifest, Version};
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    /// Objects under `objects/` by path relative to the store, with their mode
+    fn objects(store_root: &Path) -> BTreeMap<String, u32> {
+        fn walk(root: &Path, dir: &Path, out: &mut BTreeMap<String, u32>) {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                return;
+            };
+            for entry in entries.map(Result::unwrap) {
+                let path = entry.path();
+                let metadata = entry.metadata().unwrap();
+                if metadata.is_dir() {
+                    walk(root, &path, out);
+                } else {
+                    let relative = path.strip_prefix(root).unwrap();
+                    out.insert(
+                        relative.to_string_lossy().to_string(),
+                        metadata.permissions().mode(),
+                    );
+                }
+            }
+        }
+        let mut out = BTreeMap::new();
+        walk(store_root, &store_root.join("objects"), &mut out);
+        out
+    }
+
+    async fn build_package(work: &Path) -> PathBuf {
+        let src = work.join("src");
+        let live = src.join("opt/pm/live");
+        std::fs::create_dir_all(live.join("bin")).unwrap();
+        std::fs::create_dir_all(live.join("share/doc/big")).unwrap();
+        std::fs::create_dir_all(live.join("include")).unwrap();
+        let manifest = Manifest::new("big".to_string(), &Version::new(2, 0, 0), 1, &Arch::Arm64);
+        manifest_io::write_manifest(&src.join("manifest.toml"), &manifest)
+            .await
+            .unwrap();
+        std::fs::write(src.join("sbom.spdx.json"), "{}").unwrap();
+
+        std::fs::write(live.join("bin/tool"), "#!/bin/sh\necho tool\n").unwrap();
+        std::fs::set_permissions(
+            live.join("bin/tool"),
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+        std::os::unix::fs::symlink("tool", live.join("bin/tool-alias")).unwrap();
+        let large: Vec<u8> = (0..3 * 1024 * 1024u32)
+            .map(|i| i.wrapping_mul(2_654_435_761).to_le_bytes()[2])
+            .collect();
+        std::fs::write(live.join("share/doc/big/data.bin"), &large).unwrap();
+        // Identical contents under two names share one object
+        std::fs::write(live.join("include/a.h"), "#pragma once\n").unwrap();
+        std::fs::write(live.join("include/b.h"), "#pragma once\n").unwrap();
+
+        let sp = work.join("big.sp");
+        create_package(&src, &sp).await.unwrap();
+        sp
+    }
+
+    #[tokio::test]
+    async fn streamed_ingestion_matches_extract_then_store() {
+        let work = TempDir::new().unwrap();
+        let sp = build_package(work.path()).await;
+
+        let two_step_root = work.path().join("two-step");
+        let two_step = PackageStore::new(two_step_root.clone())
+            .add_package(&sp)
+            .await
+            .unwrap();
+        let streamed_root = work.path().join("streamed");
+        let streamed_store = PackageStore::new(streamed_root.clone());
+        let streamed = streamed_store.add_package_streaming(&sp).await.unwrap();
+
+        assert_eq!(streamed.hash(), two_step.hash());
+        assert_eq!(
+            serde_json::to_value(streamed.file_hashes()).unwrap(),
+            serde_json::to_value(two_step.file_hashes()).unwrap()
+        );
+        assert_eq!(objects(&streamed_root), objects(&two_step_root));
+        assert_eq!(objects(&streamed_root).len(), 3);
+        for name in ["manifest.toml", "sbom.spdx.json"] {
+            assert_eq!(
+                std::fs::read(streamed.path().join(name)).unwrap(),
+                std::fs::read(two_step.path().join(name)).unwrap()
+            );
+        }
+
+        // Streaming leaves no staging behind and links like any package
+        let leftovers: Vec<_> = std::fs::read_dir(&streamed_root)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with(".ingest-"))
+            .collect();
+        assert!(leftovers.is_empty(), "{leftovers:?}");
+
+        let live = work.path().join("live");
+        streamed.link_to(&live).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(live.join("opt/pm/live/bin/tool")).unwrap(),
+            "#!/bin/sh\necho tool\n"
+        );
+
+        // Ingesting again finds the stored package and restores lost objects
+        let stored_objects = objects(&streamed_root);
+        std::fs::remove_dir_all(streamed_root.join("objects")).unwrap();
+        let again = streamed_store.add_package_streaming(&sp).await.unwrap();
+        assert_eq!(again.hash(), streamed.hash());
+        assert_eq!(objects(&streamed_root), stored_objects);
+    }
+}
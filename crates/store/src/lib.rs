@@ -9,15 +9,22 @@
 mod archive;
 mod file_store;
 mod format_detection;
+mod gc;
+mod ingest;
 pub mod manifest_io;
+mod pack;
 mod package;
 
 pub use archive::{
     create_package, extract_package, extract_package_with_events, list_package_contents,
+    list_package_files, PackageFileEntry, PackageFileType,
 };
 pub use file_store::{FileStore, FileVerificationResult};
 pub use format_detection::{PackageFormatDetector, PackageFormatInfo, StoreFormatValidator};
+pub use gc::{GcCandidate, GcPlan, Unreferenced};
+pub use pack::{CompactionOptions, CompactionReport};
 pub use package::StoredPackage;
+pub use sps2_types::is_installed_entry;
 
 use sps2_errors::{Error, StorageError};
 use sps2_hash::Hash;
@@ -137,20 +144,34 @@ impl PackageStore {
         // Hash and store all individual files
         let file_results = self.file_store.store_directory(temp_dir.path()).await?;
 
+        Self::write_package_dir(&package_path, temp_dir.path(), &file_results).await
+    }
+
+    /// Create a package directory from its metadata files and file list
+    ///
+    /// Copies the manifest and any SBOMs from `metadata_dir` and writes
+    /// `files.json`.
+    async fn write_package_dir(
+        package_path: &Path,
+        metadata_dir: &Path,
+        file_results: &[sps2_hash::FileHashResult],
+    ) -> Result<StoredPackage, Error> {
+        let (platform, ctx) = Self::create_platform_context();
+
         // Create package directory
         platform
             .filesystem()
-            .create_dir_all(&ctx, &package_path)
+            .create_dir_all(&ctx, package_path)
             .await?;
 
         // Copy manifest to package directory
-        let manifest_src = temp_dir.path().join("manifest.toml");
+        let manifest_src = metadata_dir.join("manifest.toml");
         let manifest_dest = package_path.join("manifest.toml");
         tokio::fs::copy(&manifest_src, &manifest_dest).await?;
 
         // Copy SBOM if it exists
         for sbom_name in &["sbom.spdx.json", "sbom.cdx.json"] {
-            let sbom_src = temp_dir.path().join(sbom_name);
+            let sbom_src = metadata_dir.join(sbom_name);
             if platform.filesystem().exists(&ctx, &sbom_src).await {
                 let sbom_dest = package_path.join(sbom_name);
                 tokio::fs::copy(&sbom_src, &sbom_dest).await?;
@@ -159,16 +180,16 @@ impl PackageStore {
 
         // Create files.json with all file references
         let files_json =
-            serde_json::to_string_pretty(&file_results).map_err(|e| StorageError::IoError {
+            serde_json::to_string_pretty(file_results).map_err(|e| StorageError::IoError {
                 message: format!("failed to serialize file results: {e}"),
             })?;
         let files_path = package_path.join("files.json");
         tokio::fs::write(&files_path, files_json).await?;
 
         // Set compression on macOS
-        set_compression(&package_path)?;
+        set_compression(package_path)?;
 
-        StoredPackage::load(&package_path).await
+        StoredPackage::load(package_path).await
     }
 
     /// Remove a package from the store
@@ -279,7 +300,11 @@ impl PackageStore {
     pub async fn list_packages(&self) -> Result<Vec<Hash>, Error> {
         let mut packages = Vec::new();
 
-        let mut entries = tokio::fs::read_dir(&self.base_path).await?;
+        let packages_dir = self.base_path.join("packages");
+        if !tokio::fs::try_exists(&packages_dir).await? {
+            return Ok(packages);
+        }
+        let mut entries = tokio::fs::read_dir(&packages_dir).await?;
         while let Some(entry) = entries.next_entry().await? {
             if !entry.file_type().await?.is_dir() {
                 continue;
@@ -355,6 +380,91 @@ impl PackageStore {
         Ok(errors)
     }
 
+    /// Whether the store was migrated to the packed layout
+    pub async fn packs_enabled(&self) -> bool {
+        self.file_store.packs_enabled().await
+    }
+
+    /// Migrate the store to the packed layout
+    ///
+    /// Records the layout change under `packs/`; until then
+    /// [`compact`](Self::compact) refuses to run. [`unpack`](Self::unpack)
+    /// migrates back.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the layout marker cannot be written
+    pub async fn migrate_to_packs(&self) -> Result<(), Error> {
+        self.file_store.enable_packs().await
+    }
+
+    /// Compact the store by packing the small objects of each package
+    ///
+    /// Every stored package whose small loose objects number at least
+    /// `options.min_objects` gets a pack named after the package hash,
+    /// except those in `options.skip_packages`. Objects hardlinked into the
+    /// live prefix always stay loose. An
+    /// object shared by several packages goes into the first pack that claims
+    /// it. Packed objects stay addressable by hash and are re-inflated when
+    /// linked, so installs and verification work unchanged; running
+    /// compaction again packs nothing new and only drops re-inflated copies.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store was not migrated to the packed layout,
+    /// or listing packages or writing a pack fails
+    pub async fn compact(&self, options: &CompactionOptions) -> Result<CompactionReport, Error> {
+        let mut report = CompactionReport::default();
+        let mut claimed = std::collections::HashSet::new();
+
+        let mut packages = self.list_packages().await?;
+        packages.sort_by_key(Hash::to_hex);
+        for package_hash in packages {
+            if options.skip_packages.contains(&package_hash) {
+                continue;
+            }
+            let Ok(package) = StoredPackage::load(&self.package_path(&package_hash)).await else {
+                continue;
+            };
+            // Legacy packages keep their files in the package directory
+            let Some(file_hashes) = package.file_hashes() else {
+                continue;
+            };
+
+            let objects: Vec<Hash> = file_hashes
+                .iter()
+                .filter(|entry| !entry.is_directory && !entry.is_symlink)
+                .filter(|entry| claimed.insert(entry.hash.clone()))
+                .map(|entry| entry.hash.clone())
+                .collect();
+            let packed = self
+                .file_store
+                .compact_objects(&package_hash.to_hex(), &objects, options)
+                .await?;
+
+            report.packs_written += packed.packs_written;
+            report.objects_packed += packed.objects_packed;
+            report.bytes_packed += packed.bytes_packed;
+            report.loose_copies_removed += packed.loose_copies_removed;
+            report.packages_skipped += packed.packages_skipped;
+            report.objects_in_use += packed.objects_in_use;
+        }
+
+        Ok(report)
+    }
+
+    /// Undo compaction, moving every packed object back to `objects/`
+    ///
+    /// Returns the number of objects re-inflated. The store is migrated back
+    /// to the loose layout.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a packed object is corrupted or cannot be written
+    pub async fn unpack(&self) -> Result<usize, Error> {
+        self.file_store.unpack_all().await
+    }
+
     /// Garbage collect unreferenced packages
     ///
     /// # Errors
@@ -367,6 +477,53 @@ impl PackageStore {
         Ok(0)
     }
 
+    /// Preview garbage collection without deleting anything
+    ///
+    /// `unreferenced` comes from the state database's refcounts, the same
+    /// ones the real collection acts on. Content already missing from disk
+    /// is left out of the plan.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if measuring a package or file object fails
+    pub async fn garbage_collect_dry_run(
+        &self,
+        unreferenced: &Unreferenced,
+    ) -> Result<GcPlan, Error> {
+        let (platform, ctx) = Self::create_platform_context();
+        let mut plan = GcPlan::default();
+
+        for hash in &unreferenced.packages {
+            if !platform
+                .filesystem()
+                .exists(&ctx, &self.package_path(hash))
+                .await
+            {
+                continue;
+            }
+            let size = self.package_size(hash).await?;
+            plan.bytes_freed += size;
+            plan.packages.push(GcCandidate {
+                hash: hash.to_hex(),
+                size,
+            });
+        }
+
+        for hash in &unreferenced.file_objects {
+            if !self.file_store.has_file(hash).await {
+                continue;
+            }
+            let size = self.file_store.file_size(hash).await?;
+            plan.bytes_freed += size;
+            plan.file_objects.push(GcCandidate {
+                hash: hash.to_hex(),
+                size,
+            });
+        }
+
+        Ok(plan)
+    }
+
     /// Verify store integrity
     ///
     /// # Errors
@@ -432,6 +589,8 @@ impl PackageStore {
 
     /// Add package from file with specific name and version
     ///
+    /// Streams the archive into the store, see [`Self::add_package_streaming`].
+    ///
     /// # Errors
     ///
     /// Returns an error if package addition fails
@@ -441,8 +600,7 @@ impl PackageStore {
         _package_name: &str,
         _package_version: &sps2_types::Version,
     ) -> Result<StoredPackage, Error> {
-        // For now, just delegate to add_package
-        self.add_package(file_path).await
+        self.add_package_streaming(file_path).await
     }
 
     /// Add package from staging directory
@@ -483,42 +641,161 @@ impl PackageStore {
         // Hash and store all individual files
         let file_results = self.file_store.store_directory(staging_path).await?;
 
-        // Create package directory
-        platform
-            .filesystem()
-            .create_dir_all(&ctx, &package_path)
-            .await?;
+        Self::write_package_dir(&package_path, staging_path, &file_results).await
+    }
 
-        // Copy manifest to package directory
-        let manifest_dest = package_path.join("manifest.toml");
-        tokio::fs::copy(&manifest_path, &manifest_dest).await?;
+    /// Compute hash of staging directory contents
+    async fn compute_staging_hash(&self, staging_path: &Path) -> Result<Hash, Error> {
+        // Hash the entire staging directory for true content-addressable storage
+        sps2_hash::Hash::hash_directory(staging_path).await
+    }
+}
 
-        // Copy SBOM if it exists
-        for sbom_name in &["sbom.spdx.json", "sbom.cdx.json"] {
-            let sbom_src = staging_path.join(sbom_name);
-            if platform.filesystem().exists(&ctx, &sbom_src).await {
-                let sbom_dest = package_path.join(sbom_name);
-                tokio::fs::copy(&sbom_src, &sbom_dest).await?;
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sps2_types::{Arch, Manifest, Version};
+    use tempfile::TempDir;
+
+    /// Store a package with `count` small files under `share/data`
+    async fn store_package(store: &PackageStore, work: &Path, count: usize) -> StoredPackage {
+        let src = work.join("src");
+        let data_dir = src.join("opt/pm/live/share/data");
+        tokio::fs::create_dir_all(&data_dir).await.unwrap();
+        let manifest = Manifest::new("tiny".to_string(), &Version::new(1, 0, 0), 1, &Arch::Arm64);
+        manifest_io::write_manifest(&src.join("manifest.toml"), &manifest)
+            .await
+            .unwrap();
+        for i in 0..count {
+            tokio::fs::write(data_dir.join(format!("{i}.txt")), format!("entry {i}\n"))
+                .await
+                .unwrap();
         }
 
-        // Create files.json with all file references
-        let files_json =
-            serde_json::to_string_pretty(&file_results).map_err(|e| StorageError::IoError {
-                message: format!("failed to serialize file results: {e}"),
-            })?;
-        let files_path = package_path.join("files.json");
-        tokio::fs::write(&files_path, files_json).await?;
+        let sp = work.join("tiny.sp");
+        create_package(&src, &sp).await.unwrap();
+        store.add_package(&sp).await.unwrap()
+    }
 
-        // Set compression on macOS
-        set_compression(&package_path)?;
+    #[tokio::test]
+    async fn compacted_package_still_links_and_verifies() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = PackageStore::new(temp_dir.path().join("store"));
+        let package = store_package(&store, temp_dir.path(), 40).await;
+        let hashes: Vec<Hash> = package
+            .file_hashes()
+            .unwrap()
+            .iter()
+            .filter(|entry| !entry.is_directory && !entry.is_symlink)
+            .map(|entry| entry.hash.clone())
+            .collect();
+        assert_eq!(hashes.len(), 40);
+
+        store.migrate_to_packs().await.unwrap();
+        let report = store.compact(&CompactionOptions::default()).await.unwrap();
+        assert_eq!(report.packs_written, 1);
+        assert_eq!(report.objects_packed, 40);
+        for hash in &hashes {
+            assert!(!store.file_path(hash).exists());
+            assert!(store.file_store().verify_file(hash).await.unwrap());
+        }
+
+        let live = temp_dir.path().join("live");
+        package.link_to(&live).await.unwrap();
+        for i in 0..40 {
+            let linked = live.join(format!("opt/pm/live/share/data/{i}.txt"));
+            assert_eq!(
+                tokio::fs::read_to_string(&linked).await.unwrap(),
+                format!("entry {i}\n")
+            );
+        }
+        assert!(store.verify().await.unwrap().is_empty());
 
-        StoredPackage::load(&package_path).await
+        // A second run only drops the copies re-inflated by linking
+        let report = store.compact(&CompactionOptions::default()).await.unwrap();
+        assert_eq!(report.packs_written, 0);
+        assert_eq!(report.loose_copies_removed, 40);
+
+        assert_eq!(store.unpack().await.unwrap(), 40);
+        assert!(hashes.iter().all(|hash| store.file_path(hash).exists()));
+        assert!(!store.packs_enabled().await);
     }
 
-    /// Compute hash of staging directory contents
-    async fn compute_staging_hash(&self, staging_path: &Path) -> Result<Hash, Error> {
-        // Hash the entire staging directory for true content-addressable storage
-        sps2_hash::Hash::hash_directory(staging_path).await
+    #[tokio::test]
+    async fn compaction_requires_the_layout_migration() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = PackageStore::new(temp_dir.path().join("store"));
+        store_package(&store, temp_dir.path(), 40).await;
+
+        let err = store
+            .compact(&CompactionOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Storage(sps2_errors::StorageError::MigrationRequired { .. })
+        ));
+        assert!(!temp_dir.path().join("store/packs").exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn installed_objects_keep_sharing_their_inode() {
+        use std::os::unix::fs::MetadataExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let store = PackageStore::new(temp_dir.path().join("store"));
+        let package = store_package(&store, temp_dir.path(), 40).await;
+        let live = temp_dir.path().join("live");
+        std::fs::create_dir_all(&live).unwrap();
+        let mut linked = Vec::new();
+        for entry in package.file_hashes().unwrap() {
+            if entry.is_directory || entry.is_symlink {
+                continue;
+            }
+            let object = store.file_path(&entry.hash);
+            let path = live.join(format!("{}.txt", linked.len()));
+            std::fs::hard_link(&object, &path).unwrap();
+            linked.push((object, path));
+        }
+
+        store.migrate_to_packs().await.unwrap();
+        let report = store.compact(&CompactionOptions::default()).await.unwrap();
+        assert_eq!(report.packs_written, 0);
+        assert_eq!(report.objects_in_use, 40);
+        for (object, path) in &linked {
+            let object = std::fs::metadata(object).unwrap();
+            let live = std::fs::metadata(path).unwrap();
+            assert_eq!(object.ino(), live.ino());
+            assert_eq!(live.nlink(), 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn packages_in_use_are_skipped() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = PackageStore::new(temp_dir.path().join("store"));
+        let package = store_package(&store, temp_dir.path(), 40).await;
+
+        store.migrate_to_packs().await.unwrap();
+        let options = CompactionOptions {
+            skip_packages: [package.hash().unwrap()].into_iter().collect(),
+            ..CompactionOptions::default()
+        };
+        let report = store.compact(&options).await.unwrap();
+        assert_eq!(report.packs_written, 0);
+        assert_eq!(report.objects_packed, 0);
+    }
+
+    #[tokio::test]
+    async fn packages_with_few_small_files_stay_loose() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = PackageStore::new(temp_dir.path().join("store"));
+        store_package(&store, temp_dir.path(), 3).await;
+
+        store.migrate_to_packs().await.unwrap();
+        let report = store.compact(&CompactionOptions::default()).await.unwrap();
+        assert_eq!(report.packs_written, 0);
+        assert_eq!(report.packages_skipped, 1);
     }
 }
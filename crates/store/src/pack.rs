@@ -0,0 +1,452 @@
+//! Pack files for compacted store objects
+//!
+//! Packages with many tiny files leave thousands of small objects under
+//! `objects/`, one inode each. Compaction moves the small loose objects of a
+//! package into a pack under `packs/`: `<name>.pack` holds the object
+//! contents back to back and `<name>.idx` maps each object hash to its byte
+//! range and mode. Objects keep their content hash, so every lookup by hash
+//! still works; an object that is needed as a file again (linking into the
+//! live prefix, healing) is re-inflated from its pack after checking its
+//! contents against the hash. Garbage collection drops objects from a
+//! pack's index and rewrites the pack once most of its bytes are dead.
+//!
+//! Packs are a separate store layout: a store must be migrated to it with
+//! [`crate::PackageStore::migrate_to_packs`] before anything is packed, and
+//! [`crate::PackageStore::unpack`] migrates it back.
+
+use serde::{Deserialize, Serialize};
+use sps2_errors::{Error, StorageError};
+use sps2_hash::Hash;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+/// Directory under the store root holding pack files
+pub(crate) const PACKS_DIR: &str = "packs";
+
+/// Pack index format written by this version
+const PACK_FORMAT_VERSION: u32 = 1;
+
+/// Marker recording that the store was migrated to the packed layout
+const LAYOUT_FILE: &str = "LAYOUT";
+
+/// Packs are rewritten without their removed objects once more than this
+/// percentage of their bytes is dead
+const REPACK_DEAD_PERCENT: u64 = 50;
+
+const PACK_EXTENSION: &str = "pack";
+const INDEX_EXTENSION: &str = "idx";
+
+/// Tuning for [`crate::PackageStore::compact`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactionOptions {
+    /// Objects larger than this stay loose
+    pub max_object_size: u64,
+    /// Packages with fewer small loose objects than this are left alone
+    pub min_objects: usize,
+    /// Packages left loose regardless, such as those installed in the
+    /// active state
+    pub skip_packages: HashSet<Hash>,
+}
+
+impl Default for CompactionOptions {
+    fn default() -> Self {
+        Self {
+            max_object_size: 64 * 1024,
+            min_objects: 32,
+            skip_packages: HashSet::new(),
+        }
+    }
+}
+
+/// Outcome of a compaction run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactionReport {
+    /// Packs written by this run
+    pub packs_written: usize,
+    /// Loose objects moved into packs
+    pub objects_packed: usize,
+    /// Bytes moved into packs
+    pub bytes_packed: u64,
+    /// Loose copies of already packed objects that were dropped again
+    pub loose_copies_removed: usize,
+    /// Packages with too few small objects to be worth packing
+    pub packages_skipped: usize,
+    /// Objects left loose because they are hardlinked elsewhere, such as
+    /// into the live prefix
+    pub objects_in_use: usize,
+}
+
+/// On-disk index of one pack
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PackIndex {
+    version: u32,
+    /// Packed objects by hash (hex)
+    objects: BTreeMap<String, PackedObject>,
+}
+
+/// Byte range and mode of an object inside a pack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct PackedObject {
+    pub(crate) offset: u64,
+    pub(crate) len: u64,
+    pub(crate) mode: u32,
+}
+
+/// Where a packed object lives
+#[derive(Debug, Clone)]
+pub(crate) struct PackLocation {
+    /// Pack name, shared by its `.pack` and `.idx` files
+    pub(crate) name: String,
+    pub(crate) object: PackedObject,
+}
+
+/// In-memory index of all packs, with the pack names it was built from
+#[derive(Debug)]
+struct CachedIndex {
+    names: Vec<String>,
+    objects: HashMap<String, PackLocation>,
+}
+
+/// The packs of one store, with an in-memory index of their objects
+///
+/// The index is cached and only rebuilt once the set of packs changes:
+/// writes through this set invalidate it, and a lookup that misses compares
+/// the pack names on disk so packs written by another process are picked up.
+#[derive(Debug)]
+pub(crate) struct PackSet {
+    root: PathBuf,
+    cache: Mutex<Option<Arc<CachedIndex>>>,
+    #[cfg(test)]
+    index_reads: std::sync::atomic::AtomicUsize,
+}
+
+impl PackSet {
+    pub(crate) fn new(store_base_path: &Path) -> Self {
+        Self {
+            root: store_base_path.join(PACKS_DIR),
+            cache: Mutex::new(None),
+            #[cfg(test)]
+            index_reads: std::sync::atomic::AtomicUsize::new(0),
+        }
+    }
+
+    fn pack_path(&self, name: &str) -> PathBuf {
+        self.root.join(format!("{name}.{PACK_EXTENSION}"))
+    }
+
+    fn index_path(&self, name: &str) -> PathBuf {
+        self.root.join(format!("{name}.{INDEX_EXTENSION}"))
+    }
+
+    /// Whether the store was migrated to the packed layout
+    pub(crate) async fn is_enabled(&self) -> bool {
+        fs::try_exists(self.root.join(LAYOUT_FILE))
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Record the migration to the packed layout
+    pub(crate) async fn enable(&self) -> Result<(), Error> {
+        fs::create_dir_all(&self.root).await?;
+        fs::write(
+            self.root.join(LAYOUT_FILE),
+            format!("{PACK_FORMAT_VERSION}\n"),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Record the migration back to loose objects only
+    pub(crate) async fn disable(&self) -> Result<(), Error> {
+        match fs::remove_file(self.root.join(LAYOUT_FILE)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether a pack called `name` exists
+    pub(crate) async fn contains_pack(&self, name: &str) -> bool {
+        fs::try_exists(self.index_path(name)).await.unwrap_or(false)
+    }
+
+    /// Find the pack holding `hash`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pack index cannot be read.
+    pub(crate) async fn locate(&self, hash: &Hash) -> Result<Option<PackLocation>, Error> {
+        let key = hash.to_hex();
+        let names = match self.cached() {
+            Some(cached) => {
+                if let Some(location) = cached.objects.get(&key) {
+                    return Ok(Some(location.clone()));
+                }
+                let names = self.pack_names().await?;
+                // Same packs as the cached index, so the object is not packed
+                if names == cached.names {
+                    return Ok(None);
+                }
+                names
+            }
+            None => self.pack_names().await?,
+        };
+        Ok(self.reload(names).await?.objects.get(&key).cloned())
+    }
+
+    fn cached(&self) -> Option<Arc<CachedIndex>> {
+        self.cache.lock().ok().and_then(|cache| cache.clone())
+    }
+
+    fn invalidate(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            *cache = None;
+        }
+    }
+
+    /// Read the indexes of the packs in `names` and replace the cached index
+    async fn reload(&self, names: Vec<String>) -> Result<Arc<CachedIndex>, Error> {
+        let mut objects = HashMap::new();
+        for name in &names {
+            for (hash, object) in self.read_index(name).await?.objects {
+                objects.insert(
+                    hash,
+                    PackLocation {
+                        name: name.clone(),
+                        object,
+                    },
+                );
+            }
+        }
+
+        let index = Arc::new(CachedIndex { names, objects });
+        if let Ok(mut cache) = self.cache.lock() {
+            *cache = Some(Arc::clone(&index));
+        }
+        Ok(index)
+    }
+
+    /// Names of all packs in the store
+    pub(crate) async fn pack_names(&self) -> Result<Vec<String>, Error> {
+        if !fs::try_exists(&self.root).await? {
+            return Ok(Vec::new());
+        }
+
+        let mut names = Vec::new();
+        let mut entries = fs::read_dir(&self.root).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(INDEX_EXTENSION) {
+                continue;
+            }
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    async fn read_index(&self, name: &str) -> Result<PackIndex, Error> {
+        #[cfg(test)]
+        self.index_reads
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let path = self.index_path(name);
+        let data = fs::read(&path).await?;
+        let index: PackIndex =
+            serde_json::from_slice(&data).map_err(|e| StorageError::CorruptedData {
+                message: format!("invalid pack index {}: {e}", path.display()),
+            })?;
+        if index.version > PACK_FORMAT_VERSION {
+            return Err(StorageError::CorruptedData {
+                message: format!(
+                    "pack index {} has unsupported version {}",
+                    path.display(),
+                    index.version
+                ),
+            }
+            .into());
+        }
+        Ok(index)
+    }
+
+    async fn write_index(&self, name: &str, index: &PackIndex) -> Result<(), Error> {
+        let json = serde_json::to_vec(index).map_err(|e| StorageError::IoError {
+            message: format!("failed to serialize pack index: {e}"),
+        })?;
+        let path = self.index_path(name);
+        let staging = path.with_extension(format!("{INDEX_EXTENSION}.tmp"));
+        fs::write(&staging, json).await?;
+        fs::rename(&staging, &path).await?;
+        Ok(())
+    }
+
+    /// Read a packed object's contents
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pack cannot be read.
+    pub(crate) async fn read(&self, location: &PackLocation) -> Result<Vec<u8>, Error> {
+        let mut file = fs::File::open(self.pack_path(&location.name)).await?;
+        file.seek(SeekFrom::Start(location.object.offset)).await?;
+        let len =
+            usize::try_from(location.object.len).map_err(|_| StorageError::CorruptedData {
+                message: format!("packed object in {} is too large", location.name),
+            })?;
+        let mut data = vec![0; len];
+        file.read_exact(&mut data).await?;
+        Ok(data)
+    }
+
+    /// Write `objects` (hash, loose path, mode) into a new pack called `name`
+    ///
+    /// The pack is complete on disk before its index appears, so readers
+    /// never see an index pointing at missing data. Returns the bytes packed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a loose object cannot be read or the pack cannot
+    /// be written.
+    pub(crate) async fn write_pack(
+        &self,
+        name: &str,
+        objects: &[(Hash, PathBuf, u32)],
+    ) -> Result<u64, Error> {
+        fs::create_dir_all(&self.root).await?;
+
+        let pack_path = self.pack_path(name);
+        let staging = pack_path.with_extension(format!("{PACK_EXTENSION}.tmp"));
+        let mut pack = fs::File::create(&staging).await?;
+        let mut index = PackIndex {
+            version: PACK_FORMAT_VERSION,
+            objects: BTreeMap::new(),
+        };
+
+        let mut offset = 0u64;
+        for (hash, path, mode) in objects {
+            let data = fs::read(path).await?;
+            pack.write_all(&data).await?;
+            let len = data.len() as u64;
+            index.objects.insert(
+                hash.to_hex(),
+                PackedObject {
+                    offset,
+                    len,
+                    mode: *mode,
+                },
+            );
+            offset += len;
+        }
+        pack.sync_all().await?;
+        drop(pack);
+
+        fs::rename(&staging, &pack_path).await?;
+        self.write_index(name, &index).await?;
+        self.invalidate();
+        Ok(offset)
+    }
+
+    /// Drop `hash` from its pack, deleting the pack once it is empty
+    ///
+    /// The object's bytes stay in the pack file until more than
+    /// `REPACK_DEAD_PERCENT` of the pack is dead; the pack is then rewritten
+    /// with only its remaining objects.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pack index or the pack cannot be rewritten.
+    pub(crate) async fn remove(&self, hash: &Hash) -> Result<(), Error> {
+        let Some(location) = self.locate(hash).await? else {
+            return Ok(());
+        };
+
+        let mut index = self.read_index(&location.name).await?;
+        index.objects.remove(&hash.to_hex());
+        if index.objects.is_empty() {
+            self.remove_pack(&location.name).await?;
+        } else {
+            let pack_len = fs::metadata(self.pack_path(&location.name)).await?.len();
+            let live_len: u64 = index.objects.values().map(|object| object.len).sum();
+            if (pack_len - live_len.min(pack_len)) * 100 > pack_len * REPACK_DEAD_PERCENT {
+                self.repack(&location.name, index).await?;
+            } else {
+                self.write_index(&location.name, &index).await?;
+            }
+        }
+        self.invalidate();
+        Ok(())
+    }
+
+    /// Rewrite the pack called `name` with only the objects in `index`
+    ///
+    /// The new pack replaces the old one before its index is written, as in
+    /// [`Self::write_pack`]; a reader holding old offsets in between reads
+    /// data that fails its hash check rather than a wrong object.
+    async fn repack(&self, name: &str, index: PackIndex) -> Result<(), Error> {
+        let pack_path = self.pack_path(name);
+        let staging = pack_path.with_extension(format!("{PACK_EXTENSION}.tmp"));
+        let mut old = fs::File::open(&pack_path).await?;
+        let mut pack = fs::File::create(&staging).await?;
+        let mut repacked = PackIndex {
+            version: PACK_FORMAT_VERSION,
+            objects: BTreeMap::new(),
+        };
+
+        let mut offset = 0u64;
+        for (hash, object) in index.objects {
+            old.seek(SeekFrom::Start(object.offset)).await?;
+            let copied = tokio::io::copy(&mut (&mut old).take(object.len), &mut pack).await?;
+            if copied != object.len {
+                return Err(StorageError::CorruptedData {
+                    message: format!("pack {name} is truncated at object {hash}"),
+                }
+                .into());
+            }
+            repacked
+                .objects
+                .insert(hash, PackedObject { offset, ..object });
+            offset += object.len;
+        }
+        pack.sync_all().await?;
+        drop(pack);
+
+        fs::rename(&staging, &pack_path).await?;
+        self.write_index(name, &repacked).await
+    }
+
+    /// Objects of the pack called `name`
+    pub(crate) async fn objects(&self, name: &str) -> Result<Vec<(Hash, PackLocation)>, Error> {
+        let index = self.read_index(name).await?;
+        index
+            .objects
+            .into_iter()
+            .map(|(hash, object)| {
+                Ok((
+                    Hash::from_hex(&hash)?,
+                    PackLocation {
+                        name: name.to_string(),
+                        object,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// Delete a pack and its index
+    pub(crate) async fn remove_pack(&self, name: &str) -> Result<(), Error> {
+        // Index first so no reader follows it into a missing pack
+        fs::remove_file(self.index_path(name)).await?;
+        let _ = fs::remove_file(self.pack_path(name)).await;
+        self.invalidate();
+        Ok(())
+    }
+
+    /// Number of pack indexes read from disk so far
+    #[cfg(test)]
+    pub(crate) fn index_reads(&self) -> usize {
+        self.index_reads.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
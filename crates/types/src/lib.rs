@@ -11,6 +11,7 @@ pub mod manifest;
 pub mod package;
 pub mod recipe;
 pub mod reports;
+pub mod secret;
 pub mod state;
 pub mod version;
 
@@ -21,12 +22,13 @@ pub use format::{
     PackageFormatVersionError,
 };
 pub use manifest::{
-    CompressionInfo as ManifestCompressionInfo, Dependencies as ManifestDependencies, Manifest,
-    ManifestBuilder, PackageInfo as ManifestPackageInfo, SbomInfo,
+    runtime_env_var_error, CompressionInfo as ManifestCompressionInfo,
+    Dependencies as ManifestDependencies, Manifest, ManifestBuilder,
+    PackageInfo as ManifestPackageInfo, SbomInfo,
 };
 pub use package::{
-    DepEdge, DepKind, PackageId, PackageInfo, PackageSpec, PackageStatus, PythonPackageMetadata,
-    SearchResult,
+    is_installed_entry, DepEdge, DepKind, PackageId, PackageInfo, PackageSpec, PackageStatus,
+    PythonPackageMetadata, SearchResult,
 };
 pub use recipe::{
     Build, BuildSystem, Checksum, ChecksumAlgorithm, Dependencies, Environment, FetchSource,
@@ -34,10 +36,11 @@ pub use recipe::{
     PostOption, Source, SourceMethod, YamlRecipe,
 };
 pub use reports::{BuildReport, InstallReport, PackageChange};
+pub use secret::Secret;
 pub use semver::Version;
 pub use state::{ChangeType, OpChange, StateId, StateInfo, StateTransition};
 pub use uuid::Uuid;
-pub use version::{VersionConstraint, VersionSpec};
+pub use version::{Preference, VersionConstraint, VersionSpec};
 
 // QA pipeline override is defined below in this module
 
@@ -145,6 +148,49 @@ impl QaPipelineOverride {
     }
 }
 
+/// Which post-validation patchers run, and in what order
+///
+/// Patchers are named by their short id (`headers`, `pkgconfig`, ...) or
+/// their full name. Used both globally (`[build.patchers]`) and per recipe
+/// (`post.patchers`), with the recipe layered on top.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatcherSelection {
+    /// Patchers to run, in this order, instead of the QA profile's list
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<String>,
+    /// Patchers to skip
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disable: Vec<String>,
+}
+
+impl PatcherSelection {
+    /// Check if this is the default value (for serde `skip_serializing_if`)
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        self.order.is_empty() && self.disable.is_empty()
+    }
+
+    /// Layer `overrides` (e.g. a recipe's selection) over this one
+    ///
+    /// A non-empty order in `overrides` replaces this order; disabled
+    /// patchers from both are skipped.
+    #[must_use]
+    pub fn merged_with(&self, overrides: &Self) -> Self {
+        let order = if overrides.order.is_empty() {
+            self.order.clone()
+        } else {
+            overrides.order.clone()
+        };
+        let mut disable = self.disable.clone();
+        for name in &overrides.disable {
+            if !disable.contains(name) {
+                disable.push(name.clone());
+            }
+        }
+        Self { order, disable }
+    }
+}
+
 impl BuildSystemProfile {
     /// Determine profile from build system name
     #[must_use]
@@ -11,6 +11,52 @@ use crate::{
 };
 use serde::{Deserialize, Serialize};
 use sps2_errors::{Error, PackageError};
+use std::collections::BTreeMap;
+
+/// Environment variables a package may never set at runtime
+///
+/// These change how every process loads code or interprets commands.
+const FORBIDDEN_RUNTIME_ENV: &[&str] = &[
+    "PATH",
+    "IFS",
+    "ENV",
+    "BASH_ENV",
+    "PROMPT_COMMAND",
+    "PS4",
+    "SHELLOPTS",
+];
+
+/// Variable prefixes reserved for the dynamic loader
+const FORBIDDEN_RUNTIME_ENV_PREFIXES: &[&str] = &["LD_", "DYLD_"];
+
+/// Check a package-declared runtime environment variable
+///
+/// Returns a description of the problem if `name` is not a valid variable
+/// name, is reserved, or `value` cannot be exported.
+#[must_use]
+pub fn runtime_env_var_error(name: &str, value: &str) -> Option<String> {
+    let mut chars = name.chars();
+    let valid_name = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_name {
+        return Some(format!("invalid environment variable name '{name}'"));
+    }
+    if FORBIDDEN_RUNTIME_ENV.contains(&name)
+        || FORBIDDEN_RUNTIME_ENV_PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+    {
+        return Some(format!(
+            "packages may not set environment variable '{name}'"
+        ));
+    }
+    if value.contains('\0') {
+        return Some(format!("value of '{name}' contains a NUL byte"));
+    }
+    None
+}
 
 /// Package manifest (manifest.toml contents)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +71,11 @@ pub struct Manifest {
     /// Optional Python-specific metadata for Python packages
     #[serde(skip_serializing_if = "Option::is_none")]
     pub python: Option<PythonPackageMetadata>,
+    /// Environment variables to export while the package is installed
+    ///
+    /// Values may reference paths under `/opt/pm/live`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub runtime_env: BTreeMap<String, String>,
 }
 
 /// Package information section
@@ -51,6 +102,15 @@ pub struct Dependencies {
     pub runtime: Vec<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub build: Vec<String>,
+    /// Optional runtime dependencies, linked only when otherwise installed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weak: Vec<String>,
+    /// Packages that can never be installed alongside this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<String>,
+    /// Packages this one supersedes, and may take files over from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replaces: Vec<String>,
 }
 
 /// SBOM information section
@@ -59,6 +119,9 @@ pub struct SbomInfo {
     pub spdx: String, // BLAKE3 hash (hex)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cyclonedx: Option<String>, // BLAKE3 hash (hex)
+    /// BLAKE3 hash (hex) of the SPDX tag-value document, if one was generated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spdx_tv: Option<String>,
 }
 
 /// Compression information section
@@ -93,6 +156,7 @@ impl Manifest {
             dependencies: Dependencies::default(),
             sbom: None,
             python: None,
+            runtime_env: BTreeMap::new(),
         }
     }
 
@@ -153,6 +217,48 @@ impl Manifest {
             .map_err(Into::into)
     }
 
+    /// Get weak (optional) runtime dependencies as `PackageSpec`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any dependency specification string is invalid or cannot be parsed.
+    pub fn weak_deps(&self) -> Result<Vec<PackageSpec>, Error> {
+        self.dependencies
+            .weak
+            .iter()
+            .map(|s| PackageSpec::parse(s))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Get declared conflicts as `PackageSpec`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any conflict specification string is invalid or cannot be parsed.
+    pub fn conflicts(&self) -> Result<Vec<PackageSpec>, Error> {
+        self.dependencies
+            .conflicts
+            .iter()
+            .map(|s| PackageSpec::parse(s))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Get replaced packages as `PackageSpec`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any replacement specification string is invalid or cannot be parsed.
+    pub fn replaces(&self) -> Result<Vec<PackageSpec>, Error> {
+        self.dependencies
+            .replaces
+            .iter()
+            .map(|s| PackageSpec::parse(s))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
     /// Add a runtime dependency
     pub fn add_runtime_dep(&mut self, spec: &str) {
         self.dependencies.runtime.push(spec.to_string());
@@ -237,6 +343,16 @@ impl Manifest {
         // Validate dependencies
         self.runtime_deps()?;
         self.build_deps()?;
+        self.weak_deps()?;
+        self.conflicts()?;
+        self.replaces()?;
+
+        // Validate runtime environment variables
+        for (name, value) in &self.runtime_env {
+            if let Some(message) = runtime_env_var_error(name, value) {
+                return Err(PackageError::InvalidManifest { message }.into());
+            }
+        }
 
         // Validate format version compatibility
         let current_version = PackageFormatVersion::CURRENT;
@@ -334,6 +450,15 @@ impl ManifestBuilder {
         self
     }
 
+    /// Add a runtime environment variable
+    #[must_use]
+    pub fn runtime_env(mut self, name: &str, value: &str) -> Self {
+        self.manifest
+            .runtime_env
+            .insert(name.to_string(), value.to_string());
+        self
+    }
+
     /// Build the manifest
     ///
     /// # Errors
@@ -344,3 +469,36 @@ impl ManifestBuilder {
         Ok(self.manifest)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_env_round_trips_and_rejects_loader_variables() {
+        let version = Version::parse("1.0.0").unwrap();
+        let manifest = ManifestBuilder::new("gettext".to_string(), &version, &Arch::Arm64)
+            .runtime_env("GETTEXTDATADIR", "/opt/pm/live/share/gettext")
+            .build()
+            .unwrap();
+        let parsed = Manifest::from_toml(&manifest.to_toml().unwrap()).unwrap();
+        assert_eq!(
+            parsed.runtime_env.get("GETTEXTDATADIR").map(String::as_str),
+            Some("/opt/pm/live/share/gettext")
+        );
+
+        for name in [
+            "LD_PRELOAD",
+            "DYLD_INSERT_LIBRARIES",
+            "PATH",
+            "1BAD",
+            "A-B",
+            "",
+        ] {
+            let result = ManifestBuilder::new("evil".to_string(), &version, &Arch::Arm64)
+                .runtime_env(name, "/tmp/x")
+                .build();
+            assert!(result.is_err(), "{name} should be rejected");
+        }
+    }
+}
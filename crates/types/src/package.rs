@@ -4,6 +4,20 @@ use crate::{Arch, Version, VersionSpec};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+/// Package metadata files that live in the store, never in the live prefix
+const PACKAGE_METADATA_FILES: [&str; 3] = ["manifest.toml", "sbom.spdx.json", "sbom.cdx.json"];
+
+/// Whether a package entry belongs in its file list
+///
+/// Package metadata (manifest and SBOMs) lives only in the package
+/// directory, and the `opt/pm/live` prefix directories are not part of any
+/// package. `relative_path` is relative to the package root.
+#[must_use]
+pub fn is_installed_entry(relative_path: &str) -> bool {
+    !PACKAGE_METADATA_FILES.contains(&relative_path)
+        && !matches!(relative_path, "opt" | "opt/pm" | "opt/pm/live")
+}
+
 /// Unique identifier for a package
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct PackageId {
@@ -37,6 +51,9 @@ pub struct PackageSpec {
 impl PackageSpec {
     /// Parse a package spec from a string (e.g., "jq>=1.6,<2.0")
     ///
+    /// A wildcard may follow the name after whitespace or `==`, so
+    /// `"curl 1.2.*"` and `"curl==1.2.*"` both mean `curl>=1.2.0,<1.3.0`.
+    ///
     /// # Errors
     ///
     /// Returns `VersionError` if the package specification string is malformed
@@ -44,7 +61,7 @@ impl PackageSpec {
     ///
     pub fn parse(s: &str) -> Result<Self, sps2_errors::VersionError> {
         // Find the first constraint operator
-        let operators = ["==", ">=", "<=", "!=", "~=", ">", "<"];
+        let operators = ["==", ">=", "<=", "!=", "~=", "^", ">", "<"];
         let mut split_pos = None;
 
         for op in &operators {
@@ -59,6 +76,9 @@ impl PackageSpec {
 
         let (name, version_str) = if let Some(pos) = split_pos {
             (s[..pos].trim(), s[pos..].trim())
+        } else if let Some((name, version_str)) = s.trim().split_once(char::is_whitespace) {
+            // A bare wildcard after the name, e.g. "curl 1.2.*"
+            (name, version_str.trim())
         } else {
             // No version constraint means any version
             (s.trim(), "*")
@@ -192,3 +212,21 @@ pub struct PythonPackageMetadata {
     /// e.g., {"black": "black:main", "blackd": "blackd:main"}
     pub executables: std::collections::HashMap<String, String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_wildcard_specs() {
+        let expected: VersionSpec = ">=1.2.0,<1.3.0".parse().unwrap();
+        for input in ["curl 1.2.*", "curl==1.2.*", "curl == 1.2.*"] {
+            let spec = PackageSpec::parse(input).unwrap();
+            assert_eq!(spec.name, "curl", "{input}");
+            assert_eq!(spec.version_spec, expected, "{input}");
+        }
+
+        assert!(PackageSpec::parse("curl 1.*.3").is_err());
+        assert!(PackageSpec::parse("curl>=1.2.*").is_err());
+    }
+}
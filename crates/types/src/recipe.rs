@@ -352,6 +352,9 @@ pub struct GitSource {
     pub url: String,
     #[serde(rename = "ref")]
     pub git_ref: String,
+    /// Commits of history to clone (optional; 0 clones the full history)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depth: Option<u32>,
 }
 
 /// Fetch source specification
@@ -363,6 +366,12 @@ pub struct FetchSource {
     /// Where to extract relative to build directory (optional)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub extract_to: Option<String>,
+    /// Leading path components to strip when extracting (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strip_components: Option<usize>,
+    /// Whether to extract the download after fetching (optional; default true)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extract: Option<bool>,
 }
 
 /// Checksum specification
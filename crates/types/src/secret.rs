@@ -0,0 +1,65 @@
+//! Secret values kept out of debug output
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A password, token or other secret
+///
+/// `Debug` prints `<redacted>` instead of the value. It serializes as the
+/// bare string, so configuration files are unaffected.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    /// Wrap `value` as a secret
+    #[must_use]
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// The secret value itself
+    #[must_use]
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_output_is_redacted() {
+        let secret = Secret::new("s3cret");
+        assert_eq!(format!("{secret:?}"), "<redacted>");
+        assert_eq!(format!("{:?}", Some(&secret)), "Some(<redacted>)");
+        assert_eq!(secret.expose(), "s3cret");
+    }
+
+    #[test]
+    fn serializes_as_the_bare_string() {
+        let json = serde_json::to_string(&Secret::from("s3cret")).unwrap();
+        assert_eq!(json, "\"s3cret\"");
+        let secret: Secret = serde_json::from_str(&json).unwrap();
+        assert_eq!(secret.expose(), "s3cret");
+    }
+}
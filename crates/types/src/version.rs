@@ -6,14 +6,26 @@
 //! - `<=2.0.0` - Maximum version
 //! - `~=1.2.0` - Compatible release (>=1.2.0,<1.3.0)
 //! - `!=1.5.0` - Exclude version
+//! - `^1.2.3` - Caret range (>=1.2.3,<2.0.0; the leftmost non-zero
+//!   component may not change)
 //! - Multiple constraints: `>=1.2,<2.0,!=1.5.0`
+//!
+//! Shorthand forms from other ecosystems are also accepted and normalized
+//! to the comma-separated form:
+//! - Hyphen ranges: `1.2.0 - 2.3.4` (>=1.2.0,<=2.3.4)
+//! - Space-separated constraints: `>=1.2.0 <2.0.0`
+//! - Wildcards: `1.2.*` (>=1.2.0,<1.3.0) and `1.*` (>=1.0.0,<2.0.0)
 
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use sps2_errors::VersionError;
+use std::cmp::Ordering;
 use std::fmt;
 use std::str::FromStr;
 
+/// A version bound and whether it is inclusive
+type Bound<V> = Option<(V, bool)>;
+
 /// A single version constraint
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum VersionConstraint {
@@ -24,6 +36,7 @@ pub enum VersionConstraint {
     Less(Version),
     Compatible(Version),
     NotEqual(Version),
+    Caret(Version),
 }
 
 impl VersionConstraint {
@@ -43,6 +56,65 @@ impl VersionConstraint {
                 // For simplicity, always allow only patch updates for compatible constraints
                 version >= v && version.major == v.major && version.minor == v.minor
             }
+            Self::Caret(v) => {
+                // ^1.2.3 means >=1.2.3,<2.0.0, ^0.2.3 means >=0.2.3,<0.3.0
+                // and ^0.0.3 means >=0.0.3,<0.0.4
+                version >= v
+                    && version.major == v.major
+                    && (v.major > 0
+                        || (version.minor == v.minor && (v.minor > 0 || version.patch == v.patch)))
+            }
+        }
+    }
+
+    /// The version this constraint is written against
+    #[must_use]
+    pub fn version(&self) -> &Version {
+        match self {
+            Self::Exact(v)
+            | Self::GreaterEqual(v)
+            | Self::LessEqual(v)
+            | Self::Greater(v)
+            | Self::Less(v)
+            | Self::Compatible(v)
+            | Self::NotEqual(v)
+            | Self::Caret(v) => v,
+        }
+    }
+
+    /// Lower and upper bound of the versions this constraint admits; `!=`
+    /// restricts neither
+    fn bounds(&self) -> (Bound<&Version>, Bound<Version>) {
+        match self {
+            Self::Exact(v) => (Some((v, true)), Some((v.clone(), true))),
+            Self::GreaterEqual(v) => (Some((v, true)), None),
+            Self::Greater(v) => (Some((v, false)), None),
+            Self::LessEqual(v) => (None, Some((v.clone(), true))),
+            Self::Less(v) => (None, Some((v.clone(), false))),
+            Self::NotEqual(_) => (None, None),
+            Self::Compatible(v) => {
+                let upper = v
+                    .minor
+                    .checked_add(1)
+                    .map(|minor| Version::new(v.major, minor, 0));
+                (Some((v, true)), upper.map(|upper| (upper, false)))
+            }
+            Self::Caret(v) => {
+                let upper = if v.major > 0 {
+                    v.major
+                        .checked_add(1)
+                        .map(|major| Version::new(major, 0, 0))
+                } else if v.minor > 0 {
+                    v.minor
+                        .checked_add(1)
+                        .map(|minor| Version::new(0, minor, 0))
+                } else {
+                    v.patch
+                        .checked_add(1)
+                        .map(|patch| Version::new(0, 0, patch))
+                };
+                (Some((v, true)), upper.map(|upper| (upper, false)))
+            }
         }
     }
 
@@ -80,6 +152,12 @@ impl VersionConstraint {
                     message: e.to_string(),
                 })?;
             Ok(Self::Compatible(version))
+        } else if let Some(version_str) = s.strip_prefix('^') {
+            let version =
+                Version::parse(version_str.trim()).map_err(|e| VersionError::ParseError {
+                    message: e.to_string(),
+                })?;
+            Ok(Self::Caret(version))
         } else if let Some(version_str) = s.strip_prefix('>') {
             let version =
                 Version::parse(version_str.trim()).map_err(|e| VersionError::ParseError {
@@ -110,10 +188,21 @@ impl fmt::Display for VersionConstraint {
             Self::Less(v) => write!(f, "<{v}"),
             Self::Compatible(v) => write!(f, "~={v}"),
             Self::NotEqual(v) => write!(f, "!={v}"),
+            Self::Caret(v) => write!(f, "^{v}"),
         }
     }
 }
 
+/// Which end of the matching versions [`VersionSpec::best_match_with`] picks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Preference {
+    /// Highest matching version
+    #[default]
+    Newest,
+    /// Lowest matching version
+    Oldest,
+}
+
 /// A version specification that can contain multiple constraints
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct VersionSpec {
@@ -141,6 +230,21 @@ impl VersionSpec {
         self.constraints.iter().all(|c| c.matches(version))
     }
 
+    /// Check if a version is a selectable candidate for this spec
+    ///
+    /// Like [`VersionSpec::matches`], but pre-release versions are only
+    /// candidates when one of the constraints names a pre-release itself, so
+    /// `>=1.0.0` never selects `2.0.0-rc.1`. Every resolution path picks
+    /// versions through this rule.
+    #[must_use]
+    pub fn is_candidate(&self, version: &Version) -> bool {
+        let allow_pre = self
+            .constraints
+            .iter()
+            .any(|constraint| !constraint.version().pre.is_empty());
+        (allow_pre || version.pre.is_empty()) && self.matches(version)
+    }
+
     /// Get the constraints
     #[must_use]
     pub fn constraints(&self) -> &[VersionConstraint] {
@@ -152,6 +256,127 @@ impl VersionSpec {
     pub fn is_any(&self) -> bool {
         self.constraints.is_empty()
     }
+
+    /// Pick the highest version in `versions` that satisfies this spec
+    ///
+    /// See [`VersionSpec::best_match_with`] for how pre-releases are treated.
+    #[must_use]
+    pub fn best_match<'a>(&self, versions: &'a [Version]) -> Option<&'a Version> {
+        self.best_match_with(versions, Preference::Newest)
+    }
+
+    /// Pick the highest or lowest version in `versions` that satisfies this
+    /// spec, by semver ordering
+    ///
+    /// Only versions accepted by [`VersionSpec::is_candidate`] are considered.
+    #[must_use]
+    pub fn best_match_with<'a>(
+        &self,
+        versions: &'a [Version],
+        prefer: Preference,
+    ) -> Option<&'a Version> {
+        let candidates = versions.iter().filter(|version| self.is_candidate(version));
+        match prefer {
+            Preference::Newest => candidates.max(),
+            Preference::Oldest => candidates.min(),
+        }
+    }
+
+    /// Combine with `other` into a spec matching only versions both match
+    #[must_use]
+    pub fn intersect(&self, other: &VersionSpec) -> VersionSpec {
+        let mut constraints = self.constraints.clone();
+        for constraint in &other.constraints {
+            if !constraints.contains(constraint) {
+                constraints.push(constraint.clone());
+            }
+        }
+        Self { constraints }
+    }
+
+    /// Check whether any version can satisfy every constraint
+    ///
+    /// The check is conservative: it narrows all constraints down to a single
+    /// range and only reports `false` when that range is empty, or holds a
+    /// single version that is excluded with `!=`. Specs it cannot rule out,
+    /// such as `>1.0.0,<1.0.1`, are reported as satisfiable.
+    #[must_use]
+    pub fn is_satisfiable(&self) -> bool {
+        let mut lower: Bound<&Version> = None;
+        let mut upper: Bound<Version> = None;
+
+        for constraint in &self.constraints {
+            let (low, high) = constraint.bounds();
+            if let Some((version, inclusive)) = low {
+                let tighter =
+                    lower.is_none_or(|(current, current_inclusive)| match version.cmp(current) {
+                        Ordering::Greater => true,
+                        Ordering::Equal => current_inclusive && !inclusive,
+                        Ordering::Less => false,
+                    });
+                if tighter {
+                    lower = Some((version, inclusive));
+                }
+            }
+            if let Some((version, inclusive)) = high {
+                let tighter =
+                    upper.as_ref().is_none_or(|(current, current_inclusive)| {
+                        match version.cmp(current) {
+                            Ordering::Less => true,
+                            Ordering::Equal => *current_inclusive && !inclusive,
+                            Ordering::Greater => false,
+                        }
+                    });
+                if tighter {
+                    upper = Some((version, inclusive));
+                }
+            }
+        }
+
+        let (Some((low, low_inclusive)), Some((high, high_inclusive))) = (lower, upper) else {
+            return true;
+        };
+        match low.cmp(&high) {
+            Ordering::Less => true,
+            Ordering::Greater => false,
+            Ordering::Equal => {
+                low_inclusive
+                    && high_inclusive
+                    && !self
+                        .constraints
+                        .contains(&VersionConstraint::NotEqual(low.clone()))
+            }
+        }
+    }
+
+    /// Parse the two bounds of a hyphen range into an inclusive range
+    fn parse_hyphen_range(input: &str, lower: &str, upper: &str) -> Result<Self, VersionError> {
+        let is_bare_version = |bound: &str| {
+            !bound.is_empty()
+                && !bound.contains([',', ' '])
+                && bound.starts_with(|c: char| c.is_ascii_digit())
+        };
+
+        let (lower, upper) = (lower.trim(), upper.trim());
+        if !is_bare_version(lower) || !is_bare_version(upper) {
+            return Err(VersionError::InvalidConstraint {
+                input: input.to_string(),
+            });
+        }
+
+        let parse = |v: &str| {
+            Version::parse(v).map_err(|e| VersionError::ParseError {
+                message: e.to_string(),
+            })
+        };
+
+        Ok(Self {
+            constraints: vec![
+                VersionConstraint::GreaterEqual(parse(lower)?),
+                VersionConstraint::LessEqual(parse(upper)?),
+            ],
+        })
+    }
 }
 
 impl FromStr for VersionSpec {
@@ -167,13 +392,30 @@ impl FromStr for VersionSpec {
             });
         }
 
-        // Split by comma and parse each constraint
-        let constraints: Result<Vec<_>, _> = s
-            .split(',')
-            .map(|part| VersionConstraint::parse(part.trim()))
-            .collect();
+        // Hyphen range: `1.2.0 - 2.3.4` desugars to `>=1.2.0,<=2.3.4`
+        if let Some((lower, upper)) = s.split_once(" - ") {
+            return Self::parse_hyphen_range(s, lower, upper);
+        }
 
-        let constraints = constraints?;
+        // Comma-separated constraints, or a single space-separated group.
+        // Mixing the two forms is ambiguous and rejected.
+        let parts: Vec<&str> = s.split(',').map(str::trim).collect();
+        let mut constraints = Vec::new();
+
+        if parts.len() == 1 {
+            for token in split_space_separated(parts[0]) {
+                push_constraint(&mut constraints, &token)?;
+            }
+        } else {
+            for part in parts {
+                if split_space_separated(part).len() > 1 {
+                    return Err(VersionError::InvalidConstraint {
+                        input: s.to_string(),
+                    });
+                }
+                push_constraint(&mut constraints, part)?;
+            }
+        }
 
         if constraints.is_empty() {
             return Err(VersionError::InvalidConstraint {
@@ -185,6 +427,89 @@ impl FromStr for VersionSpec {
     }
 }
 
+/// Parse one constraint, expanding a wildcard into its two bounds
+fn push_constraint(
+    constraints: &mut Vec<VersionConstraint>,
+    token: &str,
+) -> Result<(), VersionError> {
+    if token.contains('*') {
+        let (lower, upper) = parse_wildcard(token)?;
+        constraints.push(VersionConstraint::GreaterEqual(lower));
+        constraints.push(VersionConstraint::Less(upper));
+    } else {
+        constraints.push(VersionConstraint::parse(token)?);
+    }
+    Ok(())
+}
+
+/// Parse `1.*` or `1.2.*` into its inclusive lower and exclusive upper bound
+///
+/// Only a trailing wildcard after the major or minor component is accepted,
+/// optionally prefixed with `==`; forms such as `1.*.3`, `1.2.3.*` or
+/// `>=1.*` are rejected.
+fn parse_wildcard(token: &str) -> Result<(Version, Version), VersionError> {
+    let invalid = || VersionError::InvalidConstraint {
+        input: token.to_string(),
+    };
+
+    let components = token
+        .strip_prefix("==")
+        .unwrap_or(token)
+        .trim_start()
+        .strip_suffix(".*")
+        .ok_or_else(invalid)?
+        .split('.')
+        .map(|part| {
+            if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            part.parse::<u64>().map_err(|e| VersionError::ParseError {
+                message: e.to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match components[..] {
+        [major] => Ok((
+            Version::new(major, 0, 0),
+            Version::new(major.checked_add(1).ok_or_else(invalid)?, 0, 0),
+        )),
+        [major, minor] => Ok((
+            Version::new(major, minor, 0),
+            Version::new(major, minor.checked_add(1).ok_or_else(invalid)?, 0),
+        )),
+        _ => Err(invalid()),
+    }
+}
+
+/// Split a space-separated constraint group into individual constraints.
+///
+/// An operator separated from its version by whitespace (`>= 1.2.0`) is
+/// joined back onto the version so it is treated as a single constraint.
+fn split_space_separated(s: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut pending_op: Option<&str> = None;
+
+    for word in s.split_whitespace() {
+        if let Some(op) = pending_op.take() {
+            tokens.push(format!("{op}{word}"));
+        } else if word
+            .chars()
+            .all(|c| matches!(c, '=' | '>' | '<' | '!' | '~' | '^'))
+        {
+            pending_op = Some(word);
+        } else {
+            tokens.push(word.to_string());
+        }
+    }
+
+    if let Some(op) = pending_op {
+        tokens.push(op.to_string());
+    }
+
+    tokens
+}
+
 impl fmt::Display for VersionSpec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.constraints.is_empty() {
@@ -195,3 +520,187 @@ impl fmt::Display for VersionSpec {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    #[test]
+    fn parses_hyphen_range() {
+        let spec: VersionSpec = "1.2.0 - 2.3.4".parse().unwrap();
+        assert_eq!(
+            spec.constraints(),
+            &[
+                VersionConstraint::GreaterEqual(v("1.2.0")),
+                VersionConstraint::LessEqual(v("2.3.4")),
+            ]
+        );
+        assert!(spec.matches(&v("2.3.4")));
+        assert!(!spec.matches(&v("2.3.5")));
+        assert_eq!(spec.to_string(), ">=1.2.0,<=2.3.4");
+    }
+
+    #[test]
+    fn parses_space_separated_constraints() {
+        let spec: VersionSpec = ">=1.2.0 <2.0.0".parse().unwrap();
+        assert_eq!(spec.to_string(), ">=1.2.0,<2.0.0");
+
+        let spaced: VersionSpec = ">= 1.2.0 < 2.0.0 != 1.5.0".parse().unwrap();
+        assert_eq!(spaced.to_string(), ">=1.2.0,<2.0.0,!=1.5.0");
+    }
+
+    #[test]
+    fn comma_form_is_unchanged() {
+        let spec: VersionSpec = ">=1.2.0, <2.0.0".parse().unwrap();
+        assert_eq!(spec.to_string(), ">=1.2.0,<2.0.0");
+
+        let single: VersionSpec = ">= 1.2.0".parse().unwrap();
+        assert_eq!(single.to_string(), ">=1.2.0");
+    }
+
+    #[test]
+    fn caret_allows_changes_right_of_first_nonzero_component() {
+        let cases = [
+            (
+                "^1.2.3",
+                &["1.2.3", "1.9.0", "1.99.99"][..],
+                &["1.2.2", "2.0.0"][..],
+            ),
+            ("^0.2.3", &["0.2.3", "0.2.9"], &["0.2.2", "0.3.0", "1.0.0"]),
+            ("^0.0.3", &["0.0.3"], &["0.0.2", "0.0.4", "0.1.0"]),
+            ("^0.0.0", &["0.0.0"], &["0.0.1", "0.1.0"]),
+        ];
+        for (input, allowed, rejected) in cases {
+            let spec: VersionSpec = input.parse().unwrap();
+            assert_eq!(spec.to_string(), input);
+            for version in allowed {
+                assert!(spec.matches(&v(version)), "{input} should match {version}");
+            }
+            for version in rejected {
+                assert!(
+                    !spec.matches(&v(version)),
+                    "{input} should not match {version}"
+                );
+            }
+        }
+
+        let spec: VersionSpec = "^ 1.2.3 !=1.5.0".parse().unwrap();
+        assert_eq!(
+            spec.constraints(),
+            &[
+                VersionConstraint::Caret(v("1.2.3")),
+                VersionConstraint::NotEqual(v("1.5.0")),
+            ]
+        );
+        assert!("^".parse::<VersionSpec>().is_err());
+        assert!("^1.x".parse::<VersionSpec>().is_err());
+    }
+
+    #[test]
+    fn wildcards_expand_to_ranges() {
+        let spec: VersionSpec = "1.2.*".parse().unwrap();
+        assert_eq!(
+            spec.constraints(),
+            &[
+                VersionConstraint::GreaterEqual(v("1.2.0")),
+                VersionConstraint::Less(v("1.3.0")),
+            ]
+        );
+        assert!(spec.matches(&v("1.2.9")));
+        assert!(!spec.matches(&v("1.3.0")));
+
+        let spec: VersionSpec = "1.*".parse().unwrap();
+        assert_eq!(spec.to_string(), ">=1.0.0,<2.0.0");
+        assert!(spec.matches(&v("1.99.0")));
+        assert!(!spec.matches(&v("2.0.0")));
+
+        let spec: VersionSpec = "1.2.*, !=1.2.5".parse().unwrap();
+        assert_eq!(spec.to_string(), ">=1.2.0,<1.3.0,!=1.2.5");
+
+        let any: VersionSpec = "*".parse().unwrap();
+        assert!(any.is_any());
+        assert!(any.matches(&v("0.0.1")));
+
+        for input in [
+            "1.*.3", "1.2.3.*", "*.*", ">=1.*", "1.x.*", "1.2*", "1.2.*, *",
+        ] {
+            assert!(input.parse::<VersionSpec>().is_err(), "{input} should fail");
+        }
+    }
+
+    #[test]
+    fn intersection_detects_contradictions() {
+        let spec = |s: &str| s.parse::<VersionSpec>().unwrap();
+
+        let combined = spec(">=1.0.0").intersect(&spec("<2.0.0,>=1.0.0"));
+        assert_eq!(combined.to_string(), ">=1.0.0,<2.0.0");
+        assert!(combined.is_satisfiable());
+        assert!(combined.matches(&v("1.5.0")));
+        assert!(spec("*").intersect(&spec("*")).is_any());
+
+        for satisfiable in [
+            ">=1.0.0,<=1.0.0",
+            "==1.0.0,!=1.0.1",
+            ">1.0.0,<1.0.1",
+            "^1.2.0,>=1.9.0",
+            "~=1.2.0,<1.2.1",
+            "!=1.0.0",
+            "*",
+        ] {
+            assert!(spec(satisfiable).is_satisfiable(), "{satisfiable}");
+        }
+        for contradiction in [
+            ">=2.0.0,<1.0.0",
+            "==1.0.0,!=1.0.0",
+            "==1.0.0,==2.0.0",
+            ">1.0.0,<=1.0.0",
+            ">=1.0.0,<1.0.0",
+            ">=1.0.0,<=1.0.0,!=1.0.0",
+            "^1.2.0,>=2.0.0",
+            "~=1.2.0,<1.2.0",
+        ] {
+            assert!(!spec(contradiction).is_satisfiable(), "{contradiction}");
+        }
+        assert!(!spec(">=2.0.0").intersect(&spec("<1.0.0")).is_satisfiable());
+    }
+
+    #[test]
+    fn best_match_respects_preference_and_pre_releases() {
+        let versions: Vec<Version> = ["1.0.0", "1.4.2", "1.10.0", "2.0.0-rc.1", "2.0.0", "0.9.0"]
+            .into_iter()
+            .map(v)
+            .collect();
+        let spec = |s: &str| s.parse::<VersionSpec>().unwrap();
+
+        // Semver ordering, not string ordering
+        assert_eq!(spec("^1.0.0").best_match(&versions), Some(&v("1.10.0")));
+        assert_eq!(
+            spec("^1.0.0").best_match_with(&versions, Preference::Oldest),
+            Some(&v("1.0.0"))
+        );
+        assert_eq!(spec("*").best_match(&versions), Some(&v("2.0.0")));
+        assert_eq!(spec(">=3.0.0").best_match(&versions), None);
+        assert_eq!(spec("*").best_match(&[]), None);
+
+        // Pre-releases only when the spec asks for one
+        assert_eq!(spec("<2.0.0").best_match(&versions), Some(&v("1.10.0")));
+        assert_eq!(
+            spec(">=2.0.0-rc.1").best_match_with(&versions, Preference::Oldest),
+            Some(&v("2.0.0-rc.1"))
+        );
+        let only_pre = [v("3.0.0-beta.1")];
+        assert_eq!(spec(">=2.0.0").best_match(&only_pre), None);
+    }
+
+    #[test]
+    fn rejects_mixed_shorthand() {
+        assert!(">=1.0.0 <2.0.0, !=1.5.0".parse::<VersionSpec>().is_err());
+        assert!("1.0.0 - 2.0.0, !=1.5.0".parse::<VersionSpec>().is_err());
+        assert!("1.0.0 - >=2.0.0".parse::<VersionSpec>().is_err());
+        assert!(">=1.0.0 1.0.0 - 2.0.0".parse::<VersionSpec>().is_err());
+    }
+}